@@ -41,6 +41,9 @@ fn test_sim_transfer() {
     let message = "hello world";
     let res = call!(master_account, contract.complex_call(status_id, message.to_string()));
     assert!(res.is_ok(), "complex_call has promise_errors: {:#?}", res.promise_results());
+    // The call to the status message contract and the callback burn gas on top of the call.
+    assert!(res.total_gas_burnt() > res.gas_burnt());
+    assert!(res.logs_per_receipt().iter().any(|(executor_id, _)| executor_id.as_str() == "status"));
 
     let value = res.unwrap_json_value();
     assert_eq!(message, value.to_string().trim_matches(|c| c == '"'));
@@ -30,6 +30,14 @@ impl TestContract {
         Self {}
     }
 
+    /// Built for wasm32 without the `estimate` feature, which `migrate_dry_run` requires.
+    #[migrate]
+    pub fn migrate() -> Self {
+        let _old_contract: TestContract = env::state_read().expect("Old state doesn't exist");
+
+        Self {}
+    }
+
     pub fn test_panic_macro(&mut self) {
         panic!("PANIC!");
     }
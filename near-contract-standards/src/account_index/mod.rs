@@ -0,0 +1,228 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::BorshDeserialize;
+use near_sdk::collections::paginated_range;
+use near_sdk::json_types::{Cursor, Page, U128};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, AccountId, IntoStorageKey};
+
+const DEFAULT_ACCOUNT_INDEX_PREFIX: &[u8] = b"__account_index";
+
+/// Short indices of the account ids mentioned in the events of a contract, so that
+/// high-frequency events can refer to accounts by index instead of repeating their ids, e.g.
+/// [`FtTransfer::emit_indexed`](crate::fungible_token::events::FtTransfer::emit_indexed).
+///
+/// The first time an account is indexed, an `account_index_register` event is logged before the
+/// event which refers to it:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"account_index","version":"1.0.0","event":"account_index_register","data":[{"account_id":"bob.near","index":0}]}
+/// ```
+///
+/// Indices are never reused, so indexers can build the mapping from these events alone, or
+/// read it through the paginated `account_index` view exposed with
+/// [`impl_account_index_view`](crate::impl_account_index_view).
+///
+/// Like [`Metrics`](crate::metrics::Metrics), the index is stored directly in contract storage
+/// under the prefix, with two entries per account, so it isn't part of the contract state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountIndex {
+    prefix: Vec<u8>,
+}
+
+impl Default for AccountIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACCOUNT_INDEX_PREFIX)
+    }
+}
+
+impl AccountIndex {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key() }
+    }
+
+    /// Returns the index of the account, registering it and logging its `account_index_register`
+    /// event if it wasn't indexed yet.
+    pub fn index_of(&self, account_id: &AccountId) -> u32 {
+        if let Some(index) = self.get(account_id) {
+            return index;
+        }
+        let index = self.len();
+        let next_len =
+            index.checked_add(1).unwrap_or_else(|| env::panic_str("Account index is full"));
+        env::storage_write(&self.index_key(account_id), &index.to_le_bytes());
+        env::storage_write(&self.account_key(index), account_id.as_bytes());
+        env::storage_write(&self.len_key(), &next_len.to_le_bytes());
+        NearEvent::AccountIndex(AccountIndexEvent {
+            version: "1.0.0",
+            event: "account_index_register",
+            data: &[AccountIndexRegister { account_id, index }],
+        })
+        .emit();
+        index
+    }
+
+    /// Returns the index of the account, or `None` if it wasn't indexed.
+    pub fn get(&self, account_id: &AccountId) -> Option<u32> {
+        env::storage_read(&self.index_key(account_id)).map(|bytes| {
+            u32::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize account index"))
+        })
+    }
+
+    /// Returns the account with the index, or `None` if the index wasn't assigned.
+    pub fn account_id(&self, index: u32) -> Option<AccountId> {
+        env::storage_read(&self.account_key(index)).map(|bytes| {
+            String::from_utf8(bytes)
+                .ok()
+                .and_then(|account_id| account_id.parse().ok())
+                .unwrap_or_else(|| env::panic_str("Cannot deserialize indexed account"))
+        })
+    }
+
+    /// Number of indexed accounts, which is also the next index.
+    pub fn len(&self) -> u32 {
+        env::storage_read(&self.len_key())
+            .map(|bytes| {
+                u32::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Cannot deserialize account index length"))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns `true` if no accounts were indexed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the indexed accounts in the order of their indices, paginated like the NFT
+    /// enumeration. The index of each account is `from_index` plus its position.
+    pub fn accounts(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        paginated_range(self.len() as u64, from_index, limit)
+            .map(|index| {
+                self.account_id(index as u32)
+                    .unwrap_or_else(|| env::panic_str("Account index is in an inconsistent state"))
+            })
+            .collect()
+    }
+
+    /// Returns a page of the indexed accounts in the order of their indices. Indices are never
+    /// reused, so the cursor is the index of the next account.
+    pub fn accounts_page(
+        &self,
+        from_cursor: Option<Cursor>,
+        limit: Option<u64>,
+    ) -> Page<AccountId> {
+        let start_index: u32 = from_cursor.map_or(0, |cursor| cursor.position());
+        Page::collect(
+            (start_index..self.len()).map(|index| {
+                let account_id = self
+                    .account_id(index)
+                    .unwrap_or_else(|| env::panic_str("Account index is in an inconsistent state"));
+                (index + 1, account_id)
+            }),
+            limit,
+        )
+    }
+
+    fn len_key(&self) -> Vec<u8> {
+        [self.prefix.as_slice(), b"l"].concat()
+    }
+
+    fn index_key(&self, account_id: &AccountId) -> Vec<u8> {
+        [self.prefix.as_slice(), b"i", account_id.as_bytes()].concat()
+    }
+
+    fn account_key(&self, index: u32) -> Vec<u8> {
+        [self.prefix.as_slice(), b"a", &index.to_le_bytes()].concat()
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct AccountIndexEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [AccountIndexRegister<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct AccountIndexRegister<'a> {
+    account_id: &'a AccountId,
+    index: u32,
+}
+
+/// View of the account index, for indexers which decode events referring to accounts by index.
+pub trait AccountIndexView {
+    /// Returns the indexed accounts in the order of their indices, starting at `from_index`.
+    fn account_index(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId>;
+
+    /// Returns a page of the indexed accounts in the order of their indices.
+    fn account_index_page(
+        &self,
+        from_cursor: Option<Cursor>,
+        limit: Option<u64>,
+    ) -> Page<AccountId>;
+}
+
+/// Exposes the default [`AccountIndex`] through the paginated `account_index` view method.
+/// Takes name of the Contract struct.
+#[macro_export]
+macro_rules! impl_account_index_view {
+    ($contract: ident) => {
+        use $crate::account_index::AccountIndexView;
+
+        #[near_bindgen]
+        impl AccountIndexView for $contract {
+            fn account_index(
+                &self,
+                from_index: Option<near_sdk::json_types::U128>,
+                limit: Option<u64>,
+            ) -> Vec<AccountId> {
+                $crate::account_index::AccountIndex::default().accounts(from_index, limit)
+            }
+
+            fn account_index_page(
+                &self,
+                from_cursor: Option<near_sdk::json_types::Cursor>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::Page<AccountId> {
+                $crate::account_index::AccountIndex::default().accounts_page(from_cursor, limit)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+
+    #[test]
+    fn accounts_are_registered_once() {
+        let index = AccountIndex::default();
+        assert_eq!(index.index_of(&accounts(1)), 0);
+        assert_eq!(index.index_of(&accounts(2)), 1);
+        assert_eq!(index.index_of(&accounts(1)), 0);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"account_index","version":"1.0.0","event":"account_index_register","data":[{"account_id":"bob","index":0}]}"#,
+                r#"EVENT_JSON:{"standard":"account_index","version":"1.0.0","event":"account_index_register","data":[{"account_id":"charlie","index":1}]}"#,
+            ]
+        );
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get(&accounts(3)), None);
+        assert_eq!(index.account_id(1), Some(accounts(2)));
+        assert_eq!(index.accounts(None, None), vec![accounts(1), accounts(2)]);
+        assert_eq!(index.accounts(Some(U128(1)), Some(5)), vec![accounts(2)]);
+
+        let page = index.accounts_page(None, Some(1));
+        assert_eq!(page.items, vec![accounts(1)]);
+        let page = index.accounts_page(page.next_cursor, Some(1));
+        assert_eq!(page, Page { items: vec![accounts(2)], next_cursor: None });
+    }
+}
@@ -0,0 +1,340 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId};
+use std::collections::BTreeMap;
+
+/// Accounts matched by a rule of a [`MethodPolicy`]. In JSON, rules are objects like
+/// `{"exact":"usdc.near"}`, `{"suffix":"factory.near"}` or `{"pattern":"token-*.near"}`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CallerRule {
+    /// The account itself.
+    Exact(AccountId),
+    /// The account named like the suffix and its sub-accounts, e.g. a factory and the accounts it
+    /// created with `factory.near`. The suffix only matches whole parts of the account, so
+    /// `factory.near` doesn't match `evilfactory.near`. A leading `.` is ignored.
+    Suffix(String),
+    /// Accounts matching the pattern, where `*` matches any sequence of characters.
+    Pattern(String),
+}
+
+impl CallerRule {
+    /// Returns `true` if the rule matches the account.
+    pub fn matches(&self, account_id: &AccountId) -> bool {
+        match self {
+            CallerRule::Exact(exact) => exact == account_id,
+            CallerRule::Suffix(suffix) => {
+                let suffix = suffix.strip_prefix('.').unwrap_or(suffix);
+                let account_id = account_id.as_str();
+                account_id == suffix
+                    || account_id.strip_suffix(suffix).map_or(false, |prefix| prefix.ends_with('.'))
+            }
+            CallerRule::Pattern(pattern) => matches_pattern(pattern, account_id.as_str()),
+        }
+    }
+}
+
+/// Matches `*` wildcards by consuming the literal parts of the pattern left to right.
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match value.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        // No wildcard, the pattern must match exactly.
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Whether the rules of a [`MethodPolicy`] list the only accounts allowed to call the method, or
+/// the accounts denied to.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyMode {
+    Allow,
+    Deny,
+}
+
+/// Accounts allowed or denied to call a method.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MethodPolicy {
+    pub mode: PolicyMode,
+    pub rules: Vec<CallerRule>,
+}
+
+impl MethodPolicy {
+    /// Returns `true` if the account may call the method.
+    pub fn allows(&self, account_id: &AccountId) -> bool {
+        let matched = self.rules.iter().any(|rule| rule.matches(account_id));
+        match self.mode {
+            PolicyMode::Allow => matched,
+            PolicyMode::Deny => !matched,
+        }
+    }
+}
+
+/// Standard interface of [`CallerPolicy`], exposed with
+/// [`impl_caller_policy`](crate::impl_caller_policy).
+pub trait CallerPolicyManager {
+    /// Returns the policy of the method, or `None` if anyone can call it.
+    fn caller_policy(&self, method_name: String) -> Option<MethodPolicy>;
+
+    /// Sets the policy of the method. Can only be called by the admin of the policy, with exactly
+    /// 1 yoctoNEAR attached.
+    fn set_caller_policy(&mut self, method_name: String, policy: MethodPolicy);
+
+    /// Removes the policy of the method, so that anyone can call it. Can only be called by the
+    /// admin of the policy, with exactly 1 yoctoNEAR attached.
+    fn remove_caller_policy(&mut self, method_name: String);
+}
+
+/// Accounts allowed or denied to call designated methods of a contract, e.g. receiver hooks like
+/// `ft_on_transfer` which should only accept calls from known token contracts.
+///
+/// Methods without a policy can be called by anyone. Methods check their caller with
+/// [`assert_allowed`](Self::assert_allowed):
+///
+/// ```ignore
+/// fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+///     self.caller_policy.assert_allowed("ft_on_transfer");
+///     // ...
+/// }
+/// ```
+///
+/// Policies are kept in a single map serialized with the contract state, so they should be few.
+/// Only the `admin_id` can change them, and every change is logged as a `caller_policy_set` or
+/// `caller_policy_removed` event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"caller_policy","version":"1.0.0","event":"caller_policy_set","data":[{"method_name":"ft_on_transfer","policy":{"mode":"allow","rules":[{"exact":"usdc.near"}]}}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct CallerPolicy {
+    pub admin_id: AccountId,
+    policies: BTreeMap<String, MethodPolicy>,
+}
+
+impl CallerPolicy {
+    /// Creates a policy managed by `admin_id`, under which anyone can call any method.
+    pub fn new(admin_id: AccountId) -> Self {
+        Self { admin_id, policies: BTreeMap::new() }
+    }
+
+    /// Returns the policy of the method, or `None` if anyone can call it.
+    pub fn get(&self, method_name: &str) -> Option<&MethodPolicy> {
+        self.policies.get(method_name)
+    }
+
+    /// Returns `true` if the account may call the method.
+    pub fn is_allowed(&self, method_name: &str, account_id: &AccountId) -> bool {
+        self.get(method_name).map_or(true, |policy| policy.allows(account_id))
+    }
+
+    /// Panics unless the predecessor may call the method.
+    pub fn assert_allowed(&self, method_name: &str) {
+        if !self.is_allowed(method_name, &env::predecessor_account_id()) {
+            env::panic_str(format!("The caller is not allowed to call {}", method_name).as_str());
+        }
+    }
+
+    /// Sets the policy of the method. Panics if the predecessor isn't the admin.
+    pub fn set(&mut self, method_name: &str, policy: MethodPolicy) {
+        self.assert_admin();
+        emit_event("caller_policy_set", method_name, Some(&policy));
+        self.policies.insert(method_name.to_string(), policy);
+    }
+
+    /// Removes the policy of the method, returning `true` if it had one. Panics if the
+    /// predecessor isn't the admin.
+    pub fn remove(&mut self, method_name: &str) -> bool {
+        self.assert_admin();
+        let removed = self.policies.remove(method_name).is_some();
+        if removed {
+            emit_event("caller_policy_removed", method_name, None);
+        }
+        removed
+    }
+
+    fn assert_admin(&self) {
+        require!(env::predecessor_account_id() == self.admin_id, "Unauthorized");
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct CallerPolicyEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [CallerPolicyChange<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct CallerPolicyChange<'a> {
+    method_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy: Option<&'a MethodPolicy>,
+}
+
+fn emit_event(event: &'static str, method_name: &str, policy: Option<&MethodPolicy>) {
+    NearEvent::CallerPolicy(CallerPolicyEvent {
+        version: "1.0.0",
+        event,
+        data: &[CallerPolicyChange { method_name, policy }],
+    })
+    .emit();
+}
+
+/// Exposes the [`CallerPolicyManager`] methods of a [`CallerPolicy`] field. The yoctoNEAR required
+/// by the mutating methods ensures they are signed with a full access key.
+/// Takes name of the Contract struct and the inner field for the policy.
+#[macro_export]
+macro_rules! impl_caller_policy {
+    ($contract: ident, $policy: ident) => {
+        use $crate::caller_policy::{CallerPolicyManager, MethodPolicy};
+
+        #[near_bindgen]
+        impl CallerPolicyManager for $contract {
+            fn caller_policy(&self, method_name: String) -> Option<MethodPolicy> {
+                self.$policy.get(&method_name).cloned()
+            }
+
+            #[payable]
+            fn set_caller_policy(&mut self, method_name: String, policy: MethodPolicy) {
+                near_sdk::assert_one_yocto();
+                self.$policy.set(&method_name, policy)
+            }
+
+            #[payable]
+            fn remove_caller_policy(&mut self, method_name: String) {
+                near_sdk::assert_one_yocto();
+                self.$policy.remove(&method_name);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        policy: CallerPolicy,
+    }
+
+    impl_caller_policy!(Contract, policy);
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    fn setup() -> CallerPolicy {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        CallerPolicy::new(accounts(0))
+    }
+
+    #[test]
+    fn match_rules() {
+        let suffix = CallerRule::Suffix("factory.near".to_string());
+        assert!(suffix.matches(&account("a.factory.near")));
+        assert!(suffix.matches(&account("factory.near")));
+        assert!(!suffix.matches(&account("evilfactory.near")));
+        assert!(CallerRule::Suffix(".factory.near".to_string()).matches(&account("a.factory.near")));
+        assert!(!CallerRule::Suffix(".factory.near".to_string()).matches(&account("xfactory.near")));
+        let pattern = CallerRule::Pattern("token-*.near".to_string());
+        assert!(pattern.matches(&account("token-usdc.near")));
+        assert!(!pattern.matches(&account("token-usdc.testnet")));
+        assert!(matches_pattern("*.pool.*.near", "a.pool.x.near"));
+        assert!(!matches_pattern("a*a", "a"));
+        assert!(matches_pattern("usdc.near", "usdc.near"));
+    }
+
+    #[test]
+    fn policy_methods_require_one_yocto() {
+        let mut contract = Contract { policy: setup() };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        let method_policy =
+            MethodPolicy { mode: PolicyMode::Deny, rules: vec![CallerRule::Exact(accounts(1))] };
+        contract.set_caller_policy("ft_on_transfer".to_string(), method_policy.clone());
+        assert_eq!(contract.caller_policy("ft_on_transfer".to_string()), Some(method_policy));
+        contract.remove_caller_policy("ft_on_transfer".to_string());
+        assert_eq!(contract.caller_policy("ft_on_transfer".to_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_policy_without_yocto() {
+        let mut contract = Contract { policy: setup() };
+        contract.set_caller_policy(
+            "ft_on_transfer".to_string(),
+            MethodPolicy { mode: PolicyMode::Allow, rules: vec![] },
+        );
+    }
+
+    #[test]
+    fn allow_list() {
+        let mut policy = setup();
+        assert!(policy.is_allowed("ft_on_transfer", &accounts(1)));
+        let method_policy = MethodPolicy {
+            mode: PolicyMode::Allow,
+            rules: vec![CallerRule::Exact(account("usdc.near"))],
+        };
+        policy.set("ft_on_transfer", method_policy);
+        assert!(policy.is_allowed("ft_on_transfer", &account("usdc.near")));
+        assert!(!policy.is_allowed("ft_on_transfer", &accounts(1)));
+        assert!(policy.is_allowed("other", &accounts(1)));
+        assert!(policy.remove("ft_on_transfer"));
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"caller_policy","version":"1.0.0","event":"caller_policy_set","data":[{"method_name":"ft_on_transfer","policy":{"mode":"allow","rules":[{"exact":"usdc.near"}]}}]}"#,
+                r#"EVENT_JSON:{"standard":"caller_policy","version":"1.0.0","event":"caller_policy_removed","data":[{"method_name":"ft_on_transfer"}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The caller is not allowed to call ft_on_transfer")]
+    fn deny_list() {
+        let mut policy = setup();
+        let method_policy = MethodPolicy {
+            mode: PolicyMode::Deny,
+            rules: vec![CallerRule::Suffix(".near".to_string())],
+        };
+        policy.set("ft_on_transfer", method_policy);
+        testing_env!(VMContextBuilder::new().predecessor_account_id(account("x.near")).build());
+        policy.assert_allowed("ft_on_transfer");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_admin_sets_policies() {
+        let mut policy = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        policy.remove("ft_on_transfer");
+    }
+}
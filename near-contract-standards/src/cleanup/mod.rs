@@ -0,0 +1,151 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::ClearBatch;
+use near_sdk::{env, IntoStorageKey};
+
+const DEFAULT_CLEANUP_PREFIX: &[u8] = b"__cleanup";
+
+/// Default number of elements removed by one `cleanup_component` call.
+pub const DEFAULT_CLEANUP_LIMIT: u64 = 100;
+
+/// Deprecated collections whose storage is reclaimed in batches, so that a component removed from
+/// the contract state in a migration doesn't leave its entries in storage, locking their storage
+/// stake forever.
+///
+/// In the migration, the collection is taken out of the old state and moved here with
+/// [`deprecate`](Self::deprecate), under the storage prefix it was created with. The admin then
+/// calls `cleanup_component(prefix)`, generated with
+/// [`impl_cleanup_component`](crate::impl_cleanup_component), until it returns `true`. Each call
+/// removes up to `limit` elements, found through the metadata of the collection rather than by
+/// scanning the storage, and the released storage stake becomes available in the balance of the
+/// contract.
+///
+/// Only iterable collections can be cleaned up, since the keys of a `LookupMap` or `LookupSet`
+/// can't be enumerated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageCleanup {
+    prefix: Vec<u8>,
+}
+
+impl Default for StorageCleanup {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLEANUP_PREFIX)
+    }
+}
+
+impl StorageCleanup {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key() }
+    }
+
+    /// Schedules the collection created with `prefix` for cleanup. Panics if a collection with
+    /// the same prefix is already scheduled.
+    pub fn deprecate<C>(&self, prefix: &[u8], collection: C)
+    where
+        C: ClearBatch + BorshSerialize,
+    {
+        if self.is_pending(prefix) {
+            env::panic_str("The component is already deprecated");
+        }
+        self.write(prefix, &collection);
+    }
+
+    /// Returns `true` if the collection created with `prefix` is scheduled and not fully removed.
+    pub fn is_pending(&self, prefix: &[u8]) -> bool {
+        env::storage_has_key(&self.key(prefix))
+    }
+
+    /// Removes up to `limit` elements of the collection created with `prefix`, returning `true`
+    /// once the collection is fully removed. Panics if no such collection is scheduled.
+    pub fn cleanup<C>(&self, prefix: &[u8], limit: u64) -> bool
+    where
+        C: ClearBatch + BorshSerialize + BorshDeserialize,
+    {
+        let key = self.key(prefix);
+        let mut collection: C = env::storage_read(&key)
+            .map(|bytes| {
+                C::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Cannot deserialize deprecated component"))
+            })
+            .unwrap_or_else(|| env::panic_str("The component is not deprecated"));
+        collection.clear_batch(limit);
+        if collection.is_cleared() {
+            env::storage_remove(&key);
+            true
+        } else {
+            self.write(prefix, &collection);
+            false
+        }
+    }
+
+    fn write<C: BorshSerialize>(&self, prefix: &[u8], collection: &C) {
+        env::storage_write(
+            &self.key(prefix),
+            &collection.try_to_vec().unwrap_or_else(|_| env::abort()),
+        );
+    }
+
+    fn key(&self, prefix: &[u8]) -> Vec<u8> {
+        [self.prefix.as_slice(), prefix].concat()
+    }
+}
+
+/// Generates the private `cleanup_component(prefix, limit)` method, which removes up to `limit`
+/// elements, [`DEFAULT_CLEANUP_LIMIT`] by default, of the collection deprecated under `prefix`
+/// in the default [`StorageCleanup`], and returns `true` once the collection is fully removed.
+/// Takes name of the Contract struct and the storage prefixes of the deprecated collections with
+/// their types.
+///
+/// ```ignore
+/// impl_cleanup_component!(Contract, "o" => Vector<Order>, "b" => UnorderedMap<AccountId, Balance>);
+/// ```
+#[macro_export]
+macro_rules! impl_cleanup_component {
+    ($contract: ident, $($prefix: literal => $collection: ty),+ $(,)?) => {
+        #[near_bindgen]
+        impl $contract {
+            #[private]
+            pub fn cleanup_component(&mut self, prefix: String, limit: Option<u64>) -> bool {
+                let limit = limit.unwrap_or($crate::cleanup::DEFAULT_CLEANUP_LIMIT);
+                let cleanup = $crate::cleanup::StorageCleanup::default();
+                match prefix.as_str() {
+                    $($prefix => cleanup.cleanup::<$collection>($prefix.as_bytes(), limit),)+
+                    _ => near_sdk::env::panic_str("Unknown component"),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::collections::{UnorderedMap, Vector};
+    use near_sdk::test_utils::storage_snapshot;
+
+    #[test]
+    fn cleanup_in_batches() {
+        let mut orders = Vector::new(b"o");
+        orders.extend(0..5u64);
+        let mut balances = UnorderedMap::new(b"b");
+        balances.insert(&1u64, &10u64);
+
+        let cleanup = StorageCleanup::default();
+        cleanup.deprecate(b"o", orders);
+        cleanup.deprecate(b"b", balances);
+        assert!(!cleanup.cleanup::<Vector<u64>>(b"o", 3));
+        assert!(cleanup.is_pending(b"o"));
+        assert!(cleanup.cleanup::<Vector<u64>>(b"o", 3));
+        assert!(!cleanup.is_pending(b"o"));
+        assert!(cleanup.cleanup::<UnorderedMap<u64, u64>>(b"b", 3));
+        assert!(storage_snapshot().0.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "The component is not deprecated")]
+    fn cleanup_unknown() {
+        StorageCleanup::default().cleanup::<Vector<u64>>(b"o", 1);
+    }
+}
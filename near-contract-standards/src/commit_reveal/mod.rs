@@ -0,0 +1,238 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_min_deposit, env, require, AccountId, Balance, CryptoHash, Duration, IntoStorageKey,
+    Promise, Timestamp,
+};
+use std::convert::TryFrom;
+
+/// Hash function of the commitments.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CommitmentHash {
+    Sha256,
+    Keccak256,
+}
+
+impl CommitmentHash {
+    pub fn hash(&self, value: &[u8]) -> CryptoHash {
+        match self {
+            Self::Sha256 => env::sha256_array(value),
+            Self::Keccak256 => env::keccak256_array(value),
+        }
+    }
+}
+
+/// Returns the commitment of `account_id` to `value` with the `salt`, i.e. the hash of the
+/// account, the value and the salt, with the lengths of the account and the value as
+/// little-endian `u32`s before each. The lengths make the splits unambiguous, so that a
+/// commitment can't be revealed as another value, and the account binds the commitment to its
+/// committer, so that other accounts can't copy it and reveal it once the value is public.
+///
+/// Clients generate commitments with this function before calling the contract, and so can unit
+/// tests, with a salt e.g. from `env::random_seed()`.
+pub fn commitment_of(
+    hash: CommitmentHash,
+    account_id: &AccountId,
+    value: &[u8],
+    salt: &[u8],
+) -> CryptoHash {
+    let account_len = account_id.as_bytes().len() as u32;
+    let value_len = u32::try_from(value.len())
+        .unwrap_or_else(|_| env::panic_str("The committed value is too long"));
+    hash.hash(
+        &[&account_len.to_le_bytes(), account_id.as_bytes(), &value_len.to_le_bytes(), value, salt]
+            .concat(),
+    )
+}
+
+/// Pending commitment of an account.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct Commitment {
+    pub hash: CryptoHash,
+    /// Deposit attached to the commitment, refunded on reveal and forfeited when slashed.
+    pub deposit: Balance,
+    pub committed_at: Timestamp,
+}
+
+/// Commit-reveal scheme, for values which must stay hidden until every participant committed to
+/// theirs, like the bids of blind auctions, the moves of games or the contributions to a
+/// randomness beacon.
+///
+/// An account commits to a value by calling [`commit`](Self::commit) with
+/// [`commitment_of`] the account, the value and a secret salt, attaching at least `min_deposit`. Within
+/// `reveal_period` nanoseconds of the commit it calls [`reveal`](Self::reveal) with the value and
+/// the salt, which validates them against the commitment and refunds the deposit. Commitments
+/// which weren't revealed in time can be [`slash`](Self::slash)ed, forfeiting the deposit, so
+/// that participants can't withhold values which turned out unfavourable to them.
+///
+/// Each account has at most one pending commitment.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommitReveal {
+    commitments: LookupMap<AccountId, Commitment>,
+    pub hash: CommitmentHash,
+    pub min_deposit: Balance,
+    pub reveal_period: Duration,
+}
+
+impl CommitReveal {
+    pub fn new<S>(
+        prefix: S,
+        hash: CommitmentHash,
+        min_deposit: Balance,
+        reveal_period: Duration,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { commitments: LookupMap::new(prefix), hash, min_deposit, reveal_period }
+    }
+
+    /// Records the commitment of the predecessor with the attached deposit. Panics if the
+    /// deposit is lower than `min_deposit` or the predecessor already has a pending commitment.
+    pub fn commit(&mut self, hash: CryptoHash) {
+        assert_min_deposit(self.min_deposit);
+        let account_id = env::predecessor_account_id();
+        require!(
+            !self.commitments.contains_key(&account_id),
+            "The account already has a commitment"
+        );
+        self.commitments.insert(
+            &account_id,
+            &Commitment {
+                hash,
+                deposit: env::attached_deposit(),
+                committed_at: env::block_timestamp(),
+            },
+        );
+    }
+
+    /// Reveals the value committed by the predecessor, removing its commitment and refunding
+    /// its deposit. Panics if there's no pending commitment, the reveal period is over or the
+    /// value and salt don't match the commitment.
+    pub fn reveal(&mut self, value: &[u8], salt: &[u8]) {
+        let account_id = env::predecessor_account_id();
+        let commitment = self.commitment_or_panic(&account_id);
+        require!(!self.is_expired(&commitment), "The reveal period is over");
+        require!(
+            commitment_of(self.hash, &account_id, value, salt) == commitment.hash,
+            "The revealed value doesn't match the commitment"
+        );
+        self.commitments.remove(&account_id);
+        if commitment.deposit > 0 {
+            Promise::new(account_id).transfer(commitment.deposit);
+        }
+    }
+
+    /// Removes the commitment of the account which wasn't revealed in time, returning the
+    /// forfeited deposit for the contract to keep or distribute. Panics if there's no pending
+    /// commitment or the reveal period isn't over.
+    pub fn slash(&mut self, account_id: &AccountId) -> Balance {
+        let commitment = self.commitment_or_panic(account_id);
+        require!(self.is_expired(&commitment), "The reveal period is not over");
+        self.commitments.remove(account_id);
+        commitment.deposit
+    }
+
+    /// Returns the pending commitment of the account.
+    pub fn get(&self, account_id: &AccountId) -> Option<Commitment> {
+        self.commitments.get(account_id)
+    }
+
+    /// Returns `true` if the reveal period of the commitment is over.
+    pub fn is_expired(&self, commitment: &Commitment) -> bool {
+        env::block_timestamp() > commitment.committed_at.saturating_add(self.reveal_period)
+    }
+
+    fn commitment_or_panic(&self, account_id: &AccountId) -> Commitment {
+        self.get(account_id).unwrap_or_else(|| {
+            env::panic_str(format!("The account {} has no commitment", account_id).as_str())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const PERIOD: Duration = 1_000;
+
+    fn setup(timestamp: Timestamp, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(timestamp)
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    fn committed(hash: CommitmentHash) -> CommitReveal {
+        setup(0, 10);
+        let mut commit_reveal = CommitReveal::new(b"c", hash, 10, PERIOD);
+        commit_reveal.commit(commitment_of(hash, &accounts(1), b"bid:100", b"salt"));
+        commit_reveal
+    }
+
+    #[test]
+    fn commit_and_reveal() {
+        for hash in [CommitmentHash::Sha256, CommitmentHash::Keccak256] {
+            let mut commit_reveal = committed(hash);
+            assert_eq!(commit_reveal.get(&accounts(1)).unwrap().deposit, 10);
+            setup(PERIOD, 0);
+            commit_reveal.reveal(b"bid:100", b"salt");
+            assert_eq!(commit_reveal.get(&accounts(1)), None);
+        }
+        assert_ne!(
+            commitment_of(CommitmentHash::Sha256, &accounts(1), b"bid:100", b"salt"),
+            commitment_of(CommitmentHash::Sha256, &accounts(1), b"bid:10", b"0salt")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The revealed value doesn't match the commitment")]
+    fn reveal_other_value() {
+        let mut commit_reveal = committed(CommitmentHash::Sha256);
+        commit_reveal.reveal(b"bid:200", b"salt");
+    }
+
+    #[test]
+    #[should_panic(expected = "The revealed value doesn't match the commitment")]
+    fn reveal_copied_commitment() {
+        let mut commit_reveal = committed(CommitmentHash::Sha256);
+        // Another account copies the commitment and reveals it with the value of the first.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(10)
+            .build());
+        commit_reveal.commit(commit_reveal.get(&accounts(1)).unwrap().hash);
+        commit_reveal.reveal(b"bid:100", b"salt");
+    }
+
+    #[test]
+    #[should_panic(expected = "The reveal period is over")]
+    fn reveal_late() {
+        let mut commit_reveal = committed(CommitmentHash::Sha256);
+        setup(PERIOD + 1, 0);
+        commit_reveal.reveal(b"bid:100", b"salt");
+    }
+
+    #[test]
+    fn slash_after_period() {
+        let mut commit_reveal = committed(CommitmentHash::Keccak256);
+        setup(PERIOD + 1, 0);
+        assert_eq!(commit_reveal.slash(&accounts(1)), 10);
+        assert_eq!(commit_reveal.get(&accounts(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reveal period is not over")]
+    fn slash_before_period() {
+        let mut commit_reveal = committed(CommitmentHash::Sha256);
+        commit_reveal.slash(&accounts(1));
+    }
+}
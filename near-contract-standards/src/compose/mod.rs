@@ -0,0 +1,236 @@
+//! Contracts assembled from a manifest of standard components with
+//! [`compose_contract!`](crate::compose_contract), instead of wiring the fields, storage keys,
+//! initialization and delegation impls of each component by hand.
+
+/// Generates a fungible token contract from a manifest of components: the contract struct, the
+/// storage keys of its collections, the `new` init method and the delegation impls of the
+/// components.
+///
+/// The manifest names the contract and the fields of the selected components, in this order:
+///
+/// - `fungible_token`: the [`FungibleToken`](crate::fungible_token::FungibleToken), with the
+///   NEP-141 core methods and the NEP-145 storage management which registers its accounts.
+/// - `metadata` (optional): the NEP-148 metadata, exposed with `ft_metadata` and passed to `new`.
+/// - `pause` (optional): a [`Pause`](crate::pause::Pause) exposed with `pause_state`, which
+///   guards `ft_transfer` and `ft_transfer_call`, with the private `pause_contract` and
+///   `unpause_contract` methods.
+/// - `owner` (optional): the owner of the contract, exposed with
+///   [`Ownable`](crate::upgrade::Ownable).
+///
+/// `new(owner_id, total_supply, metadata)` registers `owner_id` and mints the total supply to it.
+/// The collections are stored under the prefixes `"t"` for the token and `"m"` for the metadata.
+/// Like the other macros of this crate, the contract must import `near_bindgen`, `borsh`,
+/// `AccountId`, `U128` and `PromiseOrValue`.
+///
+/// ```ignore
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::json_types::U128;
+/// use near_sdk::{near_bindgen, AccountId, PromiseOrValue};
+///
+/// near_contract_standards::compose_contract!(Contract {
+///     fungible_token: token,
+///     metadata: metadata,
+///     pause: pause,
+///     owner: owner_id,
+/// });
+/// ```
+#[macro_export]
+macro_rules! compose_contract {
+    ($contract: ident {
+        fungible_token: $token: ident
+        $(, metadata: $metadata: ident)?
+        $(, pause: $pause: ident)?
+        $(, owner: $owner: ident)?
+        $(,)?
+    }) => {
+        #[near_bindgen]
+        #[derive(BorshDeserialize, BorshSerialize, near_sdk::PanicOnDefault)]
+        pub struct $contract {
+            $token: $crate::fungible_token::FungibleToken,
+            $($metadata: near_sdk::collections::LazyOption<
+                $crate::fungible_token::metadata::FungibleTokenMetadata,
+            >,)?
+            $($pause: $crate::pause::Pause,)?
+            $($owner: AccountId,)?
+        }
+
+        #[near_bindgen]
+        impl $contract {
+            #[init]
+            pub fn new(
+                owner_id: AccountId,
+                total_supply: near_sdk::json_types::U128,
+                $($metadata: $crate::fungible_token::metadata::FungibleTokenMetadata,)?
+            ) -> Self {
+                $($metadata.assert_valid();)?
+                let mut this = Self {
+                    $token: $crate::fungible_token::FungibleToken::new(b"t".to_vec()),
+                    $($metadata: near_sdk::collections::LazyOption::new(
+                        b"m".to_vec(),
+                        Some(&$metadata),
+                    ),)?
+                    $($pause: $crate::pause::Pause::new(),)?
+                    $($owner: owner_id.clone(),)?
+                };
+                this.$token.internal_register_account(&owner_id);
+                this.$token.internal_mint(&owner_id, total_supply.0, Some("new tokens are minted"));
+                this
+            }
+        }
+
+        use $crate::fungible_token::core::FungibleTokenCore;
+        use $crate::fungible_token::resolver::FungibleTokenResolver;
+
+        #[near_bindgen]
+        impl FungibleTokenCore for $contract {
+            #[payable]
+            fn ft_transfer(
+                &mut self,
+                receiver_id: AccountId,
+                amount: near_sdk::json_types::U128,
+                memo: Option<String>,
+            ) {
+                $(self.$pause.assert_not_paused("ft_transfer");)?
+                self.$token.ft_transfer(receiver_id, amount, memo)
+            }
+
+            #[payable]
+            fn ft_transfer_call(
+                &mut self,
+                receiver_id: AccountId,
+                amount: near_sdk::json_types::U128,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<near_sdk::json_types::U128> {
+                $(self.$pause.assert_not_paused("ft_transfer_call");)?
+                self.$token.ft_transfer_call(receiver_id, amount, memo, msg)
+            }
+
+            fn ft_total_supply(&self) -> near_sdk::json_types::U128 {
+                self.$token.ft_total_supply()
+            }
+
+            fn ft_balance_of(&self, account_id: AccountId) -> near_sdk::json_types::U128 {
+                self.$token.ft_balance_of(account_id)
+            }
+        }
+
+        #[near_bindgen]
+        impl FungibleTokenResolver for $contract {
+            #[private]
+            fn ft_resolve_transfer(
+                &mut self,
+                sender_id: AccountId,
+                receiver_id: AccountId,
+                amount: near_sdk::json_types::U128,
+            ) -> near_sdk::json_types::U128 {
+                self.$token.internal_ft_resolve_transfer(&sender_id, receiver_id, amount).0.into()
+            }
+        }
+
+        $crate::impl_fungible_token_storage!($contract, $token);
+
+        $($crate::impl_fungible_token_metadata!($contract, $metadata);)?
+
+        $(
+            $crate::impl_pausable!($contract, $pause);
+
+            #[near_bindgen]
+            impl $contract {
+                #[private]
+                pub fn pause_contract(&mut self) {
+                    self.$pause.pause();
+                }
+
+                #[private]
+                pub fn unpause_contract(&mut self) {
+                    self.$pause.unpause();
+                }
+            }
+        )?
+
+        $(
+            #[near_bindgen]
+            impl $crate::upgrade::Ownable for $contract {
+                fn get_owner(&self) -> AccountId {
+                    self.$owner.clone()
+                }
+
+                fn set_owner(&mut self, owner: AccountId) {
+                    self.assert_owner();
+                    self.$owner = owner;
+                }
+            }
+        )?
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fungible_token::metadata::{
+        FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+    };
+    use crate::pause::Pausable;
+    use crate::upgrade::Ownable;
+    use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env, AccountId, PromiseOrValue};
+
+    compose_contract!(Contract {
+        fungible_token: token,
+        metadata: metadata,
+        pause: pause,
+        owner: owner_id
+    });
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        let metadata = FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Example".to_string(),
+            symbol: "EXAMPLE".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        };
+        let mut contract = Contract::new(accounts(0), U128(1_000), metadata);
+        contract.token.internal_register_account(&accounts(1));
+        contract
+    }
+
+    #[test]
+    fn initial_supply_is_minted() {
+        setup();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"alice","amount":"1000","memo":"new tokens are minted"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn composed_contract() {
+        let mut contract = setup();
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.ft_metadata().symbol, "EXAMPLE");
+        contract.ft_transfer(accounts(1), U128(100), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(100));
+        assert_eq!(contract.ft_total_supply(), U128(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract is paused")]
+    fn paused_transfers() {
+        let mut contract = setup();
+        contract.pause_contract();
+        assert!(contract.pause_state().paused);
+        contract.ft_transfer(accounts(1), U128(100), None);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::{env, require, AccountId, Duration, IntoStorageKey, Timestamp};
+
+/// Maximum fee in basis points, i.e. 100%.
+pub const MAX_FEE_BPS: u32 = 10_000;
+
+/// Invariants of a configuration, checked on creation and after every update of a [`Config`].
+pub trait Validate {
+    /// Returns a message describing the first broken invariant, if any.
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Checks that the fee `name` is at most [`MAX_FEE_BPS`].
+pub fn validate_fee_bps(name: &str, fee_bps: u32) -> Result<(), String> {
+    if fee_bps > MAX_FEE_BPS {
+        return Err(format!("{} must be at most {} bps", name, MAX_FEE_BPS));
+    }
+    Ok(())
+}
+
+/// Checks that the duration `name` isn't zero.
+pub fn validate_nonzero_duration(name: &str, duration: Duration) -> Result<(), String> {
+    if duration == 0 {
+        return Err(format!("{} must not be zero", name));
+    }
+    Ok(())
+}
+
+/// Configuration of a contract as a single struct, e.g. fees and durations, updated by an admin
+/// with JSON patches instead of a setter method per field, exposed with
+/// [`impl_config`](crate::impl_config).
+///
+/// A patch is a JSON object with the fields to change, merged into the configuration following
+/// [RFC 7386](https://datatracker.ietf.org/doc/html/rfc7386), so nested objects are patched
+/// field by field and `null` removes optional fields. The patched configuration must pass
+/// [`Validate::validate`], and every update increments the version of the configuration and is
+/// logged as a `config_update` event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"config","version":"1.0.0","event":"config_update","data":[{"config_version":1,"patch":{"fee_bps":30}}]}
+/// ```
+///
+/// The configuration is kept in a `LazyOption`, so it's only read by the methods which use it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Config<T> {
+    pub admin_id: AccountId,
+    version: u64,
+    updated_at: Timestamp,
+    value: LazyOption<T>,
+}
+
+impl<T> Config<T>
+where
+    T: Validate + Serialize + DeserializeOwned + BorshSerialize + BorshDeserialize,
+{
+    /// Creates the configuration managed by `admin_id`. Panics if it isn't valid.
+    pub fn new<S>(prefix: S, admin_id: AccountId, value: T) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        assert_valid(&value);
+        Self {
+            admin_id,
+            version: 0,
+            updated_at: env::block_timestamp(),
+            value: LazyOption::new(prefix, Some(&value)),
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn get(&self) -> T {
+        self.value.get().unwrap_or_else(|| env::panic_str("The config is not initialized"))
+    }
+
+    /// Number of updates of the configuration.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Timestamp of the last update of the configuration, or of its creation.
+    pub fn updated_at(&self) -> Timestamp {
+        self.updated_at
+    }
+
+    /// Merges the JSON patch into the configuration and returns the new configuration. Panics if
+    /// the predecessor isn't the admin, or the patched configuration can't be deserialized or
+    /// isn't valid.
+    pub fn update(&mut self, patch: Value) -> T {
+        require!(env::predecessor_account_id() == self.admin_id, "Unauthorized");
+        require!(patch.is_object(), "The config patch must be a JSON object");
+        let mut json = serde_json::to_value(self.get()).unwrap_or_else(|_| env::abort());
+        merge_patch(&mut json, &patch);
+        let value: T = serde_json::from_value(json).unwrap_or_else(|err| {
+            env::panic_str(format!("Invalid config patch: {}", err).as_str())
+        });
+        assert_valid(&value);
+        self.value.set(&value);
+        self.version += 1;
+        self.updated_at = env::block_timestamp();
+        NearEvent::Config(ConfigEvent {
+            version: "1.0.0",
+            event: "config_update",
+            data: &[ConfigUpdate { config_version: self.version, patch: &patch }],
+        })
+        .emit();
+        value
+    }
+}
+
+fn assert_valid<T: Validate>(value: &T) {
+    if let Err(message) = value.validate() {
+        env::panic_str(format!("Invalid config: {}", message).as_str());
+    }
+}
+
+/// Applies the JSON merge patch to the target, following RFC 7386.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Default::default());
+            }
+            if let Value::Object(target) = target {
+                for (key, value) in patch {
+                    if value.is_null() {
+                        target.remove(key);
+                    } else {
+                        merge_patch(target.entry(key.as_str()).or_insert(Value::Null), value);
+                    }
+                }
+            }
+        }
+        patch => *target = patch.clone(),
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct ConfigEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [ConfigUpdate<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct ConfigUpdate<'a> {
+    config_version: u64,
+    patch: &'a Value,
+}
+
+/// Exposes the `config` and `config_version` views and the `update_config(patch)` method of a
+/// [`Config`] field. `update_config` requires a deposit of exactly 1 yoctoNEAR, which ensures it
+/// is signed with a full access key.
+/// Takes name of the Contract struct, the inner field for the config and the type of the
+/// configuration.
+#[macro_export]
+macro_rules! impl_config {
+    ($contract: ident, $config: ident, $config_type: ty) => {
+        #[near_bindgen]
+        impl $contract {
+            pub fn config(&self) -> $config_type {
+                self.$config.get()
+            }
+
+            pub fn config_version(&self) -> near_sdk::json_types::U64 {
+                self.$config.version().into()
+            }
+
+            #[payable]
+            pub fn update_config(&mut self, patch: near_sdk::serde_json::Value) -> $config_type {
+                near_sdk::assert_one_yocto();
+                self.$config.update(patch)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::serde::Deserialize;
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(crate = "near_sdk::serde")]
+    struct PoolConfig {
+        fee_bps: u32,
+        lock_duration: Duration,
+        label: Option<String>,
+    }
+
+    impl Validate for PoolConfig {
+        fn validate(&self) -> Result<(), String> {
+            validate_fee_bps("fee_bps", self.fee_bps)?;
+            validate_nonzero_duration("lock_duration", self.lock_duration)
+        }
+    }
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        config: Config<PoolConfig>,
+    }
+
+    impl_config!(Contract, config, PoolConfig);
+
+    fn setup() -> Config<PoolConfig> {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        Config::new(
+            b"c",
+            accounts(0),
+            PoolConfig { fee_bps: 25, lock_duration: 10, label: Some("pool".to_string()) },
+        )
+    }
+
+    #[test]
+    fn update_config() {
+        let mut config = setup();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(42)
+            .build());
+        let updated = config.update(json!({ "fee_bps": 30, "label": null }));
+        assert_eq!(updated, PoolConfig { fee_bps: 30, lock_duration: 10, label: None });
+        assert_eq!(config.get(), updated);
+        assert_eq!(config.version(), 1);
+        assert_eq!(config.updated_at(), 42);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"config","version":"1.0.0","event":"config_update","data":[{"config_version":1,"patch":{"fee_bps":30,"label":null}}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn update_config_with_yocto() {
+        let mut contract = Contract { config: setup() };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.update_config(json!({ "fee_bps": 30 }));
+        assert_eq!(contract.config().fee_bps, 30);
+        assert_eq!(contract.config_version().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn update_config_without_yocto() {
+        let mut contract = Contract { config: setup() };
+        contract.update_config(json!({ "fee_bps": 30 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid config: fee_bps must be at most 10000 bps")]
+    fn invalid_update() {
+        setup().update(json!({ "fee_bps": 10_001 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid config patch")]
+    fn mistyped_update() {
+        setup().update(json!({ "lock_duration": "soon" }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_admin_updates() {
+        let mut config = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        config.update(json!({ "fee_bps": 30 }));
+    }
+}
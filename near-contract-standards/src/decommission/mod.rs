@@ -0,0 +1,414 @@
+use crate::event::NearEvent;
+use crate::fungible_token::core_impl::ext_fungible_token;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, require, AccountId, Balance, Gas, Promise};
+
+/// Gas attached to the `ft_balance_of` view call of a swept token.
+pub const GAS_FOR_DECOMMISSION_QUERY: Gas = Gas(5_000_000_000_000);
+/// Gas attached to the `ft_transfer` of a swept balance.
+pub const GAS_FOR_DECOMMISSION_TRANSFER: Gas = Gas(10_000_000_000_000);
+/// Gas attached to the callback of the transfer of a swept balance.
+pub const GAS_FOR_DECOMMISSION_RESOLVE: Gas = Gas(5_000_000_000_000);
+/// Gas attached to the callback of the balance query, which covers the transfer and its callback.
+pub const GAS_FOR_DECOMMISSION_SWEEP: Gas =
+    Gas(10_000_000_000_000 + GAS_FOR_DECOMMISSION_TRANSFER.0 + GAS_FOR_DECOMMISSION_RESOLVE.0);
+
+/// Default number of elements of the state removed by one `decommission_finish` call.
+pub const DEFAULT_DECOMMISSION_LIMIT: u64 = 100;
+
+const NO_DEPOSIT: Balance = 0;
+const DECOMMISSION_MEMO: &str = "decommission";
+
+/// End of life of a contract: its fungible token balances are swept to a beneficiary, its state
+/// is removed and its account is deleted, which sends its remaining NEAR to the beneficiary.
+///
+/// Only the `owner_id` can decommission the contract, in steps exposed with
+/// [`impl_decommission`](crate::impl_decommission):
+/// 1. `decommission(beneficiary_id)` pauses the contract, [`start`](Self::start)s the
+///    decommission and transfers the balance of the contract in each of the `token_ids` to the
+///    beneficiary. The beneficiary must be registered with the tokens.
+/// 2. `decommission_sweep()` retries the tokens whose balance wasn't swept, e.g. because the
+///    beneficiary wasn't registered yet.
+/// 3. `decommission_finish(limit)` removes the designated collections of the state in batches,
+///    returning `true` once they're removed and the account deletion is issued. It panics while
+///    balances aren't swept, so that no tokens are lost with the account.
+///
+/// Each step is logged, with the events:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommission_started","data":[{"beneficiary_id":"bob.near","token_ids":["usdc.near"]}]}
+/// EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommission_token_swept","data":[{"beneficiary_id":"bob.near","token_id":"usdc.near","amount":"100"}]}
+/// EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommissioned","data":[{"beneficiary_id":"bob.near"}]}
+/// ```
+///
+/// A failed sweep is logged as `decommission_sweep_failed`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct Decommission {
+    pub owner_id: AccountId,
+    /// Fungible token contracts whose balances of the contract are swept to the beneficiary.
+    pub token_ids: Vec<AccountId>,
+    beneficiary_id: Option<AccountId>,
+    pending_token_ids: Vec<AccountId>,
+}
+
+impl Decommission {
+    pub fn new(owner_id: AccountId, token_ids: Vec<AccountId>) -> Self {
+        Self { owner_id, token_ids, beneficiary_id: None, pending_token_ids: vec![] }
+    }
+
+    /// Returns the beneficiary, or `None` if the decommission isn't started.
+    pub fn beneficiary_id(&self) -> Option<&AccountId> {
+        self.beneficiary_id.as_ref()
+    }
+
+    /// Returns the tokens whose balance isn't swept yet.
+    pub fn pending_token_ids(&self) -> &[AccountId] {
+        &self.pending_token_ids
+    }
+
+    /// Starts the decommission in favor of `beneficiary_id`, with the balances of all tokens to
+    /// be swept. Panics if the predecessor isn't the owner or the decommission is started.
+    pub fn start(&mut self, beneficiary_id: AccountId) {
+        self.assert_owner();
+        require!(self.beneficiary_id.is_none(), "The contract is already being decommissioned");
+        self.pending_token_ids = self.token_ids.clone();
+        emit_event(
+            "decommission_started",
+            &DecommissionData {
+                beneficiary_id: &beneficiary_id,
+                token_ids: Some(&self.token_ids),
+                token_id: None,
+                amount: None,
+            },
+        );
+        self.beneficiary_id = Some(beneficiary_id);
+    }
+
+    /// Queries the balance of the contract in the token. Panics if the predecessor isn't the
+    /// owner or the decommission isn't started.
+    pub fn query(&self, token_id: AccountId) -> Promise {
+        self.unwrap_beneficiary_id();
+        ext_fungible_token::ft_balance_of(
+            env::current_account_id(),
+            token_id,
+            NO_DEPOSIT,
+            GAS_FOR_DECOMMISSION_QUERY,
+        )
+    }
+
+    /// Reads the balance queried with [`query`](Self::query) from the promise result
+    /// `result_idx`, and returns it with its transfer to the beneficiary. Returns `None` if there
+    /// is nothing to transfer, marking the token as swept if the balance is 0.
+    pub fn resolve_query(
+        &mut self,
+        token_id: &AccountId,
+        result_idx: u64,
+    ) -> Option<(Balance, Promise)> {
+        match env::promise_result_json::<U128>(result_idx) {
+            Ok(U128(0)) => {
+                self.mark_swept(token_id, 0);
+                None
+            }
+            Ok(U128(amount)) => {
+                let transfer = ext_fungible_token::ft_transfer(
+                    self.unwrap_beneficiary_id().clone(),
+                    amount.into(),
+                    Some(DECOMMISSION_MEMO.to_string()),
+                    token_id.clone(),
+                    1,
+                    GAS_FOR_DECOMMISSION_TRANSFER,
+                );
+                Some((amount, transfer))
+            }
+            Err(_) => {
+                self.emit_sweep_failed(token_id, None);
+                None
+            }
+        }
+    }
+
+    /// Marks the token as swept if the transfer returned by
+    /// [`resolve_query`](Self::resolve_query) succeeded, per the promise result `result_idx`.
+    /// Returns `true` if it succeeded.
+    pub fn resolve_transfer(
+        &mut self,
+        token_id: &AccountId,
+        amount: Balance,
+        result_idx: u64,
+    ) -> bool {
+        match env::promise_result_bounded(result_idx, env::MAX_PROMISE_RESULT_BYTES) {
+            Ok(_) => {
+                self.mark_swept(token_id, amount);
+                true
+            }
+            Err(_) => {
+                self.emit_sweep_failed(token_id, Some(amount));
+                false
+            }
+        }
+    }
+
+    /// Panics unless the predecessor is the owner, the decommission is started and all balances
+    /// are swept.
+    pub fn assert_can_finish(&self) {
+        self.assert_owner();
+        self.unwrap_beneficiary_id();
+        if !self.pending_token_ids.is_empty() {
+            env::panic_str(
+                format!("The balances of {} tokens are not swept", self.pending_token_ids.len())
+                    .as_str(),
+            );
+        }
+    }
+
+    /// Deletes the account, sending its remaining NEAR to the beneficiary. Panics like
+    /// [`assert_can_finish`](Self::assert_can_finish).
+    pub fn finish(&self) -> Promise {
+        self.assert_can_finish();
+        let beneficiary_id = self.unwrap_beneficiary_id();
+        emit_event(
+            "decommissioned",
+            &DecommissionData { beneficiary_id, token_ids: None, token_id: None, amount: None },
+        );
+        Promise::new(env::current_account_id()).delete_account(beneficiary_id.clone())
+    }
+
+    fn mark_swept(&mut self, token_id: &AccountId, amount: Balance) {
+        self.pending_token_ids.retain(|pending_id| pending_id != token_id);
+        emit_event(
+            "decommission_token_swept",
+            &DecommissionData {
+                beneficiary_id: self.unwrap_beneficiary_id(),
+                token_ids: None,
+                token_id: Some(token_id),
+                amount: Some(amount.into()),
+            },
+        );
+    }
+
+    fn emit_sweep_failed(&self, token_id: &AccountId, amount: Option<Balance>) {
+        emit_event(
+            "decommission_sweep_failed",
+            &DecommissionData {
+                beneficiary_id: self.unwrap_beneficiary_id(),
+                token_ids: None,
+                token_id: Some(token_id),
+                amount: amount.map(U128),
+            },
+        );
+    }
+
+    fn unwrap_beneficiary_id(&self) -> &AccountId {
+        self.beneficiary_id
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("The contract is not being decommissioned"))
+    }
+
+    fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct DecommissionEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [&'a DecommissionData<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct DecommissionData<'a> {
+    beneficiary_id: &'a AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_ids: Option<&'a [AccountId]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_id: Option<&'a AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<U128>,
+}
+
+fn emit_event(event: &'static str, data: &DecommissionData) {
+    NearEvent::Decommission(DecommissionEvent { version: "1.0.0", event, data: &[data] }).emit();
+}
+
+/// Generates the decommission methods of a contract with a [`Decommission`] field, see
+/// [`Decommission`]. `decommission` also pauses the [`Pause`](crate::pause::Pause) field, and
+/// `decommission_finish` removes the given collections of the state, which must implement
+/// [`ClearBatch`](near_sdk::collections::ClearBatch), before deleting the account.
+/// Takes name of the Contract struct, the inner fields for the decommission and the pause flags,
+/// and the collection fields to remove.
+///
+/// ```ignore
+/// impl_decommission!(Contract, decommission, pause, orders, profiles);
+/// ```
+#[macro_export]
+macro_rules! impl_decommission {
+    ($contract: ident, $decommission: ident, $pause: ident $(, $collection: ident)* $(,)?) => {
+        #[near_bindgen]
+        impl $contract {
+            #[payable]
+            pub fn decommission(&mut self, beneficiary_id: AccountId) {
+                near_sdk::assert_one_yocto();
+                self.$decommission.start(beneficiary_id);
+                self.$pause.pause();
+                self.decommission_sweep_pending();
+            }
+
+            #[payable]
+            pub fn decommission_sweep(&mut self) {
+                near_sdk::assert_one_yocto();
+                near_sdk::require!(
+                    near_sdk::env::predecessor_account_id() == self.$decommission.owner_id,
+                    "Unauthorized"
+                );
+                self.decommission_sweep_pending();
+            }
+
+            #[private]
+            pub fn on_decommission_query(&mut self, token_id: AccountId) {
+                if let Some((amount, transfer)) = self.$decommission.resolve_query(&token_id, 0) {
+                    let args = near_sdk::serde_json::json!({
+                        "token_id": token_id,
+                        "amount": near_sdk::json_types::U128(amount),
+                    });
+                    transfer.then(
+                        near_sdk::Promise::new(near_sdk::env::current_account_id()).function_call(
+                            "on_decommission_transfer".to_string(),
+                            args.to_string().into_bytes(),
+                            0,
+                            $crate::decommission::GAS_FOR_DECOMMISSION_RESOLVE,
+                        ),
+                    );
+                }
+            }
+
+            #[private]
+            pub fn on_decommission_transfer(
+                &mut self,
+                token_id: AccountId,
+                amount: near_sdk::json_types::U128,
+            ) -> bool {
+                self.$decommission.resolve_transfer(&token_id, amount.0, 0)
+            }
+
+            #[payable]
+            pub fn decommission_finish(&mut self, limit: Option<u64>) -> bool {
+                near_sdk::assert_one_yocto();
+                self.$decommission.assert_can_finish();
+                #[allow(unused_variables)]
+                let limit = limit.unwrap_or($crate::decommission::DEFAULT_DECOMMISSION_LIMIT);
+                #[allow(unused_mut)]
+                let mut cleared = true;
+                $(
+                    if !near_sdk::collections::ClearBatch::is_cleared(&self.$collection) {
+                        near_sdk::collections::ClearBatch::clear_batch(&mut self.$collection, limit);
+                        cleared &= near_sdk::collections::ClearBatch::is_cleared(&self.$collection);
+                    }
+                )*
+                if cleared {
+                    self.$decommission.finish();
+                }
+                cleared
+            }
+
+            fn decommission_sweep_pending(&self) {
+                for token_id in self.$decommission.pending_token_ids() {
+                    let args = near_sdk::serde_json::json!({ "token_id": token_id });
+                    self.$decommission.query(token_id.clone()).then(
+                        near_sdk::Promise::new(near_sdk::env::current_account_id()).function_call(
+                            "on_decommission_query".to_string(),
+                            args.to_string().into_bytes(),
+                            0,
+                            $crate::decommission::GAS_FOR_DECOMMISSION_SWEEP,
+                        ),
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    fn callback(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new().predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn setup() -> Decommission {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut decommission = Decommission::new(accounts(0), vec![accounts(4), accounts(5)]);
+        decommission.start(accounts(1));
+        decommission
+    }
+
+    #[test]
+    fn sweep_and_finish() {
+        let mut decommission = setup();
+        callback(PromiseResult::Successful(b"\"100\"".to_vec()));
+        let (amount, _) = decommission.resolve_query(&accounts(4), 0).unwrap();
+        assert_eq!(amount, 100);
+        callback(PromiseResult::Successful(vec![]));
+        assert!(decommission.resolve_transfer(&accounts(4), amount, 0));
+        callback(PromiseResult::Successful(b"\"0\"".to_vec()));
+        assert!(decommission.resolve_query(&accounts(5), 0).is_none());
+        assert!(decommission.pending_token_ids().is_empty());
+        decommission.finish();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommission_token_swept","data":[{"beneficiary_id":"bob","token_id":"fargo","amount":"0"}]}"#,
+                r#"EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommissioned","data":[{"beneficiary_id":"bob"}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The balances of 1 tokens are not swept")]
+    fn failed_sweep_blocks_finish() {
+        let mut decommission = setup();
+        callback(PromiseResult::Successful(b"\"0\"".to_vec()));
+        decommission.resolve_query(&accounts(5), 0);
+        callback(PromiseResult::Failed);
+        assert!(!decommission.resolve_transfer(&accounts(4), 100, 0));
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommission_sweep_failed","data":[{"beneficiary_id":"bob","token_id":"eugene","amount":"100"}]}"#,
+            ]
+        );
+        assert_eq!(decommission.pending_token_ids(), &[accounts(4)]);
+        decommission.finish();
+    }
+
+    #[test]
+    fn start_is_logged() {
+        setup();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"decommission","version":"1.0.0","event":"decommission_started","data":[{"beneficiary_id":"bob","token_ids":["eugene","fargo"]}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_starts() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        Decommission::new(accounts(0), vec![]).start(accounts(1));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, Balance, Duration, IntoStorageKey, Timestamp};
+
+/// Default number of deposits scanned by one `sweep_unclaimed` call.
+pub const DEFAULT_SWEEP_LIMIT: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct DepositRecord {
+    amount: Balance,
+    last_activity: Timestamp,
+}
+
+/// Result of [`Deposits::sweep_unclaimed`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SweepOutcome {
+    /// Total amount moved to the treasury.
+    pub amount: U128,
+    /// Number of deposits swept.
+    pub swept: u64,
+    /// `from_index` of the next call, or `None` once all deposits of the token were scanned.
+    pub next_index: Option<U64>,
+}
+
+/// Fungible token balances held by a contract for its users, e.g. the deposits of a DEX credited
+/// in `ft_on_transfer`, with the time of the last activity of each balance, so that the owner
+/// can recover balances abandoned for longer than a retention window instead of leaving them
+/// locked in the contract forever.
+///
+/// Balances change with [`credit`](Self::credit) and [`debit`](Self::debit), which the contract
+/// calls from its deposit and withdrawal flows. [`sweep_unclaimed`](Self::sweep_unclaimed) moves
+/// the balances of a token without activity for `older_than` nanoseconds, at least
+/// `min_retention`, to the balance of the treasury, which withdraws them like any other account.
+/// Every swept balance is logged as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"deposits","version":"1.0.0","event":"unclaimed_swept","data":[{"token_id":"usdc.near","account_id":"alice.near","treasury_id":"treasury.near","amount":"12","idle_since":"1650000000000000000"}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Deposits {
+    pub owner_id: AccountId,
+    pub treasury_id: AccountId,
+    pub min_retention: Duration,
+    prefix: Vec<u8>,
+    tokens: LookupMap<AccountId, UnorderedMap<AccountId, DepositRecord>>,
+}
+
+impl Deposits {
+    pub fn new<S>(
+        prefix: S,
+        owner_id: AccountId,
+        treasury_id: AccountId,
+        min_retention: Duration,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            owner_id,
+            treasury_id,
+            min_retention,
+            tokens: LookupMap::new([prefix.as_slice(), b"t"].concat()),
+            prefix,
+        }
+    }
+
+    /// Returns the balance of the account in the token.
+    pub fn balance_of(&self, token_id: &AccountId, account_id: &AccountId) -> Balance {
+        self.record(token_id, account_id).map_or(0, |record| record.amount)
+    }
+
+    /// Returns the block timestamp of the last change of the balance, or `None` if it's empty.
+    pub fn last_activity(&self, token_id: &AccountId, account_id: &AccountId) -> Option<Timestamp> {
+        self.record(token_id, account_id).map(|record| record.last_activity)
+    }
+
+    /// Adds `amount` to the balance of the account, returning the new balance.
+    pub fn credit(
+        &mut self,
+        token_id: &AccountId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Balance {
+        let balance = self
+            .balance_of(token_id, account_id)
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        self.update(token_id, account_id, balance);
+        balance
+    }
+
+    /// Removes `amount` from the balance of the account, returning the new balance. Panics if
+    /// the balance is too low.
+    pub fn debit(
+        &mut self,
+        token_id: &AccountId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Balance {
+        let balance = self
+            .balance_of(token_id, account_id)
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The balance is too low"));
+        self.update(token_id, account_id, balance);
+        balance
+    }
+
+    /// Moves the balances of the token without activity for `older_than` nanoseconds to the
+    /// treasury, scanning up to `limit` deposits backwards from `from_index`, or from the last
+    /// deposit if it's `None`. Call again with the returned `next_index` until it's `None`.
+    /// Panics if the predecessor isn't the owner or `older_than` is shorter than
+    /// `min_retention`.
+    pub fn sweep_unclaimed(
+        &mut self,
+        token_id: &AccountId,
+        older_than: Duration,
+        from_index: Option<u64>,
+        limit: u64,
+    ) -> SweepOutcome {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+        require!(
+            older_than >= self.min_retention,
+            "The retention window is shorter than the minimum retention"
+        );
+        require!(limit != 0, "Cannot provide limit of 0.");
+        let mut deposits = match self.tokens.get(token_id) {
+            Some(deposits) => deposits,
+            None => return SweepOutcome { amount: U128(0), swept: 0, next_index: None },
+        };
+        let idle_since = env::block_timestamp().saturating_sub(older_than);
+        let end = from_index.unwrap_or(u64::MAX).min(deposits.len());
+        let start = end.saturating_sub(limit);
+        let keys = deposits.keys_as_vector();
+        let values = deposits.values_as_vector();
+        let abandoned: Vec<(AccountId, DepositRecord)> = (start..end)
+            .map(|index| {
+                let account_id = keys.get(index).unwrap_or_else(|| env::abort());
+                let record = values.get(index).unwrap_or_else(|| env::abort());
+                (account_id, record)
+            })
+            .filter(|(account_id, record)| {
+                record.last_activity < idle_since && account_id != &self.treasury_id
+            })
+            .collect();
+        // Removing swaps the last deposit into the removed one, and the deposits after `start`
+        // were scanned already, so none is skipped.
+        let mut amount: Balance = 0;
+        for (account_id, record) in &abandoned {
+            deposits.remove(account_id);
+            amount = amount
+                .checked_add(record.amount)
+                .unwrap_or_else(|| env::panic_str("Swept amount overflow"));
+        }
+        self.tokens.insert(token_id, &deposits);
+        let treasury_id = self.treasury_id.clone();
+        if amount > 0 {
+            self.credit(token_id, &treasury_id, amount);
+        }
+        let data: Vec<_> = abandoned
+            .iter()
+            .map(|(account_id, record)| UnclaimedSwept {
+                token_id,
+                account_id,
+                treasury_id: &treasury_id,
+                amount: U128(record.amount),
+                idle_since: U64(record.last_activity),
+            })
+            .collect();
+        if !data.is_empty() {
+            NearEvent::Deposits(DepositsEvent {
+                version: "1.0.0",
+                event: "unclaimed_swept",
+                data: &data,
+            })
+            .emit();
+        }
+        SweepOutcome {
+            amount: U128(amount),
+            swept: abandoned.len() as u64,
+            next_index: if start > 0 { Some(U64(start)) } else { None },
+        }
+    }
+
+    fn record(&self, token_id: &AccountId, account_id: &AccountId) -> Option<DepositRecord> {
+        self.tokens.get(token_id).and_then(|deposits| deposits.get(account_id))
+    }
+
+    fn update(&mut self, token_id: &AccountId, account_id: &AccountId, balance: Balance) {
+        let mut deposits = self.tokens.get(token_id).unwrap_or_else(|| {
+            let token_hash = env::sha256(token_id.as_bytes());
+            UnorderedMap::new([self.prefix.as_slice(), b"d", &token_hash].concat())
+        });
+        if balance == 0 {
+            deposits.remove(account_id);
+        } else {
+            deposits.insert(
+                account_id,
+                &DepositRecord { amount: balance, last_activity: env::block_timestamp() },
+            );
+        }
+        self.tokens.insert(token_id, &deposits);
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct DepositsEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [UnclaimedSwept<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct UnclaimedSwept<'a> {
+    token_id: &'a AccountId,
+    account_id: &'a AccountId,
+    treasury_id: &'a AccountId,
+    amount: U128,
+    idle_since: U64,
+}
+
+/// Generates the `sweep_unclaimed(token_id, older_than, from_index, limit)` method of the owner,
+/// which moves the balances of a [`Deposits`] field abandoned for `older_than` nanoseconds to
+/// the treasury, scanning up to `limit` deposits, [`DEFAULT_SWEEP_LIMIT`] by default. It
+/// requires a deposit of exactly 1 yoctoNEAR, which ensures it is signed with a full access key.
+/// Takes name of the Contract struct and the inner field for the deposits.
+#[macro_export]
+macro_rules! impl_deposits_sweep {
+    ($contract: ident, $deposits: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            #[payable]
+            pub fn sweep_unclaimed(
+                &mut self,
+                token_id: AccountId,
+                older_than: near_sdk::json_types::U64,
+                from_index: Option<near_sdk::json_types::U64>,
+                limit: Option<u64>,
+            ) -> $crate::deposits::SweepOutcome {
+                near_sdk::assert_one_yocto();
+                self.$deposits.sweep_unclaimed(
+                    &token_id,
+                    older_than.0,
+                    from_index.map(|index| index.0),
+                    limit.unwrap_or($crate::deposits::DEFAULT_SWEEP_LIMIT),
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        deposits: Deposits,
+    }
+
+    impl_deposits_sweep!(Contract, deposits);
+
+    const DAY: Duration = 86_400_000_000_000;
+
+    fn at(timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn setup() -> Deposits {
+        let mut deposits = Deposits::new(b"d", accounts(0), accounts(5), 30 * DAY);
+        at(DAY);
+        deposits.credit(&accounts(4), &accounts(1), 10);
+        deposits.credit(&accounts(4), &accounts(2), 20);
+        at(40 * DAY);
+        deposits.credit(&accounts(4), &accounts(3), 30);
+        deposits
+    }
+
+    #[test]
+    fn sweep_abandoned_deposits() {
+        let mut deposits = setup();
+        at(50 * DAY);
+        let outcome = deposits.sweep_unclaimed(&accounts(4), 30 * DAY, None, 2);
+        assert_eq!(outcome, SweepOutcome { amount: U128(20), swept: 1, next_index: Some(U64(1)) });
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"deposits","version":"1.0.0","event":"unclaimed_swept","data":[{"token_id":"eugene","account_id":"charlie","treasury_id":"fargo","amount":"20","idle_since":"86400000000000"}]}"#
+            ]
+        );
+        let outcome = deposits.sweep_unclaimed(&accounts(4), 30 * DAY, Some(1), 2);
+        assert_eq!(outcome, SweepOutcome { amount: U128(10), swept: 1, next_index: None });
+        assert_eq!(deposits.balance_of(&accounts(4), &accounts(1)), 0);
+        assert_eq!(deposits.balance_of(&accounts(4), &accounts(3)), 30);
+        assert_eq!(deposits.balance_of(&accounts(4), &accounts(5)), 30);
+    }
+
+    #[test]
+    fn sweep_method_requires_one_yocto() {
+        let mut contract = Contract { deposits: setup() };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(50 * DAY)
+            .attached_deposit(1)
+            .build());
+        let outcome = contract.sweep_unclaimed(accounts(4), U64(30 * DAY), None, None);
+        assert_eq!(outcome, SweepOutcome { amount: U128(30), swept: 2, next_index: None });
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn sweep_method_without_yocto() {
+        let mut contract = Contract { deposits: setup() };
+        at(50 * DAY);
+        contract.sweep_unclaimed(accounts(4), U64(30 * DAY), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The retention window is shorter than the minimum retention")]
+    fn sweep_before_retention() {
+        let mut deposits = setup();
+        deposits.sweep_unclaimed(&accounts(4), DAY, None, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn sweep_by_other_account() {
+        let mut deposits = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        deposits.sweep_unclaimed(&accounts(4), 30 * DAY, None, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Swept amount overflow")]
+    fn sweep_amount_overflow() {
+        let mut deposits = Deposits::new(b"d", accounts(0), accounts(5), 30 * DAY);
+        at(DAY);
+        deposits.credit(&accounts(4), &accounts(1), Balance::MAX);
+        deposits.credit(&accounts(4), &accounts(2), 1);
+        at(50 * DAY);
+        deposits.sweep_unclaimed(&accounts(4), 30 * DAY, None, 10);
+    }
+}
@@ -1,3 +1,5 @@
+use near_sdk::base64;
+use near_sdk::borsh::{self, BorshSerialize};
 use near_sdk::env;
 use serde::Serialize;
 
@@ -8,6 +10,20 @@ use serde::Serialize;
 pub(crate) enum NearEvent<'a> {
     Nep171(crate::non_fungible_token::events::Nep171Event<'a>),
     Nep141(crate::fungible_token::events::Nep141Event<'a>),
+    Nep141Indexed(crate::fungible_token::events::Nep141IndexedEvent<'a>),
+    Nep245(crate::multi_token::events::Nep245Event<'a>),
+    AccountIndex(crate::account_index::AccountIndexEvent<'a>),
+    FeatureFlags(crate::feature_flags::FeatureFlagsEvent<'a>),
+    Config(crate::config::ConfigEvent<'a>),
+    NearAllowance(crate::near_allowance::NearAllowanceEvent<'a>),
+    Guardians(crate::guardians::GuardiansEvent<'a>),
+    Sessions(crate::sessions::SessionsEvent<'a>),
+    Deposits(crate::deposits::DepositsEvent<'a>),
+    PendingCallbacks(crate::pending_callbacks::PendingCallbacksEvent<'a>),
+    LegacyWrapper(crate::fungible_token::legacy_wrapper::LegacyWrapperEvent<'a>),
+    CallerPolicy(crate::caller_policy::CallerPolicyEvent<'a>),
+    Decommission(crate::decommission::DecommissionEvent<'a>),
+    Refunds(crate::refund_queue::RefundsEvent<'a>),
 }
 
 impl<'a> NearEvent<'a> {
@@ -27,3 +43,91 @@ impl<'a> NearEvent<'a> {
         near_sdk::env::log_str(&self.to_json_event_string());
     }
 }
+
+/// NEP-297 event of a standard defined by the contract, logged as `EVENT_JSON:` followed by its
+/// JSON serialization like the events of the standards in this crate, so that indexers can parse
+/// both the same way.
+///
+/// ```
+/// use near_contract_standards::event::Nep297Event;
+/// use near_sdk::serde::Serialize;
+/// use near_sdk::AccountId;
+///
+/// #[derive(Serialize)]
+/// #[serde(crate = "near_sdk::serde")]
+/// struct Vote<'a> {
+///     voter_id: &'a AccountId,
+///     proposal_id: u64,
+/// }
+///
+/// let voter_id = "alice.near".parse().unwrap();
+/// // EVENT_JSON:{"standard":"dao","version":"1.0.0","event":"vote","data":[{"voter_id":"alice.near","proposal_id":3}]}
+/// Nep297Event::new("dao", "1.0.0", "vote", [Vote { voter_id: &voter_id, proposal_id: 3 }]).emit();
+/// ```
+#[derive(Serialize, Debug)]
+#[must_use = "don't forget to `.emit()` this event"]
+pub struct Nep297Event<'a, T: Serialize> {
+    pub standard: &'a str,
+    pub version: &'a str,
+    pub event: &'a str,
+    pub data: T,
+}
+
+impl<'a, T: Serialize> Nep297Event<'a, T> {
+    pub fn new(standard: &'a str, version: &'a str, event: &'a str, data: T) -> Self {
+        Self { standard, version, event, data }
+    }
+
+    /// Returns the log line of the event, prefixed with `EVENT_JSON:`.
+    pub fn to_event_string(&self) -> String {
+        // Events cannot fail to serialize so fine to panic on error
+        let json = serde_json::to_string(self).unwrap_or_else(|_| env::abort());
+        format!("EVENT_JSON:{}", json)
+    }
+
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        env::log_str(&self.to_event_string());
+    }
+}
+
+/// Log prefix of compact Borsh-encoded events, the alternative to `EVENT_JSON:` NEP-297 events.
+pub(crate) const EVENT_BORSH_PREFIX: &str = "EVENT_BORSH:";
+
+/// Envelope of compact Borsh-encoded events. Logged as [`EVENT_BORSH_PREFIX`] followed by the
+/// base64 encoding of the Borsh serialization of this struct. The fields mirror the keys of the
+/// JSON event format so that indexers can decode both into the same representation.
+#[derive(BorshSerialize, Debug)]
+#[must_use = "don't forget to `.emit()` this event"]
+pub(crate) struct BorshEvent<'a, T: BorshSerialize> {
+    pub standard: &'static str,
+    pub version: &'static str,
+    pub event: &'static str,
+    pub data: &'a [T],
+}
+
+impl<T: BorshSerialize> BorshEvent<'_, T> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub(crate) fn emit(self) {
+        // Events cannot fail to serialize so fine to panic on error
+        let bytes = self.try_to_vec().unwrap_or_else(|_| env::abort());
+        env::log_str(&format!("{}{}", EVENT_BORSH_PREFIX, base64::encode(bytes)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils;
+
+    #[test]
+    fn custom_event() {
+        Nep297Event::new("dao", "1.0.0", "vote", [3u64]).emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"dao","version":"1.0.0","event":"vote","data":[3]}"#
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, IntoStorageKey, StorageUsage, StorageUsageChange};
+
+/// Default number of events returned by one `recent_events` call.
+pub const DEFAULT_EVENT_LOG_LIMIT: u64 = 50;
+
+/// Event persisted by an [`EventLogStore`], with the block it was logged in.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StoredEvent {
+    /// Position of the event in the log, never reused.
+    pub index: U64,
+    pub block_height: U64,
+    pub block_timestamp: U64,
+    /// The event as logged, e.g. the JSON after the `EVENT_JSON:` prefix.
+    pub event: String,
+}
+
+/// Bounds and storage usage of an [`EventLogStore`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventLogStats {
+    /// Index of the oldest stored event.
+    pub first_index: U64,
+    /// Index of the next recorded event.
+    pub next_index: U64,
+    pub capacity: U64,
+    /// Bytes of storage used by the stored events.
+    pub storage_usage: U64,
+    /// Storage staking cost of the stored events.
+    pub storage_cost: U128,
+}
+
+/// Standard interface of [`EventLogStore`], exposed with
+/// [`impl_event_log`](crate::impl_event_log).
+pub trait EventLogView {
+    /// Returns up to `limit` stored events, [`DEFAULT_EVENT_LOG_LIMIT`] by default, from the
+    /// oldest one at or after `from_index`.
+    fn recent_events(&self, from_index: Option<U64>, limit: Option<u64>) -> Vec<StoredEvent>;
+
+    /// Returns the bounds and the storage usage of the log.
+    fn event_log_stats(&self) -> EventLogStats;
+
+    /// Removes up to `count` of the oldest events, returning how many were removed. Can only be
+    /// called by the admin of the log, with exactly 1 yoctoNEAR attached.
+    fn prune_events(&mut self, count: u64) -> u64;
+}
+
+/// Recent events of a contract persisted in a bounded ring buffer, so that light clients without
+/// an indexer can reconstruct the recent activity of the contract through the paginated
+/// `recent_events` view.
+///
+/// The contract passes the events it logs to [`record`](Self::record). Once `capacity` events
+/// are stored, recording an event removes the oldest one, so the storage of the log stays
+/// bounded. The admin can remove old events earlier with [`prune`](Self::prune), e.g. before
+/// lowering the capacity. Indices are never reused, so clients resume from the index after the
+/// last event they read.
+///
+/// The bytes used by the stored events are accounted in [`storage_usage`](Self::storage_usage),
+/// and [`record`](Self::record) returns the storage change of each event, so that the contract
+/// can charge it to the account whose action logged the event.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct EventLogStore {
+    pub admin_id: AccountId,
+    pub capacity: u64,
+    first_index: u64,
+    next_index: u64,
+    storage_usage: StorageUsage,
+    events: LookupMap<u64, StoredEvent>,
+}
+
+impl EventLogStore {
+    pub fn new<S>(prefix: S, admin_id: AccountId, capacity: u64) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        require!(capacity > 0, "The capacity should be a positive number");
+        Self {
+            admin_id,
+            capacity,
+            first_index: 0,
+            next_index: 0,
+            storage_usage: 0,
+            events: LookupMap::new(prefix),
+        }
+    }
+
+    /// Number of stored events.
+    pub fn len(&self) -> u64 {
+        self.next_index - self.first_index
+    }
+
+    /// Returns `true` if no events are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes of storage used by the stored events.
+    pub fn storage_usage(&self) -> StorageUsage {
+        self.storage_usage
+    }
+
+    /// Stores the event, removing the oldest events beyond the capacity. Returns the change of
+    /// the storage used by the log, which is negative if the removed events were larger.
+    pub fn record(&mut self, event: &str) -> StorageUsageChange {
+        let initial_storage_usage = env::storage_usage();
+        while self.len() >= self.capacity {
+            self.remove_oldest();
+        }
+        let index = self.next_index;
+        self.events.insert(
+            &index,
+            &StoredEvent {
+                index: index.into(),
+                block_height: env::block_height().into(),
+                block_timestamp: env::block_timestamp().into(),
+                event: event.to_string(),
+            },
+        );
+        self.next_index += 1;
+        self.account_storage(initial_storage_usage)
+    }
+
+    /// Returns the stored event, or `None` if it was removed or not recorded yet.
+    pub fn get(&self, index: u64) -> Option<StoredEvent> {
+        self.events.get(&index)
+    }
+
+    /// Returns up to `limit` stored events, all of them without a limit, from the oldest one at
+    /// or after `from_index`.
+    pub fn events(&self, from_index: Option<U64>, limit: Option<u64>) -> Vec<StoredEvent> {
+        let limit = limit.unwrap_or(u64::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+        let start_index = from_index.map_or(0, |index| index.0).max(self.first_index);
+        (start_index..self.next_index)
+            .take(limit.min(self.len()) as usize)
+            .map(|index| {
+                self.events
+                    .get(&index)
+                    .unwrap_or_else(|| env::panic_str("Event log is in an inconsistent state"))
+            })
+            .collect()
+    }
+
+    /// Removes up to `count` of the oldest events, returning how many were removed. Panics if
+    /// the predecessor isn't the admin.
+    pub fn prune(&mut self, count: u64) -> u64 {
+        require!(env::predecessor_account_id() == self.admin_id, "Unauthorized");
+        let initial_storage_usage = env::storage_usage();
+        let removed = count.min(self.len());
+        for _ in 0..removed {
+            self.remove_oldest();
+        }
+        self.account_storage(initial_storage_usage);
+        removed
+    }
+
+    pub fn stats(&self) -> EventLogStats {
+        EventLogStats {
+            first_index: self.first_index.into(),
+            next_index: self.next_index.into(),
+            capacity: self.capacity.into(),
+            storage_usage: self.storage_usage.into(),
+            storage_cost: (self.storage_usage as u128 * env::storage_byte_cost()).into(),
+        }
+    }
+
+    fn remove_oldest(&mut self) {
+        self.events.remove(&self.first_index);
+        self.first_index += 1;
+    }
+
+    fn account_storage(&mut self, initial_storage_usage: StorageUsage) -> StorageUsageChange {
+        let change = env::storage_usage() as StorageUsageChange
+            - initial_storage_usage as StorageUsageChange;
+        self.storage_usage = (self.storage_usage as StorageUsageChange + change) as StorageUsage;
+        change
+    }
+}
+
+/// Exposes the [`EventLogView`] methods of an [`EventLogStore`] field. The yoctoNEAR required by
+/// `prune_events` ensures it is signed with a full access key.
+/// Takes name of the Contract struct and the inner field for the log.
+#[macro_export]
+macro_rules! impl_event_log {
+    ($contract: ident, $log: ident) => {
+        use $crate::event_log::{EventLogStats, EventLogView, StoredEvent};
+
+        #[near_bindgen]
+        impl EventLogView for $contract {
+            fn recent_events(
+                &self,
+                from_index: Option<near_sdk::json_types::U64>,
+                limit: Option<u64>,
+            ) -> Vec<StoredEvent> {
+                self.$log.events(
+                    from_index,
+                    Some(limit.unwrap_or($crate::event_log::DEFAULT_EVENT_LOG_LIMIT)),
+                )
+            }
+
+            fn event_log_stats(&self) -> EventLogStats {
+                self.$log.stats()
+            }
+
+            #[payable]
+            fn prune_events(&mut self, count: u64) -> u64 {
+                near_sdk::assert_one_yocto();
+                self.$log.prune(count)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        log: EventLogStore,
+    }
+
+    impl_event_log!(Contract, log);
+
+    fn setup(capacity: u64) -> EventLogStore {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        EventLogStore::new(b"e", accounts(0), capacity)
+    }
+
+    fn events(log: &EventLogStore, from_index: Option<u64>, limit: Option<u64>) -> Vec<String> {
+        log.events(from_index.map(U64), limit).into_iter().map(|event| event.event).collect()
+    }
+
+    #[test]
+    fn ring_buffer() {
+        let mut log = setup(3);
+        for event in ["a", "b", "c", "d"] {
+            log.record(event);
+        }
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.get(0), None);
+        assert_eq!(events(&log, None, None), vec!["b", "c", "d"]);
+        assert_eq!(events(&log, Some(2), Some(1)), vec!["c"]);
+        assert_eq!(events(&log, Some(4), None), Vec::<String>::new());
+    }
+
+    #[test]
+    fn prune_and_storage_accounting() {
+        let mut log = setup(10);
+        let added = log.record("a");
+        assert!(added > 0);
+        log.record("b");
+        assert_eq!(log.storage_usage(), 2 * added as StorageUsage);
+        assert_eq!(log.prune(5), 2);
+        assert!(log.is_empty());
+        assert_eq!(log.storage_usage(), 0);
+        assert_eq!(log.stats().first_index, U64(2));
+    }
+
+    #[test]
+    fn prune_events_with_yocto() {
+        let mut contract = Contract { log: setup(10) };
+        contract.log.record("a");
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        assert_eq!(contract.prune_events(5), 1);
+        assert_eq!(contract.event_log_stats().first_index, U64(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn prune_events_without_yocto() {
+        let mut contract = Contract { log: setup(10) };
+        contract.prune_events(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_admin_prunes() {
+        let mut log = setup(10);
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        log.prune(1);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId};
+use std::collections::BTreeMap;
+
+/// Value of a feature flag. Boolean flags enable features, numeric flags hold parameters like
+/// caps. In JSON, boolean flags are `true` or `false` and numeric flags are strings like `"100"`.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum FlagValue {
+    Bool(bool),
+    Number(U64),
+}
+
+/// Feature flag as returned by the `feature_flags` view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeatureFlag {
+    pub name: String,
+    pub value: FlagValue,
+}
+
+/// Standard interface of [`FeatureFlags`], exposed with
+/// [`impl_feature_flags`](crate::impl_feature_flags).
+pub trait FeatureFlagsProvider {
+    /// Returns all flags in lexicographic order of their names.
+    fn feature_flags(&self) -> Vec<FeatureFlag>;
+
+    /// Returns the value of the flag, or `None` if it isn't set.
+    fn feature_flag(&self, name: String) -> Option<FlagValue>;
+
+    /// Sets the flag. Can only be called by the admin of the flags, with exactly 1 yoctoNEAR
+    /// attached.
+    fn set_feature_flag(&mut self, name: String, value: FlagValue);
+
+    /// Removes the flag, which disables it. Can only be called by the admin of the flags, with
+    /// exactly 1 yoctoNEAR attached.
+    fn remove_feature_flag(&mut self, name: String);
+}
+
+/// Named boolean and numeric flags of a contract, to be stored in the contract state, so that
+/// features can be rolled out in stages, e.g. enabling a new pool type or raising a cap, without
+/// redeploying the contract.
+///
+/// Flags are kept in a single map serialized with the contract state, so they should be few and
+/// their names short. Only the `admin_id` can change them, and every change is logged as a
+/// `flag_set` or `flag_removed` event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"feature_flags","version":"1.0.0","event":"flag_set","data":[{"name":"pool_v2","value":true}]}
+/// ```
+///
+/// Methods are guarded with [`assert_enabled`](Self::assert_enabled) or the
+/// [`require_enabled!`](crate::feature_flags::require_enabled) macro.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct FeatureFlags {
+    pub admin_id: AccountId,
+    flags: BTreeMap<String, FlagValue>,
+}
+
+impl FeatureFlags {
+    /// Creates flags managed by `admin_id`, with all features disabled.
+    pub fn new(admin_id: AccountId) -> Self {
+        Self { admin_id, flags: BTreeMap::new() }
+    }
+
+    /// Returns the value of the flag, or `None` if it isn't set.
+    pub fn get(&self, name: &str) -> Option<FlagValue> {
+        self.flags.get(name).copied()
+    }
+
+    /// Returns `true` if the flag is set to `true`.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name) == Some(FlagValue::Bool(true))
+    }
+
+    /// Returns the value of a numeric flag, or `None` if it isn't set. Panics if it's a boolean
+    /// flag.
+    pub fn number(&self, name: &str) -> Option<u64> {
+        self.get(name).map(|value| match value {
+            FlagValue::Number(number) => number.0,
+            FlagValue::Bool(_) => {
+                env::panic_str(format!("The flag {} is not a number", name).as_str())
+            }
+        })
+    }
+
+    /// Panics unless the flag is set to `true`.
+    pub fn assert_enabled(&self, name: &str) {
+        if !self.is_enabled(name) {
+            env::panic_str(format!("The feature {} is not enabled", name).as_str());
+        }
+    }
+
+    /// Sets the flag. Panics if the predecessor isn't the admin.
+    pub fn set(&mut self, name: &str, value: FlagValue) {
+        self.assert_admin();
+        self.flags.insert(name.to_string(), value);
+        emit_event("flag_set", name, Some(value));
+    }
+
+    /// Removes the flag, returning `true` if it was set. Panics if the predecessor isn't the
+    /// admin.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.assert_admin();
+        let removed = self.flags.remove(name).is_some();
+        if removed {
+            emit_event("flag_removed", name, None);
+        }
+        removed
+    }
+
+    /// Returns all flags in lexicographic order of their names.
+    pub fn flags(&self) -> Vec<FeatureFlag> {
+        self.flags
+            .iter()
+            .map(|(name, value)| FeatureFlag { name: name.clone(), value: *value })
+            .collect()
+    }
+
+    fn assert_admin(&self) {
+        require!(env::predecessor_account_id() == self.admin_id, "Unauthorized");
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct FeatureFlagsEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [FeatureFlagChange<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct FeatureFlagChange<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<FlagValue>,
+}
+
+fn emit_event(event: &'static str, name: &str, value: Option<FlagValue>) {
+    NearEvent::FeatureFlags(FeatureFlagsEvent {
+        version: "1.0.0",
+        event,
+        data: &[FeatureFlagChange { name, value }],
+    })
+    .emit();
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __feature_flags_require_enabled {
+    ($flags:expr, $name:expr) => {
+        $flags.assert_enabled($name)
+    };
+}
+
+/// Panics unless the flag of the [`FeatureFlags`] is set to `true`.
+///
+/// ```ignore
+/// near_contract_standards::feature_flags::require_enabled!(self.flags, "pool_v2");
+/// ```
+#[doc(inline)]
+pub use crate::__feature_flags_require_enabled as require_enabled;
+
+/// Exposes the [`FeatureFlagsProvider`] methods of a [`FeatureFlags`] field. The yoctoNEAR
+/// required by the mutating methods ensures they are signed with a full access key.
+/// Takes name of the Contract struct and the inner field for the flags.
+#[macro_export]
+macro_rules! impl_feature_flags {
+    ($contract: ident, $flags: ident) => {
+        use $crate::feature_flags::{FeatureFlag, FeatureFlagsProvider, FlagValue};
+
+        #[near_bindgen]
+        impl FeatureFlagsProvider for $contract {
+            fn feature_flags(&self) -> Vec<FeatureFlag> {
+                self.$flags.flags()
+            }
+
+            fn feature_flag(&self, name: String) -> Option<FlagValue> {
+                self.$flags.get(&name)
+            }
+
+            #[payable]
+            fn set_feature_flag(&mut self, name: String, value: FlagValue) {
+                near_sdk::assert_one_yocto();
+                self.$flags.set(&name, value)
+            }
+
+            #[payable]
+            fn remove_feature_flag(&mut self, name: String) {
+                near_sdk::assert_one_yocto();
+                self.$flags.remove(&name);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        flags: FeatureFlags,
+    }
+
+    impl_feature_flags!(Contract, flags);
+
+    fn setup() -> FeatureFlags {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        FeatureFlags::new(accounts(0))
+    }
+
+    #[test]
+    fn set_and_remove_flags() {
+        let mut flags = setup();
+        assert!(!flags.is_enabled("pool_v2"));
+        flags.set("pool_v2", FlagValue::Bool(true));
+        flags.set("max_deposit", FlagValue::Number(U64(100)));
+        assert!(flags.is_enabled("pool_v2"));
+        assert!(!flags.is_enabled("max_deposit"));
+        assert_eq!(flags.number("max_deposit"), Some(100));
+        require_enabled!(flags, "pool_v2");
+
+        assert!(flags.remove("pool_v2"));
+        assert!(!flags.remove("pool_v2"));
+        assert_eq!(
+            flags.flags(),
+            vec![FeatureFlag {
+                name: "max_deposit".to_string(),
+                value: FlagValue::Number(U64(100))
+            }]
+        );
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"feature_flags","version":"1.0.0","event":"flag_set","data":[{"name":"pool_v2","value":true}]}"#,
+                r#"EVENT_JSON:{"standard":"feature_flags","version":"1.0.0","event":"flag_set","data":[{"name":"max_deposit","value":"100"}]}"#,
+                r#"EVENT_JSON:{"standard":"feature_flags","version":"1.0.0","event":"flag_removed","data":[{"name":"pool_v2"}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature pool_v2 is not enabled")]
+    fn assert_enabled() {
+        let mut flags = setup();
+        flags.set("pool_v2", FlagValue::Bool(false));
+        flags.assert_enabled("pool_v2");
+    }
+
+    #[test]
+    fn flag_methods_require_one_yocto() {
+        let mut contract = Contract { flags: setup() };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.set_feature_flag("pool_v2".to_string(), FlagValue::Bool(true));
+        assert_eq!(contract.feature_flag("pool_v2".to_string()), Some(FlagValue::Bool(true)));
+        contract.remove_feature_flag("pool_v2".to_string());
+        assert_eq!(contract.feature_flag("pool_v2".to_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_flag_without_yocto() {
+        let mut contract = Contract { flags: setup() };
+        contract.set_feature_flag("pool_v2".to_string(), FlagValue::Bool(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_admin_sets_flags() {
+        let mut flags = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        flags.set("pool_v2", FlagValue::Bool(true));
+    }
+}
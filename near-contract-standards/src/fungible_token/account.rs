@@ -0,0 +1,37 @@
+use crate::user_store::VersionedRecord;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+
+/// Account record of a [`FungibleTokenV2`](crate::fungible_token::FungibleTokenV2) holder in its
+/// latest layout, with the fields of the account besides its balance, which is kept by the
+/// [`FungibleToken`](crate::fungible_token::FungibleToken). It has no fields yet.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq)]
+pub struct Account {}
+
+/// Versioned account record as it is persisted in state.
+///
+/// New fields are added to account records without migrating the state: the current [`Account`]
+/// layout is frozen into a new variant (e.g. `V1(AccountV1)`), a new `Current` layout is
+/// introduced, and the conversion into [`Account`] upgrades old records when they are read.
+/// Records are always written back as `Current`. Existing variants must never be reordered.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub enum VAccount {
+    Current(Account),
+}
+
+impl From<VAccount> for Account {
+    fn from(account: VAccount) -> Self {
+        match account {
+            VAccount::Current(account) => account,
+        }
+    }
+}
+
+impl From<Account> for VAccount {
+    fn from(account: Account) -> Self {
+        VAccount::Current(account)
+    }
+}
+
+impl VersionedRecord for VAccount {
+    type Current = Account;
+}
@@ -0,0 +1,434 @@
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_at_least_one_yocto, assert_one_yocto, env, refund_freed_storage, require, AccountId,
+    Balance, IntoStorageKey, StorageTracker,
+};
+
+/// Allowances of spenders which transfer tokens on behalf of their owners, up to an amount, like
+/// the escrows of NEP-20 style tokens. This extends the fungible token standard and is not part
+/// of it. Exposed with [`impl_fungible_token_allowances`](crate::impl_fungible_token_allowances).
+pub trait FungibleTokenAllowanceManagement {
+    /// Allows `spender_id` to transfer up to `amount` tokens of the predecessor, replacing its
+    /// previous allowance. An allowance of 0 removes it. Requires a deposit of at least 1
+    /// yoctoNEAR which covers the storage of a new allowance, the rest is refunded.
+    fn ft_approve(&mut self, spender_id: AccountId, amount: U128);
+
+    /// Returns the amount `spender_id` can still transfer on behalf of `owner_id`.
+    fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128;
+
+    /// Transfers tokens of `owner_id` within the allowance of the predecessor, like
+    /// `ft_transfer`. Requires a deposit of exactly 1 yoctoNEAR.
+    fn ft_transfer_from(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    );
+}
+
+/// Allowance methods of the tokens predating NEP-141, as described in NEP-21, for escrow
+/// contracts which still call them. Exposed with
+/// [`impl_fungible_token_nep21_allowances`](crate::impl_fungible_token_nep21_allowances).
+pub trait FungibleTokenNep21Allowances {
+    /// Increments the allowance of `escrow_account_id` by `amount`. Requires a deposit of at
+    /// least 1 yoctoNEAR which covers the storage of a new allowance, the rest is refunded.
+    fn inc_allowance(&mut self, escrow_account_id: AccountId, amount: U128);
+
+    /// Decrements the allowance of `escrow_account_id` by `amount`, down to 0. Requires a
+    /// deposit of exactly 1 yoctoNEAR.
+    fn dec_allowance(&mut self, escrow_account_id: AccountId, amount: U128);
+
+    /// Transfers tokens of `owner_id` within the allowance of the predecessor, which needs none
+    /// if it's the owner. Requires a deposit of exactly 1 yoctoNEAR.
+    fn transfer_from(&mut self, owner_id: AccountId, new_owner_id: AccountId, amount: U128);
+
+    fn get_allowance(&self, owner_id: AccountId, escrow_account_id: AccountId) -> U128;
+}
+
+/// Allowances of the accounts of a [`FungibleToken`], stored apart from the balances so the
+/// token's own storage deposit doesn't change. The storage of an allowance is paid by its owner
+/// when it's created and refunded to the owner when it's removed, either explicitly or because
+/// it was spent.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FungibleTokenAllowances {
+    /// (Owner, Spender) -> Remaining allowance, never 0.
+    pub allowances: LookupMap<(AccountId, AccountId), Balance>,
+}
+
+impl FungibleTokenAllowances {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { allowances: LookupMap::new(prefix) }
+    }
+
+    /// Returns the amount the spender can still transfer on behalf of the owner.
+    pub fn allowance(&self, owner_id: &AccountId, spender_id: &AccountId) -> Balance {
+        self.allowances.get(&(owner_id.clone(), spender_id.clone())).unwrap_or(0)
+    }
+
+    /// Sets the allowance of the spender, removing it if it's 0.
+    pub fn internal_set_allowance(
+        &mut self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        amount: Balance,
+    ) {
+        require!(owner_id != spender_id, "The owner can't be its own spender");
+        let key = (owner_id.clone(), spender_id.clone());
+        if amount == 0 {
+            self.allowances.remove(&key);
+        } else {
+            self.allowances.insert(&key, &amount);
+        }
+    }
+
+    /// Deducts `amount` from the allowance of the spender and refunds the storage of the
+    /// allowance to the owner if it's spent. Panics if the allowance is too low.
+    pub fn internal_spend(
+        &mut self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        amount: Balance,
+    ) {
+        let remaining = self
+            .allowance(owner_id, spender_id)
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The amount exceeds the allowance"));
+        let initial_storage_usage = env::storage_usage();
+        self.internal_set_allowance(owner_id, spender_id, remaining);
+        let freed_bytes = initial_storage_usage.saturating_sub(env::storage_usage());
+        if freed_bytes > 0 {
+            refund_freed_storage(owner_id.clone(), freed_bytes);
+        }
+    }
+
+    /// Transfers tokens of the owner within the allowance of the spender, which needs none if
+    /// it's the owner, calling the hook around the transfer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn internal_transfer_from<H: FungibleTokenTransferHook>(
+        &mut self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        if owner_id != spender_id {
+            self.internal_spend(owner_id, spender_id, amount);
+        }
+        token.internal_transfer_with_hook(hook, owner_id, receiver_id, amount, memo);
+    }
+
+    /// Sets the allowance of a spender of the predecessor, charging the storage of a new
+    /// allowance to the attached deposit and refunding the rest. The storage of a removed
+    /// allowance was paid by the predecessor, so it's released to it.
+    fn set_allowance_with_deposit(&mut self, spender_id: &AccountId, amount: Balance) {
+        let owner_id = env::predecessor_account_id();
+        let tracker = StorageTracker::start();
+        self.internal_set_allowance(&owner_id, spender_id, amount);
+        let _ = tracker.finish_with_release(owner_id);
+    }
+
+    pub fn ft_approve(&mut self, spender_id: AccountId, amount: U128) {
+        assert_at_least_one_yocto();
+        self.set_allowance_with_deposit(&spender_id, amount.0);
+    }
+
+    pub fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128 {
+        U128(self.allowance(&owner_id, &spender_id))
+    }
+
+    pub fn ft_transfer_from<H: FungibleTokenTransferHook>(
+        &mut self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let spender_id = env::predecessor_account_id();
+        self.internal_transfer_from(
+            token,
+            hook,
+            &owner_id,
+            &spender_id,
+            &receiver_id,
+            amount.0,
+            memo,
+        );
+    }
+
+    pub fn inc_allowance(&mut self, escrow_account_id: AccountId, amount: U128) {
+        assert_at_least_one_yocto();
+        let current = self.allowance(&env::predecessor_account_id(), &escrow_account_id);
+        let allowance =
+            current.checked_add(amount.0).unwrap_or_else(|| env::panic_str("Allowance overflow"));
+        self.set_allowance_with_deposit(&escrow_account_id, allowance);
+    }
+
+    pub fn dec_allowance(&mut self, escrow_account_id: AccountId, amount: U128) {
+        assert_one_yocto();
+        let current = self.allowance(&env::predecessor_account_id(), &escrow_account_id);
+        self.set_allowance_with_deposit(&escrow_account_id, current.saturating_sub(amount.0));
+    }
+
+    pub fn transfer_from<H: FungibleTokenTransferHook>(
+        &mut self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        owner_id: AccountId,
+        new_owner_id: AccountId,
+        amount: U128,
+    ) {
+        assert_one_yocto();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let spender_id = env::predecessor_account_id();
+        self.internal_transfer_from(
+            token,
+            hook,
+            &owner_id,
+            &spender_id,
+            &new_owner_id,
+            amount.0,
+            None,
+        );
+    }
+}
+
+/// Exposes the [`FungibleTokenAllowanceManagement`] methods for the [`FungibleToken`] of the
+/// contract.
+/// Takes name of the Contract struct, the inner field for the token and the inner field for the
+/// allowances.
+///
+/// With `hook = <field>`, the [`FungibleTokenTransferHook`] field is called around the transfers
+/// of `ft_transfer_from`, like the transfers of
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core).
+#[macro_export]
+macro_rules! impl_fungible_token_allowances {
+    ($contract: ident, $token: ident, $allowances: ident, hook = $hook: ident) => {
+        $crate::impl_fungible_token_allowances!(@impl $contract, $token, $allowances, [$hook]);
+    };
+    ($contract: ident, $token: ident, $allowances: ident) => {
+        $crate::impl_fungible_token_allowances!(@impl $contract, $token, $allowances, []);
+    };
+    (@impl $contract: ident, $token: ident, $allowances: ident, [$($hook: ident)?]) => {
+        use $crate::fungible_token::allowances::FungibleTokenAllowanceManagement;
+
+        #[near_bindgen]
+        impl FungibleTokenAllowanceManagement for $contract {
+            #[payable]
+            fn ft_approve(&mut self, spender_id: AccountId, amount: U128) {
+                self.$allowances.ft_approve(spender_id, amount)
+            }
+
+            fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128 {
+                self.$allowances.ft_allowance(owner_id, spender_id)
+            }
+
+            #[payable]
+            fn ft_transfer_from(
+                &mut self,
+                owner_id: AccountId,
+                receiver_id: AccountId,
+                amount: U128,
+                memo: Option<String>,
+            ) {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$allowances.ft_transfer_from(
+                    &mut self.$token,
+                    hook,
+                    owner_id,
+                    receiver_id,
+                    amount,
+                    memo,
+                )
+            }
+        }
+    };
+}
+
+/// Exposes the [`FungibleTokenNep21Allowances`] methods for the [`FungibleToken`] of the
+/// contract, sharing the allowances of
+/// [`impl_fungible_token_allowances`](crate::impl_fungible_token_allowances).
+/// Takes name of the Contract struct, the inner field for the token and the inner field for the
+/// allowances.
+///
+/// With `hook = <field>`, the [`FungibleTokenTransferHook`] field is called around the transfers
+/// of `transfer_from`.
+#[macro_export]
+macro_rules! impl_fungible_token_nep21_allowances {
+    ($contract: ident, $token: ident, $allowances: ident, hook = $hook: ident) => {
+        $crate::impl_fungible_token_nep21_allowances!(
+            @impl $contract, $token, $allowances, [$hook]
+        );
+    };
+    ($contract: ident, $token: ident, $allowances: ident) => {
+        $crate::impl_fungible_token_nep21_allowances!(@impl $contract, $token, $allowances, []);
+    };
+    (@impl $contract: ident, $token: ident, $allowances: ident, [$($hook: ident)?]) => {
+        use $crate::fungible_token::allowances::FungibleTokenNep21Allowances;
+
+        #[near_bindgen]
+        impl FungibleTokenNep21Allowances for $contract {
+            #[payable]
+            fn inc_allowance(&mut self, escrow_account_id: AccountId, amount: U128) {
+                self.$allowances.inc_allowance(escrow_account_id, amount)
+            }
+
+            #[payable]
+            fn dec_allowance(&mut self, escrow_account_id: AccountId, amount: U128) {
+                self.$allowances.dec_allowance(escrow_account_id, amount)
+            }
+
+            #[payable]
+            fn transfer_from(
+                &mut self,
+                owner_id: AccountId,
+                new_owner_id: AccountId,
+                amount: U128,
+            ) {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$allowances.transfer_from(
+                    &mut self.$token,
+                    hook,
+                    owner_id,
+                    new_owner_id,
+                    amount,
+                )
+            }
+
+            fn get_allowance(&self, owner_id: AccountId, escrow_account_id: AccountId) -> U128 {
+                self.$allowances.ft_allowance(owner_id, escrow_account_id)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause::Pause;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    fn set_caller(predecessor: AccountId, attached_deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(attached_deposit)
+            .build());
+    }
+
+    fn setup() -> (FungibleToken, FungibleTokenAllowances) {
+        set_caller(accounts(0), ONE_NEAR);
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(2));
+        token.internal_deposit(&accounts(0), 1_000);
+        (token, FungibleTokenAllowances::new(b"a".to_vec()))
+    }
+
+    #[test]
+    fn transfer_within_allowance() {
+        let (mut token, mut allowances) = setup();
+        allowances.ft_approve(accounts(1), U128(300));
+        assert_eq!(allowances.ft_allowance(accounts(0), accounts(1)), U128(300));
+
+        set_caller(accounts(1), 1);
+        allowances.ft_transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(200), None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 200);
+        assert_eq!(allowances.allowance(&accounts(0), &accounts(1)), 100);
+
+        // Spending the rest removes the allowance and refunds its storage to the owner.
+        set_caller(accounts(1), 1);
+        allowances.ft_transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(100), None);
+        assert!(!allowances.allowances.contains_key(&(accounts(0), accounts(1))));
+        let refund = get_created_receipts().pop().unwrap();
+        assert_eq!(refund.receiver_id, accounts(0));
+        assert!(matches!(refund.actions[..], [VmAction::Transfer { deposit }] if deposit > 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount exceeds the allowance")]
+    fn exceeds_allowance() {
+        let (mut token, mut allowances) = setup();
+        allowances.ft_approve(accounts(1), U128(100));
+        set_caller(accounts(1), 1);
+        allowances.ft_transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(101), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "INSUFFICIENT_DEPOSIT")]
+    fn approve_requires_storage_deposit() {
+        let (_, mut allowances) = setup();
+        set_caller(accounts(0), 1);
+        allowances.ft_approve(accounts(1), U128(100));
+    }
+
+    #[test]
+    fn nep21_allowances() {
+        let (mut token, mut allowances) = setup();
+        allowances.inc_allowance(accounts(1), U128(300));
+        set_caller(accounts(0), 1);
+        allowances.inc_allowance(accounts(1), U128(100));
+        allowances.dec_allowance(accounts(1), U128(50));
+        assert_eq!(allowances.allowance(&accounts(0), &accounts(1)), 350);
+
+        set_caller(accounts(1), 1);
+        allowances.transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(350));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 350);
+        assert_eq!(allowances.allowance(&accounts(0), &accounts(1)), 0);
+
+        // The owner doesn't need an allowance.
+        set_caller(accounts(0), 1);
+        allowances.transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(50));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 600);
+
+        set_caller(accounts(0), 1);
+        allowances.dec_allowance(accounts(1), U128(1_000));
+        assert_eq!(allowances.ft_allowance(accounts(0), accounts(1)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn nep21_transfer_requires_one_yocto() {
+        let (mut token, mut allowances) = setup();
+        allowances.inc_allowance(accounts(1), U128(100));
+        set_caller(accounts(1), 0);
+        allowances.transfer_from(&mut token, &mut (), accounts(0), accounts(2), U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn transfer_from_calls_hook() {
+        let (mut token, mut allowances) = setup();
+        allowances.ft_approve(accounts(1), U128(100));
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        set_caller(accounts(1), 1);
+        allowances.ft_transfer_from(
+            &mut token,
+            &mut pause,
+            accounts(0),
+            accounts(2),
+            U128(1),
+            None,
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use near_sdk::json_types::U128;
+use near_sdk::Balance;
+use std::fmt;
+
+/// Token amount in the smallest units of a token with `decimals` decimal places, as declared in
+/// [`FungibleTokenMetadata`](crate::fungible_token::metadata::FungibleTokenMetadata).
+///
+/// Conversions from and to human readable amounts like `"1.5"` use exact integer arithmetic.
+///
+/// ```
+/// use near_contract_standards::fungible_token::TokenAmount;
+///
+/// let amount = TokenAmount::from_human("1.5", 6).unwrap();
+/// assert_eq!(amount.amount, 1_500_000);
+/// assert_eq!(amount.to_human(), "1.5");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TokenAmount {
+    /// Amount in the smallest units of the token.
+    pub amount: Balance,
+    /// Number of decimal places of the token.
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(amount: Balance, decimals: u8) -> Self {
+        Self { amount, decimals }
+    }
+
+    /// Parses a decimal amount like `"1.5"` or `".25"`. Fails if the amount has more fractional
+    /// digits than `decimals` or doesn't fit into a [`Balance`].
+    pub fn from_human(amount: &str, decimals: u8) -> Result<Self, ParseTokenAmountError> {
+        let (whole, fraction) = match amount.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (amount, ""),
+        };
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(ParseTokenAmountError::Invalid);
+        }
+        if fraction.len() > decimals as usize {
+            return Err(ParseTokenAmountError::TooPrecise);
+        }
+        let unit = unit(decimals)?;
+        let parse = |s: &str| -> Result<Balance, ParseTokenAmountError> {
+            if s.is_empty() {
+                Ok(0)
+            } else {
+                s.parse().map_err(|_| ParseTokenAmountError::Overflow)
+            }
+        };
+        let fraction_unit = unit / 10u128.pow(fraction.len() as u32);
+        let fraction = parse(fraction)? * fraction_unit;
+        parse(whole)?
+            .checked_mul(unit)
+            .and_then(|whole| whole.checked_add(fraction))
+            .map(|amount| Self { amount, decimals })
+            .ok_or(ParseTokenAmountError::Overflow)
+    }
+
+    /// Formats the amount as a decimal number without trailing zeros, e.g. `"1.5"` or `"2"`.
+    pub fn to_human(&self) -> String {
+        self.to_string()
+    }
+
+    /// Formats the amount as a decimal number with exactly `precision` fractional digits, or
+    /// `decimals` if it is lower, rounding down, e.g. `"12.345"` for `12.3456` with precision 3.
+    pub fn to_human_with_precision(&self, precision: u8) -> String {
+        let decimals = self.decimals as usize;
+        let digits = format!("{:0>width$}", self.amount, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let precision = precision.min(self.decimals) as usize;
+        if precision == 0 {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, &fraction[..precision])
+        }
+    }
+}
+
+/// Returns `10^decimals`, the amount of smallest units in one whole token.
+fn unit(decimals: u8) -> Result<Balance, ParseTokenAmountError> {
+    10u128.checked_pow(decimals as u32).ok_or(ParseTokenAmountError::Overflow)
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.amount.to_string();
+        let decimals = self.decimals as usize;
+        if digits.len() > decimals {
+            let (whole, fraction) = digits.split_at(digits.len() - decimals);
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.is_empty() {
+                write!(f, "{}", whole)
+            } else {
+                write!(f, "{}.{}", whole, fraction)
+            }
+        } else {
+            let fraction = digits.trim_end_matches('0');
+            if fraction.is_empty() {
+                write!(f, "0")
+            } else {
+                write!(
+                    f,
+                    "0.{:0>width$}",
+                    fraction,
+                    width = decimals - digits.len() + fraction.len()
+                )
+            }
+        }
+    }
+}
+
+impl From<TokenAmount> for U128 {
+    fn from(amount: TokenAmount) -> Self {
+        U128(amount.amount)
+    }
+}
+
+impl From<TokenAmount> for Balance {
+    fn from(amount: TokenAmount) -> Self {
+        amount.amount
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseTokenAmountError {
+    /// The amount is not a decimal number.
+    Invalid,
+    /// The amount has more fractional digits than the token has decimals.
+    TooPrecise,
+    /// The amount doesn't fit into a [`Balance`].
+    Overflow,
+}
+
+impl fmt::Display for ParseTokenAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "the token amount is not a decimal number"),
+            Self::TooPrecise => write!(f, "the token amount has too many decimal places"),
+            Self::Overflow => write!(f, "the token amount is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseTokenAmountError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_human() {
+        assert_eq!(TokenAmount::from_human("1.5", 6).unwrap().amount, 1_500_000);
+        assert_eq!(TokenAmount::from_human("2", 24).unwrap().amount, 2 * 10u128.pow(24));
+        assert_eq!(TokenAmount::from_human(".25", 2).unwrap().amount, 25);
+        assert_eq!(TokenAmount::from_human("3.", 0).unwrap().amount, 3);
+        assert_eq!(TokenAmount::from_human("0.000001", 6).unwrap().amount, 1);
+        assert_eq!(TokenAmount::from_human("", 6), Err(ParseTokenAmountError::Invalid));
+        assert_eq!(TokenAmount::from_human(".", 6), Err(ParseTokenAmountError::Invalid));
+        assert_eq!(TokenAmount::from_human("-1", 6), Err(ParseTokenAmountError::Invalid));
+        assert_eq!(TokenAmount::from_human("1.2.3", 6), Err(ParseTokenAmountError::Invalid));
+        assert_eq!(TokenAmount::from_human("0.1234567", 6), Err(ParseTokenAmountError::TooPrecise));
+        assert_eq!(
+            TokenAmount::from_human("340282366920938463463374607431768211456", 0),
+            Err(ParseTokenAmountError::Overflow)
+        );
+        assert_eq!(TokenAmount::from_human("1000", 36), Err(ParseTokenAmountError::Overflow));
+    }
+
+    #[test]
+    fn to_human() {
+        assert_eq!(TokenAmount::new(1_500_000, 6).to_human(), "1.5");
+        assert_eq!(TokenAmount::new(2_000_000, 6).to_human(), "2");
+        assert_eq!(TokenAmount::new(1, 6).to_human(), "0.000001");
+        assert_eq!(TokenAmount::new(120, 6).to_human(), "0.00012");
+        assert_eq!(TokenAmount::new(0, 6).to_human(), "0");
+        assert_eq!(TokenAmount::new(42, 0).to_human(), "42");
+        assert_eq!(
+            TokenAmount::new(u128::MAX, 38).to_human(),
+            "3.40282366920938463463374607431768211455"
+        );
+    }
+
+    #[test]
+    fn to_human_with_precision() {
+        assert_eq!(TokenAmount::new(12_345_678, 6).to_human_with_precision(3), "12.345");
+        assert_eq!(TokenAmount::new(12_000_000, 6).to_human_with_precision(2), "12.00");
+        assert_eq!(TokenAmount::new(12_999_999, 6).to_human_with_precision(0), "12");
+        assert_eq!(TokenAmount::new(1, 6).to_human_with_precision(3), "0.000");
+        assert_eq!(TokenAmount::new(1, 6).to_human_with_precision(18), "0.000001");
+        assert_eq!(TokenAmount::new(42, 0).to_human_with_precision(2), "42");
+    }
+}
@@ -0,0 +1,401 @@
+use crate::fungible_token::core_impl::{
+    ext_fungible_token_receiver, unused_amount_of_result, GAS_FOR_FT_TRANSFER_CALL,
+    GAS_FOR_RESOLVE_TRANSFER,
+};
+use crate::fungible_token::events::FtTransfer;
+use crate::fungible_token::hook::{FtHookTransfer, FtTransferKind, FungibleTokenTransferHook};
+use crate::fungible_token::FungibleToken;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, require, AccountId, Balance, Gas, Promise, PromiseOrValue,
+};
+
+/// Maximum number of receivers of [`ft_transfer_call_batch`](FungibleTokenBatch::ft_transfer_call_batch),
+/// so that each `ft_on_transfer` call gets a usable amount of gas.
+pub const MAX_FT_TRANSFER_CALL_BATCH: usize = 10;
+
+/// Gas of the resolve callback for each receiver, on top of `GAS_FOR_RESOLVE_TRANSFER`.
+const GAS_FOR_RESOLVE_TRANSFER_PER_RECEIVER: Gas = Gas(3_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait FungibleTokenBatchResolver {
+    fn ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128>;
+}
+
+/// Transfers to many receivers in one call, for airdrops and payrolls. This extends the fungible
+/// token standard and is not part of it. Exposed with
+/// [`impl_fungible_token_batch`](crate::impl_fungible_token_batch).
+pub trait FungibleTokenBatch {
+    /// Transfers the amounts to their receivers, like `ft_transfer` for each `(receiver_id,
+    /// amount)` pair. The batch fails as a whole if any transfer fails. Logs one `ft_transfer`
+    /// event with all the transfers.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn ft_transfer_batch(&mut self, transfers: Vec<(AccountId, U128)>, memo: Option<String>);
+
+    /// Transfers the amounts to their receivers and calls `ft_on_transfer` on each receiver with
+    /// `msg`, in parallel and with an equal share of the remaining gas. Unused tokens are
+    /// refunded to the sender per receiver by `ft_resolve_transfer_batch`, like they are by
+    /// `ft_resolve_transfer`. At most [`MAX_FT_TRANSFER_CALL_BATCH`] receivers are allowed.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    ///
+    /// Returns a promise which will result in the amounts used by each receiver, in the order of
+    /// `transfers`.
+    fn ft_transfer_call_batch(
+        &mut self,
+        transfers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+pub trait FungibleTokenBatchResolver {
+    fn ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128>;
+}
+
+impl FungibleToken {
+    /// Transfers the amounts from `sender_id` to their receivers, checking each transfer like
+    /// [`internal_transfer`](Self::internal_transfer), and logs one event for all of them.
+    pub fn internal_transfer_batch(
+        &mut self,
+        sender_id: &AccountId,
+        transfers: &[(AccountId, U128)],
+        memo: Option<&str>,
+    ) {
+        self.internal_transfer_batch_with_hook(
+            &mut (),
+            FtTransferKind::Transfer,
+            sender_id,
+            transfers,
+            memo,
+        )
+    }
+
+    /// Same as [`internal_transfer_batch`](Self::internal_transfer_batch), calling the hook
+    /// around each transfer, with the `kind` of the batch.
+    pub fn internal_transfer_batch_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        kind: FtTransferKind,
+        sender_id: &AccountId,
+        transfers: &[(AccountId, U128)],
+        memo: Option<&str>,
+    ) {
+        require!(!transfers.is_empty(), "At least one transfer is required");
+        for (receiver_id, amount) in transfers {
+            require!(sender_id != receiver_id, "Sender and receiver should be different");
+            require!(amount.0 > 0, "The amount should be a positive number");
+            let transfer = FtHookTransfer { kind, sender_id, receiver_id, amount: amount.0 };
+            hook.before_transfer(self, &transfer);
+            self.internal_withdraw(sender_id, amount.0);
+            self.internal_deposit(receiver_id, amount.0);
+            hook.after_transfer(self, &transfer);
+        }
+        let events: Vec<FtTransfer> = transfers
+            .iter()
+            .map(|(receiver_id, amount)| FtTransfer {
+                old_owner_id: sender_id,
+                new_owner_id: receiver_id,
+                amount,
+                memo,
+            })
+            .collect();
+        FtTransfer::emit_many(&events);
+    }
+
+    /// Transfers the amounts from `sender_id` to their receivers and calls `ft_on_transfer` on
+    /// each of them, with unused tokens refunded to `sender_id` by `ft_resolve_transfer_batch`.
+    pub fn internal_transfer_call_batch(
+        &mut self,
+        sender_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        self.internal_transfer_call_batch_with_hook(&mut (), sender_id, transfers, memo, msg)
+    }
+
+    /// Same as [`internal_transfer_call_batch`](Self::internal_transfer_call_batch), calling the
+    /// hook around each transfer. The hook of the refunds is called by
+    /// [`internal_ft_resolve_transfer_batch_with_hook`](Self::internal_ft_resolve_transfer_batch_with_hook).
+    pub fn internal_transfer_call_batch_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        require!(transfers.len() <= MAX_FT_TRANSFER_CALL_BATCH, "Too many transfers");
+        let gas_for_resolve = GAS_FOR_RESOLVE_TRANSFER
+            + Gas(GAS_FOR_RESOLVE_TRANSFER_PER_RECEIVER.0 * transfers.len() as u64);
+        let reserved_gas = GAS_FOR_FT_TRANSFER_CALL + gas_for_resolve;
+        require!(env::prepaid_gas() > reserved_gas, "More gas is required");
+        self.internal_transfer_batch_with_hook(
+            hook,
+            FtTransferKind::TransferCall,
+            &sender_id,
+            &transfers,
+            memo.as_deref(),
+        );
+
+        let gas_per_call = Gas((env::prepaid_gas() - reserved_gas).0 / transfers.len() as u64);
+        // Initiating receivers' calls and the callback
+        transfers
+            .iter()
+            .map(|(receiver_id, amount)| {
+                ext_fungible_token_receiver::ext(receiver_id.clone())
+                    .with_static_gas(gas_per_call)
+                    .ft_on_transfer(sender_id.clone(), *amount, msg.clone())
+            })
+            .reduce(Promise::and)
+            .unwrap_or_else(|| env::abort())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(gas_for_resolve)
+                    .ft_resolve_transfer_batch(sender_id, transfers),
+            )
+            .into()
+    }
+
+    /// Refunds the amounts unused by each receiver, read from the promise result of its
+    /// `ft_on_transfer` call, like [`internal_ft_resolve_transfer`](Self::internal_ft_resolve_transfer).
+    /// Returns (Used token amount of each receiver, Total burned token amount)
+    pub fn internal_ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: &AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> (Vec<U128>, Balance) {
+        self.internal_ft_resolve_transfer_batch_with_hook(&mut (), sender_id, transfers)
+    }
+
+    /// Same as [`internal_ft_resolve_transfer_batch`](Self::internal_ft_resolve_transfer_batch),
+    /// calling the hook around each refund.
+    pub fn internal_ft_resolve_transfer_batch_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: &AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> (Vec<U128>, Balance) {
+        let mut burned_amount = 0;
+        let used_amounts = transfers
+            .into_iter()
+            .enumerate()
+            .map(|(result_idx, (receiver_id, amount))| {
+                let unused_amount = unused_amount_of_result(result_idx as u64, amount.0);
+                let (used_amount, burned) = self.internal_refund_unused(
+                    hook,
+                    sender_id,
+                    receiver_id,
+                    amount.0,
+                    unused_amount,
+                );
+                burned_amount += burned;
+                U128(used_amount)
+            })
+            .collect();
+        (used_amounts, burned_amount)
+    }
+}
+
+impl FungibleTokenBatch for FungibleToken {
+    fn ft_transfer_batch(&mut self, transfers: Vec<(AccountId, U128)>, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_batch(&sender_id, &transfers, memo.as_deref());
+    }
+
+    fn ft_transfer_call_batch(
+        &mut self,
+        transfers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_call_batch(sender_id, transfers, memo, msg)
+    }
+}
+
+impl FungibleTokenBatchResolver for FungibleToken {
+    fn ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128> {
+        self.internal_ft_resolve_transfer_batch(&sender_id, transfers).0
+    }
+}
+
+/// Implements the [`FungibleTokenBatch`] extension.
+/// Takes name of the Contract struct, the inner field for the token and optional method name to
+/// call when tokens refunded to a deleted account are burned, like
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core).
+///
+/// With `hook = <field>`, the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) field is
+/// called around each transfer of the batch and each refund, like with
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core).
+#[macro_export]
+macro_rules! impl_fungible_token_batch {
+    ($contract: ident, $token: ident, hook = $hook: ident $(, $on_tokens_burned_fn:ident)?) => {
+        $crate::impl_fungible_token_batch!(
+            @impl $contract, $token, [$hook], [$($on_tokens_burned_fn)?]
+        );
+    };
+    ($contract: ident, $token: ident $(, $on_tokens_burned_fn:ident)?) => {
+        $crate::impl_fungible_token_batch!(
+            @impl $contract, $token, [], [$($on_tokens_burned_fn)?]
+        );
+    };
+    (@impl $contract: ident, $token: ident, [$($hook: ident)?],
+        [$($on_tokens_burned_fn:ident)?]) => {
+        use $crate::fungible_token::batch::{FungibleTokenBatch, FungibleTokenBatchResolver};
+
+        #[near_bindgen]
+        impl FungibleTokenBatch for $contract {
+            #[payable]
+            fn ft_transfer_batch(&mut self, transfers: Vec<(AccountId, U128)>, memo: Option<String>) {
+                near_sdk::assert_one_yocto();
+                let sender_id = near_sdk::env::predecessor_account_id();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_batch_with_hook(
+                    hook,
+                    $crate::fungible_token::hook::FtTransferKind::Transfer,
+                    &sender_id,
+                    &transfers,
+                    memo.as_deref(),
+                )
+            }
+
+            #[payable]
+            fn ft_transfer_call_batch(
+                &mut self,
+                transfers: Vec<(AccountId, U128)>,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<Vec<U128>> {
+                near_sdk::assert_one_yocto();
+                let sender_id = near_sdk::env::predecessor_account_id();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_call_batch_with_hook(
+                    hook, sender_id, transfers, memo, msg,
+                )
+            }
+        }
+
+        #[near_bindgen]
+        impl FungibleTokenBatchResolver for $contract {
+            #[private]
+            fn ft_resolve_transfer_batch(
+                &mut self,
+                sender_id: AccountId,
+                transfers: Vec<(AccountId, U128)>,
+            ) -> Vec<U128> {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                let (used_amounts, burned_amount) = self
+                    .$token
+                    .internal_ft_resolve_transfer_batch_with_hook(hook, &sender_id, transfers);
+                if burned_amount > 0 {
+                    $(self.$on_tokens_burned_fn(sender_id, burned_amount);)?
+                }
+                used_amounts
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause::Pause;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    fn setup() -> FungibleToken {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        for index in 0..3 {
+            token.internal_register_account(&accounts(index));
+        }
+        token.internal_deposit(&accounts(0), 1_000);
+        token
+    }
+
+    #[test]
+    fn transfer_batch() {
+        let mut token = setup();
+        token.ft_transfer_batch(vec![(accounts(1), U128(100)), (accounts(2), U128(200))], None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 700);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 200);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"100"},{"old_owner_id":"alice","new_owner_id":"charlie","amount":"200"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn transfer_batch_is_atomic() {
+        let mut token = setup();
+        token.ft_transfer_batch(vec![(accounts(1), U128(600)), (accounts(2), U128(600))], None);
+    }
+
+    #[test]
+    fn resolve_transfer_batch() {
+        let mut token = setup();
+        let transfers = vec![(accounts(1), U128(100)), (accounts(2), U128(200))];
+        token.internal_transfer_batch(&accounts(0), &transfers, None);
+
+        let unused = near_sdk::serde_json::to_vec(&U128(40)).unwrap();
+        testing_env!(
+            VMContextBuilder::new().current_account_id(accounts(3)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(unused), PromiseResult::Failed],
+        );
+        let used = token.ft_resolve_transfer_batch(accounts(0), transfers);
+        assert_eq!(used, vec![U128(60), U128(0)]);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 940);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 60);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn transfer_batch_calls_hook() {
+        let mut token = setup();
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        token.internal_transfer_batch_with_hook(
+            &mut pause,
+            FtTransferKind::Transfer,
+            &accounts(0),
+            &[(accounts(1), U128(100))],
+            None,
+        );
+    }
+}
@@ -1,18 +1,27 @@
 use crate::fungible_token::core::FungibleTokenCore;
-use crate::fungible_token::events::FtTransfer;
+use crate::fungible_token::error::FtTransferError;
+use crate::fungible_token::events::{FtBurn, FtMint, FtTransfer};
+use crate::fungible_token::gas::FtTransferCallGas;
+use crate::fungible_token::hook::{FtHookTransfer, FtTransferKind, FungibleTokenTransferHook};
 use crate::fungible_token::resolver::FungibleTokenResolver;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::LookupMap;
 use near_sdk::json_types::U128;
+use near_sdk::layout::{StorageLayout, StorageRegion};
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, log, require, AccountId, Balance, Gas, IntoStorageKey,
-    PromiseOrValue, PromiseResult, StorageUsage,
+    assert_one_yocto, env, ext_contract, AccountId, Balance, ContractError, FunctionError, Gas,
+    IntoStorageKey, PromiseError, PromiseOrValue, StorageUsage,
 };
 
-const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
-const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+pub(crate) const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+pub(crate) const GAS_FOR_FT_TRANSFER_CALL: Gas =
+    Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
 
-const NO_DEPOSIT: Balance = 0;
+pub(crate) const NO_DEPOSIT: Balance = 0;
+
+/// Upper bound for the JSON encoded `U128` returned by `ft_on_transfer`. Larger results are
+/// treated like failures without being deserialized.
+pub(crate) const MAX_FT_ON_TRANSFER_RESULT_BYTES: u64 = 128;
 
 #[ext_contract(ext_self)]
 trait FungibleTokenResolver {
@@ -57,7 +66,7 @@ pub trait FungibleTokenContract {
 /// Allows to include NEP-141 compatible token to any contract.
 /// There are next traits that any contract may implement:
 ///     - FungibleTokenCore -- interface with ft_transfer methods. FungibleToken provides methods for it.
-///     - FungibleTokenMetaData -- return metadata for the token in NEP-148, up to contract to implement.
+///     - FungibleTokenMetadataProvider -- return metadata for the token in NEP-148, see LazyFungibleTokenMetadata.
 ///     - StorageManager -- interface for NEP-145 for allocating storage per account. FungibleToken provides methods for it.
 ///     - AccountRegistrar -- interface for an account to register and unregister
 ///
@@ -74,6 +83,12 @@ pub struct FungibleToken {
     pub account_storage_usage: StorageUsage,
 }
 
+impl StorageLayout for FungibleToken {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        LookupMap::<AccountId, Balance>::storage_layout(prefix)
+    }
+}
+
 impl FungibleToken {
     pub fn new<S>(prefix: S) -> Self
     where
@@ -96,36 +111,88 @@ impl FungibleToken {
     pub fn internal_unwrap_balance_of(&self, account_id: &AccountId) -> Balance {
         match self.accounts.get(account_id) {
             Some(balance) => balance,
-            None => {
-                env::panic_str(format!("The account {} is not registered", &account_id).as_str())
-            }
+            None => ContractError::account_not_registered(account_id).panic(),
         }
     }
 
     pub fn internal_deposit(&mut self, account_id: &AccountId, amount: Balance) {
-        let balance = self.internal_unwrap_balance_of(account_id);
-        if let Some(new_balance) = balance.checked_add(amount) {
-            self.accounts.insert(account_id, &new_balance);
-            self.total_supply = self
-                .total_supply
-                .checked_add(amount)
-                .unwrap_or_else(|| env::panic_str("Total supply overflow"));
-        } else {
-            env::panic_str("Balance overflow");
-        }
+        self.internal_try_deposit(account_id, amount).unwrap_or_else(|err| err.panic())
+    }
+
+    /// Same as [`internal_deposit`](Self::internal_deposit), but returns the error instead of
+    /// panicking, leaving the balances unchanged.
+    pub fn internal_try_deposit(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Result<(), FtTransferError> {
+        let balance = self.internal_try_balance_of(account_id)?;
+        let new_balance = balance
+            .checked_add(amount)
+            .ok_or_else(|| FtTransferError::BalanceOverflow(account_id.clone()))?;
+        let total_supply =
+            self.total_supply.checked_add(amount).ok_or(FtTransferError::TotalSupplyOverflow)?;
+        self.accounts.insert(account_id, &new_balance);
+        self.total_supply = total_supply;
+        Ok(())
     }
 
     pub fn internal_withdraw(&mut self, account_id: &AccountId, amount: Balance) {
+        self.internal_try_withdraw(account_id, amount).unwrap_or_else(|err| err.panic())
+    }
+
+    /// Same as [`internal_withdraw`](Self::internal_withdraw), but returns the error instead of
+    /// panicking, leaving the balances unchanged.
+    pub fn internal_try_withdraw(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Result<(), FtTransferError> {
+        let balance = self.internal_try_balance_of(account_id)?;
+        let new_balance = balance
+            .checked_sub(amount)
+            .ok_or_else(|| FtTransferError::InsufficientBalance(account_id.clone()))?;
+        let total_supply =
+            self.total_supply.checked_sub(amount).ok_or(FtTransferError::TotalSupplyOverflow)?;
+        self.accounts.insert(account_id, &new_balance);
+        self.total_supply = total_supply;
+        Ok(())
+    }
+
+    fn internal_try_balance_of(&self, account_id: &AccountId) -> Result<Balance, FtTransferError> {
+        self.accounts
+            .get(account_id)
+            .ok_or_else(|| FtTransferError::AccountNotRegistered(account_id.clone()))
+    }
+
+    /// Mints `amount` to the account, increasing the total supply, and emits an `ft_mint` event.
+    pub fn internal_mint(&mut self, account_id: &AccountId, amount: Balance, memo: Option<&str>) {
+        self.internal_deposit(account_id, amount);
+        FtMint { owner_id: account_id, amount: &U128(amount), memo }.emit();
+    }
+
+    /// Burns `amount` from the account, decreasing the total supply, and emits an `ft_burn` event.
+    pub fn internal_burn(&mut self, account_id: &AccountId, amount: Balance, memo: Option<&str>) {
+        self.internal_withdraw(account_id, amount);
+        FtBurn { owner_id: account_id, amount: &U128(amount), memo }.emit();
+    }
+
+    /// Same as [`internal_withdraw`](Self::internal_withdraw), but instead of panicking when
+    /// `amount` exceeds the balance of the account, withdraws the whole balance. Returns the
+    /// shortfall, i.e. the part of `amount` which could not be withdrawn.
+    ///
+    /// Useful to collect fees without aborting the transaction when the account has slightly less
+    /// than the fee. The behaviour is chosen per call, as a flag on the token would change its
+    /// stored layout.
+    pub fn internal_withdraw_allow_zeroing(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Balance {
         let balance = self.internal_unwrap_balance_of(account_id);
-        if let Some(new_balance) = balance.checked_sub(amount) {
-            self.accounts.insert(account_id, &new_balance);
-            self.total_supply = self
-                .total_supply
-                .checked_sub(amount)
-                .unwrap_or_else(|| env::panic_str("Total supply overflow"));
-        } else {
-            env::panic_str("The account doesn't have enough balance");
-        }
+        let withdrawn = balance.min(amount);
+        self.internal_withdraw(account_id, withdrawn);
+        amount - withdrawn
     }
 
     pub fn internal_transfer(
@@ -135,10 +202,35 @@ impl FungibleToken {
         amount: Balance,
         memo: Option<String>,
     ) {
-        require!(sender_id != receiver_id, "Sender and receiver should be different");
-        require!(amount > 0, "The amount should be a positive number");
-        self.internal_withdraw(sender_id, amount);
-        self.internal_deposit(receiver_id, amount);
+        self.internal_try_transfer(sender_id, receiver_id, amount, memo)
+            .unwrap_or_else(|err| err.panic())
+    }
+
+    /// Same as [`internal_transfer`](Self::internal_transfer), but returns the error instead of
+    /// panicking, leaving the balances unchanged and emitting no event.
+    pub fn internal_try_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Result<(), FtTransferError> {
+        if sender_id == receiver_id {
+            return Err(FtTransferError::SenderIsReceiver);
+        }
+        if amount == 0 {
+            return Err(FtTransferError::ZeroAmount);
+        }
+        let sender_balance = self
+            .internal_try_balance_of(sender_id)?
+            .checked_sub(amount)
+            .ok_or_else(|| FtTransferError::InsufficientBalance(sender_id.clone()))?;
+        let receiver_balance = self
+            .internal_try_balance_of(receiver_id)?
+            .checked_add(amount)
+            .ok_or_else(|| FtTransferError::BalanceOverflow(receiver_id.clone()))?;
+        self.accounts.insert(sender_id, &sender_balance);
+        self.accounts.insert(receiver_id, &receiver_balance);
         FtTransfer {
             old_owner_id: sender_id,
             new_owner_id: receiver_id,
@@ -146,6 +238,54 @@ impl FungibleToken {
             memo: memo.as_deref(),
         }
         .emit();
+        Ok(())
+    }
+
+    /// Transfers `amount` from `sender_id` to `receiver_id` and calls `ft_on_transfer` on the
+    /// receiver, with unused tokens refunded to `sender_id` by `ft_resolve_transfer`. Used by
+    /// `ft_transfer_call` and by transfers on behalf of the sender.
+    pub fn internal_transfer_call(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.internal_transfer_call_with_gas(
+            sender_id,
+            receiver_id,
+            amount,
+            memo,
+            msg,
+            &FtTransferCallGas::default(),
+        )
+    }
+
+    /// Same as [`internal_transfer_call`](Self::internal_transfer_call), with the gas amounts of
+    /// the receiver call and the callback taken from `gas`. Panics before transferring if the
+    /// prepaid gas doesn't cover them.
+    pub fn internal_transfer_call_with_gas(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        memo: Option<String>,
+        msg: String,
+        gas: &FtTransferCallGas,
+    ) -> PromiseOrValue<U128> {
+        let receiver_gas = gas.receiver_gas();
+        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
+        // Initiating receiver's call and the callback
+        ext_fungible_token_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .ft_on_transfer(sender_id.clone(), amount.into(), msg)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(gas.resolve_transfer)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount.into()),
+            )
+            .into()
     }
 
     pub fn internal_register_account(&mut self, account_id: &AccountId) {
@@ -153,6 +293,17 @@ impl FungibleToken {
             env::panic_str("The account is already registered");
         }
     }
+
+    /// Same as [`internal_register_account`](Self::internal_register_account), calling
+    /// `on_account_registered` of the hook.
+    pub fn internal_register_account_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_id: &AccountId,
+    ) {
+        self.internal_register_account(account_id);
+        hook.on_account_registered(self, account_id);
+    }
 }
 
 impl FungibleTokenCore for FungibleToken {
@@ -171,31 +322,8 @@ impl FungibleTokenCore for FungibleToken {
         msg: String,
     ) -> PromiseOrValue<U128> {
         assert_one_yocto();
-        require!(
-            env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL + GAS_FOR_RESOLVE_TRANSFER,
-            "More gas is required"
-        );
         let sender_id = env::predecessor_account_id();
-        let amount: Balance = amount.into();
-        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
-        // Initiating receiver's call and the callback
-        ext_fungible_token_receiver::ft_on_transfer(
-            sender_id.clone(),
-            amount.into(),
-            msg,
-            receiver_id.clone(),
-            NO_DEPOSIT,
-            env::prepaid_gas() - GAS_FOR_FT_TRANSFER_CALL,
-        )
-        .then(ext_self::ft_resolve_transfer(
-            sender_id,
-            receiver_id,
-            amount.into(),
-            env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_RESOLVE_TRANSFER,
-        ))
-        .into()
+        self.internal_transfer_call(sender_id, receiver_id, amount.into(), memo, msg)
     }
 
     fn ft_total_supply(&self) -> U128 {
@@ -218,20 +346,22 @@ impl FungibleToken {
         amount: U128,
     ) -> (u128, u128) {
         let amount: Balance = amount.into();
+        let unused_amount = unused_amount_of_result(0, amount);
+        self.internal_refund_unused(&mut (), sender_id, receiver_id, amount, unused_amount)
+    }
 
-        // Get the unused amount from the `ft_on_transfer` call result.
-        let unused_amount = match env::promise_result(0) {
-            PromiseResult::NotReady => env::abort(),
-            PromiseResult::Successful(value) => {
-                if let Ok(unused_amount) = near_sdk::serde_json::from_slice::<U128>(&value) {
-                    std::cmp::min(amount, unused_amount.0)
-                } else {
-                    amount
-                }
-            }
-            PromiseResult::Failed => amount,
-        };
-
+    /// Refunds `unused_amount` from `receiver_id` to `sender_id`, up to the balance left to the
+    /// receiver, or burns it if the sender's account was deleted. The hook is called around the
+    /// refund, or after the burn with `on_tokens_burned`.
+    /// Returns (Used token amount, Burned token amount)
+    pub(crate) fn internal_refund_unused<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        unused_amount: Balance,
+    ) -> (u128, u128) {
         if unused_amount > 0 {
             let receiver_balance = self.accounts.get(&receiver_id).unwrap_or(0);
             if receiver_balance > 0 {
@@ -239,13 +369,33 @@ impl FungibleToken {
                 self.accounts.insert(&receiver_id, &(receiver_balance - refund_amount));
 
                 if let Some(sender_balance) = self.accounts.get(sender_id) {
+                    let transfer = FtHookTransfer {
+                        kind: FtTransferKind::Refund,
+                        sender_id: &receiver_id,
+                        receiver_id: sender_id,
+                        amount: refund_amount,
+                    };
+                    hook.before_transfer(self, &transfer);
                     self.accounts.insert(sender_id, &(sender_balance + refund_amount));
-                    log!("Refund {} from {} to {}", refund_amount, receiver_id, sender_id);
+                    FtTransfer {
+                        old_owner_id: &receiver_id,
+                        new_owner_id: sender_id,
+                        amount: &U128(refund_amount),
+                        memo: Some("refund"),
+                    }
+                    .emit();
+                    hook.after_transfer(self, &transfer);
                     return (amount - refund_amount, 0);
                 } else {
                     // Sender's account was deleted, so we need to burn tokens.
                     self.total_supply -= refund_amount;
-                    log!("The account of the sender was deleted");
+                    FtBurn {
+                        owner_id: &receiver_id,
+                        amount: &U128(refund_amount),
+                        memo: Some("refund"),
+                    }
+                    .emit();
+                    hook.on_tokens_burned(self, &receiver_id, refund_amount);
                     return (amount, refund_amount);
                 }
             }
@@ -254,6 +404,22 @@ impl FungibleToken {
     }
 }
 
+/// Returns the amount unused by the `ft_on_transfer` call of the promise result `result_idx`,
+/// which is the whole `amount` if the call failed or returned an invalid value.
+pub(crate) fn unused_amount_of_result(result_idx: u64, amount: Balance) -> Balance {
+    match env::promise_result_bounded(result_idx, MAX_FT_ON_TRANSFER_RESULT_BYTES) {
+        Err(PromiseError::NotReady) => env::abort(),
+        Ok(value) => {
+            if let Ok(unused_amount) = near_sdk::serde_json::from_slice::<U128>(&value) {
+                std::cmp::min(amount, unused_amount.0)
+            } else {
+                amount
+            }
+        }
+        Err(_) => amount,
+    }
+}
+
 impl FungibleTokenResolver for FungibleToken {
     fn ft_resolve_transfer(
         &mut self,
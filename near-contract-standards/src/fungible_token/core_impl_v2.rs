@@ -0,0 +1,256 @@
+use crate::fungible_token::account::{Account, VAccount};
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use crate::storage_management::StorageManagement;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{
+    assert_min_deposit, env, AccountId, Balance, ContractError, FunctionError, IntoStorageKey,
+    Promise, StorageUsage,
+};
+use std::ops::{Deref, DerefMut};
+
+/// [`FungibleToken`] with a versioned record per account, holding the fields of the account
+/// besides its balance, e.g. a frozen flag or a vesting schedule.
+///
+/// Balances, transfers and storage management are those of the inner [`FungibleToken`], which
+/// the token dereferences to, so it can be used with `impl_fungible_token_core!` and
+/// `impl_fungible_token_storage!` in the same way. Records are persisted as [`VAccount`], so
+/// fields can be added to them in later versions without a full state migration. Accounts
+/// without a record have the default [`Account`], and the record is removed when the account is
+/// unregistered.
+///
+/// The storage deposit of an account only covers its balance, so an existing [`FungibleToken`]
+/// is upgraded with [`FungibleTokenV2::upgrade_from`] without changing what its accounts paid.
+/// The storage of a record is paid by the call which writes it first, see
+/// [`internal_set_account`](Self::internal_set_account), and refunded to the account when it
+/// unregisters.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FungibleTokenV2 {
+    pub token: FungibleToken,
+
+    /// AccountID -> Versioned account record.
+    pub records: LookupMap<AccountId, VAccount>,
+
+    /// The storage usage of a record of the longest account ID.
+    pub record_storage_usage: StorageUsage,
+}
+
+impl FungibleTokenV2 {
+    /// Creates the token with its balances stored under `prefix` and the account records under
+    /// `records_prefix`.
+    pub fn new<S, T>(prefix: S, records_prefix: T) -> Self
+    where
+        S: IntoStorageKey,
+        T: IntoStorageKey,
+    {
+        Self::upgrade_from(FungibleToken::new(prefix), records_prefix)
+    }
+
+    /// Upgrades `token` to versioned account records stored under `records_prefix`, which must
+    /// differ from the prefix of `token`.
+    pub fn upgrade_from<S>(token: FungibleToken, records_prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut this =
+            Self { token, records: LookupMap::new(records_prefix), record_storage_usage: 0 };
+        this.measure_record_storage_usage();
+        this
+    }
+
+    fn measure_record_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
+        self.records.insert(&tmp_account_id, &Account::default().into());
+        self.record_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.records.remove(&tmp_account_id);
+    }
+
+    /// Cost of the storage of a record, paid when it is first written.
+    pub fn record_storage_cost(&self) -> Balance {
+        Balance::from(self.record_storage_usage) * env::storage_byte_cost()
+    }
+
+    /// Returns the account record, upgraded to the latest layout, or `None` if the account isn't
+    /// registered.
+    pub fn internal_get_account(&self, account_id: &AccountId) -> Option<Account> {
+        if self.token.accounts.contains_key(account_id) {
+            Some(self.records.get(account_id).map(Account::from).unwrap_or_default())
+        } else {
+            None
+        }
+    }
+
+    /// Same as [`internal_get_account`](Self::internal_get_account), but panics if the account
+    /// isn't registered.
+    pub fn internal_unwrap_account(&self, account_id: &AccountId) -> Account {
+        self.internal_get_account(account_id)
+            .unwrap_or_else(|| ContractError::account_not_registered(account_id).panic())
+    }
+
+    /// Writes the account record in the latest layout. Panics if the account isn't registered.
+    ///
+    /// If the account has no record yet, the attached deposit must cover
+    /// [`record_storage_cost`](Self::record_storage_cost), which is refunded to the account when it
+    /// unregisters. Any deposit above it is left to the caller.
+    pub fn internal_set_account(&mut self, account_id: &AccountId, account: Account) {
+        if !self.token.accounts.contains_key(account_id) {
+            ContractError::account_not_registered(account_id).panic()
+        }
+        if !self.records.contains_key(account_id) {
+            assert_min_deposit(self.record_storage_cost());
+        }
+        self.records.insert(account_id, &account.into());
+    }
+
+    /// Same as [`FungibleToken::internal_storage_unregister`], also removing the record of the
+    /// account.
+    pub fn internal_storage_unregister(
+        &mut self,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        self.internal_storage_unregister_with_hook(&mut (), force)
+    }
+
+    /// Same as [`FungibleToken::internal_storage_unregister_with_hook`], also removing the record
+    /// of the account.
+    pub fn internal_storage_unregister_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let closed = self.internal_storage_close_with_hook(hook, force);
+        if let Some((account_id, _)) = &closed {
+            Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
+        }
+        closed
+    }
+
+    /// Same as [`FungibleToken::internal_storage_close_with_hook`], also removing the record of
+    /// the account and refunding its storage to the account.
+    pub fn internal_storage_close_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let closed = self.token.internal_storage_close_with_hook(hook, force);
+        if let Some((account_id, _)) = &closed {
+            if self.records.remove(account_id).is_some() {
+                Promise::new(account_id.clone()).transfer(self.record_storage_cost());
+            }
+        }
+        closed
+    }
+}
+
+impl Deref for FungibleTokenV2 {
+    type Target = FungibleToken;
+
+    fn deref(&self) -> &Self::Target {
+        &self.token
+    }
+}
+
+impl DerefMut for FungibleTokenV2 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::core::FungibleTokenCore;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn with_deposit(predecessor_id: AccountId, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor_id)
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    fn refunds() -> Vec<(AccountId, Balance)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn upgrade_keeps_balances() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut legacy = FungibleToken::new(b"a".to_vec());
+        legacy.internal_register_account(&accounts(0));
+        legacy.internal_register_account(&accounts(1));
+        legacy.internal_deposit(&accounts(0), 100);
+
+        let mut token = FungibleTokenV2::upgrade_from(legacy, b"b".to_vec());
+        assert_eq!(token.ft_total_supply().0, 100);
+        assert_eq!(token.internal_get_account(&accounts(0)), Some(Account::default()));
+        assert_eq!(token.internal_get_account(&accounts(2)), None);
+
+        token.internal_transfer(&accounts(0), &accounts(1), 40, None);
+        assert_eq!(token.ft_balance_of(accounts(1)).0, 40);
+        assert!(!token.records.contains_key(&accounts(0)));
+    }
+
+    #[test]
+    fn unregister_refunds_the_paid_deposit() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut legacy = FungibleToken::new(b"a".to_vec());
+        let legacy_min = legacy.storage_balance_bounds().min.0;
+        legacy.internal_register_account(&accounts(0));
+        let mut token = FungibleTokenV2::upgrade_from(legacy, b"b".to_vec());
+        assert_eq!(token.storage_balance_bounds().min.0, legacy_min);
+
+        with_deposit(accounts(0), 1);
+        assert!(token.internal_storage_unregister(None).is_some());
+        assert_eq!(refunds(), vec![(accounts(0), legacy_min + 1)]);
+    }
+
+    #[test]
+    fn unregister_refunds_the_record() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleTokenV2::new(b"a".to_vec(), b"b".to_vec());
+        let min = token.storage_balance_bounds().min.0;
+        let record_cost = token.record_storage_cost();
+        assert!(record_cost > 0);
+        token.internal_register_account(&accounts(0));
+        with_deposit(accounts(1), record_cost);
+        token.internal_set_account(&accounts(0), Account::default());
+        assert_eq!(token.storage_balance_of(accounts(0)).unwrap().total.0, min + record_cost);
+
+        with_deposit(accounts(0), 1);
+        assert!(token.internal_storage_unregister(None).is_some());
+        assert!(!token.records.contains_key(&accounts(0)));
+        assert!(token.storage_balance_of(accounts(0)).is_none());
+        assert_eq!(refunds(), vec![(accounts(0), record_cost), (accounts(0), min + 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of at least")]
+    fn set_account_without_record_deposit() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleTokenV2::new(b"a".to_vec(), b"b".to_vec());
+        token.internal_register_account(&accounts(0));
+        token.internal_set_account(&accounts(0), Account::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "The account charlie is not registered")]
+    fn set_unregistered_account() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleTokenV2::new(b"a".to_vec(), b"b".to_vec());
+        token.internal_set_account(&accounts(2), Account::default());
+    }
+}
@@ -0,0 +1,503 @@
+use crate::fungible_token::core::FungibleTokenCore;
+use crate::fungible_token::core_impl::{ext_fungible_token_receiver, ext_self};
+use crate::fungible_token::events::{FtBurn, FtTransfer};
+use crate::fungible_token::gas::FtTransferCallGas;
+use crate::fungible_token::hook::{FtHookTransfer, FtTransferKind, FungibleTokenTransferHook};
+use crate::fungible_token::resolver::FungibleTokenResolver;
+use crate::fungible_token::FungibleToken;
+use crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, require, AccountId, Balance, IntoStorageKey, PromiseOrValue,
+};
+
+/// Denominator of [`DeflationaryToken::burn_bps`].
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Memo of the `ft_burn` events of the burns on transfers.
+pub const TRANSFER_BURN_MEMO: &str = "transfer burn";
+
+/// Management of the burn rate of a [`DeflationaryToken`], exposed with
+/// [`impl_deflationary_token`](crate::impl_deflationary_token).
+pub trait DeflationaryTokenManagement {
+    /// Returns the share of each transfer which is burned, in basis points.
+    fn ft_burn_bps(&self) -> u16;
+
+    /// Returns the amount burned by transfers so far.
+    fn ft_total_burned(&self) -> U128;
+
+    /// Sets the share of each transfer which is burned, in basis points, below 10000. Can only be
+    /// called by the owner of the token, with exactly 1 yoctoNEAR attached.
+    fn ft_set_burn_bps(&mut self, burn_bps: u16);
+}
+
+/// Fungible token which burns `burn_bps` basis points of every transfer, so that the total supply
+/// decreases with use.
+///
+/// The sender is debited the full amount and the receiver credited the amount minus the burn,
+/// logged as an `ft_transfer` event of the credited amount followed by an `ft_burn` event of the
+/// sender with the [`TRANSFER_BURN_MEMO`] memo. For `ft_transfer_call`, the receiver is notified
+/// of the credited amount, and refunds are returned to the sender without being burned again.
+///
+/// Transfers call the [`FungibleTokenTransferHook`] passed to
+/// [`internal_transfer_with_hook`](Self::internal_transfer_with_hook) around the change of the
+/// balances with the full amount, so the token composes with [`Pause`](crate::pause::Pause),
+/// [`TransferLimits`](crate::fungible_token::TransferLimits), the
+/// [`Hooks`](crate::hooks::Hooks) registry and custom hooks. It implements the NEP-141 and NEP-145
+/// traits, and is exposed with [`impl_deflationary_token`](crate::impl_deflationary_token) and
+/// `impl_fungible_token_storage!`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DeflationaryToken {
+    pub token: FungibleToken,
+    pub owner_id: AccountId,
+    pub burn_bps: u16,
+    pub total_burned: Balance,
+}
+
+impl DeflationaryToken {
+    pub fn new<S>(prefix: S, owner_id: AccountId, burn_bps: u16) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        assert_valid_bps(burn_bps);
+        Self { token: FungibleToken::new(prefix), owner_id, burn_bps, total_burned: 0 }
+    }
+
+    /// Sets the burn rate. Panics if the predecessor isn't the owner.
+    pub fn set_burn_bps(&mut self, burn_bps: u16) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+        assert_valid_bps(burn_bps);
+        self.burn_bps = burn_bps;
+    }
+
+    /// Part of a transfer of `amount` which is burned, rounded down.
+    pub fn burn_amount(&self, amount: Balance) -> Balance {
+        let bps = Balance::from(self.burn_bps);
+        // Split the amount to avoid overflowing for amounts close to `u128::MAX`.
+        let denominator = Balance::from(BPS_DENOMINATOR);
+        amount / denominator * bps + amount % denominator * bps / denominator
+    }
+
+    /// Transfers `amount` from the sender, burning [`burn_amount`](Self::burn_amount) of it.
+    /// Returns the amount credited to the receiver.
+    pub fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        self.internal_transfer_with_hook(
+            &mut (),
+            FtTransferKind::Transfer,
+            sender_id,
+            receiver_id,
+            amount,
+            memo,
+        )
+    }
+
+    /// Same as [`internal_transfer`](Self::internal_transfer), calling the hook around the
+    /// transfer with the `kind` of the transfer.
+    pub fn internal_transfer_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        kind: FtTransferKind,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        require!(sender_id != receiver_id, "Sender and receiver should be different");
+        require!(amount > 0, "The amount should be a positive number");
+        let transfer = FtHookTransfer { kind, sender_id, receiver_id, amount };
+        hook.before_transfer(&mut self.token, &transfer);
+        let burned = self.burn_amount(amount);
+        let credited = amount - burned;
+        self.token.internal_withdraw(sender_id, amount);
+        self.token.internal_deposit(receiver_id, credited);
+        self.total_burned += burned;
+        FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &U128(credited),
+            memo: memo.as_deref(),
+        }
+        .emit();
+        if burned > 0 {
+            FtBurn { owner_id: sender_id, amount: &U128(burned), memo: Some(TRANSFER_BURN_MEMO) }
+                .emit();
+        }
+        hook.after_transfer(&mut self.token, &transfer);
+        credited
+    }
+
+    /// Transfers `amount` from the sender like
+    /// [`internal_transfer_with_hook`](Self::internal_transfer_with_hook) and calls
+    /// `ft_on_transfer` on the receiver with the credited amount, then `ft_resolve_transfer`,
+    /// with the gas amounts taken from `gas`. Panics before transferring if the prepaid gas
+    /// doesn't cover them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn internal_transfer_call_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        memo: Option<String>,
+        msg: String,
+        gas: &FtTransferCallGas,
+    ) -> PromiseOrValue<U128> {
+        let receiver_gas = gas.receiver_gas();
+        let credited = self.internal_transfer_with_hook(
+            hook,
+            FtTransferKind::TransferCall,
+            &sender_id,
+            &receiver_id,
+            amount,
+            memo,
+        );
+        ext_fungible_token_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .ft_on_transfer(sender_id.clone(), credited.into(), msg)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(gas.resolve_transfer)
+                    .ft_resolve_transfer(sender_id, receiver_id, credited.into()),
+            )
+            .into()
+    }
+
+    /// Removes the account of the predecessor, see
+    /// [`FungibleToken::internal_storage_unregister`].
+    pub fn internal_storage_unregister(
+        &mut self,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        self.token.internal_storage_unregister(force)
+    }
+
+    /// Resolves `ft_transfer_call`, see [`FungibleToken::internal_ft_resolve_transfer`].
+    pub fn internal_ft_resolve_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> (u128, u128) {
+        self.token.internal_ft_resolve_transfer(sender_id, receiver_id, amount)
+    }
+
+    /// Same as [`internal_ft_resolve_transfer`](Self::internal_ft_resolve_transfer), calling the
+    /// hook around the refund. Refunds aren't burned again.
+    pub fn internal_ft_resolve_transfer_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> (u128, u128) {
+        self.token.internal_ft_resolve_transfer_with_hook(hook, sender_id, receiver_id, amount)
+    }
+}
+
+/// Burning the whole amount would leave nothing to credit, so the rate must stay below 100%.
+fn assert_valid_bps(burn_bps: u16) {
+    require!(burn_bps < BPS_DENOMINATOR, "The burn rate must be below 10000 basis points");
+}
+
+impl FungibleTokenCore for DeflationaryToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, amount.0, memo);
+    }
+
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_call_with_hook(
+            &mut (),
+            sender_id,
+            receiver_id,
+            amount.0,
+            memo,
+            msg,
+            &FtTransferCallGas::default(),
+        )
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.token.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.token.ft_balance_of(account_id)
+    }
+}
+
+impl FungibleTokenResolver for DeflationaryToken {
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        self.token.ft_resolve_transfer(sender_id, receiver_id, amount)
+    }
+}
+
+impl StorageManagement for DeflationaryToken {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.token.storage_deposit(account_id, registration_only)
+    }
+
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.token.storage_withdraw(amount)
+    }
+
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.token.storage_unregister(force)
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.token.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.token.storage_balance_of(account_id)
+    }
+}
+
+/// Exposes the NEP-141 core methods and the [`DeflationaryTokenManagement`] methods of a
+/// [`DeflationaryToken`] field, where `ft_set_burn_bps` requires a deposit of exactly 1
+/// yoctoNEAR. The storage management is exposed with `impl_fungible_token_storage!`.
+/// Takes name of the Contract struct, the inner field for the token and optionally
+/// `hook = <field>` to call the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) of the
+/// contract around the transfers and refunds.
+#[macro_export]
+macro_rules! impl_deflationary_token {
+    ($contract: ident, $token: ident $(, hook = $hook: ident)?) => {
+        use $crate::fungible_token::core::FungibleTokenCore;
+        use $crate::fungible_token::deflationary::DeflationaryTokenManagement;
+        use $crate::fungible_token::resolver::FungibleTokenResolver;
+
+        #[near_bindgen]
+        impl FungibleTokenCore for $contract {
+            #[payable]
+            fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+                near_sdk::assert_one_yocto();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_with_hook(
+                    hook,
+                    $crate::fungible_token::hook::FtTransferKind::Transfer,
+                    &near_sdk::env::predecessor_account_id(),
+                    &receiver_id,
+                    amount.0,
+                    memo,
+                );
+            }
+
+            #[payable]
+            fn ft_transfer_call(
+                &mut self,
+                receiver_id: AccountId,
+                amount: U128,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<U128> {
+                near_sdk::assert_one_yocto();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_call_with_hook(
+                    hook,
+                    near_sdk::env::predecessor_account_id(),
+                    receiver_id,
+                    amount.0,
+                    memo,
+                    msg,
+                    &$crate::fungible_token::gas::FtTransferCallGas::default(),
+                )
+            }
+
+            fn ft_total_supply(&self) -> U128 {
+                self.$token.ft_total_supply()
+            }
+
+            fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+                self.$token.ft_balance_of(account_id)
+            }
+        }
+
+        #[near_bindgen]
+        impl FungibleTokenResolver for $contract {
+            #[private]
+            fn ft_resolve_transfer(
+                &mut self,
+                sender_id: AccountId,
+                receiver_id: AccountId,
+                amount: U128,
+            ) -> U128 {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token
+                    .internal_ft_resolve_transfer_with_hook(hook, &sender_id, receiver_id, amount)
+                    .0
+                    .into()
+            }
+        }
+
+        #[near_bindgen]
+        impl DeflationaryTokenManagement for $contract {
+            fn ft_burn_bps(&self) -> u16 {
+                self.$token.burn_bps
+            }
+
+            fn ft_total_burned(&self) -> near_sdk::json_types::U128 {
+                self.$token.total_burned.into()
+            }
+
+            #[payable]
+            fn ft_set_burn_bps(&mut self, burn_bps: u16) {
+                near_sdk::assert_one_yocto();
+                self.$token.set_burn_bps(burn_bps)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::Hooks;
+    use crate::pause::Pause;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        token: DeflationaryToken,
+    }
+
+    impl_deflationary_token!(Contract, token);
+
+    fn setup(burn_bps: u16) -> DeflationaryToken {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut token = DeflationaryToken::new(b"t", accounts(0), burn_bps);
+        token.token.internal_register_account(&accounts(1));
+        token.token.internal_register_account(&accounts(2));
+        token.token.internal_deposit(&accounts(1), 1_000);
+        token
+    }
+
+    #[test]
+    fn burn_on_transfer() {
+        let mut token = setup(250);
+        assert_eq!(token.internal_transfer(&accounts(1), &accounts(2), 100, None), 98);
+        assert_eq!(token.token.internal_unwrap_balance_of(&accounts(1)), 900);
+        assert_eq!(token.token.internal_unwrap_balance_of(&accounts(2)), 98);
+        assert_eq!(token.ft_total_supply(), U128(998));
+        assert_eq!(token.total_burned, 2);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"charlie","amount":"98"}]}"#,
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"bob","amount":"2","memo":"transfer burn"}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn burn_amount_rounds_down() {
+        let token = setup(333);
+        assert_eq!(token.burn_amount(29), 0);
+        assert_eq!(token.burn_amount(10_000), 333);
+        assert_eq!(token.burn_amount(u128::MAX), u128::MAX / 10_000 * 333 + 1_455 * 333 / 10_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn transfer_hooks() {
+        let mut token = setup(100);
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        let mut hooks = Hooks::new();
+        hooks.subscribe("pause", 0, None, &mut pause);
+        token.internal_transfer_with_hook(
+            &mut hooks,
+            FtTransferKind::Transfer,
+            &accounts(1),
+            &accounts(2),
+            100,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The burn rate must be below 10000 basis points")]
+    fn burn_rate_below_total() {
+        let mut token = setup(100);
+        token.set_burn_bps(BPS_DENOMINATOR);
+    }
+
+    #[test]
+    #[should_panic(expected = "More gas is required")]
+    fn transfer_call_requires_gas() {
+        let mut token = setup(100);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .prepaid_gas(FtTransferCallGas::default().required_prepaid_gas() - near_sdk::Gas(1))
+            .build());
+        token.internal_transfer_call_with_hook(
+            &mut (),
+            accounts(1),
+            accounts(2),
+            100,
+            None,
+            String::new(),
+            &FtTransferCallGas::default(),
+        );
+    }
+
+    #[test]
+    fn set_burn_bps_with_yocto() {
+        let mut contract = Contract { token: setup(100) };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.ft_set_burn_bps(200);
+        assert_eq!(contract.ft_burn_bps(), 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_burn_bps_without_yocto() {
+        let mut contract = Contract { token: setup(100) };
+        contract.ft_set_burn_bps(200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_sets_burn_rate() {
+        let mut token = setup(100);
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        token.set_burn_bps(200);
+    }
+}
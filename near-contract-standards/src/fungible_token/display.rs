@@ -0,0 +1,84 @@
+use crate::fungible_token::TokenAmount;
+use near_sdk::{AccountId, Balance};
+
+/// Views returning token amounts as decimal strings with the `decimals` of the token applied,
+/// e.g. `"12.345"`, for frontends which can't do `u128` arithmetic. Exposed with
+/// [`impl_fungible_token_display`](crate::impl_fungible_token_display).
+///
+/// Without `precision`, amounts are formatted with all their significant fractional digits.
+/// With `precision`, they are rounded down to exactly that many fractional digits, capped at
+/// the `decimals` of the token, so that a displayed balance is never more than the actual one.
+pub trait FungibleTokenDisplay {
+    /// Returns the balance of the account as a decimal string, `"0"` if the account doesn't exist.
+    fn ft_balance_of_display(&self, account_id: AccountId, precision: Option<u8>) -> String;
+
+    /// Returns the total supply of the token as a decimal string.
+    fn ft_total_supply_display(&self, precision: Option<u8>) -> String;
+}
+
+/// Formats an amount in the smallest units of a token with `decimals` decimal places as returned
+/// by the [`FungibleTokenDisplay`] views.
+pub fn format_amount(amount: Balance, decimals: u8, precision: Option<u8>) -> String {
+    let amount = TokenAmount::new(amount, decimals);
+    match precision {
+        Some(precision) => amount.to_human_with_precision(precision),
+        None => amount.to_human(),
+    }
+}
+
+/// Exposes the [`FungibleTokenDisplay`] views for the token of the contract, with the `decimals`
+/// of its `ft_metadata`.
+/// Takes name of the Contract struct and the inner field for the token. The contract must
+/// implement [`FungibleTokenMetadataProvider`](crate::fungible_token::metadata::FungibleTokenMetadataProvider).
+#[macro_export]
+macro_rules! impl_fungible_token_display {
+    ($contract: ident, $token: ident) => {
+        use $crate::fungible_token::display::FungibleTokenDisplay;
+
+        #[near_bindgen]
+        impl FungibleTokenDisplay for $contract {
+            fn ft_balance_of_display(
+                &self,
+                account_id: AccountId,
+                precision: Option<u8>,
+            ) -> String {
+                let balance = $crate::fungible_token::core::FungibleTokenCore::ft_balance_of(
+                    &self.$token,
+                    account_id,
+                );
+                $crate::fungible_token::display::format_amount(
+                    balance.0,
+                    $crate::fungible_token::metadata::FungibleTokenMetadataProvider::ft_metadata(
+                        self,
+                    )
+                    .decimals,
+                    precision,
+                )
+            }
+
+            fn ft_total_supply_display(&self, precision: Option<u8>) -> String {
+                $crate::fungible_token::display::format_amount(
+                    self.$token.total_supply,
+                    $crate::fungible_token::metadata::FungibleTokenMetadataProvider::ft_metadata(
+                        self,
+                    )
+                    .decimals,
+                    precision,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_with_precision() {
+        assert_eq!(format_amount(12_345_678, 6, None), "12.345678");
+        assert_eq!(format_amount(12_345_678, 6, Some(3)), "12.345");
+        assert_eq!(format_amount(12_000_000, 6, None), "12");
+        assert_eq!(format_amount(0, 24, Some(2)), "0.00");
+    }
+}
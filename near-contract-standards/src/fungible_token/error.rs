@@ -0,0 +1,132 @@
+use near_sdk::{AccountId, ContractError, FunctionError};
+use std::fmt;
+
+/// Error of a balance change of a [`FungibleToken`](crate::fungible_token::FungibleToken),
+/// returned by its `internal_try_*` methods. The panicking methods panic with the error as a
+/// [`ContractError`], whose `code` is given by [`code`](Self::code).
+///
+/// Contracts can return it from methods with `#[return_result]`:
+///
+/// ```ignore
+/// #[payable]
+/// #[return_result]
+/// pub fn transfer(&mut self, receiver_id: AccountId, amount: U128) -> Result<(), FtTransferError> {
+///     near_sdk::assert_one_yocto();
+///     let sender_id = env::predecessor_account_id();
+///     self.token.internal_try_transfer(&sender_id, &receiver_id, amount.0, None)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FtTransferError {
+    /// The sender and the receiver of a transfer are the same account.
+    SenderIsReceiver,
+    /// The amount of a transfer is zero.
+    ZeroAmount,
+    AccountNotRegistered(AccountId),
+    /// The balance of the account is lower than the amount.
+    InsufficientBalance(AccountId),
+    /// The balance of the account would exceed `u128::MAX`.
+    BalanceOverflow(AccountId),
+    /// The total supply would exceed `u128::MAX`.
+    TotalSupplyOverflow,
+}
+
+impl FtTransferError {
+    /// Returns the machine readable kind of the error, e.g. `"INSUFFICIENT_BALANCE"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::SenderIsReceiver => "SENDER_IS_RECEIVER",
+            Self::ZeroAmount => "ZERO_AMOUNT",
+            Self::AccountNotRegistered(_) => ContractError::ACCOUNT_NOT_REGISTERED,
+            Self::InsufficientBalance(_) => "INSUFFICIENT_BALANCE",
+            Self::BalanceOverflow(_) => "BALANCE_OVERFLOW",
+            Self::TotalSupplyOverflow => "TOTAL_SUPPLY_OVERFLOW",
+        }
+    }
+}
+
+impl fmt::Display for FtTransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SenderIsReceiver => write!(f, "Sender and receiver should be different"),
+            Self::ZeroAmount => write!(f, "The amount should be a positive number"),
+            Self::AccountNotRegistered(account_id) => {
+                write!(f, "The account {} is not registered", account_id)
+            }
+            Self::InsufficientBalance(_) => write!(f, "The account doesn't have enough balance"),
+            Self::BalanceOverflow(_) => write!(f, "Balance overflow"),
+            Self::TotalSupplyOverflow => write!(f, "Total supply overflow"),
+        }
+    }
+}
+
+impl std::error::Error for FtTransferError {}
+
+impl From<FtTransferError> for ContractError {
+    fn from(error: FtTransferError) -> Self {
+        ContractError::new(error.code(), error.to_string())
+    }
+}
+
+impl FunctionError for FtTransferError {
+    fn panic(&self) -> ! {
+        ContractError::new(self.code(), self.to_string()).panic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::FungibleToken;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn try_transfer() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(0), 100);
+        assert_eq!(
+            token.internal_try_transfer(&accounts(0), &accounts(1), 101, None),
+            Err(FtTransferError::InsufficientBalance(accounts(0)))
+        );
+        assert_eq!(
+            token.internal_try_transfer(&accounts(0), &accounts(2), 10, None),
+            Err(FtTransferError::AccountNotRegistered(accounts(2)))
+        );
+        assert_eq!(
+            token.internal_try_transfer(&accounts(0), &accounts(0), 10, None),
+            Err(FtTransferError::SenderIsReceiver)
+        );
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 100);
+        assert!(get_logs().is_empty());
+
+        assert_eq!(token.internal_try_transfer(&accounts(0), &accounts(1), 10, None), Ok(()));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 10);
+        assert_eq!(token.total_supply, 100);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = r#"ERROR_JSON:{"code":"ZERO_AMOUNT","message":"The amount should be a positive number"}"#
+    )]
+    fn transfer_panics_with_contract_error() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_transfer(&accounts(0), &accounts(1), 0, None);
+    }
+
+    #[test]
+    fn contract_error() {
+        let error: ContractError = FtTransferError::InsufficientBalance(accounts(0)).into();
+        assert_eq!(
+            error,
+            ContractError::new("INSUFFICIENT_BALANCE", "The account doesn't have enough balance")
+        );
+        let error: ContractError = FtTransferError::AccountNotRegistered(accounts(1)).into();
+        assert_eq!(error, ContractError::account_not_registered(&accounts(1)));
+    }
+}
@@ -12,15 +12,50 @@
 //! These events can be logged by calling `.emit()` on them if a single event, or calling
 //! [`FtMint::emit_many`], [`FtTransfer::emit_many`],
 //! or [`FtBurn::emit_many`] respectively.
+//! [`FungibleToken`](crate::fungible_token::FungibleToken) emits them from `internal_transfer`,
+//! `internal_mint`, `internal_burn` and `ft_resolve_transfer`, whose refunds are logged as
+//! transfers back to the sender, or burns when the sender closed its account, with the memo
+//! `"refund"`. Contracts can emit events of their own standards in the same format with
+//! [`Nep297Event`](crate::event::Nep297Event).
+//!
+//! Contracts which emit a high volume of events can instead use the compact Borsh encoding with
+//! [`FtMint::emit_borsh`] or [`FtMint::emit_borsh_many`] (and their equivalents on the other
+//! events). These are logged as `EVENT_BORSH:` followed by the base64 encoding of the Borsh
+//! serialization of the following envelope:
+//!
+//! ```ignore
+//! struct Event<T> {
+//!     standard: String, // "nep141"
+//!     version: String,  // "1.0.0"
+//!     event: String,    // "ft_mint", "ft_transfer" or "ft_burn"
+//!     data: Vec<T>,
+//! }
+//! ```
+//!
+//! where `T` is the event type with its fields serialized in declaration order, with
+//! `amount` as a `u128` and `memo` as an `Option<String>`.
+//!
+//! Contracts which emit thousands of events about the same accounts, e.g. exchanges, can refer to
+//! the accounts by their [`AccountIndex`] with [`FtTransfer::emit_indexed`] (and its equivalents
+//! on the other events). These are logged as events of the separate `nep141_indexed` standard,
+//! so that NEP-141 indexers don't misparse them, whose account ids are replaced by `owner_idx`,
+//! `old_owner_idx` and `new_owner_idx`, after the `account_index_register` events of the accounts
+//! indexed for them:
+//!
+//! ```text
+//! EVENT_JSON:{"standard":"nep141_indexed","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_idx":0,"new_owner_idx":1,"amount":"100"}]}
+//! ```
 
-use crate::event::NearEvent;
+use crate::account_index::AccountIndex;
+use crate::event::{BorshEvent, NearEvent};
+use near_sdk::borsh::{self, BorshSerialize};
 use near_sdk::json_types::U128;
 use near_sdk::AccountId;
 use serde::Serialize;
 
 /// Data to log for an FT mint event. To log this event, call [`.emit()`](FtMint::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct FtMint<'a> {
     pub owner_id: &'a AccountId,
     pub amount: &'a U128,
@@ -40,12 +75,44 @@ impl FtMint<'_> {
     pub fn emit_many(data: &[FtMint<'_>]) {
         new_141_v1(Nep141EventKind::FtMint(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an FT mint event in the compact Borsh encoding, where each [`FtMint`] represents
+    /// the data of each mint.
+    pub fn emit_borsh_many(data: &[FtMint<'_>]) {
+        new_141_borsh_v1("ft_mint", data).emit()
+    }
+
+    /// Logs the event to the host as a `nep141_indexed` event, referring to the accounts by
+    /// their index, as described in the [module documentation](self).
+    pub fn emit_indexed(self, index: &AccountIndex) {
+        Self::emit_indexed_many(&[self], index)
+    }
+
+    /// Emits a `nep141_indexed` `ft_mint` event, where each [`FtMint`] represents the data of
+    /// each mint, indexing the accounts which weren't indexed yet.
+    pub fn emit_indexed_many(data: &[FtMint<'_>], index: &AccountIndex) {
+        let data: Vec<_> = data
+            .iter()
+            .map(|event| IndexedFtMint {
+                owner_idx: index.index_of(event.owner_id),
+                amount: event.amount,
+                memo: event.memo,
+            })
+            .collect();
+        new_141_indexed_v1(Nep141IndexedEventKind::FtMint(&data)).emit()
+    }
 }
 
 /// Data to log for an FT transfer event. To log this event,
 /// call [`.emit()`](FtTransfer::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct FtTransfer<'a> {
     pub old_owner_id: &'a AccountId,
     pub new_owner_id: &'a AccountId,
@@ -66,11 +133,44 @@ impl FtTransfer<'_> {
     pub fn emit_many(data: &[FtTransfer<'_>]) {
         new_141_v1(Nep141EventKind::FtTransfer(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an FT transfer event in the compact Borsh encoding, where each [`FtTransfer`] represents
+    /// the data of each transfer.
+    pub fn emit_borsh_many(data: &[FtTransfer<'_>]) {
+        new_141_borsh_v1("ft_transfer", data).emit()
+    }
+
+    /// Logs the event to the host as a `nep141_indexed` event, referring to the accounts by
+    /// their index, as described in the [module documentation](self).
+    pub fn emit_indexed(self, index: &AccountIndex) {
+        Self::emit_indexed_many(&[self], index)
+    }
+
+    /// Emits a `nep141_indexed` `ft_transfer` event, where each [`FtTransfer`] represents the
+    /// data of each transfer, indexing the accounts which weren't indexed yet.
+    pub fn emit_indexed_many(data: &[FtTransfer<'_>], index: &AccountIndex) {
+        let data: Vec<_> = data
+            .iter()
+            .map(|event| IndexedFtTransfer {
+                old_owner_idx: index.index_of(event.old_owner_id),
+                new_owner_idx: index.index_of(event.new_owner_id),
+                amount: event.amount,
+                memo: event.memo,
+            })
+            .collect();
+        new_141_indexed_v1(Nep141IndexedEventKind::FtTransfer(&data)).emit()
+    }
 }
 
 /// Data to log for an FT burn event. To log this event, call [`.emit()`](FtBurn::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct FtBurn<'a> {
     pub owner_id: &'a AccountId,
     pub amount: &'a U128,
@@ -90,6 +190,63 @@ impl FtBurn<'_> {
     pub fn emit_many<'a>(data: &'a [FtBurn<'a>]) {
         new_141_v1(Nep141EventKind::FtBurn(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an FT burn event in the compact Borsh encoding, where each [`FtBurn`] represents
+    /// the data of each burn.
+    pub fn emit_borsh_many<'a>(data: &'a [FtBurn<'a>]) {
+        new_141_borsh_v1("ft_burn", data).emit()
+    }
+
+    /// Logs the event to the host as a `nep141_indexed` event, referring to the accounts by
+    /// their index, as described in the [module documentation](self).
+    pub fn emit_indexed(self, index: &AccountIndex) {
+        Self::emit_indexed_many(&[self], index)
+    }
+
+    /// Emits a `nep141_indexed` `ft_burn` event, where each [`FtBurn`] represents the data of
+    /// each burn, indexing the accounts which weren't indexed yet.
+    pub fn emit_indexed_many(data: &[FtBurn<'_>], index: &AccountIndex) {
+        let data: Vec<_> = data
+            .iter()
+            .map(|event| IndexedFtBurn {
+                owner_idx: index.index_of(event.owner_id),
+                amount: event.amount,
+                memo: event.memo,
+            })
+            .collect();
+        new_141_indexed_v1(Nep141IndexedEventKind::FtBurn(&data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct IndexedFtMint<'a> {
+    owner_idx: u32,
+    amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+struct IndexedFtTransfer<'a> {
+    old_owner_idx: u32,
+    new_owner_idx: u32,
+    amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+struct IndexedFtBurn<'a> {
+    owner_idx: u32,
+    amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
 }
 
 #[derive(Serialize, Debug)]
@@ -109,6 +266,24 @@ enum Nep141EventKind<'a> {
     FtBurn(&'a [FtBurn<'a>]),
 }
 
+/// Events of [`emit_indexed`](FtTransfer::emit_indexed), under their own standard name.
+#[derive(Serialize, Debug)]
+pub(crate) struct Nep141IndexedEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: Nep141IndexedEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Nep141IndexedEventKind<'a> {
+    FtMint(&'a [IndexedFtMint<'a>]),
+    FtTransfer(&'a [IndexedFtTransfer<'a>]),
+    FtBurn(&'a [IndexedFtBurn<'a>]),
+}
+
 fn new_141<'a>(version: &'static str, event_kind: Nep141EventKind<'a>) -> NearEvent<'a> {
     NearEvent::Nep141(Nep141Event { version, event_kind })
 }
@@ -117,10 +292,22 @@ fn new_141_v1(event_kind: Nep141EventKind) -> NearEvent {
     new_141("1.0.0", event_kind)
 }
 
+fn new_141_indexed_v1(event_kind: Nep141IndexedEventKind) -> NearEvent {
+    NearEvent::Nep141Indexed(Nep141IndexedEvent { version: "1.0.0", event_kind })
+}
+
+fn new_141_borsh_v1<'a, T: BorshSerialize>(
+    event: &'static str,
+    data: &'a [T],
+) -> BorshEvent<'a, T> {
+    BorshEvent { standard: "nep141", version: "1.0.0", event, data }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use near_sdk::{test_utils, AccountId};
+    use near_sdk::borsh::BorshDeserialize;
+    use near_sdk::{base64, test_utils, AccountId};
 
     fn bob() -> AccountId {
         AccountId::new_unchecked("bob".to_string())
@@ -212,4 +399,82 @@ mod tests {
             r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"200","memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","amount":"100"}]}"#
         );
     }
+
+    #[test]
+    fn ft_transfers_indexed() {
+        let index = AccountIndex::default();
+        index.index_of(&alice());
+        FtTransfer::emit_indexed_many(
+            &[
+                FtTransfer {
+                    old_owner_id: &alice(),
+                    new_owner_id: &bob(),
+                    amount: &U128(200),
+                    memo: Some("has memo"),
+                },
+                FtTransfer {
+                    old_owner_id: &bob(),
+                    new_owner_id: &alice(),
+                    amount: &U128(100),
+                    memo: None,
+                },
+            ],
+            &index,
+        );
+        let logs = test_utils::get_logs();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(
+            logs[1],
+            r#"EVENT_JSON:{"standard":"account_index","version":"1.0.0","event":"account_index_register","data":[{"account_id":"bob","index":1}]}"#
+        );
+        assert_eq!(
+            logs[2],
+            r#"EVENT_JSON:{"standard":"nep141_indexed","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_idx":0,"new_owner_idx":1,"amount":"200","memo":"has memo"},{"old_owner_idx":1,"new_owner_idx":0,"amount":"100"}]}"#
+        );
+    }
+
+    #[derive(BorshDeserialize, Debug, PartialEq)]
+    struct DecodedEvent<T> {
+        standard: String,
+        version: String,
+        event: String,
+        data: Vec<T>,
+    }
+
+    #[derive(BorshDeserialize, Debug, PartialEq)]
+    struct DecodedTransfer {
+        old_owner_id: AccountId,
+        new_owner_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    }
+
+    #[test]
+    fn ft_transfers_borsh() {
+        FtTransfer {
+            old_owner_id: &alice(),
+            new_owner_id: &bob(),
+            amount: &U128(200),
+            memo: Some("has memo"),
+        }
+        .emit_borsh();
+        let log = test_utils::get_logs().pop().unwrap();
+        let encoded = log.strip_prefix("EVENT_BORSH:").unwrap();
+        let decoded: DecodedEvent<DecodedTransfer> =
+            BorshDeserialize::try_from_slice(&base64::decode(encoded).unwrap()).unwrap();
+        assert_eq!(
+            decoded,
+            DecodedEvent {
+                standard: "nep141".to_string(),
+                version: "1.0.0".to_string(),
+                event: "ft_transfer".to_string(),
+                data: vec![DecodedTransfer {
+                    old_owner_id: alice(),
+                    new_owner_id: bob(),
+                    amount: 200,
+                    memo: Some("has memo".to_string()),
+                }],
+            }
+        );
+    }
 }
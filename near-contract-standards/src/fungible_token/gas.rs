@@ -0,0 +1,102 @@
+use crate::fungible_token::core_impl::{GAS_FOR_FT_TRANSFER_CALL, GAS_FOR_RESOLVE_TRANSFER};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, Gas};
+
+/// Gas amounts of `ft_transfer_call`, for receivers whose `ft_on_transfer` needs more gas than
+/// the defaults leave them, or tokens whose resolve callback does more work.
+///
+/// The config is a field of the contract passed to
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core) with `gas = <field>`, which
+/// calls [`FungibleToken::internal_transfer_call_with_gas`](crate::fungible_token::FungibleToken::internal_transfer_call_with_gas).
+/// The default config behaves like `ft_transfer_call` without it.
+///
+/// ```
+/// use near_contract_standards::fungible_token::gas::FtTransferCallGas;
+/// use near_sdk::Gas;
+///
+/// let gas = FtTransferCallGas::default().with_min_receiver_gas(Gas(50_000_000_000_000));
+/// assert_eq!(gas.required_prepaid_gas(), Gas(80_000_000_000_000));
+/// ```
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransferCallGas {
+    /// Gas used by `ft_transfer_call` itself, kept from the prepaid gas.
+    pub transfer_call: Gas,
+    /// Gas attached to the `ft_resolve_transfer` callback.
+    pub resolve_transfer: Gas,
+    /// Minimum gas of the `ft_on_transfer` call of the receiver, which gets all the remaining
+    /// prepaid gas.
+    pub min_receiver: Gas,
+}
+
+impl Default for FtTransferCallGas {
+    fn default() -> Self {
+        Self {
+            transfer_call: Gas(GAS_FOR_FT_TRANSFER_CALL.0 - GAS_FOR_RESOLVE_TRANSFER.0),
+            resolve_transfer: GAS_FOR_RESOLVE_TRANSFER,
+            min_receiver: GAS_FOR_RESOLVE_TRANSFER,
+        }
+    }
+}
+
+impl FtTransferCallGas {
+    pub fn with_transfer_call_gas(mut self, gas: Gas) -> Self {
+        self.transfer_call = gas;
+        self
+    }
+
+    pub fn with_resolve_transfer_gas(mut self, gas: Gas) -> Self {
+        self.resolve_transfer = gas;
+        self
+    }
+
+    pub fn with_min_receiver_gas(mut self, gas: Gas) -> Self {
+        self.min_receiver = gas;
+        self
+    }
+
+    /// Prepaid gas required by `ft_transfer_call` to schedule the receiver call and the callback.
+    pub fn required_prepaid_gas(&self) -> Gas {
+        self.transfer_call + self.resolve_transfer + self.min_receiver
+    }
+
+    /// Returns the gas of the `ft_on_transfer` call, panicking before anything is transferred if
+    /// the prepaid gas doesn't cover [`required_prepaid_gas`](Self::required_prepaid_gas).
+    pub fn receiver_gas(&self) -> Gas {
+        let prepaid_gas = env::prepaid_gas();
+        let required_gas = self.required_prepaid_gas();
+        if prepaid_gas < required_gas {
+            env::panic_str(&format!(
+                "More gas is required: {} attached, {} required",
+                prepaid_gas.0, required_gas.0
+            ));
+        }
+        prepaid_gas - self.transfer_call - self.resolve_transfer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    #[test]
+    fn receiver_gets_remaining_gas() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas(100_000_000_000_000)).build());
+        let gas = FtTransferCallGas::default().with_resolve_transfer_gas(Gas(10_000_000_000_000));
+        assert_eq!(gas.receiver_gas(), Gas(65_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "More gas is required: 50000000000000 attached, 80000000000000 required"
+    )]
+    fn insufficient_prepaid_gas() {
+        testing_env!(VMContextBuilder::new().prepaid_gas(Gas(50_000_000_000_000)).build());
+        FtTransferCallGas::default().with_min_receiver_gas(Gas(50_000_000_000_000)).receiver_gas();
+    }
+}
@@ -0,0 +1,247 @@
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{paginated_range, UnorderedSet};
+use near_sdk::json_types::U128;
+use near_sdk::layout::{StorageLayout, StorageRegion};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, AccountId, Balance, IntoStorageKey, StorageUsage};
+
+/// Account holding a fungible token, as returned by the `ft_holders` view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtHolder {
+    pub account_id: AccountId,
+    pub balance: U128,
+}
+
+/// Enumerable index of the accounts registered with a [`FungibleToken`], whose balances are
+/// kept in a `LookupMap` which can't be iterated.
+///
+/// The index is a separate field so that the storage layout of existing tokens doesn't change.
+/// [`impl_fungible_token_storage`](crate::impl_fungible_token_storage) keeps it in sync with
+/// `storage_deposit` and `storage_unregister` when given `holders = <field>`, and exposes it with
+/// the `ft_holders` and `ft_holders_count` views. Accounts registered in other ways, e.g. the
+/// owner in the init method or accounts registered before the index was added, must be added
+/// with [`insert`](Self::insert).
+///
+/// [`new`](Self::new) adds the storage of an entry of the index to the storage usage of each
+/// account of the token, so that the storage deposit reported by `storage_balance_bounds` and
+/// charged on registration covers it, and it's refunded with the deposit on unregistration.
+/// The storage of the entries of accounts registered before the index was added is paid by the
+/// contract, and also refunded when they unregister.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FungibleTokenHolders {
+    accounts: UnorderedSet<AccountId>,
+}
+
+impl StorageLayout for FungibleTokenHolders {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        UnorderedSet::<AccountId>::storage_layout(prefix)
+    }
+}
+
+impl FungibleTokenHolders {
+    /// Creates the index and adds the storage of its entries to the storage usage of the
+    /// accounts of the token.
+    pub fn new<S>(prefix: S, token: &mut FungibleToken) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut this = Self { accounts: UnorderedSet::new(prefix) };
+        token.account_storage_usage += this.measure_account_storage_usage();
+        this
+    }
+
+    fn measure_account_storage_usage(&mut self) -> StorageUsage {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
+        self.accounts.insert(&tmp_account_id);
+        let account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.accounts.remove(&tmp_account_id);
+        account_storage_usage
+    }
+
+    /// Adds the account to the index, returning `false` if it was already indexed.
+    pub fn insert(&mut self, account_id: &AccountId) -> bool {
+        self.accounts.insert(account_id)
+    }
+
+    /// Removes the account from the index, returning `false` if it wasn't indexed.
+    pub fn remove(&mut self, account_id: &AccountId) -> bool {
+        self.accounts.remove(account_id)
+    }
+
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.accounts.contains(account_id)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Returns the indexed accounts with their balances in the token, paginated like the NFT
+    /// enumeration. The order changes when accounts are removed.
+    pub fn holders(
+        &self,
+        token: &FungibleToken,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<FtHolder> {
+        let range = paginated_range(self.accounts.len(), from_index, limit);
+        self.accounts
+            .as_vector()
+            .to_vec_paginated(range.start, range.end - range.start)
+            .into_iter()
+            .map(|account_id| {
+                let balance = token.accounts.get(&account_id).unwrap_or(0);
+                FtHolder { account_id, balance: U128(balance) }
+            })
+            .collect()
+    }
+}
+
+/// Keeps the index in sync when used as the hook of the storage methods of the token, for
+/// contracts which also need the hook for other indexes.
+impl FungibleTokenTransferHook for FungibleTokenHolders {
+    fn on_account_registered(&mut self, _token: &mut FungibleToken, account_id: &AccountId) {
+        self.insert(account_id);
+    }
+
+    fn on_account_closed(
+        &mut self,
+        _token: &mut FungibleToken,
+        account_id: &AccountId,
+        _remaining_balance: Balance,
+    ) {
+        self.remove(account_id);
+    }
+}
+
+/// Enumeration of the accounts holding a fungible token, the counterpart of `nft_tokens` for
+/// NEP-141 tokens.
+pub trait FungibleTokenHoldersView {
+    /// Returns the registered accounts with their balances, starting at `from_index`.
+    fn ft_holders(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<FtHolder>;
+
+    /// Returns the number of registered accounts.
+    fn ft_holders_count(&self) -> U128;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause::Pause;
+    use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env, PromiseOrValue};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        token: FungibleToken,
+        holders: FungibleTokenHolders,
+        pause: Pause,
+        max_memo: u32,
+    }
+
+    crate::impl_fungible_token_core!(Contract, token, max_memo_length = max_memo, hook = pause);
+    crate::impl_fungible_token_storage!(Contract, token, holders = holders, hook = pause);
+
+    #[test]
+    fn holders_with_balances() {
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let mut holders = FungibleTokenHolders::new(b"h".to_vec(), &mut token);
+        for index in 0..3 {
+            token.internal_register_account(&accounts(index));
+            holders.insert(&accounts(index));
+        }
+        token.internal_deposit(&accounts(1), 100);
+        assert!(!holders.insert(&accounts(1)));
+        assert_eq!(holders.len(), 3);
+        assert_eq!(
+            holders.holders(&token, Some(U128(1)), Some(1)),
+            vec![FtHolder { account_id: accounts(1), balance: U128(100) }]
+        );
+
+        assert!(holders.remove(&accounts(0)));
+        let account_ids: Vec<AccountId> = holders
+            .holders(&token, None, None)
+            .into_iter()
+            .map(|holder| holder.account_id)
+            .collect();
+        assert_eq!(account_ids, vec![accounts(2), accounts(1)]);
+    }
+
+    fn contract_with_deposit(predecessor: AccountId) -> Contract {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(predecessor.clone()).build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let holders = FungibleTokenHolders::new(b"h".to_vec(), &mut token);
+        let contract = Contract { token, holders, pause: Pause::new(), max_memo: 8 };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(contract.storage_balance_bounds().min.0)
+            .build());
+        contract
+    }
+
+    #[test]
+    fn storage_deposit_covers_index() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let token_usage = token.account_storage_usage;
+        let mut holders = FungibleTokenHolders::new(b"h".to_vec(), &mut token);
+
+        let initial_storage_usage = env::storage_usage();
+        let account_id = AccountId::new_unchecked("a".repeat(64));
+        token.internal_register_account(&account_id);
+        holders.insert(&account_id);
+        assert!(token.account_storage_usage > token_usage);
+        assert_eq!(env::storage_usage() - initial_storage_usage, token.account_storage_usage);
+    }
+
+    #[test]
+    fn storage_macro_with_holders_and_hook() {
+        let mut contract = contract_with_deposit(accounts(1));
+        contract.storage_deposit(None, None);
+        assert_eq!(contract.ft_holders_count(), U128(1));
+        assert_eq!(contract.ft_holders(None, None)[0].account_id, accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_register is paused")]
+    fn storage_macro_calls_hook_with_holders() {
+        let mut contract = contract_with_deposit(accounts(1));
+        contract.pause.pause_feature("ft_register");
+        contract.storage_deposit(None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn core_macro_with_memo_length_and_hook() {
+        let mut contract = contract_with_deposit(accounts(1));
+        contract.storage_deposit(None, None);
+        contract.storage_deposit(Some(accounts(2)), None);
+        contract.token.internal_deposit(&accounts(1), 100);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.ft_transfer(accounts(2), U128(10), Some("short".to_string()));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(10));
+
+        contract.pause.pause_feature("ft_transfer");
+        contract.ft_transfer(accounts(2), U128(10), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "MEMO_TOO_LONG")]
+    fn core_macro_with_memo_length() {
+        let mut contract = contract_with_deposit(accounts(1));
+        contract.ft_transfer(accounts(2), U128(10), Some("a long memo".to_string()));
+    }
+}
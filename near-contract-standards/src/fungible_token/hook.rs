@@ -0,0 +1,393 @@
+use crate::fungible_token::core_impl::unused_amount_of_result;
+use crate::fungible_token::gas::FtTransferCallGas;
+use crate::fungible_token::{FungibleToken, TransferLimits};
+use crate::pause::Pause;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, Balance, PromiseOrValue};
+
+/// Kind of a transfer passed to a [`FungibleTokenTransferHook`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FtTransferKind {
+    /// Transfer of `ft_transfer`.
+    Transfer,
+    /// Transfer of `ft_transfer_call`, before `ft_on_transfer` is called on the receiver.
+    TransferCall,
+    /// Refund of the tokens unused by `ft_on_transfer` in `ft_resolve_transfer`, from the
+    /// receiver of the transfer call back to its sender.
+    Refund,
+}
+
+/// Transfer passed to a [`FungibleTokenTransferHook`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FtHookTransfer<'a> {
+    pub kind: FtTransferKind,
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: Balance,
+}
+
+/// Hook called around the transfers of a [`FungibleToken`], so that the contract can freeze
+/// accounts, charge fees or pause transfers without forking the token. The hook is a field of
+/// the contract passed to [`impl_fungible_token_core`](crate::impl_fungible_token_core) with
+/// `hook = <field>`, which calls the `_with_hook` methods of the token. The macros of the
+/// extensions which move tokens, e.g. batch transfers, allowances, minters and the native
+/// wrapper, take the same `hook = <field>`, so that the hook sees every transfer, mint and burn
+/// of the contract.
+///
+/// `before_transfer` can reject a transfer by panicking. It shouldn't reject refunds, otherwise
+/// the unused tokens of a transfer call stay with its receiver. All methods get the token,
+/// e.g. to transfer a fee with [`FungibleToken::internal_transfer`].
+///
+/// The hook is also told when accounts are registered and closed and when tokens are burned, so
+/// that the contract can keep its own indexes, e.g. holder lists or voting power, in sync with
+/// the balances. [`impl_fungible_token_storage`](crate::impl_fungible_token_storage) calls it
+/// when given `hook = <field>`, through the `_with_hook` storage methods of the token.
+///
+/// ```
+/// use near_contract_standards::fungible_token::hook::{
+///     FtHookTransfer, FtTransferKind, FungibleTokenTransferHook,
+/// };
+/// use near_contract_standards::fungible_token::FungibleToken;
+/// use near_sdk::collections::LookupSet;
+/// use near_sdk::{require, AccountId};
+///
+/// /// Accounts which can't send nor receive tokens.
+/// pub struct FrozenAccounts(LookupSet<AccountId>);
+///
+/// impl FungibleTokenTransferHook for FrozenAccounts {
+///     fn before_transfer(&mut self, _token: &mut FungibleToken, transfer: &FtHookTransfer) {
+///         if transfer.kind != FtTransferKind::Refund {
+///             require!(!self.0.contains(transfer.sender_id), "The sender is frozen");
+///             require!(!self.0.contains(transfer.receiver_id), "The receiver is frozen");
+///         }
+///     }
+/// }
+/// ```
+pub trait FungibleTokenTransferHook {
+    /// Called before the balances change.
+    fn before_transfer(&mut self, _token: &mut FungibleToken, _transfer: &FtHookTransfer) {}
+
+    /// Called after the balances changed and the `ft_transfer` event was emitted.
+    fn after_transfer(&mut self, _token: &mut FungibleToken, _transfer: &FtHookTransfer) {}
+
+    /// Called after the account was registered with a zero balance.
+    fn on_account_registered(&mut self, _token: &mut FungibleToken, _account_id: &AccountId) {}
+
+    /// Called after the account was unregistered, with the balance it had. A positive balance
+    /// was burned, which [`on_tokens_burned`](Self::on_tokens_burned) was called for first.
+    fn on_account_closed(
+        &mut self,
+        _token: &mut FungibleToken,
+        _account_id: &AccountId,
+        _remaining_balance: Balance,
+    ) {
+    }
+
+    /// Called after `amount` tokens were minted to `owner_id` and the `ft_mint` event was
+    /// emitted.
+    fn on_tokens_minted(
+        &mut self,
+        _token: &mut FungibleToken,
+        _owner_id: &AccountId,
+        _amount: Balance,
+    ) {
+    }
+
+    /// Called after `amount` tokens of `owner_id` were burned and the `ft_burn` event was
+    /// emitted, e.g. by a minter, when an account is force unregistered or when the refund of a
+    /// transfer call can't be returned because its sender was unregistered.
+    fn on_tokens_burned(
+        &mut self,
+        _token: &mut FungibleToken,
+        _owner_id: &AccountId,
+        _amount: Balance,
+    ) {
+    }
+}
+
+/// No hook, for the transfers of the token without the `_with_hook` methods.
+impl FungibleTokenTransferHook for () {}
+
+/// Rejects transfers while the contract or the `ft_transfer` feature is paused, and mints,
+/// registrations and unregistrations while the `ft_mint`, `ft_register` or `ft_unregister`
+/// feature is. Refunds aren't paused.
+impl FungibleTokenTransferHook for Pause {
+    fn before_transfer(&mut self, _token: &mut FungibleToken, transfer: &FtHookTransfer) {
+        if transfer.kind != FtTransferKind::Refund {
+            self.assert_not_paused("ft_transfer");
+        }
+    }
+
+    fn on_account_registered(&mut self, _token: &mut FungibleToken, _account_id: &AccountId) {
+        self.assert_not_paused("ft_register");
+    }
+
+    fn on_account_closed(
+        &mut self,
+        _token: &mut FungibleToken,
+        _account_id: &AccountId,
+        _remaining_balance: Balance,
+    ) {
+        self.assert_not_paused("ft_unregister");
+    }
+
+    fn on_tokens_minted(&mut self, _token: &mut FungibleToken, _owner_id: &AccountId, _: Balance) {
+        self.assert_not_paused("ft_mint");
+    }
+}
+
+/// Enforces the limits on transfers. Refunds aren't limited.
+impl FungibleTokenTransferHook for TransferLimits {
+    fn before_transfer(&mut self, _token: &mut FungibleToken, transfer: &FtHookTransfer) {
+        if transfer.kind != FtTransferKind::Refund {
+            self.assert_transfer(transfer.sender_id, transfer.amount);
+        }
+    }
+}
+
+impl FungibleToken {
+    /// Same as [`internal_mint`](Self::internal_mint), calling `on_tokens_minted` of the hook.
+    pub fn internal_mint_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        self.internal_mint(account_id, amount, memo);
+        hook.on_tokens_minted(self, account_id, amount);
+    }
+
+    /// Same as [`internal_burn`](Self::internal_burn), calling `on_tokens_burned` of the hook.
+    pub fn internal_burn_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        self.internal_burn(account_id, amount, memo);
+        hook.on_tokens_burned(self, account_id, amount);
+    }
+
+    /// Same as [`internal_transfer`](Self::internal_transfer), calling the hook around the
+    /// transfer.
+    pub fn internal_transfer_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        let transfer =
+            FtHookTransfer { kind: FtTransferKind::Transfer, sender_id, receiver_id, amount };
+        hook.before_transfer(self, &transfer);
+        self.internal_transfer(sender_id, receiver_id, amount, memo);
+        hook.after_transfer(self, &transfer);
+    }
+
+    /// Same as [`internal_transfer_call_with_gas`](Self::internal_transfer_call_with_gas),
+    /// calling the hook around the transfer. The hook of the refund is called by
+    /// [`internal_ft_resolve_transfer_with_hook`](Self::internal_ft_resolve_transfer_with_hook).
+    #[allow(clippy::too_many_arguments)]
+    pub fn internal_transfer_call_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        memo: Option<String>,
+        msg: String,
+        gas: &FtTransferCallGas,
+    ) -> PromiseOrValue<U128> {
+        let transfer = FtHookTransfer {
+            kind: FtTransferKind::TransferCall,
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            amount,
+        };
+        hook.before_transfer(self, &transfer);
+        let promise = self.internal_transfer_call_with_gas(
+            sender_id.clone(),
+            receiver_id.clone(),
+            amount,
+            memo,
+            msg,
+            gas,
+        );
+        hook.after_transfer(self, &transfer);
+        promise
+    }
+
+    /// Same as [`internal_ft_resolve_transfer`](Self::internal_ft_resolve_transfer), calling the
+    /// hook around the refund of the unused tokens.
+    /// Returns (Used token amount, Burned token amount)
+    pub fn internal_ft_resolve_transfer_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> (u128, u128) {
+        let amount: Balance = amount.into();
+        let unused_amount = unused_amount_of_result(0, amount);
+        self.internal_refund_unused(hook, sender_id, receiver_id, amount, unused_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::transfer_limits::TransferLimitsConfig;
+    use crate::storage_management::StorageManagement;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    /// Charges a fee of 1 token per transfer, paid to `accounts(3)`.
+    struct Fee;
+
+    impl FungibleTokenTransferHook for Fee {
+        fn after_transfer(&mut self, token: &mut FungibleToken, transfer: &FtHookTransfer) {
+            if transfer.kind != FtTransferKind::Refund {
+                token.internal_transfer(transfer.sender_id, &accounts(3), 1, None);
+            }
+        }
+    }
+
+    fn setup() -> FungibleToken {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        for index in 0..4 {
+            token.internal_register_account(&accounts(index));
+        }
+        token.internal_deposit(&accounts(0), 100);
+        token
+    }
+
+    #[test]
+    fn hook_charges_fee() {
+        let mut token = setup();
+        token.internal_transfer_with_hook(&mut Fee, &accounts(0), &accounts(1), 10, None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 89);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(3)), 1);
+    }
+
+    /// Tracks the registered accounts and the burned tokens.
+    #[derive(Default)]
+    struct Index {
+        accounts: Vec<AccountId>,
+        closed: Vec<(AccountId, Balance)>,
+        burned: Balance,
+    }
+
+    impl FungibleTokenTransferHook for Index {
+        fn on_account_registered(&mut self, _token: &mut FungibleToken, account_id: &AccountId) {
+            self.accounts.push(account_id.clone());
+        }
+
+        fn on_account_closed(
+            &mut self,
+            _token: &mut FungibleToken,
+            account_id: &AccountId,
+            remaining_balance: Balance,
+        ) {
+            self.accounts.retain(|id| id != account_id);
+            self.closed.push((account_id.clone(), remaining_balance));
+        }
+
+        fn on_tokens_burned(
+            &mut self,
+            _token: &mut FungibleToken,
+            _owner_id: &AccountId,
+            amount: Balance,
+        ) {
+            self.burned += amount;
+        }
+    }
+
+    #[test]
+    fn hook_tracks_accounts() {
+        let mut token = setup();
+        let mut index = Index::default();
+        let storage_deposit = token.storage_balance_bounds().min.0;
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(storage_deposit)
+            .build());
+        token.internal_storage_deposit_with_hook(&mut index, None);
+        token.internal_storage_deposit_with_hook(&mut index, Some(accounts(5)));
+        assert_eq!(index.accounts, vec![accounts(4), accounts(5)]);
+        token.internal_deposit(&accounts(4), 10);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        token.internal_storage_unregister_with_hook(&mut index, Some(true));
+        assert_eq!(index.accounts, vec![accounts(5)]);
+        assert_eq!(index.closed, vec![(accounts(4), 10)]);
+        assert_eq!(index.burned, 10);
+    }
+
+    #[test]
+    fn hook_counts_burned_refund() {
+        let mut token = setup();
+        let mut index = Index::default();
+        token.internal_transfer(&accounts(0), &accounts(1), 10, None);
+        token.accounts.remove(&accounts(0));
+
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        let (_, burned_amount) = token.internal_ft_resolve_transfer_with_hook(
+            &mut index,
+            &accounts(0),
+            accounts(1),
+            U128(10),
+        );
+        assert_eq!(burned_amount, 10);
+        assert_eq!(index.burned, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn paused_transfer() {
+        let mut token = setup();
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        token.internal_transfer_with_hook(&mut pause, &accounts(0), &accounts(1), 10, None);
+    }
+
+    #[test]
+    fn refunds_are_not_limited() {
+        let mut token = setup();
+        let mut limits = TransferLimits::new(b"l".to_vec(), accounts(0));
+        limits.set_config(TransferLimitsConfig {
+            max_transfer_amount: Some(U128(10)),
+            daily_volume: None,
+        });
+        token.internal_transfer_with_hook(&mut limits, &accounts(0), &accounts(1), 10, None);
+        token.internal_transfer(&accounts(1), &accounts(2), 10, None);
+
+        testing_env!(
+            VMContextBuilder::new().predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        let (used_amount, _) = token.internal_ft_resolve_transfer_with_hook(
+            &mut limits,
+            &accounts(1),
+            accounts(2),
+            U128(10),
+        );
+        assert_eq!(used_amount, 0);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 10);
+    }
+}
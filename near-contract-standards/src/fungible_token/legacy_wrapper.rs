@@ -0,0 +1,380 @@
+use crate::event::NearEvent;
+use crate::fungible_token::core_impl::NO_DEPOSIT;
+use crate::fungible_token::events::{FtBurn, FtMint};
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, log, require, AccountId, Balance, Gas, Promise, PromiseError};
+
+/// Gas attached to the calls to the legacy token.
+pub const GAS_FOR_LEGACY_TOKEN_CALL: Gas = Gas(10_000_000_000_000);
+/// Gas attached to the callbacks resolving the calls to the legacy token, by
+/// [`impl_legacy_token_wrapper`](crate::impl_legacy_token_wrapper).
+pub const GAS_FOR_LEGACY_TOKEN_RESOLVE: Gas = Gas(10_000_000_000_000);
+
+/// Interface of the tokens predating NEP-141, as described in NEP-21.
+#[ext_contract(ext_legacy_token)]
+pub trait LegacyToken {
+    fn transfer_from(&mut self, owner_id: AccountId, new_owner_id: AccountId, amount: U128);
+
+    fn transfer(&mut self, new_owner_id: AccountId, amount: U128);
+
+    fn get_balance(&self, owner_id: AccountId) -> U128;
+}
+
+/// Balances compared by a reconciliation, as returned by the `reconcile_legacy_token` callback.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Reconciliation {
+    /// Legacy tokens held by the wrapper.
+    pub legacy_balance: U128,
+    /// Total supply of the wrapped token.
+    pub total_supply: U128,
+}
+
+impl Reconciliation {
+    /// Returns `true` if every wrapped token is backed by a legacy token held by the wrapper.
+    pub fn is_backed(&self) -> bool {
+        self.legacy_balance.0 >= self.total_supply.0
+    }
+}
+
+/// Wrapper of a token predating NEP-141, e.g. NEP-21, exposing it as a compliant NEP-141 token so
+/// that ecosystems can standardize on `ft_*` without redeploying the legacy token.
+///
+/// The legacy token can't transfer on behalf of its holders when called by another contract, so
+/// the wrapper holds the legacy tokens and mints the same amount of its own
+/// [`FungibleToken`], exposed with `impl_fungible_token_core!`:
+///
+/// - [`wrap`](Self::wrap) pulls the legacy tokens of the owner with `transfer_from`, after the
+///   owner allowed the wrapper to with `inc_allowance`, and [`resolve_wrap`](Self::resolve_wrap)
+///   mints them once they arrived.
+/// - [`unwrap`](Self::unwrap) burns the wrapped tokens and sends the legacy tokens back with
+///   `transfer`, and [`resolve_unwrap`](Self::resolve_unwrap) mints them back if it failed.
+///
+/// Wrapped tokens are minted after the legacy tokens arrived and burned before they leave, so
+/// the wrapper always holds at least the total supply. [`reconcile`](Self::reconcile) checks it
+/// against the balance reported by the legacy token, and
+/// [`resolve_reconcile`](Self::resolve_reconcile) logs the result as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"legacy_wrapper","version":"1.0.0","event":"legacy_reconciled","data":[{"legacy_balance":"100","total_supply":"90"}]}
+/// ```
+///
+/// A surplus, from tokens sent to the wrapper directly or wraps still in flight, is harmless. A
+/// deficit means the legacy token lost tokens of the wrapper and the contract should stop
+/// wrapping.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct LegacyTokenWrapper {
+    pub legacy_token_id: AccountId,
+    /// Legacy tokens held by the wrapper as of the last reconciliation.
+    pub reconciled_balance: Balance,
+}
+
+impl LegacyTokenWrapper {
+    pub fn new(legacy_token_id: AccountId) -> Self {
+        Self { legacy_token_id, reconciled_balance: 0 }
+    }
+
+    /// Pulls `amount` legacy tokens of the owner, to be minted with
+    /// [`resolve_wrap`](Self::resolve_wrap) in the callback. Panics if the owner isn't registered
+    /// with the wrapped token, since the tokens couldn't be minted.
+    pub fn wrap(&self, token: &FungibleToken, owner_id: AccountId, amount: Balance) -> Promise {
+        require!(amount > 0, "The amount should be a positive number");
+        token.internal_unwrap_balance_of(&owner_id);
+        ext_legacy_token::transfer_from(
+            owner_id,
+            env::current_account_id(),
+            amount.into(),
+            self.legacy_token_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_LEGACY_TOKEN_CALL,
+        )
+    }
+
+    /// Mints the tokens pulled by [`wrap`](Self::wrap) if the transfer at `result_idx`
+    /// succeeded. If the owner unregistered in the meantime, the legacy tokens are sent back.
+    pub fn resolve_wrap(
+        &self,
+        token: &mut FungibleToken,
+        owner_id: &AccountId,
+        amount: Balance,
+        result_idx: u64,
+    ) -> Result<(), PromiseError> {
+        env::promise_result_bounded(result_idx, env::MAX_PROMISE_RESULT_BYTES)?;
+        if token.accounts.contains_key(owner_id) {
+            token.internal_deposit(owner_id, amount);
+            FtMint { owner_id, amount: &amount.into(), memo: Some("wrap") }.emit();
+        } else {
+            log!("The account of {} is unregistered, the legacy tokens are sent back", owner_id);
+            ext_legacy_token::transfer(
+                owner_id.clone(),
+                amount.into(),
+                self.legacy_token_id.clone(),
+                NO_DEPOSIT,
+                GAS_FOR_LEGACY_TOKEN_CALL,
+            );
+        }
+        Ok(())
+    }
+
+    /// Burns `amount` wrapped tokens of the owner and sends the legacy tokens back, to be minted
+    /// back with [`resolve_unwrap`](Self::resolve_unwrap) if the transfer fails.
+    pub fn unwrap(
+        &self,
+        token: &mut FungibleToken,
+        owner_id: AccountId,
+        amount: Balance,
+    ) -> Promise {
+        require!(amount > 0, "The amount should be a positive number");
+        token.internal_withdraw(&owner_id, amount);
+        FtBurn { owner_id: &owner_id, amount: &amount.into(), memo: Some("unwrap") }.emit();
+        ext_legacy_token::transfer(
+            owner_id,
+            amount.into(),
+            self.legacy_token_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_LEGACY_TOKEN_CALL,
+        )
+    }
+
+    /// Mints back the tokens burned by [`unwrap`](Self::unwrap) if the transfer at `result_idx`
+    /// failed. If the owner unregistered in the meantime, its account is registered again at the
+    /// expense of the contract, since the legacy token just rejected the tokens. Returns `true`
+    /// if the legacy tokens were sent.
+    pub fn resolve_unwrap(
+        &self,
+        token: &mut FungibleToken,
+        owner_id: &AccountId,
+        amount: Balance,
+        result_idx: u64,
+    ) -> bool {
+        if env::promise_result_bounded(result_idx, env::MAX_PROMISE_RESULT_BYTES).is_ok() {
+            return true;
+        }
+        if !token.accounts.contains_key(owner_id) {
+            log!("The account of {} is unregistered, it is registered again", owner_id);
+            token.internal_register_account(owner_id);
+        }
+        token.internal_deposit(owner_id, amount);
+        FtMint { owner_id, amount: &amount.into(), memo: Some("unwrap refund") }.emit();
+        false
+    }
+
+    /// Queries the legacy tokens held by the wrapper, to be compared with the total supply with
+    /// [`resolve_reconcile`](Self::resolve_reconcile) in the callback.
+    pub fn reconcile(&self) -> Promise {
+        ext_legacy_token::get_balance(
+            env::current_account_id(),
+            self.legacy_token_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_LEGACY_TOKEN_CALL,
+        )
+    }
+
+    /// Compares the balance at `result_idx` with the total supply of the wrapped token, records
+    /// it and logs a `legacy_reconciled` event.
+    pub fn resolve_reconcile(
+        &mut self,
+        token: &FungibleToken,
+        result_idx: u64,
+    ) -> Result<Reconciliation, PromiseError> {
+        let legacy_balance: U128 = env::promise_result_json(result_idx)?;
+        self.reconciled_balance = legacy_balance.0;
+        let reconciliation =
+            Reconciliation { legacy_balance, total_supply: token.total_supply.into() };
+        NearEvent::LegacyWrapper(LegacyWrapperEvent {
+            version: "1.0.0",
+            event: "legacy_reconciled",
+            data: &[&reconciliation],
+        })
+        .emit();
+        Ok(reconciliation)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct LegacyWrapperEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [&'a Reconciliation],
+}
+
+/// Generates the `wrap(amount)`, `unwrap(amount)` and `reconcile_legacy_token()` methods of a
+/// [`LegacyTokenWrapper`] field, with their private callbacks. The `ft_*` methods of the wrapped
+/// token are exposed separately with `impl_fungible_token_core!`.
+/// Takes name of the Contract struct, the inner field for the wrapped token and the inner field
+/// for the wrapper.
+#[macro_export]
+macro_rules! impl_legacy_token_wrapper {
+    ($contract: ident, $token: ident, $wrapper: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            pub fn wrap(&mut self, amount: near_sdk::json_types::U128) -> near_sdk::Promise {
+                let owner_id = near_sdk::env::predecessor_account_id();
+                let args = near_sdk::serde_json::json!({ "owner_id": owner_id, "amount": amount });
+                self.$wrapper.wrap(&self.$token, owner_id, amount.0).then(
+                    near_sdk::Promise::new(near_sdk::env::current_account_id()).function_call(
+                        "on_legacy_wrap".to_string(),
+                        args.to_string().into_bytes(),
+                        0,
+                        $crate::fungible_token::legacy_wrapper::GAS_FOR_LEGACY_TOKEN_RESOLVE,
+                    ),
+                )
+            }
+
+            #[private]
+            pub fn on_legacy_wrap(
+                &mut self,
+                owner_id: AccountId,
+                amount: near_sdk::json_types::U128,
+            ) {
+                if self.$wrapper.resolve_wrap(&mut self.$token, &owner_id, amount.0, 0).is_err() {
+                    near_sdk::env::panic_str("Failed to pull the legacy tokens");
+                }
+            }
+
+            #[payable]
+            pub fn unwrap(&mut self, amount: near_sdk::json_types::U128) -> near_sdk::Promise {
+                near_sdk::assert_one_yocto();
+                let owner_id = near_sdk::env::predecessor_account_id();
+                let args = near_sdk::serde_json::json!({ "owner_id": owner_id, "amount": amount });
+                self.$wrapper.unwrap(&mut self.$token, owner_id, amount.0).then(
+                    near_sdk::Promise::new(near_sdk::env::current_account_id()).function_call(
+                        "on_legacy_unwrap".to_string(),
+                        args.to_string().into_bytes(),
+                        0,
+                        $crate::fungible_token::legacy_wrapper::GAS_FOR_LEGACY_TOKEN_RESOLVE,
+                    ),
+                )
+            }
+
+            #[private]
+            pub fn on_legacy_unwrap(
+                &mut self,
+                owner_id: AccountId,
+                amount: near_sdk::json_types::U128,
+            ) -> bool {
+                self.$wrapper.resolve_unwrap(&mut self.$token, &owner_id, amount.0, 0)
+            }
+
+            pub fn reconcile_legacy_token(&self) -> near_sdk::Promise {
+                self.$wrapper.reconcile().then(
+                    near_sdk::Promise::new(near_sdk::env::current_account_id()).function_call(
+                        "on_legacy_reconcile".to_string(),
+                        vec![],
+                        0,
+                        $crate::fungible_token::legacy_wrapper::GAS_FOR_LEGACY_TOKEN_RESOLVE,
+                    ),
+                )
+            }
+
+            #[private]
+            pub fn on_legacy_reconcile(
+                &mut self,
+            ) -> $crate::fungible_token::legacy_wrapper::Reconciliation {
+                match self.$wrapper.resolve_reconcile(&self.$token, 0) {
+                    Ok(reconciliation) => reconciliation,
+                    Err(_) => near_sdk::env::panic_str("Failed to query the legacy token balance"),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    fn callback(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn setup() -> (FungibleToken, LegacyTokenWrapper) {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t");
+        token.internal_register_account(&accounts(1));
+        (token, LegacyTokenWrapper::new(accounts(5)))
+    }
+
+    #[test]
+    fn wrap_and_unwrap() {
+        let (mut token, mut wrapper) = setup();
+        callback(PromiseResult::Successful(vec![]));
+        wrapper.resolve_wrap(&mut token, &accounts(1), 100, 0).unwrap();
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 100);
+
+        wrapper.unwrap(&mut token, accounts(1), 40);
+        callback(PromiseResult::Failed);
+        assert!(!wrapper.resolve_unwrap(&mut token, &accounts(1), 40, 0));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 100);
+
+        wrapper.unwrap(&mut token, accounts(1), 40);
+        callback(PromiseResult::Successful(vec![]));
+        assert!(wrapper.resolve_unwrap(&mut token, &accounts(1), 40, 0));
+        assert_eq!(token.total_supply, 60);
+
+        callback(PromiseResult::Successful(br#""70""#.to_vec()));
+        let reconciliation = wrapper.resolve_reconcile(&token, 0).unwrap();
+        assert!(reconciliation.is_backed());
+        assert_eq!(wrapper.reconciled_balance, 70);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"legacy_wrapper","version":"1.0.0","event":"legacy_reconciled","data":[{"legacy_balance":"70","total_supply":"60"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_wrap_mints_nothing() {
+        let (mut token, wrapper) = setup();
+        callback(PromiseResult::Failed);
+        assert!(wrapper.resolve_wrap(&mut token, &accounts(1), 100, 0).is_err());
+        assert_eq!(token.total_supply, 0);
+    }
+
+    #[test]
+    fn wrap_for_unregistered_sends_back() {
+        let (mut token, wrapper) = setup();
+        callback(PromiseResult::Successful(vec![]));
+        wrapper.resolve_wrap(&mut token, &accounts(2), 100, 0).unwrap();
+        assert_eq!(token.total_supply, 0);
+        let receipt = get_created_receipts().pop().unwrap();
+        assert_eq!(receipt.receiver_id, accounts(5));
+        assert!(matches!(
+            &receipt.actions[..],
+            [VmAction::FunctionCall { function_name, .. }] if function_name == "transfer"
+        ));
+    }
+
+    #[test]
+    fn failed_unwrap_for_unregistered_registers() {
+        let (mut token, wrapper) = setup();
+        token.internal_deposit(&accounts(1), 100);
+        wrapper.unwrap(&mut token, accounts(1), 100);
+        token.accounts.remove(&accounts(1));
+        callback(PromiseResult::Failed);
+        assert!(!wrapper.resolve_unwrap(&mut token, &accounts(1), 100, 0));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 100);
+        assert_eq!(token.total_supply, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account charlie is not registered")]
+    fn wrap_unregistered() {
+        let (token, wrapper) = setup();
+        wrapper.wrap(&token, accounts(2), 100);
+    }
+}
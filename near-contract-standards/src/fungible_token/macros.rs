@@ -1,8 +1,50 @@
 /// The core methods for a basic fungible token. Extension standards may be
 /// added in addition to this macro.
+///
+/// With `max_memo_length = <field>` after the token, `ft_transfer` and `ft_transfer_call` panic
+/// with a `MEMO_TOO_LONG` error if the memo is longer than the number of bytes in the field. The
+/// memo is part of the `ft_transfer` event, so indexers always get it bounded.
+///
+/// With `gas = <field>`, `ft_transfer_call` takes its gas amounts from the
+/// [`FtTransferCallGas`](crate::fungible_token::gas::FtTransferCallGas) field.
+///
+/// With `hook = <field>`, the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) field is
+/// called around the transfers of `ft_transfer` and `ft_transfer_call` and the refunds of
+/// `ft_resolve_transfer`.
+///
+/// The keys can be combined in this order, e.g.
+/// `impl_fungible_token_core!(Contract, token, max_memo_length = max_memo, hook = pause)`, before
+/// the optional method name.
 #[macro_export]
 macro_rules! impl_fungible_token_core {
-    ($contract: ident, $token: ident $(, $on_tokens_burned_fn:ident)?) => {
+    (@parse $contract: ident, $token: ident, [], [], [],
+        , max_memo_length = $max_memo_length: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_core!(
+            @parse $contract, $token, [$max_memo_length], [], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($max_memo_length: ident)?], [], [],
+        , gas = $gas: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_core!(
+            @parse $contract, $token, [$($max_memo_length)?], [$gas], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($max_memo_length: ident)?], [$($gas: ident)?],
+        [], , hook = $hook: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_core!(
+            @parse $contract, $token, [$($max_memo_length)?], [$($gas)?], [$hook], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($max_memo_length: ident)?], [$($gas: ident)?],
+        [$($hook: ident)?], $(, $on_tokens_burned_fn:ident)?) => {
+        $crate::impl_fungible_token_core!(
+            @impl $contract, $token, [$($max_memo_length)?], [$($gas)?], [$($hook)?],
+            [$($on_tokens_burned_fn)?]
+        );
+    };
+    (@impl $contract: ident, $token: ident, [$($max_memo_length: ident)?], [$($gas: ident)?],
+        [$($hook: ident)?], [$($on_tokens_burned_fn:ident)?]) => {
         use $crate::fungible_token::core::FungibleTokenCore;
         use $crate::fungible_token::resolver::FungibleTokenResolver;
 
@@ -15,7 +57,18 @@ macro_rules! impl_fungible_token_core {
                 amount: U128,
                 memo: Option<String>,
             ) {
-                self.$token.ft_transfer(receiver_id, amount, memo)
+                $(near_sdk::assert_memo_length(memo.as_deref(), self.$max_memo_length as usize);)?
+                near_sdk::assert_one_yocto();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_with_hook(
+                    hook,
+                    &near_sdk::env::predecessor_account_id(),
+                    &receiver_id,
+                    amount.0,
+                    memo,
+                )
             }
 
             #[payable]
@@ -26,7 +79,23 @@ macro_rules! impl_fungible_token_core {
                 memo: Option<String>,
                 msg: String,
             ) -> PromiseOrValue<U128> {
-                self.$token.ft_transfer_call(receiver_id, amount, memo, msg)
+                $(near_sdk::assert_memo_length(memo.as_deref(), self.$max_memo_length as usize);)?
+                near_sdk::assert_one_yocto();
+                #[allow(unused_variables)]
+                let gas = $crate::fungible_token::gas::FtTransferCallGas::default();
+                $(let gas = self.$gas;)?
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_transfer_call_with_hook(
+                    hook,
+                    near_sdk::env::predecessor_account_id(),
+                    receiver_id,
+                    amount.0,
+                    memo,
+                    msg,
+                    &gas,
+                )
             }
 
             fn ft_total_supply(&self) -> U128 {
@@ -47,8 +116,12 @@ macro_rules! impl_fungible_token_core {
                 receiver_id: AccountId,
                 amount: U128,
             ) -> U128 {
-                let (used_amount, burned_amount) =
-                    self.$token.internal_ft_resolve_transfer(&sender_id, receiver_id, amount);
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                let (used_amount, burned_amount) = self
+                    .$token
+                    .internal_ft_resolve_transfer_with_hook(hook, &sender_id, receiver_id, amount);
                 if burned_amount > 0 {
                     $(self.$on_tokens_burned_fn(sender_id, burned_amount);)?
                 }
@@ -56,6 +129,27 @@ macro_rules! impl_fungible_token_core {
             }
         }
     };
+    ($contract: ident, $token: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_core!(@parse $contract, $token, [], [], [], $($rest)*);
+    };
+}
+
+/// Exposes the NEP-148 metadata of the token with `ft_metadata`.
+/// Takes name of the Contract struct and the inner field for the metadata, either a
+/// [`LazyFungibleTokenMetadata`](crate::fungible_token::metadata::LazyFungibleTokenMetadata) or a
+/// `LazyOption<FungibleTokenMetadata>`.
+#[macro_export]
+macro_rules! impl_fungible_token_metadata {
+    ($contract: ident, $metadata: ident) => {
+        #[near_bindgen]
+        impl $crate::fungible_token::metadata::FungibleTokenMetadataProvider for $contract {
+            fn ft_metadata(&self) -> $crate::fungible_token::metadata::FungibleTokenMetadata {
+                $crate::fungible_token::metadata::FungibleTokenMetadataProvider::ft_metadata(
+                    &self.$metadata,
+                )
+            }
+        }
+    };
 }
 
 /// Ensures that when fungible token storage grows by collections adding entries,
@@ -63,9 +157,95 @@ macro_rules! impl_fungible_token_core {
 /// that the FT contract runs out of Ⓝ.
 /// Takes name of the Contract struct, the inner field for the token and optional method name to
 /// call when the account was closed.
+///
+/// With `dust = <field>` after the token, the 1 yoctoⓃ attached to `storage_withdraw` is
+/// recorded in the [`StorageDust`](crate::storage_management::StorageDust) field, which is
+/// exposed through the `storage_dust` view.
+///
+/// With `holders = <field>`, registered accounts are tracked in the
+/// [`FungibleTokenHolders`](crate::fungible_token::holders::FungibleTokenHolders) field, which is
+/// exposed through the `ft_holders` and `ft_holders_count` views.
+///
+/// With `hook = <field>`, the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) field is
+/// told when `storage_deposit` registers an account and when `storage_unregister` burns the
+/// balance of an account and closes it.
+///
+/// With `refunds = <field>`, the storage deposit returned by `storage_unregister` is sent
+/// through the [`RefundQueue`](crate::refund_queue::RefundQueue) field, so that it's credited
+/// back if the transfer fails. The contract must also use
+/// [`impl_refund_queue`](crate::impl_refund_queue). `storage_withdraw` never transfers Ⓝ, since
+/// the storage balance of an account is fixed.
+///
+/// The keys can be combined in this order, e.g.
+/// `impl_fungible_token_storage!(Contract, token, holders = holders, hook = pause)`, before the
+/// optional method name.
 #[macro_export]
 macro_rules! impl_fungible_token_storage {
-    ($contract: ident, $token: ident $(, $on_account_closed_fn:ident)?) => {
+    (@parse $contract: ident, $token: ident, [], [], [], [],
+        , dust = $dust: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_storage!(
+            @parse $contract, $token, [$dust], [], [], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($dust: ident)?], [], [], [],
+        , holders = $holders: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_storage!(
+            @parse $contract, $token, [$($dust)?], [$holders], [], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($dust: ident)?], [$($holders: ident)?], [], [],
+        , hook = $hook: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_storage!(
+            @parse $contract, $token, [$($dust)?], [$($holders)?], [$hook], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($dust: ident)?], [$($holders: ident)?],
+        [$($hook: ident)?], [], , refunds = $refunds: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_storage!(
+            @parse $contract, $token, [$($dust)?], [$($holders)?], [$($hook)?], [$refunds],
+            $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($dust: ident)?], [$($holders: ident)?],
+        [$($hook: ident)?], [$($refunds: ident)?], $(, $on_account_closed_fn:ident)?) => {
+        $crate::impl_fungible_token_storage!(
+            @impl $contract, $token, [$($dust)?], [$($holders)?], [$($hook)?], [$($refunds)?],
+            [$($on_account_closed_fn)?]
+        );
+
+        $(
+            use $crate::storage_management::{StorageDustReport, StorageDustView};
+
+            #[near_bindgen]
+            impl StorageDustView for $contract {
+                fn storage_dust(&self) -> StorageDustReport {
+                    self.$dust.report()
+                }
+            }
+        )?
+
+        $(
+            use $crate::fungible_token::holders::{FtHolder, FungibleTokenHoldersView};
+
+            #[near_bindgen]
+            impl FungibleTokenHoldersView for $contract {
+                fn ft_holders(
+                    &self,
+                    from_index: Option<U128>,
+                    limit: Option<u64>,
+                ) -> Vec<FtHolder> {
+                    self.$holders.holders(&self.$token, from_index, limit)
+                }
+
+                fn ft_holders_count(&self) -> U128 {
+                    U128(self.$holders.len() as u128)
+                }
+            }
+        )?
+    };
+    (@impl $contract: ident, $token: ident, [$($dust: ident)?], [$($holders: ident)?],
+        [$($hook: ident)?], [$($refunds: ident)?], [$($on_account_closed_fn:ident)?]) => {
         use $crate::storage_management::{
             StorageManagement, StorageBalance, StorageBalanceBounds
         };
@@ -78,18 +258,30 @@ macro_rules! impl_fungible_token_storage {
                 account_id: Option<AccountId>,
                 registration_only: Option<bool>,
             ) -> StorageBalance {
-                self.$token.storage_deposit(account_id, registration_only)
+                let account_id =
+                    account_id.unwrap_or_else(near_sdk::env::predecessor_account_id);
+                let storage_balance = $crate::impl_fungible_token_storage!(
+                    @storage_deposit self, $token, [$($hook)?], account_id.clone(),
+                    registration_only
+                );
+                $(self.$holders.insert(&account_id);)?
+                storage_balance
             }
 
             #[payable]
             fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
-                self.$token.storage_withdraw(amount)
+                let storage_balance = self.$token.storage_withdraw(amount);
+                $(self.$dust.record(near_sdk::env::attached_deposit());)?
+                storage_balance
             }
 
             #[payable]
             fn storage_unregister(&mut self, force: Option<bool>) -> bool {
                 #[allow(unused_variables)]
-                if let Some((account_id, balance)) = self.$token.internal_storage_unregister(force) {
+                if let Some((account_id, balance)) = $crate::impl_fungible_token_storage!(
+                    @storage_unregister self, $token, [$($hook)?], [$($refunds)?], force
+                ) {
+                    $(self.$holders.remove(&account_id);)?
                     $(self.$on_account_closed_fn(account_id, balance);)?
                     true
                 } else {
@@ -106,4 +298,64 @@ macro_rules! impl_fungible_token_storage {
             }
         }
     };
+    // The `_with_hook` methods are only called with a hook, as other tokens used with the macro
+    // don't have them.
+    (@storage_deposit $this: ident, $token: ident, [], $account_id: expr,
+        $registration_only: expr) => {
+        $this.$token.storage_deposit(Some($account_id), $registration_only)
+    };
+    (@storage_deposit $this: ident, $token: ident, [$hook: ident], $account_id: expr,
+        $registration_only: expr) => {
+        $this.$token.internal_storage_deposit_with_hook(&mut $this.$hook, Some($account_id))
+    };
+    (@storage_unregister $this: ident, $token: ident, [], [], $force: expr) => {
+        $this.$token.internal_storage_unregister($force)
+    };
+    (@storage_unregister $this: ident, $token: ident, [$hook: ident], [], $force: expr) => {
+        $this.$token.internal_storage_unregister_with_hook(&mut $this.$hook, $force)
+    };
+    (@storage_unregister $this: ident, $token: ident, [$($hook: ident)?], [$refunds: ident],
+        $force: expr) => {{
+        #[allow(unused_variables)]
+        let hook = &mut ();
+        $(let hook = &mut $this.$hook;)?
+        let closed = $this.$token.internal_storage_close_with_hook(hook, $force);
+        if let Some((account_id, _)) = &closed {
+            let deposit = $this.$token.storage_balance_bounds().min.0 + 1;
+            $this.$refunds.transfer(account_id.clone(), deposit);
+        }
+        closed
+    }};
+    ($contract: ident, $token: ident $($rest: tt)*) => {
+        $crate::impl_fungible_token_storage!(@parse $contract, $token, [], [], [], [], $($rest)*);
+    };
+}
+
+/// Exposes `storage_deposit_many` to register many accounts from a single attached deposit.
+/// Takes name of the Contract struct and the inner field for the token, optionally followed by
+/// `hook = <field>` like [`impl_fungible_token_storage`](crate::impl_fungible_token_storage).
+#[macro_export]
+macro_rules! impl_fungible_token_storage_batch {
+    ($contract: ident, $token: ident $(, hook = $hook: ident)?) => {
+        use $crate::storage_management::StorageManagementBatch;
+
+        #[near_bindgen]
+        impl StorageManagementBatch for $contract {
+            #[payable]
+            fn storage_deposit_many(
+                &mut self,
+                account_ids: Vec<AccountId>,
+            ) -> Vec<$crate::storage_management::StorageBalance> {
+                $crate::impl_fungible_token_storage_batch!(
+                    @storage_deposit_many self, $token, [$($hook)?], account_ids
+                )
+            }
+        }
+    };
+    (@storage_deposit_many $this: ident, $token: ident, [], $account_ids: expr) => {
+        $this.$token.storage_deposit_many($account_ids)
+    };
+    (@storage_deposit_many $this: ident, $token: ident, [$hook: ident], $account_ids: expr) => {
+        $this.$token.internal_storage_deposit_many_with_hook(&mut $this.$hook, $account_ids)
+    };
 }
@@ -1,11 +1,15 @@
+use crate::fungible_token::{ParseTokenAmountError, TokenAmount};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
 use near_sdk::json_types::Base64VecU8;
-use near_sdk::require;
+use near_sdk::layout::{StorageLayout, StorageRegion};
 use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, Balance, IntoStorageKey};
 
 pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
 
-#[derive(BorshDeserialize, BorshSerialize, Clone, Deserialize, Serialize)]
+/// Metadata of a fungible token as described in NEP-148, returned by `ft_metadata`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Deserialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FungibleTokenMetadata {
     pub spec: String,
@@ -21,12 +25,104 @@ pub trait FungibleTokenMetadataProvider {
     fn ft_metadata(&self) -> FungibleTokenMetadata;
 }
 
+impl FungibleTokenMetadataProvider for LazyOption<FungibleTokenMetadata> {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        self.get().unwrap_or_else(|| env::panic_str("The metadata is not set"))
+    }
+}
+
 impl FungibleTokenMetadata {
     pub fn assert_valid(&self) {
-        require!(self.spec == FT_METADATA_SPEC);
-        require!(self.reference.is_some() == self.reference_hash.is_some());
+        require!(self.spec == FT_METADATA_SPEC, "Unsupported metadata spec");
+        require!(
+            self.reference.is_some() == self.reference_hash.is_some(),
+            "Reference and reference hash must be set together"
+        );
         if let Some(reference_hash) = &self.reference_hash {
             require!(reference_hash.0.len() == 32, "Hash has to be 32 bytes");
         }
     }
+
+    /// Returns `amount` in the smallest units of this token as a [`TokenAmount`].
+    pub fn amount(&self, amount: Balance) -> TokenAmount {
+        TokenAmount::new(amount, self.decimals)
+    }
+
+    /// Parses a human readable amount like `"1.5"` of this token, see [`TokenAmount::from_human`].
+    pub fn amount_from_human(&self, amount: &str) -> Result<TokenAmount, ParseTokenAmountError> {
+        TokenAmount::from_human(amount, self.decimals)
+    }
+}
+
+/// Metadata of a token kept in a `LazyOption`, so that it's only read by `ft_metadata` rather
+/// than by every method of the contract. The metadata is validated when it's set.
+///
+/// Exposed with [`impl_fungible_token_metadata`](crate::impl_fungible_token_metadata).
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LazyFungibleTokenMetadata {
+    metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+impl LazyFungibleTokenMetadata {
+    pub fn new<S>(prefix: S, metadata: &FungibleTokenMetadata) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        metadata.assert_valid();
+        Self { metadata: LazyOption::new(prefix, Some(metadata)) }
+    }
+
+    /// Replaces the metadata, e.g. to update the icon of the token.
+    pub fn set(&mut self, metadata: &FungibleTokenMetadata) {
+        metadata.assert_valid();
+        self.metadata.set(metadata);
+    }
+}
+
+impl FungibleTokenMetadataProvider for LazyFungibleTokenMetadata {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        self.metadata.ft_metadata()
+    }
+}
+
+impl StorageLayout for LazyFungibleTokenMetadata {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        LazyOption::<FungibleTokenMetadata>::storage_layout(prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Example Token".to_string(),
+            symbol: "EXAMPLE".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        }
+    }
+
+    #[test]
+    fn set_metadata() {
+        let mut lazy_metadata = LazyFungibleTokenMetadata::new(b"m".to_vec(), &metadata());
+        assert_eq!(lazy_metadata.ft_metadata(), metadata());
+        let updated = FungibleTokenMetadata { icon: Some("data:,".to_string()), ..metadata() };
+        lazy_metadata.set(&updated);
+        assert_eq!(lazy_metadata.ft_metadata(), updated);
+    }
+
+    #[test]
+    #[should_panic(expected = "Reference and reference hash must be set together")]
+    fn reference_without_hash() {
+        let metadata = FungibleTokenMetadata {
+            reference: Some("https://example.com".to_string()),
+            ..metadata()
+        };
+        LazyFungibleTokenMetadata::new(b"m".to_vec(), &metadata);
+    }
 }
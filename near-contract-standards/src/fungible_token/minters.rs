@@ -0,0 +1,251 @@
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{paginated_range, UnorderedSet};
+use near_sdk::json_types::U128;
+use near_sdk::layout::{StorageLayout, StorageRegion};
+use near_sdk::{assert_one_yocto, env, require, AccountId, IntoStorageKey};
+
+/// Minting and burning of a fungible token by a set of minter accounts, e.g. the bridges of a
+/// bridged token. Exposed with [`impl_fungible_token_minters`](crate::impl_fungible_token_minters).
+pub trait FungibleTokenMinting {
+    /// Mints `amount` to the account, which must be registered. Requires a deposit of exactly 1
+    /// yoctoNEAR and the predecessor to be a minter.
+    fn ft_mint(&mut self, account_id: AccountId, amount: U128, memo: Option<String>);
+
+    /// Burns `amount` from the account. Requires a deposit of exactly 1 yoctoNEAR and the
+    /// predecessor to be a minter.
+    fn ft_burn(&mut self, account_id: AccountId, amount: U128, memo: Option<String>);
+
+    /// Allows the account to mint and burn. Requires a deposit of exactly 1 yoctoNEAR and the
+    /// predecessor to be the owner. Returns `false` if the account was already a minter.
+    fn ft_add_minter(&mut self, account_id: AccountId) -> bool;
+
+    /// Disallows the account to mint and burn. Requires a deposit of exactly 1 yoctoNEAR and the
+    /// predecessor to be the owner. Returns `false` if the account wasn't a minter.
+    fn ft_remove_minter(&mut self, account_id: AccountId) -> bool;
+
+    fn ft_is_minter(&self, account_id: AccountId) -> bool;
+
+    /// Returns the minters, paginated like the NFT enumeration.
+    fn ft_minters(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId>;
+}
+
+/// Minters of a [`FungibleToken`], managed by an owner. Mints and burns emit the `ft_mint` and
+/// `ft_burn` events through [`FungibleToken::internal_mint_with_hook`] and
+/// [`FungibleToken::internal_burn_with_hook`], which contracts can also call directly for flows
+/// authorized in other ways, e.g. users burning their own tokens.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FungibleTokenMinters {
+    pub owner_id: AccountId,
+    minters: UnorderedSet<AccountId>,
+}
+
+impl FungibleTokenMinters {
+    pub fn new<S>(prefix: S, owner_id: AccountId) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { owner_id, minters: UnorderedSet::new(prefix) }
+    }
+
+    /// Allows the account to mint and burn. Panics if the predecessor isn't the owner.
+    pub fn add_minter(&mut self, account_id: &AccountId) -> bool {
+        self.assert_owner();
+        self.minters.insert(account_id)
+    }
+
+    /// Disallows the account to mint and burn. Panics if the predecessor isn't the owner.
+    pub fn remove_minter(&mut self, account_id: &AccountId) -> bool {
+        self.assert_owner();
+        self.minters.remove(account_id)
+    }
+
+    pub fn is_minter(&self, account_id: &AccountId) -> bool {
+        self.minters.contains(account_id)
+    }
+
+    /// Returns the minters, paginated like the NFT enumeration.
+    pub fn minters(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let range = paginated_range(self.minters.len(), from_index, limit);
+        self.minters.as_vector().to_vec_paginated(range.start, range.end - range.start)
+    }
+
+    /// Panics if the predecessor isn't a minter.
+    pub fn assert_minter(&self) {
+        require!(self.is_minter(&env::predecessor_account_id()), "The predecessor isn't a minter");
+    }
+
+    pub fn ft_mint<H: FungibleTokenTransferHook>(
+        &self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        account_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_minter();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        token.internal_mint_with_hook(hook, &account_id, amount.0, memo.as_deref());
+    }
+
+    pub fn ft_burn<H: FungibleTokenTransferHook>(
+        &self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        account_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_minter();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        token.internal_burn_with_hook(hook, &account_id, amount.0, memo.as_deref());
+    }
+
+    pub fn ft_add_minter(&mut self, account_id: AccountId) -> bool {
+        assert_one_yocto();
+        self.add_minter(&account_id)
+    }
+
+    pub fn ft_remove_minter(&mut self, account_id: AccountId) -> bool {
+        assert_one_yocto();
+        self.remove_minter(&account_id)
+    }
+
+    fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+    }
+}
+
+impl StorageLayout for FungibleTokenMinters {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        UnorderedSet::<AccountId>::storage_layout(prefix)
+    }
+}
+
+/// Exposes the [`FungibleTokenMinting`] methods for the [`FungibleToken`] of the contract.
+/// Takes name of the Contract struct, the inner field for the token and the inner field for the
+/// minters, and optionally `hook = <field>` to call the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) of the
+/// contract on mints and burns.
+#[macro_export]
+macro_rules! impl_fungible_token_minters {
+    ($contract: ident, $token: ident, $minters: ident $(, hook = $hook: ident)?) => {
+        use $crate::fungible_token::minters::FungibleTokenMinting;
+
+        #[near_bindgen]
+        impl FungibleTokenMinting for $contract {
+            #[payable]
+            fn ft_mint(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$minters.ft_mint(&mut self.$token, hook, account_id, amount, memo)
+            }
+
+            #[payable]
+            fn ft_burn(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$minters.ft_burn(&mut self.$token, hook, account_id, amount, memo)
+            }
+
+            #[payable]
+            fn ft_add_minter(&mut self, account_id: AccountId) -> bool {
+                self.$minters.ft_add_minter(account_id)
+            }
+
+            #[payable]
+            fn ft_remove_minter(&mut self, account_id: AccountId) -> bool {
+                self.$minters.ft_remove_minter(account_id)
+            }
+
+            fn ft_is_minter(&self, account_id: AccountId) -> bool {
+                self.$minters.is_minter(&account_id)
+            }
+
+            fn ft_minters(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+                self.$minters.minters(from_index, limit)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, Balance};
+
+    fn set_caller(predecessor: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(1)
+            .build());
+    }
+
+    fn setup() -> (FungibleToken, FungibleTokenMinters) {
+        set_caller(accounts(0));
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(2));
+        let mut minters = FungibleTokenMinters::new(b"m".to_vec(), accounts(0));
+        minters.ft_add_minter(accounts(1));
+        (token, minters)
+    }
+
+    #[test]
+    fn mint_and_burn() {
+        let (mut token, minters) = setup();
+        assert_eq!(minters.minters(None, None), vec![accounts(1)]);
+
+        set_caller(accounts(1));
+        minters.ft_mint(&mut token, &mut (), accounts(2), U128(300), None);
+        minters.ft_burn(&mut token, &mut (), accounts(2), U128(100), Some("withdraw".to_string()));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 200);
+        assert_eq!(token.total_supply, 200);
+    }
+
+    /// Net amount minted per the hook.
+    #[derive(Default)]
+    struct Supply(Balance);
+
+    impl FungibleTokenTransferHook for Supply {
+        fn on_tokens_minted(&mut self, _: &mut FungibleToken, _: &AccountId, amount: Balance) {
+            self.0 += amount;
+        }
+
+        fn on_tokens_burned(&mut self, _: &mut FungibleToken, _: &AccountId, amount: Balance) {
+            self.0 -= amount;
+        }
+    }
+
+    #[test]
+    fn mint_and_burn_call_hook() {
+        let (mut token, minters) = setup();
+        let mut supply = Supply::default();
+        set_caller(accounts(1));
+        minters.ft_mint(&mut token, &mut supply, accounts(2), U128(300), None);
+        minters.ft_burn(&mut token, &mut supply, accounts(2), U128(100), None);
+        assert_eq!(supply.0, token.total_supply);
+    }
+
+    #[test]
+    #[should_panic(expected = "The predecessor isn't a minter")]
+    fn removed_minter() {
+        let (mut token, mut minters) = setup();
+        assert!(minters.ft_remove_minter(accounts(1)));
+        set_caller(accounts(1));
+        minters.ft_mint(&mut token, &mut (), accounts(2), U128(1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn minter_cannot_add_minters() {
+        let (_, mut minters) = setup();
+        set_caller(accounts(1));
+        minters.ft_add_minter(accounts(3));
+    }
+}
@@ -1,11 +1,43 @@
+pub mod account;
+pub mod allowances;
+pub mod amount;
+pub mod batch;
 pub mod core;
 pub mod core_impl;
+pub mod core_impl_v2;
+pub mod deflationary;
+pub mod display;
+pub mod error;
 pub mod events;
+pub mod gas;
+pub mod holders;
+pub mod hook;
+pub mod legacy_wrapper;
 pub mod macros;
 pub mod metadata;
+pub mod minters;
+pub mod native_wrapper;
+pub mod operators;
+pub mod rebasing;
 pub mod receiver;
 pub mod resolver;
 pub mod storage_impl;
+pub mod storage_impl_v2;
+pub mod swap_receiver;
+pub mod transfer_fee;
+pub mod transfer_limits;
+pub mod versioned;
 
+pub use allowances::FungibleTokenAllowances;
+pub use amount::{ParseTokenAmountError, TokenAmount};
 pub use core_impl::FungibleToken;
+pub use core_impl_v2::FungibleTokenV2;
+pub use error::FtTransferError;
+pub use holders::FungibleTokenHolders;
 pub use macros::*;
+pub use minters::FungibleTokenMinters;
+pub use operators::FungibleTokenOperators;
+pub use rebasing::RebasingFungibleToken;
+pub use transfer_fee::TransferFee;
+pub use transfer_limits::TransferLimits;
+pub use versioned::VersionedFungibleToken;
@@ -0,0 +1,224 @@
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use crate::storage_management::StorageManagement;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, require, AccountId, Balance, ContractError, FunctionError, Promise,
+};
+
+/// Wrapping of NEAR into a fungible token, 1 yoctoNEAR for 1 token, like wNEAR. This extends the
+/// fungible token standard and is not part of it. Exposed with
+/// [`impl_fungible_token_native_wrapper`](crate::impl_fungible_token_native_wrapper).
+pub trait NativeWrapper {
+    /// Mints the attached deposit to the predecessor. If the predecessor isn't registered, it's
+    /// registered first and the storage deposit is taken from the attached deposit, which must
+    /// cover it.
+    fn near_deposit(&mut self);
+
+    /// Burns `amount` from the predecessor and transfers as much NEAR to it.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn near_withdraw(&mut self, amount: U128) -> Promise;
+}
+
+impl FungibleToken {
+    /// Mints `amount` minus the storage deposit of the account if it isn't registered, in which
+    /// case it's registered. Returns the minted amount.
+    pub fn internal_near_deposit(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        self.internal_near_deposit_with_hook(&mut (), account_id, amount)
+    }
+
+    /// Same as [`internal_near_deposit`](Self::internal_near_deposit), calling the hook when the
+    /// account is registered and the tokens are minted.
+    pub fn internal_near_deposit_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Balance {
+        let mut amount = amount;
+        if !self.accounts.contains_key(account_id) {
+            let min_balance = self.storage_balance_bounds().min.0;
+            if amount < min_balance {
+                ContractError::insufficient_deposit(min_balance).panic()
+            }
+            self.internal_register_account(account_id);
+            hook.on_account_registered(self, account_id);
+            amount -= min_balance;
+        }
+        if amount > 0 {
+            self.internal_mint_with_hook(hook, account_id, amount, Some("near_deposit"));
+        }
+        amount
+    }
+
+    /// Burns `amount` from the account and returns the transfer of as much NEAR to it.
+    pub fn internal_near_withdraw(&mut self, account_id: &AccountId, amount: Balance) -> Promise {
+        self.internal_near_withdraw_with_hook(&mut (), account_id, amount)
+    }
+
+    /// Same as [`internal_near_withdraw`](Self::internal_near_withdraw), calling the hook when
+    /// the tokens are burned.
+    pub fn internal_near_withdraw_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Promise {
+        require!(amount > 0, "The amount should be a positive number");
+        self.internal_burn_with_hook(hook, account_id, amount, Some("near_withdraw"));
+        Promise::new(account_id.clone()).transfer(amount)
+    }
+}
+
+impl NativeWrapper for FungibleToken {
+    fn near_deposit(&mut self) {
+        let amount = env::attached_deposit();
+        require!(amount > 0, "Requires positive attached deposit");
+        self.internal_near_deposit(&env::predecessor_account_id(), amount);
+    }
+
+    fn near_withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.internal_near_withdraw(&env::predecessor_account_id(), amount.0)
+    }
+}
+
+/// Exposes the [`NativeWrapper`] methods for the [`FungibleToken`] of the contract, whose total
+/// supply is then backed by the NEAR balance of the contract.
+/// Takes name of the Contract struct, the inner field for the token and optionally
+/// `hook = <field>` to call the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) of the
+/// contract on registrations, mints and burns.
+#[macro_export]
+macro_rules! impl_fungible_token_native_wrapper {
+    ($contract: ident, $token: ident $(, hook = $hook: ident)?) => {
+        use $crate::fungible_token::native_wrapper::NativeWrapper;
+
+        #[near_bindgen]
+        impl NativeWrapper for $contract {
+            #[payable]
+            fn near_deposit(&mut self) {
+                let amount = near_sdk::env::attached_deposit();
+                near_sdk::require!(amount > 0, "Requires positive attached deposit");
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_near_deposit_with_hook(
+                    hook,
+                    &near_sdk::env::predecessor_account_id(),
+                    amount,
+                );
+            }
+
+            #[payable]
+            fn near_withdraw(&mut self, amount: U128) -> near_sdk::Promise {
+                near_sdk::assert_one_yocto();
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$token.internal_near_withdraw_with_hook(
+                    hook,
+                    &near_sdk::env::predecessor_account_id(),
+                    amount.0,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn set_caller(attached_deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(attached_deposit)
+            .build());
+    }
+
+    #[test]
+    fn deposit_registers_account() {
+        set_caller(0);
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let min_balance = token.storage_balance_bounds().min.0;
+
+        set_caller(min_balance + 100);
+        token.near_deposit();
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 100);
+
+        set_caller(50);
+        token.near_deposit();
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 150);
+        assert_eq!(token.total_supply, 150);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"bob","amount":"50","memo":"near_deposit"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "INSUFFICIENT_DEPOSIT")]
+    fn deposit_below_storage_deposit() {
+        set_caller(1);
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.near_deposit();
+    }
+
+    #[test]
+    fn withdraw_transfers_near() {
+        set_caller(0);
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(1), 100);
+
+        set_caller(1);
+        token.near_withdraw(U128(40));
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 60);
+        assert_eq!(token.total_supply, 60);
+        let receipt = get_created_receipts().pop().unwrap();
+        assert_eq!(receipt.receiver_id, accounts(1));
+        assert_eq!(receipt.actions, vec![VmAction::Transfer { deposit: 40 }]);
+    }
+
+    /// Accounts registered and net amount minted per the hook.
+    #[derive(Default)]
+    struct Recorder {
+        registered: Vec<AccountId>,
+        supply: Balance,
+    }
+
+    impl FungibleTokenTransferHook for Recorder {
+        fn on_account_registered(&mut self, _: &mut FungibleToken, account_id: &AccountId) {
+            self.registered.push(account_id.clone());
+        }
+
+        fn on_tokens_minted(&mut self, _: &mut FungibleToken, _: &AccountId, amount: Balance) {
+            self.supply += amount;
+        }
+
+        fn on_tokens_burned(&mut self, _: &mut FungibleToken, _: &AccountId, amount: Balance) {
+            self.supply -= amount;
+        }
+    }
+
+    #[test]
+    fn deposit_and_withdraw_call_hook() {
+        set_caller(0);
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let min_balance = token.storage_balance_bounds().min.0;
+        let mut recorder = Recorder::default();
+
+        token.internal_near_deposit_with_hook(&mut recorder, &accounts(1), min_balance + 100);
+        token.internal_near_withdraw_with_hook(&mut recorder, &accounts(1), 40);
+        assert_eq!(recorder.registered, vec![accounts(1)]);
+        assert_eq!(recorder.supply, 60);
+    }
+}
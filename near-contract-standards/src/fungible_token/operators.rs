@@ -0,0 +1,386 @@
+use crate::fungible_token::gas::FtTransferCallGas;
+use crate::fungible_token::hook::FungibleTokenTransferHook;
+use crate::fungible_token::FungibleToken;
+use crate::non_fungible_token::refund_deposit_to_account;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_at_least_one_yocto, assert_one_yocto, env, refund_freed_storage, require, AccountId,
+    Balance, IntoStorageKey, PromiseOrValue,
+};
+
+/// Operator of an account as returned by the `ft_operator` view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OperatorInfo {
+    /// Amount the operator can still transfer, or `None` if it's unlimited.
+    pub remaining_cap: Option<U128>,
+}
+
+/// Operators which transfer tokens on behalf of the accounts which added them, without
+/// allowances per amount, e.g. for custodial services and game servers. Exposed with
+/// [`impl_fungible_token_operators`](crate::impl_fungible_token_operators).
+pub trait FungibleTokenOperatorManagement {
+    /// Allows `operator_id` to transfer tokens of the predecessor, up to `cap` in total if it's
+    /// set. Replaces the cap if the operator was already added. Requires a deposit of at least 1
+    /// yoctoNEAR which covers the storage of the operator, the rest is refunded.
+    fn ft_add_operator(&mut self, operator_id: AccountId, cap: Option<U128>);
+
+    /// Removes the operator of the predecessor and refunds its storage. Requires a deposit of
+    /// exactly 1 yoctoNEAR.
+    fn ft_remove_operator(&mut self, operator_id: AccountId);
+
+    /// Returns the operator of the owner, or `None` if the account isn't an operator of the owner.
+    fn ft_operator(&self, owner_id: AccountId, operator_id: AccountId) -> Option<OperatorInfo>;
+
+    /// Transfers tokens of `owner_id` as its operator, like `ft_transfer`. Requires a deposit of
+    /// exactly 1 yoctoNEAR.
+    fn ft_operator_transfer(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    );
+
+    /// Transfers tokens of `owner_id` as its operator, like `ft_transfer_call`. Unused tokens are
+    /// refunded to the owner, but don't restore the cap of the operator. Requires a deposit of
+    /// exactly 1 yoctoNEAR.
+    fn ft_operator_transfer_call(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+/// Operators of the accounts of a [`FungibleToken`], with an optional cap on the total amount
+/// each operator can transfer.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FungibleTokenOperators {
+    /// (Owner, Operator) -> Remaining cap, `None` if unlimited.
+    pub operators: LookupMap<(AccountId, AccountId), Option<Balance>>,
+}
+
+impl FungibleTokenOperators {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { operators: LookupMap::new(prefix) }
+    }
+
+    /// Returns the remaining cap of the operator, `Some(None)` if it's unlimited, or `None` if the
+    /// account isn't an operator of the owner.
+    pub fn remaining_cap(
+        &self,
+        owner_id: &AccountId,
+        operator_id: &AccountId,
+    ) -> Option<Option<Balance>> {
+        self.operators.get(&(owner_id.clone(), operator_id.clone()))
+    }
+
+    /// Adds or updates the operator of the owner and returns the storage it used in bytes.
+    pub fn internal_add(
+        &mut self,
+        owner_id: &AccountId,
+        operator_id: &AccountId,
+        cap: Option<Balance>,
+    ) -> u64 {
+        require!(owner_id != operator_id, "The owner can't be its own operator");
+        let initial_storage_usage = env::storage_usage();
+        self.operators.insert(&(owner_id.clone(), operator_id.clone()), &cap);
+        env::storage_usage().saturating_sub(initial_storage_usage)
+    }
+
+    /// Removes the operator of the owner, returning `true` if it was an operator.
+    pub fn internal_remove(&mut self, owner_id: &AccountId, operator_id: &AccountId) -> bool {
+        self.operators.remove(&(owner_id.clone(), operator_id.clone())).is_some()
+    }
+
+    /// Panics if `operator_id` isn't an operator of the owner or `amount` exceeds its remaining
+    /// cap, otherwise deducts `amount` from the cap.
+    pub fn assert_operator_transfer(
+        &mut self,
+        owner_id: &AccountId,
+        operator_id: &AccountId,
+        amount: Balance,
+    ) {
+        let key = (owner_id.clone(), operator_id.clone());
+        let cap = self
+            .operators
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("The predecessor isn't an operator of the owner"));
+        if let Some(cap) = cap {
+            let remaining_cap = cap
+                .checked_sub(amount)
+                .unwrap_or_else(|| env::panic_str("The amount exceeds the operator cap"));
+            self.operators.insert(&key, &Some(remaining_cap));
+        }
+    }
+
+    pub fn ft_add_operator(&mut self, operator_id: AccountId, cap: Option<U128>) {
+        assert_at_least_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let storage_used = self.internal_add(&owner_id, &operator_id, cap.map(|cap| cap.0));
+        refund_deposit_to_account(storage_used, owner_id);
+    }
+
+    pub fn ft_remove_operator(&mut self, operator_id: AccountId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        require!(self.internal_remove(&owner_id, &operator_id), "The account isn't an operator");
+        refund_freed_storage(owner_id, initial_storage_usage - env::storage_usage());
+    }
+
+    pub fn ft_operator(&self, owner_id: AccountId, operator_id: AccountId) -> Option<OperatorInfo> {
+        self.remaining_cap(&owner_id, &operator_id)
+            .map(|cap| OperatorInfo { remaining_cap: cap.map(U128) })
+    }
+
+    /// Transfers the tokens of the owner through the hook, so that the checks of the contract
+    /// on transfers, e.g. a pause or frozen accounts, also apply to operators.
+    pub fn ft_operator_transfer<H: FungibleTokenTransferHook>(
+        &mut self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_operator_transfer(&owner_id, &env::predecessor_account_id(), amount.0);
+        token.internal_transfer_with_hook(hook, &owner_id, &receiver_id, amount.0, memo);
+    }
+
+    /// Same as [`ft_operator_transfer`](Self::ft_operator_transfer), calling `ft_on_transfer` on
+    /// the receiver with the gas amounts of `gas`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn ft_operator_transfer_call<H: FungibleTokenTransferHook>(
+        &mut self,
+        token: &mut FungibleToken,
+        hook: &mut H,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+        gas: &FtTransferCallGas,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        self.assert_operator_transfer(&owner_id, &env::predecessor_account_id(), amount.0);
+        token.internal_transfer_call_with_hook(
+            hook,
+            owner_id,
+            receiver_id,
+            amount.0,
+            memo,
+            msg,
+            gas,
+        )
+    }
+}
+
+/// Exposes the [`FungibleTokenOperatorManagement`] methods for the [`FungibleToken`] of the
+/// contract.
+/// Takes name of the Contract struct, the inner field for the token and the inner field for the
+/// operators, and optionally `hook = <field>` to call the
+/// [`FungibleTokenTransferHook`](crate::fungible_token::hook::FungibleTokenTransferHook) of the
+/// contract around operator transfers, which should be the hook of
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core).
+#[macro_export]
+macro_rules! impl_fungible_token_operators {
+    ($contract: ident, $token: ident, $operators: ident $(, hook = $hook: ident)?) => {
+        use $crate::fungible_token::operators::{FungibleTokenOperatorManagement, OperatorInfo};
+
+        #[near_bindgen]
+        impl FungibleTokenOperatorManagement for $contract {
+            #[payable]
+            fn ft_add_operator(&mut self, operator_id: AccountId, cap: Option<U128>) {
+                self.$operators.ft_add_operator(operator_id, cap)
+            }
+
+            #[payable]
+            fn ft_remove_operator(&mut self, operator_id: AccountId) {
+                self.$operators.ft_remove_operator(operator_id)
+            }
+
+            fn ft_operator(
+                &self,
+                owner_id: AccountId,
+                operator_id: AccountId,
+            ) -> Option<OperatorInfo> {
+                self.$operators.ft_operator(owner_id, operator_id)
+            }
+
+            #[payable]
+            fn ft_operator_transfer(
+                &mut self,
+                owner_id: AccountId,
+                receiver_id: AccountId,
+                amount: U128,
+                memo: Option<String>,
+            ) {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$operators.ft_operator_transfer(
+                    &mut self.$token,
+                    hook,
+                    owner_id,
+                    receiver_id,
+                    amount,
+                    memo,
+                )
+            }
+
+            #[payable]
+            fn ft_operator_transfer_call(
+                &mut self,
+                owner_id: AccountId,
+                receiver_id: AccountId,
+                amount: U128,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<U128> {
+                #[allow(unused_variables)]
+                let hook = &mut ();
+                $(let hook = &mut self.$hook;)?
+                self.$operators.ft_operator_transfer_call(
+                    &mut self.$token,
+                    hook,
+                    owner_id,
+                    receiver_id,
+                    amount,
+                    memo,
+                    msg,
+                    &$crate::fungible_token::gas::FtTransferCallGas::default(),
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pause::Pause;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    fn setup() -> (FungibleToken, FungibleTokenOperators) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(ONE_NEAR)
+            .build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(2));
+        token.internal_deposit(&accounts(0), 1_000);
+        (token, FungibleTokenOperators::new(b"o".to_vec()))
+    }
+
+    fn set_caller(predecessor: AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(1)
+            .build());
+    }
+
+    #[test]
+    fn operator_transfers_up_to_cap() {
+        let (mut token, mut operators) = setup();
+        operators.ft_add_operator(accounts(1), Some(U128(300)));
+        assert_eq!(
+            operators.ft_operator(accounts(0), accounts(1)),
+            Some(OperatorInfo { remaining_cap: Some(U128(300)) })
+        );
+
+        set_caller(accounts(1));
+        operators.ft_operator_transfer(
+            &mut token,
+            &mut (),
+            accounts(0),
+            accounts(2),
+            U128(200),
+            None,
+        );
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(2)), 200);
+        assert_eq!(operators.remaining_cap(&accounts(0), &accounts(1)), Some(Some(100)));
+
+        set_caller(accounts(0));
+        operators.ft_remove_operator(accounts(1));
+        assert_eq!(operators.ft_operator(accounts(0), accounts(1)), None);
+    }
+
+    #[test]
+    fn unlimited_operator() {
+        let (mut token, mut operators) = setup();
+        operators.ft_add_operator(accounts(1), None);
+        set_caller(accounts(1));
+        operators.ft_operator_transfer(
+            &mut token,
+            &mut (),
+            accounts(0),
+            accounts(2),
+            U128(1_000),
+            None,
+        );
+        assert_eq!(operators.remaining_cap(&accounts(0), &accounts(1)), Some(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount exceeds the operator cap")]
+    fn exceeds_cap() {
+        let (mut token, mut operators) = setup();
+        operators.ft_add_operator(accounts(1), Some(U128(100)));
+        set_caller(accounts(1));
+        operators.ft_operator_transfer(
+            &mut token,
+            &mut (),
+            accounts(0),
+            accounts(2),
+            U128(101),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The predecessor isn't an operator of the owner")]
+    fn not_an_operator() {
+        let (mut token, mut operators) = setup();
+        set_caller(accounts(1));
+        operators.ft_operator_transfer(
+            &mut token,
+            &mut (),
+            accounts(0),
+            accounts(2),
+            U128(1),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn paused_token_cannot_be_drained() {
+        let (mut token, mut operators) = setup();
+        operators.ft_add_operator(accounts(1), None);
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        set_caller(accounts(1));
+        operators.ft_operator_transfer(
+            &mut token,
+            &mut pause,
+            accounts(0),
+            accounts(2),
+            U128(1),
+            None,
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use crate::fungible_token::core::FungibleTokenCore;
+use crate::fungible_token::core_impl::{
+    ext_fungible_token_receiver, ext_self, unused_amount_of_result,
+};
+use crate::fungible_token::events::{FtBurn, FtTransfer};
+use crate::fungible_token::gas::FtTransferCallGas;
+use crate::fungible_token::resolver::FungibleTokenResolver;
+use crate::fungible_token::FungibleToken;
+use crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, require, AccountId, Balance, IntoStorageKey, PromiseOrValue,
+};
+
+/// Denominator of [`RebasingFungibleToken::index`], i.e. the index of a token where one share is
+/// worth one token.
+pub const INDEX_DENOMINATOR: Balance = 1_000_000_000_000_000_000_000_000;
+
+/// Implementation of a FungibleToken standard for interest-bearing or rebasing tokens, such as
+/// liquid staking or lending receipts.
+///
+/// Accounts hold shares, and balances are the shares converted with a global index, the amount of
+/// tokens per share scaled by [`INDEX_DENOMINATOR`]. When the contract updates the index with
+/// [`set_index`](Self::set_index), e.g. from an admin method or an accrual hook, the balances of
+/// all accounts and the total supply change without touching the accounts.
+///
+/// The shares are the balances of an inner [`FungibleToken`], which registers the accounts and
+/// manages their storage. The token implements the same NEP-141 and NEP-145 traits as
+/// [`FungibleToken`] in tokens rather than shares, and can be used with
+/// `impl_fungible_token_core!` and `impl_fungible_token_storage!`.
+///
+/// Amounts are converted to shares rounding in favor of the contract: deposits round down and
+/// withdrawals round up, so a deposit can show up as a balance 1 lower than the deposited amount.
+/// A transfer moves the shares required to withdraw the amount from the sender, so the receiver
+/// can get slightly more than the amount, and the `ft_transfer` event logs the amount the moved
+/// shares are worth.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RebasingFungibleToken {
+    /// Token whose balances are the shares of the accounts.
+    pub token: FungibleToken,
+
+    /// Amount of tokens per share, scaled by [`INDEX_DENOMINATOR`].
+    pub index: Balance,
+}
+
+impl RebasingFungibleToken {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { token: FungibleToken::new(prefix), index: INDEX_DENOMINATOR }
+    }
+
+    /// Sets the amount of tokens per share, scaled by [`INDEX_DENOMINATOR`]. The contract is
+    /// responsible for restricting who can update it.
+    pub fn set_index(&mut self, index: Balance) {
+        require!(index > 0, "The index should be a positive number");
+        self.index = index;
+    }
+
+    /// Amount of tokens the given shares are worth, rounded down.
+    pub fn amount_from_shares(&self, shares: Balance) -> Balance {
+        mul_div(shares, self.index, INDEX_DENOMINATOR)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"))
+    }
+
+    /// Shares the given amount of tokens is worth, rounded down.
+    pub fn shares_from_amount(&self, amount: Balance) -> Balance {
+        mul_div(amount, INDEX_DENOMINATOR, self.index)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"))
+    }
+
+    /// Shares required to withdraw the given amount of tokens, rounded up.
+    fn shares_to_withdraw(&self, amount: Balance) -> Balance {
+        mul_div_ceil(amount, INDEX_DENOMINATOR, self.index)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"))
+    }
+
+    pub fn internal_unwrap_shares_of(&self, account_id: &AccountId) -> Balance {
+        self.token.internal_unwrap_balance_of(account_id)
+    }
+
+    /// Mints shares worth `amount` to the account. Returns the minted shares.
+    pub fn internal_deposit(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        let shares = self.shares_from_amount(amount);
+        self.token.internal_deposit(account_id, shares);
+        shares
+    }
+
+    /// Burns the shares required to withdraw `amount` from the account. Returns the burned shares.
+    pub fn internal_withdraw(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        let shares = self.shares_to_withdraw(amount);
+        self.token.internal_withdraw(account_id, shares);
+        shares
+    }
+
+    /// Transfers the shares required to withdraw `amount` from the sender to the receiver.
+    /// Returns the amount the shares are worth, which is logged in the `ft_transfer` event.
+    pub fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        require!(sender_id != receiver_id, "Sender and receiver should be different");
+        require!(amount > 0, "The amount should be a positive number");
+        let shares = self.shares_to_withdraw(amount);
+        self.token.internal_withdraw(sender_id, shares);
+        self.token.internal_deposit(receiver_id, shares);
+        let amount = self.amount_from_shares(shares);
+        FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &U128(amount),
+            memo: memo.as_deref(),
+        }
+        .emit();
+        amount
+    }
+
+    pub fn internal_register_account(&mut self, account_id: &AccountId) {
+        self.token.internal_register_account(account_id);
+    }
+
+    /// Internal method that returns the amount of burned tokens in a corner case when the sender
+    /// has deleted (unregistered) their account while the `ft_transfer_call` was still in flight.
+    /// Returns (Used token amount, Burned token amount)
+    pub fn internal_ft_resolve_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> (u128, u128) {
+        let amount: Balance = amount.into();
+        let unused_amount = unused_amount_of_result(0, amount);
+        if unused_amount > 0 {
+            let receiver_shares = self.token.accounts.get(&receiver_id).unwrap_or(0);
+            let refund_shares =
+                std::cmp::min(receiver_shares, self.shares_from_amount(unused_amount));
+            if refund_shares > 0 {
+                let refund_amount = self.amount_from_shares(refund_shares);
+                self.token.internal_withdraw(&receiver_id, refund_shares);
+                if self.token.accounts.contains_key(sender_id) {
+                    self.token.internal_deposit(sender_id, refund_shares);
+                    FtTransfer {
+                        old_owner_id: &receiver_id,
+                        new_owner_id: sender_id,
+                        amount: &U128(refund_amount),
+                        memo: Some("refund"),
+                    }
+                    .emit();
+                    return (amount - refund_amount, 0);
+                } else {
+                    // Sender's account was deleted, so we need to burn tokens.
+                    FtBurn {
+                        owner_id: &receiver_id,
+                        amount: &U128(refund_amount),
+                        memo: Some("refund"),
+                    }
+                    .emit();
+                    return (amount, refund_amount);
+                }
+            }
+        }
+        (amount, 0)
+    }
+
+    /// Same as [`FungibleToken::internal_storage_unregister`], with the balance burned when
+    /// `force` is set logged and returned in tokens.
+    pub fn internal_storage_unregister(
+        &mut self,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let account_id = env::predecessor_account_id();
+        let shares = self.token.accounts.get(&account_id).unwrap_or(0);
+        let balance = self.amount_from_shares(shares);
+        if shares > 0 && force.unwrap_or(false) {
+            self.token.internal_withdraw(&account_id, shares);
+            FtBurn { owner_id: &account_id, amount: &U128(balance), memo: Some("unregister") }
+                .emit();
+        }
+        // Panics if the account still has shares without `force`.
+        self.token.internal_storage_unregister(force).map(|(account_id, _)| (account_id, balance))
+    }
+}
+
+impl FungibleTokenCore for RebasingFungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, amount.into(), memo);
+    }
+
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        let gas = FtTransferCallGas::default();
+        let receiver_gas = gas.receiver_gas();
+        let sender_id = env::predecessor_account_id();
+        let amount = self.internal_transfer(&sender_id, &receiver_id, amount.into(), memo);
+        // Initiating receiver's call and the callback
+        ext_fungible_token_receiver::ext(receiver_id.clone())
+            .with_static_gas(receiver_gas)
+            .ft_on_transfer(sender_id.clone(), amount.into(), msg)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(gas.resolve_transfer)
+                    .ft_resolve_transfer(sender_id, receiver_id, amount.into()),
+            )
+            .into()
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.amount_from_shares(self.token.total_supply).into()
+    }
+
+    fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.amount_from_shares(self.token.accounts.get(&account_id).unwrap_or(0)).into()
+    }
+}
+
+impl FungibleTokenResolver for RebasingFungibleToken {
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        self.internal_ft_resolve_transfer(&sender_id, receiver_id, amount).0.into()
+    }
+}
+
+impl StorageManagement for RebasingFungibleToken {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.token.storage_deposit(account_id, registration_only)
+    }
+
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.token.storage_withdraw(amount)
+    }
+
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.internal_storage_unregister(force).is_some()
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.token.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.token.storage_balance_of(account_id)
+    }
+}
+
+/// Computes the 256 bit product of `a` and `b` as (high, low) 128 bit halves.
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+    let low = (p00 & MASK) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (high, low)
+}
+
+/// Computes `a * b / c` as (quotient, remainder) without intermediate overflow. Returns `None` if
+/// `c` is 0 or the quotient doesn't fit into 128 bits.
+fn mul_div_rem(a: u128, b: u128, c: u128) -> Option<(u128, u128)> {
+    let (high, low) = full_mul(a, b);
+    if c == 0 || high >= c {
+        return None;
+    }
+    let (mut quotient, mut remainder) = (0u128, high);
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+/// `a * b / c` rounded down.
+fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_rem(a, b, c).map(|(quotient, _)| quotient)
+}
+
+/// `a * b / c` rounded up.
+fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    mul_div_rem(a, b, c).and_then(|(quotient, remainder)| {
+        if remainder > 0 {
+            quotient.checked_add(1)
+        } else {
+            Some(quotient)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn mul_div_without_overflow() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 3, 4), Some(u128::MAX / 4 * 3 + 2));
+        assert_eq!(mul_div(10, 10, 3), Some(33));
+        assert_eq!(mul_div_ceil(10, 10, 3), Some(34));
+        assert_eq!(mul_div_ceil(10, 10, 5), Some(20));
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div(1, 1, 0), None);
+    }
+
+    #[test]
+    fn balances_follow_index() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = RebasingFungibleToken::new(b"r".to_vec());
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(0), 1_000);
+        token.internal_transfer(&accounts(0), &accounts(1), 400, None);
+        assert_eq!(token.ft_balance_of(accounts(0)).0, 600);
+        assert_eq!(token.ft_total_supply().0, 1_000);
+
+        // 10% accrued.
+        token.set_index(INDEX_DENOMINATOR / 10 * 11);
+        assert_eq!(token.ft_balance_of(accounts(0)).0, 660);
+        assert_eq!(token.ft_balance_of(accounts(1)).0, 440);
+        assert_eq!(token.ft_total_supply().0, 1_100);
+
+        token.internal_withdraw(&accounts(1), 440);
+        assert_eq!(token.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(token.internal_unwrap_shares_of(&accounts(1)), 0);
+    }
+
+    #[test]
+    fn transfers_move_the_shares_to_withdraw() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = RebasingFungibleToken::new(b"r".to_vec());
+        token.set_index(INDEX_DENOMINATOR * 3);
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(1));
+        // Deposits round down: 100 tokens are 33 shares, worth 99 tokens.
+        assert_eq!(token.internal_deposit(&accounts(0), 100), 33);
+        assert_eq!(token.ft_balance_of(accounts(0)).0, 99);
+        // Withdrawals round up: sending 10 tokens takes 4 shares, worth 12 tokens, which is the
+        // amount logged.
+        assert_eq!(token.internal_transfer(&accounts(0), &accounts(1), 10, None), 12);
+        assert_eq!(token.ft_balance_of(accounts(1)).0, 12);
+        assert_eq!(token.ft_balance_of(accounts(0)).0, 87);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"12"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn unregister_burns_tokens() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = RebasingFungibleToken::new(b"r".to_vec());
+        token.set_index(INDEX_DENOMINATOR * 2);
+        token.internal_register_account(&accounts(0));
+        token.internal_deposit(&accounts(0), 100);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        assert_eq!(token.internal_storage_unregister(Some(true)), Some((accounts(0), 100)));
+        assert_eq!(token.ft_total_supply().0, 0);
+        assert!(token.storage_balance_of(accounts(0)).is_none());
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"alice","amount":"100","memo":"unregister"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn withdraw_more_than_balance() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = RebasingFungibleToken::new(b"r".to_vec());
+        token.set_index(INDEX_DENOMINATOR * 3);
+        token.internal_register_account(&accounts(0));
+        token.internal_deposit(&accounts(0), 100);
+        token.internal_withdraw(&accounts(0), 100);
+    }
+}
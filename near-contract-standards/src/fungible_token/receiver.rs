@@ -17,7 +17,8 @@ pub trait FungibleTokenReceiver {
     /// Arguments:
     /// - `sender_id` - the account ID that initiated the transfer.
     /// - `amount` - the amount of tokens that were transferred to this account in a decimal string representation.
-    /// - `msg` - a string message that was passed with this transfer call.
+    /// - `msg` - a string message that was passed with this transfer call. Composable protocols can
+    ///   use [`TransferCallMsg`](crate::transfer_call_msg::TransferCallMsg) as its schema.
     ///
     /// Returns the amount of unused tokens that should be returned to sender, in a decimal string representation.
     fn ft_on_transfer(
@@ -1,7 +1,13 @@
+use crate::fungible_token::events::FtBurn;
+use crate::fungible_token::hook::FungibleTokenTransferHook;
 use crate::fungible_token::FungibleToken;
-use crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use crate::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageError, StorageManagement, StorageManagementBatch,
+};
 use near_sdk::json_types::U128;
-use near_sdk::{assert_one_yocto, env, log, AccountId, Balance, Promise};
+use near_sdk::{
+    assert_min_deposit, assert_one_yocto, env, log, AccountId, Balance, FunctionError, Promise,
+};
 
 impl FungibleToken {
     /// Internal method that returns the Account ID and the balance in case the account was
@@ -9,6 +15,34 @@ impl FungibleToken {
     pub fn internal_storage_unregister(
         &mut self,
         force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        self.internal_storage_unregister_with_hook(&mut (), force)
+    }
+
+    /// Same as [`internal_storage_unregister`](Self::internal_storage_unregister), calling
+    /// `on_tokens_burned` of the hook if a positive balance was burned and `on_account_closed`
+    /// when the account was unregistered.
+    pub fn internal_storage_unregister_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let closed = self.internal_storage_close_with_hook(hook, force);
+        if let Some((account_id, _)) = &closed {
+            Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
+        }
+        closed
+    }
+
+    /// Same as
+    /// [`internal_storage_unregister_with_hook`](Self::internal_storage_unregister_with_hook)
+    /// without returning the storage deposit, which is left to the caller, e.g. to send it
+    /// through a [`RefundQueue`](crate::refund_queue::RefundQueue). The deposit is
+    /// `storage_balance_bounds().min` plus the attached yoctoⓃ.
+    pub fn internal_storage_close_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        force: Option<bool>,
     ) -> Option<(AccountId, Balance)> {
         assert_one_yocto();
         let account_id = env::predecessor_account_id();
@@ -17,12 +51,19 @@ impl FungibleToken {
             if balance == 0 || force {
                 self.accounts.remove(&account_id);
                 self.total_supply -= balance;
-                Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
+                if balance > 0 {
+                    FtBurn {
+                        owner_id: &account_id,
+                        amount: &U128(balance),
+                        memo: Some("unregister"),
+                    }
+                    .emit();
+                    hook.on_tokens_burned(self, &account_id, balance);
+                }
+                hook.on_account_closed(self, &account_id, balance);
                 Some((account_id, balance))
             } else {
-                env::panic_str(
-                    "Can't unregister the account with the positive balance without force",
-                )
+                StorageError::PositiveBalance.panic()
             }
         } else {
             log!("The account {} is not registered", &account_id);
@@ -30,22 +71,12 @@ impl FungibleToken {
         }
     }
 
-    fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
-        if self.accounts.contains_key(account_id) {
-            Some(StorageBalance { total: self.storage_balance_bounds().min, available: 0.into() })
-        } else {
-            None
-        }
-    }
-}
-
-impl StorageManagement for FungibleToken {
-    // `registration_only` doesn't affect the implementation for vanilla fungible token.
-    #[allow(unused_variables)]
-    fn storage_deposit(
+    /// Same as [`storage_deposit`](StorageManagement::storage_deposit), calling
+    /// `on_account_registered` of the hook if the account is registered.
+    pub fn internal_storage_deposit_with_hook<H: FungibleTokenTransferHook>(
         &mut self,
+        hook: &mut H,
         account_id: Option<AccountId>,
-        registration_only: Option<bool>,
     ) -> StorageBalance {
         let amount: Balance = env::attached_deposit();
         let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
@@ -56,11 +87,9 @@ impl StorageManagement for FungibleToken {
             }
         } else {
             let min_balance = self.storage_balance_bounds().min.0;
-            if amount < min_balance {
-                env::panic_str("The attached deposit is less than the minimum storage balance");
-            }
+            assert_min_deposit(min_balance);
 
-            self.internal_register_account(&account_id);
+            self.internal_register_account_with_hook(hook, &account_id);
             let refund = amount - min_balance;
             if refund > 0 {
                 Promise::new(env::predecessor_account_id()).transfer(refund);
@@ -69,6 +98,55 @@ impl StorageManagement for FungibleToken {
         self.internal_storage_balance_of(&account_id).unwrap()
     }
 
+    /// Same as [`storage_deposit_many`](StorageManagementBatch::storage_deposit_many), calling
+    /// `on_account_registered` of the hook for each registered account.
+    pub fn internal_storage_deposit_many_with_hook<H: FungibleTokenTransferHook>(
+        &mut self,
+        hook: &mut H,
+        account_ids: Vec<AccountId>,
+    ) -> Vec<StorageBalance> {
+        let amount: Balance = env::attached_deposit();
+        let min_balance = self.storage_balance_bounds().min.0;
+        let mut used: Balance = 0;
+        for account_id in &account_ids {
+            if self.accounts.contains_key(account_id) {
+                log!("The account {} is already registered", account_id);
+            } else {
+                self.internal_register_account_with_hook(hook, account_id);
+                used += min_balance;
+            }
+        }
+        assert_min_deposit(used);
+        let refund = amount - used;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        account_ids
+            .iter()
+            .map(|account_id| self.internal_storage_balance_of(account_id).unwrap())
+            .collect()
+    }
+
+    fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        if self.accounts.contains_key(account_id) {
+            Some(StorageBalance { total: self.storage_balance_bounds().min, available: 0.into() })
+        } else {
+            None
+        }
+    }
+}
+
+impl StorageManagement for FungibleToken {
+    // `registration_only` doesn't affect the implementation for vanilla fungible token.
+    #[allow(unused_variables)]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.internal_storage_deposit_with_hook(&mut (), account_id)
+    }
+
     /// While storage_withdraw normally allows the caller to retrieve `available` balance, the basic
     /// Fungible Token implementation sets storage_balance_bounds.min == storage_balance_bounds.max,
     /// which means available balance will always be 0. So this implementation:
@@ -81,14 +159,12 @@ impl StorageManagement for FungibleToken {
         if let Some(storage_balance) = self.internal_storage_balance_of(&predecessor_account_id) {
             match amount {
                 Some(amount) if amount.0 > 0 => {
-                    env::panic_str("The amount is greater than the available storage balance");
+                    StorageError::InsufficientAvailableBalance.panic();
                 }
                 _ => storage_balance,
             }
         } else {
-            env::panic_str(
-                format!("The account {} is not registered", &predecessor_account_id).as_str(),
-            );
+            StorageError::AccountNotRegistered(predecessor_account_id).panic();
         }
     }
 
@@ -109,3 +185,91 @@ impl StorageManagement for FungibleToken {
         self.internal_storage_balance_of(&account_id)
     }
 }
+
+impl StorageManagementBatch for FungibleToken {
+    fn storage_deposit_many(&mut self, account_ids: Vec<AccountId>) -> Vec<StorageBalance> {
+        self.internal_storage_deposit_many_with_hook(&mut (), account_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn force_unregister_burns_balance() {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(1), 100);
+
+        assert_eq!(token.internal_storage_unregister(Some(true)), Some((accounts(1), 100)));
+        assert_eq!(token.total_supply, 0);
+        assert!(token.storage_balance_of(accounts(1)).is_none());
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"bob","amount":"100","memo":"unregister"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't unregister the account with the positive balance without force"
+    )]
+    fn unregister_with_balance() {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(1), 100);
+        token.internal_storage_unregister(None);
+    }
+
+    #[test]
+    fn storage_deposit_many() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(1));
+        let min_balance = token.storage_balance_bounds().min;
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(3 * min_balance.0)
+            .build());
+
+        let balances = token.storage_deposit_many(vec![accounts(1), accounts(2), accounts(3)]);
+        assert_eq!(balances.len(), 3);
+        assert!(balances
+            .iter()
+            .all(|balance| balance.total == min_balance && balance.available.0 == 0));
+        assert!(token.accounts.contains_key(&accounts(3)));
+        assert_eq!(get_logs(), vec!["The account bob is already registered"]);
+        let refunds: Vec<_> = get_created_receipts()
+            .into_iter()
+            .map(|receipt| (receipt.receiver_id, receipt.actions))
+            .collect();
+        assert_eq!(
+            refunds,
+            vec![(accounts(0), vec![VmAction::Transfer { deposit: min_balance.0 }])]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of at least")]
+    fn storage_deposit_many_without_enough_deposit() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        let min_balance = token.storage_balance_bounds().min.0;
+        testing_env!(VMContextBuilder::new().attached_deposit(min_balance).build());
+        token.storage_deposit_many(vec![accounts(1), accounts(2)]);
+    }
+}
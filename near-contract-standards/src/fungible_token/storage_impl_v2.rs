@@ -0,0 +1,36 @@
+use crate::fungible_token::FungibleTokenV2;
+use crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+
+impl StorageManagement for FungibleTokenV2 {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.token.storage_deposit(account_id, registration_only)
+    }
+
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.token.storage_withdraw(amount)
+    }
+
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.internal_storage_unregister(force).is_some()
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.token.storage_balance_bounds()
+    }
+
+    /// The total includes the storage of the record of the account, if it has one.
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        let record_cost =
+            if self.records.contains_key(&account_id) { self.record_storage_cost() } else { 0 };
+        self.token.storage_balance_of(account_id).map(|balance| StorageBalance {
+            total: U128(balance.total.0 + record_cost),
+            available: balance.available,
+        })
+    }
+}
@@ -0,0 +1,205 @@
+use crate::fungible_token::receiver::FungibleTokenReceiver;
+use crate::impl_transfer_call_action;
+use crate::transfer_call_msg::TransferCallMsg;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, AccountId, Balance, IntoStorageKey, PromiseOrValue};
+
+/// Parameters of the `swap` action of a [`TransferCallMsg`] sent to a [`SwapReceiver`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapAction {
+    /// Token expected in exchange for the transferred token.
+    pub token_out: AccountId,
+    /// Minimum amount of `token_out`, the swap is cancelled if it would return less.
+    pub min_amount_out: U128,
+    /// Amount of the transferred tokens to swap, all of them if omitted. The rest is returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_in: Option<U128>,
+}
+
+impl_transfer_call_action!(SwapAction, "swap");
+
+/// Fixed rate pool swapping the token it is registered for into `token_out`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct SwapPool {
+    pub token_out: AccountId,
+    /// Amount of `token_out` available for swaps.
+    pub reserve_out: Balance,
+    /// Amount of `token_out` paid per `rate_denominator` tokens in.
+    pub rate_numerator: Balance,
+    pub rate_denominator: Balance,
+}
+
+/// Reference receiver of `ft_transfer_call` routing messages, which swaps the received tokens
+/// with a fixed rate [`SwapPool`] and credits the output to an internal balance of the sender.
+///
+/// It serves as the canonical fixture for the refund semantics of transfer calls: the unused
+/// amount is returned from `ft_on_transfer` whenever the swap can't take all of the tokens,
+/// i.e. the whole amount if the message is invalid, the pool is unknown or can't pay
+/// `min_amount_out`, and the rest if only `amount_in` is swapped.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SwapReceiver {
+    /// Token in -> Pool.
+    pub pools: LookupMap<AccountId, SwapPool>,
+    /// (Account, Token) -> Balance of swapped tokens.
+    pub deposits: LookupMap<(AccountId, AccountId), Balance>,
+}
+
+impl SwapReceiver {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            pools: LookupMap::new([prefix.as_slice(), b"p"].concat()),
+            deposits: LookupMap::new([prefix.as_slice(), b"d"].concat()),
+        }
+    }
+
+    /// Sets the pool swapping `token_in`.
+    pub fn set_pool(&mut self, token_in: &AccountId, pool: SwapPool) {
+        self.pools.insert(token_in, &pool);
+    }
+
+    /// Returns the balance of swapped `token_id` tokens of the account.
+    pub fn deposit_of(&self, account_id: &AccountId, token_id: &AccountId) -> Balance {
+        self.deposits.get(&(account_id.clone(), token_id.clone())).unwrap_or(0)
+    }
+
+    /// Handles `amount` of `token_in` received from `sender_id` with the transfer message `msg`.
+    /// Returns the amount of unused tokens.
+    pub fn internal_on_transfer(
+        &mut self,
+        token_in: &AccountId,
+        sender_id: &AccountId,
+        amount: Balance,
+        msg: &str,
+    ) -> Balance {
+        let action =
+            match TransferCallMsg::parse(msg).ok().and_then(|msg| msg.params::<SwapAction>()) {
+                Some(action) => action,
+                None => {
+                    log!("Invalid swap message, refunding {}", amount);
+                    return amount;
+                }
+            };
+        let mut pool = match self.pools.get(token_in) {
+            Some(pool) if pool.token_out == action.token_out => pool,
+            _ => {
+                log!("No pool from {} to {}, refunding {}", token_in, action.token_out, amount);
+                return amount;
+            }
+        };
+        let amount_in = action.amount_in.map_or(amount, |amount_in| amount_in.0.min(amount));
+        let amount_out = amount_in
+            .checked_mul(pool.rate_numerator)
+            .and_then(|amount_out| amount_out.checked_div(pool.rate_denominator))
+            .unwrap_or_else(|| env::panic_str("Invalid swap rate"));
+        if amount_out < action.min_amount_out.0 || amount_out > pool.reserve_out {
+            log!("Swap of {} would return {}, refunding {}", amount_in, amount_out, amount);
+            return amount;
+        }
+
+        pool.reserve_out -= amount_out;
+        self.pools.insert(token_in, &pool);
+        let key = (sender_id.clone(), pool.token_out);
+        let deposit = self.deposits.get(&key).unwrap_or(0);
+        self.deposits.insert(&key, &(deposit + amount_out));
+        log!("Swapped {} {} for {} {}", amount_in, token_in, amount_out, key.1);
+        amount - amount_in
+    }
+}
+
+impl FungibleTokenReceiver for SwapReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token_in = env::predecessor_account_id();
+        PromiseOrValue::Value(
+            self.internal_on_transfer(&token_in, &sender_id, amount.0, &msg).into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn token_in() -> AccountId {
+        accounts(3)
+    }
+
+    fn token_out() -> AccountId {
+        accounts(4)
+    }
+
+    fn setup() -> SwapReceiver {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(token_in()).build());
+        let mut receiver = SwapReceiver::new(b"s".to_vec());
+        receiver.set_pool(
+            &token_in(),
+            SwapPool {
+                token_out: token_out(),
+                reserve_out: 1_000,
+                rate_numerator: 2,
+                rate_denominator: 1,
+            },
+        );
+        receiver
+    }
+
+    fn swap(min_amount_out: u128, amount_in: Option<u128>) -> String {
+        TransferCallMsg::new(&SwapAction {
+            token_out: token_out(),
+            min_amount_out: U128(min_amount_out),
+            amount_in: amount_in.map(U128),
+        })
+        .to_msg()
+    }
+
+    fn on_transfer(receiver: &mut SwapReceiver, amount: u128, msg: String) -> u128 {
+        match receiver.ft_on_transfer(accounts(0), U128(amount), msg) {
+            PromiseOrValue::Value(unused) => unused.0,
+            PromiseOrValue::Promise(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn swaps_all() {
+        let mut receiver = setup();
+        assert_eq!(on_transfer(&mut receiver, 100, swap(200, None)), 0);
+        assert_eq!(receiver.deposit_of(&accounts(0), &token_out()), 200);
+        assert_eq!(receiver.pools.get(&token_in()).unwrap().reserve_out, 800);
+    }
+
+    #[test]
+    fn returns_rest_of_amount_in() {
+        let mut receiver = setup();
+        assert_eq!(on_transfer(&mut receiver, 100, swap(0, Some(30))), 70);
+        assert_eq!(receiver.deposit_of(&accounts(0), &token_out()), 60);
+    }
+
+    #[test]
+    fn refunds_all() {
+        let mut receiver = setup();
+        // Invalid message.
+        assert_eq!(on_transfer(&mut receiver, 100, "swap".to_string()), 100);
+        // Below `min_amount_out`.
+        assert_eq!(on_transfer(&mut receiver, 100, swap(201, None)), 100);
+        // Not enough reserve.
+        assert_eq!(on_transfer(&mut receiver, 600, swap(0, None)), 600);
+        // Unknown pool.
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(5)).build());
+        assert_eq!(on_transfer(&mut receiver, 100, swap(0, None)), 100);
+        assert_eq!(receiver.deposit_of(&accounts(0), &token_out()), 0);
+    }
+}
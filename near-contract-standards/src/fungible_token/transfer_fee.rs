@@ -0,0 +1,211 @@
+use crate::fungible_token::deflationary::BPS_DENOMINATOR;
+use crate::fungible_token::hook::{FtHookTransfer, FtTransferKind, FungibleTokenTransferHook};
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, require, AccountId, Balance};
+
+/// Fee on transfers as returned by the `ft_transfer_fee` view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferFeeConfig {
+    /// Fee in basis points of the transferred amount.
+    pub fee_bps: u16,
+    /// Account receiving the fees.
+    pub treasury_id: AccountId,
+}
+
+/// Management of the fee of a token, exposed with
+/// [`impl_fungible_token_transfer_fee`](crate::impl_fungible_token_transfer_fee).
+pub trait TransferFeeManagement {
+    fn ft_transfer_fee(&self) -> TransferFeeConfig;
+
+    /// Sets the fee. Requires a deposit of exactly 1 yoctoNEAR and the predecessor to be the
+    /// owner of the fee.
+    fn ft_set_transfer_fee(&mut self, config: TransferFeeConfig);
+}
+
+/// Fee of `fee_bps` basis points on the transfers of a [`FungibleToken`], sent to the
+/// `treasury_id` account on top of the transferred amount, so the receiver gets the full amount
+/// and the sender needs a balance covering both.
+///
+/// The fee is a [`FungibleTokenTransferHook`], charged as an `ft_transfer` to the treasury with
+/// the `fee` memo after the transfers of the methods given the fee with `hook = <field>`, e.g.
+/// `ft_transfer` and `ft_transfer_call` of
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core) and the batch transfers of
+/// [`impl_fungible_token_batch`](crate::impl_fungible_token_batch). Transfers of methods without
+/// the hook and direct calls of [`FungibleToken::internal_transfer`] aren't charged. Refunds of
+/// `ft_transfer_call` and transfers from the treasury are free, and the fee is rounded down.
+///
+/// The treasury must be registered with the token when the fee is created or changed, and stay
+/// registered, otherwise the charged transfers fail. Only the `owner_id` of the fee can change
+/// it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TransferFee {
+    pub owner_id: AccountId,
+    pub fee_bps: u16,
+    pub treasury_id: AccountId,
+}
+
+impl TransferFee {
+    /// Creates a fee managed by `owner_id`. Panics if `fee_bps` exceeds 10000 or the treasury
+    /// isn't registered with the token.
+    pub fn new(
+        token: &FungibleToken,
+        owner_id: AccountId,
+        fee_bps: u16,
+        treasury_id: AccountId,
+    ) -> Self {
+        assert_valid_fee_bps(fee_bps);
+        assert_registered_treasury(token, &treasury_id);
+        Self { owner_id, fee_bps, treasury_id }
+    }
+
+    pub fn config(&self) -> TransferFeeConfig {
+        TransferFeeConfig { fee_bps: self.fee_bps, treasury_id: self.treasury_id.clone() }
+    }
+
+    /// Sets the fee. Panics if the predecessor isn't the owner, `fee_bps` exceeds 10000 or the
+    /// treasury isn't registered with the token.
+    pub fn set_config(&mut self, token: &FungibleToken, config: TransferFeeConfig) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+        assert_valid_fee_bps(config.fee_bps);
+        assert_registered_treasury(token, &config.treasury_id);
+        self.fee_bps = config.fee_bps;
+        self.treasury_id = config.treasury_id;
+    }
+
+    /// Returns the fee of a transfer of `amount`.
+    pub fn fee_of(&self, amount: Balance) -> Balance {
+        let bps = Balance::from(self.fee_bps);
+        let denominator = Balance::from(BPS_DENOMINATOR);
+        amount / denominator * bps + amount % denominator * bps / denominator
+    }
+
+    pub fn ft_set_transfer_fee(&mut self, token: &FungibleToken, config: TransferFeeConfig) {
+        assert_one_yocto();
+        self.set_config(token, config);
+    }
+}
+
+impl FungibleTokenTransferHook for TransferFee {
+    fn after_transfer(&mut self, token: &mut FungibleToken, transfer: &FtHookTransfer) {
+        if transfer.kind == FtTransferKind::Refund || transfer.sender_id == &self.treasury_id {
+            return;
+        }
+        let fee = self.fee_of(transfer.amount);
+        if fee > 0 {
+            token.internal_transfer(
+                transfer.sender_id,
+                &self.treasury_id,
+                fee,
+                Some("fee".to_string()),
+            );
+        }
+    }
+}
+
+fn assert_valid_fee_bps(fee_bps: u16) {
+    require!(fee_bps <= BPS_DENOMINATOR, "The fee can't exceed 10000 basis points");
+}
+
+fn assert_registered_treasury(token: &FungibleToken, treasury_id: &AccountId) {
+    require!(token.accounts.contains_key(treasury_id), "The treasury isn't registered");
+}
+
+/// Exposes the [`TransferFeeManagement`] methods of a [`TransferFee`] field. The fee is charged
+/// by passing the same field to [`impl_fungible_token_core`](crate::impl_fungible_token_core)
+/// with `hook = <field>`.
+/// Takes name of the Contract struct, the inner field for the token and the inner field for the
+/// fee.
+#[macro_export]
+macro_rules! impl_fungible_token_transfer_fee {
+    ($contract: ident, $token: ident, $fee: ident) => {
+        use $crate::fungible_token::transfer_fee::{TransferFeeConfig, TransferFeeManagement};
+
+        #[near_bindgen]
+        impl TransferFeeManagement for $contract {
+            fn ft_transfer_fee(&self) -> TransferFeeConfig {
+                self.$fee.config()
+            }
+
+            #[payable]
+            fn ft_set_transfer_fee(&mut self, config: TransferFeeConfig) {
+                self.$fee.ft_set_transfer_fee(&self.$token, config)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn setup() -> (FungibleToken, TransferFee) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        for index in 0..4 {
+            token.internal_register_account(&accounts(index));
+        }
+        token.internal_deposit(&accounts(0), 10_000);
+        let fee = TransferFee::new(&token, accounts(0), 250, accounts(3));
+        (token, fee)
+    }
+
+    #[test]
+    fn fee_is_sent_to_treasury() {
+        let (mut token, mut fee) = setup();
+        token.internal_transfer_with_hook(&mut fee, &accounts(0), &accounts(1), 1_000, None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 1_000);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(3)), 25);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(0)), 8_975);
+
+        // Rounded down to no fee.
+        token.internal_transfer_with_hook(&mut fee, &accounts(1), &accounts(2), 39, None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 961);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(3)), 25);
+    }
+
+    #[test]
+    fn treasury_transfers_are_free() {
+        let (mut token, mut fee) = setup();
+        token.internal_transfer(&accounts(0), &accounts(3), 100, None);
+        token.internal_transfer_with_hook(&mut fee, &accounts(3), &accounts(1), 100, None);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(3)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn fee_requires_balance() {
+        let (mut token, mut fee) = setup();
+        token.internal_transfer_with_hook(&mut fee, &accounts(0), &accounts(1), 10_000, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The fee can't exceed 10000 basis points")]
+    fn invalid_fee() {
+        let (token, mut fee) = setup();
+        let config = TransferFeeConfig { fee_bps: 10_001, treasury_id: accounts(3) };
+        fee.ft_set_transfer_fee(&token, config);
+    }
+
+    #[test]
+    #[should_panic(expected = "The treasury isn't registered")]
+    fn unregistered_treasury() {
+        let (token, mut fee) = setup();
+        let config = TransferFeeConfig { fee_bps: 100, treasury_id: accounts(4) };
+        fee.ft_set_transfer_fee(&token, config);
+    }
+
+    #[test]
+    #[should_panic(expected = "The treasury isn't registered")]
+    fn new_with_unregistered_treasury() {
+        let (token, _) = setup();
+        TransferFee::new(&token, accounts(0), 100, accounts(4));
+    }
+}
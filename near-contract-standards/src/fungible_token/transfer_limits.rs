@@ -0,0 +1,231 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, Balance, IntoStorageKey, Timestamp};
+
+/// Length of the windows of the per-account volume limit, one day in nanoseconds.
+pub const VOLUME_WINDOW: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Limits of transfers as returned by the `ft_transfer_limits` view.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferLimitsConfig {
+    /// Maximum amount of a single transfer.
+    pub max_transfer_amount: Option<U128>,
+    /// Maximum amount an account can send per day, in windows starting at midnight UTC.
+    pub daily_volume: Option<U128>,
+}
+
+/// Management of the limits of a token, exposed with
+/// [`impl_fungible_token_transfer_limits`](crate::impl_fungible_token_transfer_limits).
+pub trait TransferLimitsManagement {
+    fn ft_transfer_limits(&self) -> TransferLimitsConfig;
+
+    /// Returns the amount the account can still send in the current day, or `None` if the daily
+    /// volume isn't limited.
+    fn ft_remaining_daily_volume(&self, account_id: AccountId) -> Option<U128>;
+
+    /// Sets the limits, `None` removes a limit. Can only be called by the owner of the limits, with
+    /// exactly 1 yoctoNEAR attached.
+    fn ft_set_transfer_limits(&mut self, limits: TransferLimitsConfig);
+}
+
+/// Risk limits on the transfers of a token, e.g. for bridged assets: a maximum amount per
+/// transfer and a maximum volume each account can send per day.
+///
+/// The limits are advisory: [`FungibleToken`](crate::fungible_token::FungibleToken) doesn't know
+/// them, so they only apply to the transfers the contract checks. They are enforced on the
+/// `_with_hook` methods of the token when the limits are passed as, or subscribed to, the hook,
+/// e.g. on `ft_transfer` and `ft_transfer_call` of a token implemented with
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core) and `hook = <field>`. Other
+/// paths, e.g. `internal_transfer`, allowances or operators without the hook, are not limited
+/// unless the contract calls [`assert_transfer`](Self::assert_transfer) before them. Only the
+/// `owner_id` of the limits can change them. Refunds of `ft_transfer_call` don't restore the
+/// volume of the sender.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TransferLimits {
+    pub owner_id: AccountId,
+    pub max_transfer_amount: Option<Balance>,
+    pub daily_volume: Option<Balance>,
+    /// AccountID -> (Start of the window, Volume sent in the window).
+    pub volumes: LookupMap<AccountId, (Timestamp, Balance)>,
+}
+
+impl TransferLimits {
+    /// Creates limits managed by `owner_id`, which don't restrict transfers until they are set.
+    pub fn new<S>(prefix: S, owner_id: AccountId) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self {
+            owner_id,
+            max_transfer_amount: None,
+            daily_volume: None,
+            volumes: LookupMap::new(prefix),
+        }
+    }
+
+    pub fn config(&self) -> TransferLimitsConfig {
+        TransferLimitsConfig {
+            max_transfer_amount: self.max_transfer_amount.map(U128),
+            daily_volume: self.daily_volume.map(U128),
+        }
+    }
+
+    /// Sets the limits. Panics if the predecessor isn't the owner.
+    pub fn set_config(&mut self, config: TransferLimitsConfig) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+        self.max_transfer_amount = config.max_transfer_amount.map(|amount| amount.0);
+        self.daily_volume = config.daily_volume.map(|amount| amount.0);
+    }
+
+    fn current_window() -> Timestamp {
+        let timestamp = env::block_timestamp();
+        timestamp - timestamp % VOLUME_WINDOW
+    }
+
+    /// Volume the account sent in the current window.
+    fn current_volume(&self, account_id: &AccountId) -> Balance {
+        match self.volumes.get(account_id) {
+            Some((window, volume)) if window == Self::current_window() => volume,
+            _ => 0,
+        }
+    }
+
+    pub fn remaining_daily_volume(&self, account_id: &AccountId) -> Option<Balance> {
+        self.daily_volume
+            .map(|daily_volume| daily_volume.saturating_sub(self.current_volume(account_id)))
+    }
+
+    /// Panics if the transfer of `amount` by `sender_id` exceeds the limits, otherwise records
+    /// it in the daily volume of the sender.
+    pub fn assert_transfer(&mut self, sender_id: &AccountId, amount: Balance) {
+        if let Some(max_transfer_amount) = self.max_transfer_amount {
+            require!(
+                amount <= max_transfer_amount,
+                "The amount exceeds the maximum transfer amount"
+            );
+        }
+        if let Some(daily_volume) = self.daily_volume {
+            let volume = self.current_volume(sender_id).saturating_add(amount);
+            require!(volume <= daily_volume, "The amount exceeds the daily transfer volume");
+            self.volumes.insert(sender_id, &(Self::current_window(), volume));
+        }
+    }
+}
+
+/// Exposes the [`TransferLimitsManagement`] methods of a [`TransferLimits`] field. The limits are
+/// enforced by passing the same field to
+/// [`impl_fungible_token_core`](crate::impl_fungible_token_core) with `hook = <field>`, and
+/// `ft_set_transfer_limits` requires a deposit of exactly 1 yoctoNEAR.
+/// Takes name of the Contract struct and the inner field for the limits.
+#[macro_export]
+macro_rules! impl_fungible_token_transfer_limits {
+    ($contract: ident, $limits: ident) => {
+        use $crate::fungible_token::transfer_limits::{
+            TransferLimitsConfig, TransferLimitsManagement,
+        };
+
+        #[near_bindgen]
+        impl TransferLimitsManagement for $contract {
+            fn ft_transfer_limits(&self) -> TransferLimitsConfig {
+                self.$limits.config()
+            }
+
+            fn ft_remaining_daily_volume(&self, account_id: AccountId) -> Option<U128> {
+                self.$limits.remaining_daily_volume(&account_id).map(U128)
+            }
+
+            #[payable]
+            fn ft_set_transfer_limits(&mut self, limits: TransferLimitsConfig) {
+                near_sdk::assert_one_yocto();
+                self.$limits.set_config(limits)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        limits: TransferLimits,
+    }
+
+    impl_fungible_token_transfer_limits!(Contract, limits);
+
+    fn setup(block_timestamp: Timestamp) -> TransferLimits {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(block_timestamp)
+            .build());
+        let mut limits = TransferLimits::new(b"l".to_vec(), accounts(0));
+        limits.set_config(TransferLimitsConfig {
+            max_transfer_amount: Some(U128(100)),
+            daily_volume: Some(U128(150)),
+        });
+        limits
+    }
+
+    #[test]
+    fn daily_volume_resets_with_window() {
+        let mut limits = setup(VOLUME_WINDOW + 10);
+        limits.assert_transfer(&accounts(1), 100);
+        limits.assert_transfer(&accounts(1), 50);
+        assert_eq!(limits.remaining_daily_volume(&accounts(1)), Some(0));
+        assert_eq!(limits.remaining_daily_volume(&accounts(2)), Some(150));
+
+        testing_env!(VMContextBuilder::new().block_timestamp(2 * VOLUME_WINDOW).build());
+        assert_eq!(limits.remaining_daily_volume(&accounts(1)), Some(150));
+        limits.assert_transfer(&accounts(1), 100);
+        assert_eq!(limits.remaining_daily_volume(&accounts(1)), Some(50));
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount exceeds the daily transfer volume")]
+    fn exceeds_daily_volume() {
+        let mut limits = setup(0);
+        limits.assert_transfer(&accounts(1), 100);
+        limits.assert_transfer(&accounts(1), 51);
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount exceeds the maximum transfer amount")]
+    fn exceeds_max_transfer_amount() {
+        let mut limits = setup(0);
+        limits.assert_transfer(&accounts(1), 101);
+    }
+
+    #[test]
+    fn set_limits_with_yocto() {
+        let mut contract = Contract { limits: setup(0) };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.ft_set_transfer_limits(TransferLimitsConfig::default());
+        assert_eq!(contract.ft_transfer_limits(), TransferLimitsConfig::default());
+        assert_eq!(contract.ft_remaining_daily_volume(accounts(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_limits_without_yocto() {
+        let mut contract = Contract { limits: setup(0) };
+        contract.ft_set_transfer_limits(TransferLimitsConfig::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_sets_limits() {
+        let mut limits = setup(0);
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        limits.set_config(TransferLimitsConfig::default());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::Migrate;
+
+/// Versions of the layout of [`FungibleToken`], for contracts storing their token as
+/// `near_sdk::Versioned<VersionedFungibleToken>` instead of a plain [`FungibleToken`].
+///
+/// When fields are added to [`FungibleToken`], its previous layout is kept as a new variant
+/// before `Current`, so the state of deployed contracts is upgraded when it's read instead of
+/// failing to deserialize with the new code. The versioned token dereferences to
+/// [`FungibleToken`], so it can be passed to the `impl_fungible_token_*` macros like a plain one.
+///
+/// Contracts which stored a plain [`FungibleToken`] switch to the versioned one with a
+/// `#[migrate]` method wrapping their old token in `near_sdk::Versioned::new`.
+#[derive(BorshDeserialize, BorshSerialize, Migrate)]
+pub enum VersionedFungibleToken {
+    Current(FungibleToken),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, Versioned};
+
+    #[test]
+    fn versioned_token() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut token = Versioned::<VersionedFungibleToken>::new(FungibleToken::new(b"t".to_vec()));
+        token.internal_register_account(&accounts(0));
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(0), 100);
+        token.internal_transfer(&accounts(0), &accounts(1), 40, None);
+
+        let bytes = token.try_to_vec().unwrap();
+        assert_eq!(bytes[0], VersionedFungibleToken::CURRENT_VERSION);
+        let token = Versioned::<VersionedFungibleToken>::try_from_slice(&bytes).unwrap();
+        assert_eq!(token.total_supply, 100);
+        assert_eq!(token.internal_unwrap_balance_of(&accounts(1)), 40);
+    }
+}
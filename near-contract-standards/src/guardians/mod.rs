@@ -0,0 +1,396 @@
+use crate::event::NearEvent;
+use crate::ownership::Ownership;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, Duration, Promise, PublicKey};
+use std::collections::BTreeSet;
+
+/// Change of the control of the contract made by a recovery.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// Replaces the owner of the [`Ownership`] of the contract.
+    SetOwner { new_owner_id: AccountId },
+    /// Adds a full access key to the contract account, removing the lost key if it's given.
+    RotateKey { old_public_key: Option<PublicKey>, new_public_key: PublicKey },
+}
+
+/// Recovery proposed by a guardian, as returned by the `pending_recovery` view.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Recovery {
+    pub action: RecoveryAction,
+    pub confirmations: BTreeSet<AccountId>,
+    pub proposed_at: U64,
+}
+
+/// Standard interface of [`Guardians`], exposed with [`impl_guardians`](crate::impl_guardians).
+pub trait GuardianRecovery {
+    fn guardians(&self) -> Vec<AccountId>;
+
+    fn pending_recovery(&self) -> Option<Recovery>;
+
+    /// Replaces the guardians and the number of confirmations of a recovery. Can only be called by
+    /// the owner, with exactly 1 yoctoNEAR attached.
+    fn set_guardians(&mut self, guardians: Vec<AccountId>, threshold: u32);
+
+    /// Proposes the recovery, confirmed by the calling guardian. Requires exactly 1 yoctoNEAR.
+    fn propose_recovery(&mut self, action: RecoveryAction);
+
+    /// Confirms the pending recovery. Can only be called by a guardian, with exactly 1 yoctoNEAR
+    /// attached.
+    fn confirm_recovery(&mut self);
+
+    /// Cancels the pending recovery. Can only be called by the owner, with exactly 1 yoctoNEAR
+    /// attached.
+    fn cancel_recovery(&mut self);
+
+    /// Executes the pending recovery once it's confirmed and its delay passed.
+    fn execute_recovery(&mut self) -> Option<Promise>;
+}
+
+/// Guardian accounts which can take over the control of the contract when its owner lost their
+/// key, for DAO treasuries and token issuers controlled by a single key.
+///
+/// A guardian proposes a [`RecoveryAction`], which replaces the owner of the [`Ownership`] of the
+/// contract or rotates a full access key of the contract account, and at least `threshold`
+/// guardians must confirm it. The
+/// recovery can be executed `delay` nanoseconds after it was proposed, which gives an owner who
+/// still holds their key the time to [`cancel`](Self::cancel) a recovery they didn't ask for.
+/// Every step is logged as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"guardians","version":"1.0.0","event":"recovery_confirmed","data":[{"guardian_id":"bob.near"}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct Guardians {
+    pub delay: Duration,
+    guardians: BTreeSet<AccountId>,
+    threshold: u32,
+    recovery: Option<Recovery>,
+}
+
+impl Guardians {
+    /// Creates the guardians of the contract, of which `threshold` must confirm a recovery. Panics
+    /// if `threshold` is zero or greater than the number of guardians.
+    pub fn new(guardians: Vec<AccountId>, threshold: u32, delay: Duration) -> Self {
+        let guardians = guardians.into_iter().collect();
+        assert_threshold(&guardians, threshold);
+        Self { delay, guardians, threshold, recovery: None }
+    }
+
+    pub fn guardians(&self) -> &BTreeSet<AccountId> {
+        &self.guardians
+    }
+
+    /// Number of guardians who must confirm a recovery.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub fn pending_recovery(&self) -> Option<&Recovery> {
+        self.recovery.as_ref()
+    }
+
+    /// Replaces the guardians, cancelling the pending recovery. Panics if the predecessor isn't
+    /// the owner or the threshold is invalid.
+    pub fn set_guardians(
+        &mut self,
+        ownership: &Ownership,
+        guardians: Vec<AccountId>,
+        threshold: u32,
+    ) {
+        ownership.assert_owner();
+        let guardians = guardians.into_iter().collect();
+        assert_threshold(&guardians, threshold);
+        self.guardians = guardians;
+        self.threshold = threshold;
+        if self.recovery.take().is_some() {
+            emit_event("recovery_cancelled", None);
+        }
+    }
+
+    /// Proposes the recovery, confirmed by the predecessor. Panics if the predecessor isn't a
+    /// guardian or another recovery is pending.
+    pub fn propose(&mut self, action: RecoveryAction) {
+        let guardian_id = self.assert_guardian();
+        require!(self.recovery.is_none(), "A recovery is already pending");
+        self.recovery = Some(Recovery {
+            action,
+            confirmations: std::iter::once(guardian_id.clone()).collect(),
+            proposed_at: env::block_timestamp().into(),
+        });
+        emit_event("recovery_proposed", Some(&guardian_id));
+    }
+
+    /// Confirms the pending recovery. Panics if the predecessor isn't a guardian or there's no
+    /// pending recovery.
+    pub fn confirm(&mut self) {
+        let guardian_id = self.assert_guardian();
+        let recovery = self.recovery_or_panic();
+        if recovery.confirmations.insert(guardian_id.clone()) {
+            emit_event("recovery_confirmed", Some(&guardian_id));
+        }
+    }
+
+    /// Cancels the pending recovery. Panics if the predecessor isn't the owner or there's no
+    /// pending recovery.
+    pub fn cancel(&mut self, ownership: &Ownership) {
+        ownership.assert_owner();
+        self.recovery_or_panic();
+        self.recovery = None;
+        emit_event("recovery_cancelled", None);
+    }
+
+    /// Executes the pending recovery, replacing the owner of `ownership` or returning the promise
+    /// changing the keys for a key rotation. Panics if there's no pending recovery, it lacks
+    /// confirmations or its delay didn't pass.
+    pub fn execute(&mut self, ownership: &mut Ownership) -> Option<Promise> {
+        let threshold = self.threshold;
+        let delay = self.delay;
+        let recovery = self.recovery_or_panic();
+        let confirmations = recovery.confirmations.len() as u32;
+        require!(confirmations >= threshold, "The recovery is not confirmed by enough guardians");
+        require!(
+            env::block_timestamp() >= recovery.proposed_at.0.saturating_add(delay),
+            "The recovery delay did not pass"
+        );
+        let action = self.recovery.take().map(|recovery| recovery.action);
+        emit_event("recovery_executed", None);
+        match action {
+            Some(RecoveryAction::SetOwner { new_owner_id }) => {
+                ownership.recover(new_owner_id);
+                None
+            }
+            Some(RecoveryAction::RotateKey { old_public_key, new_public_key }) => {
+                let promise =
+                    Promise::new(env::current_account_id()).add_full_access_key(new_public_key);
+                Some(match old_public_key {
+                    Some(old_public_key) => promise.delete_key(old_public_key),
+                    None => promise,
+                })
+            }
+            None => None,
+        }
+    }
+
+    fn assert_guardian(&self) -> AccountId {
+        let account_id = env::predecessor_account_id();
+        require!(self.guardians.contains(&account_id), "Only guardians can recover the contract");
+        account_id
+    }
+
+    fn recovery_or_panic(&mut self) -> &mut Recovery {
+        self.recovery.as_mut().unwrap_or_else(|| env::panic_str("No recovery is pending"))
+    }
+}
+
+fn assert_threshold(guardians: &BTreeSet<AccountId>, threshold: u32) {
+    require!(
+        threshold > 0 && threshold as usize <= guardians.len(),
+        "The threshold must be between 1 and the number of guardians"
+    );
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct GuardiansEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [RecoveryStep<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct RecoveryStep<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guardian_id: Option<&'a AccountId>,
+}
+
+fn emit_event(event: &'static str, guardian_id: Option<&AccountId>) {
+    NearEvent::Guardians(GuardiansEvent {
+        version: "1.0.0",
+        event,
+        data: &[RecoveryStep { guardian_id }],
+    })
+    .emit();
+}
+
+/// Exposes the [`GuardianRecovery`] methods of a [`Guardians`] field. The yoctoNEAR required by
+/// the mutating methods, except `execute_recovery`, ensures they are signed with a full access
+/// key.
+/// Takes name of the Contract struct, the inner field for the guardians and the inner
+/// [`Ownership`] field whose owner they recover.
+#[macro_export]
+macro_rules! impl_guardians {
+    ($contract: ident, $guardians: ident, $ownership: ident) => {
+        use $crate::guardians::{GuardianRecovery, Recovery, RecoveryAction};
+
+        #[near_bindgen]
+        impl GuardianRecovery for $contract {
+            fn guardians(&self) -> Vec<AccountId> {
+                self.$guardians.guardians().iter().cloned().collect()
+            }
+
+            fn pending_recovery(&self) -> Option<Recovery> {
+                self.$guardians.pending_recovery().cloned()
+            }
+
+            #[payable]
+            fn set_guardians(&mut self, guardians: Vec<AccountId>, threshold: u32) {
+                near_sdk::assert_one_yocto();
+                self.$guardians.set_guardians(&self.$ownership, guardians, threshold)
+            }
+
+            #[payable]
+            fn propose_recovery(&mut self, action: RecoveryAction) {
+                near_sdk::assert_one_yocto();
+                self.$guardians.propose(action)
+            }
+
+            #[payable]
+            fn confirm_recovery(&mut self) {
+                near_sdk::assert_one_yocto();
+                self.$guardians.confirm()
+            }
+
+            #[payable]
+            fn cancel_recovery(&mut self) {
+                near_sdk::assert_one_yocto();
+                self.$guardians.cancel(&self.$ownership)
+            }
+
+            fn execute_recovery(&mut self) -> Option<near_sdk::Promise> {
+                self.$guardians.execute(&mut self.$ownership)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env, Timestamp};
+
+    const DELAY: Duration = 1_000;
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        ownership: Ownership,
+        guardians: Guardians,
+    }
+
+    impl_ownable!(Contract, ownership);
+    impl_guardians!(Contract, guardians, ownership);
+
+    fn call(predecessor: usize, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(predecessor))
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn call_with_yocto(predecessor: usize, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(predecessor))
+            .block_timestamp(timestamp)
+            .attached_deposit(1)
+            .build());
+    }
+
+    fn proposed() -> (Guardians, Ownership) {
+        call(1, 0);
+        let mut guardians = Guardians::new(vec![accounts(1), accounts(2), accounts(3)], 2, DELAY);
+        guardians.propose(RecoveryAction::SetOwner { new_owner_id: accounts(4) });
+        (guardians, Ownership::new(accounts(0)))
+    }
+
+    #[test]
+    fn recover_owner() {
+        let (mut guardians, mut ownership) = proposed();
+        call(2, 0);
+        guardians.confirm();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"guardians","version":"1.0.0","event":"recovery_confirmed","data":[{"guardian_id":"charlie"}]}"#
+            ]
+        );
+        call(5, DELAY);
+        assert!(guardians.execute(&mut ownership).is_none());
+        assert_eq!(ownership.owner_id(), &accounts(4));
+        assert_eq!(guardians.pending_recovery(), None);
+    }
+
+    #[test]
+    fn recover_contract_owner() {
+        call(0, 0);
+        let mut contract = Contract {
+            ownership: Ownership::new(accounts(0)),
+            guardians: Guardians::new(vec![accounts(1), accounts(2)], 2, DELAY),
+        };
+        call_with_yocto(1, 0);
+        contract.propose_recovery(RecoveryAction::SetOwner { new_owner_id: accounts(4) });
+        call_with_yocto(2, 0);
+        contract.confirm_recovery();
+        call(5, DELAY);
+        assert!(contract.execute_recovery().is_none());
+        assert_eq!(contract.owner_get(), accounts(4));
+
+        // The recovered owner controls the contract.
+        call_with_yocto(4, DELAY);
+        contract.set_guardians(vec![accounts(3)], 1);
+        assert_eq!(contract.guardians(), vec![accounts(3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn propose_without_yocto() {
+        call(1, 0);
+        let mut contract = Contract {
+            ownership: Ownership::new(accounts(0)),
+            guardians: Guardians::new(vec![accounts(1)], 1, DELAY),
+        };
+        contract.propose_recovery(RecoveryAction::SetOwner { new_owner_id: accounts(4) });
+    }
+
+    #[test]
+    #[should_panic(expected = "The recovery is not confirmed by enough guardians")]
+    fn execute_unconfirmed() {
+        let (mut guardians, mut ownership) = proposed();
+        call(1, DELAY);
+        guardians.confirm();
+        guardians.execute(&mut ownership);
+    }
+
+    #[test]
+    #[should_panic(expected = "The recovery delay did not pass")]
+    fn execute_early() {
+        let (mut guardians, mut ownership) = proposed();
+        call(2, DELAY - 1);
+        guardians.confirm();
+        guardians.execute(&mut ownership);
+    }
+
+    #[test]
+    #[should_panic(expected = "No recovery is pending")]
+    fn owner_cancels() {
+        let (mut guardians, ownership) = proposed();
+        call(0, 0);
+        guardians.cancel(&ownership);
+        call(2, 0);
+        guardians.confirm();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only guardians can recover the contract")]
+    fn only_guardians_propose() {
+        call(4, 0);
+        let mut guardians = Guardians::new(vec![accounts(1)], 1, DELAY);
+        guardians.propose(RecoveryAction::SetOwner { new_owner_id: accounts(4) });
+    }
+}
@@ -0,0 +1,265 @@
+use crate::fungible_token::hook::{FtHookTransfer, FungibleTokenTransferHook};
+use crate::fungible_token::FungibleToken;
+use near_sdk::{env, AccountId, Balance, Gas};
+
+struct Subscription<'a> {
+    name: &'static str,
+    order: i32,
+    gas_budget: Option<Gas>,
+    gas_used: Gas,
+    hook: &'a mut dyn FungibleTokenTransferHook,
+}
+
+/// Registry of the [`FungibleTokenTransferHook`]s of a contract, so that several components,
+/// e.g. [`Pause`](crate::pause::Pause),
+/// [`TransferLimits`](crate::fungible_token::TransferLimits) and custom logic, can react to the
+/// same transfers, mints, burns and registrations. The registry is itself a
+/// [`FungibleTokenTransferHook`], passed to the `_with_hook` methods of the token.
+///
+/// Hooks are called in ascending `order`, and in the order they were subscribed for the same
+/// `order`. A hook which uses more gas than its budget in a call panics, and the gas used by each
+/// hook is accumulated in [`gas_used`](Self::gas_used).
+///
+/// The registry borrows the components, so it's built in the method which moves the tokens,
+/// after borrowing the fields of the contract individually.
+///
+/// # Example
+///
+/// ```
+/// use near_contract_standards::fungible_token::{FungibleToken, TransferLimits};
+/// use near_contract_standards::hooks::Hooks;
+/// use near_contract_standards::pause::Pause;
+/// use near_sdk::{AccountId, Balance, Gas};
+///
+/// struct Contract {
+///     token: FungibleToken,
+///     pause: Pause,
+///     limits: TransferLimits,
+/// }
+///
+/// impl Contract {
+///     fn transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: Balance) {
+///         let Self { token, pause, limits } = self;
+///         let mut hooks = Hooks::new();
+///         hooks.subscribe("pause", 0, None, pause);
+///         hooks.subscribe("limits", 10, Some(Gas(10_000_000_000_000)), limits);
+///         token.internal_transfer_with_hook(&mut hooks, sender_id, receiver_id, amount, None);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct Hooks<'a> {
+    subscriptions: Vec<Subscription<'a>>,
+}
+
+impl<'a> Hooks<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes a component. Panics if a hook with the same name was subscribed.
+    pub fn subscribe<H>(
+        &mut self,
+        name: &'static str,
+        order: i32,
+        gas_budget: Option<Gas>,
+        hook: &'a mut H,
+    ) where
+        H: FungibleTokenTransferHook,
+    {
+        if self.subscriptions.iter().any(|subscription| subscription.name == name) {
+            env::panic_str(format!("Hook {} is already subscribed", name).as_str());
+        }
+        // Insert after the hooks with the same order, to keep the order of subscription.
+        let index = self.subscriptions.partition_point(|subscription| subscription.order <= order);
+        self.subscriptions
+            .insert(index, Subscription { name, order, gas_budget, gas_used: Gas(0), hook });
+    }
+
+    /// Names of the hooks in the order they are called.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.subscriptions.iter().map(|subscription| subscription.name).collect()
+    }
+
+    /// Gas used by the hook in all calls so far, or `None` if it isn't subscribed.
+    pub fn gas_used(&self, name: &str) -> Option<Gas> {
+        self.subscriptions
+            .iter()
+            .find(|subscription| subscription.name == name)
+            .map(|subscription| subscription.gas_used)
+    }
+
+    /// Calls `f` with each hook. Panics if a hook panics or exceeds its gas budget.
+    fn dispatch<F>(&mut self, token: &mut FungibleToken, mut f: F)
+    where
+        F: FnMut(&mut dyn FungibleTokenTransferHook, &mut FungibleToken),
+    {
+        for subscription in self.subscriptions.iter_mut() {
+            let initial_gas = env::used_gas();
+            f(&mut *subscription.hook, token);
+            let gas_used = env::used_gas() - initial_gas;
+            subscription.gas_used += gas_used;
+            if let Some(gas_budget) = subscription.gas_budget {
+                if gas_used > gas_budget {
+                    env::panic_str(
+                        format!("Hook {} exceeded its gas budget", subscription.name).as_str(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl FungibleTokenTransferHook for Hooks<'_> {
+    fn before_transfer(&mut self, token: &mut FungibleToken, transfer: &FtHookTransfer) {
+        self.dispatch(token, |hook, token| hook.before_transfer(token, transfer));
+    }
+
+    fn after_transfer(&mut self, token: &mut FungibleToken, transfer: &FtHookTransfer) {
+        self.dispatch(token, |hook, token| hook.after_transfer(token, transfer));
+    }
+
+    fn on_account_registered(&mut self, token: &mut FungibleToken, account_id: &AccountId) {
+        self.dispatch(token, |hook, token| hook.on_account_registered(token, account_id));
+    }
+
+    fn on_account_closed(
+        &mut self,
+        token: &mut FungibleToken,
+        account_id: &AccountId,
+        remaining_balance: Balance,
+    ) {
+        self.dispatch(token, |hook, token| {
+            hook.on_account_closed(token, account_id, remaining_balance)
+        });
+    }
+
+    fn on_tokens_minted(
+        &mut self,
+        token: &mut FungibleToken,
+        owner_id: &AccountId,
+        amount: Balance,
+    ) {
+        self.dispatch(token, |hook, token| hook.on_tokens_minted(token, owner_id, amount));
+    }
+
+    fn on_tokens_burned(
+        &mut self,
+        token: &mut FungibleToken,
+        owner_id: &AccountId,
+        amount: Balance,
+    ) {
+        self.dispatch(token, |hook, token| hook.on_tokens_burned(token, owner_id, amount));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::hook::FtTransferKind;
+    use crate::fungible_token::transfer_limits::TransferLimitsConfig;
+    use crate::fungible_token::TransferLimits;
+    use crate::pause::Pause;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use std::cell::RefCell;
+
+    /// Records its name in the shared list of calls.
+    struct Recorder<'c> {
+        name: &'static str,
+        calls: &'c RefCell<Vec<&'static str>>,
+    }
+
+    impl FungibleTokenTransferHook for Recorder<'_> {
+        fn before_transfer(&mut self, _: &mut FungibleToken, _: &FtHookTransfer) {
+            self.calls.borrow_mut().push(self.name);
+        }
+    }
+
+    /// Writes to the storage before each transfer.
+    struct StorageWriter;
+
+    impl FungibleTokenTransferHook for StorageWriter {
+        fn before_transfer(&mut self, _: &mut FungibleToken, _: &FtHookTransfer) {
+            env::storage_write(b"k", b"v");
+        }
+    }
+
+    fn setup() -> FungibleToken {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_register_account(&accounts(2));
+        token.internal_deposit(&accounts(1), 100);
+        token
+    }
+
+    fn transfer<'a>(sender_id: &'a AccountId, amount: Balance) -> FtHookTransfer<'a> {
+        FtHookTransfer { kind: FtTransferKind::Transfer, sender_id, receiver_id: sender_id, amount }
+    }
+
+    #[test]
+    fn hooks_are_ordered() {
+        let mut token = setup();
+        let calls = RefCell::new(vec![]);
+        let (mut a, mut b, mut c) = (
+            Recorder { name: "a", calls: &calls },
+            Recorder { name: "b", calls: &calls },
+            Recorder { name: "c", calls: &calls },
+        );
+        let mut hooks = Hooks::new();
+        hooks.subscribe("b", 10, None, &mut b);
+        hooks.subscribe("a", 0, None, &mut a);
+        hooks.subscribe("c", 10, None, &mut c);
+        assert_eq!(hooks.names(), vec!["a", "b", "c"]);
+        hooks.before_transfer(&mut token, &transfer(&accounts(0), 1));
+        assert_eq!(*calls.borrow(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn components_as_hooks() {
+        let mut token = setup();
+        let mut pause = Pause::new();
+        let mut limits = TransferLimits::new(b"l".to_vec(), accounts(0));
+        limits.set_config(TransferLimitsConfig {
+            max_transfer_amount: None,
+            daily_volume: Some(U128(100)),
+        });
+        {
+            let mut hooks = Hooks::new();
+            hooks.subscribe("pause", 0, None, &mut pause);
+            hooks.subscribe("limits", 1, None, &mut limits);
+            token.internal_transfer_with_hook(&mut hooks, &accounts(1), &accounts(2), 60, None);
+        }
+        assert_eq!(limits.remaining_daily_volume(&accounts(1)), Some(40));
+
+        pause.pause_feature("ft_transfer");
+        let mut hooks = Hooks::new();
+        hooks.subscribe("pause", 0, None, &mut pause);
+        token.internal_transfer_with_hook(&mut hooks, &accounts(1), &accounts(2), 1, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Hook storage exceeded its gas budget")]
+    fn gas_budget() {
+        let mut token = setup();
+        let mut writer = StorageWriter;
+        let mut hooks = Hooks::new();
+        hooks.subscribe("storage", 0, Some(Gas(1)), &mut writer);
+        hooks.before_transfer(&mut token, &transfer(&accounts(0), 1));
+    }
+
+    #[test]
+    fn gas_used() {
+        let mut token = setup();
+        let mut writer = StorageWriter;
+        let mut hooks = Hooks::new();
+        hooks.subscribe("storage", 0, None, &mut writer);
+        assert_eq!(hooks.gas_used("storage"), Some(Gas(0)));
+        hooks.before_transfer(&mut token, &transfer(&accounts(0), 1));
+        assert!(hooks.gas_used("storage").unwrap() > Gas(0));
+        assert_eq!(hooks.gas_used("other"), None);
+    }
+}
@@ -1,11 +1,64 @@
+/// Short indices of account ids, for events which refer to accounts by index.
+pub mod account_index;
+/// Accounts allowed or denied to call designated methods of a contract.
+pub mod caller_policy;
+/// Batched removal of the storage of deprecated components.
+pub mod cleanup;
+/// Commit-reveal scheme for values which stay hidden until they're revealed, like blind bids.
+pub mod commit_reveal;
+/// Contracts assembled from a manifest of standard components.
+pub mod compose;
+/// Configuration of a contract as a single struct with validated updates.
+pub mod config;
+/// End of life of a contract, sweeping its assets to a beneficiary and deleting its account.
+pub mod decommission;
+/// Fungible token balances held for users, with the recovery of abandoned balances.
+pub mod deposits;
+/// Events in the NEP-297 format, for contracts emitting events of their own standards.
+pub mod event;
+/// Recent events of a contract stored in a bounded ring buffer, for clients without an indexer.
+pub mod event_log;
+/// Named boolean and numeric flags to roll out features of a contract without redeploying it.
+pub mod feature_flags;
 /// Fungible tokens as described in [by the spec](https://nomicon.io/Standards/FungibleToken/README.html).
 pub mod fungible_token;
+/// Guardian accounts which can recover the control of a contract after a delay.
+pub mod guardians;
+/// Registry of the fungible token hooks of a contract, called in a deterministic order and with
+/// gas budgets.
+pub mod hooks;
+/// Contract-level counters and gauges which can be monitored through a view method.
+pub mod metrics;
+/// Multi tokens as described in [by the spec](https://github.com/near/NEPs/blob/master/neps/nep-0245.md).
+pub mod multi_token;
+/// Batches of function calls to allowed contracts, executed in parallel or in sequence.
+pub mod multicall;
+/// Deposits of native NEAR which other contracts can pull up to an allowance.
+pub mod near_allowance;
 /// Non-fungible tokens as described in [by the spec](https://nomicon.io/Standards/NonFungibleToken/README.html).
 pub mod non_fungible_token;
+/// Owner of a contract with a two-step transfer of the ownership.
+pub mod ownership;
+/// Pause flags of a contract and the standard `pause_state` view exposing them.
+pub mod pause;
+/// Locks of cross-contract flows with deadlines, to unwind flows whose callback never executed.
+pub mod pending_callbacks;
+/// Refunds of Ⓝ kept as claimable credits when their transfer fails.
+pub mod refund_queue;
+/// Temporary keys scoped to some methods of a contract, for acting without wallet prompts.
+pub mod sessions;
 /// Storage management deals with handling [state storage](https://docs.near.org/docs/concepts/storage-staking) on NEAR. This follows the [storage management standard](https://nomicon.io/Standards/StorageManagement.html).
 pub mod storage_management;
+/// Builders populating the state of standard components with deterministic data, for tests and
+/// benchmarks.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod test_fixtures;
+/// Guards of methods requiring the caller to hold a token, for `#[requires_token]`.
+pub mod token_gate;
+/// Common envelope for the `msg` argument of transfer calls, for routing through several contracts.
+pub mod transfer_call_msg;
 /// This upgrade standard is a use case where a staging area exists for a WASM
 /// blob, allowing it to be stored for a period of time before deployed.
 pub mod upgrade;
-
-pub(crate) mod event;
+/// Versioned per-account records migrated lazily to their latest layout.
+pub mod user_store;
@@ -0,0 +1,325 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::paginated_range;
+use near_sdk::json_types::{Cursor, Page, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, IntoStorageKey};
+
+mod sharded;
+pub use sharded::ShardedCounter;
+
+const DEFAULT_METRICS_PREFIX: &[u8] = b"__metrics";
+
+/// Kind of a [`Metric`].
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    /// Monotonically increasing value, e.g. the number of transfers.
+    Counter,
+    /// Value which can be set to anything, e.g. the number of registered accounts.
+    Gauge,
+}
+
+/// Current value of a named metric, as returned by the metrics view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Metric {
+    pub name: String,
+    pub kind: MetricKind,
+    pub value: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct MetricValue {
+    kind: MetricKind,
+    value: u64,
+}
+
+/// Named counters and gauges which operators can monitor through a view method without running
+/// an indexer.
+///
+/// Metrics are stored directly in contract storage under the prefix, with one entry of 9 bytes
+/// per metric and an index of names, so they can be updated from anywhere in the contract
+/// without being part of the contract state. Updates are usually done through the
+/// [`incr!`](crate::metrics::incr) and [`gauge!`](crate::metrics::gauge) macros, which use
+/// [`Metrics::default`]. To expose the metrics, implement [`MetricsView`] on the contract with
+/// [`impl_metrics_view`](crate::impl_metrics_view).
+///
+/// Every new metric name adds to the storage of the contract, so names should not be derived
+/// from user input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    prefix: Vec<u8>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_METRICS_PREFIX)
+    }
+}
+
+impl Metrics {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key() }
+    }
+
+    /// Increments the counter `name` by one.
+    pub fn incr(&self, name: &str) {
+        self.incr_by(name, 1)
+    }
+
+    /// Increments the counter `name` by `amount`, saturating at `u64::MAX`.
+    pub fn incr_by(&self, name: &str, amount: u64) {
+        let value = self.read_value(name, MetricKind::Counter).saturating_add(amount);
+        self.write_value(name, MetricKind::Counter, value);
+    }
+
+    /// Sets the gauge `name` to `value`.
+    pub fn set_gauge(&self, name: &str, value: u64) {
+        self.read_value(name, MetricKind::Gauge);
+        self.write_value(name, MetricKind::Gauge, value);
+    }
+
+    /// Returns the current value of the metric, if it was ever set.
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.read_metric(name).map(|metric| metric.value)
+    }
+
+    /// Number of metrics.
+    pub fn len(&self) -> u64 {
+        env::storage_read(&self.len_key())
+            .map(|bytes| {
+                u64::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Cannot deserialize metrics length"))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns `true` if no metrics were set.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns metrics in the order they were first set, paginated like the NFT enumeration.
+    pub fn metrics(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Metric> {
+        paginated_range(self.len(), from_index, limit)
+            .map(|index| {
+                let name = self.name_at(index);
+                let metric = self.read_metric(&name).unwrap_or_else(|| env::abort());
+                Metric { name, kind: metric.kind, value: metric.value.into() }
+            })
+            .collect()
+    }
+
+    /// Returns a page of the metrics in the order they were first set. Metrics are never
+    /// removed, so the cursor is the index of the next metric.
+    pub fn metrics_page(&self, from_cursor: Option<Cursor>, limit: Option<u64>) -> Page<Metric> {
+        let start_index: u64 = from_cursor.map_or(0, |cursor| cursor.position());
+        Page::collect(
+            (start_index..self.len()).map(|index| {
+                let name = self.name_at(index);
+                let metric = self.read_metric(&name).unwrap_or_else(|| env::abort());
+                (index + 1, Metric { name, kind: metric.kind, value: metric.value.into() })
+            }),
+            limit,
+        )
+    }
+
+    fn read_metric(&self, name: &str) -> Option<MetricValue> {
+        env::storage_read(&self.value_key(name)).map(|bytes| {
+            MetricValue::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize metric"))
+        })
+    }
+
+    /// Reads the current value of the metric, registering its name in the index if it is new.
+    fn read_value(&self, name: &str, kind: MetricKind) -> u64 {
+        match self.read_metric(name) {
+            Some(metric) => {
+                require!(metric.kind == kind, "Metric is used as both a counter and a gauge");
+                metric.value
+            }
+            None => {
+                let len = self.len();
+                env::storage_write(&self.name_key(len), name.as_bytes());
+                env::storage_write(
+                    &self.len_key(),
+                    &(len + 1).try_to_vec().unwrap_or_else(|_| env::abort()),
+                );
+                0
+            }
+        }
+    }
+
+    fn write_value(&self, name: &str, kind: MetricKind, value: u64) {
+        let metric = MetricValue { kind, value };
+        env::storage_write(
+            &self.value_key(name),
+            &metric.try_to_vec().unwrap_or_else(|_| env::abort()),
+        );
+    }
+
+    fn name_at(&self, index: u64) -> String {
+        env::storage_read(&self.name_key(index))
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .unwrap_or_else(|| env::panic_str("Metrics index is in an inconsistent state"))
+    }
+
+    fn len_key(&self) -> Vec<u8> {
+        [self.prefix.as_slice(), b"l"].concat()
+    }
+
+    fn name_key(&self, index: u64) -> Vec<u8> {
+        [self.prefix.as_slice(), b"n", &index.to_le_bytes()].concat()
+    }
+
+    fn value_key(&self, name: &str) -> Vec<u8> {
+        [self.prefix.as_slice(), b"v", name.as_bytes()].concat()
+    }
+}
+
+/// View of the contract metrics.
+pub trait MetricsView {
+    /// Returns the metrics of the contract in the order they were first set.
+    fn metrics(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Metric>;
+
+    /// Returns a page of the metrics of the contract in the order they were first set.
+    fn metrics_page(&self, from_cursor: Option<Cursor>, limit: Option<u64>) -> Page<Metric>;
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __metrics_incr {
+    ($name:expr) => {
+        $crate::metrics::Metrics::default().incr($name)
+    };
+    ($name:expr, $amount:expr) => {
+        $crate::metrics::Metrics::default().incr_by($name, $amount)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __metrics_gauge {
+    ($name:expr, $value:expr) => {
+        $crate::metrics::Metrics::default().set_gauge($name, $value)
+    };
+}
+
+/// Increments a counter of the default [`Metrics`] by one, or by the given amount.
+///
+/// ```ignore
+/// near_contract_standards::metrics::incr!("ft_transfers");
+/// near_contract_standards::metrics::incr!("ft_transferred_tokens", 100);
+/// ```
+#[doc(inline)]
+pub use crate::__metrics_incr as incr;
+
+/// Sets a gauge of the default [`Metrics`].
+///
+/// ```ignore
+/// near_contract_standards::metrics::gauge!("ft_accounts", accounts.len());
+/// ```
+#[doc(inline)]
+pub use crate::__metrics_gauge as gauge;
+
+/// Exposes the default [`Metrics`] through the paginated `metrics` view method.
+/// Takes name of the Contract struct.
+#[macro_export]
+macro_rules! impl_metrics_view {
+    ($contract: ident) => {
+        use $crate::metrics::{Metric, MetricsView};
+
+        #[near_bindgen]
+        impl MetricsView for $contract {
+            fn metrics(
+                &self,
+                from_index: Option<near_sdk::json_types::U128>,
+                limit: Option<u64>,
+            ) -> Vec<Metric> {
+                $crate::metrics::Metrics::default().metrics(from_index, limit)
+            }
+
+            fn metrics_page(
+                &self,
+                from_cursor: Option<near_sdk::json_types::Cursor>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::Page<Metric> {
+                $crate::metrics::Metrics::default().metrics_page(from_cursor, limit)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn metric(name: &str, kind: MetricKind, value: u64) -> Metric {
+        Metric { name: name.to_string(), kind, value: U64(value) }
+    }
+
+    #[test]
+    fn counters_and_gauges() {
+        testing_env!(VMContextBuilder::new().build());
+        let metrics = Metrics::default();
+        assert!(metrics.is_empty());
+        incr!("transfers");
+        incr!("transfers", 4);
+        gauge!("accounts", 7);
+        gauge!("accounts", 3);
+        metrics.incr_by("transfers", u64::MAX);
+        assert_eq!(metrics.get("transfers"), Some(u64::MAX));
+        assert_eq!(metrics.get("accounts"), Some(3));
+        assert_eq!(metrics.get("other"), None);
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(Metrics::new(b"m").get("accounts"), None);
+    }
+
+    #[test]
+    fn pagination() {
+        testing_env!(VMContextBuilder::new().build());
+        let metrics = Metrics::default();
+        metrics.incr("a");
+        metrics.set_gauge("b", 2);
+        metrics.incr_by("c", 3);
+        let all = vec![
+            metric("a", MetricKind::Counter, 1),
+            metric("b", MetricKind::Gauge, 2),
+            metric("c", MetricKind::Counter, 3),
+        ];
+        assert_eq!(metrics.metrics(None, None), all);
+        assert_eq!(metrics.metrics(Some(U128(1)), Some(1)), all[1..2].to_vec());
+
+        let page = metrics.metrics_page(None, Some(2));
+        assert_eq!(page.items, all[..2].to_vec());
+        let page = metrics.metrics_page(page.next_cursor, Some(2));
+        assert_eq!(page.items, all[2..].to_vec());
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds")]
+    fn pagination_out_of_bounds() {
+        testing_env!(VMContextBuilder::new().build());
+        let metrics = Metrics::default();
+        metrics.incr("a");
+        metrics.metrics(Some(U128(2)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric is used as both a counter and a gauge")]
+    fn counter_used_as_gauge() {
+        testing_env!(VMContextBuilder::new().build());
+        let metrics = Metrics::default();
+        metrics.incr("a");
+        metrics.set_gauge("a", 1);
+    }
+}
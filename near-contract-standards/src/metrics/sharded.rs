@@ -0,0 +1,106 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, require, AccountId, IntoStorageKey};
+
+/// Hot counter, e.g. the number of transfers or the total fees of a token, split across `shards`
+/// storage entries so that concurrent updates by different callers touch different keys.
+///
+/// Each update adds to the shard selected by a hash of the predecessor, so that the updates of a
+/// caller always go to the same entry and the updates of many callers are spread evenly across
+/// the shards. [`total`](Self::total) reads and sums all shards, which makes it more expensive
+/// than updates, so it's meant for views.
+///
+/// Like [`Metrics`](super::Metrics), the shards are stored directly in contract storage under the
+/// prefix, with one entry of 16 bytes per shard. The counter itself only holds the prefix and the
+/// number of shards, and can be stored in the contract state.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq)]
+pub struct ShardedCounter {
+    prefix: Vec<u8>,
+    shards: u8,
+}
+
+impl ShardedCounter {
+    /// Creates a counter split across `shards` entries. Panics if `shards` is 0.
+    pub fn new<S>(prefix: S, shards: u8) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        require!(shards > 0, "A sharded counter needs at least one shard");
+        Self { prefix: prefix.into_storage_key(), shards }
+    }
+
+    pub fn shards(&self) -> u8 {
+        self.shards
+    }
+
+    /// Increments the counter by one.
+    pub fn incr(&self) {
+        self.incr_by(1)
+    }
+
+    /// Increments the counter by `amount` in the shard of the predecessor, saturating at
+    /// `u128::MAX`.
+    pub fn incr_by(&self, amount: u128) {
+        let shard = self.shard_of(&env::predecessor_account_id());
+        let value = self.shard_value(shard).saturating_add(amount);
+        env::storage_write(&self.shard_key(shard), &value.to_le_bytes());
+    }
+
+    /// Returns the index of the shard updated by the account.
+    pub fn shard_of(&self, account_id: &AccountId) -> u8 {
+        let hash = env::sha256_array(account_id.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[..8]);
+        (u64::from_le_bytes(bytes) % u64::from(self.shards)) as u8
+    }
+
+    /// Returns the value of one shard.
+    pub fn shard_value(&self, shard: u8) -> u128 {
+        env::storage_read(&self.shard_key(shard))
+            .map(|bytes| {
+                u128::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Cannot deserialize counter shard"))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns the value of the counter, the sum of all shards, saturating at `u128::MAX`.
+    pub fn total(&self) -> u128 {
+        (0..self.shards).fold(0u128, |total, shard| total.saturating_add(self.shard_value(shard)))
+    }
+
+    fn shard_key(&self, shard: u8) -> Vec<u8> {
+        [self.prefix.as_slice(), &[shard]].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn updates_are_spread_across_shards() {
+        let counter = ShardedCounter::new(b"c", 4);
+        for i in 0..6 {
+            testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(i)).build());
+            counter.incr_by(10);
+            counter.incr();
+        }
+        assert_eq!(counter.total(), 66);
+        let shards: Vec<u128> = (0..4).map(|shard| counter.shard_value(shard)).collect();
+        assert_eq!(shards.iter().sum::<u128>(), 66);
+        assert!(shards.iter().filter(|value| **value > 0).count() > 1);
+        assert_eq!(
+            counter.shard_value(counter.shard_of(&accounts(0))) % 11,
+            0,
+            "the updates of an account go to the same shard"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A sharded counter needs at least one shard")]
+    fn no_shards() {
+        ShardedCounter::new(b"c", 0);
+    }
+}
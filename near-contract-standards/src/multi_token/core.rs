@@ -0,0 +1,83 @@
+use crate::multi_token::TokenId;
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use near_sdk::PromiseOrValue;
+
+pub trait MultiTokenCore {
+    /// Transfers positive `amount` of the token `token_id` from the `env::predecessor_account_id`
+    /// to `receiver_id`. Exactly 1 yoctoNEAR must be attached.
+    /// See [the Security section](https://github.com/near/NEPs/blob/master/neps/nep-0245.md#security)
+    /// of the standard.
+    ///
+    /// Arguments:
+    /// - `receiver_id` - the account ID of the receiver.
+    /// - `token_id` - the token to transfer.
+    /// - `amount` - the amount of tokens to transfer. Must be a positive number in decimal string representation.
+    /// - `approval` - the owner and the approval ID of an approved transfer, made on behalf of
+    ///   the owner.
+    /// - `memo` - an optional string field in a free form to associate a memo with this transfer.
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    );
+
+    /// Transfers positive `amounts` of the tokens `token_ids` from the `env::predecessor_account_id`
+    /// to `receiver_id`, like [`mt_transfer`](Self::mt_transfer) for each token. The transfer
+    /// fails as a whole if any of the amounts can't be transferred.
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+
+    /// Transfers positive `amount` of the token `token_id` from the `env::predecessor_account_id`
+    /// to `receiver_id` account. Then calls `mt_on_transfer` method on `receiver_id` contract and
+    /// attaches a callback to resolve this transfer. `mt_on_transfer` must return the amounts of
+    /// tokens unused by the receiver contract, which are refunded to the sender by the resolve
+    /// transfer callback. Exactly 1 yoctoNEAR must be attached.
+    ///
+    /// Token contract must pass all the remaining unused gas to the `mt_on_transfer` call.
+    ///
+    /// Returns a promise which will result in the amounts of tokens withdrawn from sender's
+    /// account.
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Batched version of [`mt_transfer_call`](Self::mt_transfer_call), calling `mt_on_transfer`
+    /// once with all the transferred tokens.
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Returns the balance of the token of the account, `"0"` if the account has none.
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128;
+
+    /// Returns the balances of the tokens of the account, in the order of `token_ids`.
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128>;
+
+    /// Returns the total supply of the token, or `None` if it was never minted.
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128>;
+
+    /// Returns the total supplies of the tokens, in the order of `token_ids`.
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>>;
+}
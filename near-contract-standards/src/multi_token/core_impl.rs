@@ -0,0 +1,751 @@
+use crate::multi_token::core::MultiTokenCore;
+use crate::multi_token::events::{MtBurn, MtMint, MtTransfer};
+use crate::multi_token::resolver::MultiTokenResolver;
+use crate::multi_token::TokenId;
+use crate::storage_management::StorageError;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::layout::{StorageLayout, StorageRegion};
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, log, require, AccountId, Balance, FunctionError, Gas,
+    IntoStorageKey, PromiseError, PromiseOrValue, StorageUsage,
+};
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// Gas of the resolve transfer callback for each transferred token, on top of
+/// [`GAS_FOR_RESOLVE_TRANSFER`].
+const GAS_FOR_RESOLVE_TRANSFER_PER_TOKEN: Gas = Gas(1_000_000_000_000);
+const GAS_FOR_MT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER.0);
+
+const NO_DEPOSIT: Balance = 0;
+
+/// Upper bound for the JSON encoded `U128` of each token returned by `mt_on_transfer`. Larger
+/// results are treated like failures without being deserialized.
+const MAX_MT_ON_TRANSFER_RESULT_BYTES_PER_TOKEN: u64 = 128;
+
+#[ext_contract(ext_self)]
+trait MultiTokenResolver {
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128>;
+}
+
+#[ext_contract(ext_multi_token_receiver)]
+pub trait MultiTokenReceiver {
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+#[ext_contract(ext_multi_token)]
+pub trait MultiTokenContract {
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    );
+
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128;
+
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128>;
+
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128>;
+
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>>;
+}
+
+/// Implementation of a MultiToken standard.
+/// Allows to include NEP-245 compatible semi-fungible tokens to any contract, e.g. the items of
+/// a game, where each token ID is a fungible token of its own.
+/// There are next traits that any contract may implement:
+///     - MultiTokenCore -- interface with mt_transfer methods. MultiToken provides methods for it.
+///     - MultiTokenResolver -- callback of the transfer calls. MultiToken provides methods for it.
+///
+/// Tokens are created by minting them with [`internal_mint`](Self::internal_mint). Accounts
+/// register with the storage management standard, exposed with
+/// [`impl_multi_token_storage`](crate::impl_multi_token_storage), and their storage deposit
+/// pays for their balances: the storage of the balance of a token, added when the account first
+/// receives it, is charged to the deposit and released when the balance goes back to zero.
+/// Transfers to accounts which aren't registered or lack the storage deposit fail.
+///
+/// Approvals aren't supported, so the transfer methods panic if they're given any.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MultiToken {
+    /// Balance by token ID and account ID, removed when it's zero.
+    pub balances: LookupMap<(TokenId, AccountId), Balance>,
+
+    /// Total supply by token ID, set when the token is first minted.
+    pub supply: LookupMap<TokenId, Balance>,
+
+    /// Storage of the registered accounts.
+    pub accounts: LookupMap<AccountId, AccountStorage>,
+
+    /// The storage size in bytes of the registration of an account.
+    pub account_storage_usage: StorageUsage,
+}
+
+/// Storage deposit of an account registered with a [`MultiToken`] and the bytes used by its
+/// balances, on top of the bytes of its registration.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccountStorage {
+    pub deposit: Balance,
+    pub used_bytes: StorageUsage,
+}
+
+impl MultiToken {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        let mut this = Self {
+            balances: LookupMap::new([prefix.as_slice(), b"b"].concat()),
+            supply: LookupMap::new([prefix.as_slice(), b"s"].concat()),
+            accounts: LookupMap::new([prefix.as_slice(), b"a"].concat()),
+            account_storage_usage: 0,
+        };
+        this.measure_account_storage_usage();
+        this
+    }
+
+    fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
+        self.accounts.insert(&tmp_account_id, &AccountStorage::default());
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.accounts.remove(&tmp_account_id);
+    }
+
+    pub fn internal_balance_of(&self, token_id: &TokenId, account_id: &AccountId) -> Balance {
+        self.balances.get(&(token_id.clone(), account_id.clone())).unwrap_or(0)
+    }
+
+    /// Returns the storage deposit of the account which isn't used by its registration and
+    /// balances.
+    pub fn internal_storage_available(&self, account: &AccountStorage) -> Balance {
+        let used_bytes = (self.account_storage_usage + account.used_bytes) as Balance;
+        account.deposit.saturating_sub(used_bytes * env::storage_byte_cost())
+    }
+
+    /// Sets the balance of the token of the account, charging the storage of a new balance to
+    /// the storage deposit of the account and releasing it when the balance is zero. Fails,
+    /// leaving the balance unchanged, if the account isn't registered or its deposit doesn't
+    /// cover the storage.
+    fn internal_try_set_balance(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        balance: Balance,
+    ) -> Result<(), StorageError> {
+        let mut account = self
+            .accounts
+            .get(account_id)
+            .ok_or_else(|| StorageError::AccountNotRegistered(account_id.clone()))?;
+        let key = (token_id.clone(), account_id.clone());
+        let initial_storage_usage = env::storage_usage();
+        let previous = if balance == 0 {
+            self.balances.remove(&key)
+        } else {
+            self.balances.insert(&key, &balance)
+        };
+        let storage_usage = env::storage_usage();
+        if storage_usage > initial_storage_usage {
+            account.used_bytes += storage_usage - initial_storage_usage;
+            let required_deposit = (self.account_storage_usage + account.used_bytes) as Balance
+                * env::storage_byte_cost();
+            if account.deposit < required_deposit {
+                match previous {
+                    Some(previous) => self.balances.insert(&key, &previous),
+                    None => self.balances.remove(&key),
+                };
+                return Err(StorageError::InsufficientAvailableBalance);
+            }
+        } else {
+            account.used_bytes -= initial_storage_usage - storage_usage;
+        }
+        self.accounts.insert(account_id, &account);
+        Ok(())
+    }
+
+    fn internal_set_balance(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        balance: Balance,
+    ) {
+        self.internal_try_set_balance(token_id, account_id, balance)
+            .unwrap_or_else(|err| err.panic())
+    }
+
+    /// Deposits `amount` of the token to the account, failing if the account isn't registered
+    /// or its storage deposit doesn't cover a new balance.
+    pub fn internal_try_deposit(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> Result<(), StorageError> {
+        let balance = self.internal_balance_of(token_id, account_id);
+        let new_balance =
+            balance.checked_add(amount).unwrap_or_else(|| env::panic_str("Balance overflow"));
+        self.internal_try_set_balance(token_id, account_id, new_balance)
+    }
+
+    pub fn internal_deposit(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        self.internal_try_deposit(token_id, account_id, amount).unwrap_or_else(|err| err.panic())
+    }
+
+    pub fn internal_withdraw(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let balance = self.internal_balance_of(token_id, account_id);
+        let new_balance = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough balance"));
+        self.internal_set_balance(token_id, account_id, new_balance);
+    }
+
+    /// Mints `amount` of the token to the account, increasing its total supply, and emits an
+    /// `mt_mint` event. Creates the token if it was never minted.
+    pub fn internal_mint(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        require!(amount > 0, "The amount should be a positive number");
+        let supply = self.supply.get(token_id).unwrap_or(0);
+        let new_supply =
+            supply.checked_add(amount).unwrap_or_else(|| env::panic_str("Total supply overflow"));
+        self.supply.insert(token_id, &new_supply);
+        self.internal_deposit(token_id, account_id, amount);
+        MtMint {
+            owner_id: account_id,
+            token_ids: &[token_id.as_str()],
+            amounts: &[U128(amount)],
+            memo,
+        }
+        .emit();
+    }
+
+    /// Burns `amount` of the token from the account, decreasing its total supply, and emits an
+    /// `mt_burn` event.
+    pub fn internal_burn(
+        &mut self,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        require!(amount > 0, "The amount should be a positive number");
+        self.internal_withdraw(token_id, account_id, amount);
+        let supply = self.supply.get(token_id).unwrap_or(0);
+        self.supply.insert(token_id, &(supply - amount));
+        MtBurn {
+            owner_id: account_id,
+            token_ids: &[token_id.as_str()],
+            amounts: &[U128(amount)],
+            memo,
+        }
+        .emit();
+    }
+
+    /// Transfers the `amounts` of the tokens `token_ids` from `sender_id` to `receiver_id` and
+    /// emits a single `mt_transfer` event.
+    pub fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[Balance],
+        memo: Option<&str>,
+    ) {
+        require!(sender_id != receiver_id, "Sender and receiver should be different");
+        require!(!token_ids.is_empty(), "At least one token is required");
+        require!(
+            token_ids.len() == amounts.len(),
+            "Token IDs and amounts should have the same length"
+        );
+        for (token_id, &amount) in token_ids.iter().zip(amounts) {
+            require!(amount > 0, "The amount should be a positive number");
+            self.internal_withdraw(token_id, sender_id, amount);
+            self.internal_deposit(token_id, receiver_id, amount);
+        }
+        let token_ids: Vec<&str> = token_ids.iter().map(String::as_str).collect();
+        let amounts: Vec<U128> = amounts.iter().map(|&amount| U128(amount)).collect();
+        MtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            token_ids: &token_ids,
+            amounts: &amounts,
+            memo,
+        }
+        .emit();
+    }
+
+    /// Transfers the tokens from `sender_id` to `receiver_id` and calls `mt_on_transfer` on the
+    /// receiver, with unused tokens refunded to `sender_id` by `mt_resolve_transfer`.
+    pub fn internal_transfer_call(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<Balance>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        let gas_for_resolve = GAS_FOR_RESOLVE_TRANSFER
+            + Gas(GAS_FOR_RESOLVE_TRANSFER_PER_TOKEN.0 * token_ids.len() as u64);
+        require!(
+            env::prepaid_gas() > GAS_FOR_MT_TRANSFER_CALL + gas_for_resolve,
+            "More gas is required"
+        );
+        self.internal_transfer(&sender_id, &receiver_id, &token_ids, &amounts, memo.as_deref());
+        let previous_owner_ids = vec![sender_id.clone(); token_ids.len()];
+        let amounts: Vec<U128> = amounts.into_iter().map(U128).collect();
+        // Initiating receiver's call and the callback
+        ext_multi_token_receiver::mt_on_transfer(
+            sender_id,
+            previous_owner_ids.clone(),
+            token_ids.clone(),
+            amounts.clone(),
+            msg,
+            receiver_id.clone(),
+            NO_DEPOSIT,
+            env::prepaid_gas() - GAS_FOR_MT_TRANSFER_CALL - gas_for_resolve,
+        )
+        .then(ext_self::mt_resolve_transfer(
+            previous_owner_ids,
+            receiver_id,
+            token_ids,
+            amounts,
+            env::current_account_id(),
+            NO_DEPOSIT,
+            gas_for_resolve,
+        ))
+        .into()
+    }
+
+    /// Refunds the amounts unused by `mt_on_transfer` from `receiver_id` to the previous owners,
+    /// up to the balances left to the receiver. Returns the used amounts.
+    pub fn internal_resolve_transfer(
+        &mut self,
+        previous_owner_ids: &[AccountId],
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: Vec<U128>,
+    ) -> Vec<Balance> {
+        require!(
+            previous_owner_ids.len() == token_ids.len() && token_ids.len() == amounts.len(),
+            "Previous owner IDs, token IDs and amounts should have the same length"
+        );
+        let amounts: Vec<Balance> = amounts.into_iter().map(|amount| amount.0).collect();
+
+        // Get the unused amounts from the `mt_on_transfer` call result.
+        let max_result_len = MAX_MT_ON_TRANSFER_RESULT_BYTES_PER_TOKEN * amounts.len() as u64;
+        let unused_amounts = match env::promise_result_bounded(0, max_result_len) {
+            Err(PromiseError::NotReady) => env::abort(),
+            Ok(value) => near_sdk::serde_json::from_slice::<Vec<U128>>(&value)
+                .ok()
+                .filter(|unused_amounts| unused_amounts.len() == amounts.len())
+                .map(|unused_amounts| {
+                    unused_amounts
+                        .iter()
+                        .zip(&amounts)
+                        .map(|(unused_amount, &amount)| std::cmp::min(amount, unused_amount.0))
+                        .collect()
+                })
+                .unwrap_or_else(|| amounts.clone()),
+            Err(_) => amounts.clone(),
+        };
+
+        let mut used_amounts = amounts;
+        for (i, unused_amount) in unused_amounts.into_iter().enumerate() {
+            let token_id = &token_ids[i];
+            let receiver_balance = self.internal_balance_of(token_id, receiver_id);
+            let refund_amount = std::cmp::min(receiver_balance, unused_amount);
+            if refund_amount == 0 {
+                continue;
+            }
+            let previous_owner_id = &previous_owner_ids[i];
+            if let Err(err) = self.internal_try_deposit(token_id, previous_owner_id, refund_amount)
+            {
+                // The previous owner unregistered or withdrew its storage deposit meanwhile.
+                log!("The refund of {} to {} failed: {}", token_id, previous_owner_id, err);
+                continue;
+            }
+            self.internal_set_balance(token_id, receiver_id, receiver_balance - refund_amount);
+            used_amounts[i] -= refund_amount;
+            MtTransfer {
+                old_owner_id: receiver_id,
+                new_owner_id: previous_owner_id,
+                token_ids: &[token_id.as_str()],
+                amounts: &[U128(refund_amount)],
+                memo: Some("refund"),
+            }
+            .emit();
+        }
+        used_amounts
+    }
+}
+
+fn assert_no_approval(approval: Option<&(AccountId, u64)>) {
+    require!(approval.is_none(), "Approvals are not supported");
+}
+
+fn assert_no_approvals(approvals: Option<Vec<Option<(AccountId, u64)>>>) {
+    for approval in approvals.unwrap_or_default() {
+        assert_no_approval(approval.as_ref());
+    }
+}
+
+impl MultiTokenCore for MultiToken {
+    fn mt_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        assert_no_approval(approval.as_ref());
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer(&sender_id, &receiver_id, &[token_id], &[amount.0], memo.as_deref());
+    }
+
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        assert_no_approvals(approvals);
+        let sender_id = env::predecessor_account_id();
+        let amounts: Vec<Balance> = amounts.into_iter().map(|amount| amount.0).collect();
+        self.internal_transfer(&sender_id, &receiver_id, &token_ids, &amounts, memo.as_deref());
+    }
+
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        assert_no_approval(approval.as_ref());
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_call(
+            sender_id,
+            receiver_id,
+            vec![token_id],
+            vec![amount.0],
+            memo,
+            msg,
+        )
+    }
+
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        assert_no_approvals(approvals);
+        let sender_id = env::predecessor_account_id();
+        let amounts = amounts.into_iter().map(|amount| amount.0).collect();
+        self.internal_transfer_call(sender_id, receiver_id, token_ids, amounts, memo, msg)
+    }
+
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+        self.internal_balance_of(&token_id, &account_id).into()
+    }
+
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128> {
+        token_ids
+            .iter()
+            .map(|token_id| self.internal_balance_of(token_id, &account_id).into())
+            .collect()
+    }
+
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
+        self.supply.get(&token_id).map(U128)
+    }
+
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>> {
+        token_ids.iter().map(|token_id| self.supply.get(token_id).map(U128)).collect()
+    }
+}
+
+impl MultiTokenResolver for MultiToken {
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128> {
+        self.internal_resolve_transfer(&previous_owner_ids, &receiver_id, &token_ids, amounts)
+            .into_iter()
+            .map(U128)
+            .collect()
+    }
+}
+
+impl StorageLayout for MultiToken {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![
+            StorageRegion::map::<(TokenId, AccountId), Balance>([prefix, b"b"].concat()),
+            StorageRegion::map::<TokenId, Balance>([prefix, b"s"].concat()),
+            StorageRegion::map::<AccountId, AccountStorage>([prefix, b"a"].concat()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_management::StorageManagement;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+    fn setup() -> MultiToken {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(ONE_NEAR)
+            .build());
+        let mut tokens = MultiToken::new(b"mt".to_vec());
+        tokens.storage_deposit(None, None);
+        tokens.storage_deposit(Some(accounts(1)), None);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        tokens.internal_mint(&"sword".to_string(), &accounts(0), 10, None);
+        tokens.internal_mint(&"shield".to_string(), &accounts(0), 5, None);
+        tokens
+    }
+
+    #[test]
+    fn batch_transfer() {
+        let mut tokens = setup();
+        tokens.mt_batch_transfer(
+            accounts(1),
+            vec!["sword".to_string(), "shield".to_string()],
+            vec![U128(3), U128(5)],
+            None,
+            None,
+        );
+        assert_eq!(
+            tokens
+                .mt_batch_balance_of(accounts(1), vec!["sword".to_string(), "shield".to_string()]),
+            vec![U128(3), U128(5)]
+        );
+        assert_eq!(tokens.mt_balance_of(accounts(0), "shield".to_string()), U128(0));
+        assert_eq!(tokens.mt_supply("sword".to_string()), Some(U128(10)));
+        assert_eq!(tokens.mt_supply("bow".to_string()), None);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","token_ids":["sword","shield"],"amounts":["3","5"]}]}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn transfer_more_than_balance() {
+        let mut tokens = setup();
+        tokens.mt_transfer(accounts(1), "shield".to_string(), U128(6), None, None);
+    }
+
+    #[test]
+    fn burn() {
+        let mut tokens = setup();
+        tokens.internal_burn(&"sword".to_string(), &accounts(0), 4, Some("crafting"));
+        assert_eq!(tokens.mt_balance_of(accounts(0), "sword".to_string()), U128(6));
+        assert_eq!(tokens.mt_supply("sword".to_string()), Some(U128(6)));
+    }
+
+    #[test]
+    fn resolve_transfer_refunds_unused() {
+        let mut tokens = setup();
+        let token_ids = vec!["sword".to_string(), "shield".to_string()];
+        tokens.mt_batch_transfer(
+            accounts(1),
+            token_ids.clone(),
+            vec![U128(4), U128(2)],
+            None,
+            None,
+        );
+
+        let unused = near_sdk::serde_json::to_vec(&vec![U128(1), U128(5)]).unwrap();
+        testing_env!(
+            VMContextBuilder::new().current_account_id(accounts(3)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(unused)]
+        );
+        let used = tokens.mt_resolve_transfer(
+            vec![accounts(0), accounts(0)],
+            accounts(1),
+            token_ids.clone(),
+            vec![U128(4), U128(2)],
+        );
+        // The unused amount of shields is capped by the transferred amount.
+        assert_eq!(used, vec![U128(3), U128(0)]);
+        assert_eq!(tokens.mt_batch_balance_of(accounts(0), token_ids), vec![U128(7), U128(5)]);
+    }
+
+    #[test]
+    fn resolve_failed_transfer_call() {
+        let mut tokens = setup();
+        tokens.mt_transfer(accounts(1), "sword".to_string(), U128(4), None, None);
+        testing_env!(
+            VMContextBuilder::new().current_account_id(accounts(3)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let used = tokens.mt_resolve_transfer(
+            vec![accounts(0)],
+            accounts(1),
+            vec!["sword".to_string()],
+            vec![U128(4)],
+        );
+        assert_eq!(used, vec![U128(0)]);
+        assert!(tokens.balances.get(&("sword".to_string(), accounts(1))).is_none());
+    }
+
+    #[test]
+    fn balances_use_storage_deposit() {
+        let mut tokens = setup();
+        let deposit = tokens.storage_balance_of(accounts(1)).unwrap().total;
+        tokens.mt_transfer(accounts(1), "sword".to_string(), U128(4), None, None);
+        let used_bytes = tokens.accounts.get(&accounts(1)).unwrap().used_bytes;
+        assert!(used_bytes > 0);
+        let balance = tokens.storage_balance_of(accounts(1)).unwrap();
+        assert_eq!(balance.total, deposit);
+        assert_eq!(
+            balance.available.0,
+            deposit.0
+                - (tokens.account_storage_usage + used_bytes) as Balance * env::storage_byte_cost()
+        );
+
+        // Sending the whole balance back releases its storage.
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        tokens.mt_transfer(accounts(0), "sword".to_string(), U128(4), None, None);
+        assert_eq!(tokens.accounts.get(&accounts(1)).unwrap().used_bytes, 0);
+        assert!(tokens.storage_unregister(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account charlie is not registered")]
+    fn transfer_to_unregistered() {
+        let mut tokens = setup();
+        tokens.mt_transfer(accounts(2), "sword".to_string(), U128(1), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount is greater than the available storage balance")]
+    fn transfer_without_storage_deposit() {
+        let mut tokens = setup();
+        let mut account = tokens.accounts.get(&accounts(1)).unwrap();
+        account.deposit = tokens.storage_balance_bounds().min.0;
+        tokens.accounts.insert(&accounts(1), &account);
+        tokens.mt_transfer(accounts(1), "sword".to_string(), U128(1), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Approvals are not supported")]
+    fn transfer_with_approval() {
+        let mut tokens = setup();
+        tokens.mt_transfer(accounts(1), "sword".to_string(), U128(1), Some((accounts(0), 1)), None);
+    }
+
+    #[test]
+    fn refund_to_unregistered_stays_with_receiver() {
+        let mut tokens = setup();
+        tokens.mt_transfer(accounts(1), "shield".to_string(), U128(5), None, None);
+        tokens.accounts.remove(&accounts(0));
+        testing_env!(
+            VMContextBuilder::new().current_account_id(accounts(3)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let used = tokens.mt_resolve_transfer(
+            vec![accounts(0)],
+            accounts(1),
+            vec!["shield".to_string()],
+            vec![U128(5)],
+        );
+        assert_eq!(used, vec![U128(5)]);
+        assert_eq!(tokens.mt_balance_of(accounts(1), "shield".to_string()), U128(5));
+    }
+}
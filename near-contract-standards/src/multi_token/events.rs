@@ -0,0 +1,177 @@
+//! Standard for nep245 (Multi Token) events.
+//!
+//! These events will be picked up by the NEAR indexer.
+//!
+//! <https://github.com/near/NEPs/blob/master/neps/nep-0245.md#events>
+//!
+//! This is an extension of the events format (nep-297):
+//! <https://github.com/near/NEPs/blob/master/specs/Standards/EventsFormat.md>
+//!
+//! The three events in this standard are [`MtMint`], [`MtTransfer`], and [`MtBurn`].
+//!
+//! These events can be logged by calling `.emit()` on them if a single event, or calling
+//! [`MtMint::emit_many`], [`MtTransfer::emit_many`],
+//! or [`MtBurn::emit_many`] respectively.
+
+use crate::event::NearEvent;
+use near_sdk::json_types::U128;
+use near_sdk::AccountId;
+use serde::Serialize;
+
+/// Data to log for an MT mint event. To log this event, call [`.emit()`](MtMint::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MtMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    pub amounts: &'a [U128],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl MtMint<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an MT mint event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MtMint`] represents the data of each mint.
+    pub fn emit_many(data: &[MtMint<'_>]) {
+        new_245_v1(Nep245EventKind::MtMint(data)).emit()
+    }
+}
+
+/// Data to log for an MT transfer event. To log this event,
+/// call [`.emit()`](MtTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MtTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    pub amounts: &'a [U128],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl MtTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an MT transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MtTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[MtTransfer<'_>]) {
+        new_245_v1(Nep245EventKind::MtTransfer(data)).emit()
+    }
+}
+
+/// Data to log for an MT burn event. To log this event, call [`.emit()`](MtBurn::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MtBurn<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    pub amounts: &'a [U128],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl MtBurn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an MT burn event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MtBurn`] represents the data of each burn.
+    pub fn emit_many(data: &[MtBurn<'_>]) {
+        new_245_v1(Nep245EventKind::MtBurn(data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Nep245Event<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: Nep245EventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Nep245EventKind<'a> {
+    MtMint(&'a [MtMint<'a>]),
+    MtTransfer(&'a [MtTransfer<'a>]),
+    MtBurn(&'a [MtBurn<'a>]),
+}
+
+fn new_245<'a>(version: &'static str, event_kind: Nep245EventKind<'a>) -> NearEvent<'a> {
+    NearEvent::Nep245(Nep245Event { version, event_kind })
+}
+
+fn new_245_v1(event_kind: Nep245EventKind) -> NearEvent {
+    new_245("1.0.0", event_kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::{test_utils, AccountId};
+
+    fn bob() -> AccountId {
+        AccountId::new_unchecked("bob".to_string())
+    }
+
+    fn alice() -> AccountId {
+        AccountId::new_unchecked("alice".to_string())
+    }
+
+    #[test]
+    fn mt_mint() {
+        let owner_id = &bob();
+        MtMint { owner_id, token_ids: &["0", "1"], amounts: &[U128(1), U128(100)], memo: None }
+            .emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_mint","data":[{"owner_id":"bob","token_ids":["0","1"],"amounts":["1","100"]}]}"#
+        );
+    }
+
+    #[test]
+    fn mt_burns() {
+        let owner_id = &bob();
+        MtBurn::emit_many(&[
+            MtBurn { owner_id, token_ids: &["0"], amounts: &[U128(1)], memo: None },
+            MtBurn { owner_id, token_ids: &["1"], amounts: &[U128(2)], memo: Some("has memo") },
+        ]);
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_burn","data":[{"owner_id":"bob","token_ids":["0"],"amounts":["1"]},{"owner_id":"bob","token_ids":["1"],"amounts":["2"],"memo":"has memo"}]}"#
+        );
+    }
+
+    #[test]
+    fn mt_transfer() {
+        let old_owner_id = &bob();
+        let new_owner_id = &alice();
+        MtTransfer {
+            old_owner_id,
+            new_owner_id,
+            token_ids: &["0"],
+            amounts: &[U128(10)],
+            memo: Some("refund"),
+        }
+        .emit();
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"nep245","version":"1.0.0","event":"mt_transfer","data":[{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0"],"amounts":["10"],"memo":"refund"}]}"#
+        );
+    }
+}
@@ -0,0 +1,155 @@
+/// The core methods for a basic multi token. Extension standards may be
+/// added in addition to this macro.
+#[macro_export]
+macro_rules! impl_multi_token_core {
+    ($contract: ident, $tokens: ident) => {
+        use $crate::multi_token::core::MultiTokenCore;
+        use $crate::multi_token::resolver::MultiTokenResolver;
+
+        #[near_bindgen]
+        impl MultiTokenCore for $contract {
+            #[payable]
+            fn mt_transfer(
+                &mut self,
+                receiver_id: AccountId,
+                token_id: $crate::multi_token::TokenId,
+                amount: U128,
+                approval: Option<(AccountId, u64)>,
+                memo: Option<String>,
+            ) {
+                self.$tokens.mt_transfer(receiver_id, token_id, amount, approval, memo)
+            }
+
+            #[payable]
+            fn mt_batch_transfer(
+                &mut self,
+                receiver_id: AccountId,
+                token_ids: Vec<$crate::multi_token::TokenId>,
+                amounts: Vec<U128>,
+                approvals: Option<Vec<Option<(AccountId, u64)>>>,
+                memo: Option<String>,
+            ) {
+                self.$tokens.mt_batch_transfer(receiver_id, token_ids, amounts, approvals, memo)
+            }
+
+            #[payable]
+            fn mt_transfer_call(
+                &mut self,
+                receiver_id: AccountId,
+                token_id: $crate::multi_token::TokenId,
+                amount: U128,
+                approval: Option<(AccountId, u64)>,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<Vec<U128>> {
+                self.$tokens.mt_transfer_call(receiver_id, token_id, amount, approval, memo, msg)
+            }
+
+            #[payable]
+            fn mt_batch_transfer_call(
+                &mut self,
+                receiver_id: AccountId,
+                token_ids: Vec<$crate::multi_token::TokenId>,
+                amounts: Vec<U128>,
+                approvals: Option<Vec<Option<(AccountId, u64)>>>,
+                memo: Option<String>,
+                msg: String,
+            ) -> PromiseOrValue<Vec<U128>> {
+                self.$tokens.mt_batch_transfer_call(
+                    receiver_id,
+                    token_ids,
+                    amounts,
+                    approvals,
+                    memo,
+                    msg,
+                )
+            }
+
+            fn mt_balance_of(
+                &self,
+                account_id: AccountId,
+                token_id: $crate::multi_token::TokenId,
+            ) -> U128 {
+                self.$tokens.mt_balance_of(account_id, token_id)
+            }
+
+            fn mt_batch_balance_of(
+                &self,
+                account_id: AccountId,
+                token_ids: Vec<$crate::multi_token::TokenId>,
+            ) -> Vec<U128> {
+                self.$tokens.mt_batch_balance_of(account_id, token_ids)
+            }
+
+            fn mt_supply(&self, token_id: $crate::multi_token::TokenId) -> Option<U128> {
+                self.$tokens.mt_supply(token_id)
+            }
+
+            fn mt_batch_supply(
+                &self,
+                token_ids: Vec<$crate::multi_token::TokenId>,
+            ) -> Vec<Option<U128>> {
+                self.$tokens.mt_batch_supply(token_ids)
+            }
+        }
+
+        #[near_bindgen]
+        impl MultiTokenResolver for $contract {
+            #[private]
+            fn mt_resolve_transfer(
+                &mut self,
+                previous_owner_ids: Vec<AccountId>,
+                receiver_id: AccountId,
+                token_ids: Vec<$crate::multi_token::TokenId>,
+                amounts: Vec<U128>,
+            ) -> Vec<U128> {
+                self.$tokens.mt_resolve_transfer(
+                    previous_owner_ids,
+                    receiver_id,
+                    token_ids,
+                    amounts,
+                )
+            }
+        }
+    };
+}
+
+/// Storage management of the accounts of a multi token, whose storage deposits pay for their
+/// balances.
+/// Takes name of the Contract struct and the inner field for the tokens.
+#[macro_export]
+macro_rules! impl_multi_token_storage {
+    ($contract: ident, $tokens: ident) => {
+        use $crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+
+        #[near_bindgen]
+        impl StorageManagement for $contract {
+            #[payable]
+            fn storage_deposit(
+                &mut self,
+                account_id: Option<AccountId>,
+                registration_only: Option<bool>,
+            ) -> StorageBalance {
+                self.$tokens.storage_deposit(account_id, registration_only)
+            }
+
+            #[payable]
+            fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+                self.$tokens.storage_withdraw(amount)
+            }
+
+            #[payable]
+            fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+                self.$tokens.storage_unregister(force)
+            }
+
+            fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+                self.$tokens.storage_balance_bounds()
+            }
+
+            fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+                self.$tokens.storage_balance_of(account_id)
+            }
+        }
+    };
+}
@@ -0,0 +1,11 @@
+pub mod core;
+pub mod core_impl;
+pub mod events;
+pub mod macros;
+pub mod receiver;
+pub mod resolver;
+pub mod storage_impl;
+
+pub use crate::non_fungible_token::TokenId;
+pub use core_impl::MultiToken;
+pub use macros::*;
@@ -0,0 +1,32 @@
+use crate::multi_token::TokenId;
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, PromiseOrValue};
+
+pub trait MultiTokenReceiver {
+    /// Called by multi token contract after `mt_transfer_call` or `mt_batch_transfer_call` was
+    /// initiated by `sender_id` with the transfer message given in `msg` field. The `amounts` of
+    /// the tokens `token_ids` were already transferred to this contract account and ready to be
+    /// used.
+    ///
+    /// The method must return the amounts of tokens that are *not* used/accepted by this contract
+    /// from the transferred amounts, in the order of `token_ids`, like `ft_on_transfer` does for
+    /// a single fungible token.
+    ///
+    /// Arguments:
+    /// - `sender_id` - the account ID that initiated the transfer.
+    /// - `previous_owner_ids` - the accounts which owned the tokens before the transfer.
+    /// - `token_ids` - the transferred tokens.
+    /// - `amounts` - the amounts of the tokens that were transferred to this account.
+    /// - `msg` - a string message that was passed with this transfer call. Composable protocols can
+    ///   use [`TransferCallMsg`](crate::transfer_call_msg::TransferCallMsg) as its schema.
+    ///
+    /// Returns the amounts of unused tokens that should be returned to the previous owners.
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
@@ -0,0 +1,14 @@
+use crate::multi_token::TokenId;
+use near_sdk::{json_types::U128, AccountId};
+
+pub trait MultiTokenResolver {
+    /// Refunds the amounts unused by `mt_on_transfer` to the previous owners and returns the used
+    /// amounts, in the order of `token_ids`.
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128>;
+}
@@ -0,0 +1,108 @@
+use crate::multi_token::core_impl::AccountStorage;
+use crate::multi_token::MultiToken;
+use crate::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageError, StorageManagement,
+};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_min_deposit, assert_one_yocto, env, log, AccountId, Balance, FunctionError, Promise,
+};
+
+impl MultiToken {
+    fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        self.accounts.get(account_id).map(|account| StorageBalance {
+            total: account.deposit.into(),
+            available: self.internal_storage_available(&account).into(),
+        })
+    }
+}
+
+impl StorageManagement for MultiToken {
+    /// Registers the account or adds the attached deposit to its storage deposit, which pays
+    /// for the balances of the tokens it receives. With `registration_only`, only the minimum
+    /// deposit of a new account is kept and the rest is refunded.
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount: Balance = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let refund = match self.accounts.get(&account_id) {
+            Some(_) if registration_only => {
+                log!("The account is already registered, refunding the deposit");
+                amount
+            }
+            Some(mut account) => {
+                account.deposit += amount;
+                self.accounts.insert(&account_id, &account);
+                0
+            }
+            None => {
+                let min_balance = self.storage_balance_bounds().min.0;
+                assert_min_deposit(min_balance);
+                let deposit = if registration_only { min_balance } else { amount };
+                self.accounts.insert(&account_id, &AccountStorage { deposit, used_bytes: 0 });
+                amount - deposit
+            }
+        };
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    /// Withdraws the storage deposit which isn't used by the balances of the predecessor.
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .accounts
+            .get(&account_id)
+            .unwrap_or_else(|| StorageError::AccountNotRegistered(account_id.clone()).panic());
+        let available = self.internal_storage_available(&account);
+        let amount = amount.map_or(available, |amount| amount.0);
+        if amount > available {
+            StorageError::InsufficientAvailableBalance.panic();
+        }
+        if amount > 0 {
+            account.deposit -= amount;
+            self.accounts.insert(&account_id, &account);
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    /// Unregisters the predecessor and refunds its storage deposit. Panics if it has a balance
+    /// of any token, even with `force`, since its balances can't be enumerated to burn them.
+    #[allow(unused_variables)]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        match self.accounts.get(&account_id) {
+            Some(account) if account.used_bytes > 0 => StorageError::PositiveBalance.panic(),
+            Some(account) => {
+                self.accounts.remove(&account_id);
+                if account.deposit > 0 {
+                    Promise::new(account_id).transfer(account.deposit);
+                }
+                true
+            }
+            None => {
+                log!("The account {} is not registered", &account_id);
+                false
+            }
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let required_storage_balance =
+            Balance::from(self.account_storage_usage) * env::storage_byte_cost();
+        StorageBalanceBounds { min: required_storage_balance.into(), max: None }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.internal_storage_balance_of(&account_id)
+    }
+}
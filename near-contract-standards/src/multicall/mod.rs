@@ -0,0 +1,480 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{paginated_range, UnorderedSet};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::layout::{StorageLayout, StorageRegion};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    env, require, AccountId, Balance, Gas, IntoStorageKey, Promise, PromiseError, PromiseOrValue,
+};
+
+/// Gas attached to each `on_multicall` callback, on top of the gas of the calls it schedules.
+pub const GAS_FOR_RESOLVE_MULTICALL: Gas = Gas(10_000_000_000_000);
+/// Maximum number of calls of one multicall.
+pub const MAX_MULTICALL_CALLS: usize = 16;
+
+/// Function call of a multicall.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CallEntry {
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub args: Base64VecU8,
+    /// Deposit attached to the call, in yoctoⓃ.
+    pub deposit: U128,
+    pub gas: U64,
+}
+
+/// How the calls of a multicall are executed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum MulticallStrategy {
+    /// All calls are sent at once and run in any order.
+    Parallel,
+    /// Each call is sent once the previous one succeeded. The first failure stops the
+    /// multicall, so the calls after it aren't executed, and the deposits of the failed call and
+    /// of the calls after it are refunded to the caller.
+    Sequential,
+}
+
+/// Result of a call of a multicall, in the order of the calls.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CallResult {
+    pub success: bool,
+    /// Value returned by the call, `None` if it failed or returned more than
+    /// `env::MAX_PROMISE_RESULT_BYTES`.
+    pub value: Option<Base64VecU8>,
+}
+
+/// Batches of function calls to an allowlist of contracts, for DAO proposals and batching UIs.
+///
+/// The owner sends a list of [`CallEntry`]s with [`execute`](Self::execute), which are executed
+/// by the contract according to the [`MulticallStrategy`], and their results are aggregated by
+/// the `on_multicall` callback generated with [`impl_multicall`](crate::impl_multicall), which
+/// calls [`resolve`](Self::resolve) and returns a [`CallResult`] per executed call. Parallel
+/// calls are joined into one callback, while sequential calls are scheduled one at a time by the
+/// callback of the previous one, so each callback is attached the gas of the calls after it.
+///
+/// The calls are executed as the contract, so only the owner can execute them, and only on
+/// receivers in the allowlist of targets which the owner manages with
+/// [`add_target`](Self::add_target) and [`remove_target`](Self::remove_target). The deposits of
+/// the calls which fail or aren't executed are refunded to the caller, since the runtime refunds
+/// them to the contract.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Multicall {
+    pub owner_id: AccountId,
+    targets: UnorderedSet<AccountId>,
+}
+
+impl Multicall {
+    pub fn new<S>(prefix: S, owner_id: AccountId) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { owner_id, targets: UnorderedSet::new(prefix) }
+    }
+
+    /// Allows the calls to the account. Panics if the predecessor isn't the owner.
+    pub fn add_target(&mut self, account_id: &AccountId) -> bool {
+        self.assert_owner();
+        self.targets.insert(account_id)
+    }
+
+    /// Disallows the calls to the account. Panics if the predecessor isn't the owner.
+    pub fn remove_target(&mut self, account_id: &AccountId) -> bool {
+        self.assert_owner();
+        self.targets.remove(account_id)
+    }
+
+    pub fn is_target(&self, account_id: &AccountId) -> bool {
+        self.targets.contains(account_id)
+    }
+
+    /// Returns the allowed targets, paginated like the NFT enumeration.
+    pub fn targets(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let range = paginated_range(self.targets.len(), from_index, limit);
+        self.targets.as_vector().to_vec_paginated(range.start, range.end - range.start)
+    }
+
+    /// Sum of the deposits of the calls.
+    pub fn total_deposit(calls: &[CallEntry]) -> Balance {
+        calls.iter().fold(0, |total, call| {
+            total
+                .checked_add(call.deposit.0)
+                .unwrap_or_else(|| env::panic_str("Total deposit overflow"))
+        })
+    }
+
+    /// Executes the calls as the contract, with the `on_multicall` callback aggregating their
+    /// results. Panics if the predecessor isn't the owner, if there are no calls or more than
+    /// [`MAX_MULTICALL_CALLS`], or if a receiver isn't an allowed target. The deposits of the
+    /// calls are paid from the balance of the contract, and the deposits of the calls which fail
+    /// or aren't executed are refunded to the predecessor.
+    pub fn execute(&self, calls: Vec<CallEntry>, strategy: MulticallStrategy) -> Promise {
+        self.assert_owner();
+        require!(!calls.is_empty(), "Multicall requires at least one call");
+        require!(calls.len() <= MAX_MULTICALL_CALLS, "Too many calls");
+        for call in &calls {
+            if !self.targets.contains(&call.receiver_id) {
+                env::panic_str(&format!("{} is not an allowed target", call.receiver_id));
+            }
+        }
+        let caller_id = env::predecessor_account_id();
+        match strategy {
+            MulticallStrategy::Parallel => {
+                let joined = calls
+                    .iter()
+                    .map(function_call)
+                    .reduce(Promise::and)
+                    .unwrap_or_else(|| env::abort());
+                joined.then(resolve_call(&calls, strategy, &[], &caller_id))
+            }
+            MulticallStrategy::Sequential => {
+                function_call(&calls[0]).then(resolve_call(&calls, strategy, &[], &caller_id))
+            }
+        }
+    }
+
+    /// Resolves the calls of a multicall, given the results of the calls executed before the
+    /// last callback. Returns the results of all executed calls, or the promise of the next
+    /// sequential call. The deposits of the failed calls, and of the sequential calls after a
+    /// failure, are refunded to `caller_id`.
+    pub fn resolve(
+        calls: Vec<CallEntry>,
+        strategy: MulticallStrategy,
+        mut results: Vec<CallResult>,
+        caller_id: AccountId,
+    ) -> PromiseOrValue<Vec<CallResult>> {
+        match strategy {
+            MulticallStrategy::Parallel => {
+                let results: Vec<_> = (0..env::promise_results_count()).map(call_result).collect();
+                let failed: Vec<_> = calls
+                    .into_iter()
+                    .zip(&results)
+                    .filter(|(_, result)| !result.success)
+                    .map(|(call, _)| call)
+                    .collect();
+                refund(caller_id, &failed);
+                PromiseOrValue::Value(results)
+            }
+            MulticallStrategy::Sequential => {
+                let result = call_result(0);
+                let success = result.success;
+                results.push(result);
+                match calls.get(results.len()) {
+                    Some(next) if success => PromiseOrValue::Promise(
+                        function_call(next)
+                            .then(resolve_call(&calls, strategy, &results, &caller_id)),
+                    ),
+                    _ => {
+                        if !success {
+                            refund(caller_id, &calls[results.len() - 1..]);
+                        }
+                        PromiseOrValue::Value(results)
+                    }
+                }
+            }
+        }
+    }
+
+    fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+    }
+}
+
+impl StorageLayout for Multicall {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        UnorderedSet::<AccountId>::storage_layout(prefix)
+    }
+}
+
+/// Refunds the deposits of the calls, which the runtime refunded to the contract.
+fn refund(caller_id: AccountId, calls: &[CallEntry]) {
+    let deposit = Multicall::total_deposit(calls);
+    if deposit > 0 {
+        Promise::new(caller_id).transfer(deposit);
+    }
+}
+
+fn function_call(call: &CallEntry) -> Promise {
+    Promise::new(call.receiver_id.clone()).function_call(
+        call.method_name.clone(),
+        call.args.0.clone(),
+        call.deposit.0,
+        Gas(call.gas.0),
+    )
+}
+
+/// The `on_multicall` callback of the calls before `results.len()`, with the gas of the
+/// sequential calls it will schedule.
+fn resolve_call(
+    calls: &[CallEntry],
+    strategy: MulticallStrategy,
+    results: &[CallResult],
+    caller_id: &AccountId,
+) -> Promise {
+    let gas = match strategy {
+        MulticallStrategy::Parallel => GAS_FOR_RESOLVE_MULTICALL,
+        MulticallStrategy::Sequential => {
+            calls[results.len() + 1..].iter().fold(GAS_FOR_RESOLVE_MULTICALL, |gas, call| {
+                gas + Gas(call.gas.0) + GAS_FOR_RESOLVE_MULTICALL
+            })
+        }
+    };
+    let args = json!({
+        "calls": calls,
+        "strategy": strategy,
+        "results": results,
+        "caller_id": caller_id,
+    });
+    Promise::new(env::current_account_id()).function_call(
+        "on_multicall".to_string(),
+        args.to_string().into_bytes(),
+        0,
+        gas,
+    )
+}
+
+fn call_result(result_idx: u64) -> CallResult {
+    match env::promise_result_bounded(result_idx, env::MAX_PROMISE_RESULT_BYTES) {
+        Ok(value) => CallResult { success: true, value: Some(value.into()) },
+        Err(PromiseError::TooLarge(_)) => CallResult { success: true, value: None },
+        Err(_) => CallResult { success: false, value: None },
+    }
+}
+
+/// Exposes the `multicall` method of a [`Multicall`] field with its `on_multicall` callback,
+/// the owner-only `add_multicall_target` and `remove_multicall_target` methods, which require a
+/// deposit of exactly 1 yoctoNEAR, and the `multicall_targets` view. `multicall` requires a
+/// deposit equal to the total deposit of the calls. Takes name of the Contract struct and the inner field for the multicall.
+#[macro_export]
+macro_rules! impl_multicall {
+    ($contract: ident, $multicall: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            #[payable]
+            pub fn multicall(
+                &mut self,
+                calls: Vec<$crate::multicall::CallEntry>,
+                strategy: $crate::multicall::MulticallStrategy,
+            ) -> near_sdk::Promise {
+                near_sdk::require!(
+                    near_sdk::env::attached_deposit()
+                        == $crate::multicall::Multicall::total_deposit(&calls),
+                    "The attached deposit must equal the total deposit of the calls"
+                );
+                self.$multicall.execute(calls, strategy)
+            }
+
+            #[private]
+            pub fn on_multicall(
+                &mut self,
+                calls: Vec<$crate::multicall::CallEntry>,
+                strategy: $crate::multicall::MulticallStrategy,
+                results: Vec<$crate::multicall::CallResult>,
+                caller_id: AccountId,
+            ) -> near_sdk::PromiseOrValue<Vec<$crate::multicall::CallResult>> {
+                $crate::multicall::Multicall::resolve(calls, strategy, results, caller_id)
+            }
+
+            #[payable]
+            pub fn add_multicall_target(&mut self, account_id: AccountId) -> bool {
+                near_sdk::assert_one_yocto();
+                self.$multicall.add_target(&account_id)
+            }
+
+            #[payable]
+            pub fn remove_multicall_target(&mut self, account_id: AccountId) -> bool {
+                near_sdk::assert_one_yocto();
+                self.$multicall.remove_target(&account_id)
+            }
+
+            pub fn multicall_targets(
+                &self,
+                from_index: Option<near_sdk::json_types::U128>,
+                limit: Option<u64>,
+            ) -> Vec<AccountId> {
+                self.$multicall.targets(from_index, limit)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        multicall: Multicall,
+    }
+
+    impl_multicall!(Contract, multicall);
+
+    fn call(receiver_id: AccountId) -> CallEntry {
+        CallEntry {
+            receiver_id,
+            method_name: "ping".to_string(),
+            args: b"{}".to_vec().into(),
+            deposit: U128(1),
+            gas: U64(5_000_000_000_000),
+        }
+    }
+
+    fn callback(results: Vec<PromiseResult>) {
+        testing_env!(
+            VMContextBuilder::new().predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+    }
+
+    /// Transfers of the created receipts, by receiver.
+    fn refunds() -> Vec<(AccountId, Balance)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    fn setup() -> Multicall {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut multicall = Multicall::new(b"m", accounts(0));
+        multicall.add_target(&accounts(1));
+        multicall.add_target(&accounts(2));
+        multicall
+    }
+
+    #[test]
+    fn targets() {
+        let mut multicall = setup();
+        assert!(multicall.is_target(&accounts(1)));
+        assert!(!multicall.add_target(&accounts(1)));
+        assert_eq!(multicall.targets(Some(U128(1)), None), vec![accounts(2)]);
+        assert!(multicall.remove_target(&accounts(1)));
+        assert_eq!(multicall.targets(None, None), vec![accounts(2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "danny is not an allowed target")]
+    fn target_not_allowed() {
+        let multicall = setup();
+        multicall.execute(vec![call(accounts(1)), call(accounts(3))], MulticallStrategy::Parallel);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_executes() {
+        let multicall = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        multicall.execute(vec![call(accounts(1))], MulticallStrategy::Parallel);
+    }
+
+    #[test]
+    fn parallel_results() {
+        setup();
+        callback(vec![PromiseResult::Successful(b"1".to_vec()), PromiseResult::Failed]);
+        let results = match Multicall::resolve(
+            vec![call(accounts(1)), call(accounts(2))],
+            MulticallStrategy::Parallel,
+            vec![],
+            accounts(0),
+        ) {
+            PromiseOrValue::Value(results) => results,
+            PromiseOrValue::Promise(_) => panic!("Expected the results"),
+        };
+        assert_eq!(
+            results,
+            vec![
+                CallResult { success: true, value: Some(b"1".to_vec().into()) },
+                CallResult { success: false, value: None },
+            ]
+        );
+        // The deposit of the failed call is refunded.
+        assert_eq!(refunds(), vec![(accounts(0), 1)]);
+    }
+
+    #[test]
+    fn sequential_stops_at_failure() {
+        let calls = vec![call(accounts(1)), call(accounts(2)), call(accounts(1))];
+        setup();
+        callback(vec![PromiseResult::Successful(vec![])]);
+        assert!(matches!(
+            Multicall::resolve(calls.clone(), MulticallStrategy::Sequential, vec![], accounts(0)),
+            PromiseOrValue::Promise(_)
+        ));
+
+        callback(vec![PromiseResult::Failed]);
+        let ok = CallResult { success: true, value: Some(vec![].into()) };
+        let results = match Multicall::resolve(
+            calls,
+            MulticallStrategy::Sequential,
+            vec![ok.clone()],
+            accounts(0),
+        ) {
+            PromiseOrValue::Value(results) => results,
+            PromiseOrValue::Promise(_) => panic!("Expected the results"),
+        };
+        assert_eq!(results, vec![ok, CallResult { success: false, value: None }]);
+
+        // The deposits of the failed call and of the third call, which wasn't executed, are
+        // refunded.
+        assert_eq!(refunds(), vec![(accounts(0), 2)]);
+    }
+
+    #[test]
+    fn sequential_completes_without_refund() {
+        let calls = vec![call(accounts(1)), call(accounts(2))];
+        setup();
+        callback(vec![PromiseResult::Successful(vec![])]);
+        let ok = CallResult { success: true, value: Some(vec![].into()) };
+        let results = match Multicall::resolve(
+            calls,
+            MulticallStrategy::Sequential,
+            vec![ok.clone()],
+            accounts(0),
+        ) {
+            PromiseOrValue::Value(results) => results,
+            PromiseOrValue::Promise(_) => panic!("Expected the results"),
+        };
+        assert_eq!(results, vec![ok.clone(), ok]);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn target_methods_require_one_yocto() {
+        let mut contract = Contract { multicall: setup() };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        assert!(contract.add_multicall_target(accounts(3)));
+        assert!(contract.remove_multicall_target(accounts(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn add_target_without_yocto() {
+        let mut contract = Contract { multicall: setup() };
+        contract.add_multicall_target(accounts(3));
+    }
+
+    #[test]
+    fn total_deposit() {
+        assert_eq!(Multicall::total_deposit(&[call(accounts(1)), call(accounts(2))]), 2);
+    }
+}
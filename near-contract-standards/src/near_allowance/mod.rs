@@ -0,0 +1,357 @@
+use crate::event::NearEvent;
+use crate::non_fungible_token::refund_deposit;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{
+    assert_at_least_one_yocto, assert_one_yocto, env, ext_contract, require, AccountId, Balance,
+    ContractError, FunctionError, Gas, IntoStorageKey, Promise, PromiseError,
+};
+
+/// Gas attached to `pull_payment` by [`NearAllowanceClient`].
+pub const GAS_FOR_PULL_PAYMENT: Gas = Gas(10_000_000_000_000);
+
+const NO_DEPOSIT: Balance = 0;
+
+/// Interface of a contract holding [`NearAllowance`]s, exposed with
+/// [`impl_near_allowance`](crate::impl_near_allowance).
+#[ext_contract(ext_near_allowance)]
+pub trait NearAllowanceCore {
+    /// Adds the attached deposit, minus the storage cost of a new account, to the balance of the
+    /// predecessor. Returns the new balance.
+    fn near_allowance_deposit(&mut self) -> U128;
+
+    /// Withdraws `amount` from the balance of the predecessor. Requires one yoctoNEAR.
+    fn near_allowance_withdraw(&mut self, amount: U128) -> U128;
+
+    /// Authorizes `spender_id` to pull up to `allowance` from the balance of the predecessor,
+    /// replacing its previous allowance. Requires a deposit for the storage of a new allowance,
+    /// at least one yoctoNEAR.
+    fn near_allowance_approve(&mut self, spender_id: AccountId, allowance: U128);
+
+    /// Transfers `amount` from the balance of `owner_id` to the predecessor, within the allowance
+    /// of the predecessor. Returns the amount.
+    fn pull_payment(&mut self, owner_id: AccountId, amount: U128, memo: Option<String>) -> U128;
+
+    fn near_allowance_balance_of(&self, account_id: AccountId) -> U128;
+
+    fn near_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128;
+}
+
+/// Deposits of native NEAR which the depositors authorize other contracts to pull up to an
+/// allowance, e.g. for subscriptions billed in NEAR, without handing their funds over to the
+/// billing contracts.
+///
+/// The billing contract calls `pull_payment` through [`NearAllowanceClient`] and receives the
+/// amount with a transfer. Its callback reads the result with
+/// [`NearAllowanceClient::pull_payment_result`], which is an error if the allowance or the
+/// balance of the owner was too low. Every change of an allowance and every payment is logged
+/// as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"near_allowance","version":"1.0.0","event":"pull_payment","data":[{"owner_id":"alice.near","spender_id":"billing.near","amount":"100","memo":"March"}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct NearAllowance {
+    balances: LookupMap<AccountId, Balance>,
+    allowances: LookupMap<(AccountId, AccountId), Balance>,
+}
+
+impl NearAllowance {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            balances: LookupMap::new([prefix.as_slice(), b"b"].concat()),
+            allowances: LookupMap::new([prefix.as_slice(), b"a"].concat()),
+        }
+    }
+
+    /// Returns the balance of the account.
+    pub fn balance_of(&self, account_id: &AccountId) -> Balance {
+        self.balances.get(account_id).unwrap_or(0)
+    }
+
+    /// Returns the remaining allowance of the spender on the balance of the owner.
+    pub fn allowance(&self, owner_id: &AccountId, spender_id: &AccountId) -> Balance {
+        self.allowances.get(&(owner_id.clone(), spender_id.clone())).unwrap_or(0)
+    }
+
+    /// Adds the attached deposit to the balance of the predecessor, minus the storage cost of
+    /// the balance if the predecessor is a new account. Returns the new balance.
+    pub fn deposit(&mut self) -> Balance {
+        let account_id = env::predecessor_account_id();
+        let mut deposit = env::attached_deposit();
+        if !self.balances.contains_key(&account_id) {
+            let initial_storage = env::storage_usage();
+            self.balances.insert(&account_id, &0);
+            let storage_cost =
+                env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage);
+            if deposit < storage_cost {
+                ContractError::insufficient_deposit(storage_cost).panic()
+            }
+            deposit -= storage_cost;
+        }
+        let balance = self.balance_of(&account_id) + deposit;
+        self.balances.insert(&account_id, &balance);
+        balance
+    }
+
+    /// Withdraws `amount` from the balance of the predecessor, returning the new balance.
+    /// Requires exactly one yoctoNEAR attached.
+    pub fn withdraw(&mut self, amount: Balance) -> Balance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let balance = self.debit(&account_id, amount);
+        Promise::new(account_id).transfer(amount + 1);
+        balance
+    }
+
+    /// Sets the allowance of the spender on the balance of the predecessor, removing it if it's
+    /// zero. Requires at least one yoctoNEAR attached, and enough to cover the storage of a new
+    /// allowance, with the rest refunded.
+    pub fn approve(&mut self, spender_id: AccountId, allowance: Balance) {
+        assert_at_least_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let key = (owner_id.clone(), spender_id.clone());
+        let initial_storage = env::storage_usage();
+        if allowance == 0 {
+            self.allowances.remove(&key);
+        } else {
+            self.allowances.insert(&key, &allowance);
+        }
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage));
+        emit_event(
+            "allowance_set",
+            AllowanceEventData {
+                owner_id: &owner_id,
+                spender_id: &spender_id,
+                allowance: Some(U128(allowance)),
+                amount: None,
+                memo: None,
+            },
+        );
+    }
+
+    /// Transfers `amount` from the balance of the owner to the predecessor, decreasing the
+    /// allowance of the predecessor. Panics if the allowance or the balance is too low.
+    pub fn pull_payment(&mut self, owner_id: &AccountId, amount: Balance, memo: Option<String>) {
+        let spender_id = env::predecessor_account_id();
+        let key = (owner_id.clone(), spender_id.clone());
+        let allowance = self.allowances.get(&key).unwrap_or(0);
+        require!(allowance >= amount, "The allowance is too low");
+        self.debit(owner_id, amount);
+        self.allowances.insert(&key, &(allowance - amount));
+        if amount > 0 {
+            Promise::new(spender_id.clone()).transfer(amount);
+        }
+        emit_event(
+            "pull_payment",
+            AllowanceEventData {
+                owner_id,
+                spender_id: &spender_id,
+                allowance: None,
+                amount: Some(U128(amount)),
+                memo: memo.as_deref(),
+            },
+        );
+    }
+
+    fn debit(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        let balance = self
+            .balance_of(account_id)
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The balance is too low"));
+        self.balances.insert(account_id, &balance);
+        balance
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct NearAllowanceEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [AllowanceEventData<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct AllowanceEventData<'a> {
+    owner_id: &'a AccountId,
+    spender_id: &'a AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowance: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+}
+
+fn emit_event(event: &'static str, data: AllowanceEventData) {
+    NearEvent::NearAllowance(NearAllowanceEvent { version: "1.0.0", event, data: &[data] }).emit();
+}
+
+/// Client for pulling payments from a contract holding [`NearAllowance`]s.
+///
+/// ```ignore
+/// NearAllowanceClient::new(allowance_id)
+///     .pull_payment(subscriber_id.clone(), U128(price), Some("March".to_string()))
+///     .then(ext_self::on_payment(subscriber_id, ..));
+///
+/// // In `on_payment`:
+/// let paid = NearAllowanceClient::pull_payment_result(0).is_ok();
+/// ```
+pub struct NearAllowanceClient {
+    pub contract_id: AccountId,
+}
+
+impl NearAllowanceClient {
+    pub fn new(contract_id: AccountId) -> Self {
+        Self { contract_id }
+    }
+
+    pub fn pull_payment(&self, owner_id: AccountId, amount: U128, memo: Option<String>) -> Promise {
+        ext_near_allowance::pull_payment(
+            owner_id,
+            amount,
+            memo,
+            self.contract_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_PULL_PAYMENT,
+        )
+    }
+
+    /// Reads the result of [`pull_payment`](Self::pull_payment) in a callback, which is the
+    /// pulled amount, already transferred to this contract.
+    pub fn pull_payment_result(result_idx: u64) -> Result<U128, PromiseError> {
+        env::promise_result_json(result_idx)
+    }
+}
+
+/// Exposes the [`NearAllowanceCore`] methods of a [`NearAllowance`] field.
+/// Takes name of the Contract struct and the inner field for the allowances.
+#[macro_export]
+macro_rules! impl_near_allowance {
+    ($contract: ident, $allowance: ident) => {
+        use $crate::near_allowance::NearAllowanceCore;
+
+        #[near_bindgen]
+        impl NearAllowanceCore for $contract {
+            #[payable]
+            fn near_allowance_deposit(&mut self) -> near_sdk::json_types::U128 {
+                self.$allowance.deposit().into()
+            }
+
+            #[payable]
+            fn near_allowance_withdraw(
+                &mut self,
+                amount: near_sdk::json_types::U128,
+            ) -> near_sdk::json_types::U128 {
+                self.$allowance.withdraw(amount.into()).into()
+            }
+
+            #[payable]
+            fn near_allowance_approve(
+                &mut self,
+                spender_id: AccountId,
+                allowance: near_sdk::json_types::U128,
+            ) {
+                self.$allowance.approve(spender_id, allowance.into())
+            }
+
+            fn pull_payment(
+                &mut self,
+                owner_id: AccountId,
+                amount: near_sdk::json_types::U128,
+                memo: Option<String>,
+            ) -> near_sdk::json_types::U128 {
+                self.$allowance.pull_payment(&owner_id, amount.into(), memo);
+                amount
+            }
+
+            fn near_allowance_balance_of(
+                &self,
+                account_id: AccountId,
+            ) -> near_sdk::json_types::U128 {
+                self.$allowance.balance_of(&account_id).into()
+            }
+
+            fn near_allowance(
+                &self,
+                owner_id: AccountId,
+                spender_id: AccountId,
+            ) -> near_sdk::json_types::U128 {
+                self.$allowance.allowance(&owner_id, &spender_id).into()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const NEAR: Balance = 10u128.pow(24);
+
+    fn call(predecessor: usize, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(predecessor))
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    fn setup() -> NearAllowance {
+        let mut allowances = NearAllowance::new(b"n");
+        call(0, NEAR);
+        allowances.deposit();
+        call(0, NEAR / 100);
+        allowances.approve(accounts(1), 300);
+        allowances
+    }
+
+    #[test]
+    fn pull_within_allowance() {
+        let mut allowances = setup();
+        let balance = allowances.balance_of(&accounts(0));
+        assert!(balance > 0 && balance < NEAR);
+        call(1, 0);
+        allowances.pull_payment(&accounts(0), 100, Some("March".to_string()));
+        assert_eq!(allowances.allowance(&accounts(0), &accounts(1)), 200);
+        assert_eq!(allowances.balance_of(&accounts(0)), balance - 100);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"near_allowance","version":"1.0.0","event":"pull_payment","data":[{"owner_id":"alice","spender_id":"bob","amount":"100","memo":"March"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is too low")]
+    fn pull_over_allowance() {
+        let mut allowances = setup();
+        call(1, 0);
+        allowances.pull_payment(&accounts(0), 301, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is too low")]
+    fn pull_without_allowance() {
+        let mut allowances = setup();
+        call(2, 0);
+        allowances.pull_payment(&accounts(0), 1, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "INSUFFICIENT_DEPOSIT")]
+    fn deposit_below_storage_cost() {
+        call(0, 1);
+        NearAllowance::new(b"n").deposit();
+    }
+}
@@ -3,11 +3,14 @@
 use crate::non_fungible_token::approval::NonFungibleTokenApproval;
 use crate::non_fungible_token::token::TokenId;
 use crate::non_fungible_token::utils::{
-    assert_at_least_one_yocto, bytes_for_approved_account_id, refund_approved_account_ids,
-    refund_approved_account_ids_iter, refund_deposit,
+    bytes_for_approved_account_id, refund_approved_account_ids, refund_approved_account_ids_iter,
+    refund_deposit,
 };
 use crate::non_fungible_token::NonFungibleToken;
-use near_sdk::{assert_one_yocto, env, ext_contract, require, AccountId, Balance, Gas, Promise};
+use near_sdk::{
+    assert_at_least_one_yocto, assert_one_yocto, env, ext_contract, require, AccountId, Balance,
+    Gas, Promise,
+};
 
 const GAS_FOR_NFT_APPROVE: Gas = Gas(10_000_000_000_000);
 const NO_DEPOSIT: Balance = 0;
@@ -168,3 +171,65 @@ impl NonFungibleTokenApproval for NonFungibleToken {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+    fn setup() -> NonFungibleToken {
+        let mut token =
+            NonFungibleToken::new(b"o", accounts(0), Some(b"m"), Some(b"e"), Some(b"a"));
+        let metadata = near_sdk::serde_json::from_str("{}").unwrap();
+        token.internal_mint_with_refund("1".to_string(), accounts(1), Some(metadata), None);
+        token
+    }
+
+    fn context(predecessor: AccountId, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn approve_and_revoke() {
+        let mut token = setup();
+        context(accounts(1), ONE_NEAR);
+        assert!(token.nft_approve("1".to_string(), accounts(2), None).is_none());
+        token.nft_approve("1".to_string(), accounts(3), None);
+        assert!(token.nft_is_approved("1".to_string(), accounts(2), Some(1)));
+        assert!(!token.nft_is_approved("1".to_string(), accounts(2), Some(2)));
+        assert!(token.nft_is_approved("1".to_string(), accounts(3), None));
+
+        // Approving again replaces the approval id.
+        token.nft_approve("1".to_string(), accounts(2), None);
+        assert!(token.nft_is_approved("1".to_string(), accounts(2), Some(3)));
+
+        context(accounts(1), 1);
+        token.nft_revoke("1".to_string(), accounts(2));
+        assert!(!token.nft_is_approved("1".to_string(), accounts(2), None));
+        assert!(token.nft_is_approved("1".to_string(), accounts(3), None));
+        token.nft_revoke_all("1".to_string());
+        assert!(!token.nft_is_approved("1".to_string(), accounts(3), None));
+    }
+
+    #[test]
+    #[should_panic(expected = "Predecessor must be token owner.")]
+    fn approve_by_other_account() {
+        let mut token = setup();
+        context(accounts(2), ONE_NEAR);
+        token.nft_approve("1".to_string(), accounts(2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn revoke_without_yocto() {
+        let mut token = setup();
+        context(accounts(1), 0);
+        token.nft_revoke_all("1".to_string());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::non_fungible_token::events::NftTransfer;
+use crate::non_fungible_token::{NonFungibleToken, TokenId};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+
+/// One transfer of [`nft_transfer_batch`](NonFungibleTokenBatchTransfer::nft_transfer_batch),
+/// with the arguments of `nft_transfer`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferRequest {
+    pub receiver_id: AccountId,
+    pub token_id: TokenId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// Outcome of one transfer of a batch: `error` is the reason the transfer failed, or `None` if
+/// the token was transferred.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferResult {
+    pub token_id: TokenId,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Transfers of many tokens in one call, for marketplaces settling many sales in one
+/// transaction. Exposed with
+/// [`impl_non_fungible_token_batch_transfer`](crate::impl_non_fungible_token_batch_transfer).
+pub trait NonFungibleTokenBatchTransfer {
+    /// Transfers the tokens in order, like `nft_transfer`. A transfer which fails, e.g. because
+    /// the caller isn't approved for the token, doesn't revert the others. Returns the outcome of
+    /// each transfer, in the order of `transfers`, and logs one `nft_transfer` event with all the
+    /// successful transfers.
+    ///
+    /// Requirements:
+    /// * Caller of the method must attach a deposit of 1 yoctoⓃ for security purposes
+    fn nft_transfer_batch(&mut self, transfers: Vec<TransferRequest>) -> Vec<TransferResult>;
+}
+
+impl NonFungibleToken {
+    /// Transfers the tokens from their owners on behalf of `sender_id`, checking each transfer
+    /// like [`internal_transfer`](Self::internal_transfer) but without panicking when it fails.
+    /// Logs one event for the successful transfers, and returns the outcome of each transfer.
+    pub fn internal_transfer_batch(
+        &mut self,
+        sender_id: &AccountId,
+        transfers: &[TransferRequest],
+    ) -> Vec<TransferResult> {
+        let mut transferred = Vec::new();
+        let results = transfers
+            .iter()
+            .map(|transfer| {
+                let error = match self.internal_check_transfer(
+                    sender_id,
+                    &transfer.receiver_id,
+                    &transfer.token_id,
+                    transfer.approval_id,
+                ) {
+                    Ok(owner_id) => {
+                        // clear approvals, if using Approval Management extension
+                        if let Some(by_id) = self.approvals_by_id.as_mut() {
+                            by_id.remove(&transfer.token_id);
+                        }
+                        self.internal_transfer_unguarded(
+                            &transfer.token_id,
+                            &owner_id,
+                            &transfer.receiver_id,
+                        );
+                        transferred.push((owner_id, transfer));
+                        None
+                    }
+                    Err(error) => Some(error),
+                };
+                TransferResult {
+                    token_id: transfer.token_id.clone(),
+                    success: error.is_none(),
+                    error,
+                }
+            })
+            .collect();
+
+        if !transferred.is_empty() {
+            let token_ids: Vec<[&str; 1]> =
+                transferred.iter().map(|(_, transfer)| [transfer.token_id.as_str()]).collect();
+            let events: Vec<NftTransfer> = transferred
+                .iter()
+                .zip(token_ids.iter())
+                .map(|((owner_id, transfer), token_ids)| NftTransfer {
+                    old_owner_id: owner_id,
+                    new_owner_id: &transfer.receiver_id,
+                    token_ids,
+                    authorized_id: Some(sender_id).filter(|sender_id| *sender_id != owner_id),
+                    memo: transfer.memo.as_deref(),
+                })
+                .collect();
+            NftTransfer::emit_many(&events);
+        }
+        results
+    }
+}
+
+/// Implements the [`NonFungibleTokenBatchTransfer`] extension.
+/// Takes name of the Contract struct and the inner field for the token.
+#[macro_export]
+macro_rules! impl_non_fungible_token_batch_transfer {
+    ($contract: ident, $token: ident) => {
+        use $crate::non_fungible_token::batch::{
+            NonFungibleTokenBatchTransfer, TransferRequest, TransferResult,
+        };
+
+        #[near_bindgen]
+        impl NonFungibleTokenBatchTransfer for $contract {
+            #[payable]
+            fn nft_transfer_batch(
+                &mut self,
+                transfers: Vec<TransferRequest>,
+            ) -> Vec<TransferResult> {
+                near_sdk::assert_one_yocto();
+                let sender_id = near_sdk::env::predecessor_account_id();
+                self.$token.internal_transfer_batch(&sender_id, &transfers)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn transfer(token_id: &str, receiver_id: AccountId) -> TransferRequest {
+        TransferRequest {
+            receiver_id,
+            token_id: token_id.to_string(),
+            approval_id: None,
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn failed_transfers_do_not_revert_the_batch() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        let mut tokens = NonFungibleToken::new(
+            b"t".to_vec(),
+            accounts(0),
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+        );
+        tokens.owner_by_id.insert(&"1".to_string(), &accounts(1));
+        tokens.owner_by_id.insert(&"2".to_string(), &accounts(2));
+        tokens.owner_by_id.insert(&"3".to_string(), &accounts(1));
+
+        let results = tokens.internal_transfer_batch(
+            &accounts(1),
+            &[
+                transfer("1", accounts(3)),
+                transfer("2", accounts(3)),
+                transfer("3", accounts(1)),
+                transfer("4", accounts(3)),
+                transfer("1", accounts(4)),
+            ],
+        );
+        let errors: Vec<_> = results.iter().map(|result| result.error.as_deref()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                None,
+                Some("Unauthorized"),
+                Some("Current and next owner must differ"),
+                Some("Token not found"),
+                Some("Unauthorized"),
+            ]
+        );
+        assert!(results[0].success);
+        assert_eq!(tokens.owner_by_id.get(&"1".to_string()), Some(accounts(3)));
+        assert_eq!(tokens.owner_by_id.get(&"2".to_string()), Some(accounts(2)));
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"bob","new_owner_id":"danny","token_ids":["1"]}]}"#
+            ]
+        );
+    }
+}
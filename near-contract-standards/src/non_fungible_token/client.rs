@@ -0,0 +1,229 @@
+use crate::non_fungible_token::{Token, TokenId};
+use near_sdk::{env, ext_contract, AccountId, Balance, Gas, Promise, PromiseError, PromiseOrValue};
+
+/// Gas attached to the view calls `nft_token` and `nft_is_approved`.
+pub const GAS_FOR_NFT_VIEW: Gas = Gas(5_000_000_000_000);
+/// Gas attached to `nft_transfer`.
+pub const GAS_FOR_NFT_TRANSFER: Gas = Gas(10_000_000_000_000);
+/// Gas attached to `nft_transfer_call`, which covers the receiver call and the resolution of the
+/// transfer in the standard implementation.
+pub const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(50_000_000_000_000);
+
+/// Deposit required by `nft_transfer` and `nft_transfer_call`.
+const ONE_YOCTO: Balance = 1;
+const NO_DEPOSIT: Balance = 0;
+
+#[ext_contract(ext_nft)]
+pub trait NonFungibleTokenContract {
+    fn nft_token(&self, token_id: TokenId) -> Option<Token>;
+
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    );
+
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
+    fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> bool;
+}
+
+/// Client for calling a third party contract implementing the non-fungible token standard, e.g.
+/// from a marketplace.
+///
+/// Each method returns the promise of the call with gas defaults suitable for the standard
+/// implementation, so a callback can be chained with `.then(..)`. Inside that callback the
+/// result is read with the matching typed `*_result` function.
+///
+/// ```ignore
+/// NonFungibleTokenClient::new(nft_id)
+///     .nft_is_approved(token_id, env::current_account_id(), Some(approval_id))
+///     .then(ext_self::on_approval_checked(..));
+///
+/// // In `on_approval_checked`:
+/// let approved = NonFungibleTokenClient::is_approved_result(0).unwrap_or(false);
+/// ```
+pub struct NonFungibleTokenClient {
+    pub contract_id: AccountId,
+}
+
+impl NonFungibleTokenClient {
+    pub fn new(contract_id: AccountId) -> Self {
+        Self { contract_id }
+    }
+
+    pub fn nft_token(&self, token_id: TokenId) -> Promise {
+        ext_nft::nft_token(token_id, self.contract_id.clone(), NO_DEPOSIT, GAS_FOR_NFT_VIEW)
+    }
+
+    /// Transfers the token with one yoctoNEAR attached, which this contract pays.
+    pub fn nft_transfer(
+        &self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> Promise {
+        ext_nft::nft_transfer(
+            receiver_id,
+            token_id,
+            approval_id,
+            memo,
+            self.contract_id.clone(),
+            ONE_YOCTO,
+            GAS_FOR_NFT_TRANSFER,
+        )
+    }
+
+    /// Transfers the token and calls `nft_on_transfer` on the receiver, with one yoctoNEAR
+    /// attached, which this contract pays.
+    pub fn nft_transfer_call(
+        &self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise {
+        ext_nft::nft_transfer_call(
+            receiver_id,
+            token_id,
+            approval_id,
+            memo,
+            msg,
+            self.contract_id.clone(),
+            ONE_YOCTO,
+            GAS_FOR_NFT_TRANSFER_CALL,
+        )
+    }
+
+    pub fn nft_is_approved(
+        &self,
+        token_id: TokenId,
+        approved_account_id: AccountId,
+        approval_id: Option<u64>,
+    ) -> Promise {
+        ext_nft::nft_is_approved(
+            token_id,
+            approved_account_id,
+            approval_id,
+            self.contract_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_NFT_VIEW,
+        )
+    }
+
+    /// Reads the result of [`nft_token`](Self::nft_token) in a callback. Returns `None` if the
+    /// token doesn't exist.
+    pub fn token_result(result_idx: u64) -> Result<Option<Token>, PromiseError> {
+        env::promise_result_json(result_idx)
+    }
+
+    /// Reads the result of [`nft_transfer`](Self::nft_transfer) in a callback. Any returned value
+    /// is ignored without being read.
+    pub fn transfer_result(result_idx: u64) -> Result<(), PromiseError> {
+        match env::promise_result_bounded(result_idx, 0) {
+            Ok(_) | Err(PromiseError::TooLarge(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads the result of [`nft_transfer_call`](Self::nft_transfer_call) in a callback. Returns
+    /// whether the token was kept by the receiver, i.e. it wasn't returned to the owner.
+    pub fn transfer_call_result(result_idx: u64) -> Result<bool, PromiseError> {
+        env::promise_result_json(result_idx)
+    }
+
+    /// Reads the result of [`nft_is_approved`](Self::nft_is_approved) in a callback.
+    pub fn is_approved_result(result_idx: u64) -> Result<bool, PromiseError> {
+        env::promise_result_json(result_idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    /// Returns the receivers, methods, deposits and gas of the function calls created.
+    fn created_calls() -> Vec<(AccountId, String, Balance, Gas)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::FunctionCall { function_name, deposit, gas, .. } => {
+                        Some((receiver_id.clone(), function_name, deposit, gas))
+                    }
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    fn callback(results: Vec<PromiseResult>) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+    }
+
+    #[test]
+    fn calls() {
+        testing_env!(VMContextBuilder::new().build());
+        let client = NonFungibleTokenClient::new(accounts(1));
+        client.nft_token("1".to_string());
+        client.nft_transfer(accounts(2), "1".to_string(), Some(3), None);
+        client.nft_transfer_call(accounts(2), "1".to_string(), None, None, "msg".to_string());
+        client.nft_is_approved("1".to_string(), accounts(3), None);
+        assert_eq!(
+            created_calls(),
+            vec![
+                (accounts(1), "nft_token".to_string(), 0, GAS_FOR_NFT_VIEW),
+                (accounts(1), "nft_transfer".to_string(), 1, GAS_FOR_NFT_TRANSFER),
+                (accounts(1), "nft_transfer_call".to_string(), 1, GAS_FOR_NFT_TRANSFER_CALL),
+                (accounts(1), "nft_is_approved".to_string(), 0, GAS_FOR_NFT_VIEW),
+            ]
+        );
+    }
+
+    #[test]
+    fn results() {
+        callback(vec![
+            PromiseResult::Successful(
+                br#"{"token_id":"1","owner_id":"bob","metadata":null,"approved_account_ids":null}"#
+                    .to_vec(),
+            ),
+            PromiseResult::Successful(b"null".to_vec()),
+            PromiseResult::Successful(b"true".to_vec()),
+            PromiseResult::Failed,
+        ]);
+        let token = NonFungibleTokenClient::token_result(0).unwrap().unwrap();
+        assert_eq!((token.token_id.as_str(), token.owner_id), ("1", accounts(1)));
+        assert_eq!(NonFungibleTokenClient::token_result(1), Ok(None));
+        assert_eq!(NonFungibleTokenClient::transfer_result(1), Ok(()));
+        assert_eq!(NonFungibleTokenClient::transfer_call_result(2), Ok(true));
+        assert_eq!(NonFungibleTokenClient::is_approved_result(2), Ok(true));
+        assert_eq!(NonFungibleTokenClient::transfer_result(3), Err(PromiseError::Failed));
+        assert_eq!(NonFungibleTokenClient::is_approved_result(3), Err(PromiseError::Failed));
+    }
+}
@@ -4,15 +4,17 @@ use crate::non_fungible_token::events::{NftMint, NftTransfer};
 use crate::non_fungible_token::metadata::TokenMetadata;
 use crate::non_fungible_token::token::{Token, TokenId};
 use crate::non_fungible_token::utils::{
-    hash_account_id, refund_approved_account_ids, refund_deposit_to_account,
+    approved_account_ids_storage_cost, hash_account_id, refund_deposit_to_account,
 };
+use crate::refund_queue::RefundQueue;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LookupMap, TreeMap, UnorderedSet};
 use near_sdk::json_types::Base64VecU8;
 use near_sdk::{
     assert_one_yocto, env, ext_contract, require, AccountId, Balance, BorshStorageKey, CryptoHash,
-    Gas, IntoStorageKey, PromiseOrValue, PromiseResult, StorageUsage,
+    Gas, IntoStorageKey, Promise, PromiseError, PromiseOrValue, StorageUsage,
 };
+use std::collections::hash_map::Keys;
 use std::collections::HashMap;
 
 const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
@@ -20,6 +22,10 @@ const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_RESOLVE_
 
 const NO_DEPOSIT: Balance = 0;
 
+/// Upper bound for the JSON encoded `bool` returned by `nft_on_transfer`. Larger results are
+/// treated like failures without being deserialized.
+const MAX_NFT_ON_TRANSFER_RESULT_BYTES: u64 = 64;
+
 #[ext_contract(ext_self)]
 trait NFTResolver {
     fn nft_resolve_transfer(
@@ -226,6 +232,46 @@ impl NonFungibleToken {
         }
     }
 
+    /// Checks that `sender_id` is allowed to transfer the token to `receiver_id`, i.e. it's the
+    /// owner or an approved account, without changing anything. Returns the current owner, or the
+    /// reason the transfer would fail.
+    pub fn internal_check_transfer(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        #[allow(clippy::ptr_arg)] token_id: &TokenId,
+        approval_id: Option<u64>,
+    ) -> Result<AccountId, String> {
+        let owner_id = self.owner_by_id.get(token_id).ok_or("Token not found")?;
+
+        // check if authorized
+        if sender_id != &owner_id {
+            // if approval extension is NOT being used, or if token has no approved accounts
+            let app_acc_ids = self
+                .approvals_by_id
+                .as_ref()
+                .and_then(|by_id| by_id.get(token_id))
+                .ok_or("Unauthorized")?;
+
+            // Approval extension is being used; get approval_id for sender.
+            let actual_approval_id = app_acc_ids.get(sender_id).ok_or("Sender not approved")?;
+
+            // If approval_id included, check that it matches
+            if approval_id.is_some() && Some(actual_approval_id) != approval_id.as_ref() {
+                return Err(format!(
+                    "The actual approval_id {:?} is different from the given approval_id {:?}",
+                    Some(actual_approval_id),
+                    approval_id
+                ));
+            }
+        }
+
+        if &owner_id == receiver_id {
+            return Err("Current and next owner must differ".to_string());
+        }
+        Ok(owner_id)
+    }
+
     /// Transfer from current owner to receiver_id, checking that sender is allowed to transfer.
     /// Clear approvals, if approval extension being used.
     /// Return previous owner and approvals.
@@ -237,45 +283,17 @@ impl NonFungibleToken {
         approval_id: Option<u64>,
         memo: Option<String>,
     ) -> (AccountId, Option<HashMap<AccountId, u64>>) {
-        let owner_id =
-            self.owner_by_id.get(token_id).unwrap_or_else(|| env::panic_str("Token not found"));
+        let owner_id = self
+            .internal_check_transfer(sender_id, receiver_id, token_id, approval_id)
+            .unwrap_or_else(|err| env::panic_str(&err));
 
         // clear approvals, if using Approval Management extension
-        // this will be rolled back by a panic if sending fails
         let approved_account_ids =
             self.approvals_by_id.as_mut().and_then(|by_id| by_id.remove(token_id));
 
-        // check if authorized
-        let sender_id = if sender_id != &owner_id {
-            // if approval extension is NOT being used, or if token has no approved accounts
-            let app_acc_ids =
-                approved_account_ids.as_ref().unwrap_or_else(|| env::panic_str("Unauthorized"));
-
-            // Approval extension is being used; get approval_id for sender.
-            let actual_approval_id = app_acc_ids.get(sender_id);
-
-            // Panic if sender not approved at all
-            if actual_approval_id.is_none() {
-                env::panic_str("Sender not approved");
-            }
-
-            // If approval_id included, check that it matches
-            require!(
-                approval_id.is_none() || actual_approval_id == approval_id.as_ref(),
-                format!(
-                    "The actual approval_id {:?} is different from the given approval_id {:?}",
-                    actual_approval_id, approval_id
-                )
-            );
-            Some(sender_id)
-        } else {
-            None
-        };
-
-        require!(&owner_id != receiver_id, "Current and next owner must differ");
-
         self.internal_transfer_unguarded(token_id, &owner_id, receiver_id);
 
+        let sender_id = Some(sender_id).filter(|sender_id| *sender_id != &owner_id);
         NonFungibleToken::emit_transfer(&owner_id, receiver_id, token_id, sender_id, memo);
 
         // return previous owner & approvals
@@ -402,6 +420,77 @@ impl NonFungibleToken {
 
         Token { token_id, owner_id, metadata: token_metadata, approved_account_ids }
     }
+
+    /// Same as [`nft_resolve_transfer`](NonFungibleTokenResolver::nft_resolve_transfer), sending
+    /// the storage of the approvals refunded to the previous owner or the receiver through the
+    /// queue if it's given, since either account could have been deleted since the transfer.
+    pub fn internal_resolve_transfer_with_refunds(
+        &mut self,
+        refunds: Option<&RefundQueue>,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let refund = |account_id: AccountId, approved_account_ids: Keys<AccountId, u64>| {
+            let amount = approved_account_ids_storage_cost(approved_account_ids);
+            match refunds {
+                Some(refunds) => refunds.transfer(account_id, amount),
+                None => Promise::new(account_id).transfer(amount),
+            }
+        };
+
+        // Get whether token should be returned
+        let must_revert = match env::promise_result_bounded(0, MAX_NFT_ON_TRANSFER_RESULT_BYTES) {
+            Err(PromiseError::NotReady) => env::abort(),
+            Ok(value) => {
+                if let Ok(yes_or_no) = near_sdk::serde_json::from_slice::<bool>(&value) {
+                    yes_or_no
+                } else {
+                    true
+                }
+            }
+            Err(_) => true,
+        };
+
+        // if call succeeded, return early
+        if !must_revert {
+            return true;
+        }
+
+        // OTHERWISE, try to set owner back to previous_owner_id and restore approved_account_ids
+
+        // Check that receiver didn't already transfer it away or burn it.
+        if let Some(current_owner) = self.owner_by_id.get(&token_id) {
+            if current_owner != receiver_id {
+                // The token is not owned by the receiver anymore. Can't return it.
+                return true;
+            }
+        } else {
+            // The token was burned and doesn't exist anymore.
+            // Refund storage cost for storing approvals to original owner and return early.
+            if let Some(approved_account_ids) = approved_account_ids {
+                refund(previous_owner_id, approved_account_ids.keys());
+            }
+            return true;
+        };
+
+        self.internal_transfer_unguarded(&token_id, &receiver_id, &previous_owner_id);
+
+        // If using Approval Management extension,
+        // 1. revert any approvals receiver already set, refunding storage costs
+        // 2. reset approvals to what previous owner had set before call to nft_transfer_call
+        if let Some(by_id) = &mut self.approvals_by_id {
+            if let Some(receiver_approvals) = by_id.get(&token_id) {
+                refund(receiver_id.clone(), receiver_approvals.keys());
+            }
+            if let Some(previous_owner_approvals) = approved_account_ids {
+                by_id.insert(&token_id, &previous_owner_approvals);
+            }
+        }
+        NonFungibleToken::emit_transfer(&receiver_id, &previous_owner_id, &token_id, None, None);
+        false
+    }
 }
 
 impl NonFungibleTokenCore for NonFungibleToken {
@@ -475,55 +564,12 @@ impl NonFungibleTokenResolver for NonFungibleToken {
         token_id: TokenId,
         approved_account_ids: Option<HashMap<AccountId, u64>>,
     ) -> bool {
-        // Get whether token should be returned
-        let must_revert = match env::promise_result(0) {
-            PromiseResult::NotReady => env::abort(),
-            PromiseResult::Successful(value) => {
-                if let Ok(yes_or_no) = near_sdk::serde_json::from_slice::<bool>(&value) {
-                    yes_or_no
-                } else {
-                    true
-                }
-            }
-            PromiseResult::Failed => true,
-        };
-
-        // if call succeeded, return early
-        if !must_revert {
-            return true;
-        }
-
-        // OTHERWISE, try to set owner back to previous_owner_id and restore approved_account_ids
-
-        // Check that receiver didn't already transfer it away or burn it.
-        if let Some(current_owner) = self.owner_by_id.get(&token_id) {
-            if current_owner != receiver_id {
-                // The token is not owned by the receiver anymore. Can't return it.
-                return true;
-            }
-        } else {
-            // The token was burned and doesn't exist anymore.
-            // Refund storage cost for storing approvals to original owner and return early.
-            if let Some(approved_account_ids) = approved_account_ids {
-                refund_approved_account_ids(previous_owner_id, &approved_account_ids);
-            }
-            return true;
-        };
-
-        self.internal_transfer_unguarded(&token_id, &receiver_id, &previous_owner_id);
-
-        // If using Approval Management extension,
-        // 1. revert any approvals receiver already set, refunding storage costs
-        // 2. reset approvals to what previous owner had set before call to nft_transfer_call
-        if let Some(by_id) = &mut self.approvals_by_id {
-            if let Some(receiver_approvals) = by_id.get(&token_id) {
-                refund_approved_account_ids(receiver_id.clone(), &receiver_approvals);
-            }
-            if let Some(previous_owner_approvals) = approved_account_ids {
-                by_id.insert(&token_id, &previous_owner_approvals);
-            }
-        }
-        NonFungibleToken::emit_transfer(&receiver_id, &previous_owner_id, &token_id, None, None);
-        false
+        self.internal_resolve_transfer_with_refunds(
+            None,
+            previous_owner_id,
+            receiver_id,
+            token_id,
+            approved_account_ids,
+        )
     }
 }
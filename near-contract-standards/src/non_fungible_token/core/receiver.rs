@@ -16,7 +16,8 @@ pub trait NonFungibleTokenReceiver {
     ///   Approval Management extension
     /// * `token_id`: the `token_id` argument given to `nft_transfer_call`
     /// * `msg`: information necessary for this contract to know how to process the
-    ///   request. This may include method names and/or arguments.
+    ///   request. This may include method names and/or arguments, e.g. as a
+    ///   [`TransferCallMsg`](crate::transfer_call_msg::TransferCallMsg).
     ///
     /// Returns true if token should be returned to `sender_id`
     fn nft_on_transfer(
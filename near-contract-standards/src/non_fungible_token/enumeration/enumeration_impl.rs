@@ -1,7 +1,8 @@
 use super::NonFungibleTokenEnumeration;
 use crate::non_fungible_token::token::Token;
 use crate::non_fungible_token::NonFungibleToken;
-use near_sdk::json_types::U128;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Cursor, JsonSeq, Page, U128};
 use near_sdk::{env, require, AccountId};
 
 type TokenId = String;
@@ -16,15 +17,13 @@ impl NonFungibleToken {
 
         Token { token_id, owner_id, metadata, approved_account_ids }
     }
-}
-
-impl NonFungibleTokenEnumeration for NonFungibleToken {
-    fn nft_total_supply(&self) -> U128 {
-        // An unfortunate cast from the max of TreeMap to the spec
-        (self.owner_by_id.len() as u128).into()
-    }
 
-    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+    /// Tokens returned by `nft_tokens`, read from storage as they are iterated.
+    fn enum_tokens(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> impl Iterator<Item = Token> + '_ {
         // Get starting index, whether or not it was explicitly given.
         // Defaults to 0 based on the spec:
         // https://nomicon.io/Standards/NonFungibleToken/Enumeration.html#interface
@@ -39,29 +38,16 @@ impl NonFungibleTokenEnumeration for NonFungibleToken {
             .iter()
             .skip(start_index as usize)
             .take(limit)
-            .map(|(token_id, owner_id)| self.enum_get_token(owner_id, token_id))
-            .collect()
+            .map(move |(token_id, owner_id)| self.enum_get_token(owner_id, token_id))
     }
 
-    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
-        let tokens_per_owner = self.tokens_per_owner.as_ref().unwrap_or_else(|| {
-            env::panic_str(
-                "Could not find tokens_per_owner when calling a method on the \
-                enumeration standard.",
-            )
-        });
-        tokens_per_owner
-            .get(&account_id)
-            .map(|account_tokens| U128::from(account_tokens.len() as u128))
-            .unwrap_or(U128(0))
-    }
-
-    fn nft_tokens_for_owner(
+    /// Tokens returned by `nft_tokens_for_owner`, read from storage as they are iterated.
+    fn enum_tokens_for_owner(
         &self,
         account_id: AccountId,
         from_index: Option<U128>,
         limit: Option<u64>,
-    ) -> Vec<Token> {
+    ) -> Box<dyn Iterator<Item = Token> + '_> {
         let tokens_per_owner = self.tokens_per_owner.as_ref().unwrap_or_else(|| {
             env::panic_str(
                 "Could not find tokens_per_owner when calling a method on the \
@@ -71,7 +57,7 @@ impl NonFungibleTokenEnumeration for NonFungibleToken {
         let token_set = if let Some(token_set) = tokens_per_owner.get(&account_id) {
             token_set
         } else {
-            return vec![];
+            return Box::new(std::iter::empty());
         };
         let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
         require!(limit != 0, "Cannot provide limit of 0.");
@@ -80,11 +66,195 @@ impl NonFungibleTokenEnumeration for NonFungibleToken {
             token_set.len() as u128 > start_index,
             "Out of bounds, please use a smaller from_index."
         );
-        token_set
-            .iter()
-            .skip(start_index as usize)
-            .take(limit)
-            .map(|token_id| self.enum_get_token(account_id.clone(), token_id))
-            .collect()
+        Box::new((start_index as u64..token_set.len()).take(limit).map(move |index| {
+            let token_id = token_set.as_vector().get(index).unwrap_or_else(|| env::abort());
+            self.enum_get_token(account_id.clone(), token_id)
+        }))
+    }
+
+    /// Returns the tokens of `nft_tokens` as a [`JsonSeq`], serialized one by one into the
+    /// result of the view instead of being collected first, for views returning thousands of
+    /// tokens which would otherwise exhaust the memory of the contract.
+    pub fn nft_tokens_seq(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> JsonSeq<impl Iterator<Item = Token> + '_> {
+        JsonSeq::new(self.enum_tokens(from_index, limit))
+    }
+
+    /// Returns the tokens of `nft_tokens_for_owner` as a [`JsonSeq`], like
+    /// [`nft_tokens_seq`](Self::nft_tokens_seq).
+    pub fn nft_tokens_for_owner_seq(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> JsonSeq<impl Iterator<Item = Token> + '_> {
+        JsonSeq::new(self.enum_tokens_for_owner(account_id, from_index, limit))
+    }
+
+    /// Returns a page of all tokens in the order of their ids. The cursor is the id of the last
+    /// returned token, so pages aren't affected by tokens burned in the meantime.
+    pub fn nft_tokens_page(&self, from_cursor: Option<Cursor>, limit: Option<u64>) -> Page<Token> {
+        let tokens: Box<dyn Iterator<Item = (TokenId, AccountId)>> = match from_cursor {
+            Some(cursor) => Box::new(self.owner_by_id.iter_from(cursor.position())),
+            None => Box::new(self.owner_by_id.iter()),
+        };
+        Page::collect(
+            tokens.map(|(token_id, owner_id)| {
+                (token_id.clone(), self.enum_get_token(owner_id, token_id))
+            }),
+            limit,
+        )
+    }
+
+    /// Returns a page of the tokens of the owner. The tokens of an owner are reordered when one
+    /// of them is removed, so the cursor is rejected as stale if tokens of the owner were removed
+    /// since it was returned, instead of silently skipping tokens.
+    pub fn nft_tokens_for_owner_page(
+        &self,
+        account_id: AccountId,
+        from_cursor: Option<Cursor>,
+        limit: Option<u64>,
+    ) -> Page<Token> {
+        let tokens_per_owner = self.tokens_per_owner.as_ref().unwrap_or_else(|| {
+            env::panic_str(
+                "Could not find tokens_per_owner when calling a method on the \
+                enumeration standard.",
+            )
+        });
+        let token_set = match tokens_per_owner.get(&account_id) {
+            Some(token_set) => token_set,
+            None => return Page { items: vec![], next_cursor: None },
+        };
+        let tokens = token_set.as_vector();
+        let start_index = match from_cursor {
+            Some(cursor) => {
+                let position: OwnerTokensPosition = cursor.position();
+                require!(
+                    tokens.len() >= position.len
+                        && tokens.get(position.index) == Some(position.token_id),
+                    "The cursor is stale, the tokens of the owner changed"
+                );
+                position.index + 1
+            }
+            None => 0,
+        };
+        Page::collect(
+            (start_index..tokens.len()).map(|index| {
+                let token_id = tokens.get(index).unwrap_or_else(|| env::abort());
+                let position =
+                    OwnerTokensPosition { index, token_id: token_id.clone(), len: tokens.len() };
+                (position, self.enum_get_token(account_id.clone(), token_id))
+            }),
+            limit,
+        )
+    }
+}
+
+/// Position of a page of the tokens of an owner: the last returned token, with its index and
+/// the number of tokens of the owner at the time.
+#[derive(BorshDeserialize, BorshSerialize)]
+struct OwnerTokensPosition {
+    index: u64,
+    token_id: TokenId,
+    len: u64,
+}
+
+impl NonFungibleTokenEnumeration for NonFungibleToken {
+    fn nft_total_supply(&self) -> U128 {
+        // An unfortunate cast from the max of TreeMap to the spec
+        (self.owner_by_id.len() as u128).into()
+    }
+
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        self.enum_tokens(from_index, limit).collect()
+    }
+
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        let tokens_per_owner = self.tokens_per_owner.as_ref().unwrap_or_else(|| {
+            env::panic_str(
+                "Could not find tokens_per_owner when calling a method on the \
+                enumeration standard.",
+            )
+        });
+        tokens_per_owner
+            .get(&account_id)
+            .map(|account_tokens| U128::from(account_tokens.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        self.enum_tokens_for_owner(account_id, from_index, limit).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+
+    fn setup() -> NonFungibleToken {
+        let mut token =
+            NonFungibleToken::new(b"o", accounts(0), Some(b"m"), Some(b"e"), Some(b"a"));
+        for token_id in ["1", "2", "3"] {
+            let metadata = near_sdk::serde_json::from_str("{}").unwrap();
+            token.internal_mint_with_refund(
+                token_id.to_string(),
+                accounts(1),
+                Some(metadata),
+                None,
+            );
+        }
+        token
+    }
+
+    fn json<T: near_sdk::serde::Serialize>(value: &T) -> String {
+        near_sdk::serde_json::to_string(value).unwrap()
+    }
+
+    #[test]
+    fn streamed_tokens() {
+        let token = setup();
+        assert_eq!(
+            json(&token.nft_tokens_seq(Some(U128(1)), Some(5))),
+            json(&token.nft_tokens(Some(U128(1)), Some(5)))
+        );
+        assert_eq!(
+            json(&token.nft_tokens_for_owner_seq(accounts(1), None, Some(2))),
+            json(&token.nft_tokens_for_owner(accounts(1), None, Some(2)))
+        );
+        assert_eq!(json(&token.nft_tokens_for_owner_seq(accounts(2), None, None)), "[]");
+    }
+
+    fn token_ids(page: &Page<Token>) -> Vec<&str> {
+        page.items.iter().map(|token| token.token_id.as_str()).collect()
+    }
+
+    #[test]
+    fn tokens_page_after_transfer() {
+        let mut token = setup();
+        let page = token.nft_tokens_page(None, Some(2));
+        assert_eq!(token_ids(&page), vec!["1", "2"]);
+        token.internal_transfer_unguarded(&"1".to_string(), &accounts(1), &accounts(2));
+        let page = token.nft_tokens_page(page.next_cursor, Some(2));
+        assert_eq!(token_ids(&page), vec!["3"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The cursor is stale, the tokens of the owner changed")]
+    fn owner_page_after_transfer() {
+        let mut token = setup();
+        let page = token.nft_tokens_for_owner_page(accounts(1), None, Some(2));
+        assert_eq!(token_ids(&page), vec!["1", "2"]);
+        token.internal_transfer_unguarded(&"1".to_string(), &accounts(1), &accounts(2));
+        token.nft_tokens_for_owner_page(accounts(1), page.next_cursor, Some(2));
     }
 }
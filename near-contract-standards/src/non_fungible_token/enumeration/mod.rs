@@ -1,7 +1,7 @@
 mod enumeration_impl;
 
 use crate::non_fungible_token::token::Token;
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Cursor, Page, U128};
 use near_sdk::AccountId;
 
 /// Offers methods helpful in determining account ownership of NFTs and provides a way to page through NFTs per owner, determine total supply, etc.
@@ -50,3 +50,20 @@ pub trait NonFungibleTokenEnumeration {
         limit: Option<u64>,       // default: unlimited (could fail due to gas limit)
     ) -> Vec<Token>;
 }
+
+/// Cursor-based variants of the [`NonFungibleTokenEnumeration`] views, which return a [`Page`]
+/// with the cursor of the next page, to be passed back as `from_cursor`, instead of relying on
+/// indices which shift when tokens are burned or transferred.
+pub trait NonFungibleTokenEnumerationPages {
+    /// Returns a page of all tokens in the order of their ids.
+    fn nft_tokens_page(&self, from_cursor: Option<Cursor>, limit: Option<u64>) -> Page<Token>;
+
+    /// Returns a page of the tokens owned by the account. Panics if the cursor is stale because
+    /// tokens of the account were removed since it was returned.
+    fn nft_tokens_for_owner_page(
+        &self,
+        account_id: AccountId,
+        from_cursor: Option<Cursor>,
+        limit: Option<u64>,
+    ) -> Page<Token>;
+}
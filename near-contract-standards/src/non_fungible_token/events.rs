@@ -12,14 +12,32 @@
 //! These events can be logged by calling `.emit()` on them if a single event, or calling
 //! [`NftMint::emit_many`], [`NftTransfer::emit_many`],
 //! or [`NftBurn::emit_many`] respectively.
+//!
+//! Contracts which emit a high volume of events can instead use the compact Borsh encoding with
+//! [`NftMint::emit_borsh`] or [`NftMint::emit_borsh_many`] (and their equivalents on the other
+//! events). These are logged as `EVENT_BORSH:` followed by the base64 encoding of the Borsh
+//! serialization of the following envelope:
+//!
+//! ```ignore
+//! struct Event<T> {
+//!     standard: String, // "nep171"
+//!     version: String,  // "1.0.0"
+//!     event: String,    // "nft_mint", "nft_transfer" or "nft_burn"
+//!     data: Vec<T>,
+//! }
+//! ```
+//!
+//! where `T` is the event type with its fields serialized in declaration order, with
+//! `token_ids` as a `Vec<String>` and optional fields as `Option`s.
 
-use crate::event::NearEvent;
+use crate::event::{BorshEvent, NearEvent};
+use near_sdk::borsh::{self, BorshSerialize};
 use near_sdk::AccountId;
 use serde::Serialize;
 
 /// Data to log for an NFT mint event. To log this event, call [`.emit()`](NftMint::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct NftMint<'a> {
     pub owner_id: &'a AccountId,
     pub token_ids: &'a [&'a str],
@@ -39,12 +57,24 @@ impl NftMint<'_> {
     pub fn emit_many(data: &[NftMint<'_>]) {
         new_171_v1(Nep171EventKind::NftMint(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an nft mint event in the compact Borsh encoding, where each [`NftMint`]
+    /// represents the data of each mint.
+    pub fn emit_borsh_many(data: &[NftMint<'_>]) {
+        new_171_borsh_v1("nft_mint", data).emit()
+    }
 }
 
 /// Data to log for an NFT transfer event. To log this event,
 /// call [`.emit()`](NftTransfer::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct NftTransfer<'a> {
     pub old_owner_id: &'a AccountId,
     pub new_owner_id: &'a AccountId,
@@ -67,11 +97,23 @@ impl NftTransfer<'_> {
     pub fn emit_many(data: &[NftTransfer<'_>]) {
         new_171_v1(Nep171EventKind::NftTransfer(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an nft transfer event in the compact Borsh encoding, where each [`NftTransfer`]
+    /// represents the data of each transfer.
+    pub fn emit_borsh_many(data: &[NftTransfer<'_>]) {
+        new_171_borsh_v1("nft_transfer", data).emit()
+    }
 }
 
 /// Data to log for an NFT burn event. To log this event, call [`.emit()`](NftBurn::emit).
 #[must_use]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, BorshSerialize, Debug, Clone)]
 pub struct NftBurn<'a> {
     pub owner_id: &'a AccountId,
     pub token_ids: &'a [&'a str],
@@ -93,6 +135,18 @@ impl NftBurn<'_> {
     pub fn emit_many<'a>(data: &'a [NftBurn<'a>]) {
         new_171_v1(Nep171EventKind::NftBurn(data)).emit()
     }
+
+    /// Logs the event to the host in the compact Borsh encoding described in the
+    /// [module documentation](self).
+    pub fn emit_borsh(self) {
+        Self::emit_borsh_many(&[self])
+    }
+
+    /// Emits an nft burn event in the compact Borsh encoding, where each [`NftBurn`]
+    /// represents the data of each burn.
+    pub fn emit_borsh_many<'a>(data: &'a [NftBurn<'a>]) {
+        new_171_borsh_v1("nft_burn", data).emit()
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -120,10 +174,18 @@ fn new_171_v1(event_kind: Nep171EventKind) -> NearEvent {
     new_171("1.0.0", event_kind)
 }
 
+fn new_171_borsh_v1<'a, T: BorshSerialize>(
+    event: &'static str,
+    data: &'a [T],
+) -> BorshEvent<'a, T> {
+    BorshEvent { standard: "nep171", version: "1.0.0", event, data }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use near_sdk::{test_utils, AccountId};
+    use near_sdk::borsh::BorshDeserialize;
+    use near_sdk::{base64, test_utils, AccountId};
 
     fn bob() -> AccountId {
         AccountId::new_unchecked("bob".to_string())
@@ -222,4 +284,43 @@ mod tests {
             r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","token_ids":["2","3"],"authorized_id":"bob","memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0","1"]}]}"#
         );
     }
+
+    #[derive(BorshDeserialize, Debug, PartialEq)]
+    struct DecodedMint {
+        owner_id: AccountId,
+        token_ids: Vec<String>,
+        memo: Option<String>,
+    }
+
+    #[test]
+    fn nft_mints_borsh() {
+        let owner_id = &bob();
+        NftMint::emit_borsh_many(&[
+            NftMint { owner_id, token_ids: &["0", "1"], memo: None },
+            NftMint { owner_id: &alice(), token_ids: &["2"], memo: Some("has memo") },
+        ]);
+        let log = test_utils::get_logs().pop().unwrap();
+        let encoded = log.strip_prefix("EVENT_BORSH:").unwrap();
+        let (standard, version, event, data): (String, String, String, Vec<DecodedMint>) =
+            BorshDeserialize::try_from_slice(&base64::decode(encoded).unwrap()).unwrap();
+        assert_eq!(
+            (standard.as_str(), version.as_str(), event.as_str()),
+            ("nep171", "1.0.0", "nft_mint")
+        );
+        assert_eq!(
+            data,
+            vec![
+                DecodedMint {
+                    owner_id: bob(),
+                    token_ids: vec!["0".into(), "1".into()],
+                    memo: None
+                },
+                DecodedMint {
+                    owner_id: alice(),
+                    token_ids: vec!["2".into()],
+                    memo: Some("has memo".into()),
+                },
+            ]
+        );
+    }
 }
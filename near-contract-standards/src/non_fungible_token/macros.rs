@@ -1,8 +1,39 @@
 /// The core methods for a basic non-fungible token. Extension standards may be
 /// added in addition to this macro.
+///
+/// With `max_memo_length = <field>` after the token, `nft_transfer` and `nft_transfer_call`
+/// panic with a `MEMO_TOO_LONG` error if the memo is longer than the number of bytes in the
+/// field.
+///
+/// With `refunds = <field>`, the storage of the approvals refunded by `nft_resolve_transfer` is
+/// sent through the [`RefundQueue`](crate::refund_queue::RefundQueue) field, so that it's
+/// credited back if the transfer fails. The contract must also use
+/// [`impl_refund_queue`](crate::impl_refund_queue).
+///
+/// The keys can be combined in this order, e.g.
+/// `impl_non_fungible_token_core!(Contract, token, max_memo_length = max_memo, refunds = refunds)`.
 #[macro_export]
 macro_rules! impl_non_fungible_token_core {
-    ($contract: ident, $token: ident) => {
+    (@parse $contract: ident, $token: ident, [], [],
+        , max_memo_length = $max_memo_length: ident $($rest: tt)*) => {
+        $crate::impl_non_fungible_token_core!(
+            @parse $contract, $token, [$max_memo_length], [], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($max_memo_length: ident)?], [],
+        , refunds = $refunds: ident $($rest: tt)*) => {
+        $crate::impl_non_fungible_token_core!(
+            @parse $contract, $token, [$($max_memo_length)?], [$refunds], $($rest)*
+        );
+    };
+    (@parse $contract: ident, $token: ident, [$($max_memo_length: ident)?],
+        [$($refunds: ident)?],) => {
+        $crate::impl_non_fungible_token_core!(
+            @impl $contract, $token, [$($max_memo_length)?], [$($refunds)?]
+        );
+    };
+    (@impl $contract: ident, $token: ident, [$($max_memo_length: ident)?],
+        [$($refunds: ident)?]) => {
         use $crate::non_fungible_token::core::NonFungibleTokenCore;
         use $crate::non_fungible_token::core::NonFungibleTokenResolver;
 
@@ -16,6 +47,7 @@ macro_rules! impl_non_fungible_token_core {
                 approval_id: Option<u64>,
                 memo: Option<String>,
             ) {
+                $(near_sdk::assert_memo_length(memo.as_deref(), self.$max_memo_length as usize);)?
                 self.$token.nft_transfer(receiver_id, token_id, approval_id, memo)
             }
 
@@ -28,6 +60,7 @@ macro_rules! impl_non_fungible_token_core {
                 memo: Option<String>,
                 msg: String,
             ) -> PromiseOrValue<bool> {
+                $(near_sdk::assert_memo_length(memo.as_deref(), self.$max_memo_length as usize);)?
                 self.$token.nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
             }
 
@@ -46,7 +79,11 @@ macro_rules! impl_non_fungible_token_core {
                 token_id: TokenId,
                 approved_account_ids: Option<std::collections::HashMap<AccountId, u64>>,
             ) -> bool {
-                self.$token.nft_resolve_transfer(
+                #[allow(unused_variables)]
+                let refunds: Option<&$crate::refund_queue::RefundQueue> = None;
+                $(let refunds = Some(&self.$refunds);)?
+                self.$token.internal_resolve_transfer_with_refunds(
+                    refunds,
                     previous_owner_id,
                     receiver_id,
                     token_id,
@@ -55,6 +92,9 @@ macro_rules! impl_non_fungible_token_core {
             }
         }
     };
+    ($contract: ident, $token: ident $($rest: tt)*) => {
+        $crate::impl_non_fungible_token_core!(@parse $contract, $token, [], [], $($rest)*);
+    };
 }
 
 /// Non-fungible token approval management allows for an escrow system where
@@ -134,3 +174,75 @@ macro_rules! impl_non_fungible_token_enumeration {
         }
     };
 }
+
+/// Non-fungible token enumeration with the same view methods as
+/// [`impl_non_fungible_token_enumeration`], whose `nft_tokens` and `nft_tokens_for_owner` serialize
+/// the tokens one by one into the result with
+/// [`JsonSeq`](near_sdk::json_types::JsonSeq) instead of collecting them first, for contracts
+/// returning thousands of tokens. Use instead of [`impl_non_fungible_token_enumeration`].
+#[macro_export]
+macro_rules! impl_non_fungible_token_enumeration_streamed {
+    ($contract: ident, $token: ident) => {
+        use $crate::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+
+        #[near_bindgen]
+        impl $contract {
+            pub fn nft_total_supply(&self) -> near_sdk::json_types::U128 {
+                self.$token.nft_total_supply()
+            }
+
+            pub fn nft_tokens<'a>(
+                &'a self,
+                from_index: Option<near_sdk::json_types::U128>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::JsonSeq<impl Iterator<Item = Token> + 'a> {
+                self.$token.nft_tokens_seq(from_index, limit)
+            }
+
+            pub fn nft_supply_for_owner(
+                &self,
+                account_id: AccountId,
+            ) -> near_sdk::json_types::U128 {
+                self.$token.nft_supply_for_owner(account_id)
+            }
+
+            pub fn nft_tokens_for_owner<'a>(
+                &'a self,
+                account_id: AccountId,
+                from_index: Option<near_sdk::json_types::U128>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::JsonSeq<impl Iterator<Item = Token> + 'a> {
+                self.$token.nft_tokens_for_owner_seq(account_id, from_index, limit)
+            }
+        }
+    };
+}
+
+/// Cursor-based pagination of the non-fungible token enumeration, with the `nft_tokens_page`
+/// and `nft_tokens_for_owner_page` view methods.
+#[macro_export]
+macro_rules! impl_non_fungible_token_enumeration_pages {
+    ($contract: ident, $token: ident) => {
+        use $crate::non_fungible_token::enumeration::NonFungibleTokenEnumerationPages;
+
+        #[near_bindgen]
+        impl NonFungibleTokenEnumerationPages for $contract {
+            fn nft_tokens_page(
+                &self,
+                from_cursor: Option<near_sdk::json_types::Cursor>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::Page<Token> {
+                self.$token.nft_tokens_page(from_cursor, limit)
+            }
+
+            fn nft_tokens_for_owner_page(
+                &self,
+                account_id: AccountId,
+                from_cursor: Option<near_sdk::json_types::Cursor>,
+                limit: Option<u64>,
+            ) -> near_sdk::json_types::Page<Token> {
+                self.$token.nft_tokens_for_owner_page(account_id, from_cursor, limit)
+            }
+        }
+    };
+}
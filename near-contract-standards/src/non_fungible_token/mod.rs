@@ -1,5 +1,10 @@
 /// The [approval management standard](https://nomicon.io/Standards/NonFungibleToken/ApprovalManagement.html) for NFTs.
 pub mod approval;
+/// Transfers of many tokens in one call, with the outcome of each transfer.
+pub mod batch;
+/// Client for calling external non-fungible token contracts.
+pub mod client;
+pub use self::client::NonFungibleTokenClient;
 /// The [core non-fungible token standard](https://nomicon.io/Standards/NonFungibleToken/Core.html). This can be though of as the base standard, with the others being extension standards.
 pub mod core;
 /// Common implementation of the [core non-fungible token standard](https://nomicon.io/Standards/NonFungibleToken/Core.html).
@@ -12,9 +17,19 @@ mod macros;
 /// Metadata traits and implementation according to the [NFT enumeration standard](https://nomicon.io/Standards/NonFungibleToken/Metadata.html).
 /// This covers both the contract metadata and the individual token metadata.
 pub mod metadata;
+/// The [payout standard](https://nomicon.io/Standards/NonFungibleToken/Payout.html) for NFTs, with
+/// default royalties of the collection and per-token overrides.
+pub mod payout;
+pub use self::payout::Royalties;
+/// Royalties of tokens looked up in an external royalty registry, for marketplaces.
+pub mod royalty_registry;
+pub use self::royalty_registry::RoyaltyRegistry;
 /// The Token struct for the non-fungible token.
 mod token;
 pub use self::token::{Token, TokenId};
+/// Generation of token IDs on mint.
+pub mod token_id_generator;
+pub use self::token_id_generator::TokenIdGenerator;
 
 /// NFT utility functions
 mod utils;
@@ -0,0 +1,287 @@
+use crate::non_fungible_token::royalty_registry::{assert_valid_royalty, payouts, Royalty};
+use crate::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, Balance, IntoStorageKey};
+use std::collections::HashMap;
+
+/// Accounts and amounts to pay for the sale of a token, as described in the
+/// [payout standard](https://nomicon.io/Standards/NonFungibleToken/Payout.html).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+impl Payout {
+    /// Returns the sum of the amounts, `None` if it overflows.
+    pub fn total(&self) -> Option<Balance> {
+        self.payout.values().try_fold(0, |total: Balance, amount| total.checked_add(amount.0))
+    }
+
+    /// Returns whether the amounts sum to `balance` and there are at most `max_len_payout`
+    /// receivers, e.g. for a marketplace to check the payout returned by `nft_transfer_payout`
+    /// before paying the receivers.
+    pub fn is_valid_for(&self, balance: Balance, max_len_payout: Option<u32>) -> bool {
+        self.total() == Some(balance)
+            && max_len_payout.map_or(true, |max_len| self.payout.len() <= max_len as usize)
+    }
+}
+
+/// Payout extension of the NFT standard, called by marketplaces to split the price of a sale
+/// between the owner and the royalty receivers. Exposed with
+/// [`impl_non_fungible_token_payout`](crate::impl_non_fungible_token_payout).
+pub trait NonFungibleTokenPayout {
+    /// Returns the payout of a sale of the token for `balance`. Panics if it has more than
+    /// `max_len_payout` receivers.
+    fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout;
+
+    /// Transfers the token like `nft_transfer` and returns the payout of the sale for `balance`.
+    fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout;
+}
+
+/// Royalties of the tokens of a contract for the payout extension: a default royalty of the
+/// collection, applied to the tokens without a royalty of their own, and per-token overrides.
+///
+/// Issuers set royalties on tokens minted before royalties existed without re-minting them,
+/// either through the default or, to pin the royalty of existing tokens before the default
+/// changes, with [`backfill`](Self::backfill) in batches from a migration. Royalties are in
+/// basis points, like those of the [`RoyaltyRegistry`](super::RoyaltyRegistry), and can only be
+/// changed by the `owner_id`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Royalties {
+    pub owner_id: AccountId,
+    default: Royalty,
+    overrides: LookupMap<TokenId, Royalty>,
+}
+
+impl Royalties {
+    /// Creates the royalties of a collection owned by `owner_id`, with `default` applied to all
+    /// tokens. Panics if the shares of the default royalty exceed the price.
+    pub fn new<S>(prefix: S, owner_id: AccountId, default: Royalty) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        assert_valid_royalty(&default);
+        Self { owner_id, default, overrides: LookupMap::new(prefix) }
+    }
+
+    /// Returns the default royalty of the collection.
+    pub fn default_royalty(&self) -> &Royalty {
+        &self.default
+    }
+
+    /// Returns the royalty of the token, which is its override or the default royalty.
+    pub fn royalty_of(&self, token_id: &TokenId) -> Royalty {
+        self.overrides.get(token_id).unwrap_or_else(|| self.default.clone())
+    }
+
+    /// Replaces the default royalty. Panics if the predecessor isn't the owner or the shares
+    /// exceed the price.
+    pub fn set_default(&mut self, royalty: Royalty) {
+        self.assert_owner();
+        assert_valid_royalty(&royalty);
+        self.default = royalty;
+    }
+
+    /// Overrides the royalty of the token, or restores the default royalty if `royalty` is
+    /// `None`. Panics if the predecessor isn't the owner or the shares exceed the price.
+    pub fn set_token_royalty(&mut self, token_id: &TokenId, royalty: Option<Royalty>) {
+        self.assert_owner();
+        match royalty {
+            Some(royalty) => {
+                assert_valid_royalty(&royalty);
+                self.overrides.insert(token_id, &royalty);
+            }
+            None => {
+                self.overrides.remove(token_id);
+            }
+        }
+    }
+
+    /// Sets `royalty` as the override of the tokens which don't have one, e.g. legacy tokens
+    /// minted before the contract had royalties, returning the number of tokens updated. Panics
+    /// if the predecessor isn't the owner or the shares exceed the price.
+    pub fn backfill(&mut self, token_ids: &[TokenId], royalty: &Royalty) -> u32 {
+        self.assert_owner();
+        assert_valid_royalty(royalty);
+        let mut updated = 0;
+        for token_id in token_ids {
+            if !self.overrides.contains_key(token_id) {
+                self.overrides.insert(token_id, royalty);
+                updated += 1;
+            }
+        }
+        updated
+    }
+
+    /// Returns the payout of a sale of the token owned by `owner_id` for `balance`, with the
+    /// rest of the royalties paid to the owner. Panics if it has more than `max_len_payout`
+    /// receivers.
+    pub fn payout(
+        &self,
+        token_id: &TokenId,
+        owner_id: AccountId,
+        balance: Balance,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let mut payout = HashMap::new();
+        for (account_id, amount) in payouts(balance, &self.royalty_of(token_id), owner_id) {
+            let total: &mut U128 = payout.entry(account_id).or_insert(U128(0));
+            total.0 += amount;
+        }
+        if let Some(max_len_payout) = max_len_payout {
+            require!(
+                payout.len() <= max_len_payout as usize,
+                "The payout has more receivers than max_len_payout"
+            );
+        }
+        Payout { payout }
+    }
+
+    fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+    }
+}
+
+/// Implements the [`NonFungibleTokenPayout`] extension with a [`Royalties`] field.
+/// Takes name of the Contract struct, the inner field for the token and the field for the
+/// royalties.
+#[macro_export]
+macro_rules! impl_non_fungible_token_payout {
+    ($contract: ident, $token: ident, $royalties: ident) => {
+        use $crate::non_fungible_token::payout::{NonFungibleTokenPayout, Payout};
+
+        #[near_bindgen]
+        impl NonFungibleTokenPayout for $contract {
+            fn nft_payout(
+                &self,
+                token_id: String,
+                balance: near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> Payout {
+                let owner_id = self
+                    .$token
+                    .owner_by_id
+                    .get(&token_id)
+                    .unwrap_or_else(|| near_sdk::env::panic_str("Token not found"));
+                self.$royalties.payout(&token_id, owner_id, balance.0, max_len_payout)
+            }
+
+            #[payable]
+            fn nft_transfer_payout(
+                &mut self,
+                receiver_id: AccountId,
+                token_id: String,
+                approval_id: Option<u64>,
+                memo: Option<String>,
+                balance: near_sdk::json_types::U128,
+                max_len_payout: Option<u32>,
+            ) -> Payout {
+                near_sdk::assert_one_yocto();
+                let sender_id = near_sdk::env::predecessor_account_id();
+                let (owner_id, _) = self.$token.internal_transfer(
+                    &sender_id,
+                    &receiver_id,
+                    &token_id,
+                    approval_id,
+                    memo,
+                );
+                self.$royalties.payout(&token_id, owner_id, balance.0, max_len_payout)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn setup() -> Royalties {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        Royalties::new(b"r", accounts(0), vec![(accounts(0), 500)].into_iter().collect())
+    }
+
+    fn amounts(payout: &Payout) -> Vec<(AccountId, Balance)> {
+        let mut amounts: Vec<_> = payout
+            .payout
+            .iter()
+            .map(|(account_id, amount)| (account_id.clone(), amount.0))
+            .collect();
+        amounts.sort();
+        amounts
+    }
+
+    #[test]
+    fn payout_validation() {
+        let royalties = setup();
+        let payout = royalties.payout(&"1".to_string(), accounts(1), 1000, None);
+        assert_eq!(payout.total(), Some(1000));
+        assert!(payout.is_valid_for(1000, Some(2)));
+        assert!(!payout.is_valid_for(1000, Some(1)));
+        assert!(!payout.is_valid_for(1001, None));
+
+        let overflow = Payout {
+            payout: vec![(accounts(0), U128(u128::MAX)), (accounts(1), U128(1))]
+                .into_iter()
+                .collect(),
+        };
+        assert_eq!(overflow.total(), None);
+    }
+
+    #[test]
+    fn default_and_override() {
+        let mut royalties = setup();
+        let payout = royalties.payout(&"1".to_string(), accounts(1), 1000, Some(2));
+        assert_eq!(amounts(&payout), vec![(accounts(0), 50), (accounts(1), 950)]);
+
+        royalties.set_token_royalty(
+            &"1".to_string(),
+            Some(vec![(accounts(2), 1000)].into_iter().collect()),
+        );
+        let payout = royalties.payout(&"1".to_string(), accounts(1), 1000, None);
+        assert_eq!(amounts(&payout), vec![(accounts(1), 900), (accounts(2), 100)]);
+
+        // The owner receiving royalties is paid once.
+        let payout = royalties.payout(&"2".to_string(), accounts(0), 1000, Some(1));
+        assert_eq!(amounts(&payout), vec![(accounts(0), 1000)]);
+    }
+
+    #[test]
+    fn backfill_legacy_tokens() {
+        let mut royalties = setup();
+        royalties.set_token_royalty(&"1".to_string(), Some(Royalty::new()));
+        let legacy: Royalty = vec![(accounts(3), 250)].into_iter().collect();
+        assert_eq!(royalties.backfill(&["1".to_string(), "2".to_string()], &legacy), 1);
+        royalties.set_default(Royalty::new());
+        assert_eq!(royalties.royalty_of(&"1".to_string()), Royalty::new());
+        assert_eq!(royalties.royalty_of(&"2".to_string()), legacy);
+        assert_eq!(royalties.royalty_of(&"3".to_string()), Royalty::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "The payout has more receivers than max_len_payout")]
+    fn payout_too_long() {
+        setup().payout(&"1".to_string(), accounts(1), 1000, Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_sets_royalties() {
+        let mut royalties = setup();
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        royalties.set_default(Royalty::new());
+    }
+}
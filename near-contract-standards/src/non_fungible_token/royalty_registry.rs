@@ -0,0 +1,243 @@
+use crate::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    env, ext_contract, require, AccountId, Balance, Duration, Gas, IntoStorageKey, Promise,
+    PromiseError, Timestamp,
+};
+use std::collections::BTreeMap;
+
+/// Gas attached to the `royalty_of` view call.
+pub const GAS_FOR_ROYALTY_OF: Gas = Gas(5_000_000_000_000);
+/// Denominator of the royalty shares, which are in basis points.
+pub const ROYALTY_DENOMINATOR: u32 = 10_000;
+
+const NO_DEPOSIT: Balance = 0;
+
+/// Shares of the price of a sale paid to the royalty receivers, in basis points.
+pub type Royalty = BTreeMap<AccountId, u32>;
+
+#[ext_contract(ext_royalty_registry)]
+pub trait RoyaltyRegistryContract {
+    /// Returns the royalty of the token of the NFT contract, or `None` if none was registered.
+    fn royalty_of(&self, nft_contract_id: AccountId, token_id: TokenId) -> Option<Royalty>;
+}
+
+/// What to do with a sale when the royalty of the token is unknown, because the registry
+/// doesn't have it or couldn't be queried.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum RoyaltyFallback {
+    /// Sell without royalties.
+    NoRoyalty,
+    /// Pay the given royalty, e.g. the default royalty of a collection.
+    Fixed(Royalty),
+    /// Refuse the sale.
+    Reject,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct CachedRoyalty {
+    royalty: Option<Royalty>,
+    cached_at: Timestamp,
+}
+
+/// Royalties of tokens set off-contract in an external royalty registry, for marketplaces which
+/// sell tokens lacking embedded payout info.
+///
+/// The marketplace looks up the royalty with [`cached`](Self::cached) first. On a miss, it calls
+/// [`query`](Self::query) and passes the result of the call to [`resolve`](Self::resolve) in the
+/// callback, which caches it for `cache_ttl` nanoseconds and applies the `fallback` policy if
+/// the registry has no royalty for the token or the call failed. The royalty is turned into the
+/// transfers of the sale with [`payouts`], e.g. for [`near_sdk::payout_many`].
+///
+/// ```ignore
+/// match self.royalties.cached(&nft_contract_id, &token_id) {
+///     Some(royalty) => self.settle(payouts(price, &royalty, seller_id)),
+///     None => self
+///         .royalties
+///         .query(nft_contract_id.clone(), token_id.clone())
+///         .then(ext_self::on_royalty(nft_contract_id, token_id, ..)),
+/// }
+///
+/// // In `on_royalty`:
+/// let royalty = self.royalties.resolve(&nft_contract_id, &token_id, RoyaltyRegistry::royalty_of_result(0));
+/// ```
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RoyaltyRegistry {
+    pub registry_id: AccountId,
+    pub cache_ttl: Duration,
+    pub fallback: RoyaltyFallback,
+    cache: LookupMap<(AccountId, TokenId), CachedRoyalty>,
+}
+
+impl RoyaltyRegistry {
+    pub fn new<S>(
+        prefix: S,
+        registry_id: AccountId,
+        cache_ttl: Duration,
+        fallback: RoyaltyFallback,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { registry_id, cache_ttl, fallback, cache: LookupMap::new(prefix) }
+    }
+
+    /// Returns the royalty of the token from the cache, with the fallback policy applied, or
+    /// `None` if it isn't cached or the cached royalty expired.
+    pub fn cached(&self, nft_contract_id: &AccountId, token_id: &TokenId) -> Option<Royalty> {
+        let key = (nft_contract_id.clone(), token_id.clone());
+        self.cache
+            .get(&key)
+            .filter(|cached| {
+                env::block_timestamp() <= cached.cached_at.saturating_add(self.cache_ttl)
+            })
+            .map(|cached| self.with_fallback(cached.royalty))
+    }
+
+    /// Calls `royalty_of` on the registry. The result is read with
+    /// [`royalty_of_result`](Self::royalty_of_result) in the callback.
+    pub fn query(&self, nft_contract_id: AccountId, token_id: TokenId) -> Promise {
+        ext_royalty_registry::royalty_of(
+            nft_contract_id,
+            token_id,
+            self.registry_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_ROYALTY_OF,
+        )
+    }
+
+    /// Reads the result of [`query`](Self::query) in a callback.
+    pub fn royalty_of_result(result_idx: u64) -> Result<Option<Royalty>, PromiseError> {
+        env::promise_result_json(result_idx)
+    }
+
+    /// Caches the result of [`query`](Self::query) and returns the royalty of the token, with the
+    /// fallback policy applied. Failed calls aren't cached, so the next sale queries the
+    /// registry again. Panics if the royalty shares exceed the price, or if the royalty is unknown
+    /// and the fallback policy rejects the sale.
+    pub fn resolve(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        result: Result<Option<Royalty>, PromiseError>,
+    ) -> Royalty {
+        let royalty = match result {
+            Ok(royalty) => {
+                if let Some(royalty) = &royalty {
+                    assert_valid_royalty(royalty);
+                }
+                self.cache.insert(
+                    &(nft_contract_id.clone(), token_id.clone()),
+                    &CachedRoyalty { royalty: royalty.clone(), cached_at: env::block_timestamp() },
+                );
+                royalty
+            }
+            Err(_) => None,
+        };
+        self.with_fallback(royalty)
+    }
+
+    /// Removes the cached royalty of the token, e.g. when the registry notified a change.
+    pub fn invalidate(&mut self, nft_contract_id: &AccountId, token_id: &TokenId) {
+        self.cache.remove(&(nft_contract_id.clone(), token_id.clone()));
+    }
+
+    fn with_fallback(&self, royalty: Option<Royalty>) -> Royalty {
+        match (royalty, &self.fallback) {
+            (Some(royalty), _) => royalty,
+            (None, RoyaltyFallback::NoRoyalty) => Royalty::new(),
+            (None, RoyaltyFallback::Fixed(royalty)) => royalty.clone(),
+            (None, RoyaltyFallback::Reject) => {
+                env::panic_str("The royalty of the token is unknown")
+            }
+        }
+    }
+}
+
+pub(crate) fn assert_valid_royalty(royalty: &Royalty) {
+    let total = royalty.values().fold(0u64, |total, share| total + u64::from(*share));
+    require!(total <= u64::from(ROYALTY_DENOMINATOR), "Royalties exceed the price");
+}
+
+/// Share of the price in basis points, rounded down, without overflowing for any price.
+fn royalty_amount(price: Balance, share: u32) -> Balance {
+    let denominator = Balance::from(ROYALTY_DENOMINATOR);
+    let share = Balance::from(share);
+    price / denominator * share + price % denominator * share / denominator
+}
+
+/// Splits the price of a sale between the royalty receivers and the seller, who gets the rest of
+/// the price after the royalties are rounded down. Panics if the royalty shares exceed the price.
+pub fn payouts(
+    price: Balance,
+    royalty: &Royalty,
+    seller_id: AccountId,
+) -> Vec<(AccountId, Balance)> {
+    assert_valid_royalty(royalty);
+    let mut payouts: Vec<_> = royalty
+        .iter()
+        .map(|(account_id, share)| (account_id.clone(), royalty_amount(price, *share)))
+        .filter(|(_, amount)| *amount > 0)
+        .collect();
+    let royalties: Balance = payouts.iter().map(|(_, amount)| amount).sum();
+    payouts.push((seller_id, price - royalties));
+    payouts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn setup(timestamp: Timestamp, fallback: RoyaltyFallback) -> RoyaltyRegistry {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+        RoyaltyRegistry::new(b"r", accounts(5), 100, fallback)
+    }
+
+    fn royalty() -> Royalty {
+        vec![(accounts(1), 1_000), (accounts(2), 250)].into_iter().collect()
+    }
+
+    #[test]
+    fn resolve_and_cache() {
+        let mut registry = setup(0, RoyaltyFallback::NoRoyalty);
+        let token_id = "1".to_string();
+        assert_eq!(registry.cached(&accounts(0), &token_id), None);
+        assert_eq!(registry.resolve(&accounts(0), &token_id, Ok(Some(royalty()))), royalty());
+        assert_eq!(registry.cached(&accounts(0), &token_id), Some(royalty()));
+
+        testing_env!(VMContextBuilder::new().block_timestamp(101).build());
+        assert_eq!(registry.cached(&accounts(0), &token_id), None);
+    }
+
+    #[test]
+    fn fallback() {
+        let mut registry = setup(0, RoyaltyFallback::Fixed(royalty()));
+        let token_id = "1".to_string();
+        assert_eq!(registry.resolve(&accounts(0), &token_id, Err(PromiseError::Failed)), royalty());
+        assert_eq!(registry.cached(&accounts(0), &token_id), None);
+        assert_eq!(registry.resolve(&accounts(0), &token_id, Ok(None)), royalty());
+        assert_eq!(registry.cached(&accounts(0), &token_id), Some(royalty()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The royalty of the token is unknown")]
+    fn fallback_reject() {
+        let mut registry = setup(0, RoyaltyFallback::Reject);
+        registry.resolve(&accounts(0), &"1".to_string(), Ok(None));
+    }
+
+    #[test]
+    fn split_payouts() {
+        assert_eq!(
+            payouts(1_000_003, &royalty(), accounts(3)),
+            vec![(accounts(1), 100_000), (accounts(2), 25_000), (accounts(3), 875_003)]
+        );
+        assert_eq!(payouts(10, &Royalty::new(), accounts(3)), vec![(accounts(3), 10)]);
+    }
+}
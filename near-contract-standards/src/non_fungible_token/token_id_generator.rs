@@ -0,0 +1,143 @@
+use crate::non_fungible_token::{NonFungibleToken, TokenId};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{env, AccountId, IntoStorageKey};
+
+/// Number of digits of the largest `u64`, the width sequential token IDs are padded to.
+const SEQUENTIAL_ID_WIDTH: usize = 20;
+
+/// Generates token IDs on mint instead of relying on IDs supplied by clients, which may collide
+/// or break the order of enumeration.
+///
+/// Every generated ID is checked against the minted tokens, and IDs which are already taken, e.g.
+/// by tokens minted with explicit IDs, are skipped. The strategies are:
+/// - [`next_sequential`](Self::next_sequential): `"00000000000000000000"`,
+///   `"00000000000000000001"`, ... zero-padded, so the lexicographic order of enumeration is the
+///   order of minting.
+/// - [`next_hashed`](Self::next_hashed): UUID formatted hash of the minter and a nonce, for IDs
+///   which don't reveal the number of minted tokens.
+/// - [`next_edition`](Self::next_edition): `"<series>:1"`, `"<series>:2"`, ... numbered per
+///   series.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenIdGenerator {
+    /// Next sequence number, shared by sequential IDs and the nonces of hashed IDs.
+    pub next_nonce: u64,
+    /// Series -> Next edition number.
+    pub next_edition_by_series: LookupMap<String, u64>,
+}
+
+impl TokenIdGenerator {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { next_nonce: 0, next_edition_by_series: LookupMap::new(prefix) }
+    }
+
+    fn take_nonce(&mut self) -> u64 {
+        let nonce = self.next_nonce;
+        self.next_nonce =
+            nonce.checked_add(1).unwrap_or_else(|| env::panic_str("Token ID nonce overflow"));
+        nonce
+    }
+
+    /// Returns the next zero-padded sequence number which isn't a minted token ID.
+    pub fn next_sequential(&mut self, tokens: &NonFungibleToken) -> TokenId {
+        loop {
+            let token_id = format!("{:0width$}", self.take_nonce(), width = SEQUENTIAL_ID_WIDTH);
+            if !tokens.owner_by_id.contains_key(&token_id) {
+                return token_id;
+            }
+        }
+    }
+
+    /// Returns a UUID formatted ID derived from the SHA-256 hash of `minter_id` and the next
+    /// nonce, which isn't a minted token ID. The ID is deterministic, so it can be computed by
+    /// clients from the state of the generator.
+    pub fn next_hashed(&mut self, tokens: &NonFungibleToken, minter_id: &AccountId) -> TokenId {
+        loop {
+            let mut input = minter_id.as_bytes().to_vec();
+            input.extend_from_slice(&self.take_nonce().to_le_bytes());
+            let token_id = uuid_from_hash(&env::sha256_array(&input));
+            if !tokens.owner_by_id.contains_key(&token_id) {
+                return token_id;
+            }
+        }
+    }
+
+    /// Returns the next edition of `series_id` which isn't a minted token ID. Editions are
+    /// numbered from 1.
+    pub fn next_edition(&mut self, tokens: &NonFungibleToken, series_id: &str) -> TokenId {
+        let series_key = series_id.to_string();
+        let mut edition = self.next_edition_by_series.get(&series_key).unwrap_or(1);
+        let token_id = loop {
+            let token_id = format!("{}:{}", series_id, edition);
+            edition =
+                edition.checked_add(1).unwrap_or_else(|| env::panic_str("Edition number overflow"));
+            if !tokens.owner_by_id.contains_key(&token_id) {
+                break token_id;
+            }
+        };
+        self.next_edition_by_series.insert(&series_key, &edition);
+        token_id
+    }
+}
+
+/// Formats the first 16 bytes of the hash as a UUID, e.g.
+/// `"2c26b46b-68ff-c68f-f99b-453c1d304134"`.
+fn uuid_from_hash(hash: &[u8; 32]) -> String {
+    let hex = hex_encode(&hash[..16]);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn setup() -> (NonFungibleToken, TokenIdGenerator) {
+        testing_env!(VMContextBuilder::new().build());
+        let tokens = NonFungibleToken::new(
+            b"t".to_vec(),
+            accounts(0),
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+            None::<Vec<u8>>,
+        );
+        (tokens, TokenIdGenerator::new(b"g".to_vec()))
+    }
+
+    #[test]
+    fn sequential_skips_minted_ids() {
+        let (mut tokens, mut generator) = setup();
+        tokens.owner_by_id.insert(&"00000000000000000001".to_string(), &accounts(1));
+        assert_eq!(generator.next_sequential(&tokens), "00000000000000000000");
+        assert_eq!(generator.next_sequential(&tokens), "00000000000000000002");
+        assert!(generator.next_sequential(&tokens) > "00000000000000000002".to_string());
+    }
+
+    #[test]
+    fn hashed_is_deterministic() {
+        let (tokens, mut generator) = setup();
+        let first = generator.next_hashed(&tokens, &accounts(1));
+        assert_eq!(first.len(), 36);
+        assert_ne!(generator.next_hashed(&tokens, &accounts(1)), first);
+
+        let (tokens, mut generator) = setup();
+        assert_eq!(generator.next_hashed(&tokens, &accounts(1)), first);
+    }
+
+    #[test]
+    fn editions_per_series() {
+        let (mut tokens, mut generator) = setup();
+        tokens.owner_by_id.insert(&"moon:2".to_string(), &accounts(1));
+        assert_eq!(generator.next_edition(&tokens, "moon"), "moon:1");
+        assert_eq!(generator.next_edition(&tokens, "moon"), "moon:3");
+        assert_eq!(generator.next_edition(&tokens, "sun"), "sun:1");
+    }
+}
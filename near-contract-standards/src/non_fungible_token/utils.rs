@@ -1,4 +1,4 @@
-use near_sdk::{env, require, AccountId, Balance, CryptoHash, Promise};
+use near_sdk::{env, AccountId, Balance, ContractError, CryptoHash, FunctionError, Promise};
 use std::collections::HashMap;
 use std::mem::size_of;
 
@@ -12,11 +12,19 @@ pub fn refund_approved_account_ids_iter<'a, I>(
     account_id: AccountId,
     approved_account_ids: I,
 ) -> Promise
+where
+    I: Iterator<Item = &'a AccountId>,
+{
+    Promise::new(account_id).transfer(approved_account_ids_storage_cost(approved_account_ids))
+}
+
+/// Storage staking cost of the approvals, refunded when they're removed.
+pub fn approved_account_ids_storage_cost<'a, I>(approved_account_ids: I) -> Balance
 where
     I: Iterator<Item = &'a AccountId>,
 {
     let storage_released: u64 = approved_account_ids.map(bytes_for_approved_account_id).sum();
-    Promise::new(account_id).transfer(Balance::from(storage_released) * env::storage_byte_cost())
+    Balance::from(storage_released) * env::storage_byte_cost()
 }
 
 pub fn refund_approved_account_ids(
@@ -30,10 +38,9 @@ pub fn refund_deposit_to_account(storage_used: u64, account_id: AccountId) {
     let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
     let attached_deposit = env::attached_deposit();
 
-    require!(
-        required_cost <= attached_deposit,
-        format!("Must attach {} yoctoNEAR to cover storage", required_cost)
-    );
+    if attached_deposit < required_cost {
+        ContractError::insufficient_deposit(required_cost).panic()
+    }
 
     let refund = attached_deposit - required_cost;
     if refund > 1 {
@@ -51,8 +58,3 @@ pub fn hash_account_id(account_id: &AccountId) -> CryptoHash {
     hash.copy_from_slice(&env::sha256(account_id.as_bytes()));
     hash
 }
-
-/// Assert that at least 1 yoctoNEAR was attached.
-pub(crate) fn assert_at_least_one_yocto() {
-    require!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR")
-}
@@ -0,0 +1,185 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, require, AccountId};
+
+/// Standard interface of [`Ownership`], exposed with [`impl_ownable`](crate::impl_ownable).
+pub trait OwnershipManagement {
+    fn owner_get(&self) -> AccountId;
+
+    /// Returns the account proposed as the next owner, which hasn't accepted yet.
+    fn owner_pending(&self) -> Option<AccountId>;
+
+    /// Proposes `new_owner_id` as the next owner, or withdraws the proposal with `None`. Can only
+    /// be called by the owner, with exactly 1 yoctoNEAR attached.
+    fn owner_propose(&mut self, new_owner_id: Option<AccountId>);
+
+    /// Accepts the ownership. Can only be called by the proposed owner, with exactly 1 yoctoNEAR
+    /// attached.
+    fn owner_accept(&mut self);
+}
+
+/// Owner of a contract, transferred in two steps: the owner proposes the next owner, which
+/// becomes the owner once it accepts. A typo in the proposed account can't lock the contract,
+/// since the current owner stays in control until the proposal is accepted.
+///
+/// Contracts guard their privileged methods with [`assert_owner`](Self::assert_owner), and their
+/// paused methods with a [`Pause`](crate::pause::Pause) field and `#[pausable(field = "pause")]`.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct Ownership {
+    owner_id: AccountId,
+    pending_owner_id: Option<AccountId>,
+}
+
+impl Ownership {
+    pub fn new(owner_id: AccountId) -> Self {
+        Self { owner_id, pending_owner_id: None }
+    }
+
+    pub fn owner_id(&self) -> &AccountId {
+        &self.owner_id
+    }
+
+    pub fn pending_owner_id(&self) -> Option<&AccountId> {
+        self.pending_owner_id.as_ref()
+    }
+
+    /// Panics if the predecessor isn't the owner.
+    pub fn assert_owner(&self) {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+    }
+
+    /// Proposes the next owner, replacing the previous proposal. Panics if the predecessor isn't
+    /// the owner.
+    pub fn propose(&mut self, new_owner_id: Option<AccountId>) {
+        self.assert_owner();
+        self.pending_owner_id = new_owner_id;
+    }
+
+    /// Makes the predecessor the owner. Panics if it isn't the proposed owner.
+    pub fn accept(&mut self) {
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&account_id),
+            "The predecessor isn't the proposed owner"
+        );
+        self.pending_owner_id = None;
+        self.owner_id = account_id;
+    }
+
+    /// Replaces the owner without its acceptance and withdraws the pending proposal, for the
+    /// recoveries of [`Guardians`](crate::guardians::Guardians).
+    pub(crate) fn recover(&mut self, owner_id: AccountId) {
+        self.pending_owner_id = None;
+        self.owner_id = owner_id;
+    }
+}
+
+/// Exposes the [`OwnershipManagement`] methods of an [`Ownership`] field. The yoctoNEAR required
+/// by the mutating methods ensures they are signed with a full access key.
+/// Takes name of the Contract struct and the inner field for the ownership.
+#[macro_export]
+macro_rules! impl_ownable {
+    ($contract: ident, $ownership: ident) => {
+        use $crate::ownership::OwnershipManagement;
+
+        #[near_bindgen]
+        impl OwnershipManagement for $contract {
+            fn owner_get(&self) -> AccountId {
+                self.$ownership.owner_id().clone()
+            }
+
+            fn owner_pending(&self) -> Option<AccountId> {
+                self.$ownership.pending_owner_id().cloned()
+            }
+
+            #[payable]
+            fn owner_propose(&mut self, new_owner_id: Option<AccountId>) {
+                near_sdk::assert_one_yocto();
+                self.$ownership.propose(new_owner_id)
+            }
+
+            #[payable]
+            fn owner_accept(&mut self) {
+                near_sdk::assert_one_yocto();
+                self.$ownership.accept()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env};
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        ownership: Ownership,
+    }
+
+    impl_ownable!(Contract, ownership);
+
+    fn call(predecessor: usize) {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(predecessor)).build());
+    }
+
+    #[test]
+    fn transfer_ownership() {
+        call(0);
+        let mut ownership = Ownership::new(accounts(0));
+        ownership.propose(Some(accounts(1)));
+        assert_eq!(ownership.owner_id(), &accounts(0));
+
+        call(1);
+        ownership.accept();
+        assert_eq!(ownership.owner_id(), &accounts(1));
+        assert_eq!(ownership.pending_owner_id(), None);
+        ownership.assert_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "The predecessor isn't the proposed owner")]
+    fn withdrawn_proposal() {
+        call(0);
+        let mut ownership = Ownership::new(accounts(0));
+        ownership.propose(Some(accounts(1)));
+        ownership.propose(None);
+        call(1);
+        ownership.accept();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_owner_proposes() {
+        call(1);
+        let mut ownership = Ownership::new(accounts(0));
+        ownership.propose(Some(accounts(1)));
+    }
+
+    #[test]
+    fn owner_methods_require_one_yocto() {
+        let mut contract = Contract { ownership: Ownership::new(accounts(0)) };
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.owner_propose(Some(accounts(1)));
+        assert_eq!(contract.owner_pending(), Some(accounts(1)));
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.owner_accept();
+        assert_eq!(contract.owner_get(), accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn propose_without_yocto() {
+        call(0);
+        let mut contract = Contract { ownership: Ownership::new(accounts(0)) };
+        contract.owner_propose(Some(accounts(1)));
+    }
+}
@@ -0,0 +1,141 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::PauseFlags;
+use std::collections::BTreeSet;
+
+/// Pause state of a contract as returned by the [`Pausable::pause_state`] view.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    /// Whether the whole contract is paused.
+    pub paused: bool,
+    /// Names of the features which are paused individually, in lexicographic order.
+    pub paused_features: Vec<String>,
+}
+
+/// Standard view for wallets and frontends to detect a paused contract before submitting
+/// transactions to it.
+pub trait Pausable {
+    /// Returns whether the contract, or some of its features, are paused.
+    fn pause_state(&self) -> PauseState;
+}
+
+/// Pause flags of a contract, to be stored in the contract state.
+///
+/// The contract decides who may change the flags, and guards its methods with
+/// [`assert_not_paused`](Self::assert_not_paused) using the feature names it documents, e.g.
+/// `"ft_transfer"`, or with `#[pausable(field = "pause", feature = "ft_transfer")]` on the
+/// methods of a `#[near_bindgen]` impl, the feature being the method name if not set. The
+/// state is exposed through [`Pausable`] with [`impl_pausable`](crate::impl_pausable).
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq)]
+pub struct Pause {
+    paused: bool,
+    paused_features: BTreeSet<String>,
+}
+
+impl Pause {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pauses the whole contract.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Unpauses the whole contract. Features which are paused individually stay paused.
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn pause_feature(&mut self, feature: &str) {
+        self.paused_features.insert(feature.to_string());
+    }
+
+    pub fn unpause_feature(&mut self, feature: &str) {
+        self.paused_features.remove(feature);
+    }
+
+    /// Returns `true` if the whole contract or the given feature is paused.
+    pub fn is_paused(&self, feature: &str) -> bool {
+        self.paused || self.paused_features.contains(feature)
+    }
+
+    /// Panics if the whole contract or the given feature is paused.
+    pub fn assert_not_paused(&self, feature: &str) {
+        near_sdk::assert_not_paused(self, feature)
+    }
+
+    pub fn state(&self) -> PauseState {
+        PauseState {
+            paused: self.paused,
+            paused_features: self.paused_features.iter().cloned().collect(),
+        }
+    }
+}
+
+impl PauseFlags for Pause {
+    fn is_contract_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn is_feature_paused(&self, feature: &str) -> bool {
+        self.paused_features.contains(feature)
+    }
+}
+
+/// Exposes the `pause_state` view of a [`Pause`] field.
+/// Takes name of the Contract struct and the inner field for the pause flags.
+#[macro_export]
+macro_rules! impl_pausable {
+    ($contract: ident, $pause: ident) => {
+        use $crate::pause::{Pausable, PauseState};
+
+        #[near_bindgen]
+        impl Pausable for $contract {
+            fn pause_state(&self) -> PauseState {
+                self.$pause.state()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_features() {
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        pause.pause_feature("nft_mint");
+        assert!(pause.is_paused("ft_transfer"));
+        assert!(!pause.is_paused("storage_deposit"));
+
+        pause.pause();
+        assert!(pause.is_paused("storage_deposit"));
+        assert_eq!(
+            pause.state(),
+            PauseState {
+                paused: true,
+                paused_features: vec!["ft_transfer".to_string(), "nft_mint".to_string()]
+            }
+        );
+
+        pause.unpause();
+        pause.unpause_feature("nft_mint");
+        assert_eq!(
+            pause.state(),
+            PauseState { paused: false, paused_features: vec!["ft_transfer".to_string()] }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The feature ft_transfer is paused")]
+    fn assert_not_paused() {
+        let mut pause = Pause::new();
+        pause.pause_feature("ft_transfer");
+        pause.assert_not_paused("storage_deposit");
+        pause.assert_not_paused("ft_transfer");
+    }
+}
@@ -0,0 +1,202 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U64;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, require, Duration, IntoStorageKey, Timestamp};
+
+/// Outstanding callback of a flow, as recorded by [`PendingCallbacks::start`].
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct PendingCallback<T> {
+    /// Key of the state locked until the callback executes or expires.
+    pub lock: String,
+    /// Block timestamp after which anyone can expire the callback.
+    pub deadline: Timestamp,
+    /// What the contract needs to unwind the flow if the callback never executes.
+    pub context: T,
+}
+
+/// Reentrancy guard of the flows of a contract which span a cross-contract call and its
+/// callback, with a deadline after which anyone can unwind a flow whose callback never executed,
+/// e.g. because the receiver ran out of gas and the callback was never scheduled.
+///
+/// [`start`](Self::start) locks a key of the state touched by the flow, e.g. the account or
+/// the pool, so that no other flow using the key starts until this one ends, and returns the id
+/// of the flow, which is passed to the callback. The callback calls
+/// [`complete`](Self::complete), which releases the lock, and must not apply the result of the
+/// call if it returns `None` because the flow already expired. Once `timeout` nanoseconds
+/// passed, [`expire_stale`](Self::expire_stale) releases the lock and returns the context of the
+/// flow, with which the contract reverts its effects, e.g. refunds a deposit. Expirations are
+/// logged as events:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"pending_callbacks","version":"1.0.0","event":"callback_expired","data":[{"id":"3","lock":"pool:usdc.near"}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PendingCallbacks<T> {
+    pub timeout: Duration,
+    next_id: u64,
+    pending: LookupMap<u64, PendingCallback<T>>,
+    locks: LookupMap<String, u64>,
+}
+
+impl<T> PendingCallbacks<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    pub fn new<S>(prefix: S, timeout: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            timeout,
+            next_id: 0,
+            pending: LookupMap::new([prefix.as_slice(), b"p"].concat()),
+            locks: LookupMap::new([prefix.as_slice(), b"l"].concat()),
+        }
+    }
+
+    /// Starts a flow locking `lock`, returning its id. Panics if the key is locked by another
+    /// flow.
+    pub fn start(&mut self, lock: &str, context: T) -> u64 {
+        require!(!self.is_locked(lock), "The state is locked by a pending callback");
+        let id = self.next_id;
+        self.next_id += 1;
+        let deadline = env::block_timestamp().saturating_add(self.timeout);
+        self.locks.insert(&lock.to_string(), &id);
+        self.pending.insert(&id, &PendingCallback { lock: lock.to_string(), deadline, context });
+        id
+    }
+
+    /// Ends the flow from its callback, releasing its lock and returning its context, or `None`
+    /// if the flow already expired, in which case the callback must not apply its result.
+    pub fn complete(&mut self, id: u64) -> Option<T> {
+        self.remove(id).map(|pending| pending.context)
+    }
+
+    /// Ends the flow once its deadline passed, releasing its lock and returning its context to
+    /// unwind it. Can be called by anyone. Panics if the flow doesn't exist or isn't stale yet.
+    pub fn expire_stale(&mut self, id: u64) -> T {
+        let pending = self
+            .pending
+            .get(&id)
+            .unwrap_or_else(|| env::panic_str("No pending callback with this id"));
+        require!(env::block_timestamp() > pending.deadline, "The callback is not stale yet");
+        self.remove(id);
+        NearEvent::PendingCallbacks(PendingCallbacksEvent {
+            version: "1.0.0",
+            event: "callback_expired",
+            data: &[CallbackExpired { id: U64(id), lock: &pending.lock }],
+        })
+        .emit();
+        pending.context
+    }
+
+    /// Returns the pending flow, or `None` if it ended.
+    pub fn get(&self, id: u64) -> Option<PendingCallback<T>> {
+        self.pending.get(&id)
+    }
+
+    /// Returns `true` if a pending flow locks the key.
+    pub fn is_locked(&self, lock: &str) -> bool {
+        self.locks.contains_key(&lock.to_string())
+    }
+
+    fn remove(&mut self, id: u64) -> Option<PendingCallback<T>> {
+        let pending = self.pending.remove(&id)?;
+        self.locks.remove(&pending.lock);
+        Some(pending)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct PendingCallbacksEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [CallbackExpired<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct CallbackExpired<'a> {
+    id: U64,
+    lock: &'a str,
+}
+
+/// Generates the public `expire_stale(id)` method, which expires a stale flow of a
+/// [`PendingCallbacks`] field and passes its context to a method of the contract unwinding it.
+/// Takes name of the Contract struct, the inner field for the pending callbacks and the name of
+/// the unwinding method, taking `&mut self` and the context.
+#[macro_export]
+macro_rules! impl_expire_stale {
+    ($contract: ident, $callbacks: ident, $unwind: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            pub fn expire_stale(&mut self, id: near_sdk::json_types::U64) {
+                let context = self.$callbacks.expire_stale(id.0);
+                self.$unwind(context);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn at(timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn complete_releases_lock() {
+        let mut callbacks = PendingCallbacks::new(b"c", 100);
+        at(0);
+        let id = callbacks.start("pool:usdc", 42u128);
+        assert!(callbacks.is_locked("pool:usdc"));
+        assert_eq!(callbacks.get(id).map(|pending| pending.deadline), Some(100));
+        assert_eq!(callbacks.complete(id), Some(42));
+        assert!(!callbacks.is_locked("pool:usdc"));
+        assert_eq!(callbacks.complete(id), None);
+    }
+
+    #[test]
+    fn expire_stale_callback() {
+        let mut callbacks = PendingCallbacks::new(b"c", 100);
+        at(0);
+        let id = callbacks.start("pool:usdc", 42u128);
+        at(101);
+        assert_eq!(callbacks.expire_stale(id), 42);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"pending_callbacks","version":"1.0.0","event":"callback_expired","data":[{"id":"0","lock":"pool:usdc"}]}"#
+            ]
+        );
+        // The late callback doesn't apply its result.
+        assert_eq!(callbacks.complete(id), None);
+        callbacks.start("pool:usdc", 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "The callback is not stale yet")]
+    fn expire_before_deadline() {
+        let mut callbacks = PendingCallbacks::new(b"c", 100);
+        at(0);
+        let id = callbacks.start("pool:usdc", 42u128);
+        at(100);
+        callbacks.expire_stale(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "The state is locked by a pending callback")]
+    fn reentrant_flow() {
+        let mut callbacks = PendingCallbacks::new(b"c", 100);
+        callbacks.start("pool:usdc", 42u128);
+        callbacks.start("pool:usdc", 7);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::event::NearEvent;
+use crate::non_fungible_token::payout::Payout;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedMap;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::layout::{StorageLayout, StorageRegion};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    assert_one_yocto, env, require, AccountId, Balance, Duration, Gas, IntoStorageKey, Promise,
+    Timestamp,
+};
+
+/// Gas attached to the `on_refund` callback of a refund.
+pub const GAS_FOR_RESOLVE_REFUND: Gas = Gas(5_000_000_000_000);
+/// Default number of credits scanned by one `expire_refunds` call.
+pub const DEFAULT_EXPIRE_LIMIT: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct RefundCredit {
+    amount: Balance,
+    queued_at: Timestamp,
+}
+
+/// Result of [`RefundQueue::expire`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpireOutcome {
+    /// Total amount sent to the treasury.
+    pub amount: U128,
+    /// Number of credits expired.
+    pub expired: u64,
+    /// `from_index` of the next call, or `None` once all credits were scanned.
+    pub next_index: Option<U64>,
+}
+
+/// Refunds of Ⓝ which are kept as claimable credits when their transfer fails, e.g. because the
+/// receiving account was deleted, instead of being lost in the balance of the contract.
+///
+/// Refunds are sent with [`transfer`](Self::transfer), which adds an `on_refund` callback to the
+/// transfer. The callback, generated with [`impl_refund_queue`](crate::impl_refund_queue), calls
+/// [`resolve`](Self::resolve), which credits the amount to the account if the transfer failed.
+/// The account, once recreated, gets its credits back with `claim_refund`. Credits which are
+/// not claimed within `expiry` nanoseconds of their first failed refund are sent to the treasury
+/// by the owner with [`expire`](Self::expire). Every change is logged as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"refunds","version":"1.0.0","event":"refund_queued","data":[{"account_id":"alice.near","amount":"100"}]}
+/// ```
+///
+/// With `refunds = <field>`,
+/// [`impl_shared_storage_management`](crate::impl_shared_storage_management) sends the
+/// withdrawals of `storage_withdraw` and `storage_unregister` through the queue,
+/// [`impl_fungible_token_storage`](crate::impl_fungible_token_storage) the storage deposit
+/// returned by `storage_unregister`, and
+/// [`impl_non_fungible_token_core`](crate::impl_non_fungible_token_core) the approval storage
+/// refunded by `nft_resolve_transfer`. [`pay_out`](Self::pay_out) pays the shares of a
+/// [`Payout`].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RefundQueue {
+    pub owner_id: AccountId,
+    pub treasury_id: AccountId,
+    /// Time after which an unclaimed credit can be sent to the treasury.
+    pub expiry: Duration,
+    credits: UnorderedMap<AccountId, RefundCredit>,
+}
+
+impl RefundQueue {
+    pub fn new<S>(prefix: S, owner_id: AccountId, treasury_id: AccountId, expiry: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { owner_id, treasury_id, expiry, credits: UnorderedMap::new(prefix) }
+    }
+
+    /// Transfers `amount` to the account, with the `on_refund` callback which credits it to the
+    /// account if the transfer fails.
+    pub fn transfer(&self, account_id: AccountId, amount: Balance) -> Promise {
+        let args = json!({ "account_id": account_id, "amount": U128(amount) });
+        Promise::new(account_id).transfer(amount).then(
+            Promise::new(env::current_account_id()).function_call(
+                "on_refund".to_string(),
+                args.to_string().into_bytes(),
+                0,
+                GAS_FOR_RESOLVE_REFUND,
+            ),
+        )
+    }
+
+    /// Transfers the share of each receiver of the payout with [`transfer`](Self::transfer).
+    pub fn pay_out(&self, payout: &Payout) {
+        for (account_id, amount) in payout.payout.iter() {
+            if amount.0 > 0 {
+                self.transfer(account_id.clone(), amount.0);
+            }
+        }
+    }
+
+    /// Resolves the transfer of a refund, crediting `amount` to the account if the transfer with
+    /// the result `result_idx` failed. Returns `true` if the transfer succeeded.
+    pub fn resolve(&mut self, account_id: &AccountId, amount: Balance, result_idx: u64) -> bool {
+        match env::promise_result_bounded(result_idx, env::MAX_PROMISE_RESULT_BYTES) {
+            Ok(_) => true,
+            Err(_) => {
+                // The credit keeps the time of its first refund, so that later refunds don't
+                // postpone its expiry.
+                let credit = match self.credits.get(account_id) {
+                    Some(credit) => RefundCredit {
+                        amount: credit.amount.checked_add(amount).unwrap_or_else(|| {
+                            env::panic_str("The refund credit overflows the balance")
+                        }),
+                        queued_at: credit.queued_at,
+                    },
+                    None => RefundCredit { amount, queued_at: env::block_timestamp() },
+                };
+                self.credits.insert(account_id, &credit);
+                emit_event(
+                    "refund_queued",
+                    &[RefundChange { account_id, amount: U128(amount), treasury_id: None }],
+                );
+                false
+            }
+        }
+    }
+
+    /// Returns the credit of the account, 0 if it has none.
+    pub fn refund_of(&self, account_id: &AccountId) -> Balance {
+        self.credits.get(account_id).map_or(0, |credit| credit.amount)
+    }
+
+    /// Transfers the credit of the predecessor back to it. Requires 1 yoctoⓃ and panics if the
+    /// predecessor has no credit.
+    pub fn claim(&mut self) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let credit = self
+            .credits
+            .remove(&account_id)
+            .unwrap_or_else(|| env::panic_str("The account has no refund to claim"));
+        emit_event(
+            "refund_claimed",
+            &[RefundChange {
+                account_id: &account_id,
+                amount: U128(credit.amount),
+                treasury_id: None,
+            }],
+        );
+        self.transfer(account_id, credit.amount)
+    }
+
+    /// Sends the credits queued more than `expiry` ago to the treasury, scanning up to `limit`
+    /// credits backwards from `from_index`. Panics if the predecessor isn't the owner.
+    pub fn expire(&mut self, from_index: Option<u64>, limit: u64) -> ExpireOutcome {
+        require!(env::predecessor_account_id() == self.owner_id, "Unauthorized");
+        require!(limit != 0, "Cannot provide limit of 0.");
+        let queued_before = env::block_timestamp().saturating_sub(self.expiry);
+        let end = from_index.unwrap_or(u64::MAX).min(self.credits.len());
+        let start = end.saturating_sub(limit);
+        let keys = self.credits.keys_as_vector();
+        let values = self.credits.values_as_vector();
+        let expired: Vec<(AccountId, RefundCredit)> = (start..end)
+            .map(|index| {
+                let account_id = keys.get(index).unwrap_or_else(|| env::abort());
+                let credit = values.get(index).unwrap_or_else(|| env::abort());
+                (account_id, credit)
+            })
+            .filter(|(_, credit)| credit.queued_at < queued_before)
+            .collect();
+        // Removing swaps the last credit into the removed one, and the credits after `start`
+        // were scanned already, so none is skipped.
+        let mut amount: Balance = 0;
+        for (account_id, credit) in &expired {
+            self.credits.remove(account_id);
+            amount = amount
+                .checked_add(credit.amount)
+                .unwrap_or_else(|| env::panic_str("The expired refunds overflow the balance"));
+        }
+        if amount > 0 {
+            self.transfer(self.treasury_id.clone(), amount);
+        }
+        let data: Vec<_> = expired
+            .iter()
+            .map(|(account_id, credit)| RefundChange {
+                account_id,
+                amount: U128(credit.amount),
+                treasury_id: Some(&self.treasury_id),
+            })
+            .collect();
+        if !data.is_empty() {
+            emit_event("refund_expired", &data);
+        }
+        ExpireOutcome {
+            amount: U128(amount),
+            expired: expired.len() as u64,
+            next_index: if start > 0 { Some(U64(start)) } else { None },
+        }
+    }
+}
+
+impl StorageLayout for RefundQueue {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        UnorderedMap::<AccountId, RefundCredit>::storage_layout(prefix)
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct RefundsEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [RefundChange<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct RefundChange<'a> {
+    account_id: &'a AccountId,
+    amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    treasury_id: Option<&'a AccountId>,
+}
+
+fn emit_event(event: &'static str, data: &[RefundChange<'_>]) {
+    NearEvent::Refunds(RefundsEvent { version: "1.0.0", event, data }).emit();
+}
+
+/// Exposes the `on_refund` callback of a [`RefundQueue`] field, with the `claim_refund` method,
+/// the `refund_of` view and the owner-only `expire_refunds` method.
+/// Takes name of the Contract struct and the inner field for the refunds.
+#[macro_export]
+macro_rules! impl_refund_queue {
+    ($contract: ident, $refunds: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            #[private]
+            pub fn on_refund(
+                &mut self,
+                account_id: AccountId,
+                amount: near_sdk::json_types::U128,
+            ) -> bool {
+                self.$refunds.resolve(&account_id, amount.0, 0)
+            }
+
+            #[payable]
+            pub fn claim_refund(&mut self) -> near_sdk::Promise {
+                self.$refunds.claim()
+            }
+
+            pub fn refund_of(&self, account_id: AccountId) -> near_sdk::json_types::U128 {
+                self.$refunds.refund_of(&account_id).into()
+            }
+
+            pub fn expire_refunds(
+                &mut self,
+                from_index: Option<near_sdk::json_types::U64>,
+                limit: Option<u64>,
+            ) -> $crate::refund_queue::ExpireOutcome {
+                self.$refunds.expire(
+                    from_index.map(|index| index.0),
+                    limit.unwrap_or($crate::refund_queue::DEFAULT_EXPIRE_LIMIT),
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::FungibleToken;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::{near_bindgen, testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    const DAY: Duration = 86_400_000_000_000;
+
+    #[near_bindgen]
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Contract {
+        token: FungibleToken,
+        refunds: RefundQueue,
+    }
+
+    crate::impl_fungible_token_storage!(Contract, token, refunds = refunds);
+    crate::impl_refund_queue!(Contract, refunds);
+
+    /// Returns the receivers of the transfers and the methods of the function calls created.
+    fn created_actions() -> Vec<(AccountId, Option<Balance>, Option<String>)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().map(move |action| match action {
+                    VmAction::Transfer { deposit } => (receiver_id.clone(), Some(deposit), None),
+                    VmAction::FunctionCall { function_name, .. } => {
+                        (receiver_id.clone(), None, Some(function_name))
+                    }
+                    _ => (receiver_id.clone(), None, None),
+                })
+            })
+            .collect()
+    }
+
+    fn callback(result: PromiseResult, timestamp: Timestamp) {
+        testing_env!(
+            VMContextBuilder::new()
+                .predecessor_account_id(accounts(0))
+                .block_timestamp(timestamp)
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn setup() -> RefundQueue {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        RefundQueue::new(b"r", accounts(0), accounts(5), 30 * DAY)
+    }
+
+    #[test]
+    fn failed_refund_is_claimable() {
+        let mut refunds = setup();
+        callback(PromiseResult::Successful(vec![]), DAY);
+        assert!(refunds.resolve(&accounts(1), 10, 0));
+        callback(PromiseResult::Failed, DAY);
+        assert!(!refunds.resolve(&accounts(1), 10, 0));
+        assert!(!refunds.resolve(&accounts(1), 5, 0));
+        assert_eq!(refunds.refund_of(&accounts(1)), 15);
+        assert_eq!(
+            get_logs()[0],
+            r#"EVENT_JSON:{"standard":"refunds","version":"1.0.0","event":"refund_queued","data":[{"account_id":"bob","amount":"10"}]}"#
+        );
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        refunds.claim();
+        assert_eq!(refunds.refund_of(&accounts(1)), 0);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"refunds","version":"1.0.0","event":"refund_claimed","data":[{"account_id":"bob","amount":"15"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn expired_refunds_go_to_treasury() {
+        let mut refunds = setup();
+        callback(PromiseResult::Failed, DAY);
+        refunds.resolve(&accounts(1), 10, 0);
+        callback(PromiseResult::Failed, 20 * DAY);
+        refunds.resolve(&accounts(2), 20, 0);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(40 * DAY)
+            .build());
+        let outcome = refunds.expire(None, 10);
+        assert_eq!(outcome, ExpireOutcome { amount: U128(10), expired: 1, next_index: None });
+        assert_eq!(refunds.refund_of(&accounts(2)), 20);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"refunds","version":"1.0.0","event":"refund_expired","data":[{"account_id":"bob","amount":"10","treasury_id":"fargo"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account has no refund to claim")]
+    fn claim_without_refund() {
+        let mut refunds = setup();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        refunds.claim();
+    }
+
+    #[test]
+    fn pay_out_transfers_shares() {
+        let refunds = setup();
+        let payout = Payout {
+            payout: vec![(accounts(1), U128(70)), (accounts(2), U128(0))].into_iter().collect(),
+        };
+        refunds.pay_out(&payout);
+        let callback = (accounts(0), None, Some("on_refund".to_string()));
+        assert_eq!(created_actions(), vec![(accounts(1), Some(70), None), callback]);
+    }
+
+    #[test]
+    fn storage_unregister_refund_is_claimable() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut contract = Contract {
+            token: FungibleToken::new(b"t".to_vec()),
+            refunds: RefundQueue::new(b"r", accounts(0), accounts(5), 30 * DAY),
+        };
+        let deposit = contract.storage_balance_bounds().min.0;
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(deposit)
+            .build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        assert!(contract.storage_unregister(None));
+        assert_eq!(
+            created_actions(),
+            vec![
+                (accounts(1), Some(deposit + 1), None),
+                (accounts(0), None, Some("on_refund".to_string())),
+            ]
+        );
+
+        // bob was deleted before the refund arrived.
+        callback(PromiseResult::Failed, DAY);
+        assert!(!contract.on_refund(accounts(1), U128(deposit + 1)));
+        assert_eq!(contract.refund_of(accounts(1)), U128(deposit + 1));
+    }
+
+    #[test]
+    fn credit_keeps_first_refund_time() {
+        let mut refunds = setup();
+        callback(PromiseResult::Failed, DAY);
+        refunds.resolve(&accounts(1), 10, 0);
+        callback(PromiseResult::Failed, 35 * DAY);
+        refunds.resolve(&accounts(1), 1, 0);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(35 * DAY)
+            .build());
+        let outcome = refunds.expire(None, 10);
+        assert_eq!(outcome, ExpireOutcome { amount: U128(11), expired: 1, next_index: None });
+    }
+
+    #[test]
+    #[should_panic(expected = "The refund credit overflows the balance")]
+    fn credit_overflow() {
+        let mut refunds = setup();
+        callback(PromiseResult::Failed, DAY);
+        refunds.resolve(&accounts(1), Balance::MAX, 0);
+        refunds.resolve(&accounts(1), 1, 0);
+    }
+}
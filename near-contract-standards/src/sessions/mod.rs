@@ -0,0 +1,277 @@
+use crate::event::NearEvent;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_at_least_one_yocto, assert_one_yocto, env, require, AccountId, Balance, Duration,
+    IntoStorageKey, PublicKey, StorageTracker,
+};
+
+/// Permissions of a session key, as set by its account in `create_session`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SessionScope {
+    /// Methods the session key can call. Must not be empty.
+    pub methods: Vec<String>,
+    /// Block timestamp in nanoseconds after which the session key is rejected.
+    pub expires_at: U64,
+    /// Maximum amount a single call made with the session key can spend, in the unit of the
+    /// balances of the contract.
+    pub spend_cap: U128,
+}
+
+/// Standard interface of [`Sessions`], exposed with [`impl_sessions`](crate::impl_sessions).
+pub trait SessionManager {
+    /// Scopes the function-call access key `public_key` of the predecessor to `scope`. Requires a
+    /// deposit for the storage of the session, at least one yoctoNEAR, so that it can't be called
+    /// with a session key.
+    fn create_session(&mut self, public_key: PublicKey, scope: SessionScope);
+
+    /// Revokes the session of the predecessor. Requires one yoctoNEAR.
+    fn revoke_session(&mut self, public_key: PublicKey);
+
+    fn session(&self, account_id: AccountId, public_key: PublicKey) -> Option<SessionScope>;
+}
+
+/// Temporary keys with which the users of a contract act for a while without confirming every
+/// transaction in their wallet, e.g. the moves of a game, with bounded risk if the key leaks.
+///
+/// The user adds a function-call access key for the contract to their account, which is the one
+/// wallet prompt, and registers it with `create_session`, giving the methods the key can call, an
+/// expiry at most `max_duration` away and a cap on the amount each call can spend. Methods call
+/// [`authorize`](Self::authorize), which checks the scope of the session when the transaction
+/// is signed with a session key, and returns the account the call acts for.
+///
+/// Calls signed with other keys of the account, or made by other contracts, are not restricted,
+/// so a user shouldn't add function-call access keys for the contract without registering them.
+/// Creating and revoking sessions require an attached deposit, which function-call access keys
+/// can't attach. Every change is logged as an event:
+///
+/// ```text
+/// EVENT_JSON:{"standard":"sessions","version":"1.0.0","event":"session_revoked","data":[{"account_id":"alice.near","public_key":"ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"}]}
+/// ```
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Sessions {
+    pub max_duration: Duration,
+    sessions: LookupMap<(AccountId, PublicKey), SessionScope>,
+}
+
+impl Sessions {
+    pub fn new<S>(prefix: S, max_duration: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { max_duration, sessions: LookupMap::new(prefix) }
+    }
+
+    /// Returns the scope of the session, or `None` if there's no such session, even expired.
+    pub fn get(&self, account_id: &AccountId, public_key: &PublicKey) -> Option<SessionScope> {
+        self.sessions.get(&(account_id.clone(), public_key.clone()))
+    }
+
+    /// Registers the session key of the predecessor, replacing its previous scope. Panics if the
+    /// scope has no methods or expires more than `max_duration` from now. Requires at least one
+    /// yoctoNEAR attached, and enough to cover the storage of a new session, with the rest
+    /// refunded.
+    pub fn create(&mut self, public_key: PublicKey, scope: SessionScope) {
+        assert_at_least_one_yocto();
+        require!(!scope.methods.is_empty(), "The session must allow at least one method");
+        let now = env::block_timestamp();
+        require!(scope.expires_at.0 > now, "The session is already expired");
+        require!(
+            scope.expires_at.0 - now <= self.max_duration,
+            "The session exceeds the maximum duration"
+        );
+        let account_id = env::predecessor_account_id();
+        let storage_tracker = StorageTracker::start();
+        self.sessions.insert(&(account_id.clone(), public_key.clone()), &scope);
+        storage_tracker.finish();
+        emit_event("session_created", &account_id, &public_key, Some(scope.expires_at));
+    }
+
+    /// Revokes the session key of the predecessor, returning `true` if it was registered.
+    /// Requires exactly one yoctoNEAR attached.
+    pub fn revoke(&mut self, public_key: &PublicKey) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let revoked = self.sessions.remove(&(account_id.clone(), public_key.clone())).is_some();
+        if revoked {
+            emit_event("session_revoked", &account_id, public_key, None);
+        }
+        revoked
+    }
+
+    /// Returns the account the current call acts for, which is the predecessor. If the
+    /// transaction is signed with a session key of the predecessor, panics unless the session
+    /// isn't expired, allows `method` and `spend` is within its cap.
+    pub fn authorize(&self, method: &str, spend: Balance) -> AccountId {
+        let account_id = env::predecessor_account_id();
+        if account_id != env::signer_account_id() {
+            return account_id;
+        }
+        if let Some(scope) = self.get(&account_id, &env::signer_account_pk()) {
+            require!(env::block_timestamp() <= scope.expires_at.0, "The session is expired");
+            require!(
+                scope.methods.iter().any(|allowed| allowed == method),
+                "The method is not allowed in the session"
+            );
+            require!(spend <= scope.spend_cap.0, "The spend exceeds the cap of the session");
+        }
+        account_id
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct SessionsEvent<'a> {
+    version: &'static str,
+    event: &'static str,
+    data: &'a [SessionChange<'a>],
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+struct SessionChange<'a> {
+    account_id: &'a AccountId,
+    public_key: &'a PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<U64>,
+}
+
+fn emit_event(
+    event: &'static str,
+    account_id: &AccountId,
+    public_key: &PublicKey,
+    expires_at: Option<U64>,
+) {
+    NearEvent::Sessions(SessionsEvent {
+        version: "1.0.0",
+        event,
+        data: &[SessionChange { account_id, public_key, expires_at }],
+    })
+    .emit();
+}
+
+/// Exposes the [`SessionManager`] methods of a [`Sessions`] field.
+/// Takes name of the Contract struct and the inner field for the sessions.
+#[macro_export]
+macro_rules! impl_sessions {
+    ($contract: ident, $sessions: ident) => {
+        use $crate::sessions::{SessionManager, SessionScope};
+
+        #[near_bindgen]
+        impl SessionManager for $contract {
+            #[payable]
+            fn create_session(&mut self, public_key: near_sdk::PublicKey, scope: SessionScope) {
+                self.$sessions.create(public_key, scope)
+            }
+
+            #[payable]
+            fn revoke_session(&mut self, public_key: near_sdk::PublicKey) {
+                self.$sessions.revoke(&public_key);
+            }
+
+            fn session(
+                &self,
+                account_id: AccountId,
+                public_key: near_sdk::PublicKey,
+            ) -> Option<SessionScope> {
+                self.$sessions.get(&account_id, &public_key)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const HOUR: Duration = 3_600_000_000_000;
+
+    fn session_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap()
+    }
+
+    fn wallet_key() -> PublicKey {
+        "secp256k1:qMoRgcoXai4mBPsdbHi1wfyxF9TdbPCF4qSDQTRP3TfescSRoUdSx6nmeQoN3aiwGzwMyGXAb1gUjBTv5AY8DXj"
+            .parse()
+            .unwrap()
+    }
+
+    fn call(public_key: PublicKey, deposit: Balance, timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .signer_account_id(accounts(0))
+            .signer_account_pk(public_key)
+            .attached_deposit(deposit)
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn setup() -> Sessions {
+        let mut sessions = Sessions::new(b"s", 24 * HOUR);
+        call(wallet_key(), 10u128.pow(22), 0);
+        sessions.create(
+            session_key(),
+            SessionScope {
+                methods: vec!["play".to_string()],
+                expires_at: U64(2 * HOUR),
+                spend_cap: U128(100),
+            },
+        );
+        sessions
+    }
+
+    #[test]
+    fn authorize_session_calls() {
+        let sessions = setup();
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"sessions","version":"1.0.0","event":"session_created","data":[{"account_id":"alice","public_key":"ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp","expires_at":"7200000000000"}]}"#
+            ]
+        );
+        call(session_key(), 0, HOUR);
+        assert_eq!(sessions.authorize("play", 100), accounts(0));
+        // Calls signed with other keys aren't restricted.
+        call(wallet_key(), 0, HOUR);
+        assert_eq!(sessions.authorize("withdraw", 1000), accounts(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "The method is not allowed in the session")]
+    fn authorize_other_method() {
+        let sessions = setup();
+        call(session_key(), 0, HOUR);
+        sessions.authorize("withdraw", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The spend exceeds the cap of the session")]
+    fn authorize_over_cap() {
+        let sessions = setup();
+        call(session_key(), 0, HOUR);
+        sessions.authorize("play", 101);
+    }
+
+    #[test]
+    #[should_panic(expected = "The session is expired")]
+    fn authorize_expired() {
+        let sessions = setup();
+        call(session_key(), 0, 3 * HOUR);
+        sessions.authorize("play", 0);
+    }
+
+    #[test]
+    fn revoke() {
+        let mut sessions = setup();
+        call(wallet_key(), 1, HOUR);
+        assert!(sessions.revoke(&session_key()));
+        assert_eq!(sessions.get(&accounts(0), &session_key()), None);
+        call(session_key(), 0, HOUR);
+        sessions.authorize("withdraw", 1000);
+    }
+}
@@ -0,0 +1,300 @@
+use super::{StorageBalance, StorageBalanceBounds};
+use near_sdk::json_types::U128;
+use near_sdk::{env, ext_contract, AccountId, Balance, Gas, Promise, PromiseError, PromiseOrValue};
+
+const GAS_FOR_STORAGE_VIEW: Gas = Gas(5_000_000_000_000);
+const GAS_FOR_STORAGE_DEPOSIT: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_ON_DEPOSIT: Gas = Gas(5_000_000_000_000);
+const GAS_FOR_ON_BOUNDS: Gas =
+    Gas(5_000_000_000_000 + GAS_FOR_STORAGE_DEPOSIT.0 + GAS_FOR_ON_DEPOSIT.0);
+
+/// Gas needed by [`StorageDepositClient::register`] for the whole registration flow.
+pub const GAS_FOR_STORAGE_REGISTRATION: Gas =
+    Gas(2 * GAS_FOR_STORAGE_VIEW.0 + GAS_FOR_ON_BOUNDS.0 + 5_000_000_000_000);
+
+const NO_DEPOSIT: Balance = 0;
+
+#[ext_contract(ext_storage_management)]
+pub trait StorageManagementContract {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance;
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+}
+
+#[ext_contract(ext_self)]
+trait StorageDepositResolver {
+    fn storage_registration_on_bounds(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        payer_id: AccountId,
+        amount: U128,
+    ) -> PromiseOrValue<bool>;
+
+    fn storage_registration_on_deposit(&mut self, payer_id: AccountId, deposit: U128) -> bool;
+}
+
+/// Callbacks of the [`StorageDepositClient`] registration flow. These should be implemented
+/// with [`impl_storage_deposit_client`](crate::impl_storage_deposit_client).
+pub trait StorageDepositResolver {
+    /// Called with the results of `storage_balance_of` and `storage_balance_bounds`. Calls
+    /// `storage_deposit` with the minimum bound if the account is not registered yet, and refunds
+    /// the rest of `amount` to `payer_id`.
+    fn storage_registration_on_bounds(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        payer_id: AccountId,
+        amount: U128,
+    ) -> PromiseOrValue<bool>;
+
+    /// Called with the result of `storage_deposit`. Returns whether the account was registered,
+    /// refunding `deposit` to `payer_id` if it wasn't.
+    fn storage_registration_on_deposit(&mut self, payer_id: AccountId, deposit: U128) -> bool;
+}
+
+/// Client for registering accounts on third party contracts implementing the storage
+/// management standard, such as fungible tokens, before sending tokens to them.
+///
+/// [`register`](Self::register) queries `storage_balance_of` and `storage_balance_bounds`, then
+/// calls `storage_deposit` with exactly the minimum bound and verifies the returned balance in a
+/// callback. The unused part of the attached amount, or all of it if registration fails or the
+/// account is already registered, is refunded to the payer. The resulting promise resolves to
+/// `true` if the account is registered.
+///
+/// The callbacks are exposed by the contract through
+/// [`impl_storage_deposit_client`](crate::impl_storage_deposit_client).
+pub struct StorageDepositClient {
+    pub token_id: AccountId,
+}
+
+impl StorageDepositClient {
+    pub fn new(token_id: AccountId) -> Self {
+        Self { token_id }
+    }
+
+    /// Registers `account_id` on the token contract paying with up to `amount`, which must be
+    /// held by this contract, e.g. attached by `payer_id` to the current call. Requires
+    /// [`GAS_FOR_STORAGE_REGISTRATION`] gas.
+    pub fn register(&self, account_id: AccountId, payer_id: AccountId, amount: Balance) -> Promise {
+        ext_storage_management::storage_balance_of(
+            account_id.clone(),
+            self.token_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_STORAGE_VIEW,
+        )
+        .and(ext_storage_management::storage_balance_bounds(
+            self.token_id.clone(),
+            NO_DEPOSIT,
+            GAS_FOR_STORAGE_VIEW,
+        ))
+        .then(ext_self::storage_registration_on_bounds(
+            self.token_id.clone(),
+            account_id,
+            payer_id,
+            amount.into(),
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_ON_BOUNDS,
+        ))
+    }
+
+    /// Implementation of [`StorageDepositResolver::storage_registration_on_bounds`].
+    pub fn resolve_bounds(
+        token_id: AccountId,
+        account_id: AccountId,
+        payer_id: AccountId,
+        amount: U128,
+    ) -> PromiseOrValue<bool> {
+        let amount: Balance = amount.into();
+        let registered = match promise_result_json::<Option<StorageBalance>>(0) {
+            Some(balance) => balance.is_some(),
+            None => return refund(payer_id, amount, false),
+        };
+        if registered {
+            return refund(payer_id, amount, true);
+        }
+        let min_deposit: Balance = match promise_result_json::<StorageBalanceBounds>(1) {
+            Some(bounds) if bounds.min.0 <= amount => bounds.min.into(),
+            _ => return refund(payer_id, amount, false),
+        };
+        if amount > min_deposit {
+            Promise::new(payer_id.clone()).transfer(amount - min_deposit);
+        }
+        ext_storage_management::storage_deposit(
+            Some(account_id),
+            Some(true),
+            token_id,
+            min_deposit,
+            GAS_FOR_STORAGE_DEPOSIT,
+        )
+        .then(ext_self::storage_registration_on_deposit(
+            payer_id,
+            min_deposit.into(),
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_ON_DEPOSIT,
+        ))
+        .into()
+    }
+
+    /// Implementation of [`StorageDepositResolver::storage_registration_on_deposit`].
+    pub fn resolve_deposit(payer_id: AccountId, deposit: U128) -> bool {
+        match promise_result_json::<StorageBalance>(0) {
+            Some(balance) if balance.total.0 > 0 => true,
+            // The deposit of a failed call is returned to this contract, so it's passed on.
+            _ => {
+                refund(payer_id, deposit.into(), false);
+                false
+            }
+        }
+    }
+}
+
+fn promise_result_json<T: near_sdk::serde::de::DeserializeOwned>(index: u64) -> Option<T> {
+    match env::promise_result_bounded(index, env::MAX_PROMISE_RESULT_BYTES) {
+        Err(PromiseError::NotReady) => env::abort(),
+        Ok(value) => near_sdk::serde_json::from_slice(&value).ok(),
+        Err(_) => None,
+    }
+}
+
+fn refund(payer_id: AccountId, amount: Balance, registered: bool) -> PromiseOrValue<bool> {
+    if amount > 0 {
+        Promise::new(payer_id).transfer(amount);
+    }
+    PromiseOrValue::Value(registered)
+}
+
+/// Exposes the callbacks of [`StorageDepositClient`] on the contract.
+/// Takes name of the Contract struct.
+#[macro_export]
+macro_rules! impl_storage_deposit_client {
+    ($contract: ident) => {
+        use $crate::storage_management::{StorageDepositClient, StorageDepositResolver};
+
+        #[near_bindgen]
+        impl StorageDepositResolver for $contract {
+            #[private]
+            fn storage_registration_on_bounds(
+                &mut self,
+                token_id: AccountId,
+                account_id: AccountId,
+                payer_id: AccountId,
+                amount: U128,
+            ) -> PromiseOrValue<bool> {
+                StorageDepositClient::resolve_bounds(token_id, account_id, payer_id, amount)
+            }
+
+            #[private]
+            fn storage_registration_on_deposit(
+                &mut self,
+                payer_id: AccountId,
+                deposit: U128,
+            ) -> bool {
+                StorageDepositClient::resolve_deposit(payer_id, deposit)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    /// Returns the receivers, deposits and methods of the actions created.
+    fn created_actions() -> Vec<(AccountId, Balance, Option<String>)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().map(move |action| match action {
+                    VmAction::Transfer { deposit } => (receiver_id.clone(), deposit, None),
+                    VmAction::FunctionCall { function_name, deposit, .. } => {
+                        (receiver_id.clone(), deposit, Some(function_name))
+                    }
+                    _ => (receiver_id.clone(), 0, None),
+                })
+            })
+            .collect()
+    }
+
+    fn callback(results: Vec<PromiseResult>) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(accounts(0))
+                .predecessor_account_id(accounts(0))
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+    }
+
+    fn json(value: &str) -> PromiseResult {
+        PromiseResult::Successful(value.as_bytes().to_vec())
+    }
+
+    fn resolve_bounds(amount: Balance) -> PromiseOrValue<bool> {
+        StorageDepositClient::resolve_bounds(accounts(1), accounts(2), accounts(3), U128(amount))
+    }
+
+    #[test]
+    fn registers_with_min_bound() {
+        callback(vec![json("null"), json(r#"{"min":"10","max":null}"#)]);
+        assert!(matches!(resolve_bounds(25), PromiseOrValue::Promise(_)));
+        assert_eq!(
+            created_actions(),
+            vec![
+                (accounts(3), 15, None),
+                (accounts(1), 10, Some("storage_deposit".to_string())),
+                (accounts(0), 0, Some("storage_registration_on_deposit".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn already_registered() {
+        callback(vec![
+            json(r#"{"total":"10","available":"0"}"#),
+            json(r#"{"min":"10","max":null}"#),
+        ]);
+        assert!(matches!(resolve_bounds(25), PromiseOrValue::Value(true)));
+        assert_eq!(created_actions(), vec![(accounts(3), 25, None)]);
+    }
+
+    #[test]
+    fn amount_below_min_bound() {
+        callback(vec![json("null"), json(r#"{"min":"30","max":null}"#)]);
+        assert!(matches!(resolve_bounds(25), PromiseOrValue::Value(false)));
+        assert_eq!(created_actions(), vec![(accounts(3), 25, None)]);
+    }
+
+    #[test]
+    fn failed_view() {
+        callback(vec![PromiseResult::Failed, json(r#"{"min":"10","max":null}"#)]);
+        assert!(matches!(resolve_bounds(25), PromiseOrValue::Value(false)));
+        assert_eq!(created_actions(), vec![(accounts(3), 25, None)]);
+    }
+
+    #[test]
+    fn resolve_deposit() {
+        callback(vec![json(r#"{"total":"10","available":"0"}"#)]);
+        assert!(StorageDepositClient::resolve_deposit(accounts(3), U128(10)));
+        assert!(created_actions().is_empty());
+
+        callback(vec![PromiseResult::Failed]);
+        assert!(!StorageDepositClient::resolve_deposit(accounts(3), U128(10)));
+        assert_eq!(created_actions(), vec![(accounts(3), 10, None)]);
+    }
+}
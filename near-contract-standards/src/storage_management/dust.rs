@@ -0,0 +1,120 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, require, AccountId, Balance, Promise};
+
+/// Dust of the storage operations as returned by the `storage_dust` view.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDustReport {
+    pub dust_account_id: AccountId,
+    /// Dust collected and not forwarded to the dust account yet.
+    pub pending: U128,
+    /// Dust forwarded to the dust account so far.
+    pub forwarded: U128,
+}
+
+/// View of the [`StorageDust`] of a contract, exposed with
+/// [`impl_fungible_token_storage`](crate::impl_fungible_token_storage).
+pub trait StorageDustView {
+    fn storage_dust(&self) -> StorageDustReport;
+}
+
+/// Small amounts which storage operations leave on the contract, like the 1 yoctoⓃ attached to
+/// `storage_withdraw` or refunds too small to be worth a transfer, routed to a dust account so
+/// that operators can account for them.
+///
+/// Dust is [`record`](Self::record)ed as it's left and forwarded to the `dust_account_id` in one
+/// transfer once the pending dust reaches `flush_threshold`, so that each storage operation
+/// doesn't create a transfer receipt. The totals are reported by the `storage_dust` view.
+///
+/// Pass the field to [`impl_fungible_token_storage`](crate::impl_fungible_token_storage) with
+/// `dust = <field>` to record the deposits of `storage_withdraw`. Other components record their
+/// dust themselves.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct StorageDust {
+    pub admin_id: AccountId,
+    pub dust_account_id: AccountId,
+    /// Pending dust which triggers a transfer to the dust account.
+    pub flush_threshold: Balance,
+    pending: Balance,
+    forwarded: Balance,
+}
+
+impl StorageDust {
+    pub fn new(admin_id: AccountId, dust_account_id: AccountId, flush_threshold: Balance) -> Self {
+        Self { admin_id, dust_account_id, flush_threshold, pending: 0, forwarded: 0 }
+    }
+
+    /// Records dust left on the contract, and forwards the pending dust if it reached the
+    /// threshold.
+    pub fn record(&mut self, amount: Balance) -> Option<Promise> {
+        self.pending += amount;
+        if self.pending > 0 && self.pending >= self.flush_threshold {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Forwards the pending dust to the dust account, returning `None` if there is none.
+    pub fn flush(&mut self) -> Option<Promise> {
+        if self.pending == 0 {
+            return None;
+        }
+        let amount = std::mem::take(&mut self.pending);
+        self.forwarded += amount;
+        Some(Promise::new(self.dust_account_id.clone()).transfer(amount))
+    }
+
+    /// Changes the dust account. Pending dust goes to the new account. Panics if the predecessor
+    /// isn't the admin.
+    pub fn set_dust_account(&mut self, dust_account_id: AccountId) {
+        require!(env::predecessor_account_id() == self.admin_id, "Unauthorized");
+        self.dust_account_id = dust_account_id;
+    }
+
+    pub fn report(&self) -> StorageDustReport {
+        StorageDustReport {
+            dust_account_id: self.dust_account_id.clone(),
+            pending: self.pending.into(),
+            forwarded: self.forwarded.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn dust_is_forwarded_at_the_threshold() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let mut dust = StorageDust::new(accounts(0), accounts(1), 3);
+        assert!(dust.record(1).is_none());
+        assert!(dust.record(1).is_none());
+        assert!(dust.record(1).is_some());
+        assert!(dust.record(1).is_none());
+        assert_eq!(
+            dust.report(),
+            StorageDustReport {
+                dust_account_id: accounts(1),
+                pending: U128(1),
+                forwarded: U128(3)
+            }
+        );
+        dust.set_dust_account(accounts(2));
+        assert!(dust.flush().is_some());
+        assert!(dust.flush().is_none());
+        assert_eq!(dust.report().forwarded, U128(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn only_admin_sets_dust_account() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        StorageDust::new(accounts(0), accounts(1), 1).set_dust_account(accounts(1));
+    }
+}
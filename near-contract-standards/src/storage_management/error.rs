@@ -0,0 +1,62 @@
+use near_sdk::{AccountId, Balance, ContractError, FunctionError};
+use std::fmt;
+
+/// Error of the [`StorageManagement`](super::StorageManagement) of an account. The storage
+/// management implementations panic with the error as a [`ContractError`], whose `code` is given
+/// by [`code`](Self::code).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StorageError {
+    AccountNotRegistered(AccountId),
+    /// The attached deposit is lower than the minimum storage balance.
+    InsufficientDeposit(Balance),
+    /// The withdrawn amount is greater than the available storage balance.
+    InsufficientAvailableBalance,
+    /// The account still has tokens and the unregistration isn't forced.
+    PositiveBalance,
+}
+
+impl StorageError {
+    /// Returns the machine readable kind of the error, e.g. `"INSUFFICIENT_DEPOSIT"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AccountNotRegistered(_) => ContractError::ACCOUNT_NOT_REGISTERED,
+            Self::InsufficientDeposit(_) => ContractError::INSUFFICIENT_DEPOSIT,
+            Self::InsufficientAvailableBalance => "INSUFFICIENT_AVAILABLE_BALANCE",
+            Self::PositiveBalance => "POSITIVE_BALANCE",
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AccountNotRegistered(account_id) => {
+                write!(f, "The account {} is not registered", account_id)
+            }
+            Self::InsufficientDeposit(amount) => {
+                write!(f, "Requires attached deposit of at least {} yoctoNEAR", amount)
+            }
+            Self::InsufficientAvailableBalance => {
+                write!(f, "The amount is greater than the available storage balance")
+            }
+            Self::PositiveBalance => {
+                write!(f, "Can't unregister the account with the positive balance without force")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<StorageError> for ContractError {
+    fn from(error: StorageError) -> Self {
+        ContractError::new(error.code(), error.to_string())
+    }
+}
+
+impl FunctionError for StorageError {
+    fn panic(&self) -> ! {
+        ContractError::new(self.code(), self.to_string()).panic()
+    }
+}
@@ -3,6 +3,21 @@ use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::AccountId;
 
+mod client;
+pub use client::{
+    ext_storage_management, StorageDepositClient, StorageDepositResolver,
+    GAS_FOR_STORAGE_REGISTRATION,
+};
+
+mod dust;
+pub use dust::{StorageDust, StorageDustReport, StorageDustView};
+
+mod error;
+pub use error::StorageError;
+
+mod shared;
+pub use shared::{SharedStorageAccount, SharedStorageAccounts};
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StorageBalance {
@@ -59,3 +74,17 @@ pub trait StorageManagement {
 
     fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
 }
+
+/// Registration of many accounts from a single attached deposit, e.g. to onboard the recipients
+/// of an airdrop in one transaction. This extends the storage management standard and is not
+/// part of it.
+pub trait StorageManagementBatch {
+    /// Registers every account of `account_ids` which isn't registered yet, paying the minimum
+    /// storage balance of each one from the attached deposit. Accounts which are already
+    /// registered are skipped and the remainder of the deposit is refunded to the predecessor.
+    ///
+    /// MUST panic if the attached deposit doesn't cover the registration of all new accounts.
+    ///
+    /// Returns the StorageBalance structure of each account in the order of `account_ids`.
+    fn storage_deposit_many(&mut self, account_ids: Vec<AccountId>) -> Vec<StorageBalance>;
+}
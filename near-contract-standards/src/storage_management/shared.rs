@@ -0,0 +1,439 @@
+use crate::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageError, StorageManagement,
+};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_min_deposit, assert_one_yocto, env, log, AccountId, Balance, FunctionError,
+    IntoStorageKey, Promise, StorageUsage,
+};
+use std::collections::BTreeMap;
+
+/// Storage record of an account in [`SharedStorageAccounts`].
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq)]
+pub struct SharedStorageAccount {
+    /// Total Ⓝ deposited for storage by the account.
+    pub deposit: Balance,
+    /// Bytes used by the components on behalf of the account, in addition to the registration.
+    pub extra_bytes: StorageUsage,
+}
+
+/// One storage balance per account which covers every component a contract embeds, e.g. a
+/// fungible token, a non-fungible token and custom per-user state, so that users make one NEP-145
+/// deposit instead of one per component.
+///
+/// Each component registers the bytes it needs for an account with
+/// [`register_component`](Self::register_component), usually in the initializer of the contract,
+/// and the minimum storage balance is the cost of all of them. Storage which components use later
+/// on behalf of an account is charged to its `available` balance with
+/// [`internal_charge_storage`](Self::internal_charge_storage).
+///
+/// The NEP-145 interface is exposed with
+/// [`impl_shared_storage_management`](crate::impl_shared_storage_management), which also lets
+/// the contract register the account with its components.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SharedStorageAccounts {
+    pub accounts: LookupMap<AccountId, SharedStorageAccount>,
+    /// Component name -> Bytes required to register an account with the component.
+    pub components: BTreeMap<String, StorageUsage>,
+    /// Bytes of the record of an account in `accounts`.
+    pub account_storage_usage: StorageUsage,
+}
+
+impl SharedStorageAccounts {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let mut this = Self {
+            accounts: LookupMap::new(prefix),
+            components: BTreeMap::new(),
+            account_storage_usage: 0,
+        };
+        this.measure_account_storage_usage();
+        this
+    }
+
+    fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = AccountId::new_unchecked("a".repeat(64));
+        self.accounts.insert(&tmp_account_id, &SharedStorageAccount::default());
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.accounts.remove(&tmp_account_id);
+    }
+
+    /// Sets the bytes which the component `name` needs to register an account, e.g.
+    /// `token.account_storage_usage` of a [`FungibleToken`](crate::fungible_token::FungibleToken).
+    ///
+    /// Raising the requirement after accounts were registered raises their minimum balance too,
+    /// which reduces their `available` balance, down to 0.
+    pub fn register_component(&mut self, name: &str, bytes: StorageUsage) {
+        self.components.insert(name.to_string(), bytes);
+    }
+
+    /// Bytes required to register an account with all components.
+    pub fn registration_bytes(&self) -> StorageUsage {
+        self.account_storage_usage + self.components.values().sum::<StorageUsage>()
+    }
+
+    pub fn is_registered(&self, account_id: &AccountId) -> bool {
+        self.accounts.contains_key(account_id)
+    }
+
+    pub fn internal_unwrap_account(&self, account_id: &AccountId) -> SharedStorageAccount {
+        self.accounts
+            .get(account_id)
+            .unwrap_or_else(|| StorageError::AccountNotRegistered(account_id.clone()).panic())
+    }
+
+    /// Ⓝ required to cover the registration and the extra bytes of an account.
+    fn required_balance(&self, account: &SharedStorageAccount) -> Balance {
+        Balance::from(self.registration_bytes() + account.extra_bytes) * env::storage_byte_cost()
+    }
+
+    /// Charges the account for the change of the storage usage since `initial_storage_usage`,
+    /// i.e. the storage used or released by components on behalf of the account. Panics if the
+    /// account doesn't have enough `available` balance to cover the increase.
+    ///
+    /// ```ignore
+    /// let initial_storage_usage = env::storage_usage();
+    /// let token = self.tokens.internal_mint(token_id, owner_id.clone(), Some(metadata));
+    /// self.storage.internal_charge_storage(&owner_id, initial_storage_usage);
+    /// ```
+    pub fn internal_charge_storage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let mut account = self.internal_unwrap_account(account_id);
+        let storage_usage = env::storage_usage();
+        if storage_usage >= initial_storage_usage {
+            account.extra_bytes += storage_usage - initial_storage_usage;
+            if self.required_balance(&account) > account.deposit {
+                env::panic_str(
+                    format!("The account {} doesn't have enough storage balance", account_id)
+                        .as_str(),
+                );
+            }
+        } else {
+            account.extra_bytes =
+                account.extra_bytes.saturating_sub(initial_storage_usage - storage_usage);
+        }
+        self.accounts.insert(account_id, &account);
+    }
+
+    /// Internal method that returns the Account ID and the storage deposit in case the account was
+    /// unregistered. Panics if components still use storage on behalf of the account, since their
+    /// state has to be released by the contract first.
+    pub fn internal_storage_unregister(
+        &mut self,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let closed = self.internal_storage_close(force);
+        if let Some((account_id, deposit)) = &closed {
+            Promise::new(account_id.clone()).transfer(*deposit);
+        }
+        closed
+    }
+
+    /// Like [`internal_storage_unregister`](Self::internal_storage_unregister), but leaves the
+    /// refund of the storage deposit to the caller, e.g. to send it through a
+    /// [`RefundQueue`](crate::refund_queue::RefundQueue).
+    pub fn internal_storage_close(&mut self, force: Option<bool>) -> Option<(AccountId, Balance)> {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        if let Some(account) = self.accounts.get(&account_id) {
+            if account.extra_bytes > 0 {
+                env::panic_str(if force.unwrap_or(false) {
+                    "Force unregistration is not supported"
+                } else {
+                    "Can't unregister the account with storage in use"
+                })
+            }
+            self.accounts.remove(&account_id);
+            Some((account_id, account.deposit))
+        } else {
+            log!("The account {} is not registered", &account_id);
+            None
+        }
+    }
+
+    /// Withdraws `amount`, by default the whole available balance, from the storage deposit of
+    /// the predecessor and returns the withdrawn amount, leaving its transfer to the caller.
+    /// Requires 1 yoctoⓃ.
+    pub fn internal_storage_withdraw(&mut self, amount: Option<U128>) -> Balance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        let available = account.deposit.saturating_sub(self.required_balance(&account));
+        let amount = amount.map_or(available, |amount| amount.0);
+        if amount > available {
+            StorageError::InsufficientAvailableBalance.panic();
+        }
+        if amount > 0 {
+            account.deposit -= amount;
+            self.accounts.insert(&account_id, &account);
+        }
+        amount
+    }
+
+    fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        self.accounts.get(account_id).map(|account| StorageBalance {
+            total: account.deposit.into(),
+            available: account.deposit.saturating_sub(self.required_balance(&account)).into(),
+        })
+    }
+}
+
+impl StorageManagement for SharedStorageAccounts {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount: Balance = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let mut refund = 0;
+        if let Some(mut account) = self.accounts.get(&account_id) {
+            if registration_only {
+                log!("The account is already registered, refunding the deposit");
+                refund = amount;
+            } else {
+                account.deposit += amount;
+                self.accounts.insert(&account_id, &account);
+            }
+        } else {
+            let min_balance = self.storage_balance_bounds().min.0;
+            assert_min_deposit(min_balance);
+            let deposit = if registration_only { min_balance } else { amount };
+            refund = amount - deposit;
+            self.accounts.insert(&account_id, &SharedStorageAccount { deposit, extra_bytes: 0 });
+        }
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        let amount = self.internal_storage_withdraw(amount);
+        let account_id = env::predecessor_account_id();
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.internal_storage_unregister(force).is_some()
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let required_storage_balance =
+            Balance::from(self.registration_bytes()) * env::storage_byte_cost();
+        StorageBalanceBounds { min: required_storage_balance.into(), max: None }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.internal_storage_balance_of(&account_id)
+    }
+}
+
+/// Exposes the NEP-145 interface of a [`SharedStorageAccounts`] field.
+/// Takes name of the Contract struct, the inner field for the storage accounts and optional
+/// method names to call when an account was registered, to register it with the components,
+/// and when an account was closed.
+///
+/// With `refunds = <field>` after the storage accounts, the Ⓝ withdrawn with `storage_withdraw`
+/// and `storage_unregister` is sent through the
+/// [`RefundQueue`](crate::refund_queue::RefundQueue) field, so that it's credited back if the
+/// transfer fails. The contract must also use [`impl_refund_queue`](crate::impl_refund_queue).
+#[macro_export]
+macro_rules! impl_shared_storage_management {
+    ($contract: ident, $storage: ident, refunds = $refunds: ident
+        $(, $on_account_registered_fn:ident $(, $on_account_closed_fn:ident)?)?) => {
+        $crate::impl_shared_storage_management!(
+            @impl $contract, $storage, [$refunds],
+            [$($on_account_registered_fn)?], [$($($on_account_closed_fn)?)?]
+        );
+    };
+    ($contract: ident, $storage: ident
+        $(, $on_account_registered_fn:ident $(, $on_account_closed_fn:ident)?)?) => {
+        $crate::impl_shared_storage_management!(
+            @impl $contract, $storage, [],
+            [$($on_account_registered_fn)?], [$($($on_account_closed_fn)?)?]
+        );
+    };
+    (@refund $self: ident, [], $account_id: expr, $amount: expr) => {
+        near_sdk::Promise::new($account_id).transfer($amount);
+    };
+    (@refund $self: ident, [$refunds: ident], $account_id: expr, $amount: expr) => {
+        $self.$refunds.transfer($account_id, $amount);
+    };
+    (@impl $contract: ident, $storage: ident, [$($refunds: ident)?],
+        [$($on_account_registered_fn:ident)?], [$($on_account_closed_fn:ident)?]) => {
+        use $crate::storage_management::{
+            StorageManagement, StorageBalance, StorageBalanceBounds
+        };
+
+        #[near_bindgen]
+        impl StorageManagement for $contract {
+            #[payable]
+            fn storage_deposit(
+                &mut self,
+                account_id: Option<AccountId>,
+                registration_only: Option<bool>,
+            ) -> StorageBalance {
+                let account_id = account_id.unwrap_or_else(near_sdk::env::predecessor_account_id);
+                #[allow(unused_variables)]
+                let is_new = !self.$storage.is_registered(&account_id);
+                let storage_balance =
+                    self.$storage.storage_deposit(Some(account_id.clone()), registration_only);
+                $(if is_new {
+                    self.$on_account_registered_fn(account_id);
+                })?
+                storage_balance
+            }
+
+            #[payable]
+            fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+                let amount = self.$storage.internal_storage_withdraw(amount);
+                let account_id = near_sdk::env::predecessor_account_id();
+                if amount > 0 {
+                    $crate::impl_shared_storage_management!(
+                        @refund self, [$($refunds)?], account_id.clone(), amount
+                    );
+                }
+                self.$storage.storage_balance_of(account_id).unwrap()
+            }
+
+            #[payable]
+            fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+                #[allow(unused_variables)]
+                if let Some((account_id, balance)) = self.$storage.internal_storage_close(force) {
+                    $crate::impl_shared_storage_management!(
+                        @refund self, [$($refunds)?], account_id.clone(), balance
+                    );
+                    $(self.$on_account_closed_fn(account_id, balance);)?
+                    true
+                } else {
+                    false
+                }
+            }
+
+            fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+                self.$storage.storage_balance_bounds()
+            }
+
+            fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+                self.$storage.storage_balance_of(account_id)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const COMPONENT_BYTES: StorageUsage = 100;
+
+    fn setup(attached_deposit: Balance) -> SharedStorageAccounts {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(attached_deposit)
+            .build());
+        let mut storage = SharedStorageAccounts::new(b"s".to_vec());
+        storage.register_component("ft", COMPONENT_BYTES);
+        storage.register_component("nft", COMPONENT_BYTES);
+        storage
+    }
+
+    fn min_balance(storage: &SharedStorageAccounts) -> Balance {
+        storage.storage_balance_bounds().min.0
+    }
+
+    #[test]
+    fn one_deposit_covers_all_components() {
+        let mut storage = setup(0);
+        assert_eq!(storage.registration_bytes(), storage.account_storage_usage + 200);
+        let min = min_balance(&storage);
+        let extra = 500 * env::storage_byte_cost();
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(min + extra)
+            .build());
+        let balance = storage.storage_deposit(None, None);
+        assert_eq!(balance.total.0, min + extra);
+        assert_eq!(balance.available.0, extra);
+
+        // A component uses storage on behalf of the account.
+        let initial_storage_usage = env::storage_usage();
+        env::storage_write(b"component", &[0; 21]);
+        storage.internal_charge_storage(&accounts(0), initial_storage_usage);
+        let used = env::storage_usage() - initial_storage_usage;
+        let balance = storage.storage_balance_of(accounts(0)).unwrap();
+        assert_eq!(balance.available.0, extra - Balance::from(used) * env::storage_byte_cost());
+
+        // And releases it.
+        let initial_storage_usage = env::storage_usage();
+        env::storage_remove(b"component");
+        storage.internal_charge_storage(&accounts(0), initial_storage_usage);
+        assert_eq!(storage.storage_balance_of(accounts(0)).unwrap().available.0, extra);
+    }
+
+    #[test]
+    fn registration_only_refunds_excess() {
+        let mut storage = setup(0);
+        let min = min_balance(&storage);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(min + 10)
+            .build());
+        let balance = storage.storage_deposit(Some(accounts(1)), Some(true));
+        assert_eq!(balance.total.0, min);
+        assert_eq!(balance.available.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't have enough storage balance")]
+    fn charge_more_than_available() {
+        let mut storage = setup(0);
+        let min = min_balance(&storage);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(min)
+            .build());
+        storage.storage_deposit(None, None);
+        let initial_storage_usage = env::storage_usage();
+        env::storage_write(b"component", b"value");
+        storage.internal_charge_storage(&accounts(0), initial_storage_usage);
+    }
+
+    #[test]
+    fn withdraw_and_unregister() {
+        let mut storage = setup(0);
+        let min = min_balance(&storage);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(min + 100)
+            .build());
+        storage.storage_deposit(None, None);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        let balance = storage.storage_withdraw(None);
+        assert_eq!(balance.total.0, min);
+        assert_eq!(balance.available.0, 0);
+        assert_eq!(storage.internal_storage_unregister(None), Some((accounts(0), min)));
+        assert!(!storage.is_registered(&accounts(0)));
+        assert_eq!(storage.internal_storage_unregister(None), None);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::fungible_token::FungibleToken;
+use crate::non_fungible_token::metadata::TokenMetadata;
+use crate::non_fungible_token::NonFungibleToken;
+use near_sdk::{AccountId, Balance, IntoStorageKey};
+
+/// Returns the `index`-th account of the fixtures, `account-<index>.near`.
+pub fn fixture_account(index: usize) -> AccountId {
+    AccountId::new_unchecked(format!("account-{}.near", index))
+}
+
+/// Builder of a [`FungibleToken`] with many registered accounts holding balances, for tests and
+/// benchmarks. The accounts are the [`fixture_account`]s, so the state only depends on the
+/// parameters of the builder.
+///
+/// ```
+/// use near_contract_standards::test_fixtures::{fixture_account, FtFixture};
+///
+/// let token = FtFixture::with_accounts(100).with_balances(|index| 10 * index as u128).build(b"t");
+/// assert_eq!(token.internal_unwrap_balance_of(&fixture_account(7)), 70);
+/// ```
+pub struct FtFixture {
+    accounts: usize,
+    balance: Box<dyn Fn(usize) -> Balance>,
+}
+
+impl FtFixture {
+    /// Registers the first `accounts` fixture accounts, with no balance by default.
+    pub fn with_accounts(accounts: usize) -> Self {
+        Self { accounts, balance: Box::new(|_| 0) }
+    }
+
+    /// Gives the balance computed from its index to each account.
+    pub fn with_balances<F>(mut self, balance: F) -> Self
+    where
+        F: Fn(usize) -> Balance + 'static,
+    {
+        self.balance = Box::new(balance);
+        self
+    }
+
+    /// Returns the accounts registered by the fixture.
+    pub fn accounts(&self) -> Vec<AccountId> {
+        (0..self.accounts).map(fixture_account).collect()
+    }
+
+    /// Creates the token under `prefix` and populates it. No events are logged.
+    pub fn build<S>(&self, prefix: S) -> FungibleToken
+    where
+        S: IntoStorageKey,
+    {
+        let mut token = FungibleToken::new(prefix);
+        for index in 0..self.accounts {
+            let account_id = fixture_account(index);
+            token.internal_register_account(&account_id);
+            let balance = (self.balance)(index);
+            if balance > 0 {
+                token.internal_deposit(&account_id, balance);
+            }
+        }
+        token
+    }
+}
+
+/// Builder of a [`NonFungibleToken`] with the metadata, enumeration and approval extensions and
+/// many minted tokens, for tests and benchmarks. Tokens have the ids `"0"`, `"1"`, ... and are
+/// owned in turn by the first fixture accounts, one by default.
+///
+/// ```
+/// use near_contract_standards::test_fixtures::{fixture_account, NftFixture};
+///
+/// let token = NftFixture::with_tokens(50).with_owners(5).build(b"n", fixture_account(0));
+/// assert_eq!(token.owner_by_id.get(&"7".to_string()), Some(fixture_account(2)));
+/// ```
+pub struct NftFixture {
+    tokens: usize,
+    owners: usize,
+}
+
+impl NftFixture {
+    /// Mints `tokens` tokens.
+    pub fn with_tokens(tokens: usize) -> Self {
+        Self { tokens, owners: 1 }
+    }
+
+    /// Spreads the tokens over the first `owners` fixture accounts, token `i` being owned by
+    /// account `i % owners`. Panics if `owners` is zero.
+    pub fn with_owners(mut self, owners: usize) -> Self {
+        assert!(owners > 0, "A fixture needs at least one owner");
+        self.owners = owners;
+        self
+    }
+
+    /// Returns the metadata of the `index`-th token.
+    pub fn metadata(index: usize) -> TokenMetadata {
+        TokenMetadata {
+            title: Some(format!("Token #{}", index)),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: Some(1),
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    /// Creates the token of `owner_id` under `prefix` and mints the tokens. No events are logged
+    /// and no storage is charged.
+    pub fn build<S>(&self, prefix: S, owner_id: AccountId) -> NonFungibleToken
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        let mut token = NonFungibleToken::new(
+            [prefix.as_slice(), b"o"].concat(),
+            owner_id,
+            Some([prefix.as_slice(), b"m"].concat()),
+            Some([prefix.as_slice(), b"e"].concat()),
+            Some([prefix.as_slice(), b"a"].concat()),
+        );
+        for index in 0..self.tokens {
+            token.internal_mint_with_refund(
+                index.to_string(),
+                fixture_account(index % self.owners),
+                Some(Self::metadata(index)),
+                None,
+            );
+        }
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_fungible_token::enumeration::NonFungibleTokenEnumeration;
+    use near_sdk::json_types::U128;
+
+    #[test]
+    fn fixtures_are_deterministic() {
+        let fixture = FtFixture::with_accounts(3).with_balances(|index| 100 + index as Balance);
+        let token = fixture.build(b"t");
+        assert_eq!(token.total_supply, 303);
+        assert_eq!(fixture.accounts()[2], "account-2.near".parse::<AccountId>().unwrap());
+        assert_eq!(token.internal_unwrap_balance_of(&fixture_account(1)), 101);
+
+        let token = NftFixture::with_tokens(10).with_owners(3).build(b"n", fixture_account(0));
+        assert_eq!(token.nft_total_supply(), U128(10));
+        assert_eq!(token.nft_supply_for_owner(fixture_account(0)), U128(4));
+        assert_eq!(token.nft_supply_for_owner(fixture_account(2)), U128(3));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::fungible_token::FungibleToken;
+use crate::non_fungible_token::{NonFungibleToken, Token, TokenId};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    env, ext_contract, AccountId, Balance, Duration, Gas, IntoStorageKey, Promise, PromiseError,
+    Timestamp, TokenGate, TokenRequirement,
+};
+
+/// Gas attached to the view call querying the holdings of an account.
+pub const GAS_FOR_TOKEN_GATE_QUERY: Gas = Gas(5_000_000_000_000);
+/// Gas attached to the callback caching the holdings, by
+/// [`impl_token_gate_refresh`](crate::impl_token_gate_refresh).
+pub const GAS_FOR_TOKEN_GATE_RESOLVE: Gas = Gas(5_000_000_000_000);
+
+const NO_DEPOSIT: Balance = 0;
+
+#[ext_contract(ext_token_holdings)]
+trait TokenHoldings {
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token>;
+}
+
+impl TokenGate for FungibleToken {
+    /// Checks the balance of the account in the token. Panics if a token id is required.
+    fn holds(&self, account_id: &AccountId, requirement: &TokenRequirement) -> bool {
+        match requirement {
+            TokenRequirement::MinBalance(amount) => {
+                self.accounts.get(account_id).unwrap_or(0) >= *amount
+            }
+            TokenRequirement::Token(_) => {
+                env::panic_str("Fungible tokens can only require a minimum balance")
+            }
+        }
+    }
+}
+
+impl TokenGate for NonFungibleToken {
+    /// Checks the owner of the token, or the number of tokens of the account. Panics if a number
+    /// of tokens is required without the enumeration extension.
+    fn holds(&self, account_id: &AccountId, requirement: &TokenRequirement) -> bool {
+        match requirement {
+            TokenRequirement::MinBalance(amount) => {
+                let tokens_per_owner = self.tokens_per_owner.as_ref().unwrap_or_else(|| {
+                    env::panic_str(
+                        "Could not find tokens_per_owner when calling a method on the \
+                        enumeration standard.",
+                    )
+                });
+                let supply = tokens_per_owner
+                    .get(account_id)
+                    .map(|tokens| Balance::from(tokens.len()))
+                    .unwrap_or(0);
+                supply >= *amount
+            }
+            TokenRequirement::Token(token_id) => {
+                self.owner_by_id.get(token_id).as_ref() == Some(account_id)
+            }
+        }
+    }
+}
+
+/// Standard of the token of a [`CachedTokenGate`].
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStandard {
+    /// NEP-141, queried with `ft_balance_of`.
+    FungibleToken,
+    /// NEP-171, queried with `nft_token` for a token id, and `nft_supply_for_owner` of the
+    /// enumeration extension for a number of tokens.
+    NonFungibleToken,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct CachedHoldings {
+    amount: Balance,
+    cached_at: Timestamp,
+}
+
+/// [`TokenGate`] of a token of another contract, e.g. a membership NFT, checked against the
+/// holdings of the caller as last queried, since a method can't wait for a cross-contract call
+/// before executing.
+///
+/// The holdings of an account are queried with [`query`](Self::query) and cached by
+/// [`resolve`](Self::resolve) in the callback for `cache_ttl` nanoseconds.
+/// [`impl_token_gate_refresh`](crate::impl_token_gate_refresh) generates the
+/// `refresh_token_gate` method doing both, which members call before using a method guarded with
+/// `#[requires_token(contract = "field")]`. The guard panics if the holdings of the caller aren't
+/// cached or expired, so a member who sold their token loses access at most `cache_ttl` later.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CachedTokenGate {
+    pub token_contract_id: AccountId,
+    pub standard: TokenStandard,
+    pub cache_ttl: Duration,
+    cache: LookupMap<(AccountId, Option<TokenId>), CachedHoldings>,
+}
+
+impl CachedTokenGate {
+    pub fn new<S>(
+        prefix: S,
+        token_contract_id: AccountId,
+        standard: TokenStandard,
+        cache_ttl: Duration,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { token_contract_id, standard, cache_ttl, cache: LookupMap::new(prefix) }
+    }
+
+    /// Returns the cached balance of the account, or whether it owns the token if `token_id` is
+    /// given as 1 or 0, or `None` if it isn't cached or expired.
+    pub fn cached(&self, account_id: &AccountId, token_id: Option<&TokenId>) -> Option<Balance> {
+        self.cache
+            .get(&(account_id.clone(), token_id.cloned()))
+            .filter(|cached| {
+                env::block_timestamp() <= cached.cached_at.saturating_add(self.cache_ttl)
+            })
+            .map(|cached| cached.amount)
+    }
+
+    /// Queries the balance of the account, or the owner of the token if `token_id` is given. The
+    /// result is cached with [`resolve`](Self::resolve) in the callback. Panics if a token id is
+    /// given for a fungible token.
+    pub fn query(&self, account_id: AccountId, token_id: Option<TokenId>) -> Promise {
+        let contract_id = self.token_contract_id.clone();
+        match (self.standard, token_id) {
+            (TokenStandard::FungibleToken, None) => ext_token_holdings::ft_balance_of(
+                account_id,
+                contract_id,
+                NO_DEPOSIT,
+                GAS_FOR_TOKEN_GATE_QUERY,
+            ),
+            (TokenStandard::NonFungibleToken, None) => ext_token_holdings::nft_supply_for_owner(
+                account_id,
+                contract_id,
+                NO_DEPOSIT,
+                GAS_FOR_TOKEN_GATE_QUERY,
+            ),
+            (TokenStandard::NonFungibleToken, Some(token_id)) => ext_token_holdings::nft_token(
+                token_id,
+                contract_id,
+                NO_DEPOSIT,
+                GAS_FOR_TOKEN_GATE_QUERY,
+            ),
+            (TokenStandard::FungibleToken, Some(_)) => {
+                env::panic_str("Fungible tokens can only require a minimum balance")
+            }
+        }
+    }
+
+    /// Caches the result of [`query`](Self::query), read from the promise result at
+    /// `result_idx`, and returns the cached amount. Failed calls aren't cached.
+    pub fn resolve(
+        &mut self,
+        account_id: &AccountId,
+        token_id: Option<TokenId>,
+        result_idx: u64,
+    ) -> Result<Balance, PromiseError> {
+        let amount = match &token_id {
+            None => env::promise_result_json::<U128>(result_idx)?.0,
+            Some(_) => {
+                let token: Option<Token> = env::promise_result_json(result_idx)?;
+                Balance::from(token.map_or(false, |token| &token.owner_id == account_id))
+            }
+        };
+        self.cache.insert(
+            &(account_id.clone(), token_id),
+            &CachedHoldings { amount, cached_at: env::block_timestamp() },
+        );
+        Ok(amount)
+    }
+
+    /// Removes the cached holdings of the account.
+    pub fn invalidate(&mut self, account_id: &AccountId, token_id: Option<&TokenId>) {
+        self.cache.remove(&(account_id.clone(), token_id.cloned()));
+    }
+}
+
+impl TokenGate for CachedTokenGate {
+    /// Checks the cached holdings of the account. Panics if they aren't cached or expired.
+    fn holds(&self, account_id: &AccountId, requirement: &TokenRequirement) -> bool {
+        let (token_id, min_amount) = match requirement {
+            TokenRequirement::MinBalance(amount) => (None, *amount),
+            TokenRequirement::Token(token_id) => (Some(token_id), 1),
+        };
+        let amount = self.cached(account_id, token_id).unwrap_or_else(|| {
+            env::panic_str("The token holdings of the caller are unknown, call refresh_token_gate")
+        });
+        amount >= min_amount
+    }
+}
+
+/// Generates the `refresh_token_gate(account_id, token_id)` method, which queries the holdings
+/// of the account with a [`CachedTokenGate`] field and caches them in a private
+/// `on_token_gate_refresh` callback, returning the cached amount.
+/// Takes name of the Contract struct and the inner field for the gate.
+#[macro_export]
+macro_rules! impl_token_gate_refresh {
+    ($contract: ident, $gate: ident) => {
+        #[near_bindgen]
+        impl $contract {
+            pub fn refresh_token_gate(
+                &mut self,
+                account_id: AccountId,
+                token_id: Option<String>,
+            ) -> near_sdk::Promise {
+                let args = near_sdk::serde_json::json!({
+                    "account_id": account_id,
+                    "token_id": token_id,
+                });
+                self.$gate.query(account_id, token_id).then(near_sdk::Promise::new(
+                    near_sdk::env::current_account_id(),
+                )
+                .function_call(
+                    "on_token_gate_refresh".to_string(),
+                    args.to_string().into_bytes(),
+                    0,
+                    $crate::token_gate::GAS_FOR_TOKEN_GATE_RESOLVE,
+                ))
+            }
+
+            #[private]
+            pub fn on_token_gate_refresh(
+                &mut self,
+                account_id: AccountId,
+                token_id: Option<String>,
+            ) -> near_sdk::json_types::U128 {
+                match self.$gate.resolve(&account_id, token_id, 0) {
+                    Ok(amount) => amount.into(),
+                    Err(_) => near_sdk::env::panic_str("Failed to query the token holdings"),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    fn callback(timestamp: Timestamp, result: &[u8]) {
+        testing_env!(
+            VMContextBuilder::new().block_timestamp(timestamp).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(result.to_vec())],
+        );
+    }
+
+    fn call(timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    #[test]
+    fn cached_balance() {
+        let mut gate = CachedTokenGate::new(b"g", accounts(5), TokenStandard::FungibleToken, 100);
+        callback(10, b"\"150\"");
+        assert_eq!(gate.resolve(&accounts(0), None, 0), Ok(150));
+        call(110);
+        assert!(gate.holds(&accounts(0), &TokenRequirement::MinBalance(100)));
+        assert!(!gate.holds(&accounts(0), &TokenRequirement::MinBalance(151)));
+        near_sdk::utils::check_token_gate(&gate, TokenRequirement::MinBalance(150));
+        assert_eq!(gate.cached(&accounts(0), None), Some(150));
+        call(111);
+        assert_eq!(gate.cached(&accounts(0), None), None);
+    }
+
+    #[test]
+    fn cached_token_owner() {
+        let mut gate =
+            CachedTokenGate::new(b"g", accounts(5), TokenStandard::NonFungibleToken, 100);
+        callback(0, br#"{"token_id":"1","owner_id":"alice"}"#);
+        assert_eq!(gate.resolve(&accounts(0), Some("1".to_string()), 0), Ok(1));
+        callback(0, b"null");
+        assert_eq!(gate.resolve(&accounts(0), Some("2".to_string()), 0), Ok(0));
+        call(0);
+        assert!(gate.holds(&accounts(0), &TokenRequirement::Token("1".to_string())));
+        assert!(!gate.holds(&accounts(0), &TokenRequirement::Token("2".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "The caller must hold at least 1 tokens")]
+    fn check_gate_without_tokens() {
+        let mut gate = CachedTokenGate::new(b"g", accounts(5), TokenStandard::FungibleToken, 100);
+        callback(0, b"\"0\"");
+        gate.resolve(&accounts(0), None, 0).unwrap();
+        call(0);
+        near_sdk::utils::check_token_gate(&gate, TokenRequirement::MinBalance(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "The token holdings of the caller are unknown")]
+    fn check_gate_not_cached() {
+        let gate = CachedTokenGate::new(b"g", accounts(5), TokenStandard::FungibleToken, 100);
+        call(0);
+        near_sdk::utils::check_token_gate(&gate, TokenRequirement::MinBalance(1));
+    }
+}
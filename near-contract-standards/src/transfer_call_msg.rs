@@ -0,0 +1,152 @@
+use near_sdk::env;
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, Value};
+
+/// Common envelope for the `msg` of `ft_transfer_call`, `nft_transfer_call` and
+/// `mt_transfer_call`.
+///
+/// The receiver dispatches on `action` and deserializes `params` into the type of that action.
+/// A protocol which forwards the tokens to another contract passes `nested` on as the `msg` of
+/// the next transfer call, so messages can be routed through several contracts:
+///
+/// ```json
+/// {"action":"swap","params":{"min_out":"10"},"nested":{"action":"deposit","params":{}}}
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferCallMsg {
+    pub action: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested: Option<Box<TransferCallMsg>>,
+}
+
+/// Parameters of one action of a [`TransferCallMsg`]. Usually implemented with
+/// [`impl_transfer_call_action`](crate::impl_transfer_call_action).
+pub trait TransferCallAction: Serialize + DeserializeOwned {
+    /// Value of [`TransferCallMsg::action`] for these parameters.
+    const ACTION: &'static str;
+}
+
+impl TransferCallMsg {
+    pub fn new<A: TransferCallAction>(params: &A) -> Self {
+        Self {
+            action: A::ACTION.to_string(),
+            params: serde_json::to_value(params)
+                .unwrap_or_else(|_| env::panic_str("Failed to serialize the message params")),
+            nested: None,
+        }
+    }
+
+    /// Sets the message to be forwarded with the next transfer call.
+    pub fn with_nested(mut self, nested: TransferCallMsg) -> Self {
+        self.nested = Some(Box::new(nested));
+        self
+    }
+
+    /// Parses the `msg` argument of a transfer call receiver.
+    pub fn parse(msg: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(msg)
+    }
+
+    /// Returns the parameters if this message is for the action `A` and they are valid.
+    pub fn params<A: TransferCallAction>(&self) -> Option<A> {
+        if self.action != A::ACTION {
+            return None;
+        }
+        A::deserialize(&self.params).ok()
+    }
+
+    /// Same as [`params`](Self::params), but panics if the message is not for the action `A`.
+    pub fn expect_params<A: TransferCallAction>(&self) -> A {
+        self.params().unwrap_or_else(|| {
+            env::panic_str(format!("Invalid params for the action {}", A::ACTION).as_str())
+        })
+    }
+
+    /// Returns the nested message serialized to be passed on as `msg`, if any.
+    pub fn nested_msg(&self) -> Option<String> {
+        self.nested.as_ref().map(|nested| nested.to_msg())
+    }
+
+    /// Serializes the message to be passed as `msg` of a transfer call.
+    pub fn to_msg(&self) -> String {
+        serde_json::to_string(self)
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize the message"))
+    }
+}
+
+/// Implements [`TransferCallAction`](crate::transfer_call_msg::TransferCallAction) for a
+/// parameters type with the given action name.
+///
+/// ```
+/// use near_contract_standards::impl_transfer_call_action;
+/// use near_contract_standards::transfer_call_msg::TransferCallMsg;
+/// use near_sdk::serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// #[serde(crate = "near_sdk::serde")]
+/// struct Swap {
+///     min_out: u64,
+/// }
+/// impl_transfer_call_action!(Swap, "swap");
+///
+/// let msg = TransferCallMsg::parse(r#"{"action":"swap","params":{"min_out":10}}"#).unwrap();
+/// assert_eq!(msg.params::<Swap>(), Some(Swap { min_out: 10 }));
+/// ```
+#[macro_export]
+macro_rules! impl_transfer_call_action {
+    ($params: ty, $action: expr) => {
+        impl $crate::transfer_call_msg::TransferCallAction for $params {
+            const ACTION: &'static str = $action;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(crate = "near_sdk::serde")]
+    struct Deposit {
+        pool_id: u64,
+    }
+    impl_transfer_call_action!(Deposit, "deposit");
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(crate = "near_sdk::serde")]
+    struct Swap {
+        min_out: String,
+    }
+    impl_transfer_call_action!(Swap, "swap");
+
+    #[test]
+    fn nested_roundtrip() {
+        let msg = TransferCallMsg::new(&Swap { min_out: "10".to_string() })
+            .with_nested(TransferCallMsg::new(&Deposit { pool_id: 3 }));
+        let json = msg.to_msg();
+        assert_eq!(
+            json,
+            r#"{"action":"swap","params":{"min_out":"10"},"nested":{"action":"deposit","params":{"pool_id":3}}}"#
+        );
+
+        let parsed = TransferCallMsg::parse(&json).unwrap();
+        assert_eq!(parsed, msg);
+        assert_eq!(parsed.params::<Swap>(), Some(Swap { min_out: "10".to_string() }));
+        assert_eq!(parsed.params::<Deposit>(), None);
+
+        let nested = TransferCallMsg::parse(&parsed.nested_msg().unwrap()).unwrap();
+        assert_eq!(nested.expect_params::<Deposit>(), Deposit { pool_id: 3 });
+        assert_eq!(nested.nested_msg(), None);
+    }
+
+    #[test]
+    fn missing_params() {
+        let msg = TransferCallMsg::parse(r#"{"action":"deposit"}"#).unwrap();
+        assert_eq!(msg.params, Value::Null);
+        assert_eq!(msg.params::<Deposit>(), None);
+    }
+}
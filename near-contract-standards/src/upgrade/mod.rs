@@ -1,6 +1,6 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U64;
-use near_sdk::{env, require, AccountId, Duration, Promise, Timestamp};
+use near_sdk::{env, require, AccountId, Duration, Gas, Promise, Timestamp};
 
 type WrappedDuration = U64;
 
@@ -36,6 +36,24 @@ impl Upgrade {
     pub fn new(owner: AccountId, staging_duration: Duration) -> Self {
         Self { owner, staging_duration, staging_timestamp: 0 }
     }
+
+    /// Deploys the staged code like [`deploy_code`](Upgradable::deploy_code) and calls
+    /// `migrate_method` of the new code without arguments in the same batch, so that the state
+    /// is migrated before any other call reaches the new code. The deploy is reverted if the
+    /// migration fails.
+    pub fn deploy_code_and_migrate(&mut self, migrate_method: &str, gas: Gas) -> Promise {
+        self.deploy_code().function_call(migrate_method.to_string(), vec![], 0, gas)
+    }
+
+    /// Returns the timestamp from which the staged code can be deployed, or `None` if no code is
+    /// staged.
+    pub fn pending_upgrade(&self) -> Option<Timestamp> {
+        if env::storage_has_key(b"upgrade") {
+            Some(self.staging_timestamp)
+        } else {
+            None
+        }
+    }
 }
 
 impl Ownable for Upgrade {
@@ -66,13 +84,10 @@ impl Upgradable for Upgrade {
     }
 
     fn deploy_code(&mut self) -> Promise {
-        if self.staging_timestamp < env::block_timestamp() {
+        if env::block_timestamp() < self.staging_timestamp {
             env::panic_str(
-                format!(
-                    "Deploy code too early: staging ends on {}",
-                    self.staging_timestamp + self.staging_duration
-                )
-                .as_str(),
+                format!("Deploy code too early: staging ends on {}", self.staging_timestamp)
+                    .as_str(),
             );
         }
         let code = env::storage_read(b"upgrade")
@@ -81,3 +96,126 @@ impl Upgradable for Upgrade {
         Promise::new(env::current_account_id()).deploy_contract(code)
     }
 }
+
+/// Reads the contract state written by the previous code as `V`, and converts it to the state of
+/// the new code, for the `#[migrate]` method called after an upgrade. `V` is usually an enum with
+/// a variant per layout the state had, or the previous state struct itself.
+///
+/// ```
+/// use near_contract_standards::upgrade::migrate_state;
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::near_bindgen;
+///
+/// #[derive(BorshDeserialize)]
+/// pub struct OldContract {
+///     counter: u32,
+/// }
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize, Default)]
+/// pub struct Contract {
+///     counter: u64,
+///     label: String,
+/// }
+///
+/// impl From<OldContract> for Contract {
+///     fn from(old: OldContract) -> Self {
+///         Self { counter: old.counter.into(), label: String::new() }
+///     }
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     #[migrate]
+///     pub fn migrate() -> Self {
+///         migrate_state::<OldContract, Self>()
+///     }
+/// }
+/// ```
+pub fn migrate_state<V, S>() -> S
+where
+    V: BorshDeserialize,
+    S: From<V>,
+{
+    env::state_read::<V>()
+        .unwrap_or_else(|| env::panic_str("The contract state doesn't exist"))
+        .into()
+}
+
+/// Exposes the [`Upgradable`] methods of an [`Upgrade`] field, with `deploy_code` calling
+/// `migrate_method` of the new code if it's given.
+/// Takes name of the Contract struct, the inner field for the upgrade and optional name of the
+/// migration method of the new code with its gas.
+#[macro_export]
+macro_rules! impl_upgradable {
+    ($contract: ident, $upgrade: ident $(, $migrate_method: literal, $gas: expr)?) => {
+        #[near_bindgen]
+        impl $crate::upgrade::Upgradable for $contract {
+            fn get_staging_duration(&self) -> near_sdk::json_types::U64 {
+                $crate::upgrade::Upgradable::get_staging_duration(&self.$upgrade)
+            }
+
+            fn stage_code(&mut self, code: Vec<u8>, timestamp: near_sdk::Timestamp) {
+                $crate::upgrade::Upgradable::stage_code(&mut self.$upgrade, code, timestamp)
+            }
+
+            fn deploy_code(&mut self) -> near_sdk::Promise {
+                #[allow(unused_variables)]
+                let migration: Option<(&str, near_sdk::Gas)> = None;
+                $(let migration = Some(($migrate_method, $gas));)?
+                match migration {
+                    Some((method, gas)) => self.$upgrade.deploy_code_and_migrate(method, gas),
+                    None => $crate::upgrade::Upgradable::deploy_code(&mut self.$upgrade),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const STAGING_DURATION: Duration = 1_000;
+
+    fn call(predecessor: usize, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(predecessor))
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    fn staged() -> Upgrade {
+        call(0, 0);
+        let mut upgrade = Upgrade::new(accounts(0), STAGING_DURATION);
+        upgrade.stage_code(vec![1, 2, 3], STAGING_DURATION + 1);
+        upgrade
+    }
+
+    #[test]
+    fn deploy_after_staging() {
+        let mut upgrade = staged();
+        assert_eq!(upgrade.pending_upgrade(), Some(STAGING_DURATION + 1));
+        call(1, STAGING_DURATION + 1);
+        upgrade.deploy_code_and_migrate("migrate", Gas(10_000_000_000_000));
+        assert_eq!(upgrade.pending_upgrade(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deploy code too early: staging ends on 1001")]
+    fn deploy_too_early() {
+        let mut upgrade = staged();
+        call(1, STAGING_DURATION);
+        upgrade.deploy_code();
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner must be predecessor")]
+    fn only_owner_stages() {
+        call(1, 0);
+        let mut upgrade = Upgrade::new(accounts(0), STAGING_DURATION);
+        upgrade.stage_code(vec![1, 2, 3], STAGING_DURATION + 1);
+    }
+}
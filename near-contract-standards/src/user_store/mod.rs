@@ -0,0 +1,243 @@
+use crate::storage_management::SharedStorageAccounts;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{env, AccountId, Balance, IntoStorageKey};
+
+/// Record persisted in its versioned form, an enum with a variant per layout the record ever had,
+/// like [`VAccount`](crate::fungible_token::account::VAccount).
+///
+/// Old variants are upgraded into the [`Current`](Self::Current) layout when they are read, and
+/// records are always written back in the current layout. Existing variants must never be
+/// reordered.
+pub trait VersionedRecord: BorshSerialize + BorshDeserialize + From<Self::Current> {
+    /// Latest layout of the record.
+    type Current: From<Self>;
+}
+
+/// One record per account, e.g. the profile of a user in a social contract, whose layout can
+/// evolve without migrating the state of the contract.
+///
+/// Records are stored as their [`VersionedRecord`] `V` and returned in the current layout, so
+/// records written by older versions of the contract are migrated lazily: in memory when they're
+/// read, and in storage when they're next written or explicitly [`migrate`](Self::migrate)d.
+///
+/// The storage used by a record is charged to the NEP-145 storage balance of its account in the
+/// [`SharedStorageAccounts`] of the contract on every write and released when the record is
+/// removed. [`internal_storage_unregister`](Self::internal_storage_unregister) removes the record
+/// of the account before unregistering it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct UserStore<V> {
+    pub records: LookupMap<AccountId, V>,
+}
+
+impl<V> UserStore<V>
+where
+    V: VersionedRecord,
+{
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { records: LookupMap::new(prefix) }
+    }
+
+    /// Returns the record of the account in the current layout, or `None` if it has no record.
+    pub fn get(&self, account_id: &AccountId) -> Option<V::Current> {
+        self.records.get(account_id).map(V::Current::from)
+    }
+
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.records.contains_key(account_id)
+    }
+
+    /// Writes the record of the account, charging the change of its storage to the storage
+    /// balance of the account. Panics if the account isn't registered in `storage` or doesn't have
+    /// enough available storage balance.
+    pub fn set(
+        &mut self,
+        storage: &mut SharedStorageAccounts,
+        account_id: &AccountId,
+        record: V::Current,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        self.records.insert(account_id, &V::from(record));
+        storage.internal_charge_storage(account_id, initial_storage_usage);
+    }
+
+    /// Updates the record of the account in the current layout and writes it back, charging its
+    /// storage like [`set`](Self::set). Panics if the account has no record.
+    pub fn update<F>(
+        &mut self,
+        storage: &mut SharedStorageAccounts,
+        account_id: &AccountId,
+        f: F,
+    ) -> V::Current
+    where
+        F: FnOnce(&mut V::Current),
+        V::Current: Clone,
+    {
+        let mut record = self.get(account_id).unwrap_or_else(|| {
+            env::panic_str(format!("The account {} has no record", account_id).as_str())
+        });
+        f(&mut record);
+        self.set(storage, account_id, record.clone());
+        record
+    }
+
+    /// Rewrites the record of the account in the current layout if it's stored in an older one,
+    /// charging the change of its storage. Returns `true` if the record was rewritten.
+    pub fn migrate(&mut self, storage: &mut SharedStorageAccounts, account_id: &AccountId) -> bool {
+        let record = match self.records.get(account_id) {
+            Some(record) => record,
+            None => return false,
+        };
+        let stored = record.try_to_vec().unwrap_or_else(|_| env::abort());
+        let current = V::from(V::Current::from(record));
+        if current.try_to_vec().unwrap_or_else(|_| env::abort()) == stored {
+            return false;
+        }
+        let initial_storage_usage = env::storage_usage();
+        self.records.insert(account_id, &current);
+        storage.internal_charge_storage(account_id, initial_storage_usage);
+        true
+    }
+
+    /// Removes the record of the account, releasing its storage in the storage balance of the
+    /// account. Returns the removed record in the current layout.
+    pub fn remove(
+        &mut self,
+        storage: &mut SharedStorageAccounts,
+        account_id: &AccountId,
+    ) -> Option<V::Current> {
+        let initial_storage_usage = env::storage_usage();
+        let record = self.records.remove(account_id)?;
+        storage.internal_charge_storage(account_id, initial_storage_usage);
+        Some(record.into())
+    }
+
+    /// Removes the record of the predecessor and unregisters it from `storage`, see
+    /// [`SharedStorageAccounts::internal_storage_unregister`]. Returns the Account ID and the
+    /// storage deposit in case the account was unregistered.
+    pub fn internal_storage_unregister(
+        &mut self,
+        storage: &mut SharedStorageAccounts,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let account_id = env::predecessor_account_id();
+        if storage.is_registered(&account_id) {
+            self.remove(storage, &account_id);
+        }
+        storage.internal_storage_unregister(force)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_management::StorageManagement;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct ProfileV1 {
+        name: String,
+    }
+
+    #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+    struct Profile {
+        name: String,
+        bio: Option<String>,
+    }
+
+    #[derive(BorshDeserialize, BorshSerialize)]
+    enum VProfile {
+        V1(ProfileV1),
+        Current(Profile),
+    }
+
+    impl From<VProfile> for Profile {
+        fn from(profile: VProfile) -> Self {
+            match profile {
+                VProfile::V1(ProfileV1 { name }) => Profile { name, bio: None },
+                VProfile::Current(profile) => profile,
+            }
+        }
+    }
+
+    impl From<Profile> for VProfile {
+        fn from(profile: Profile) -> Self {
+            VProfile::Current(profile)
+        }
+    }
+
+    impl VersionedRecord for VProfile {
+        type Current = Profile;
+    }
+
+    fn setup() -> (UserStore<VProfile>, SharedStorageAccounts) {
+        let mut storage = SharedStorageAccounts::new(b"s");
+        let deposit = storage.storage_balance_bounds().min.0 + 1000 * env::storage_byte_cost();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(deposit)
+            .build());
+        storage.storage_deposit(None, None);
+        (UserStore::new(b"u"), storage)
+    }
+
+    fn available(storage: &SharedStorageAccounts) -> Balance {
+        storage.storage_balance_of(accounts(0)).unwrap().available.0
+    }
+
+    #[test]
+    fn old_records_are_migrated_lazily() {
+        let (mut store, mut storage) = setup();
+        // A record written by the previous version of the contract.
+        store.records.insert(&accounts(0), &VProfile::V1(ProfileV1 { name: "alice".to_string() }));
+        let profile = Profile { name: "alice".to_string(), bio: None };
+        assert_eq!(store.get(&accounts(0)), Some(profile));
+
+        let initial_available = available(&storage);
+        assert!(store.migrate(&mut storage, &accounts(0)));
+        assert!(!store.migrate(&mut storage, &accounts(0)));
+        // The `Option` tag of `bio` is charged.
+        assert_eq!(available(&storage), initial_available - env::storage_byte_cost());
+
+        let profile = store.update(&mut storage, &accounts(0), |profile| {
+            profile.bio = Some("hi".to_string());
+        });
+        assert_eq!(store.get(&accounts(0)), Some(profile));
+    }
+
+    #[test]
+    fn writes_are_charged_and_removals_released() {
+        let (mut store, mut storage) = setup();
+        let initial_available = available(&storage);
+        let profile = Profile { name: "alice".to_string(), bio: Some("hi".to_string()) };
+        store.set(&mut storage, &accounts(0), profile.clone());
+        assert!(available(&storage) < initial_available);
+        assert_eq!(store.remove(&mut storage, &accounts(0)), Some(profile));
+        assert_eq!(available(&storage), initial_available);
+    }
+
+    #[test]
+    fn unregister_removes_the_record() {
+        let (mut store, mut storage) = setup();
+        let profile = Profile { name: "alice".to_string(), bio: None };
+        store.set(&mut storage, &accounts(0), profile);
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        assert!(store.internal_storage_unregister(&mut storage, None).is_some());
+        assert!(!store.contains(&accounts(0)));
+        assert!(!storage.is_registered(&accounts(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account bob has no record")]
+    fn update_missing_record() {
+        let (mut store, mut storage) = setup();
+        store.update(&mut storage, &accounts(1), |_| {});
+    }
+}
@@ -23,6 +23,10 @@ impl AttrSigInfo {
     ///   arg2: (u64, Vec<String>),
     /// }
     /// ```
+    /// With `#[zero_copy]`, the struct used for deserialization borrows from `env::input()` and
+    /// `&SUBTYPE` arguments are converted to `binding: &'nearinput SUBTYPE,`. `&str` arguments are
+    /// converted to `binding: Cow<'nearinput, str>,`, which borrows unless the JSON string has
+    /// escapes, like the nested JSON `msg` of `ft_transfer_call`.
     pub fn input_struct(&self, input_struct_type: InputStructType) -> TokenStream2 {
         let args: Vec<_> = self.input_args().collect();
         assert!(
@@ -53,21 +57,55 @@ impl AttrSigInfo {
                 }
             },
         };
+        let is_borrowing = matches!(input_struct_type, InputStructType::Deserialization)
+            && args.iter().any(|arg| self.is_borrowed_arg(arg));
         let mut fields = TokenStream2::new();
         for arg in args {
             let ArgInfo { ty, ident, .. } = &arg;
-            fields.extend(quote! {
-                #ident: #ty,
-            });
+            if is_borrowing && self.is_borrowed_str_arg(arg) {
+                fields.extend(quote! {
+                    #[serde(borrow)]
+                    #ident: ::std::borrow::Cow<'nearinput, str>,
+                });
+            } else if is_borrowing && self.is_borrowed_arg(arg) {
+                fields.extend(quote! {
+                    #ident: &'nearinput #ty,
+                });
+            } else {
+                fields.extend(quote! {
+                    #ident: #ty,
+                });
+            }
         }
+        let lifetime = if is_borrowing {
+            quote! { <'nearinput> }
+        } else {
+            TokenStream2::new()
+        };
         quote! {
             #attribute
-            struct Input {
+            struct Input #lifetime {
                 #fields
             }
         }
     }
 
+    /// Whether the argument is deserialized as a reference into `env::input()`, which is the case
+    /// for `&SUBTYPE` arguments of `#[zero_copy]` methods.
+    pub fn is_borrowed_arg(&self, arg: &ArgInfo) -> bool {
+        self.is_zero_copy
+            && matches!(arg.bindgen_ty, BindgenArgType::Regular)
+            && arg.reference.is_some()
+            && arg.mutability.is_none()
+    }
+
+    /// Whether the argument is a `&str` deserialized as a string borrowed from `env::input()`
+    /// when possible.
+    pub fn is_borrowed_str_arg(&self, arg: &ArgInfo) -> bool {
+        self.is_borrowed_arg(arg)
+            && matches!(&arg.ty, syn::Type::Path(ty) if ty.path.is_ident("str"))
+    }
+
     /// Create pattern that decomposes input struct using correct mutability modifiers.
     /// # Example:
     /// ```ignore
@@ -137,9 +175,19 @@ impl AttrSigInfo {
         let mut result = TokenStream2::new();
         for arg in &self.args {
             let ArgInfo { reference, mutability, ident, .. } = &arg;
-            result.extend(quote! {
-                #reference #mutability #ident,
-            });
+            if self.is_borrowed_str_arg(arg) {
+                result.extend(quote! {
+                    &#ident,
+                });
+            } else if self.is_borrowed_arg(arg) {
+                result.extend(quote! {
+                    #ident,
+                });
+            } else {
+                result.extend(quote! {
+                    #reference #mutability #ident,
+                });
+            }
         }
         result
     }
@@ -1,45 +1,21 @@
 use crate::core_impl::info_extractor::{
-    AttrSigInfo, ImplItemMethodInfo, InputStructType, MethodType, SerializerType,
+    ArgInfo, AttrSigInfo, ImplItemMethodInfo, InputStructType, MethodType, SerializerType,
+    TokenRequirementAttr,
 };
 use crate::core_impl::utils;
+use inflector::Inflector;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{ReturnType, Signature};
 
 impl ImplItemMethodInfo {
     /// Generate wrapper method for the given method of the contract.
     pub fn method_wrapper(&self) -> TokenStream2 {
         let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
-        // Args provided by `env::input()`.
-        let has_input_args = attr_signature_info.input_args().next().is_some();
-
         let panic_hook = quote! {
             near_sdk::env::setup_panic_hook();
         };
-        let arg_struct;
-        let arg_parsing;
-        if has_input_args {
-            arg_struct = attr_signature_info.input_struct(InputStructType::Deserialization);
-            let decomposition = attr_signature_info.decomposition_pattern();
-            let serializer_invocation = match attr_signature_info.input_serializer {
-                SerializerType::JSON => quote! {
-                    near_sdk::serde_json::from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
-                    ).expect("Failed to deserialize input from JSON.")
-                },
-                SerializerType::Borsh => quote! {
-                    near_sdk::borsh::BorshDeserialize::try_from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
-                    ).expect("Failed to deserialize input from Borsh.")
-                },
-            };
-            arg_parsing = quote! {
-                let #decomposition : Input = #serializer_invocation ;
-            };
-        } else {
-            arg_struct = TokenStream2::new();
-            arg_parsing = TokenStream2::new();
-        };
+        let (arg_struct, arg_parsing) = self.input_parsing();
 
         let callback_deser = attr_signature_info.callback_deserialization();
         let callback_vec_deser = attr_signature_info.callback_vec_deserialization();
@@ -53,11 +29,67 @@ impl ImplItemMethodInfo {
             result_serializer,
             method_type,
             is_payable,
-            is_private,
+            min_deposit,
             is_returns_result,
+            idempotent,
+            locks,
+            sunset,
+            requires_token,
+            pausable,
             ..
         } = attr_signature_info;
-        let deposit_check = if *is_payable || matches!(method_type, &MethodType::View) {
+        let sunset_check = if let Some(sunset) = sunset {
+            let ident_str = ident.to_string();
+            let after = sunset.after;
+            let message = match &sunset.message {
+                Some(message) => quote! { #message },
+                None => quote! { "" },
+            };
+            quote! {
+                near_sdk::utils::check_sunset(#ident_str, #after, #message);
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let token_gate_check = if let Some(requires_token) = requires_token {
+            let contract = &requires_token.contract;
+            let requirement = match &requires_token.requirement {
+                TokenRequirementAttr::MinBalance(min_balance) => quote! {
+                    near_sdk::TokenRequirement::MinBalance(#min_balance)
+                },
+                TokenRequirementAttr::TokenId(token_id) => quote! {
+                    near_sdk::TokenRequirement::Token((#token_id).to_string())
+                },
+            };
+            quote! {
+                near_sdk::utils::check_token_gate(&contract.#contract, #requirement);
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let pause_check = if let Some(pausable) = pausable {
+            let field = &pausable.field;
+            let feature = match &pausable.feature {
+                Some(feature) => feature.value(),
+                None => ident.to_string(),
+            };
+            quote! {
+                near_sdk::assert_not_paused(&contract.#field, #feature);
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let deposit_check = if let Some(min_deposit) = min_deposit {
+            let error = format!(
+                "Method {} requires a deposit of at least {} yoctoNEAR",
+                ident, min_deposit
+            );
+            quote! {
+                if near_sdk::env::attached_deposit() < #min_deposit {
+                    near_sdk::env::panic_str(#error);
+                }
+            }
+        } else if *is_payable || matches!(method_type, &MethodType::View) {
             // No check if the method is payable or a view method
             quote! {}
         } else {
@@ -69,16 +101,61 @@ impl ImplItemMethodInfo {
                 }
             }
         };
-        let is_private_check = if *is_private {
-            let error = format!("Method {} is private", ident);
+        let is_private_check = self.private_check(ident);
+        let lock_acquire = locks.iter().fold(TokenStream2::new(), |acc, lock| {
+            let format = &lock.format;
+            let args = &lock.args;
+            let intent_lock = match &lock.ttl_blocks {
+                Some(ttl_blocks) => quote! { near_sdk::IntentLock::with_ttl(#ttl_blocks) },
+                None => quote! { near_sdk::IntentLock::default() },
+            };
             quote! {
-                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
-                    near_sdk::env::panic_str(#error);
-                }
+                #acc
+                #intent_lock.lock(&format!(#format, #(#args),*));
             }
+        });
+        let idempotency_check;
+        let idempotency_record;
+        let idempotency_record_unit;
+        if let Some(idempotent) = idempotent {
+            let key_arg = &idempotent.key_arg;
+            let guard = match &idempotent.ttl {
+                Some(ttl) => quote! { near_sdk::Idempotency::with_ttl(#ttl) },
+                None => quote! { near_sdk::Idempotency::default() },
+            };
+            let method_name = ident.to_string();
+            let deposit_refund = if *is_payable {
+                // The deposit of a replayed call is not used, so it is returned to the caller.
+                quote! {
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::Promise::new(near_sdk::env::predecessor_account_id())
+                            .transfer(near_sdk::env::attached_deposit());
+                    }
+                }
+            } else {
+                TokenStream2::new()
+            };
+            idempotency_check = quote! {
+                let idempotency = #guard;
+                let idempotency_key =
+                    near_sdk::Idempotency::caller_request_id(#method_name, &#key_arg.to_string());
+                if let Some(result) = idempotency.cached_result(&idempotency_key) {
+                    #deposit_refund
+                    near_sdk::env::value_return(&result);
+                    return;
+                }
+            };
+            idempotency_record = quote! {
+                idempotency.record(&idempotency_key, &result);
+            };
+            idempotency_record_unit = quote! {
+                idempotency.record(&idempotency_key, &[]);
+            };
         } else {
-            quote! {}
-        };
+            idempotency_check = TokenStream2::new();
+            idempotency_record = TokenStream2::new();
+            idempotency_record_unit = TokenStream2::new();
+        }
         let body = if matches!(method_type, &MethodType::Init) {
             if matches!(returns, ReturnType::Default) {
                 return syn::Error::new(
@@ -114,6 +191,8 @@ impl ImplItemMethodInfo {
                 let mutability = &receiver.mutability;
                 contract_deser = quote! {
                     let #mutability contract: #struct_type = near_sdk::env::state_read().unwrap_or_default();
+                    #token_gate_check
+                    #pause_check
                 };
                 method_invocation = quote! {
                     contract.#ident(#arg_list)
@@ -136,6 +215,7 @@ impl ImplItemMethodInfo {
                 ReturnType::Default => quote! {
                     #contract_deser
                     #method_invocation;
+                    #idempotency_record_unit
                     #contract_ser
                 },
                 ReturnType::Type(_, return_type)
@@ -155,6 +235,7 @@ impl ImplItemMethodInfo {
                         match result {
                             Ok(result) => {
                                 #value_ser
+                                #idempotency_record
                                 near_sdk::env::value_return(&result);
                                 #contract_ser
                             }
@@ -175,6 +256,7 @@ impl ImplItemMethodInfo {
                         #contract_deser
                         let result = #method_invocation;
                         #value_ser
+                        #idempotency_record
                         near_sdk::env::value_return(&result);
                         #contract_ser
                     }
@@ -193,12 +275,15 @@ impl ImplItemMethodInfo {
             #[no_mangle]
             pub extern "C" fn #ident() {
                 #panic_hook
+                #sunset_check
                 #is_private_check
                 #deposit_check
                 #arg_struct
                 #arg_parsing
                 #callback_deser
                 #callback_vec_deser
+                #idempotency_check
+                #lock_acquire
                 #body
             }
         }
@@ -206,22 +291,9 @@ impl ImplItemMethodInfo {
 
     pub fn marshal_method(&self) -> TokenStream2 {
         let ImplItemMethodInfo { attr_signature_info, .. } = self;
-        let has_input_args = attr_signature_info.input_args().next().is_some();
 
         let pat_type_list = attr_signature_info.pat_type_list();
-        let serialize_args = if has_input_args {
-            match &attr_signature_info.input_serializer {
-                SerializerType::Borsh => crate::TraitItemMethodInfo::generate_serialier(
-                    attr_signature_info,
-                    &attr_signature_info.input_serializer,
-                ),
-                SerializerType::JSON => json_serialize(attr_signature_info),
-            }
-        } else {
-            quote! {
-             let args = vec![];
-            }
-        };
+        let serialize_args = self.marshal_args();
 
         let AttrSigInfo {
             non_bindgen_attrs,
@@ -261,6 +333,333 @@ impl ImplItemMethodInfo {
             }
         }
     }
+
+    /// Generate a typed view call constructor for off-chain clients on the proxy struct, named
+    /// `<method>_view`. Only generated for view methods whose result is JSON serialized and can be
+    /// deserialized by the client, i.e. does not contain references, `Self` or `impl Trait`.
+    pub fn view_call_method(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, .. } = self;
+        let AttrSigInfo {
+            non_bindgen_attrs,
+            ident,
+            method_type,
+            returns,
+            result_serializer,
+            is_returns_result,
+            original_sig,
+            ..
+        } = attr_signature_info;
+        if !matches!(method_type, MethodType::View) || result_serializer != &SerializerType::JSON {
+            return TokenStream2::new();
+        }
+        let return_type = match returns {
+            ReturnType::Type(_, ty) if *is_returns_result => match utils::extract_ok_type(ty) {
+                Some(ty) => ty,
+                None => return TokenStream2::new(),
+            },
+            ReturnType::Type(_, ty) => ty.as_ref(),
+            ReturnType::Default => return TokenStream2::new(),
+        };
+        if !utils::type_is_owned_data(return_type) {
+            return TokenStream2::new();
+        }
+
+        let pat_type_list = attr_signature_info.pat_type_list();
+        let serialize_args = self.marshal_args();
+        let ident_str = ident.to_string();
+        let view_ident = format_ident!("{}_view", ident);
+        let non_bindgen_attrs = non_bindgen_attrs.iter().fold(TokenStream2::new(), |acc, value| {
+            quote! {
+                #acc
+                #value
+            }
+        });
+        let Signature { generics, .. } = original_sig;
+        quote! {
+            #[cfg(not(target_arch = "wasm32"))]
+            #non_bindgen_attrs
+            pub fn #view_ident#generics(&self, #pat_type_list) -> near_sdk::ViewCall<#return_type> {
+                #serialize_args
+                near_sdk::ViewCall::new(
+                    near_sdk::PendingContractTx::new_from_bytes(self.account_id.clone(), #ident_str, args, true)
+                )
+            }
+        }
+    }
+
+    /// Generate a public struct with the arguments of the method, named `<Method>Args`, which
+    /// implements `near_sdk::MethodArgs` to serialize the arguments of calls to the method. Only
+    /// generated for methods marked with `#[method_args]`.
+    pub fn args_struct(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, .. } = self;
+        let AttrSigInfo { ident, input_serializer, is_method_args, .. } = attr_signature_info;
+        if !is_method_args {
+            return TokenStream2::new();
+        }
+        let args: Vec<_> = attr_signature_info.input_args().collect();
+
+        let struct_ident = format_ident!("{}Args", ident.to_string().to_pascal_case());
+        let ident_str = ident.to_string();
+        let doc = format!(" Arguments of the `{}` method.", ident_str);
+        let fields = args.iter().fold(TokenStream2::new(), |acc, ArgInfo { ident, ty, .. }| {
+            quote! {
+                #acc
+                pub #ident: #ty,
+            }
+        });
+        let (attribute, serialize) = match input_serializer {
+            SerializerType::JSON => (
+                quote! {
+                    #[derive(near_sdk::serde::Serialize, near_sdk::serde::Deserialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                },
+                quote! {
+                    near_sdk::serde_json::to_vec(self)
+                        .expect("Failed to serialize the method arguments.")
+                },
+            ),
+            SerializerType::Borsh => (
+                quote! {
+                    #[derive(near_sdk::borsh::BorshSerialize, near_sdk::borsh::BorshDeserialize)]
+                },
+                quote! {
+                    near_sdk::borsh::BorshSerialize::try_to_vec(self)
+                        .expect("Failed to serialize the method arguments.")
+                },
+            ),
+        };
+        quote! {
+            #[doc = #doc]
+            #attribute
+            #[allow(dead_code)]
+            pub struct #struct_ident {
+                #fields
+            }
+
+            impl near_sdk::MethodArgs for #struct_ident {
+                const METHOD_NAME: &'static str = #ident_str;
+
+                fn to_args(&self) -> Vec<u8> {
+                    #serialize
+                }
+            }
+        }
+    }
+
+    /// Generate the `<method>_dry_run` view of a `#[migrate]` method, which runs the migration
+    /// against a scratch storage overlay and returns its `near_sdk::MigrationSummary`. Only
+    /// generated with the `estimate` feature, which `near_sdk::MigrationSummary` requires.
+    pub fn migration_dry_run_wrapper(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
+        if !cfg!(feature = "estimate") || !attr_signature_info.is_migrate {
+            return TokenStream2::new();
+        }
+        let ident = &attr_signature_info.ident;
+        let dry_run_ident = format_ident!("{}_dry_run", ident);
+        let deposit_error = format!("Method {} doesn't accept deposit", dry_run_ident);
+        let (arg_struct, arg_parsing) = self.input_parsing();
+        let is_private_check = self.private_check(&dry_run_ident);
+        let arg_list = attr_signature_info.arg_list();
+        quote! {
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn #dry_run_ident() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str(#deposit_error);
+                }
+                #is_private_check
+                #arg_struct
+                #arg_parsing
+                let summary = near_sdk::MigrationSummary::measure(|| #struct_type::#ident(#arg_list));
+                let result = near_sdk::serde_json::to_vec(&summary).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+            }
+        }
+    }
+
+    /// Generate the `estimate_<method>` view of an `#[estimate]` method, which runs the method
+    /// against a scratch storage overlay and returns its `near_sdk::GasEstimate`.
+    pub fn estimate_wrapper(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
+        let AttrSigInfo { ident, receiver, is_returns_result, returns, .. } = attr_signature_info;
+        if !attr_signature_info.is_estimate {
+            return TokenStream2::new();
+        }
+        let estimate_ident = format_ident!("estimate_{}", ident);
+        let deposit_error = format!("Method {} doesn't accept deposit", estimate_ident);
+        let (arg_struct, arg_parsing) = self.input_parsing();
+        let is_private_check = self.private_check(&estimate_ident);
+        let arg_list = attr_signature_info.arg_list();
+        let method_invocation = if let Some(receiver) = receiver {
+            let mutability = &receiver.mutability;
+            quote! {
+                let #mutability contract: #struct_type = near_sdk::env::state_read().unwrap_or_default();
+                let result = contract.#ident(#arg_list);
+                near_sdk::env::state_write(&contract);
+            }
+        } else {
+            quote! {
+                let result = #struct_type::#ident(#arg_list);
+            }
+        };
+        let result_check = match returns {
+            ReturnType::Type(_, return_type)
+                if utils::type_is_result(return_type) && *is_returns_result =>
+            {
+                quote! {
+                    if let Err(err) = result {
+                        near_sdk::FunctionError::panic(&err);
+                    }
+                }
+            }
+            _ => quote! {
+                let _ = result;
+            },
+        };
+        quote! {
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn #estimate_ident() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str(#deposit_error);
+                }
+                #is_private_check
+                #arg_struct
+                #arg_parsing
+                let estimate = near_sdk::GasEstimate::measure(|| {
+                    #method_invocation
+                    #result_check
+                });
+                let result = near_sdk::serde_json::to_vec(&estimate).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+            }
+        }
+    }
+
+    /// Generate the match arm of the `batch_view` method which calls this `#[batch_view]` method
+    /// with the JSON arguments of a `near_sdk::ViewRequest`, evaluating to its
+    /// `near_sdk::ViewResponse`. The `Err` of a method with `#[return_result]` is converted with
+    /// `near_sdk::IntoContractError` instead of panicking.
+    pub fn batch_view_arm(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, .. } = self;
+        let AttrSigInfo { ident, is_returns_result, .. } = attr_signature_info;
+        let ident_str = ident.to_string();
+        let arg_parsing = if attr_signature_info.input_args().next().is_some() {
+            let arg_struct = attr_signature_info.input_struct(InputStructType::Deserialization);
+            let decomposition = attr_signature_info.decomposition_pattern();
+            quote! {
+                #arg_struct
+                let #decomposition: Input = match near_sdk::serde_json::from_value(request.args) {
+                    Ok(input) => input,
+                    Err(err) => {
+                        return Err(near_sdk::ContractError::new(
+                            near_sdk::ContractError::INVALID_ARGUMENTS,
+                            format!("Failed to deserialize arguments of {}: {}", #ident_str, err),
+                        ))
+                    }
+                };
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let arg_list = attr_signature_info.arg_list();
+        let result_unwrap = if *is_returns_result {
+            quote! {
+                let result = match result {
+                    Ok(result) => result,
+                    Err(err) => return Err(near_sdk::IntoContractError::into_contract_error(err)),
+                };
+            }
+        } else {
+            TokenStream2::new()
+        };
+        quote! {
+            #ident_str => {
+                #arg_parsing
+                let result = self.#ident(#arg_list);
+                #result_unwrap
+                Ok(near_sdk::serde_json::to_value(&result)
+                    .expect("Failed to serialize the return value using JSON."))
+            }
+        }
+    }
+
+    /// Generate the check that a `#[private]` method is called by the contract itself, naming the
+    /// exported function `name` in the panic message.
+    fn private_check(&self, name: &syn::Ident) -> TokenStream2 {
+        if !self.attr_signature_info.is_private {
+            return TokenStream2::new();
+        }
+        let error = format!("Method {} is private", name);
+        quote! {
+            if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                near_sdk::env::panic_str(#error);
+            }
+        }
+    }
+
+    /// Generate the struct of the arguments of the method provided by `env::input()` and the code
+    /// that deserializes them into local variables.
+    fn input_parsing(&self) -> (TokenStream2, TokenStream2) {
+        let ImplItemMethodInfo { attr_signature_info, .. } = self;
+        // Args provided by `env::input()`.
+        let has_input_args = attr_signature_info.input_args().next().is_some();
+
+        if has_input_args {
+            let arg_struct = attr_signature_info.input_struct(InputStructType::Deserialization);
+            let decomposition = attr_signature_info.decomposition_pattern();
+            let serializer_invocation = match attr_signature_info.input_serializer {
+                // Keep the input alive after deserialization, so that arguments can borrow from it.
+                SerializerType::JSON if attr_signature_info.is_zero_copy => quote! {
+                    near_sdk::serde_json::from_slice(&near_input).expect("Failed to deserialize input from JSON.")
+                },
+                SerializerType::JSON => quote! {
+                    near_sdk::serde_json::from_slice(
+                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                    ).expect("Failed to deserialize input from JSON.")
+                },
+                SerializerType::Borsh => quote! {
+                    near_sdk::borsh::BorshDeserialize::try_from_slice(
+                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                    ).expect("Failed to deserialize input from Borsh.")
+                },
+            };
+            let input_binding = if attr_signature_info.is_zero_copy {
+                quote! {
+                    let near_input = near_sdk::env::input().expect("Expected input since method has arguments.");
+                }
+            } else {
+                TokenStream2::new()
+            };
+            let arg_parsing = quote! {
+                #input_binding
+                let #decomposition : Input = #serializer_invocation ;
+            };
+            (arg_struct, arg_parsing)
+        } else {
+            (TokenStream2::new(), TokenStream2::new())
+        }
+    }
+
+    fn marshal_args(&self) -> TokenStream2 {
+        let ImplItemMethodInfo { attr_signature_info, .. } = self;
+        let has_input_args = attr_signature_info.input_args().next().is_some();
+        if has_input_args {
+            match &attr_signature_info.input_serializer {
+                SerializerType::Borsh => crate::TraitItemMethodInfo::generate_serialier(
+                    attr_signature_info,
+                    &attr_signature_info.input_serializer,
+                ),
+                SerializerType::JSON => json_serialize(attr_signature_info),
+            }
+        } else {
+            quote! {
+             let args = vec![];
+            }
+        }
+    }
 }
 
 fn json_serialize(attr_signature_info: &AttrSigInfo) -> TokenStream2 {
@@ -9,11 +9,37 @@ impl ItemImplInfo {
         for method in &self.methods {
             if method.is_public || self.is_trait_impl {
                 res.extend(method.method_wrapper());
+                res.extend(method.migration_dry_run_wrapper());
+                res.extend(method.estimate_wrapper());
             }
         }
         res
     }
 
+    /// Generate the public argument structs of the exported methods.
+    pub fn args_structs_code(&self) -> TokenStream2 {
+        let mut res = TokenStream2::new();
+        for method in &self.methods {
+            if method.is_public || self.is_trait_impl {
+                res.extend(method.args_struct());
+            }
+        }
+        res
+    }
+
+    /// Generate the implementation of `near_sdk::BatchView` for a section with `#[batch_view]`
+    /// methods, which conflicts with the one of another section exporting `batch_view`.
+    pub fn batch_view_code(&self) -> TokenStream2 {
+        use quote::quote;
+        if !self.methods.iter().any(|method| method.attr_signature_info.is_batch_view) {
+            return TokenStream2::new();
+        }
+        let ty = &self.ty;
+        quote! {
+            impl near_sdk::BatchView for #ty {}
+        }
+    }
+
     pub fn marshall_code(&self) -> TokenStream2 {
         use quote::{format_ident, quote, ToTokens};
         let orig_name = self.ty.clone().into_token_stream();
@@ -26,6 +52,7 @@ impl ItemImplInfo {
         for method in &self.methods {
             if method.is_public || self.is_trait_impl {
                 res.extend(method.marshal_method());
+                res.extend(method.view_call_method());
             }
         }
         quote! {
@@ -41,7 +68,7 @@ impl ItemImplInfo {
 #[cfg(test)]
 mod tests {
     use syn::{Type, ImplItemMethod, parse_quote};
-    use quote::quote;
+    use quote::{quote, ToTokens};
     use crate::core_impl::info_extractor::ImplItemMethodInfo;
 
 
@@ -140,6 +167,168 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn sunset_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[sunset(after = "2023-01-01", message = "Use storage_deposit")]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::utils::check_sunset("method", 1672531200000000000u64, "Use storage_deposit");
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn requires_token_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[requires_token(contract = "passes", token_id = "pass_id")]
+            pub fn method(&mut self, pass_id: String) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    pass_id: String,
+                }
+                let Input { pass_id, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                near_sdk::utils::check_token_gate(
+                    &contract.passes,
+                    near_sdk::TokenRequirement::Token((pass_id).to_string())
+                );
+                contract.method(pass_id,);
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn requires_token_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut view: ImplItemMethod = parse_quote! {
+            #[requires_token(contract = "membership", min_balance = "100")]
+            pub fn method(&self) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type.clone()).is_err());
+        let mut both: ImplItemMethod = parse_quote! {
+            #[requires_token(contract = "passes", min_balance = "1", token_id = "pass_id")]
+            pub fn method(&mut self, pass_id: String) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut both, impl_type).is_err());
+    }
+
+    #[test]
+    fn pausable_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[pausable(field = "pause", feature = "ft_transfer")]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                near_sdk::assert_not_paused(&contract.pause, "ft_transfer");
+                contract.method();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn pausable_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut view: ImplItemMethod = parse_quote! {
+            #[pausable(field = "pause")]
+            pub fn method(&self) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type.clone()).is_err());
+        let mut no_field: ImplItemMethod = parse_quote! {
+            #[pausable(feature = "ft_transfer")]
+            pub fn method(&mut self) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut no_field, impl_type).is_err());
+    }
+
+    #[test]
+    fn payable_min_deposit() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(min_deposit = 1000)]
+            #[gas_budget(tgas = 30)]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        assert_eq!(method_info.attr_signature_info.gas_budget, Some(30_000_000_000_000));
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() < 1000u128 {
+                    near_sdk::env::panic_str("Method method requires a deposit of at least 1000 yoctoNEAR");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn gas_budget_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut view: ImplItemMethod = parse_quote! {
+            #[gas_budget(tgas = 30)]
+            pub fn method(&self) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type.clone()).is_err());
+        let mut missing: ImplItemMethod = parse_quote! {
+            #[gas_budget(budget = 30)]
+            pub fn method(&mut self) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut missing, impl_type).is_err());
+    }
+
     #[test]
     fn arg_no_return_no_mut() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -313,6 +502,43 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn zero_copy_args() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[zero_copy]
+            pub fn method(&self, prefix: &str, k: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type.clone()).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input<'nearinput> {
+                    #[serde(borrow)]
+                    prefix: ::std::borrow::Cow<'nearinput, str>,
+                    k: u64,
+                }
+                let near_input = near_sdk::env::input().expect("Expected input since method has arguments.");
+                let Input { prefix, k, }: Input = near_sdk::serde_json::from_slice(&near_input)
+                    .expect("Failed to deserialize input from JSON.");
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method(&prefix, k, );
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut borsh: ImplItemMethod = parse_quote! {
+            #[zero_copy]
+            pub fn method(&self, #[serializer(borsh)] prefix: &str) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut borsh, impl_type).is_err());
+    }
+
     #[test]
     fn callback_args() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -552,6 +778,116 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn init_with_self_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[init]
+            pub fn method(&mut self) -> Self { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type.clone()).err().unwrap();
+        assert_eq!(err.to_string(), "Init methods can't take `self`");
+        let mut method: ImplItemMethod = parse_quote! {
+            #[init(ignore_state)]
+            #[payable]
+            pub fn method(self) -> Self { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut method, impl_type).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "estimate")]
+    fn migrate_dry_run() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[migrate]
+            pub fn migrate(k: u64) -> Self { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.migration_dry_run_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn migrate_dry_run() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method migrate_dry_run doesn't accept deposit");
+                }
+                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                    near_sdk::env::panic_str("Method migrate_dry_run is private");
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let summary = near_sdk::MigrationSummary::measure(|| Hello::migrate(k,));
+                let result = near_sdk::serde_json::to_vec(&summary).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+        assert!(method_info.method_wrapper().to_string().contains("Method migrate is private"));
+    }
+
+    #[test]
+    fn estimate() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[estimate]
+            pub fn method(&mut self, k: u64) -> u64 { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type.clone()).unwrap();
+        let actual = method_info.estimate_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn estimate_method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method estimate_method doesn't accept deposit");
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let estimate = near_sdk::GasEstimate::measure(|| {
+                    let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                    let result = contract.method(k, );
+                    near_sdk::env::state_write(&contract);
+                    let _ = result;
+                });
+                let result = near_sdk::serde_json::to_vec(&estimate).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut private: ImplItemMethod = parse_quote! {
+            #[estimate]
+            #[private]
+            pub fn method(&mut self) { }
+        };
+        let private_info = ImplItemMethodInfo::new(&mut private, impl_type.clone()).unwrap();
+        let private_wrapper = private_info.estimate_wrapper().to_string();
+        assert!(private_wrapper.contains("Method estimate_method is private"));
+
+        let mut view: ImplItemMethod = parse_quote! {
+            #[estimate]
+            pub fn method(&self) -> u64 { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type).is_err());
+    }
+
     #[test]
     fn init_payable() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -623,6 +959,29 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn method_serializer_borsh() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[serializer(borsh)]
+            #[result_serializer(borsh)]
+            pub fn method(&mut self, k: u64, m: Bar) -> Option<u64> { }
+        };
+        let mut per_arg: ImplItemMethod = parse_quote! {
+            #[result_serializer(borsh)]
+            pub fn method(&mut self, #[serializer(borsh)] k: u64, #[serializer(borsh)]m: Bar) -> Option<u64> { }
+        };
+        let actual = ImplItemMethodInfo::new(&mut method, impl_type.clone()).unwrap().method_wrapper();
+        let expected = ImplItemMethodInfo::new(&mut per_arg, impl_type.clone()).unwrap().method_wrapper();
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut mixed: ImplItemMethod = parse_quote! {
+            #[serializer(borsh)]
+            pub fn method(&mut self, k: u64, #[serializer(json)] m: Bar) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut mixed, impl_type).is_err());
+    }
+
     #[test]
     fn callback_args_mixed_serialization() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -752,6 +1111,91 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn view_call_one_arg() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("pub fn method(&self, k: String) -> Vec<u64> { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.view_call_method();
+        let expected = quote!(
+                #[cfg(not(target_arch = "wasm32"))]
+                pub fn method_view(&self, k: String,) -> near_sdk::ViewCall<Vec<u64> > {
+                  let args = near_sdk::serde_json::json!({ "k": k })
+                  .to_string()
+                  .into_bytes();
+                  near_sdk::ViewCall::new(
+                      near_sdk::PendingContractTx::new_from_bytes(self.account_id.clone(), "method", args, true)
+                  )
+                }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn view_call_skips_non_views_and_references() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        for method in [
+            "pub fn method(&mut self) -> u64 { }",
+            "pub fn method(&self) { }",
+            "pub fn method(&self) -> &String { }",
+            "pub fn method(&self) -> Self { }",
+        ] {
+            let mut method: ImplItemMethod = syn::parse_str(method).unwrap();
+            let method_info = ImplItemMethodInfo::new(&mut method, impl_type.clone()).unwrap();
+            assert!(method_info.view_call_method().is_empty());
+        }
+    }
+
+    #[test]
+    fn args_struct_json() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[method_args] pub fn ft_transfer(&mut self, receiver_id: AccountId, memo: Option<String>) { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.args_struct();
+        let expected = quote!(
+            #[doc = " Arguments of the `ft_transfer` method."]
+            #[derive(near_sdk::serde::Serialize, near_sdk::serde::Deserialize)]
+            #[serde(crate = "near_sdk::serde")]
+            #[allow(dead_code)]
+            pub struct FtTransferArgs {
+                pub receiver_id: AccountId,
+                pub memo: Option<String>,
+            }
+
+            impl near_sdk::MethodArgs for FtTransferArgs {
+                const METHOD_NAME: &'static str = "ft_transfer";
+
+                fn to_args(&self) -> Vec<u8> {
+                    near_sdk::serde_json::to_vec(self)
+                        .expect("Failed to serialize the method arguments.")
+                }
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn args_struct_is_opt_in() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod =
+            syn::parse_str("pub fn method(&mut self, a: u64) { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        assert!(method_info.args_struct().is_empty());
+    }
+
+    #[test]
+    fn args_struct_rejects_references_and_generics() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        for method in [
+            "#[method_args] pub fn method(&mut self) { }",
+            "#[method_args] pub fn method(&mut self, a: &String) { }",
+            "#[method_args] pub fn method<T>(&mut self, a: u64) { }",
+        ] {
+            let mut method: ImplItemMethod = syn::parse_str(method).unwrap();
+            assert!(ImplItemMethodInfo::new(&mut method, impl_type.clone()).is_err());
+        }
+    }
+
     #[test]
     fn return_result_json() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -810,4 +1254,245 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn idempotent_payable() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable]
+            #[idempotent(key_arg = "request_id", ttl = 1000)]
+            pub fn method(&mut self, request_id: String) -> u64 { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    request_id: String,
+                }
+                let Input { request_id, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let idempotency = near_sdk::Idempotency::with_ttl(1000);
+                let idempotency_key =
+                    near_sdk::Idempotency::caller_request_id("method", &request_id.to_string());
+                if let Some(result) = idempotency.cached_result(&idempotency_key) {
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::Promise::new(near_sdk::env::predecessor_account_id())
+                            .transfer(near_sdk::env::attached_deposit());
+                    }
+                    near_sdk::env::value_return(&result);
+                    return;
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                let result = contract.method(request_id, );
+                let result =
+                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                idempotency.record(&idempotency_key, &result);
+                near_sdk::env::value_return(&result);
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn idempotent_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut view: ImplItemMethod = parse_quote! {
+            #[idempotent(key_arg = "request_id")]
+            pub fn method(&self, request_id: String) -> u64 { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type.clone()).is_err());
+        let mut missing_arg: ImplItemMethod = parse_quote! {
+            #[idempotent(key_arg = "id")]
+            pub fn method(&mut self, request_id: String) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut missing_arg, impl_type).is_err());
+    }
+
+    #[test]
+    fn locks() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[locks("withdraw:{account_id}")]
+            #[locks("pool:{pool_id}:{account_id}", ttl_blocks = 50)]
+            pub fn method(&mut self, account_id: AccountId, pool_id: u64) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    account_id: AccountId,
+                    pool_id: u64,
+                }
+                let Input { account_id, pool_id, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                near_sdk::IntentLock::default().lock(&format!("withdraw:{}", account_id));
+                near_sdk::IntentLock::with_ttl(50).lock(&format!("pool:{}:{}", pool_id, account_id));
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method(account_id, pool_id, );
+                near_sdk::env::state_write(&contract);
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn locks_invalid() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut view: ImplItemMethod = parse_quote! {
+            #[locks("withdraw:{account_id}")]
+            pub fn method(&self, account_id: AccountId) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut view, impl_type.clone()).is_err());
+        let mut missing_arg: ImplItemMethod = parse_quote! {
+            #[locks("withdraw:{receiver_id}")]
+            pub fn method(&mut self, account_id: AccountId) { }
+        };
+        assert!(ImplItemMethodInfo::new(&mut missing_arg, impl_type).is_err());
+    }
+
+    #[test]
+    fn invariant_check() {
+        let mut item: syn::ItemImpl = parse_quote! {
+            #[invariant(check = "self.assert_invariants()")]
+            impl Hello {
+                pub fn method(&mut self, k: u64) -> u64 { self.value += k; self.value }
+                pub fn view(&self) -> u64 { self.value }
+                fn helper(&mut self) { self.value = 0; }
+            }
+        };
+        let info = crate::ItemImplInfo::new(&mut item).unwrap();
+        assert!(item.attrs.is_empty());
+        let body = |index: usize| match &item.items[index] {
+            syn::ImplItem::Method(m) => m.block.to_token_stream().to_string(),
+            _ => unreachable!(),
+        };
+        let expected: syn::Block = parse_quote!(
+            {
+                #[allow(clippy::redundant_closure_call)]
+                let result = (|| { self.value += k; self.value })();
+                #[cfg(debug_assertions)]
+                {
+                    self.assert_invariants();
+                }
+                result
+            }
+        );
+        assert_eq!(expected.to_token_stream().to_string(), body(0));
+        assert_eq!(quote!({ self.value }).to_string(), body(1));
+        assert_eq!(quote!({ self.value = 0; }).to_string(), body(2));
+        assert_eq!(info.methods.len(), 3);
+    }
+
+    #[test]
+    fn health_check() {
+        let mut item: syn::ItemImpl = parse_quote! {
+            #[health_check(paused = "self.pause", pending_upgrade = "self.upgrade.pending_upgrade()")]
+            impl Hello {
+                pub fn method(&mut self) { }
+            }
+        };
+        let info = crate::ItemImplInfo::new(&mut item).unwrap();
+        assert!(item.attrs.is_empty());
+        assert_eq!(info.methods.len(), 2);
+        let actual = item.items.last().unwrap().to_token_stream();
+        let expected = quote!(
+            pub fn health(&self) -> near_sdk::HealthReport {
+                near_sdk::HealthReport::new(
+                    None,
+                    near_sdk::PauseFlags::is_contract_paused(&self.pause),
+                    ::core::option::Option::<near_sdk::Timestamp>::map(self.upgrade.pending_upgrade(), near_sdk::json_types::U64),
+                    None,
+                    vec![],
+                )
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut trait_impl: syn::ItemImpl = parse_quote! {
+            #[health_check]
+            impl Trait for Hello { }
+        };
+        assert!(crate::ItemImplInfo::new(&mut trait_impl).is_err());
+    }
+
+    #[test]
+    fn batch_view() {
+        let mut item: syn::ItemImpl = parse_quote! {
+            impl Hello {
+                #[batch_view]
+                pub fn balance_of(&self, account_id: AccountId) -> u64 { 0 }
+                #[batch_view]
+                pub fn total(&self) -> u64 { 0 }
+                pub fn other(&self) -> u64 { 0 }
+            }
+        };
+        let info = crate::ItemImplInfo::new(&mut item).unwrap();
+        assert_eq!(info.methods.len(), 4);
+        let actual = item.items.last().unwrap().to_token_stream();
+        let expected = quote!(
+            pub fn batch_view(
+                &self,
+                requests: Vec<near_sdk::ViewRequest>,
+            ) -> Vec<near_sdk::ViewResponse> {
+                requests
+                    .into_iter()
+                    .map(|request| match request.method_name.as_str() {
+                        "balance_of" => {
+                            #[derive(near_sdk::serde::Deserialize)]
+                            #[serde(crate = "near_sdk::serde")]
+                            struct Input {
+                                account_id: AccountId,
+                            }
+                            let Input { account_id, }: Input = match near_sdk::serde_json::from_value(request.args) {
+                                Ok(input) => input,
+                                Err(err) => {
+                                    return Err(near_sdk::ContractError::new(
+                                        near_sdk::ContractError::INVALID_ARGUMENTS,
+                                        format!("Failed to deserialize arguments of {}: {}", "balance_of", err),
+                                    ))
+                                }
+                            };
+                            let result = self.balance_of(account_id,);
+                            Ok(near_sdk::serde_json::to_value(&result)
+                                .expect("Failed to serialize the return value using JSON."))
+                        }
+                        "total" => {
+                            let result = self.total();
+                            Ok(near_sdk::serde_json::to_value(&result)
+                                .expect("Failed to serialize the return value using JSON."))
+                        }
+                        method_name => Err(near_sdk::ContractError::new(
+                            near_sdk::ContractError::UNKNOWN_METHOD,
+                            format!("Unknown batch view method {}", method_name),
+                        )),
+                    })
+                    .collect()
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+        let expected = quote!(
+            impl near_sdk::BatchView for Hello {}
+        );
+        assert_eq!(expected.to_string(), info.batch_view_code().to_string());
+    }
 }
@@ -1,20 +1,80 @@
 use crate::core_impl::info_extractor::ItemTraitInfo;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use quote::{format_ident, quote};
 
 impl ItemTraitInfo {
     /// Generate code that wrapps external calls.
+    ///
+    /// Each method gets a function taking the account, deposit and gas as trailing arguments,
+    /// and a method of the call builder returned by `ext(account_id)`, which sets them by name.
+    /// The gas of the builder must be set, and its unused gas share is computed when the method
+    /// is called.
     pub fn wrapped_module(&self) -> TokenStream2 {
         let mut result = TokenStream2::new();
+        let mut builder_methods = TokenStream2::new();
         for method in &self.methods {
             result.extend(method.method_wrapper());
+            builder_methods.extend(method.method_builder());
         }
         let mod_name = &self.mod_name;
+        let builder_name = format_ident!("{}Ext", self.original.ident);
         quote! {
            pub mod #mod_name {
                 use super::*;
                 use near_sdk::{Gas, Balance, AccountId, Promise};
                 #result
+                #[must_use]
+                pub struct #builder_name {
+                    account_id: AccountId,
+                    deposit: near_sdk::Balance,
+                    static_gas: Option<near_sdk::Gas>,
+                    unused_gas: Option<(near_sdk::Gas, near_sdk::GasWeight, near_sdk::GasWeight)>,
+                }
+                pub fn ext(account_id: AccountId) -> #builder_name {
+                    #builder_name { account_id, deposit: 0, static_gas: None, unused_gas: None }
+                }
+                impl #builder_name {
+                    pub fn with_attached_deposit(mut self, amount: near_sdk::Balance) -> Self {
+                        self.deposit = amount;
+                        self
+                    }
+                    pub fn with_static_gas(mut self, gas: near_sdk::Gas) -> Self {
+                        self.static_gas = Some(gas);
+                        self
+                    }
+                    pub fn with_unused_gas(self, reserved: near_sdk::Gas) -> Self {
+                        self.with_unused_gas_weight(
+                            reserved,
+                            near_sdk::GasWeight(1),
+                            near_sdk::GasWeight(1),
+                        )
+                    }
+                    pub fn with_unused_gas_weight(
+                        mut self,
+                        reserved: near_sdk::Gas,
+                        weight: near_sdk::GasWeight,
+                        total_weight: near_sdk::GasWeight,
+                    ) -> Self {
+                        self.unused_gas = Some((reserved, weight, total_weight));
+                        self
+                    }
+                    fn __gas(&self) -> near_sdk::Gas {
+                        if self.static_gas.is_none() && self.unused_gas.is_none() {
+                            near_sdk::env::panic_str(
+                                "Set the gas of the call with with_static_gas or with_unused_gas",
+                            )
+                        }
+                        let unused_gas = self.unused_gas.map_or(
+                            near_sdk::Gas(0),
+                            |(reserved, weight, total_weight)| {
+                                near_sdk::Gas::remaining_after(reserved)
+                                    .weighted_share(weight, total_weight)
+                            },
+                        );
+                        self.static_gas.unwrap_or_default() + unused_gas
+                    }
+                    #builder_methods
+                }
             }
         }
     }
@@ -83,6 +143,84 @@ mod tests {
                         __gas,
                     )
                 }
+                #[must_use]
+                pub struct ExternalCrossContractExt {
+                    account_id: AccountId,
+                    deposit: near_sdk::Balance,
+                    static_gas: Option<near_sdk::Gas>,
+                    unused_gas: Option<(near_sdk::Gas, near_sdk::GasWeight, near_sdk::GasWeight)>,
+                }
+                pub fn ext(account_id: AccountId) -> ExternalCrossContractExt {
+                    ExternalCrossContractExt { account_id, deposit: 0, static_gas: None, unused_gas: None }
+                }
+                impl ExternalCrossContractExt {
+                    pub fn with_attached_deposit(mut self, amount: near_sdk::Balance) -> Self {
+                        self.deposit = amount;
+                        self
+                    }
+                    pub fn with_static_gas(mut self, gas: near_sdk::Gas) -> Self {
+                        self.static_gas = Some(gas);
+                        self
+                    }
+                    pub fn with_unused_gas(self, reserved: near_sdk::Gas) -> Self {
+                        self.with_unused_gas_weight(
+                            reserved,
+                            near_sdk::GasWeight(1),
+                            near_sdk::GasWeight(1),
+                        )
+                    }
+                    pub fn with_unused_gas_weight(
+                        mut self,
+                        reserved: near_sdk::Gas,
+                        weight: near_sdk::GasWeight,
+                        total_weight: near_sdk::GasWeight,
+                    ) -> Self {
+                        self.unused_gas = Some((reserved, weight, total_weight));
+                        self
+                    }
+                    fn __gas(&self) -> near_sdk::Gas {
+                        if self.static_gas.is_none() && self.unused_gas.is_none() {
+                            near_sdk::env::panic_str(
+                                "Set the gas of the call with with_static_gas or with_unused_gas",
+                            )
+                        }
+                        let unused_gas = self.unused_gas.map_or(
+                            near_sdk::Gas(0),
+                            |(reserved, weight, total_weight)| {
+                                near_sdk::Gas::remaining_after(reserved)
+                                    .weighted_share(weight, total_weight)
+                            },
+                        );
+                        self.static_gas.unwrap_or_default() + unused_gas
+                    }
+                    pub fn merge_sort(self, arr: Vec<u8>,) -> near_sdk::Promise {
+                        #[derive(near_sdk :: serde :: Serialize)]
+                        #[serde(crate = "near_sdk::serde")]
+                        struct Input {
+                            arr: Vec<u8>,
+                        }
+                        let args = Input { arr, };
+                        let args = near_sdk::serde_json::to_vec(&args)
+                            .expect("Failed to serialize the cross contract args using JSON.");
+                        let gas = self.__gas();
+                        near_sdk::Promise::new(self.account_id).function_call(
+                            "merge_sort".to_string(),
+                            args,
+                            self.deposit,
+                            gas,
+                        )
+                    }
+                    pub fn merge(self,) -> near_sdk::Promise {
+                        let args = vec![];
+                        let gas = self.__gas();
+                        near_sdk::Promise::new(self.account_id).function_call(
+                            "merge".to_string(),
+                            args,
+                            self.deposit,
+                            gas,
+                        )
+                    }
+                }
             }
         };
         assert_eq!(actual.to_string(), expected.to_string());
@@ -125,6 +263,73 @@ mod tests {
                     __gas,
                 )
             }
+                #[must_use]
+                pub struct TestExtExt {
+                    account_id: AccountId,
+                    deposit: near_sdk::Balance,
+                    static_gas: Option<near_sdk::Gas>,
+                    unused_gas: Option<(near_sdk::Gas, near_sdk::GasWeight, near_sdk::GasWeight)>,
+                }
+                pub fn ext(account_id: AccountId) -> TestExtExt {
+                    TestExtExt { account_id, deposit: 0, static_gas: None, unused_gas: None }
+                }
+                impl TestExtExt {
+                    pub fn with_attached_deposit(mut self, amount: near_sdk::Balance) -> Self {
+                        self.deposit = amount;
+                        self
+                    }
+                    pub fn with_static_gas(mut self, gas: near_sdk::Gas) -> Self {
+                        self.static_gas = Some(gas);
+                        self
+                    }
+                    pub fn with_unused_gas(self, reserved: near_sdk::Gas) -> Self {
+                        self.with_unused_gas_weight(
+                            reserved,
+                            near_sdk::GasWeight(1),
+                            near_sdk::GasWeight(1),
+                        )
+                    }
+                    pub fn with_unused_gas_weight(
+                        mut self,
+                        reserved: near_sdk::Gas,
+                        weight: near_sdk::GasWeight,
+                        total_weight: near_sdk::GasWeight,
+                    ) -> Self {
+                        self.unused_gas = Some((reserved, weight, total_weight));
+                        self
+                    }
+                    fn __gas(&self) -> near_sdk::Gas {
+                        if self.static_gas.is_none() && self.unused_gas.is_none() {
+                            near_sdk::env::panic_str(
+                                "Set the gas of the call with with_static_gas or with_unused_gas",
+                            )
+                        }
+                        let unused_gas = self.unused_gas.map_or(
+                            near_sdk::Gas(0),
+                            |(reserved, weight, total_weight)| {
+                                near_sdk::Gas::remaining_after(reserved)
+                                    .weighted_share(weight, total_weight)
+                            },
+                        );
+                        self.static_gas.unwrap_or_default() + unused_gas
+                    }
+                    pub fn test(self, v: Vec<String>,) -> near_sdk::Promise {
+                        #[derive(near_sdk :: borsh :: BorshSerialize)]
+                        struct Input {
+                            v: Vec<String>,
+                        }
+                        let args = Input { v, };
+                        let args = near_sdk::borsh::BorshSerialize::try_to_vec(&args)
+                            .expect("Failed to serialize the cross contract args using Borsh.");
+                        let gas = self.__gas();
+                        near_sdk::Promise::new(self.account_id).function_call(
+                            "test".to_string(),
+                            args,
+                            self.deposit,
+                            gas,
+                        )
+                    }
+                }
         }
         };
         assert_eq!(actual.to_string(), expected.to_string());
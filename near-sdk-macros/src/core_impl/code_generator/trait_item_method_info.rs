@@ -29,6 +29,31 @@ impl TraitItemMethodInfo {
         }
     }
 
+    /// Generate the method of the call builder, which takes the account, deposit and gas from the
+    /// builder. The gas is computed when the method is called, after serializing the arguments.
+    pub fn method_builder(&self) -> TokenStream2 {
+        let ident = &self.attr_sig_info.ident;
+        let ident_byte_str = &self.ident_byte_str;
+        let pat_type_list = self.attr_sig_info.pat_type_list();
+        let serialize = TraitItemMethodInfo::generate_serialier(
+            &self.attr_sig_info,
+            &self.attr_sig_info.result_serializer,
+        );
+        quote! {
+            pub fn #ident(self, #pat_type_list) -> near_sdk::Promise {
+                #serialize
+                let gas = self.__gas();
+                near_sdk::Promise::new(self.account_id)
+                .function_call(
+                    #ident_byte_str.to_string(),
+                    args,
+                    self.deposit,
+                    gas,
+                )
+            }
+        }
+    }
+
     pub fn generate_serialier(
         attr_sig_info: &AttrSigInfo,
         serializer: &SerializerType,
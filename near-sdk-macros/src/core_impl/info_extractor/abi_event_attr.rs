@@ -0,0 +1,38 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Lit, LitStr, MetaNameValue, Token};
+
+/// Arguments of the `#[abi_event(standard = "nep141", version = "1.0.0", event = "ft_mint")]`
+/// attribute, marking the type of the data of a NEP-297 event for the ABI.
+pub struct AbiEventAttr {
+    pub standard: LitStr,
+    pub version: LitStr,
+    pub event: LitStr,
+}
+
+impl Parse for AbiEventAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut standard = None;
+        let mut version = None;
+        let mut event = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("standard"), Lit::Str(lit)) => standard = Some(lit),
+                (Some("version"), Lit::Str(lit)) => version = Some(lit),
+                (Some("event"), Lit::Str(lit)) => event = Some(lit),
+                (_, lit) => return Err(Error::new(lit.span(), "Unsupported abi_event attribute.")),
+            }
+        }
+        match (standard, version, event) {
+            (Some(standard), Some(version), Some(event)) => Ok(Self { standard, version, event }),
+            _ => Err(Error::new(
+                Span::call_site(),
+                "abi_event attribute requires `standard`, `version` and `event`.",
+            )),
+        }
+    }
+}
@@ -39,8 +39,9 @@ pub struct ArgInfo {
 }
 
 impl ArgInfo {
-    /// Extract near-sdk specific argument info.
-    pub fn new(original: &mut PatType) -> syn::Result<Self> {
+    /// Extract near-sdk specific argument info. Arguments read from `env::input()` without a
+    /// `#[serializer(...)]` attribute use `input_serializer`, the serializer of the method.
+    pub fn new(original: &mut PatType, input_serializer: SerializerType) -> syn::Result<Self> {
         let mut non_bindgen_attrs = vec![];
         let pat_reference;
         let pat_mutability;
@@ -67,8 +68,7 @@ impl ArgInfo {
         };
         // In the absence of callback attributes this is a regular argument.
         let mut bindgen_ty = BindgenArgType::Regular;
-        // In the absence of serialization attributes this is a JSON serialization.
-        let mut serializer_ty = SerializerType::JSON;
+        let mut serializer_attr = None;
         for attr in &mut original.attrs {
             let attr_str = attr.path.to_token_stream().to_string();
             match attr_str.as_str() {
@@ -83,7 +83,7 @@ impl ArgInfo {
                 }
                 "serializer" => {
                     let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
-                    serializer_ty = serializer.serializer_type;
+                    serializer_attr = Some(serializer.serializer_type);
                 }
                 _ => {
                     non_bindgen_attrs.push((*attr).clone());
@@ -91,6 +91,13 @@ impl ArgInfo {
             }
         }
 
+        // In the absence of serialization attributes callback results are JSON, and input
+        // arguments use the serializer of the method.
+        let serializer_ty = serializer_attr.unwrap_or(match bindgen_ty {
+            BindgenArgType::Regular => input_serializer,
+            _ => SerializerType::JSON,
+        });
+
         original.attrs.retain(|attr| {
             let attr_str = attr.path.to_token_stream().to_string();
             attr_str != "callback"
@@ -1,4 +1,8 @@
-use super::{ArgInfo, BindgenArgType, InitAttr, MethodType, SerializerAttr, SerializerType};
+use super::{
+    ArgInfo, BindgenArgType, GasBudgetAttr, IdempotentAttr, InitAttr, LocksAttr, MethodType,
+    PausableAttr, PayableAttr, RequiresTokenAttr, SerializerAttr, SerializerType, SunsetAttr,
+};
+use crate::core_impl::utils;
 use proc_macro2::Span;
 use quote::ToTokens;
 use syn::spanned::Spanned;
@@ -16,10 +20,34 @@ pub struct AttrSigInfo {
     pub method_type: MethodType,
     /// Whether method accepting $NEAR.
     pub is_payable: bool,
+    /// Minimum deposit of a payable method, checked before the method runs.
+    pub min_deposit: Option<u128>,
+    /// Gas recommended to attach to calls of the method, declared in the metadata.
+    pub gas_budget: Option<u64>,
     /// Whether method can accept calls from self (current account)
     pub is_private: bool,
     /// Whether method returns Result type where only Ok type is serialized
     pub is_returns_result: bool,
+    /// Whether replays of the method with the same request id return the recorded result.
+    pub idempotent: Option<IdempotentAttr>,
+    /// Resources locked with `near_sdk::IntentLock` before the method runs.
+    pub locks: Vec<LocksAttr>,
+    /// Date after which the method is retired, with a deprecation notice before it.
+    pub sunset: Option<SunsetAttr>,
+    /// Tokens the caller must hold, checked with a `near_sdk::TokenGate` field of the contract.
+    pub requires_token: Option<RequiresTokenAttr>,
+    /// Pause flags checked before the method runs, a `near_sdk::PauseFlags` field of the contract.
+    pub pausable: Option<PausableAttr>,
+    /// Whether reference arguments like `&str` borrow from `env::input()` instead of being copied.
+    pub is_zero_copy: bool,
+    /// Whether the method is a private migration with a generated `<method>_dry_run` view.
+    pub is_migrate: bool,
+    /// Whether the method has a generated `estimate_<method>` view.
+    pub is_estimate: bool,
+    /// Whether the view method can be called through the generated `batch_view` method.
+    pub is_batch_view: bool,
+    /// Whether the method has a generated public `<Method>Args` struct.
+    pub is_method_args: bool,
     /// The serializer that we use for `env::input()`.
     pub input_serializer: SerializerType,
     /// The serializer that we use for the return type.
@@ -62,10 +90,24 @@ impl AttrSigInfo {
         let mut args = vec![];
         let mut method_type = MethodType::Regular;
         let mut is_payable = false;
+        let mut min_deposit = None;
+        let mut gas_budget = None;
         let mut is_private = false;
         let mut is_returns_result = false;
+        let mut idempotent = None;
+        let mut locks = vec![];
+        let mut sunset = None;
+        let mut requires_token = None;
+        let mut pausable = None;
+        let mut zero_copy_attr = None;
+        let mut batch_view_attr = None;
+        let mut is_migrate = false;
+        let mut estimate_attr = None;
+        let mut method_args_attr = None;
         // By the default we serialize the result with JSON.
         let mut result_serializer = SerializerType::JSON;
+        // By the default we deserialize the arguments with JSON, unless they override it.
+        let mut input_serializer = SerializerType::JSON;
 
         let mut payable_attr = None;
         for attr in original_attrs.iter() {
@@ -79,13 +121,28 @@ impl AttrSigInfo {
                         method_type = MethodType::Init;
                     }
                 }
+                "migrate" => {
+                    method_type = MethodType::InitIgnoreState;
+                    is_private = true;
+                    is_migrate = true;
+                }
                 "payable" => {
+                    let payable: PayableAttr = syn::parse2(attr.tokens.clone())?;
+                    min_deposit = payable.min_deposit;
                     payable_attr = Some(attr);
                     is_payable = true;
                 }
+                "gas_budget" => {
+                    let gas_budget_attr: GasBudgetAttr = syn::parse2(attr.tokens.clone())?;
+                    gas_budget = Some((attr.span(), gas_budget_attr.gas));
+                }
                 "private" => {
                     is_private = true;
                 }
+                "serializer" => {
+                    let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
+                    input_serializer = serializer.serializer_type;
+                }
                 "result_serializer" => {
                     let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
                     result_serializer = serializer.serializer_type;
@@ -93,6 +150,37 @@ impl AttrSigInfo {
                 "return_result" => {
                     is_returns_result = true;
                 }
+                "idempotent" => {
+                    let idempotent_attr: IdempotentAttr = syn::parse2(attr.tokens.clone())?;
+                    idempotent = Some((attr.span(), idempotent_attr));
+                }
+                "locks" => {
+                    let locks_attr: LocksAttr = syn::parse2(attr.tokens.clone())?;
+                    locks.push((attr.span(), locks_attr));
+                }
+                "sunset" => {
+                    sunset = Some(syn::parse2::<SunsetAttr>(attr.tokens.clone())?);
+                }
+                "requires_token" => {
+                    let requires_token_attr: RequiresTokenAttr = syn::parse2(attr.tokens.clone())?;
+                    requires_token = Some((attr.span(), requires_token_attr));
+                }
+                "pausable" => {
+                    let pausable_attr: PausableAttr = syn::parse2(attr.tokens.clone())?;
+                    pausable = Some((attr.span(), pausable_attr));
+                }
+                "estimate" => {
+                    estimate_attr = Some(attr);
+                }
+                "zero_copy" => {
+                    zero_copy_attr = Some(attr);
+                }
+                "batch_view" => {
+                    batch_view_attr = Some(attr);
+                }
+                "method_args" => {
+                    method_args_attr = Some(attr);
+                }
                 _ => {
                     non_bindgen_attrs.push((*attr).clone());
                 }
@@ -104,7 +192,7 @@ impl AttrSigInfo {
             match fn_arg {
                 FnArg::Receiver(r) => receiver = Some((*r).clone()),
                 FnArg::Typed(pat_typed) => {
-                    args.push(ArgInfo::new(pat_typed)?);
+                    args.push(ArgInfo::new(pat_typed, input_serializer)?);
                 }
             }
         }
@@ -115,10 +203,7 @@ impl AttrSigInfo {
                     method_type = MethodType::View;
                 }
             } else {
-                return Err(Error::new(
-                    payable_attr.span(),
-                    "Init methods can't have `self` attribute",
-                ));
+                return Err(Error::new(receiver.span(), "Init methods can't take `self`"));
             }
         };
 
@@ -131,6 +216,121 @@ impl AttrSigInfo {
             }
         }
 
+        if let Some((span, _)) = &gas_budget {
+            if matches!(method_type, MethodType::View) {
+                return Err(Error::new(*span, "View methods don't need a gas budget"));
+            }
+        }
+
+        if let Some(batch_view_attr) = batch_view_attr {
+            if !matches!(method_type, MethodType::View)
+                || receiver.as_ref().map_or(true, |receiver| receiver.reference.is_none())
+            {
+                return Err(Error::new(
+                    batch_view_attr.span(),
+                    "Batch view method must be a view method taking `&self`",
+                ));
+            }
+            if zero_copy_attr.is_some()
+                || result_serializer != SerializerType::JSON
+                || args.iter().any(|arg| {
+                    !matches!(arg.bindgen_ty, BindgenArgType::Regular)
+                        || arg.serializer_ty != SerializerType::JSON
+                })
+            {
+                return Err(Error::new(
+                    batch_view_attr.span(),
+                    "Batch view method must take and return JSON without zero-copy arguments",
+                ));
+            }
+        }
+
+        if let Some((span, idempotent_attr)) = &idempotent {
+            if !matches!(method_type, MethodType::Regular) {
+                return Err(Error::new(
+                    *span,
+                    "Idempotent method must be mutable (not view or init)",
+                ));
+            }
+            let key_arg = &idempotent_attr.key_arg;
+            if !args.iter().any(|arg| {
+                matches!(arg.bindgen_ty, BindgenArgType::Regular) && &arg.ident == key_arg
+            }) {
+                return Err(Error::new(
+                    key_arg.span(),
+                    format!("Idempotent key `{}` is not an argument of the method", key_arg),
+                ));
+            }
+        }
+
+        if let Some(estimate_attr) = estimate_attr {
+            if !matches!(method_type, MethodType::Regular)
+                || args.iter().any(|arg| !matches!(arg.bindgen_ty, BindgenArgType::Regular))
+            {
+                return Err(Error::new(
+                    estimate_attr.span(),
+                    "Estimated method must be mutable (not view or init) and can't take callback \
+                     results",
+                ));
+            }
+        }
+
+        if let Some(method_args_attr) = method_args_attr {
+            let regular_args: Vec<_> = args
+                .iter()
+                .filter(|arg| matches!(arg.bindgen_ty, BindgenArgType::Regular))
+                .collect();
+            if regular_args.is_empty()
+                || !original_sig.generics.params.is_empty()
+                || regular_args
+                    .iter()
+                    .any(|arg| arg.reference.is_some() || !utils::type_is_owned_data(&arg.ty))
+            {
+                return Err(Error::new(
+                    method_args_attr.span(),
+                    "Method with an arguments struct must take arguments of owned types and can't \
+                     be generic",
+                ));
+            }
+        }
+
+        for (span, locks_attr) in &locks {
+            if !matches!(method_type, MethodType::Regular) {
+                return Err(Error::new(*span, "Locking method must be mutable (not view or init)"));
+            }
+            for arg_ident in &locks_attr.args {
+                if !args.iter().any(|arg| {
+                    matches!(arg.bindgen_ty, BindgenArgType::Regular) && &arg.ident == arg_ident
+                }) {
+                    return Err(Error::new(
+                        arg_ident.span(),
+                        format!(
+                            "Locked resource refers to `{}` which is not an argument of the method",
+                            arg_ident
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some((span, _)) = &requires_token {
+            if !matches!(method_type, MethodType::Regular) || receiver.is_none() {
+                return Err(Error::new(
+                    *span,
+                    "Token-gated method must take `&mut self` (not view, init or static)",
+                ));
+            }
+        }
+
+        if let Some((span, _)) = &pausable {
+            if !matches!(method_type, MethodType::Regular) || receiver.is_none() {
+                return Err(Error::new(
+                    *span,
+                    "Pausable method must take `&mut self` (not view, init or static)",
+                ));
+            }
+        }
+
         *original_attrs = non_bindgen_attrs.clone();
         let returns = original_sig.output.clone();
 
@@ -141,8 +341,20 @@ impl AttrSigInfo {
             input_serializer: SerializerType::JSON,
             method_type,
             is_payable,
+            min_deposit,
+            gas_budget: gas_budget.map(|(_, gas)| gas),
             is_private,
             is_returns_result,
+            idempotent: idempotent.map(|(_, idempotent_attr)| idempotent_attr),
+            locks: locks.into_iter().map(|(_, locks_attr)| locks_attr).collect(),
+            sunset,
+            requires_token: requires_token.map(|(_, requires_token_attr)| requires_token_attr),
+            pausable: pausable.map(|(_, pausable_attr)| pausable_attr),
+            is_zero_copy: zero_copy_attr.is_some(),
+            is_migrate,
+            is_estimate: estimate_attr.is_some(),
+            is_batch_view: batch_view_attr.is_some(),
+            is_method_args: method_args_attr.is_some(),
             result_serializer,
             receiver,
             returns,
@@ -160,6 +372,14 @@ impl AttrSigInfo {
                     "Input arguments should be all of the same serialization type.",
                 ));
             };
+        if let Some(zero_copy_attr) = zero_copy_attr {
+            if input_serializer != SerializerType::JSON {
+                return Err(Error::new(
+                    zero_copy_attr.span(),
+                    "Zero-copy arguments are only supported with JSON serialization",
+                ));
+            }
+        }
         result.input_serializer = input_serializer;
         Ok(result)
     }
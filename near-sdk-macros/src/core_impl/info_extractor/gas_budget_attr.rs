@@ -0,0 +1,38 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Lit, MetaNameValue, Token};
+
+/// Arguments of the `#[gas_budget(tgas = 30)]` or `#[gas_budget(gas = 30000000000000)]`
+/// attribute.
+pub struct GasBudgetAttr {
+    /// Gas recommended to attach to calls of the method.
+    pub gas: u64,
+}
+
+impl Parse for GasBudgetAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut gas = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("gas"), Lit::Int(lit)) => gas = Some(lit.base10_parse()?),
+                (Some("tgas"), Lit::Int(lit)) => {
+                    let tgas: u64 = lit.base10_parse()?;
+                    gas = Some(tgas.checked_mul(1_000_000_000_000).ok_or_else(|| {
+                        Error::new(lit.span(), "The gas budget doesn't fit in 64 bits.")
+                    })?);
+                }
+                (_, lit) => {
+                    return Err(Error::new(lit.span(), "Unsupported gas budget attribute."))
+                }
+            }
+        }
+        let gas = gas.ok_or_else(|| {
+            Error::new(Span::call_site(), "Gas budget attribute requires `tgas = <amount>`.")
+        })?;
+        Ok(Self { gas })
+    }
+}
@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Expr, Lit, MetaNameValue, Token};
+
+/// Arguments of the `#[health_check(paused = "self.pause", ..)]` attribute, each an expression
+/// evaluated in the generated `health` view.
+#[derive(Default)]
+pub struct HealthCheckAttr {
+    /// Version of the contract state, convertible into `u64`.
+    pub state_version: Option<Expr>,
+    /// Pause flags implementing `near_sdk::PauseFlags`.
+    pub paused: Option<Expr>,
+    /// Timestamp from which staged code can be deployed, as `Option<Timestamp>`.
+    pub pending_upgrade: Option<Expr>,
+    /// Timestamp of the last configuration change, as `Option<Timestamp>`.
+    pub last_config_change: Option<Expr>,
+    /// Results of the invariant checks, as `Vec<near_sdk::InvariantCheck>`.
+    pub invariants: Option<Expr>,
+}
+
+impl Parse for HealthCheckAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut result = Self::default();
+        if input.is_empty() {
+            return Ok(result);
+        }
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        for pair in pairs {
+            let expr = match &pair.lit {
+                Lit::Str(lit) => Some(lit.parse()?),
+                lit => return Err(Error::new(lit.span(), "Expected a string with an expression.")),
+            };
+            match pair.path.get_ident().map(|ident| ident.to_string()).as_deref() {
+                Some("state_version") => result.state_version = expr,
+                Some("paused") => result.paused = expr,
+                Some("pending_upgrade") => result.pending_upgrade = expr,
+                Some("last_config_change") => result.last_config_change = expr,
+                Some("invariants") => result.invariants = expr,
+                _ => {
+                    return Err(Error::new(pair.lit.span(), "Unsupported health check attribute."))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl HealthCheckAttr {
+    /// Generates the `health` view method.
+    pub fn health_method(&self) -> TokenStream2 {
+        let state_version = match &self.state_version {
+            Some(expr) => quote! {
+                Some(near_sdk::json_types::U64(::core::convert::Into::<u64>::into(#expr)))
+            },
+            None => quote! { None },
+        };
+        let paused = match &self.paused {
+            Some(expr) => quote! { near_sdk::PauseFlags::is_contract_paused(&#expr) },
+            None => quote! { false },
+        };
+        let timestamp = |expr: &Option<Expr>| match expr {
+            Some(expr) => quote! {
+                ::core::option::Option::<near_sdk::Timestamp>::map(#expr, near_sdk::json_types::U64)
+            },
+            None => quote! { None },
+        };
+        let pending_upgrade = timestamp(&self.pending_upgrade);
+        let last_config_change = timestamp(&self.last_config_change);
+        let invariants = match &self.invariants {
+            Some(expr) => quote! { #expr },
+            None => quote! { vec![] },
+        };
+        quote! {
+            pub fn health(&self) -> near_sdk::HealthReport {
+                near_sdk::HealthReport::new(
+                    #state_version,
+                    #paused,
+                    #pending_upgrade,
+                    #last_config_change,
+                    #invariants,
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Ident, Lit, LitInt, MetaNameValue, Token};
+
+/// Arguments of the `#[idempotent(key_arg = "request_id", ttl = 3_600_000_000_000)]` attribute.
+pub struct IdempotentAttr {
+    /// Name of the method argument holding the request id.
+    pub key_arg: Ident,
+    /// Custom time to live of recorded results in nanoseconds.
+    pub ttl: Option<LitInt>,
+}
+
+impl Parse for IdempotentAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut key_arg = None;
+        let mut ttl = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("key_arg"), Lit::Str(lit)) => key_arg = Some(lit.parse()?),
+                (Some("ttl"), Lit::Int(lit)) => ttl = Some(lit),
+                (_, lit) => {
+                    return Err(Error::new(lit.span(), "Unsupported idempotent attribute."))
+                }
+            }
+        }
+        let key_arg = key_arg.ok_or_else(|| {
+            Error::new(Span::call_site(), "Idempotent attribute requires `key_arg = \"<arg>\"`.")
+        })?;
+        Ok(Self { key_arg, ttl })
+    }
+}
@@ -0,0 +1,43 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Block, Error, Expr, LitStr, Token};
+
+/// Arguments of the `#[invariant(check = "self.assert_invariants()")]` attribute.
+pub struct InvariantAttr {
+    /// Expression which panics if the invariants of the contract are broken.
+    pub check: Expr,
+}
+
+impl Parse for InvariantAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let ident: syn::Ident = content.parse()?;
+        if ident != "check" {
+            return Err(Error::new(ident.span(), "Unsupported invariant attribute."));
+        }
+        let _eq_token: Token![=] = content.parse()?;
+        let check: LitStr = content.parse()?;
+        Ok(Self { check: check.parse()? })
+    }
+}
+
+impl InvariantAttr {
+    /// Wraps the body of a state-mutating method to run the check after it, only in builds with
+    /// debug assertions.
+    pub fn wrap_block(&self, block: &Block) -> TokenStream2 {
+        let check = &self.check;
+        quote! {
+            {
+                #[allow(clippy::redundant_closure_call)]
+                let result = (|| #block)();
+                #[cfg(debug_assertions)]
+                {
+                    #check;
+                }
+                result
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use crate::core_impl::info_extractor::{HealthCheckAttr, InvariantAttr};
 use crate::ImplItemMethodInfo;
+use quote::{quote, ToTokens};
 use syn::spanned::Spanned;
-use syn::{Error, ImplItem, ItemImpl, Type};
+use syn::{Error, FnArg, ImplItem, ImplItemMethod, ItemImpl, Type, Visibility};
 
 /// Information extracted from `impl` section.
 pub struct ItemImplInfo {
@@ -23,13 +25,82 @@ impl ItemImplInfo {
         let is_trait_impl = original.trait_.is_some();
         let ty = (*original.self_ty.as_ref()).clone();
 
+        let mut invariant = None;
+        let mut health_check = None;
+        let mut attrs = vec![];
+        for attr in original.attrs.drain(..) {
+            let path = attr.path.to_token_stream().to_string();
+            if path == "invariant" {
+                invariant = Some(syn::parse2::<InvariantAttr>(attr.tokens)?);
+            } else if path == "health_check" {
+                if is_trait_impl {
+                    return Err(Error::new(
+                        attr.span(),
+                        "The health check must be declared on an inherent impl section.",
+                    ));
+                }
+                health_check = Some(syn::parse2::<HealthCheckAttr>(attr.tokens)?);
+            } else {
+                attrs.push(attr);
+            }
+        }
+        original.attrs = attrs;
+
         let mut methods = vec![];
         for subitem in &mut original.items {
             if let ImplItem::Method(m) = subitem {
+                if let Some(invariant) = &invariant {
+                    let is_exported = is_trait_impl || matches!(m.vis, Visibility::Public(_));
+                    let is_mut_receiver = matches!(
+                        m.sig.inputs.first(),
+                        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_some()
+                    );
+                    if is_exported && is_mut_receiver {
+                        m.block = syn::parse2(invariant.wrap_block(&m.block))?;
+                    }
+                }
                 let method_info = ImplItemMethodInfo::new(m, ty.clone())?;
                 methods.push(method_info);
             }
         }
+
+        let batch_view_arms: Vec<_> = methods
+            .iter()
+            .filter(|method| method.attr_signature_info.is_batch_view)
+            .map(|method| method.batch_view_arm())
+            .collect();
+        if !batch_view_arms.is_empty() {
+            if is_trait_impl {
+                return Err(Error::new(
+                    original.span(),
+                    "Batch view methods must be declared in an inherent impl section.",
+                ));
+            }
+            let mut batch_view: ImplItemMethod = syn::parse2(quote! {
+                pub fn batch_view(
+                    &self,
+                    requests: Vec<near_sdk::ViewRequest>,
+                ) -> Vec<near_sdk::ViewResponse> {
+                    requests
+                        .into_iter()
+                        .map(|request| match request.method_name.as_str() {
+                            #(#batch_view_arms)*
+                            method_name => Err(near_sdk::ContractError::new(
+                                near_sdk::ContractError::UNKNOWN_METHOD,
+                                format!("Unknown batch view method {}", method_name),
+                            )),
+                        })
+                        .collect()
+                }
+            })?;
+            methods.push(ImplItemMethodInfo::new(&mut batch_view, ty.clone())?);
+            original.items.push(ImplItem::Method(batch_view));
+        }
+        if let Some(health_check) = health_check {
+            let mut health: ImplItemMethod = syn::parse2(health_check.health_method())?;
+            methods.push(ImplItemMethodInfo::new(&mut health, ty.clone())?);
+            original.items.push(ImplItem::Method(health));
+        }
         Ok(Self { is_trait_impl, ty, methods })
     }
 }
@@ -0,0 +1,101 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Ident, Lit, LitInt, LitStr, MetaNameValue, Token};
+
+/// Arguments of the `#[locks("withdraw:{account_id}", ttl_blocks = 50)]` attribute.
+pub struct LocksAttr {
+    /// Name of the locked resource as a `format!` string, with `{}` in place of the arguments.
+    pub format: LitStr,
+    /// Method arguments replacing the placeholders of the resource name, in order.
+    pub args: Vec<Ident>,
+    /// Custom number of blocks after which the lock expires.
+    pub ttl_blocks: Option<LitInt>,
+}
+
+impl Parse for LocksAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let resource: LitStr = content.parse()?;
+        let mut ttl_blocks = None;
+        if !content.is_empty() {
+            let _comma: Token![,] = content.parse()?;
+            let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+            for pair in pairs {
+                match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                    (Some("ttl_blocks"), Lit::Int(lit)) => ttl_blocks = Some(lit),
+                    (_, lit) => return Err(Error::new(lit.span(), "Unsupported locks attribute.")),
+                }
+            }
+        }
+        let (format, args) = parse_resource(&resource)?;
+        Ok(Self { format: LitStr::new(&format, resource.span()), args, ttl_blocks })
+    }
+}
+
+/// Splits a resource name like `withdraw:{account_id}` into a `format!` string and the names of
+/// its placeholders. `{{` and `}}` are kept as escaped braces.
+fn parse_resource(resource: &LitStr) -> syn::Result<(String, Vec<Ident>)> {
+    let value = resource.value();
+    let mut format = String::new();
+    let mut args = vec![];
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                format.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                format.push_str("}}");
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => {
+                            return Err(Error::new(resource.span(), "Unclosed `{` in resource."))
+                        }
+                    }
+                }
+                let arg = syn::parse_str::<Ident>(name.trim()).map_err(|_| {
+                    Error::new(
+                        resource.span(),
+                        format!("Expected an argument name in `{{{}}}` of resource.", name),
+                    )
+                })?;
+                args.push(Ident::new(&arg.to_string(), resource.span()));
+                format.push_str("{}");
+            }
+            '}' => return Err(Error::new(resource.span(), "Unmatched `}` in resource.")),
+            c => format.push(c),
+        }
+    }
+    Ok((format, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn placeholders() {
+        let attr: LocksAttr =
+            syn::parse2(quote! { ("withdraw:{account_id}:{{{ token_id }}}", ttl_blocks = 5) })
+                .unwrap();
+        assert_eq!(attr.format.value(), "withdraw:{}:{{{}}}");
+        let args: Vec<String> = attr.args.iter().map(ToString::to_string).collect();
+        assert_eq!(args, vec!["account_id", "token_id"]);
+        assert_eq!(attr.ttl_blocks.unwrap().base10_parse::<u64>().unwrap(), 5);
+    }
+
+    #[test]
+    fn invalid_placeholder() {
+        assert!(syn::parse2::<LocksAttr>(quote! { ("withdraw:{account id}") }).is_err());
+        assert!(syn::parse2::<LocksAttr>(quote! { ("withdraw:{account_id") }).is_err());
+    }
+}
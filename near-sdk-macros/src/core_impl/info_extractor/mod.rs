@@ -1,6 +1,9 @@
 mod serializer_attr;
 pub use serializer_attr::SerializerAttr;
 
+mod abi_event_attr;
+pub use abi_event_attr::AbiEventAttr;
+
 mod arg_info;
 pub use arg_info::{ArgInfo, BindgenArgType};
 
@@ -21,10 +24,37 @@ mod item_impl_info;
 mod init_attr;
 pub use init_attr::InitAttr;
 
+mod idempotent_attr;
+pub use idempotent_attr::IdempotentAttr;
+
+mod gas_budget_attr;
+pub use gas_budget_attr::GasBudgetAttr;
+
+mod health_check_attr;
+pub use health_check_attr::HealthCheckAttr;
+
+mod invariant_attr;
+pub use invariant_attr::InvariantAttr;
+
+mod locks_attr;
+pub use locks_attr::LocksAttr;
+
+mod payable_attr;
+pub use payable_attr::PayableAttr;
+
+mod pausable_attr;
+pub use pausable_attr::PausableAttr;
+
+mod requires_token_attr;
+pub use requires_token_attr::{RequiresTokenAttr, TokenRequirementAttr};
+
+mod sunset_attr;
+pub use sunset_attr::SunsetAttr;
+
 pub use item_impl_info::ItemImplInfo;
 
 /// Type of serialization we use.
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum SerializerType {
     JSON,
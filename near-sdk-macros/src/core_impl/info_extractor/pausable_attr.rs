@@ -0,0 +1,33 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Ident, Lit, LitStr, MetaNameValue, Token};
+
+/// Arguments of the `#[pausable(field = "pause", feature = "ft_transfer")]` attribute.
+pub struct PausableAttr {
+    /// Field of the contract implementing `near_sdk::PauseFlags`.
+    pub field: Ident,
+    /// Name of the feature, the name of the method if not set.
+    pub feature: Option<LitStr>,
+}
+
+impl Parse for PausableAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut field = None;
+        let mut feature = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("field"), Lit::Str(lit)) => field = Some(lit.parse()?),
+                (Some("feature"), Lit::Str(lit)) => feature = Some(lit),
+                (_, lit) => return Err(Error::new(lit.span(), "Unsupported pausable attribute.")),
+            }
+        }
+        let field = field.ok_or_else(|| {
+            Error::new(Span::call_site(), "Pausable attribute requires `field = \"<field>\"`.")
+        })?;
+        Ok(Self { field, feature })
+    }
+}
@@ -0,0 +1,29 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Lit, MetaNameValue, Token};
+
+/// Arguments of the `#[payable]` or `#[payable(min_deposit = 1)]` attribute.
+#[derive(Default)]
+pub struct PayableAttr {
+    /// Minimum attached deposit in yoctoNEAR, declared in the metadata for wallets.
+    pub min_deposit: Option<u128>,
+}
+
+impl Parse for PayableAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self::default());
+        }
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut min_deposit = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("min_deposit"), Lit::Int(lit)) => min_deposit = Some(lit.base10_parse()?),
+                (_, lit) => return Err(Error::new(lit.span(), "Unsupported payable attribute.")),
+            }
+        }
+        Ok(Self { min_deposit })
+    }
+}
@@ -0,0 +1,59 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Expr, Ident, Lit, MetaNameValue, Token};
+
+/// Tokens required by the `#[requires_token(contract = "gate", min_balance = "100")]` attribute.
+pub enum TokenRequirementAttr {
+    /// Minimum balance, an expression which can use the arguments of the method.
+    MinBalance(Expr),
+    /// Id of the token, an expression which can use the arguments of the method.
+    TokenId(Expr),
+}
+
+/// Arguments of the `#[requires_token(contract = "gate", min_balance = "100")]` attribute.
+pub struct RequiresTokenAttr {
+    /// Field of the contract implementing `near_sdk::TokenGate`.
+    pub contract: Ident,
+    pub requirement: TokenRequirementAttr,
+}
+
+impl Parse for RequiresTokenAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut contract = None;
+        let mut requirement = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("contract"), Lit::Str(lit)) => contract = Some(lit.parse()?),
+                (Some("min_balance"), Lit::Str(lit)) if requirement.is_none() => {
+                    requirement = Some(TokenRequirementAttr::MinBalance(lit.parse()?))
+                }
+                (Some("token_id"), Lit::Str(lit)) if requirement.is_none() => {
+                    requirement = Some(TokenRequirementAttr::TokenId(lit.parse()?))
+                }
+                (Some("min_balance"), lit) | (Some("token_id"), lit) if requirement.is_some() => {
+                    return Err(Error::new(
+                        lit.span(),
+                        "Only one of `min_balance` and `token_id` can be required.",
+                    ))
+                }
+                (_, lit) => {
+                    return Err(Error::new(lit.span(), "Unsupported requires_token attribute."))
+                }
+            }
+        }
+        let contract = contract.ok_or_else(|| {
+            Error::new(
+                Span::call_site(),
+                "Requires_token attribute requires `contract = \"<field>\"`.",
+            )
+        })?;
+        // Without a requirement, holding any amount of the token is enough.
+        let requirement =
+            requirement.unwrap_or_else(|| TokenRequirementAttr::MinBalance(syn::parse_quote!(1)));
+        Ok(Self { contract, requirement })
+    }
+}
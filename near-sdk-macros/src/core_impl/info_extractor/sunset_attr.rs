@@ -0,0 +1,99 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Error, Lit, LitStr, MetaNameValue, Token};
+
+/// Arguments of the `#[sunset(after = "2023-01-01", message = "Use `storage_deposit`")]`
+/// attribute.
+pub struct SunsetAttr {
+    /// Block timestamp in nanoseconds after which the method panics.
+    pub after: u64,
+    /// Notice for integrators, e.g. the method to use instead.
+    pub message: Option<LitStr>,
+}
+
+impl Parse for SunsetAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _paren_token = parenthesized!(content in input);
+        let pairs = Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&content)?;
+        let mut after = None;
+        let mut message = None;
+        for pair in pairs {
+            match (pair.path.get_ident().map(|ident| ident.to_string()).as_deref(), pair.lit) {
+                (Some("after"), Lit::Str(lit)) => {
+                    after = Some(parse_timestamp(&lit.value()).ok_or_else(|| {
+                        Error::new(
+                            lit.span(),
+                            "Expected a date like \"2023-01-01\" or \"2023-01-01T12:00:00Z\", \
+                             or a timestamp in nanoseconds.",
+                        )
+                    })?)
+                }
+                (Some("after"), Lit::Int(lit)) => after = Some(lit.base10_parse()?),
+                (Some("message"), Lit::Str(lit)) => message = Some(lit),
+                (_, lit) => return Err(Error::new(lit.span(), "Unsupported sunset attribute.")),
+            }
+        }
+        let after = after.ok_or_else(|| {
+            Error::new(Span::call_site(), "Sunset attribute requires `after = \"<date>\"`.")
+        })?;
+        Ok(Self { after, message })
+    }
+}
+
+/// Parses a UTC date `YYYY-MM-DD` or date time `YYYY-MM-DDTHH:MM:SSZ`, or a timestamp in
+/// nanoseconds, into nanoseconds since the Unix epoch.
+fn parse_timestamp(value: &str) -> Option<u64> {
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return value.parse().ok();
+    }
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time.strip_suffix('Z')?)),
+        None => (value, None),
+    };
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if !(1970..=2500).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let seconds_of_day = match time {
+        Some(time) => {
+            let mut time = time.splitn(3, ':').map(|part| part.parse::<u64>().ok());
+            let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+            if hours > 23 || minutes > 59 || seconds > 59 {
+                return None;
+            }
+            hours * 3600 + minutes * 60 + seconds
+        }
+        None => 0,
+    };
+    let seconds = days_from_civil(year, month, day) * 86_400 + seconds_of_day;
+    seconds.checked_mul(1_000_000_000)
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar, for years from 1970.
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_timestamp;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01"), Some(0));
+        assert_eq!(parse_timestamp("2023-01-01"), Some(1_672_531_200_000_000_000));
+        assert_eq!(parse_timestamp("2024-02-29T12:30:15Z"), Some(1_709_209_815_000_000_000));
+        assert_eq!(parse_timestamp("1672531200000000000"), Some(1_672_531_200_000_000_000));
+        assert_eq!(parse_timestamp("2023-13-01"), None);
+        assert_eq!(parse_timestamp("2023-01-01T12:00:00"), None);
+        assert_eq!(parse_timestamp("tomorrow"), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core_impl::utils;
 use crate::{BindgenArgType, ImplItemMethodInfo, InputStructType, MethodType, SerializerType};
 
 use proc_macro2::TokenStream as TokenStream2;
@@ -29,7 +30,9 @@ impl ImplItemMethodInfo {
     ///     },
     ///     callbacks: vec![],
     ///     callbacks_vec: None,
-    ///     result: Some(Result < IsOk, Error > ::schema_container())
+    ///     result: Some(Result < IsOk, Error > ::schema_container()),
+    ///     min_deposit: None,
+    ///     recommended_gas: None
     /// }
     /// ```
     /// If args are serialized with Borsh it will not include `#[derive(borsh::BorshSchema)]`.
@@ -94,6 +97,14 @@ impl ImplItemMethodInfo {
                 }
             }
         };
+        let min_deposit = match self.attr_signature_info.min_deposit {
+            Some(min_deposit) => quote! { Some(#min_deposit) },
+            None => quote! { None },
+        };
+        let recommended_gas = match self.attr_signature_info.gas_budget {
+            Some(gas) => quote! { Some(#gas) },
+            None => quote! { None },
+        };
         let result = match &self.attr_signature_info.returns {
             ReturnType::Default => {
                 quote! {
@@ -115,8 +126,131 @@ impl ImplItemMethodInfo {
                  args: #args,
                  callbacks: vec![#(#callbacks),*],
                  callbacks_vec: #callbacks_vec,
-                 result: #result
+                 result: #result,
+                 min_deposit: #min_deposit,
+                 recommended_gas: #recommended_gas
              }
         }
     }
+
+    /// Generates the call declaring the JSON arguments and result of this method in a
+    /// `near_sdk::schema::TypeScript`, or `None` for private methods, which frontends don't call.
+    ///
+    /// # Example:
+    /// The following method:
+    /// ```ignore
+    /// pub fn set_status(&mut self, message: String) -> Result<Status, Error> { }
+    /// ```
+    /// will produce this call:
+    /// ```ignore
+    /// definitions.add_method(
+    ///     "set_status",
+    ///     false,
+    ///     Some(vec![("message", <String as near_sdk::schema::NearSchema>::json_schema())]),
+    ///     Some(<Status as near_sdk::schema::NearSchema>::json_schema())
+    /// );
+    /// ```
+    pub fn typescript_method(&self) -> Option<TokenStream2> {
+        let attr_sig_info = &self.attr_signature_info;
+        if attr_sig_info.is_private {
+            return None;
+        }
+        let method_name_str = attr_sig_info.ident.to_string();
+        let is_view = matches!(&attr_sig_info.method_type, &MethodType::View);
+        let (args, result) = self.json_args_and_result();
+        let args = match args {
+            Some(args) => quote! { Some(vec![#(#args),*]) },
+            None => quote! { None },
+        };
+        Some(quote! {
+            definitions.add_method(#method_name_str, #is_view, #args, #result);
+        })
+    }
+
+    /// Generates the call adding this method to a `near_sdk::schema::Abi`.
+    ///
+    /// # Example:
+    /// The following method:
+    /// ```ignore
+    /// #[payable]
+    /// pub fn set_status(&mut self, message: String) { }
+    /// ```
+    /// will produce this call:
+    /// ```ignore
+    /// abi.add_method(near_sdk::schema::AbiMethod {
+    ///     name: "set_status".to_string(),
+    ///     is_view: false,
+    ///     is_init: false,
+    ///     is_payable: true,
+    ///     is_private: false,
+    ///     params: Some(near_sdk::schema::AbiMethod::params_schema(vec![
+    ///         ("message", <String as near_sdk::schema::NearSchema>::json_schema())
+    ///     ])),
+    ///     result: None
+    /// });
+    /// ```
+    pub fn abi_method(&self) -> TokenStream2 {
+        let attr_sig_info = &self.attr_signature_info;
+        let method_name_str = attr_sig_info.ident.to_string();
+        let is_view = matches!(&attr_sig_info.method_type, &MethodType::View);
+        let is_init =
+            matches!(&attr_sig_info.method_type, &MethodType::Init | &MethodType::InitIgnoreState);
+        let is_payable = attr_sig_info.is_payable;
+        let is_private = attr_sig_info.is_private;
+        let (args, result) = self.json_args_and_result();
+        let params = match args {
+            Some(args) => {
+                quote! { Some(near_sdk::schema::AbiMethod::params_schema(vec![#(#args),*])) }
+            }
+            None => quote! { None },
+        };
+        quote! {
+            abi.add_method(near_sdk::schema::AbiMethod {
+                name: #method_name_str.to_string(),
+                is_view: #is_view,
+                is_init: #is_init,
+                is_payable: #is_payable,
+                is_private: #is_private,
+                params: #params,
+                result: #result
+            });
+        }
+    }
+
+    /// Returns the names and schemas of the JSON arguments, `None` if the method takes no JSON
+    /// arguments, and the optional schema of the JSON result.
+    fn json_args_and_result(&self) -> (Option<Vec<TokenStream2>>, TokenStream2) {
+        let attr_sig_info = &self.attr_signature_info;
+        let is_init =
+            matches!(&attr_sig_info.method_type, &MethodType::Init | &MethodType::InitIgnoreState);
+        let args: Vec<_> = attr_sig_info
+            .input_args()
+            .map(|arg| {
+                let name = arg.ident.to_string();
+                let ty = &arg.ty;
+                quote! { (#name, <#ty as near_sdk::schema::NearSchema>::json_schema()) }
+            })
+            .collect();
+        let args = match &attr_sig_info.input_serializer {
+            SerializerType::JSON if !args.is_empty() => Some(args),
+            _ => None,
+        };
+        let result = match &attr_sig_info.returns {
+            ReturnType::Type(_, ty)
+                if !is_init && matches!(attr_sig_info.result_serializer, SerializerType::JSON) =>
+            {
+                let ty = match utils::extract_ok_type(ty) {
+                    Some(ok_type) if attr_sig_info.is_returns_result => ok_type,
+                    _ => ty,
+                };
+                if utils::type_is_owned_data(ty) {
+                    quote! { Some(<#ty as near_sdk::schema::NearSchema>::json_schema()) }
+                } else {
+                    quote! { None }
+                }
+            }
+            _ => quote! { None },
+        };
+        (args, result)
+    }
 }
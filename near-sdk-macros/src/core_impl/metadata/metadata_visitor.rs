@@ -2,17 +2,19 @@
 //! it decorates. Note, that this in an inner attribute. For it to work we should be
 //! able to visit every method in the module intended to be a contract method.
 //! For this we implement the visitor.
-use crate::ItemImplInfo;
+use crate::{AbiEventAttr, ItemImplInfo};
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
 use syn::visit::Visit;
-use syn::{Error, ItemImpl};
+use syn::{Attribute, Error, Ident, ItemEnum, ItemImpl, ItemStruct};
 
 /// Information relevant to metadata extracted from the `impl` section decorated with `#[near_bindgen]`.
 #[derive(Default)]
 pub struct MetadataVisitor {
     impl_item_infos: Vec<ItemImplInfo>,
+    /// Event data types marked with `#[abi_event]`.
+    abi_events: Vec<(Ident, AbiEventAttr)>,
     /// Errors that occured while extracting the data.
     errors: Vec<Error>,
 }
@@ -31,6 +33,16 @@ impl<'ast> Visit<'ast> for MetadataVisitor {
         }
         syn::visit::visit_item_impl(self, i);
     }
+
+    fn visit_item_struct(&mut self, i: &'ast ItemStruct) {
+        self.visit_abi_event(&i.ident, &i.attrs);
+        syn::visit::visit_item_struct(self, i);
+    }
+
+    fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
+        self.visit_abi_event(&i.ident, &i.attrs);
+        syn::visit::visit_item_enum(self, i);
+    }
 }
 
 impl MetadataVisitor {
@@ -38,6 +50,18 @@ impl MetadataVisitor {
         Default::default()
     }
 
+    fn visit_abi_event(&mut self, ident: &Ident, attrs: &[Attribute]) {
+        let attr = attrs.iter().find(|attr| {
+            attr.path.segments.last().map_or(false, |segment| segment.ident == "abi_event")
+        });
+        if let Some(attr) = attr {
+            match syn::parse2::<AbiEventAttr>(attr.tokens.clone()) {
+                Ok(abi_event) => self.abi_events.push((ident.clone(), abi_event)),
+                Err(err) => self.errors.push(err),
+            }
+        }
+    }
+
     pub fn generate_metadata_method(&self) -> syn::Result<TokenStream2> {
         if !self.errors.is_empty() {
             return Err(self.errors[0].clone());
@@ -51,7 +75,16 @@ impl MetadataVisitor {
             .flat_map(|i| &i.methods)
             .map(|m| m.metadata_struct())
             .collect();
+        let typescript = if cfg!(feature = "typescript") {
+            self.generate_typescript_test()
+        } else {
+            TokenStream2::new()
+        };
+        let abi =
+            if cfg!(feature = "abi") { self.generate_abi_method() } else { TokenStream2::new() };
         Ok(quote! {
+            #typescript
+            #abi
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn metadata() {
@@ -65,6 +98,61 @@ impl MetadataVisitor {
             }
         })
     }
+
+    /// Generates the test which writes the TypeScript declarations of the public methods to
+    /// `<crate name>.d.ts` in the crate directory, or to the path in `NEAR_TYPESCRIPT_OUT`.
+    pub fn generate_typescript_test(&self) -> TokenStream2 {
+        let methods: Vec<TokenStream2> = self
+            .impl_item_infos
+            .iter()
+            .flat_map(|i| &i.methods)
+            .filter_map(|m| m.typescript_method())
+            .collect();
+        quote! {
+            #[cfg(all(test, not(target_arch = "wasm32")))]
+            #[test]
+            fn __near_typescript_definitions() {
+                let mut definitions = near_sdk::schema::TypeScript::new();
+                #(#methods)*
+                definitions
+                    .write_output(concat!(env!("CARGO_MANIFEST_DIR"), "/", env!("CARGO_PKG_NAME"), ".d.ts"))
+                    .expect("Failed to write the TypeScript definitions");
+            }
+        }
+    }
+
+    /// Generates the `__contract_abi` view method returning the JSON `near_sdk::schema::Abi` of
+    /// all methods and of the events marked with `#[abi_event]`.
+    pub fn generate_abi_method(&self) -> TokenStream2 {
+        let methods: Vec<TokenStream2> =
+            self.impl_item_infos.iter().flat_map(|i| &i.methods).map(|m| m.abi_method()).collect();
+        let events: Vec<TokenStream2> = self
+            .abi_events
+            .iter()
+            .map(|(ident, abi_event)| {
+                let AbiEventAttr { standard, version, event } = abi_event;
+                quote! {
+                    abi.add_event(near_sdk::schema::AbiEvent {
+                        standard: #standard.to_string(),
+                        version: #version.to_string(),
+                        event: #event.to_string(),
+                        data: <#ident as near_sdk::schema::NearSchema>::json_schema()
+                    });
+                }
+            })
+            .collect();
+        quote! {
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn __contract_abi() {
+                near_sdk::env::setup_panic_hook();
+                let mut abi = near_sdk::schema::Abi::new();
+                #(#methods)*
+                #(#events)*
+                near_sdk::env::value_return(abi.to_json().as_bytes());
+            }
+        }
+    }
 }
 
 #[rustfmt::skip]
@@ -108,7 +196,9 @@ mod tests {
                         args: None,
                         callbacks: vec![],
                         callbacks_vec: None,
-                        result: None
+                        result: None,
+                        min_deposit: None,
+                        recommended_gas: None
                     },
                     near_sdk::MethodMetadata {
                         name: "f2".to_string(),
@@ -127,7 +217,9 @@ mod tests {
                         },
                         callbacks: vec![],
                         callbacks_vec: None,
-                        result: None
+                        result: None,
+                        min_deposit: None,
+                        recommended_gas: None
                     },
                     near_sdk::MethodMetadata {
                         name: "f3".to_string(),
@@ -146,7 +238,9 @@ mod tests {
                         },
                         callbacks: vec![],
                         callbacks_vec: None,
-                        result: Some(Result < IsOk, Error > ::schema_container())
+                        result: Some(Result < IsOk, Error > ::schema_container()),
+                        min_deposit: None,
+                        recommended_gas: None
                     }
                 ]);
                 let data = near_sdk::borsh::BorshSerialize::try_to_vec(&metadata)
@@ -156,4 +250,99 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn typescript_definitions() {
+        let code = quote! {
+            #[near_bindgen]
+            impl Hello {
+                pub fn get(&self, account_id: AccountId) -> Option<Status> { }
+                #[result_serializer(borsh)]
+                pub fn set(&mut self, message: &str) -> Vec<u8> { }
+                #[private]
+                pub fn on_set(&mut self, #[callback_unwrap] status: Status) { }
+            }
+        };
+
+        let file: syn::File = syn::parse2(code).unwrap();
+
+        let mut visitor = MetadataVisitor::new();
+        visitor.visit_file(&file);
+
+        let actual = visitor.generate_typescript_test();
+        let expected = quote!(
+            #[cfg(all(test, not(target_arch = "wasm32")))]
+            #[test]
+            fn __near_typescript_definitions() {
+                let mut definitions = near_sdk::schema::TypeScript::new();
+                definitions.add_method("get", true, Some(vec![("account_id", <AccountId as near_sdk::schema::NearSchema>::json_schema())]), Some(<Option<Status> as near_sdk::schema::NearSchema>::json_schema()));
+                definitions.add_method("set", false, Some(vec![("message", <str as near_sdk::schema::NearSchema>::json_schema())]), None);
+                definitions
+                    .write_output(concat!(env!("CARGO_MANIFEST_DIR"), "/", env!("CARGO_PKG_NAME"), ".d.ts"))
+                    .expect("Failed to write the TypeScript definitions");
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn abi_method() {
+        let code = quote! {
+            #[abi_event(standard = "nep141", version = "1.0.0", event = "ft_mint")]
+            #[derive(Serialize, NearSchema)]
+            pub struct FtMint {
+                owner_id: AccountId,
+                amount: U128,
+            }
+
+            #[near_bindgen]
+            impl Hello {
+                #[payable]
+                pub fn set(&mut self, message: String) { }
+                #[private]
+                pub fn on_set(&mut self, #[callback_unwrap] status: Status) -> Status { }
+            }
+        };
+
+        let file: syn::File = syn::parse2(code).unwrap();
+
+        let mut visitor = MetadataVisitor::new();
+        visitor.visit_file(&file);
+
+        let actual = visitor.generate_abi_method();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn __contract_abi() {
+                near_sdk::env::setup_panic_hook();
+                let mut abi = near_sdk::schema::Abi::new();
+                abi.add_method(near_sdk::schema::AbiMethod {
+                    name: "set".to_string(),
+                    is_view: false,
+                    is_init: false,
+                    is_payable: true,
+                    is_private: false,
+                    params: Some(near_sdk::schema::AbiMethod::params_schema(vec![("message", <String as near_sdk::schema::NearSchema>::json_schema())])),
+                    result: None
+                });
+                abi.add_method(near_sdk::schema::AbiMethod {
+                    name: "on_set".to_string(),
+                    is_view: false,
+                    is_init: false,
+                    is_payable: false,
+                    is_private: true,
+                    params: None,
+                    result: Some(<Status as near_sdk::schema::NearSchema>::json_schema())
+                });
+                abi.add_event(near_sdk::schema::AbiEvent {
+                    standard: "nep141".to_string(),
+                    version: "1.0.0".to_string(),
+                    event: "ft_mint".to_string(),
+                    data: <FtMint as near_sdk::schema::NearSchema>::json_schema()
+                });
+                near_sdk::env::value_return(abi.to_json().as_bytes());
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
 }
@@ -0,0 +1,133 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Type};
+
+/// Generates the `Migrate` implementation of an enum with a variant per version of a type, from
+/// the oldest to the current one, each holding the type of that version. Old versions are
+/// upgraded by calling `FromPreviousVersion::from_previous_version` of each later version in turn.
+pub fn generate_migrate(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => return Err(Error::new(input.span(), "Migrate can only be derived for enums.")),
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new(input.span(), "Migrate requires at least one variant."));
+    }
+    if data.variants.len() > 256 {
+        return Err(Error::new(input.span(), "Migrate supports at most 256 variants."));
+    }
+    let mut variants = vec![];
+    let mut types: Vec<&Type> = vec![];
+    for variant in data.variants.iter() {
+        if let Some((_, discriminant)) = &variant.discriminant {
+            return Err(Error::new(
+                discriminant.span(),
+                "Migrate variants can't have explicit discriminants.",
+            ));
+        }
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                variants.push(&variant.ident);
+                types.push(&fields.unnamed[0].ty);
+            }
+            _ => {
+                return Err(Error::new(
+                    variant.span(),
+                    "Migrate variants must hold exactly one unnamed field.",
+                ))
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let current_variant = variants[variants.len() - 1];
+    let current = types[types.len() - 1];
+    let current_version = (variants.len() - 1) as u8;
+    let upgrades = variants.windows(2).map(|pair| {
+        let (variant, next) = (pair[0], pair[1]);
+        quote! {
+            #name::#variant(previous) => ::core::convert::From::from(#name::#next(
+                near_sdk::FromPreviousVersion::from_previous_version(previous),
+            )),
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics near_sdk::Migrate for #name #ty_generics #where_clause {
+            type Current = #current;
+            const CURRENT_VERSION: u8 = #current_version;
+        }
+
+        impl #impl_generics ::core::convert::From<#current> for #name #ty_generics #where_clause {
+            fn from(current: #current) -> Self {
+                #name::#current_variant(current)
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for #current #where_clause {
+            fn from(versioned: #name #ty_generics) -> Self {
+                match versioned {
+                    #(#upgrades)*
+                    #name::#current_variant(current) => current,
+                }
+            }
+        }
+    })
+}
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn chains_versions() {
+        let input: DeriveInput = parse_quote! {
+            enum VersionedContract {
+                V1(ContractV1),
+                V2(ContractV2),
+                Current(Contract),
+            }
+        };
+        let actual = generate_migrate(&input).unwrap();
+        let expected = quote!(
+            impl near_sdk::Migrate for VersionedContract {
+                type Current = Contract;
+                const CURRENT_VERSION: u8 = 2u8;
+            }
+
+            impl ::core::convert::From<Contract> for VersionedContract {
+                fn from(current: Contract) -> Self {
+                    VersionedContract::Current(current)
+                }
+            }
+
+            impl ::core::convert::From<VersionedContract> for Contract {
+                fn from(versioned: VersionedContract) -> Self {
+                    match versioned {
+                        VersionedContract::V1(previous) => ::core::convert::From::from(VersionedContract::V2(
+                            near_sdk::FromPreviousVersion::from_previous_version(previous),
+                        )),
+                        VersionedContract::V2(previous) => ::core::convert::From::from(VersionedContract::Current(
+                            near_sdk::FromPreviousVersion::from_previous_version(previous),
+                        )),
+                        VersionedContract::Current(current) => current,
+                    }
+                }
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn rejects_named_fields() {
+        let input: DeriveInput = parse_quote! {
+            enum VersionedContract {
+                V1 { owner: String },
+            }
+        };
+        assert!(generate_migrate(&input).is_err());
+    }
+}
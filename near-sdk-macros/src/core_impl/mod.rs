@@ -1,7 +1,11 @@
 mod code_generator;
 mod info_extractor;
 mod metadata;
+mod migrate;
+mod schema;
 mod utils;
 pub use code_generator::*;
 pub use info_extractor::*;
 pub use metadata::metadata_visitor::MetadataVisitor;
+pub use migrate::generate_migrate;
+pub use schema::generate_near_schema;
@@ -0,0 +1,365 @@
+use inflector::Inflector;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, Error, Fields, GenericParam, Lit, Meta, NestedMeta,
+    Type,
+};
+
+/// Serde attributes which affect the JSON representation of a container, field or variant.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    optional: bool,
+}
+
+impl SerdeAttrs {
+    fn new(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("serde")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                other => return Err(Error::new(other.span(), "Expected `#[serde(...)]`.")),
+            };
+            for nested in list.nested {
+                let meta = match nested {
+                    NestedMeta::Meta(meta) => meta,
+                    NestedMeta::Lit(lit) => {
+                        return Err(Error::new(
+                            lit.span(),
+                            "Unexpected literal in serde attribute.",
+                        ))
+                    }
+                };
+                let name = meta.path().get_ident().map(|ident| ident.to_string());
+                match (name.as_deref(), &meta) {
+                    (Some("rename"), Meta::NameValue(nv)) => {
+                        result.rename = Some(lit_str(&nv.lit)?)
+                    }
+                    (Some("rename_all"), Meta::NameValue(nv)) => {
+                        let rename_all = lit_str(&nv.lit)?;
+                        if rename(&rename_all, "Name").is_none() {
+                            return Err(Error::new(
+                                nv.lit.span(),
+                                "Unsupported `rename_all` case.",
+                            ));
+                        }
+                        result.rename_all = Some(rename_all);
+                    }
+                    (Some("skip") | Some("skip_serializing"), Meta::Path(_)) => result.skip = true,
+                    (Some("default"), _) | (Some("skip_serializing_if"), _) => {
+                        result.optional = true
+                    }
+                    (
+                        Some("tag")
+                        | Some("content")
+                        | Some("untagged")
+                        | Some("flatten")
+                        | Some("transparent")
+                        | Some("with")
+                        | Some("serialize_with")
+                        | Some("deserialize_with")
+                        | Some("remote")
+                        | Some("into")
+                        | Some("from")
+                        | Some("try_from"),
+                        _,
+                    ) => {
+                        return Err(Error::new(
+                            meta.span(),
+                            "NearSchema does not support this serde attribute, implement \
+                             `near_sdk::schema::NearSchema` manually instead.",
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn lit_str(lit: &Lit) -> syn::Result<String> {
+    match lit {
+        Lit::Str(lit) => Ok(lit.value()),
+        _ => Err(Error::new(lit.span(), "Expected a string literal.")),
+    }
+}
+
+/// Applies a serde `rename_all` rule to a field or variant name.
+fn rename(rule: &str, name: &str) -> Option<String> {
+    Some(match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => name.to_pascal_case(),
+        "camelCase" => name.to_camel_case(),
+        "snake_case" => name.to_snake_case(),
+        "SCREAMING_SNAKE_CASE" => name.to_screaming_snake_case(),
+        "kebab-case" => name.to_kebab_case(),
+        "SCREAMING-KEBAB-CASE" => name.to_kebab_case().to_uppercase(),
+        _ => return None,
+    })
+}
+
+/// Joins the doc comments of an item into a description.
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => lit_str(&nv.lit).ok(),
+            _ => None,
+        })
+        .map(|line| line.trim().to_string())
+        .collect();
+    let description = lines.join("\n").trim().to_string();
+    if description.is_empty() {
+        None
+    } else {
+        Some(description)
+    }
+}
+
+fn with_description(schema: TokenStream2, attrs: &[Attribute]) -> TokenStream2 {
+    match description(attrs) {
+        Some(description) => quote! {
+            near_sdk::schema::describe(#schema, #description)
+        },
+        None => schema,
+    }
+}
+
+fn type_is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => segment.ident == "Option",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Schema of the fields of a struct or enum variant.
+fn fields_schema(fields: &Fields, rename_all: Option<&str>) -> syn::Result<TokenStream2> {
+    match fields {
+        Fields::Named(fields) => {
+            let mut properties = vec![];
+            let mut required = vec![];
+            for field in fields.named.iter() {
+                let attrs = SerdeAttrs::new(&field.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                let ident = field.ident.as_ref().unwrap().to_string();
+                let name = match (attrs.rename, rename_all) {
+                    (Some(name), _) => name,
+                    (None, Some(rule)) => rename(rule, &ident).unwrap(),
+                    (None, None) => ident,
+                };
+                let ty = &field.ty;
+                let schema = with_description(
+                    quote! { <#ty as near_sdk::schema::NearSchema>::json_schema() },
+                    &field.attrs,
+                );
+                if !attrs.optional && !type_is_option(ty) {
+                    required.push(name.clone());
+                }
+                properties.push(quote! {
+                    properties.insert(#name.to_string(), #schema);
+                });
+            }
+            Ok(quote! {
+                {
+                    let mut properties = near_sdk::serde_json::Map::new();
+                    #(#properties)*
+                    near_sdk::serde_json::json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": [#(#required),*],
+                        "additionalProperties": false,
+                    })
+                }
+            })
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            Ok(quote! { <#ty as near_sdk::schema::NearSchema>::json_schema() })
+        }
+        Fields::Unnamed(fields) => {
+            let items = fields.unnamed.iter().map(|field| {
+                let ty = &field.ty;
+                quote! { <#ty as near_sdk::schema::NearSchema>::json_schema() }
+            });
+            let len = fields.unnamed.len();
+            Ok(quote! {
+                near_sdk::serde_json::json!({
+                    "type": "array",
+                    "items": [#(#items),*],
+                    "minItems": #len,
+                    "maxItems": #len,
+                })
+            })
+        }
+        Fields::Unit => Ok(quote! { near_sdk::serde_json::json!({ "type": "null" }) }),
+    }
+}
+
+/// Generates the `NearSchema` implementation describing the serde JSON representation of the type.
+pub fn generate_near_schema(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container_attrs = SerdeAttrs::new(&input.attrs)?;
+    let rename_all = container_attrs.rename_all.as_deref();
+    let schema = match &input.data {
+        Data::Struct(data) => fields_schema(&data.fields, rename_all)?,
+        Data::Enum(data) => {
+            let mut unit_names = vec![];
+            let mut variants = vec![];
+            for variant in data.variants.iter() {
+                let attrs = SerdeAttrs::new(&variant.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                let ident = variant.ident.to_string();
+                let name = match (attrs.rename, rename_all) {
+                    (Some(name), _) => name,
+                    (None, Some(rule)) => rename(rule, &ident).unwrap(),
+                    (None, None) => ident,
+                };
+                // Enums are externally tagged by default in serde.
+                let schema = if let Fields::Unit = variant.fields {
+                    unit_names.push(name.clone());
+                    quote! { near_sdk::serde_json::json!({ "enum": [#name] }) }
+                } else {
+                    let inner = fields_schema(&variant.fields, None)?;
+                    quote! {
+                        near_sdk::serde_json::json!({
+                            "type": "object",
+                            "properties": { #name: (#inner) },
+                            "required": [#name],
+                            "additionalProperties": false,
+                        })
+                    }
+                };
+                variants.push(with_description(schema, &variant.attrs));
+            }
+            if !unit_names.is_empty()
+                && unit_names.len() == variants.len()
+                && data.variants.iter().all(|variant| description(&variant.attrs).is_none())
+            {
+                quote! {
+                    near_sdk::serde_json::json!({ "type": "string", "enum": [#(#unit_names),*] })
+                }
+            } else {
+                quote! {
+                    near_sdk::serde_json::json!({ "oneOf": [#(#variants),*] })
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(input.span(), "NearSchema can not be derived for unions."))
+        }
+    };
+
+    let name = &input.ident;
+    let title = container_attrs.rename.unwrap_or_else(|| name.to_string());
+    let schema =
+        with_description(quote! { near_sdk::schema::with_title(#schema, #title) }, &input.attrs);
+
+    let mut generics = input.generics.clone();
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(near_sdk::schema::NearSchema));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics near_sdk::schema::NearSchema for #name #ty_generics #where_clause {
+            fn json_schema() -> near_sdk::serde_json::Value {
+                #schema
+            }
+        }
+    })
+}
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_struct() {
+        let input: DeriveInput = parse_quote! {
+            /// Arguments of a transfer.
+            #[serde(rename_all = "camelCase")]
+            struct Transfer {
+                receiver_id: AccountId,
+                /// Amount in yocto.
+                amount: U128,
+                memo: Option<String>,
+                #[serde(skip)]
+                internal: u8,
+            }
+        };
+        let actual = generate_near_schema(&input).unwrap();
+        let expected = quote! {
+            impl near_sdk::schema::NearSchema for Transfer {
+                fn json_schema() -> near_sdk::serde_json::Value {
+                    near_sdk::schema::describe(near_sdk::schema::with_title({
+                        let mut properties = near_sdk::serde_json::Map::new();
+                        properties.insert("receiverId".to_string(), <AccountId as near_sdk::schema::NearSchema>::json_schema());
+                        properties.insert("amount".to_string(), near_sdk::schema::describe(<U128 as near_sdk::schema::NearSchema>::json_schema(), "Amount in yocto."));
+                        properties.insert("memo".to_string(), <Option<String> as near_sdk::schema::NearSchema>::json_schema());
+                        near_sdk::serde_json::json!({
+                            "type": "object",
+                            "properties": properties,
+                            "required": ["receiverId", "amount"],
+                            "additionalProperties": false,
+                        })
+                    }, "Transfer"), "Arguments of a transfer.")
+                }
+            }
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn unit_enum_with_generics() {
+        let input: DeriveInput = parse_quote! {
+            #[serde(rename_all = "snake_case")]
+            enum Status { NotStarted, InProgress }
+        };
+        let actual = generate_near_schema(&input).unwrap();
+        let expected = quote! {
+            impl near_sdk::schema::NearSchema for Status {
+                fn json_schema() -> near_sdk::serde_json::Value {
+                    near_sdk::schema::with_title(near_sdk::serde_json::json!({ "type": "string", "enum": ["not_started", "in_progress"] }), "Status")
+                }
+            }
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let input: DeriveInput = parse_quote! {
+            struct Wrapper<T>(T);
+        };
+        let actual = generate_near_schema(&input).unwrap();
+        let expected = quote! {
+            impl<T: near_sdk::schema::NearSchema> near_sdk::schema::NearSchema for Wrapper<T> {
+                fn json_schema() -> near_sdk::serde_json::Value {
+                    near_sdk::schema::with_title(<T as near_sdk::schema::NearSchema>::json_schema(), "Wrapper")
+                }
+            }
+        };
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn unsupported_serde_attributes() {
+        let input: DeriveInput = parse_quote! {
+            #[serde(tag = "type")]
+            enum Action { Transfer { amount: U128 } }
+        };
+        assert!(generate_near_schema(&input).is_err());
+    }
+}
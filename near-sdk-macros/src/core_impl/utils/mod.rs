@@ -1,3 +1,4 @@
+use quote::ToTokens;
 use syn::{GenericArgument, Path, PathArguments, Type};
 
 /// Checks whether the given path is literally "Result".
@@ -39,3 +40,13 @@ pub(crate) fn extract_ok_type(ty: &Type) -> Option<&Type> {
         _ => None,
     }
 }
+
+/// Checks whether the type can be deserialized into an owned value by an off-chain client, i.e.
+/// it does not contain references, `Self` or `impl Trait` types.
+pub(crate) fn type_is_owned_data(ty: &Type) -> bool {
+    let ty_str = ty.to_token_stream().to_string();
+    !ty_str.contains('&')
+        && !ty_str
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|t| t == "Self" || t == "impl")
+}
@@ -9,7 +9,7 @@ use self::core_impl::*;
 use proc_macro2::Span;
 use quote::quote;
 use syn::visit::Visit;
-use syn::{File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
+use syn::{DeriveInput, File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
 
 #[proc_macro_attribute]
 pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -29,10 +29,14 @@ pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
         let generated_code = item_impl_info.wrapper_code();
         // Add helper type for simulation testing only if not wasm32
         let marshalled_code = item_impl_info.marshall_code();
+        let args_structs = item_impl_info.args_structs_code();
+        let batch_view = item_impl_info.batch_view_code();
         TokenStream::from(quote! {
             #marshalled_code
             #input
             #generated_code
+            #args_structs
+            #batch_view
         })
     } else {
         TokenStream::from(
@@ -45,6 +49,25 @@ pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// Generates a module, named after the trait in snake case or given as argument, to call the
+/// methods of the trait on another contract.
+///
+/// The calls are built with `ext(account_id)`, which attaches no deposit unless set. The gas
+/// must be set, otherwise the call panics:
+/// ```ignore
+/// ext_ft_receiver::ext(receiver_id)
+///     .with_attached_deposit(0)
+///     .with_static_gas(gas)
+///     .ft_on_transfer(sender_id, amount, msg)
+/// ```
+/// `with_unused_gas(reserved)` attaches the gas left after keeping `reserved` for the current
+/// call, see `Gas::remaining_after`, and `with_unused_gas_weight(reserved, weight, total_weight)`
+/// a share of it, see `Gas::weighted_share`, e.g. to split it between calls created one after
+/// the other. The gas left is read when the method of the builder is called, and added to the
+/// static gas if both are set.
+///
+/// The module also has a function per method, which takes the account, deposit and gas as
+/// trailing arguments. Prefer the builder, which names them.
 #[proc_macro_attribute]
 pub fn ext_contract(attr: TokenStream, item: TokenStream) -> TokenStream {
     if let Ok(mut input) = syn::parse::<ItemTrait>(item) {
@@ -91,6 +114,16 @@ pub fn callback_vec(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// `abi_event` is a marker attribute it does not generate code by itself.
+///
+/// Placed on the type of the data of a NEP-297 event inside `metadata!{}`, as
+/// `#[abi_event(standard = "nep141", version = "1.0.0", event = "ft_mint")]`, it adds the event
+/// to the ABI generated with the `abi` feature.
+#[proc_macro_attribute]
+pub fn abi_event(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /// `serializer` is a marker attribute it does not generate code by itself.
 #[proc_macro_attribute]
 pub fn serializer(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -109,7 +142,49 @@ pub fn init(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// `invariant` is a marker attribute for `impl` sections, it does not generate code by itself.
+///
+/// When placed below `#[near_bindgen]` as `#[invariant(check = "self.assert_invariants()")]`,
+/// the check is run after every exported method of the section that takes `&mut self`, in
+/// builds with debug assertions (which includes unit tests). In release builds the check is
+/// omitted.
+#[proc_macro_attribute]
+pub fn invariant(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    TokenStream::from(
+        syn::Error::new(
+            Span::call_site(),
+            "`invariant` must be placed below `#[near_bindgen]` on an impl section.",
+        )
+        .to_compile_error(),
+    )
+    .into_iter()
+    .chain(item)
+    .collect()
+}
+
+/// `health_check` is a marker attribute for `impl` sections, it does not generate code by itself.
+///
+/// When placed below `#[near_bindgen]`, e.g. as
+/// `#[health_check(state_version = "self.version", paused = "self.pause")]`, a `health` view
+/// returning a `near_sdk::HealthReport` is added to the section. The `state_version`, `paused`,
+/// `pending_upgrade`, `last_config_change` and `invariants` expressions are all optional.
+#[proc_macro_attribute]
+pub fn health_check(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    TokenStream::from(
+        syn::Error::new(
+            Span::call_site(),
+            "`health_check` must be placed below `#[near_bindgen]` on an impl section.",
+        )
+        .to_compile_error(),
+    )
+    .into_iter()
+    .chain(item)
+    .collect()
+}
+
 /// `metadata` generates the metadata method and should be placed at the very end of the `lib.rs` file.
+/// With the `abi` feature it also generates the `__contract_abi` view method returning the JSON
+/// `near_sdk::schema::Abi` of the contract.
 // TODO: Once Rust allows inner attributes and custom procedural macros for modules we should switch this
 // to be `#![metadata]` attribute at the top of the contract file instead. https://github.com/rust-lang/rust/issues/54727
 #[proc_macro]
@@ -211,3 +286,36 @@ pub fn function_error(item: TokenStream) -> TokenStream {
         }
     })
 }
+
+/// `Migrate` generates implementation for `near_sdk::Migrate` trait on an enum with a variant per
+/// version of a type, from the oldest to the current one, each holding the type of its version.
+/// Old versions are upgraded into the last variant by chaining the
+/// `near_sdk::FromPreviousVersion::from_previous_version` conversions of the later versions.
+/// Variants must never be reordered or removed once the enum is persisted.
+#[proc_macro_derive(Migrate)]
+pub fn migrate(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<DeriveInput>(item) {
+        Ok(input) => input,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    match generate_migrate(&input) {
+        Ok(generated) => TokenStream::from(generated),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+/// `NearSchema` generates implementation for `near_sdk::schema::NearSchema` trait, which
+/// describes the JSON representation of the type as a JSON Schema. Field and variant names follow
+/// the `rename`, `rename_all` and `skip` serde attributes, and doc comments are included as
+/// descriptions, so wallets can render readable prompts for the arguments of custom methods.
+#[proc_macro_derive(NearSchema)]
+pub fn near_schema(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<DeriveInput>(item) {
+        Ok(input) => input,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    match generate_near_schema(&input) {
+        Ok(generated) => TokenStream::from(generated),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
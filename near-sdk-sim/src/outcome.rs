@@ -130,6 +130,22 @@ impl ExecutionResult {
         res
     }
 
+    /// Logs of each outcome since the last transaction, i.e. of the transaction and of each
+    /// receipt it caused, with the account which executed it, in the order of execution.
+    pub fn logs_per_receipt(&self) -> Vec<(AccountId, Vec<String>)> {
+        self.promise_results()
+            .into_iter()
+            .flatten()
+            .map(|result| (result.executor_id().clone(), result.logs().clone()))
+            .collect()
+    }
+
+    /// Gas burnt by all the outcomes since the last transaction, i.e. by the transaction and by
+    /// each receipt it caused, including the callbacks of cross-contract calls.
+    pub fn total_gas_burnt(&self) -> Gas {
+        Gas(self.promise_results().iter().flatten().map(|result| result.outcome.gas_burnt).sum())
+    }
+
     /// Execution status. Contains the result in case of successful execution.
     /// NOTE: Should be the latest field since it contains unparsable by light client
     /// ExecutionStatus::Failure
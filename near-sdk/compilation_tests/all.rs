@@ -20,4 +20,17 @@ fn compilation_tests() {
     t.compile_fail("compilation_tests/payable_view.rs");
     t.pass("compilation_tests/borsh_storage_key.rs");
     t.pass("compilation_tests/function_error.rs");
+    t.pass("compilation_tests/invariant.rs");
+    t.pass("compilation_tests/method_args.rs");
+    t.pass("compilation_tests/zero_copy.rs");
+    t.pass("compilation_tests/batch_view.rs");
+    t.pass("compilation_tests/requires_token.rs");
+    t.pass("compilation_tests/gas_budget.rs");
+    t.pass("compilation_tests/health_check.rs");
+    t.pass("compilation_tests/locks.rs");
+    #[cfg(feature = "estimate")]
+    t.pass("compilation_tests/estimate.rs");
+    t.pass("compilation_tests/storage_layout.rs");
+    t.compile_fail("compilation_tests/storage_layout_overlap.rs");
+    t.pass("compilation_tests/ext_builder.rs");
 }
@@ -0,0 +1,44 @@
+//! Views dispatched through the generated `batch_view` method.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, ContractError};
+use std::collections::HashMap;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Token {
+    balances: HashMap<AccountId, u64>,
+}
+
+#[near_bindgen]
+impl Token {
+    #[batch_view]
+    pub fn balance_of(&self, account_id: AccountId) -> u64 {
+        self.balances.get(&account_id).copied().unwrap_or_default()
+    }
+
+    #[batch_view]
+    pub fn total_supply(&self) -> u64 {
+        self.balances.values().sum()
+    }
+
+    #[batch_view]
+    #[return_result]
+    pub fn checked_balance_of(&self, account_id: AccountId) -> Result<u64, ContractError> {
+        self.balances
+            .get(&account_id)
+            .copied()
+            .ok_or_else(|| ContractError::account_not_registered(&account_id))
+    }
+
+    #[batch_view]
+    #[return_result]
+    pub fn positive_balance_of(&self, account_id: AccountId) -> Result<u64, String> {
+        match self.balance_of(account_id) {
+            0 => Err("The balance is zero".to_string()),
+            balance => Ok(balance),
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,42 @@
+//! Methods with a generated `estimate_<method>` view.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, FunctionError};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(FunctionError)]
+enum NoteError {
+    TooLong,
+}
+
+impl fmt::Display for NoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the note is too long")
+    }
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Notes {
+    notes: HashMap<u64, String>,
+}
+
+#[near_bindgen]
+impl Notes {
+    #[estimate]
+    pub fn add_note(&mut self, id: u64, text: String) {
+        self.notes.insert(id, text);
+    }
+
+    #[estimate]
+    #[return_result]
+    pub fn replace_note(&mut self, id: u64, text: String) -> Result<Option<String>, NoteError> {
+        if text.len() > 100 {
+            return Err(NoteError::TooLong);
+        }
+        Ok(self.notes.insert(id, text))
+    }
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+//! Cross contract calls built with the call builder of `#[ext_contract]`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{ext_contract, near_bindgen, AccountId, Gas, GasWeight, Promise};
+
+#[ext_contract(ext_calculator)]
+trait Calculator {
+    fn sum(&self, a: u64, b: u64) -> u64;
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Caller {}
+
+#[near_bindgen]
+impl Caller {
+    pub fn sum_static(&self, calculator_id: AccountId) -> Promise {
+        ext_calculator::ext(calculator_id)
+            .with_attached_deposit(1)
+            .with_static_gas(Gas::ONE_TERA * 5)
+            .sum(1, 2)
+    }
+
+    pub fn sum_twice(&self, calculator_id: AccountId) -> Promise {
+        let first = ext_calculator::ext(calculator_id.clone())
+            .with_unused_gas_weight(Gas::ONE_TERA * 5, GasWeight(1), GasWeight(2))
+            .sum(1, 2);
+        let second = ext_calculator::ext(calculator_id)
+            .with_static_gas(Gas::ONE_TERA)
+            .with_unused_gas(Gas::ONE_TERA * 5)
+            .sum(3, 4);
+        first.and(second)
+    }
+}
+
+fn main() {}
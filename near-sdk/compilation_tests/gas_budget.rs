@@ -0,0 +1,26 @@
+//! Payable methods with a minimum deposit and methods with a recommended gas budget.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use std::collections::HashMap;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Registry {
+    names: HashMap<String, AccountId>,
+}
+
+#[near_bindgen]
+impl Registry {
+    #[payable(min_deposit = 10_000_000_000_000_000_000_000)]
+    #[gas_budget(tgas = 10)]
+    pub fn register(&mut self, name: String) {
+        self.names.insert(name, env::predecessor_account_id());
+    }
+
+    #[payable]
+    #[gas_budget(gas = 5_000_000_000_000)]
+    pub fn donate(&mut self) {}
+}
+
+fn main() {}
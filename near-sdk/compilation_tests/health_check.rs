@@ -0,0 +1,60 @@
+//! Contract exposing the generated `health` view.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, InvariantCheck, PauseFlags, Timestamp};
+
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Flags {
+    paused: bool,
+}
+
+impl PauseFlags for Flags {
+    fn is_contract_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn is_feature_paused(&self, _feature: &str) -> bool {
+        false
+    }
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Vault {
+    version: u32,
+    flags: Flags,
+    config_updated_at: Option<Timestamp>,
+    total: u64,
+    deposits: Vec<u64>,
+}
+
+#[near_bindgen]
+#[health_check(
+    state_version = "self.version",
+    paused = "self.flags",
+    last_config_change = "self.config_updated_at",
+    invariants = "vec![InvariantCheck::new(\"total\", self.check_total())]"
+)]
+impl Vault {
+    pub fn deposit(&mut self, amount: u64) {
+        self.deposits.push(amount);
+        self.total += amount;
+    }
+
+    fn check_total(&self) -> Result<(), String> {
+        if self.deposits.iter().sum::<u64>() == self.total {
+            Ok(())
+        } else {
+            Err("total doesn't match the deposits".to_string())
+        }
+    }
+}
+
+fn main() {
+    let mut contract = Vault::default();
+    contract.deposit(5);
+    let report = contract.health();
+    assert!(report.healthy);
+    assert_eq!(report.state_version, Some(0.into()));
+    assert_eq!(report.pending_upgrade, None);
+}
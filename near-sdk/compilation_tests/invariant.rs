@@ -0,0 +1,44 @@
+//! Contract with invariants checked after state-mutating methods.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Incrementer {
+    value: u32,
+    total: u32,
+}
+
+#[near_bindgen]
+#[invariant(check = "self.assert_invariants()")]
+impl Incrementer {
+    pub fn inc(&mut self, by: u32) -> u32 {
+        if by == 0 {
+            return self.value;
+        }
+        self.value += by;
+        self.total += by;
+        self.value
+    }
+
+    pub fn inc_value(&mut self) {
+        self.value += 1;
+    }
+
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+
+    fn assert_invariants(&self) {
+        assert_eq!(self.value, self.total, "value must match total");
+    }
+}
+
+fn main() {
+    let mut contract = Incrementer::default();
+    assert_eq!(contract.inc(0), 0);
+    assert_eq!(contract.inc(2), 2);
+    assert_eq!(contract.get(), 2);
+    assert!(std::panic::catch_unwind(move || contract.inc_value()).is_err());
+}
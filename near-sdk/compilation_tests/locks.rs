@@ -0,0 +1,34 @@
+//! Methods locking a resource for the duration of a cross-contract workflow.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, IntentLock, Promise};
+use std::collections::HashMap;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Vault {
+    balances: HashMap<AccountId, Balance>,
+}
+
+#[near_bindgen]
+impl Vault {
+    #[locks("withdraw:{account_id}", ttl_blocks = 100)]
+    pub fn withdraw(&mut self, account_id: AccountId) -> Promise {
+        let amount = self.balances.remove(&account_id).unwrap_or_default();
+        Promise::new(account_id.clone()).transfer(amount).then(
+            Promise::new(env::current_account_id()).function_call(
+                "on_withdraw".to_string(),
+                format!("{{\"account_id\":\"{}\"}}", account_id).into_bytes(),
+                0,
+                Gas(5_000_000_000_000),
+            ),
+        )
+    }
+
+    #[private]
+    pub fn on_withdraw(&mut self, account_id: AccountId) {
+        IntentLock::default().unlock(&format!("withdraw:{}", account_id));
+    }
+}
+
+fn main() {}
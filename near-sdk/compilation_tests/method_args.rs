@@ -0,0 +1,30 @@
+//! Method argument structs generated for exported methods marked with `#[method_args]`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, MethodArgs};
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Token {}
+
+#[near_bindgen]
+impl Token {
+    #[method_args]
+    pub fn transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {}
+
+    #[method_args]
+    pub fn store(&mut self, #[serializer(borsh)] data: Vec<u8>) {}
+
+    pub fn get(&self, key: &String) -> u64 {
+        0
+    }
+}
+
+fn main() {
+    let args =
+        TransferArgs { receiver_id: "bob.near".parse().unwrap(), amount: U128(1), memo: None };
+    assert_eq!(TransferArgs::METHOD_NAME, "transfer");
+    assert_eq!(args.to_args(), br#"{"receiver_id":"bob.near","amount":"1","memo":null}"#.to_vec());
+    assert_eq!(StoreArgs { data: vec![1] }.to_args(), vec![1, 0, 0, 0, 1]);
+}
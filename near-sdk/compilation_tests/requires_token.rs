@@ -0,0 +1,49 @@
+//! Methods guarded by the tokens held by the caller.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, Balance, TokenGate, TokenRequirement};
+use std::collections::HashMap;
+
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Holdings {
+    balances: HashMap<AccountId, Balance>,
+}
+
+impl TokenGate for Holdings {
+    fn holds(&self, account_id: &AccountId, requirement: &TokenRequirement) -> bool {
+        match requirement {
+            TokenRequirement::MinBalance(amount) => {
+                self.balances.get(account_id).copied().unwrap_or_default() >= *amount
+            }
+            TokenRequirement::Token(_) => false,
+        }
+    }
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Club {
+    members: Holdings,
+    posts: Vec<String>,
+}
+
+#[near_bindgen]
+impl Club {
+    #[requires_token(contract = "members")]
+    pub fn post(&mut self, text: String) {
+        self.posts.push(text);
+    }
+
+    #[requires_token(contract = "members", min_balance = "price.0")]
+    pub fn buy(&mut self, price: U128) -> U128 {
+        price
+    }
+
+    #[requires_token(contract = "members", token_id = "ticket_id")]
+    pub fn enter(&mut self, ticket_id: String) {
+        self.posts.push(ticket_id);
+    }
+}
+
+fn main() {}
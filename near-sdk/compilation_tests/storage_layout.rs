@@ -0,0 +1,30 @@
+//! Storage prefixes declared with `storage_layout!` and passed to the constructors.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+struct Registry {
+    owners: LookupMap<String, AccountId>,
+    history: Vector<String>,
+}
+
+near_sdk::storage_layout!(Registry {
+    owners: LookupMap<String, AccountId> = b"o",
+    history: Vector<String> = b"h",
+});
+
+#[near_bindgen]
+impl Registry {
+    #[init]
+    pub fn new() -> Self {
+        Self { owners: LookupMap::new(Self::owners), history: Vector::new(Self::history) }
+    }
+}
+
+fn main() {
+    assert!(Registry::storage_layout().overlaps().is_empty());
+    assert_eq!(Registry::owners, b"o");
+}
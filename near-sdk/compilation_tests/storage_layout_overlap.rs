@@ -0,0 +1,19 @@
+//! Overlapping storage prefixes fail to compile.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+struct Registry {
+    owners: LookupMap<String, AccountId>,
+    history: Vector<String>,
+}
+
+near_sdk::storage_layout!(Registry {
+    owners: LookupMap<String, AccountId> = b"o",
+    history: Vector<String> = b"ow",
+});
+
+fn main() {}
@@ -0,0 +1,46 @@
+//! Arguments borrowed from the input, Borsh methods and views streaming their result.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::JsonSeq;
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Registry {
+    names: Vector<String>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self { names: Vector::new(b"n") }
+    }
+}
+
+#[near_bindgen]
+impl Registry {
+    #[zero_copy]
+    pub fn find(&self, prefix: &str, limit: u64) -> Vec<u64> {
+        (0..self.names.len())
+            .filter(|index| self.names.get(*index).unwrap().starts_with(prefix))
+            .take(limit as usize)
+            .collect()
+    }
+
+    #[serializer(borsh)]
+    pub fn add_names(&mut self, names: Vec<String>, #[serializer(borsh)] limit: u64) {
+        for name in names.iter().take(limit as usize) {
+            self.names.push(name);
+        }
+    }
+
+    #[zero_copy]
+    pub fn names_with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> JsonSeq<impl Iterator<Item = String> + 'a> {
+        JsonSeq::new(self.names.iter().filter(move |name| name.starts_with(prefix)))
+    }
+}
+
+fn main() {}
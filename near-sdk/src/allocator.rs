@@ -0,0 +1,218 @@
+//! Bump allocator for contracts, enabled as the global allocator with the `bump_alloc` feature
+//! instead of `wee_alloc`.
+//!
+//! Every function call runs in a fresh instance of the contract, so memory never has to be
+//! reused across calls. [`BumpAlloc`] takes advantage of this by handing out memory from the end
+//! of the wasm linear memory and never freeing it, except for the most recent allocation, which
+//! makes allocations and growing the last allocated buffer (e.g. a `Vec` filled while
+//! deserializing a large input) a few instructions each. Methods which allocate and free many
+//! buffers in a loop may use more memory than with `wee_alloc`, so the allocator is opt-in.
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::ptr;
+
+/// Size of a page of the wasm linear memory.
+pub const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+#[cfg(target_arch = "wasm32")]
+mod memory {
+    use super::WASM_PAGE_SIZE;
+
+    /// End of the initial linear memory, below which are the stack and static data.
+    pub fn initial_end() -> usize {
+        core::arch::wasm32::memory_size(0) * WASM_PAGE_SIZE
+    }
+
+    /// Grows the linear memory by `pages`, returning `false` if it can't grow.
+    pub fn grow(pages: usize) -> bool {
+        core::arch::wasm32::memory_grow(0, pages) != usize::MAX
+    }
+}
+
+/// Linear memory simulated for unit tests, with one page used initially, which can grow up to
+/// [`MAX_PAGES`](memory::MAX_PAGES). Each test thread has its own memory.
+#[cfg(not(target_arch = "wasm32"))]
+mod memory {
+    use super::WASM_PAGE_SIZE;
+    use std::alloc::{alloc, Layout};
+    use std::cell::Cell;
+
+    pub const MAX_PAGES: usize = 4;
+
+    thread_local! {
+        /// Start of the memory and its number of pages.
+        static MEMORY: (usize, Cell<usize>) = {
+            let layout = Layout::from_size_align(MAX_PAGES * WASM_PAGE_SIZE, WASM_PAGE_SIZE)
+                .unwrap();
+            (unsafe { alloc(layout) } as usize, Cell::new(1))
+        };
+    }
+
+    pub fn initial_end() -> usize {
+        MEMORY.with(|(start, pages)| start + pages.get() * WASM_PAGE_SIZE)
+    }
+
+    pub fn grow(new_pages: usize) -> bool {
+        MEMORY.with(|(_, pages)| {
+            if pages.get() + new_pages > MAX_PAGES {
+                return false;
+            }
+            pages.set(pages.get() + new_pages);
+            true
+        })
+    }
+}
+
+/// Allocator which never frees memory, tuned for the lifetime of a single function call. See the
+/// [module documentation](self).
+pub struct BumpAlloc {
+    /// Start, next free address and end of the memory owned by the allocator, or zeros before the
+    /// first allocation.
+    state: UnsafeCell<(usize, usize, usize)>,
+}
+
+// Contracts are single-threaded.
+unsafe impl Sync for BumpAlloc {}
+
+impl BumpAlloc {
+    pub const INIT: Self = Self { state: UnsafeCell::new((0, 0, 0)) };
+
+    /// Number of bytes allocated so far.
+    pub fn allocated_bytes(&self) -> usize {
+        let (start, next, _) = unsafe { *self.state.get() };
+        next - start
+    }
+
+    /// Grows the memory owned by the allocator to at least `end`, returning `false` if the wasm
+    /// memory can't grow.
+    unsafe fn reserve(&self, end: usize) -> bool {
+        let state = &mut *self.state.get();
+        if end <= state.2 {
+            return true;
+        }
+        let pages = (end - state.2 + WASM_PAGE_SIZE - 1) / WASM_PAGE_SIZE;
+        if !memory::grow(pages) {
+            return false;
+        }
+        state.2 += pages * WASM_PAGE_SIZE;
+        true
+    }
+}
+
+unsafe impl GlobalAlloc for BumpAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if (*self.state.get()).0 == 0 {
+            // The stack and static data are below the initial memory size.
+            let memory_end = memory::initial_end();
+            *self.state.get() = (memory_end, memory_end, memory_end);
+        }
+        let next = (*self.state.get()).1;
+        let start = match next.checked_add(layout.align() - 1) {
+            Some(start) => start & !(layout.align() - 1),
+            None => return ptr::null_mut(),
+        };
+        let end = match start.checked_add(layout.size()) {
+            Some(end) => end,
+            None => return ptr::null_mut(),
+        };
+        if !self.reserve(end) {
+            return ptr::null_mut();
+        }
+        (*self.state.get()).1 = end;
+        start as *mut u8
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let state = &mut *self.state.get();
+        if ptr as usize + layout.size() == state.1 {
+            state.1 = ptr as usize;
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let next = (*self.state.get()).1;
+        if ptr as usize + layout.size() == next {
+            // The last allocation grows or shrinks in place.
+            let end = match (ptr as usize).checked_add(new_size) {
+                Some(end) => end,
+                None => return ptr::null_mut(),
+            };
+            if !self.reserve(end) {
+                return ptr::null_mut();
+            }
+            (*self.state.get()).1 = end;
+            return ptr;
+        }
+        let new_ptr = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test]
+    fn alignment() {
+        let alloc = BumpAlloc::INIT;
+        unsafe {
+            let a = alloc.alloc(layout(1, 1));
+            let b = alloc.alloc(layout(8, 8));
+            let c = alloc.alloc(layout(3, 64));
+            assert_eq!(a as usize % WASM_PAGE_SIZE, 0);
+            assert_eq!(b as usize, a as usize + 8);
+            assert_eq!(c as usize % 64, 0);
+            assert_eq!(c as usize, a as usize + 64);
+            assert_eq!(alloc.allocated_bytes(), 67);
+        }
+    }
+
+    #[test]
+    fn growth() {
+        let alloc = BumpAlloc::INIT;
+        unsafe {
+            let a = alloc.alloc(layout(2 * WASM_PAGE_SIZE + 1, 1));
+            assert!(!a.is_null());
+            // The memory after the initial page is owned by the allocator.
+            ptr::write_bytes(a, 1, 2 * WASM_PAGE_SIZE + 1);
+            assert!(!alloc.alloc(layout(WASM_PAGE_SIZE - 1, 1)).is_null());
+            assert!(alloc.alloc(layout(1, 1)).is_null());
+            assert!(alloc.alloc(layout(usize::MAX, 1)).is_null());
+            assert_eq!(alloc.allocated_bytes(), 3 * WASM_PAGE_SIZE);
+        }
+    }
+
+    #[test]
+    fn realloc() {
+        let alloc = BumpAlloc::INIT;
+        unsafe {
+            let a = alloc.alloc(layout(4, 4));
+            ptr::copy_nonoverlapping([1u8, 2, 3, 4].as_ptr(), a, 4);
+            // The last allocation grows and shrinks in place.
+            assert_eq!(alloc.realloc(a, layout(4, 4), 16), a);
+            assert_eq!(alloc.allocated_bytes(), 16);
+            assert_eq!(alloc.realloc(a, layout(16, 4), 8), a);
+            assert_eq!(alloc.allocated_bytes(), 8);
+
+            // Other allocations are copied to the end.
+            let b = alloc.alloc(layout(1, 1));
+            let moved = alloc.realloc(a, layout(8, 4), 32);
+            assert_eq!(moved as usize, b as usize + 4);
+            assert_eq!(*(moved as *const [u8; 4]), [1, 2, 3, 4]);
+            assert_eq!(alloc.allocated_bytes(), 44);
+
+            // Only the last allocation is freed.
+            alloc.dealloc(b, layout(1, 1));
+            assert_eq!(alloc.allocated_bytes(), 44);
+            alloc.dealloc(moved, layout(32, 4));
+            assert_eq!(alloc.allocated_bytes(), 12);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Compact storage keys for collections keyed by [`AccountId`].
+use std::convert::TryFrom;
+use std::io;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::collections::{append, LookupMap};
+use crate::{env, AccountId, IntoStorageKey};
+
+/// [`AccountId`] which is Borsh encoded with a single byte length prefix instead of the four
+/// bytes of a Borsh string, since account IDs are at most 64 bytes long. Used as the key type of
+/// collections to save 3 bytes of storage per entry, e.g. `LookupMap<CompactAccountId, Balance>`.
+///
+/// The encoding is not compatible with the one of [`AccountId`], so existing collections can't
+/// switch their key type without a migration.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CompactAccountId(pub AccountId);
+
+impl BorshSerialize for CompactAccountId {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let bytes = self.0.as_bytes();
+        let len = u8::try_from(bytes.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        writer.write_all(&[len])?;
+        writer.write_all(bytes)
+    }
+}
+
+impl BorshDeserialize for CompactAccountId {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let len = u8::deserialize(buf)? as usize;
+        if buf.len() < len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Unexpected length of input"));
+        }
+        let (bytes, rest) = buf.split_at(len);
+        *buf = rest;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|s| {
+                AccountId::try_from(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+            .map(Self)
+    }
+}
+
+impl From<AccountId> for CompactAccountId {
+    fn from(account_id: AccountId) -> Self {
+        Self(account_id)
+    }
+}
+
+impl From<&AccountId> for CompactAccountId {
+    fn from(account_id: &AccountId) -> Self {
+        Self(account_id.clone())
+    }
+}
+
+impl From<CompactAccountId> for AccountId {
+    fn from(account_id: CompactAccountId) -> Self {
+        account_id.0
+    }
+}
+
+impl AsRef<AccountId> for CompactAccountId {
+    fn as_ref(&self) -> &AccountId {
+        &self.0
+    }
+}
+
+/// Table assigning sequential `u32` ids to account IDs, so that collections can be keyed by the
+/// 4 byte id instead of the account ID, e.g. `LookupMap<u32, Balance>`.
+///
+/// Interning stores the account ID twice, to look up the id and to resolve the id back, so it
+/// pays off when the same accounts are the keys of several collections or of a
+/// [`TreeMap`](crate::collections::TreeMap), which stores its keys more than once. Ids are never
+/// reused, even if the account isn't referenced anymore.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AccountIdInterner {
+    ids: LookupMap<CompactAccountId, u32>,
+    accounts: LookupMap<u32, CompactAccountId>,
+    next_id: u32,
+}
+
+impl AccountIdInterner {
+    /// Creates a new table. Use `prefix` as a unique prefix for its keys.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            ids: LookupMap::new(append(&prefix, b'i')),
+            accounts: LookupMap::new(append(&prefix, b'a')),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the id of the account, assigning the next id if it doesn't have one yet.
+    pub fn intern(&mut self, account_id: &AccountId) -> u32 {
+        let key = CompactAccountId::from(account_id);
+        if let Some(id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.next_id;
+        self.next_id =
+            id.checked_add(1).unwrap_or_else(|| env::panic_str("Account id table is full"));
+        self.ids.insert(&key, &id);
+        self.accounts.insert(&id, &key);
+        id
+    }
+
+    /// Returns the id of the account if it was interned.
+    pub fn get_id(&self, account_id: &AccountId) -> Option<u32> {
+        self.ids.get(&CompactAccountId::from(account_id))
+    }
+
+    /// Returns the account with the given id.
+    pub fn account_id(&self, id: u32) -> Option<AccountId> {
+        self.accounts.get(&id).map(AccountId::from)
+    }
+
+    /// Number of interned accounts.
+    pub fn len(&self) -> u32 {
+        self.next_id
+    }
+
+    /// Returns `true` if no account was interned.
+    pub fn is_empty(&self) -> bool {
+        self.next_id == 0
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::accounts;
+
+    #[test]
+    fn compact_roundtrip() {
+        let key = CompactAccountId::from(accounts(0));
+        let bytes = key.try_to_vec().unwrap();
+        assert_eq!(bytes, b"\x05alice".to_vec());
+        assert_eq!(bytes.len() + 3, accounts(0).try_to_vec().unwrap().len());
+        assert_eq!(CompactAccountId::try_from_slice(&bytes).unwrap(), key);
+
+        assert!(CompactAccountId::try_from_slice(b"\x06alice").is_err());
+        assert!(CompactAccountId::try_from_slice(b"\x05Alice").is_err());
+    }
+
+    #[test]
+    fn compact_keys_in_map() {
+        let mut map = LookupMap::<CompactAccountId, u64>::new(b"m");
+        map.insert(&accounts(0).into(), &1);
+        assert_eq!(map.get(&accounts(0).into()), Some(1));
+        assert_eq!(map.get(&accounts(1).into()), None);
+    }
+
+    #[test]
+    fn interner() {
+        let mut interner = AccountIdInterner::new(b"t");
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern(&accounts(0)), 0);
+        assert_eq!(interner.intern(&accounts(1)), 1);
+        assert_eq!(interner.intern(&accounts(0)), 0);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get_id(&accounts(1)), Some(1));
+        assert_eq!(interner.get_id(&accounts(2)), None);
+        assert_eq!(interner.account_id(1), Some(accounts(1)));
+        assert_eq!(interner.account_id(2), None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Tiered storage for rarely accessed records, which stores groups of records in compressed blobs.
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::collections::{
+    append_slice, ERR_ELEMENT_DESERIALIZATION, ERR_ELEMENT_SERIALIZATION, ERR_INCONSISTENT_STATE,
+};
+use crate::{env, require, IntoStorageKey};
+
+/// Map for the long tail of rarely accessed records, e.g. the balances of dormant accounts, which
+/// stores its records in a fixed number of groups instead of a storage entry per record.
+///
+/// Each group is stored as a single compressed blob under one key, so a record takes the bytes
+/// of its key and value, with runs of zero bytes compressed, instead of the 40 bytes of the
+/// overhead of a storage entry plus its key and value. In exchange, every access reads and
+/// decompresses and every change also compresses and writes the whole group of the record, so
+/// the gas cost of an access grows with the size of the groups. Choose the number of groups so that groups hold
+/// tens of records, and keep frequently accessed records in a map like
+/// [`LookupMap`](crate::collections::LookupMap), moving them here when they become dormant and
+/// back when they are accessed again.
+///
+/// The number of groups is fixed when the map is created, records are assigned to groups by the
+/// hash of their key.
+///
+/// # Example
+///
+/// ```
+/// use near_sdk::collections::{ColdStorage, LookupMap};
+/// use near_sdk::AccountId;
+///
+/// let mut hot: LookupMap<AccountId, u128> = LookupMap::new(b"h");
+/// let mut cold: ColdStorage<u128> = ColdStorage::new(b"c", 64);
+/// let account_id: AccountId = "alice.near".parse().unwrap();
+///
+/// // Move a dormant account to cold storage.
+/// hot.insert(&account_id, &100);
+/// let balance = hot.remove(&account_id).unwrap();
+/// cold.insert(account_id.as_bytes(), &balance);
+///
+/// // Expand it again when it's accessed.
+/// let balance = cold.remove(account_id.as_bytes()).unwrap();
+/// hot.insert(&account_id, &balance);
+/// assert!(cold.is_empty());
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ColdStorage<T> {
+    prefix: Vec<u8>,
+    groups: u32,
+    len: u64,
+    #[borsh_skip]
+    el: PhantomData<T>,
+}
+
+/// Records of a group by key, with their values serialized.
+type Group = BTreeMap<Vec<u8>, Vec<u8>>;
+
+impl<T> ColdStorage<T> {
+    /// Creates a new map which stores its records in `groups` groups. Use `prefix` as a unique
+    /// prefix for its keys.
+    pub fn new<S>(prefix: S, groups: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        require!(groups > 0, "Cold storage requires at least one group");
+        Self { prefix: prefix.into_storage_key(), groups, len: 0, el: PhantomData }
+    }
+
+    /// Number of groups the records are stored in.
+    pub fn groups(&self) -> u32 {
+        self.groups
+    }
+
+    /// Number of records in the map.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn group_key(&self, key: &[u8]) -> Vec<u8> {
+        let hash = env::sha256_array(key);
+        let group = u32::from_le_bytes(hash[..4].try_into().unwrap()) % self.groups;
+        append_slice(&self.prefix, &group.to_le_bytes())
+    }
+
+    fn read_group(group_key: &[u8]) -> Group {
+        match env::storage_read(group_key) {
+            Some(blob) => decompress(&blob)
+                .and_then(|bytes| Group::try_from_slice(&bytes).ok())
+                .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)),
+            None => Group::new(),
+        }
+    }
+
+    fn write_group(group_key: &[u8], group: &Group) {
+        if group.is_empty() {
+            env::storage_remove(group_key);
+        } else {
+            let bytes = group.try_to_vec().unwrap_or_else(|_| env::abort());
+            env::storage_write(group_key, &compress(&bytes));
+        }
+    }
+
+    /// Returns `true` if the map contains a record for the key.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        Self::read_group(&self.group_key(key)).contains_key(key)
+    }
+}
+
+impl<T> ColdStorage<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn deserialize_element(raw_element: &[u8]) -> T {
+        T::try_from_slice(raw_element)
+            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
+    /// Returns the record of the key, expanding its group.
+    pub fn get(&self, key: &[u8]) -> Option<T> {
+        Self::read_group(&self.group_key(key)).get(key).map(|raw| Self::deserialize_element(raw))
+    }
+
+    /// Inserts the record of the key, returning the previous record if there was one.
+    pub fn insert(&mut self, key: &[u8], value: &T) -> Option<T> {
+        let group_key = self.group_key(key);
+        let mut group = Self::read_group(&group_key);
+        let raw_value =
+            value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+        let previous = group.insert(key.to_vec(), raw_value);
+        Self::write_group(&group_key, &group);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous.map(|raw| Self::deserialize_element(&raw))
+    }
+
+    /// Removes the record of the key, returning it if there was one.
+    pub fn remove(&mut self, key: &[u8]) -> Option<T> {
+        let group_key = self.group_key(key);
+        let mut group = Self::read_group(&group_key);
+        let previous = group.remove(key)?;
+        Self::write_group(&group_key, &group);
+        self.len -= 1;
+        Some(Self::deserialize_element(&previous))
+    }
+}
+
+/// Compresses runs of zero bytes, which make up most of the Borsh encoding of small integers
+/// and balances, as a zero byte followed by the length of the run.
+fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::with_capacity(bytes.len());
+    let mut zeros: u8 = 0;
+    for &byte in bytes {
+        if byte == 0 {
+            if zeros == u8::MAX {
+                compressed.extend_from_slice(&[0, zeros]);
+                zeros = 0;
+            }
+            zeros += 1;
+        } else {
+            if zeros > 0 {
+                compressed.extend_from_slice(&[0, zeros]);
+                zeros = 0;
+            }
+            compressed.push(byte);
+        }
+    }
+    if zeros > 0 {
+        compressed.extend_from_slice(&[0, zeros]);
+    }
+    compressed
+}
+
+/// Reverses [`compress`], returns `None` if the input isn't valid.
+fn decompress(compressed: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(compressed.len() * 2);
+    let mut iter = compressed.iter();
+    while let Some(&byte) = iter.next() {
+        if byte == 0 {
+            let zeros = *iter.next()?;
+            if zeros == 0 {
+                return None;
+            }
+            bytes.resize(bytes.len() + zeros as usize, 0);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::accounts;
+
+    #[test]
+    fn compression_roundtrip() {
+        let mut long_run = vec![1u8];
+        long_run.extend(vec![0; 600]);
+        long_run.push(2);
+        for bytes in
+            vec![vec![], vec![0], vec![7, 0, 0, 0, 8, 0], 1u128.to_le_bytes().to_vec(), long_run]
+        {
+            assert_eq!(decompress(&compress(&bytes)), Some(bytes));
+        }
+        assert_eq!(compress(&1u128.to_le_bytes()), vec![1, 0, 15]);
+        assert_eq!(decompress(&[0]), None);
+        assert_eq!(decompress(&[0, 0]), None);
+    }
+
+    #[test]
+    fn records_in_groups() {
+        let mut cold = ColdStorage::<u128>::new(b"c", 2);
+        for id in 0..6 {
+            assert_eq!(cold.insert(accounts(id).as_bytes(), &(id as u128)), None);
+        }
+        assert_eq!(cold.len(), 6);
+        assert_eq!(cold.insert(accounts(1).as_bytes(), &10), Some(1));
+        assert_eq!(cold.len(), 6);
+        assert_eq!(cold.get(accounts(1).as_bytes()), Some(10));
+        assert_eq!(cold.get(accounts(2).as_bytes()), Some(2));
+        assert!(!cold.contains_key(b"dave"));
+
+        for id in 0..6 {
+            assert!(cold.remove(accounts(id).as_bytes()).is_some());
+        }
+        assert!(cold.is_empty());
+        assert_eq!(cold.remove(accounts(0).as_bytes()), None);
+        assert!(!env::storage_has_key(&cold.group_key(accounts(0).as_bytes())));
+    }
+
+    #[test]
+    fn smaller_than_entries() {
+        let initial_storage = env::storage_usage();
+        let mut cold = ColdStorage::<u128>::new(b"c", 1);
+        for id in 0..6 {
+            cold.insert(accounts(id).as_bytes(), &1_000);
+        }
+        let cold_storage = env::storage_usage() - initial_storage;
+
+        let mut map = crate::collections::LookupMap::<Vec<u8>, u128>::new(b"m");
+        for id in 0..6 {
+            map.insert(&accounts(id).as_bytes().to_vec(), &1_000);
+        }
+        assert!(cold_storage < env::storage_usage() - initial_storage - cold_storage);
+    }
+}
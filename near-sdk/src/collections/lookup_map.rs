@@ -6,7 +6,7 @@ use std::marker::PhantomData;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::collections::append_slice;
-use crate::{env, IntoStorageKey};
+use crate::{env, IntoStorageKey, StorageUsage};
 
 const ERR_KEY_SERIALIZATION: &str = "Cannot serialize key with Borsh";
 const ERR_VALUE_DESERIALIZATION: &str = "Cannot deserialize value with Borsh";
@@ -113,6 +113,14 @@ where
             .map(|value_raw| Self::deserialize_value(&value_raw))
     }
 
+    /// Removes a key from the map like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, key: &K) -> (Option<V>, StorageUsage) {
+        super::measure_freed_storage(|| self.remove(key))
+    }
+
     /// Inserts a key-value pair into the map.
     /// If the map did not have this key present, `None` is returned. Otherwise returns
     /// a value. Note, the keys that have the same hash value are undistinguished by
@@ -304,4 +312,22 @@ mod tests {
             format!("LookupMap {{ key_prefix: {:?} }}", map.key_prefix)
         );
     }
+
+    #[test]
+    pub fn test_remove_measured() {
+        use crate::test_utils::{accounts, expect, VMContextBuilder};
+        use crate::{env, refund_freed_storage, testing_env};
+
+        testing_env!(VMContextBuilder::new().build());
+        let mut map = LookupMap::new(b"m");
+        let initial_storage = env::storage_usage();
+        map.insert(&1u64, &"value".to_string());
+        let used = env::storage_usage() - initial_storage;
+        assert_eq!(map.remove_measured(&1), (Some("value".to_string()), used));
+        assert_eq!(map.remove_measured(&1), (None, 0));
+
+        assert!(refund_freed_storage(accounts(1), 0).is_none());
+        assert!(refund_freed_storage(accounts(1), used).is_some());
+        expect().transfer(&accounts(1), env::storage_byte_cost() * used as u128);
+    }
 }
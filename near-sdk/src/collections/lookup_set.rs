@@ -6,7 +6,7 @@ use std::marker::PhantomData;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::collections::append_slice;
-use crate::{env, IntoStorageKey};
+use crate::{env, IntoStorageKey, StorageUsage};
 
 const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element with Borsh";
 
@@ -74,6 +74,14 @@ where
         self.remove_raw(&Self::serialize_element(element))
     }
 
+    /// Removes a value from the set like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, element: &T) -> (bool, StorageUsage) {
+        super::measure_freed_storage(|| self.remove(element))
+    }
+
     /// Adds a value to the set.
     /// If the set did not have this value present, `true` is returned.
     /// If the set did have this value present, `false` is returned.
@@ -86,6 +94,11 @@ where
             self.insert(&el);
         }
     }
+
+    /// Returns true if the set contains any of the elements, stopping at the first one found.
+    pub fn contains_any(&self, elements: &[T]) -> bool {
+        elements.iter().any(|element| self.contains(element))
+    }
 }
 
 impl<T> std::fmt::Debug for LookupSet<T>
@@ -214,6 +227,15 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_contains_any() {
+        let mut set = LookupSet::new(b"s");
+        set.extend(vec![1u64, 5, 9]);
+        assert!(set.contains_any(&[2, 4, 9]));
+        assert!(!set.contains_any(&[2, 4, 6]));
+        assert!(!set.contains_any(&[]));
+    }
+
     #[test]
     fn test_debug() {
         let set: LookupSet<u64> = LookupSet::new(b"m");
@@ -35,6 +35,9 @@
 //! The efficiency of `LookupMap` comes at the cost, since it has fewer methods than `HashMap` and is not
 //! that seemlessly integrated with the rest of the Rust standard library.
 
+use crate::{env, StorageUsage};
+use borsh::{BorshDeserialize, BorshSerialize};
+
 mod legacy_tree_map;
 pub use legacy_tree_map::LegacyTreeMap;
 
@@ -45,7 +48,7 @@ mod lookup_set;
 pub use lookup_set::LookupSet;
 
 pub mod vector;
-pub use vector::Vector;
+pub use vector::{paginated_range, Vector};
 
 mod unordered_map;
 pub use unordered_map::UnorderedMap;
@@ -53,16 +56,93 @@ pub use unordered_map::UnorderedMap;
 mod unordered_set;
 pub use unordered_set::UnorderedSet;
 
+mod sharded_unordered_map;
+pub use sharded_unordered_map::ShardedUnorderedMap;
+
 mod lazy_option;
 pub use lazy_option::LazyOption;
 
 mod tree_map;
 pub use tree_map::TreeMap;
 
+mod account_keys;
+pub use account_keys::{AccountIdInterner, CompactAccountId};
+
+mod cold_storage;
+pub use cold_storage::ColdStorage;
+
 pub const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart contract execution terminate unexpectedly?";
 pub const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element with Borsh.";
 pub const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element with Borsh.";
 
+/// Collections which can be cleared over several calls, when they are too large to be cleared
+/// within the gas limit of one, e.g. to reclaim the storage of a deprecated component.
+pub trait ClearBatch {
+    /// Removes up to `limit` elements, returning the number of removed elements.
+    fn clear_batch(&mut self, limit: u64) -> u64;
+
+    /// Returns `true` if all elements were removed.
+    fn is_cleared(&self) -> bool;
+}
+
+impl<T> ClearBatch for Vector<T> {
+    fn clear_batch(&mut self, limit: u64) -> u64 {
+        Vector::clear_batch(self, limit)
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<K, V> ClearBatch for UnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: BorshSerialize + BorshDeserialize,
+{
+    fn clear_batch(&mut self, limit: u64) -> u64 {
+        UnorderedMap::clear_batch(self, limit)
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<K, V> ClearBatch for ShardedUnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: BorshSerialize + BorshDeserialize,
+{
+    fn clear_batch(&mut self, limit: u64) -> u64 {
+        ShardedUnorderedMap::clear_batch(self, limit)
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> ClearBatch for UnorderedSet<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn clear_batch(&mut self, limit: u64) -> u64 {
+        UnorderedSet::clear_batch(self, limit)
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Runs `f`, returning its result along with the number of storage bytes that were freed by it.
+pub(crate) fn measure_freed_storage<R>(f: impl FnOnce() -> R) -> (R, StorageUsage) {
+    let initial_storage = env::storage_usage();
+    let result = f();
+    (result, initial_storage.saturating_sub(env::storage_usage()))
+}
+
 pub(crate) fn append(id: &[u8], chr: u8) -> Vec<u8> {
     append_slice(id, &[chr])
 }
@@ -0,0 +1,229 @@
+//! A map partitioned across several [`UnorderedMap`]s by the hash of the keys.
+use crate::collections::{append_slice, UnorderedMap};
+use crate::{env, IntoStorageKey, StorageUsage};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+const ERR_KEY_SERIALIZATION: &str = "Cannot serialize key with Borsh";
+
+/// An iterable map whose entries are partitioned across a fixed number of [`UnorderedMap`]
+/// shards by the hash of their keys, with the same API as `UnorderedMap`.
+///
+/// Very large maps are hard to maintain as a whole: clearing or migrating one takes many calls
+/// which all touch the same `len` entries of the same vectors. With shards, each shard is a
+/// separate `UnorderedMap` under its own prefix, accessible with [`shard`](Self::shard) and
+/// [`shard_mut`](Self::shard_mut), so that migrations and cleanups can process the shards
+/// independently, e.g. one shard per call or per worker.
+///
+/// The number of shards is chosen on creation and can't be changed, since it determines the shard
+/// of every key. Iteration visits the shards in order, so it doesn't follow the insertion order.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ShardedUnorderedMap<K, V> {
+    shards: Vec<UnorderedMap<K, V>>,
+}
+
+impl<K, V> ShardedUnorderedMap<K, V> {
+    /// Create new map with zero elements in `shard_count` shards. Use `prefix` as a unique
+    /// identifier. Panics if `shard_count` is zero.
+    pub fn new<S>(prefix: S, shard_count: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if shard_count == 0 {
+            env::panic_str("The number of shards must be positive");
+        }
+        let prefix = prefix.into_storage_key();
+        let shards = (0..shard_count)
+            .map(|shard| UnorderedMap::new(append_slice(&prefix, &shard_prefix(shard))))
+            .collect();
+        Self { shards }
+    }
+
+    /// Returns the number of elements in the map, also referred to as its size.
+    pub fn len(&self) -> u64 {
+        self.shards.iter().map(UnorderedMap::len).sum()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(UnorderedMap::is_empty)
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> u32 {
+        self.shards.len() as u32
+    }
+
+    /// Returns the shard with the index, or `None` if it's out of bounds.
+    pub fn shard(&self, index: u32) -> Option<&UnorderedMap<K, V>> {
+        self.shards.get(index as usize)
+    }
+
+    /// Returns the shard with the index mutably, or `None` if it's out of bounds. Entries must
+    /// only be inserted in the shard of their key, see [`shard_of`](Self::shard_of).
+    pub fn shard_mut(&mut self, index: u32) -> Option<&mut UnorderedMap<K, V>> {
+        self.shards.get_mut(index as usize)
+    }
+
+    /// Returns the shards in the order of their indices.
+    pub fn shards(&self) -> &[UnorderedMap<K, V>] {
+        &self.shards
+    }
+}
+
+impl<K, V> ShardedUnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the index of the shard of the key.
+    pub fn shard_of(&self, key: &K) -> u32 {
+        let raw_key = key.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_KEY_SERIALIZATION));
+        (fnv1a(&raw_key) % self.shards.len() as u64) as u32
+    }
+
+    fn shard_of_key(&self, key: &K) -> &UnorderedMap<K, V> {
+        &self.shards[self.shard_of(key) as usize]
+    }
+
+    fn shard_of_key_mut(&mut self, key: &K) -> &mut UnorderedMap<K, V> {
+        let index = self.shard_of(key) as usize;
+        &mut self.shards[index]
+    }
+
+    /// Returns the value corresponding to the key.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.shard_of_key(key).get(key)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the
+    /// map.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.shard_of_key_mut(key).remove(key)
+    }
+
+    /// Removes a key from the map like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, key: &K) -> (Option<V>, StorageUsage) {
+        self.shard_of_key_mut(key).remove_measured(key)
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map did not have this key present, `None` is returned. Otherwise returns
+    /// a value.
+    pub fn insert(&mut self, key: &K, value: &V) -> Option<V> {
+        self.shard_of_key_mut(key).insert(key, value)
+    }
+
+    /// Clears the map, removing all elements.
+    pub fn clear(&mut self) {
+        for shard in &mut self.shards {
+            shard.clear();
+        }
+    }
+
+    /// Removes up to `limit` elements from the map, starting with the last shard, returning the
+    /// number of removed elements. Used to clear maps which are too large to clear in one call.
+    pub fn clear_batch(&mut self, limit: u64) -> u64 {
+        let mut removed = 0;
+        for shard in self.shards.iter_mut().rev() {
+            if removed == limit {
+                break;
+            }
+            removed += shard.clear_batch(limit - removed);
+        }
+        removed
+    }
+
+    /// Copies elements into an `std::vec::Vec`.
+    pub fn to_vec(&self) -> std::vec::Vec<(K, V)> {
+        self.iter().collect()
+    }
+
+    /// An iterator visiting all keys. The iterator element type is `K`.
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.shards.iter().flat_map(|shard| shard.keys())
+    }
+
+    /// An iterator visiting all values. The iterator element type is `V`.
+    pub fn values(&self) -> impl Iterator<Item = V> + '_ {
+        self.shards.iter().flat_map(|shard| shard.values())
+    }
+
+    /// Iterate over deserialized keys and values.
+    pub fn iter(&self) -> impl Iterator<Item = (K, V)> + '_ {
+        self.shards.iter().flat_map(|shard| shard.iter())
+    }
+
+    pub fn extend<IT: IntoIterator<Item = (K, V)>>(&mut self, iter: IT) {
+        for (el_key, el_value) in iter {
+            self.insert(&el_key, &el_value);
+        }
+    }
+}
+
+impl<K, V> std::fmt::Debug for ShardedUnorderedMap<K, V>
+where
+    K: std::fmt::Debug + BorshSerialize + BorshDeserialize,
+    V: std::fmt::Debug + BorshSerialize + BorshDeserialize,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedUnorderedMap").field("shards", &self.shards).finish()
+    }
+}
+
+fn shard_prefix(shard: u32) -> Vec<u8> {
+    [&[b's'][..], &shard.to_le_bytes()].concat()
+}
+
+/// 64-bit FNV-1a hash, which is cheap to compute in the contract and stable across versions, so
+/// keys stay in their shards.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use crate::collections::ShardedUnorderedMap;
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_insert_remove() {
+        let mut map = ShardedUnorderedMap::new(b"m", 4);
+        let mut baseline = HashMap::new();
+        for key in 0..100u64 {
+            map.insert(&key, &(key * 2));
+            baseline.insert(key, key * 2);
+        }
+        assert_eq!(map.len(), 100);
+        assert!((0..4).all(|shard| !map.shard(shard).unwrap().is_empty()));
+        for key in (0..100u64).step_by(3) {
+            assert_eq!(map.remove(&key), baseline.remove(&key));
+        }
+        assert_eq!(map.get(&3), None);
+        assert_eq!(map.get(&4), Some(8));
+        let mut actual = map.to_vec();
+        actual.sort_unstable();
+        let mut expected: Vec<_> = baseline.into_iter().collect();
+        expected.sort_unstable();
+        assert_eq!(actual, expected);
+        for shard in 0..4 {
+            assert!(map.shard(shard).unwrap().keys().all(|key| map.shard_of(&key) == shard));
+        }
+    }
+
+    #[test]
+    pub fn test_clear_batch() {
+        let mut map = ShardedUnorderedMap::new(b"m", 3);
+        map.extend((0..10u64).map(|key| (key, key)));
+        assert_eq!(map.clear_batch(7), 7);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.clear_batch(7), 3);
+        assert!(map.is_empty());
+        assert!(crate::mock::with_mocked_blockchain(|b| b.storage().is_empty()));
+    }
+}
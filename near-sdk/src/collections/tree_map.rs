@@ -1,9 +1,9 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use std::ops::Bound;
+use std::ops::{Bound, RangeBounds};
 
 use crate::collections::LookupMap;
 use crate::collections::{append, Vector};
-use crate::{env, IntoStorageKey};
+use crate::{env, IntoStorageKey, StorageUsage};
 
 /// TreeMap based on AVL-tree
 ///
@@ -109,6 +109,14 @@ where
         }
     }
 
+    /// Removes a key from the map like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, key: &K) -> (Option<V>, StorageUsage) {
+        super::measure_freed_storage(|| self.remove(key))
+    }
+
     /// Returns the smallest stored key from the tree
     pub fn min(&self) -> Option<K> {
         self.min_at(self.root, self.root).map(|(n, _)| n.key)
@@ -119,6 +127,16 @@ where
         self.max_at(self.root, self.root).map(|(n, _)| n.key)
     }
 
+    /// Returns the entry with the smallest key from the tree
+    pub fn first_key_value(&self) -> Option<(K, V)> {
+        self.min().and_then(|key| self.get(&key).map(|value| (key, value)))
+    }
+
+    /// Returns the entry with the largest key from the tree
+    pub fn last_key_value(&self) -> Option<(K, V)> {
+        self.max().and_then(|key| self.get(&key).map(|value| (key, value)))
+    }
+
     /// Returns the smallest key that is strictly greater than key given as the parameter
     pub fn higher(&self, key: &K) -> Option<K> {
         self.above_at(self.root, key)
@@ -167,14 +185,19 @@ where
         Cursor::desc_from(self, key)
     }
 
-    /// Iterate entries in ascending order according to specified bounds.
+    /// Iterate entries in ascending order according to specified bounds, either a range like
+    /// `start..end` or a pair of [`Bound`]s.
+    ///
+    /// Views can page through the map with the last key of the previous page as the excluded
+    /// start of the next page. `skip` moves the cursor over the skipped keys without reading their
+    /// values from storage.
     ///
     /// # Panics
     ///
     /// Panics if range start > end.
     /// Panics if range start == end and both bounds are Excluded.
-    pub fn range(&self, r: (Bound<K>, Bound<K>)) -> impl Iterator<Item = (K, V)> + '_ {
-        let (lo, hi) = match r {
+    pub fn range<R: RangeBounds<K>>(&self, r: R) -> impl Iterator<Item = (K, V)> + '_ {
+        let (lo, hi) = match (r.start_bound().cloned(), r.end_bound().cloned()) {
             (Bound::Included(a), Bound::Included(b)) if a > b => env::panic_str("Invalid range."),
             (Bound::Excluded(a), Bound::Included(b)) if a > b => env::panic_str("Invalid range."),
             (Bound::Included(a), Bound::Excluded(b)) if a > b => env::panic_str("Invalid range."),
@@ -1534,6 +1557,12 @@ mod tests {
         assert_eq!(map.range((Bound::Excluded(20), Bound::Excluded(45))).count(), 4);
         assert_eq!(map.range((Bound::Excluded(20), Bound::Excluded(45))).last(), Some((40, 42)));
 
+        // Test ranges from `std::ops`
+        assert_eq!(map.range(20..30).collect::<Vec<(u32, u32)>>(), vec![(20, 42), (25, 42)]);
+        assert_eq!(map.range(..=10).collect::<Vec<(u32, u32)>>(), vec![(5, 42), (10, 42)]);
+        assert_eq!(map.range(40..).skip(1).collect::<Vec<(u32, u32)>>(), vec![(45, 42), (50, 42)]);
+        assert_eq!(map.range(..).count(), 10);
+
         map.clear();
     }
 
@@ -1758,6 +1787,40 @@ mod tests {
         QuickCheck::new().tests(300).quickcheck(prop as Prop);
     }
 
+    #[test]
+    fn prop_avl_vs_rb_neighbours() {
+        fn prop(insert: Vec<(u32, u32)>, remove: Vec<u32>, key: u32) -> bool {
+            let a = avl(&insert, &remove);
+            let b = rb(&insert, &remove);
+            let key_of = |entry: Option<(&u32, &u32)>| entry.map(|(k, _)| *k);
+            a.floor_key(&key) == key_of(b.range(..=key).next_back())
+                && a.ceil_key(&key) == key_of(b.range(key..).next())
+                && a.lower(&key) == key_of(b.range(..key).next_back())
+                && a.higher(&key)
+                    == key_of(b.range((Bound::Excluded(key), Bound::Unbounded)).next())
+                && a.first_key_value() == b.iter().next().map(|(k, v)| (*k, *v))
+                && a.last_key_value() == b.iter().next_back().map(|(k, v)| (*k, *v))
+        }
+
+        QuickCheck::new().tests(300).quickcheck(prop as fn(Vec<(u32, u32)>, Vec<u32>, u32) -> bool);
+    }
+
+    #[test]
+    fn prop_avl_vs_rb_iter_rev_from() {
+        fn prop(insert: Vec<(u32, u32)>, remove: Vec<u32>, key: u32) -> bool {
+            let a = avl(&insert, &remove);
+            let b = rb(&insert, &remove);
+            let v1: Vec<(u32, u32)> = a.iter_rev_from(key).collect();
+            let v2: Vec<(u32, u32)> = b.range(..key).rev().map(|(k, v)| (*k, *v)).collect();
+            let v3: Vec<(u32, u32)> = a.iter_from(key).collect();
+            let v4: Vec<(u32, u32)> =
+                b.range((Bound::Excluded(key), Bound::Unbounded)).map(|(k, v)| (*k, *v)).collect();
+            v1 == v2 && v3 == v4
+        }
+
+        QuickCheck::new().tests(300).quickcheck(prop as fn(Vec<(u32, u32)>, Vec<u32>, u32) -> bool);
+    }
+
     #[test]
     fn test_debug() {
         let mut map = TreeMap::new(b"m");
@@ -1,8 +1,9 @@
 //! A map implemented on a trie. Unlike `std::collections::HashMap` the keys in this map are not
 //! hashed but are instead serialized.
-use crate::collections::{append, append_slice, Vector};
-use crate::{env, IntoStorageKey};
+use crate::collections::{append, append_slice, vector, Vector};
+use crate::{env, IntoStorageKey, StorageUsage};
 use borsh::{BorshDeserialize, BorshSerialize};
+use std::iter::FusedIterator;
 use std::mem::size_of;
 
 const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart contract execution terminate unexpectedly?";
@@ -183,6 +184,14 @@ where
             .map(|value_raw| Self::deserialize_value(&value_raw))
     }
 
+    /// Removes a key from the map like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, key: &K) -> (Option<V>, StorageUsage) {
+        super::measure_freed_storage(|| self.remove(key))
+    }
+
     /// Inserts a key-value pair into the map.
     /// If the map did not have this key present, `None` is returned. Otherwise returns
     /// a value. Note, the keys that have the same hash value are undistinguished by
@@ -202,24 +211,45 @@ where
         self.values.clear();
     }
 
+    /// Removes up to `limit` elements from the map, returning the number of removed elements.
+    /// Used to clear maps which are too large to clear in one call.
+    pub fn clear_batch(&mut self, limit: u64) -> u64 {
+        let len = self.len();
+        let removed = limit.min(len);
+        for index in len - removed..len {
+            let raw_key = self.keys.get_raw(index).unwrap_or_else(|| env::abort());
+            let index_lookup = self.raw_key_to_index_lookup(&raw_key);
+            env::storage_remove(&index_lookup);
+        }
+        self.keys.clear_batch(removed);
+        self.values.clear_batch(removed);
+        removed
+    }
+
     /// Copies elements into an `std::vec::Vec`.
     pub fn to_vec(&self) -> std::vec::Vec<(K, V)> {
         self.iter().collect()
     }
 
     /// An iterator visiting all keys. The iterator element type is `K`.
-    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+    pub fn keys(&self) -> impl Iterator<Item = K> + DoubleEndedIterator + ExactSizeIterator + '_ {
         self.keys.iter()
     }
 
     /// An iterator visiting all values. The iterator element type is `V`.
-    pub fn values(&self) -> impl Iterator<Item = V> + '_ {
+    pub fn values(&self) -> impl Iterator<Item = V> + DoubleEndedIterator + ExactSizeIterator + '_ {
         self.values.iter()
     }
 
     /// Iterate over deserialized keys and values.
-    pub fn iter(&self) -> impl Iterator<Item = (K, V)> + '_ {
-        self.keys.iter().zip(self.values.iter())
+    ///
+    /// The iterator knows its length and can be iterated from both ends, so that views can page
+    /// through the map with `skip` and `take`, or return the last entries with `rev`, without
+    /// reading the skipped entries from storage.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (K, V)> + DoubleEndedIterator + ExactSizeIterator + '_ {
+        Iter { keys: self.keys.iter(), values: self.values.iter() }
     }
 
     pub fn extend<IT: IntoIterator<Item = (K, V)>>(&mut self, iter: IT) {
@@ -255,10 +285,70 @@ where
     }
 }
 
+/// An iterator over the entries of an [`UnorderedMap`], which skips entries by index without
+/// reading them, unlike zipping the iterators of the keys and values.
+struct Iter<'a, K, V> {
+    keys: vector::Iter<'a, K>,
+    values: vector::Iter<'a, V>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.keys.count()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        Some((self.keys.nth(n)?, self.values.nth(n)?))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        Some((self.keys.nth_back(n)?, self.values.nth_back(n)?))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use crate::collections::UnorderedMap;
+    use crate::env;
     use rand::seq::SliceRandom;
     use rand::{Rng, SeedableRng};
     use std::collections::{HashMap, HashSet};
@@ -394,6 +484,20 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_clear_batch() {
+        let mut map = UnorderedMap::new(b"m");
+        for key in 0..10u64 {
+            map.insert(&key, &(key * 2));
+        }
+        assert_eq!(map.clear_batch(4), 4);
+        assert_eq!(map.to_vec(), (0..6u64).map(|key| (key, key * 2)).collect::<Vec<_>>());
+        assert_eq!(map.get(&7), None);
+        assert_eq!(map.clear_batch(10), 6);
+        assert!(map.is_empty());
+        assert!(crate::mock::with_mocked_blockchain(|b| b.storage().is_empty()));
+    }
+
     #[test]
     pub fn test_keys_values() {
         let mut map = UnorderedMap::new(b"m");
@@ -431,6 +535,34 @@ mod tests {
         assert_eq!(actual, key_to_value);
     }
 
+    #[test]
+    pub fn test_iter_rev() {
+        let mut map = UnorderedMap::new(b"m");
+        for key in 0..10u64 {
+            map.insert(&key, &(key * 2));
+        }
+        assert_eq!(map.iter().len(), 10);
+        assert_eq!(map.iter().rev().take(2).collect::<Vec<_>>(), vec![(9, 18), (8, 16)]);
+        assert_eq!(map.keys().skip(3).rev().nth(5), Some(4));
+        assert_eq!(map.values().next_back(), Some(18));
+        assert_eq!(map.iter().skip(3).rev().nth(5), Some((4, 8)));
+    }
+
+    #[test]
+    pub fn test_iter_skip_reads_nothing() {
+        let mut map = UnorderedMap::new(b"m");
+        for key in 0..10u64 {
+            map.insert(&key, &(key * 2));
+        }
+        let initial_gas = env::used_gas();
+        assert_eq!(map.iter().nth(8), Some((8, 16)));
+        let skipping_gas = env::used_gas() - initial_gas;
+        let initial_gas = env::used_gas();
+        assert_eq!(map.iter().take(9).last(), Some((8, 16)));
+        let reading_gas = env::used_gas() - initial_gas;
+        assert!(skipping_gas.0 * 4 < reading_gas.0);
+    }
+
     #[test]
     pub fn test_extend() {
         let mut map = UnorderedMap::new(b"m");
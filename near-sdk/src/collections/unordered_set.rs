@@ -1,11 +1,12 @@
 //! A set implemented on a trie. Unlike `std::collections::HashSet` the elements in this set are not
 //! hashed but are instead serialized.
 use crate::collections::{append, append_slice, Vector};
-use crate::{env, IntoStorageKey};
+use crate::{env, IntoStorageKey, StorageUsage};
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::mem::size_of;
 
 const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart contract execution terminate unexpectedly?";
+const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element with Borsh";
 const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element with Borsh";
 
 /// An iterable implementation of a set that stores its content directly on the trie.
@@ -122,6 +123,11 @@ where
         }
     }
 
+    fn deserialize_element(raw_element: &[u8]) -> T {
+        T::try_from_slice(raw_element)
+            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+    }
+
     /// Returns true if the set contains an element.
     pub fn contains(&self, element: &T) -> bool {
         self.contains_raw(&Self::serialize_element(element))
@@ -132,6 +138,14 @@ where
         self.remove_raw(&Self::serialize_element(element))
     }
 
+    /// Removes a value from the set like [`remove`](Self::remove), also returning the number of
+    /// storage bytes freed, which can be refunded with [`refund_freed_storage`].
+    ///
+    /// [`refund_freed_storage`]: crate::refund_freed_storage
+    pub fn remove_measured(&mut self, element: &T) -> (bool, StorageUsage) {
+        super::measure_freed_storage(|| self.remove(element))
+    }
+
     /// Adds a value to the set.
     /// If the set did not have this value present, `true` is returned.
     /// If the set did have this value present, `false` is returned.
@@ -148,13 +162,67 @@ where
         self.elements.clear();
     }
 
+    /// Removes up to `limit` elements from the set, returning the number of removed elements.
+    /// Used to clear sets which are too large to clear in one call.
+    pub fn clear_batch(&mut self, limit: u64) -> u64 {
+        let len = self.len();
+        let removed = limit.min(len);
+        for index in len - removed..len {
+            let raw_element = self.elements.get_raw(index).unwrap_or_else(|| env::abort());
+            let index_lookup = self.raw_element_to_index_lookup(&raw_element);
+            env::storage_remove(&index_lookup);
+        }
+        self.elements.clear_batch(removed);
+        removed
+    }
+
+    /// Removes all elements from the set, returning them in an `std::vec::Vec`. Each element is
+    /// read once, like with [`clear`](Self::clear).
+    pub fn drain(&mut self) -> std::vec::Vec<T> {
+        let elements = self
+            .elements
+            .iter_raw()
+            .map(|raw_element| {
+                let index_lookup = self.raw_element_to_index_lookup(&raw_element);
+                env::storage_remove(&index_lookup);
+                Self::deserialize_element(&raw_element)
+            })
+            .collect();
+        self.elements.clear();
+        elements
+    }
+
+    /// Retains only the elements for which `f` returns `true`, removing the others. Elements are
+    /// visited from the last to the first, so that each element is read once although removals
+    /// move the last element into the removed slot.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        for index in (0..self.len()).rev() {
+            let raw_element = self.elements.get_raw(index).unwrap_or_else(|| env::abort());
+            if !f(&Self::deserialize_element(&raw_element)) {
+                self.remove_raw(&raw_element);
+            }
+        }
+    }
+
+    /// Returns true if the set contains any of the elements, stopping at the first one found.
+    pub fn contains_any(&self, elements: &[T]) -> bool {
+        elements.iter().any(|element| self.contains(element))
+    }
+
     /// Copies elements into an `std::vec::Vec`.
     pub fn to_vec(&self) -> std::vec::Vec<T> {
         self.iter().collect()
     }
 
     /// Iterate over deserialized elements.
-    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+    ///
+    /// The iterator knows its length and can be iterated from both ends, so that views can page
+    /// through the set with `skip` and `take`, or return the last elements with `rev`, without
+    /// reading the skipped elements from storage.
+    pub fn iter(&self) -> impl Iterator<Item = T> + DoubleEndedIterator + ExactSizeIterator + '_ {
         self.elements.iter()
     }
 
@@ -305,6 +373,18 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_clear_batch() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..10u64);
+        assert_eq!(set.clear_batch(4), 4);
+        assert_eq!(set.to_vec(), (0..6u64).collect::<Vec<_>>());
+        assert!(!set.contains(&7));
+        assert_eq!(set.clear_batch(10), 6);
+        assert!(set.is_empty());
+        assert!(crate::mock::with_mocked_blockchain(|b| b.storage().is_empty()));
+    }
+
     #[test]
     pub fn test_iter() {
         let mut set = UnorderedSet::new(b"s");
@@ -319,6 +399,32 @@ mod tests {
         assert_eq!(actual, keys);
     }
 
+    #[test]
+    pub fn test_retain_drain() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..20u64);
+        set.retain(|element| element % 3 == 0);
+        assert_eq!(set.len(), 7);
+        assert!(set.contains(&9));
+        assert!(!set.contains(&10));
+        assert!(set.contains_any(&[1, 2, 18]));
+        assert!(!set.contains_any(&[1, 2, 19]));
+
+        let mut drained = set.drain();
+        drained.sort_unstable();
+        assert_eq!(drained, vec![0, 3, 6, 9, 12, 15, 18]);
+        assert!(set.is_empty());
+        assert!(!set.contains(&9));
+    }
+
+    #[test]
+    pub fn test_iter_rev() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..10u64);
+        assert_eq!(set.iter().len(), 10);
+        assert_eq!(set.iter().rev().take(3).collect::<Vec<_>>(), vec![9, 8, 7]);
+    }
+
     #[test]
     pub fn test_extend() {
         let mut set = UnorderedSet::new(b"s");
@@ -7,7 +7,8 @@ use std::marker::PhantomData;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::collections::append_slice;
-use crate::{env, IntoStorageKey};
+use crate::json_types::U128;
+use crate::{env, require, IntoStorageKey};
 
 const ERR_INCONSISTENT_STATE: &str = "The collection is an inconsistent state. Did previous smart contract execution terminate unexpectedly?";
 const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element";
@@ -126,6 +127,36 @@ impl<T> Vector<T> {
         }
     }
 
+    /// Removes the elements in `range`, shifting the following elements down to keep the order,
+    /// and returns them in serialized form. Costs a write per element after the range, so
+    /// draining from the back is cheapest.
+    ///
+    /// # Panics
+    ///
+    /// If the range is decreasing or its end is out of bounds.
+    pub fn drain_raw(&mut self, range: Range<u64>) -> Vec<Vec<u8>> {
+        if range.start > range.end || range.end > self.len {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)
+        }
+        let drained_len = range.end - range.start;
+        let drained = range
+            .clone()
+            .map(|index| {
+                expect_consistent_state(env::storage_read(&self.index_to_lookup_key(index)))
+            })
+            .collect();
+        for index in range.end..self.len {
+            let raw_element =
+                expect_consistent_state(env::storage_read(&self.index_to_lookup_key(index)));
+            env::storage_write(&self.index_to_lookup_key(index - drained_len), &raw_element);
+        }
+        for index in self.len - drained_len..self.len {
+            env::storage_remove(&self.index_to_lookup_key(index));
+        }
+        self.len -= drained_len;
+        drained
+    }
+
     /// Iterate over raw serialized elements.
     pub fn iter_raw(&self) -> RawIter<T> {
         RawIter::new(self)
@@ -148,6 +179,18 @@ impl<T> Vector<T> {
         }
         self.len = 0;
     }
+
+    /// Removes up to `limit` elements from the back of the collection, returning the number of
+    /// removed elements. Used to clear collections which are too large to clear in one call.
+    pub fn clear_batch(&mut self, limit: u64) -> u64 {
+        let removed = limit.min(self.len);
+        for i in self.len - removed..self.len {
+            let lookup_key = self.index_to_lookup_key(i);
+            env::storage_remove(&lookup_key);
+        }
+        self.len -= removed;
+        removed
+    }
 }
 
 impl<T> Vector<T>
@@ -170,6 +213,24 @@ where
             self.push(&el)
         }
     }
+
+    /// Appends the elements to the back of the collection, reusing one key and one serialization
+    /// buffer for all of them instead of allocating them for each element like
+    /// [`push`](Self::push).
+    pub fn extend_from_slice(&mut self, elements: &[T]) {
+        let mut lookup_key = self.index_to_lookup_key(self.len);
+        let index_offset = self.prefix.len();
+        let mut raw_element = Vec::new();
+        for element in elements {
+            raw_element.clear();
+            element
+                .serialize(&mut raw_element)
+                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+            lookup_key[index_offset..].copy_from_slice(&self.len.to_le_bytes());
+            env::storage_write(&lookup_key, &raw_element);
+            self.len += 1;
+        }
+    }
 }
 
 impl<T> Vector<T>
@@ -211,6 +272,23 @@ where
     pub fn to_vec(&self) -> Vec<T> {
         self.iter().collect()
     }
+
+    /// Returns up to `limit` elements starting at `from_index`, e.g. for a paginated view. Returns
+    /// an empty vector if `from_index` is out of bounds.
+    pub fn to_vec_paginated(&self, from_index: u64, limit: u64) -> Vec<T> {
+        let end = from_index.saturating_add(limit).min(self.len);
+        (from_index..end).map(|index| expect_consistent_state(self.get(index))).collect()
+    }
+
+    /// Removes the elements in `range`, shifting the following elements down to keep the order,
+    /// and returns them. See [`drain_raw`](Self::drain_raw).
+    ///
+    /// # Panics
+    ///
+    /// If the range is decreasing or its end is out of bounds.
+    pub fn drain(&mut self, range: Range<u64>) -> Vec<T> {
+        self.drain_raw(range).into_iter().map(|x| Self::deserialize_element(&x)).collect()
+    }
 }
 
 impl<T> Vector<T>
@@ -346,13 +424,35 @@ where
     }
 }
 
+/// Range of the indices of a page of a collection of `len` elements, for paginated views like
+/// the NFT enumeration. The page starts at `from_index`, 0 by default, and has up to `limit`
+/// elements, all of them by default. The range is empty for an empty collection.
+///
+/// # Panics
+///
+/// If `from_index` is out of bounds or `limit` is 0.
+pub fn paginated_range(len: u64, from_index: Option<U128>, limit: Option<u64>) -> Range<u64> {
+    let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+    if len == 0 && start_index == 0 {
+        return 0..0;
+    }
+    require!((len as u128) > start_index, "Out of bounds, please use a smaller from_index.");
+    let limit = limit.unwrap_or(u64::MAX);
+    require!(limit != 0, "Cannot provide limit of 0.");
+    let start = start_index as u64;
+    start..start.saturating_add(limit).min(len)
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use borsh::BorshDeserialize;
     use rand::{Rng, SeedableRng};
 
+    use super::paginated_range;
     use crate::collections::Vector;
+    use crate::env;
+    use crate::json_types::U128;
 
     #[test]
     fn test_push_pop() {
@@ -435,6 +535,18 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_clear_batch() {
+        let mut vec = Vector::new(b"v".to_vec());
+        vec.extend(0..10u64);
+        assert_eq!(vec.clear_batch(4), 4);
+        assert_eq!(vec.to_vec(), (0..6u64).collect::<Vec<_>>());
+        assert_eq!(vec.clear_batch(10), 6);
+        assert!(vec.is_empty());
+        assert_eq!(vec.clear_batch(10), 0);
+        assert!(crate::mock::with_mocked_blockchain(|b| b.storage().is_empty()));
+    }
+
     #[test]
     pub fn test_extend() {
         let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
@@ -459,6 +571,88 @@ mod tests {
         assert_eq!(actual, baseline);
     }
 
+    #[test]
+    pub fn test_extend_from_slice() {
+        let mut vec = Vector::new(b"v".to_vec());
+        vec.push(&0u64);
+        let values: Vec<u64> = (1..300).collect();
+        vec.extend_from_slice(&values);
+        assert_eq!(vec.len(), 300);
+        assert_eq!(vec.to_vec(), (0..300).collect::<Vec<_>>());
+        // Keys of the appended elements are the same as those written by `push`.
+        vec.push(&300);
+        assert_eq!(vec.get(299), Some(299));
+        assert_eq!(vec.pop(), Some(300));
+    }
+
+    #[test]
+    pub fn test_drain() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(5);
+        let mut vec = Vector::new(b"v".to_vec());
+        let mut baseline = vec![];
+        for _ in 0..200 {
+            let value = rng.gen::<u64>();
+            vec.push(&value);
+            baseline.push(value);
+        }
+        while !baseline.is_empty() {
+            let start = rng.gen::<u64>() % baseline.len() as u64;
+            let end = start + rng.gen::<u64>() % (baseline.len() as u64 - start + 1);
+            let expected: Vec<u64> = baseline.drain(start as usize..end as usize).collect();
+            assert_eq!(vec.drain(start..end), expected);
+            assert_eq!(vec.len(), baseline.len() as u64);
+            assert_eq!(vec.to_vec(), baseline);
+            // Keys after the new end were removed, so pushes reuse them cleanly.
+            assert!(vec.get_raw(vec.len()).is_none());
+            assert!(!env::storage_has_key(&vec.index_to_lookup_key(vec.len())));
+            if rng.gen::<u64>() % 4 == 0 {
+                let value = rng.gen::<u64>();
+                vec.push(&value);
+                baseline.push(value);
+            }
+        }
+        assert!(vec.drain(0..0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds")]
+    pub fn test_drain_out_of_bounds() {
+        let mut vec = Vector::new(b"v".to_vec());
+        vec.extend_from_slice(&[1u64, 2, 3]);
+        vec.drain(2..4);
+    }
+
+    #[test]
+    pub fn test_to_vec_paginated() {
+        let mut vec = Vector::new(b"v".to_vec());
+        vec.extend_from_slice(&(0..10u64).collect::<Vec<_>>());
+        assert_eq!(vec.to_vec_paginated(0, 3), vec![0, 1, 2]);
+        assert_eq!(vec.to_vec_paginated(8, 5), vec![8, 9]);
+        assert!(vec.to_vec_paginated(10, 5).is_empty());
+        assert!(vec.to_vec_paginated(3, 0).is_empty());
+        assert_eq!(vec.to_vec_paginated(9, u64::MAX), vec![9]);
+    }
+
+    #[test]
+    fn test_paginated_range() {
+        assert_eq!(paginated_range(0, None, Some(0)), 0..0);
+        assert_eq!(paginated_range(10, None, None), 0..10);
+        assert_eq!(paginated_range(10, Some(U128(8)), Some(5)), 8..10);
+        assert_eq!(paginated_range(10, Some(U128(9)), Some(u64::MAX)), 9..10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds, please use a smaller from_index.")]
+    fn test_paginated_range_out_of_bounds() {
+        paginated_range(10, Some(U128(10)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot provide limit of 0.")]
+    fn test_paginated_range_zero_limit() {
+        paginated_range(10, None, Some(0));
+    }
+
     #[test]
     fn test_debug() {
         let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(4);
@@ -10,8 +10,13 @@ use std::{convert::TryFrom, mem::MaybeUninit};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::mock::MockedBlockchain;
 use crate::types::{
-    AccountId, Balance, BlockHeight, Gas, PromiseIndex, PromiseResult, PublicKey, StorageUsage,
+    AccountId, Balance, BlockHeight, Gas, PromiseError, PromiseIndex, PromiseResult, PublicKey,
+    StorageUsage,
 };
+#[cfg(feature = "protocol_feature_alt_bn128")]
+use crate::types::{AltBn128Fr, G1Point, G2Point};
+#[cfg(feature = "protocol_feature_alt_bn128")]
+use borsh::BorshSerialize;
 use near_sys as sys;
 
 const REGISTER_EXPECTED_ERR: &str =
@@ -195,7 +200,31 @@ pub fn epoch_height() -> u64 {
 
 /// Current total storage usage of this smart contract that this account would be paying for.
 pub fn storage_usage() -> StorageUsage {
-    unsafe { sys::storage_usage() }
+    let usage = unsafe { sys::storage_usage() };
+    #[cfg(feature = "estimate")]
+    #[cfg(feature = "estimate")]
+    if let Some(delta) = super::overlay::storage_usage_delta() {
+        return (usage as i64 + delta) as StorageUsage;
+    }
+    usage
+}
+
+/// Approximate number of bytes of wasm memory used by the contract: the bytes allocated so far if
+/// the `bump_alloc` feature is enabled, otherwise the size of the linear memory, which includes
+/// the stack and freed memory. Always `0` in unit tests.
+pub fn memory_usage() -> u64 {
+    #[cfg(all(feature = "bump_alloc", target_arch = "wasm32"))]
+    {
+        crate::ALLOC.allocated_bytes() as u64
+    }
+    #[cfg(all(not(feature = "bump_alloc"), target_arch = "wasm32"))]
+    {
+        (core::arch::wasm32::memory_size(0) * crate::allocator::WASM_PAGE_SIZE) as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
 }
 
 // #################
@@ -326,8 +355,8 @@ pub fn ripemd160_array(value: &[u8]) -> [u8; 20] {
 /// Returns 64 bytes representing the public key if the recovery was successful.
 #[cfg(feature = "unstable")]
 pub fn ecrecover(
-    hash: &[u8],
-    signature: &[u8],
+    hash: &[u8; 32],
+    signature: &[u8; 64],
     v: u8,
     malleability_flag: bool,
 ) -> Option<[u8; 64]> {
@@ -349,6 +378,56 @@ pub fn ecrecover(
     }
 }
 
+/// Verifies the ed25519 `signature` of `message` by `public_key`, e.g. a message signed off-chain
+/// by a bridge relayer. Requires the `protocol_feature_ed25519_verify` feature and a runtime
+/// with the `ed25519_verify` host function.
+#[cfg(feature = "protocol_feature_ed25519_verify")]
+pub fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    unsafe {
+        sys::ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        ) == 1
+    }
+}
+
+/// Computes the sum of `scalar * point` for the `(point, scalar)` pairs on the G1 group of the
+/// alt_bn128 curve. Panics if a point isn't on the curve. Requires the
+/// `protocol_feature_alt_bn128` feature.
+#[cfg(feature = "protocol_feature_alt_bn128")]
+pub fn alt_bn128_g1_multiexp(items: &[(G1Point, AltBn128Fr)]) -> G1Point {
+    let value = items.try_to_vec().unwrap_or_else(|_| abort());
+    unsafe {
+        sys::alt_bn128_g1_multiexp(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER);
+        G1Point::from_bytes(&read_register_fixed_64(ATOMIC_OP_REGISTER))
+    }
+}
+
+/// Computes the sum of the points on the G1 group of the alt_bn128 curve, subtracting the points
+/// paired with `true`. Panics if a point isn't on the curve. Requires the
+/// `protocol_feature_alt_bn128` feature.
+#[cfg(feature = "protocol_feature_alt_bn128")]
+pub fn alt_bn128_g1_sum(items: &[(bool, G1Point)]) -> G1Point {
+    let value = items.try_to_vec().unwrap_or_else(|_| abort());
+    unsafe {
+        sys::alt_bn128_g1_sum(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER);
+        G1Point::from_bytes(&read_register_fixed_64(ATOMIC_OP_REGISTER))
+    }
+}
+
+/// Returns whether the product of the pairings of the `(G1, G2)` pairs on the alt_bn128 curve is
+/// one, the check of Groth16 proof verifiers. Panics if a point isn't on its curve. Requires the
+/// `protocol_feature_alt_bn128` feature.
+#[cfg(feature = "protocol_feature_alt_bn128")]
+pub fn alt_bn128_pairing_check(items: &[(G1Point, G2Point)]) -> bool {
+    let value = items.try_to_vec().unwrap_or_else(|_| abort());
+    unsafe { sys::alt_bn128_pairing_check(value.len() as _, value.as_ptr() as _) == 1 }
+}
+
 // ################
 // # Promises API #
 // ################
@@ -361,6 +440,8 @@ pub fn promise_create(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    #[cfg(feature = "estimate")]
+    super::overlay::deny_promise();
     let account_id = account_id.as_bytes();
     unsafe {
         sys::promise_create(
@@ -385,6 +466,8 @@ pub fn promise_then(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    #[cfg(feature = "estimate")]
+    super::overlay::deny_promise();
     let account_id = account_id.as_bytes();
     unsafe {
         sys::promise_then(
@@ -412,11 +495,15 @@ pub fn promise_and(promise_indices: &[PromiseIndex]) -> PromiseIndex {
 }
 
 pub fn promise_batch_create(account_id: &AccountId) -> PromiseIndex {
+    #[cfg(feature = "estimate")]
+    super::overlay::deny_promise();
     let account_id = account_id.as_ref();
     unsafe { sys::promise_batch_create(account_id.len() as _, account_id.as_ptr() as _) }
 }
 
 pub fn promise_batch_then(promise_index: PromiseIndex, account_id: &AccountId) -> PromiseIndex {
+    #[cfg(feature = "estimate")]
+    super::overlay::deny_promise();
     let account_id: &str = account_id.as_ref();
     unsafe {
         sys::promise_batch_then(promise_index, account_id.len() as _, account_id.as_ptr() as _)
@@ -555,6 +642,38 @@ pub fn promise_result(result_idx: u64) -> PromiseResult {
         _ => abort(),
     }
 }
+/// Default upper bound in bytes for promise results read with [`promise_result_bounded`]. Contracts
+/// can pass their own bound where callbacks expect larger or smaller values.
+pub const MAX_PROMISE_RESULT_BYTES: u64 = 16 * 1024;
+
+/// Same as [`promise_result`], but the value is only copied out of the register if it is at most
+/// `max_len` bytes long, so that a callback doesn't spend gas reading and deserializing a payload
+/// which is larger than anything it expects. Larger values return [`PromiseError::TooLarge`].
+pub fn promise_result_bounded(result_idx: u64, max_len: u64) -> Result<Vec<u8>, PromiseError> {
+    match unsafe { sys::promise_result(result_idx, ATOMIC_OP_REGISTER) } {
+        0 => Err(PromiseError::NotReady),
+        1 => {
+            let len = expect_register(register_len(ATOMIC_OP_REGISTER));
+            if len > max_len {
+                Err(PromiseError::TooLarge(len))
+            } else {
+                Ok(expect_register(read_register(ATOMIC_OP_REGISTER)))
+            }
+        }
+        2 => Err(PromiseError::Failed),
+        _ => abort(),
+    }
+}
+/// Same as [`promise_result_bounded`] with [`MAX_PROMISE_RESULT_BYTES`], deserializing the value
+/// from JSON, for callbacks which read their results by index instead of with
+/// `#[callback_result]`, e.g. callbacks joining a variable number of promises. Values which can't
+/// be deserialized as `T` are returned as [`PromiseError::Failed`].
+pub fn promise_result_json<T: serde::de::DeserializeOwned>(
+    result_idx: u64,
+) -> Result<T, PromiseError> {
+    let value = promise_result_bounded(result_idx, MAX_PROMISE_RESULT_BYTES)?;
+    serde_json::from_slice(&value).map_err(|_| PromiseError::Failed)
+}
 /// Consider the execution result of promise under `promise_idx` as execution result of this
 /// function.
 pub fn promise_return(promise_idx: PromiseIndex) {
@@ -619,6 +738,10 @@ pub fn abort() -> ! {
 
 /// Logs the string message message. This message is stored on chain.
 pub fn log_str(message: &str) {
+    #[cfg(feature = "estimate")]
+    if super::overlay::discard_log() {
+        return;
+    }
     #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
     eprintln!("{}", message);
 
@@ -628,6 +751,10 @@ pub fn log_str(message: &str) {
 /// Log the UTF-8 encodable message.
 #[deprecated(since = "4.0.0", note = "Use env::log_str for logging messages.")]
 pub fn log(message: &[u8]) {
+    #[cfg(feature = "estimate")]
+    if super::overlay::discard_log() {
+        return;
+    }
     #[cfg(all(debug_assertions, not(target_arch = "wasm32")))]
     eprintln!("{}", String::from_utf8_lossy(message));
 
@@ -640,6 +767,10 @@ pub fn log(message: &[u8]) {
 /// Writes key-value into storage.
 /// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
 pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
+    #[cfg(feature = "estimate")]
+    if let Some(existed) = super::overlay::write(key, value) {
+        return existed;
+    }
     match unsafe {
         sys::storage_write(
             key.len() as _,
@@ -656,6 +787,10 @@ pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
 }
 /// Reads the value stored under the given key.
 pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+    #[cfg(feature = "estimate")]
+    if let Some(value) = super::overlay::read(key) {
+        return value;
+    }
     match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, ATOMIC_OP_REGISTER) } {
         0 => None,
         1 => Some(expect_register(read_register(ATOMIC_OP_REGISTER))),
@@ -665,6 +800,10 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
+    #[cfg(feature = "estimate")]
+    if let Some(existed) = super::overlay::remove(key) {
+        return existed;
+    }
     match unsafe { sys::storage_remove(key.len() as _, key.as_ptr() as _, EVICTED_REGISTER) } {
         0 => false,
         1 => true,
@@ -673,10 +812,18 @@ pub fn storage_remove(key: &[u8]) -> bool {
 }
 /// Reads the most recent value that was evicted with `storage_write` or `storage_remove` command.
 pub fn storage_get_evicted() -> Option<Vec<u8>> {
+    #[cfg(feature = "estimate")]
+    if let Some(evicted) = super::overlay::evicted() {
+        return evicted;
+    }
     read_register(EVICTED_REGISTER)
 }
 /// Checks if there is a key-value in the storage.
 pub fn storage_has_key(key: &[u8]) -> bool {
+    #[cfg(feature = "estimate")]
+    if let Some(value) = super::overlay::read(key) {
+        return value.is_some();
+    }
     match unsafe { sys::storage_has_key(key.len() as _, key.as_ptr() as _) } {
         0 => false,
         1 => true,
@@ -866,6 +1013,42 @@ mod tests {
         );
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn promise_result_bounded_checks_len() {
+        crate::testing_env!(
+            crate::test_utils::VMContextBuilder::new().build(),
+            crate::VMConfig::test(),
+            crate::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![1; 8]), PromiseResult::Failed],
+        );
+
+        assert_eq!(super::promise_result_bounded(0, 8), Ok(vec![1; 8]));
+        assert_eq!(super::promise_result_bounded(0, 7), Err(PromiseError::TooLarge(8)));
+        assert_eq!(super::promise_result_bounded(1, 8), Err(PromiseError::Failed));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn promise_result_json_deserializes() {
+        crate::testing_env!(
+            crate::test_utils::VMContextBuilder::new().build(),
+            crate::VMConfig::test(),
+            crate::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                PromiseResult::Successful(b"\"42\"".to_vec()),
+                PromiseResult::Successful(b"oops".to_vec()),
+                PromiseResult::Failed,
+            ],
+        );
+
+        assert_eq!(super::promise_result_json::<String>(0), Ok("42".to_string()));
+        assert_eq!(super::promise_result_json::<u64>(1), Err(PromiseError::Failed));
+        assert_eq!(super::promise_result_json::<u64>(2), Err(PromiseError::Failed));
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     #[test]
     fn random_seed_smoke_test() {
@@ -876,6 +1059,26 @@ mod tests {
         assert_eq!(super::random_seed(), [8; 32]);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    #[test]
+    fn test_ed25519_verify() {
+        use hex::FromHex;
+
+        // Test 1 of RFC 8032, which signs the empty message.
+        let public_key = <[u8; 32]>::from_hex(
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        )
+        .unwrap();
+        let signature = <[u8; 64]>::from_hex(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+             5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        )
+        .unwrap();
+        assert!(super::ed25519_verify(&signature, b"", &public_key));
+        assert!(!super::ed25519_verify(&signature, b"message", &public_key));
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg(feature = "unstable")]
     #[test]
@@ -917,4 +1120,67 @@ mod tests {
             assert_eq!(super::ecrecover(&m, &sig, v, mc), res);
         }
     }
+
+    /// Reads a field element of alt_bn128 from its big-endian hex, as in the Ethereum test vectors.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    fn alt_bn128_fq(hex: &str) -> crate::types::AltBn128Fq {
+        use hex::FromHex;
+
+        let mut bytes = <[u8; 32]>::from_hex(hex).unwrap();
+        bytes.reverse();
+        bytes
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    fn alt_bn128_generators() -> (G1Point, G2Point) {
+        let mut one = [0; 32];
+        one[0] = 1;
+        let mut two = [0; 32];
+        two[0] = 2;
+        let g2 = G2Point {
+            x: [
+                alt_bn128_fq("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"),
+                alt_bn128_fq("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"),
+            ],
+            y: [
+                alt_bn128_fq("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"),
+                alt_bn128_fq("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"),
+            ],
+        };
+        (G1Point { x: one, y: two }, g2)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    #[test]
+    fn test_alt_bn128_g1() {
+        let (g1, _) = alt_bn128_generators();
+        let double = G1Point {
+            x: alt_bn128_fq("030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3"),
+            y: alt_bn128_fq("15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4"),
+        };
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, g1), (false, g1)]), double);
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, g1), (true, g1)]), G1Point::default());
+
+        let mut scalar: AltBn128Fr = [0; 32];
+        scalar[0] = 2;
+        assert_eq!(super::alt_bn128_g1_multiexp(&[(g1, scalar)]), double);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    #[test]
+    fn test_alt_bn128_pairing_check() {
+        let (g1, g2) = alt_bn128_generators();
+        let neg_g1 = G1Point {
+            x: g1.x,
+            y: alt_bn128_fq("30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"),
+        };
+        assert!(super::alt_bn128_pairing_check(&[]));
+        assert!(super::alt_bn128_pairing_check(&[(G1Point::default(), g2)]));
+        assert!(super::alt_bn128_pairing_check(&[(g1, g2), (neg_g1, g2)]));
+        assert!(!super::alt_bn128_pairing_check(&[(g1, g2)]));
+    }
 }
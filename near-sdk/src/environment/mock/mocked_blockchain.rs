@@ -15,6 +15,8 @@ use std::collections::HashMap;
 /// message.
 pub struct MockedBlockchain {
     logic: RefCell<VMLogic<'static>>,
+    /// Block timestamp overriding the one of the context, set by `test_utils::set_block_timestamp`.
+    block_timestamp: Option<u64>,
     // We keep ownership over logic fixture so that references in `VMLogic` are valid.
     #[allow(dead_code)]
     logic_fixture: LogicFixture,
@@ -76,13 +78,18 @@ impl MockedBlockchain {
         };
 
         let logic = RefCell::new(logic);
-        Self { logic, logic_fixture }
+        Self { logic, block_timestamp: None, logic_fixture }
     }
 
     pub fn take_storage(&mut self) -> HashMap<Vec<u8>, Vec<u8>> {
         std::mem::take(&mut self.logic_fixture.ext.fake_trie)
     }
 
+    /// Contents of the storage, keeping it in place.
+    pub fn storage(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.logic_fixture.ext.fake_trie
+    }
+
     pub fn created_receipts(&self) -> &Vec<Receipt> {
         &self.logic_fixture.ext.receipts
     }
@@ -97,6 +104,11 @@ impl MockedBlockchain {
     pub fn logs(&self) -> Vec<String> {
         self.logic.borrow().clone_outcome().logs
     }
+
+    /// Overrides the block timestamp of the context, keeping the rest of the state.
+    pub fn set_block_timestamp(&mut self, block_timestamp: u64) {
+        self.block_timestamp = Some(block_timestamp);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -144,7 +156,8 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C" fn block_timestamp() -> u64 {
-        with_mock_interface(|b| b.block_timestamp())
+        let block_timestamp = crate::mock::with_mocked_blockchain(|b| b.block_timestamp);
+        block_timestamp.unwrap_or_else(|| with_mock_interface(|b| b.block_timestamp()))
     }
     #[no_mangle]
     extern "C" fn epoch_height() -> u64 {
@@ -457,4 +470,47 @@ mod mock_chain {
     extern "C" fn validator_total_stake(stake_ptr: u64) {
         with_mock_interface(|b| b.validator_total_stake(stake_ptr))
     }
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    #[no_mangle]
+    extern "C" fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) {
+        with_mock_interface(|b| b.alt_bn128_g1_multiexp(value_len, value_ptr, register_id))
+    }
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    #[no_mangle]
+    extern "C" fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64) {
+        with_mock_interface(|b| b.alt_bn128_g1_sum(value_len, value_ptr, register_id))
+    }
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    #[no_mangle]
+    extern "C" fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64 {
+        with_mock_interface(|b| b.alt_bn128_pairing_check(value_len, value_ptr))
+    }
+    /// `VMLogic` of the mocked blockchain predates `ed25519_verify`, so the signature is verified
+    /// here, without charging gas. The pointers of the mocked memory are host pointers.
+    #[cfg(feature = "protocol_feature_ed25519_verify")]
+    #[no_mangle]
+    extern "C" fn ed25519_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64 {
+        use ed25519_dalek::{PublicKey, Signature, Verifier};
+        use std::convert::TryFrom;
+
+        let read = |len: u64, ptr: u64| unsafe {
+            std::slice::from_raw_parts(ptr as *const u8, len as usize)
+        };
+        let signature = match Signature::try_from(read(sig_len, sig_ptr)) {
+            Ok(signature) => signature,
+            Err(_) => return 0,
+        };
+        let public_key = match PublicKey::from_bytes(read(pub_key_len, pub_key_ptr)) {
+            Ok(public_key) => public_key,
+            Err(_) => return 0,
+        };
+        public_key.verify(read(msg_len, msg_ptr), &signature).is_ok() as u64
+    }
 }
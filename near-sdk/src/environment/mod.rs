@@ -1,5 +1,8 @@
 pub mod env;
 
+#[cfg(feature = "estimate")]
+pub(crate) mod overlay;
+
 #[cfg(not(target_arch = "wasm32"))]
 /// Mock blockchain utilities. These can only be used inside tests and are not available for
 /// a wasm32 target.
@@ -0,0 +1,138 @@
+//! Scratch storage overlay used by [`GasEstimate::measure`](crate::GasEstimate::measure) and
+//! [`MigrationSummary::measure`](crate::MigrationSummary::measure). While it is active,
+//! `env::storage_*` writes are kept in memory instead of being sent to the host, logs are
+//! dropped and promises can't be created, so that methods with side effects can be run without
+//! committing anything.
+//! Only compiled with the `estimate` feature, so that the storage functions don't check for it
+//! otherwise.
+
+use super::env;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// Number of bytes the runtime charges for every storage record on top of its key and value.
+const STORAGE_RECORD_EXTRA_BYTES: i64 = 40;
+
+// Fees of the storage host functions of the current protocol, charged on top of the gas used by
+// the method for the writes which are kept in the overlay.
+const STORAGE_WRITE_BASE: u64 = 64_196_736_000;
+const STORAGE_WRITE_KEY_BYTE: u64 = 70_482_867;
+const STORAGE_WRITE_VALUE_BYTE: u64 = 31_018_539;
+const STORAGE_WRITE_EVICTED_BYTE: u64 = 32_117_307;
+const STORAGE_REMOVE_BASE: u64 = 53_473_030_500;
+const STORAGE_REMOVE_KEY_BYTE: u64 = 38_220_384;
+const STORAGE_REMOVE_RET_VALUE_BYTE: u64 = 11_531_556;
+
+#[derive(Default)]
+pub(crate) struct Overlay {
+    /// Written values by key, `None` for removed keys.
+    entries: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    evicted: Option<Vec<u8>>,
+    /// Change of the storage usage caused by the writes, in bytes.
+    pub(crate) storage_usage_delta: i64,
+    /// Fees of the storage host functions which weren't called.
+    pub(crate) storage_gas: u64,
+}
+
+thread_local! {
+    static OVERLAY: RefCell<Option<Overlay>> = RefCell::new(None);
+}
+
+/// Starts keeping storage writes in memory. Panics if the overlay is already active.
+pub(crate) fn begin() {
+    OVERLAY.with(|overlay| {
+        let mut overlay = overlay.borrow_mut();
+        if overlay.is_some() {
+            env::panic_str("Storage overlay is already active");
+        }
+        *overlay = Some(Overlay::default());
+    })
+}
+
+/// Discards the writes kept in memory and returns the overlay, `None` if it wasn't active.
+pub(crate) fn end() -> Option<Overlay> {
+    OVERLAY.with(|overlay| overlay.borrow_mut().take())
+}
+
+fn is_active() -> bool {
+    OVERLAY.with(|overlay| overlay.borrow().is_some())
+}
+
+/// Whether logs should be dropped, since the logs of a method run against the overlay, e.g.
+/// events, would describe writes which are discarded.
+pub(crate) fn discard_log() -> bool {
+    is_active()
+}
+
+/// Panics if the overlay is active, since the receipts of promises can't be discarded.
+pub(crate) fn deny_promise() {
+    if is_active() {
+        env::panic_str("Promises can't be created while estimating a method");
+    }
+}
+
+fn update<R>(f: impl FnOnce(&mut Overlay) -> R) -> R {
+    OVERLAY.with(|overlay| f(overlay.borrow_mut().as_mut().unwrap_or_else(|| env::abort())))
+}
+
+/// Returns the value written to the overlay, or `None` if the overlay isn't active or the key
+/// wasn't written, in which case it is read from the host.
+pub(crate) fn read(key: &[u8]) -> Option<Option<Vec<u8>>> {
+    OVERLAY.with(|overlay| overlay.borrow().as_ref()?.entries.get(key).cloned())
+}
+
+/// Writes the value to the overlay, returning whether the key existed, or `None` if the overlay
+/// isn't active.
+pub(crate) fn write(key: &[u8], value: &[u8]) -> Option<bool> {
+    if !is_active() {
+        return None;
+    }
+    let previous = env::storage_read(key);
+    update(|overlay| {
+        overlay.storage_usage_delta += match &previous {
+            Some(previous) => value.len() as i64 - previous.len() as i64,
+            None => key.len() as i64 + value.len() as i64 + STORAGE_RECORD_EXTRA_BYTES,
+        };
+        overlay.storage_gas += STORAGE_WRITE_BASE
+            + STORAGE_WRITE_KEY_BYTE * key.len() as u64
+            + STORAGE_WRITE_VALUE_BYTE * value.len() as u64
+            + STORAGE_WRITE_EVICTED_BYTE * previous.as_ref().map_or(0, Vec::len) as u64;
+        overlay.entries.insert(key.to_vec(), Some(value.to_vec()));
+        let existed = previous.is_some();
+        overlay.evicted = previous;
+        Some(existed)
+    })
+}
+
+/// Removes the key in the overlay, returning whether it existed, or `None` if the overlay isn't
+/// active.
+pub(crate) fn remove(key: &[u8]) -> Option<bool> {
+    if !is_active() {
+        return None;
+    }
+    let previous = env::storage_read(key);
+    update(|overlay| {
+        if let Some(previous) = &previous {
+            overlay.storage_usage_delta -=
+                key.len() as i64 + previous.len() as i64 + STORAGE_RECORD_EXTRA_BYTES;
+        }
+        overlay.storage_gas += STORAGE_REMOVE_BASE
+            + STORAGE_REMOVE_KEY_BYTE * key.len() as u64
+            + STORAGE_REMOVE_RET_VALUE_BYTE * previous.as_ref().map_or(0, Vec::len) as u64;
+        overlay.entries.insert(key.to_vec(), None);
+        let existed = previous.is_some();
+        overlay.evicted = previous;
+        Some(existed)
+    })
+}
+
+/// Returns the change of the storage usage caused by the writes to the overlay, or `None` if it
+/// isn't active.
+pub(crate) fn storage_usage_delta() -> Option<i64> {
+    OVERLAY.with(|overlay| overlay.borrow().as_ref().map(|overlay| overlay.storage_usage_delta))
+}
+
+/// Returns the value evicted by the last write to the overlay, or `None` if it isn't active.
+pub(crate) fn evicted() -> Option<Option<Vec<u8>>> {
+    OVERLAY.with(|overlay| overlay.borrow().as_ref().map(|overlay| overlay.evicted.clone()))
+}
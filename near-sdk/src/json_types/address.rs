@@ -0,0 +1,394 @@
+use crate::env;
+use borsh::{BorshDeserialize, BorshSerialize};
+use bs58::decode::Error as B58Error;
+use serde::{de, ser, Deserialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+
+/// Ethereum address, as used by bridge connectors and omnichain tokens which release assets on
+/// Ethereum or EVM chains.
+///
+/// In JSON, it's a `0x`-prefixed hex string. Parsing accepts all-lowercase and all-uppercase
+/// addresses, while mixed-case addresses must have a valid
+/// [EIP-55](https://eips.ethereum.org/EIPS/eip-55) checksum, so that a mistyped address is
+/// rejected instead of receiving the assets. Addresses are displayed with their checksum. In
+/// Borsh, it's the 20 bytes of the address.
+///
+/// Computing the checksum uses [`env::keccak256_array`], so parsing and displaying an address
+/// requires the blockchain environment, or the mocked one in tests.
+#[derive(
+    Debug, Copy, Clone, PartialEq, PartialOrd, Ord, Eq, Hash, BorshDeserialize, BorshSerialize,
+)]
+pub struct EthAddress([u8; 20]);
+
+impl EthAddress {
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Returns the address as a `0x`-prefixed hex string with the EIP-55 checksum.
+    pub fn to_checksum_string(&self) -> String {
+        let lowercase = encode_hex(&self.0);
+        let hash = env::keccak256_array(lowercase.as_bytes());
+        let checksummed: String = lowercase
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let byte = hash[i / 2];
+                let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!("0x{}", checksummed)
+    }
+}
+
+impl From<[u8; 20]> for EthAddress {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<EthAddress> for [u8; 20] {
+    fn from(address: EthAddress) -> Self {
+        address.0
+    }
+}
+
+impl fmt::Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum_string())
+    }
+}
+
+impl std::str::FromStr for EthAddress {
+    type Err = ParseAddressError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value.strip_prefix("0x").ok_or(ParseAddressErrorKind::MissingPrefix)?;
+        if digits.len() != 40 {
+            return Err(ParseAddressErrorKind::InvalidLength(digits.len()).into());
+        }
+        let mut bytes = [0u8; 20];
+        for (byte, pair) in bytes.iter_mut().zip(digits.as_bytes().chunks(2)) {
+            *byte = (decode_hex_digit(pair[0])? << 4) | decode_hex_digit(pair[1])?;
+        }
+        let address = Self(bytes);
+        let has_lowercase = digits.bytes().any(|c| c.is_ascii_lowercase());
+        let has_uppercase = digits.bytes().any(|c| c.is_ascii_uppercase());
+        if has_lowercase && has_uppercase && address.to_checksum_string()[2..] != *digits {
+            return Err(ParseAddressErrorKind::InvalidChecksum.into());
+        }
+        Ok(address)
+    }
+}
+
+impl TryFrom<&str> for EthAddress {
+    type Error = ParseAddressError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl ser::Serialize for EthAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_checksum_string())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for EthAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse::<Self>().map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+/// Network of a [`BtcAddress`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BtcNetwork {
+    Mainnet,
+    Testnet,
+}
+
+/// Type of the script a [`BtcAddress`] pays to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BtcAddressKind {
+    /// Pay to public key hash, addresses starting with `1` on mainnet.
+    P2pkh,
+    /// Pay to script hash, addresses starting with `3` on mainnet.
+    P2sh,
+}
+
+/// Bitcoin legacy address, as used by bridge connectors which release assets on Bitcoin.
+///
+/// In JSON, it's the Base58Check string of the address, e.g.
+/// `"1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"`. Parsing verifies the checksum and only accepts P2PKH
+/// and P2SH addresses of the mainnet and testnet. In Borsh, it's the version byte followed by the
+/// 20 bytes of the hash.
+///
+/// Verifying the checksum uses [`env::sha256_array`], so parsing and displaying an address
+/// requires the blockchain environment, or the mocked one in tests.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Ord, Eq, Hash, BorshSerialize)]
+pub struct BtcAddress {
+    version: u8,
+    hash: [u8; 20],
+}
+
+const BTC_VERSIONS: [(u8, BtcNetwork, BtcAddressKind); 4] = [
+    (0x00, BtcNetwork::Mainnet, BtcAddressKind::P2pkh),
+    (0x05, BtcNetwork::Mainnet, BtcAddressKind::P2sh),
+    (0x6f, BtcNetwork::Testnet, BtcAddressKind::P2pkh),
+    (0xc4, BtcNetwork::Testnet, BtcAddressKind::P2sh),
+];
+
+impl BtcAddress {
+    pub fn new(network: BtcNetwork, kind: BtcAddressKind, hash: [u8; 20]) -> Self {
+        let version = match (network, kind) {
+            (BtcNetwork::Mainnet, BtcAddressKind::P2pkh) => 0x00,
+            (BtcNetwork::Mainnet, BtcAddressKind::P2sh) => 0x05,
+            (BtcNetwork::Testnet, BtcAddressKind::P2pkh) => 0x6f,
+            (BtcNetwork::Testnet, BtcAddressKind::P2sh) => 0xc4,
+        };
+        Self { version, hash }
+    }
+
+    pub fn network(&self) -> BtcNetwork {
+        self.info().1
+    }
+
+    pub fn kind(&self) -> BtcAddressKind {
+        self.info().2
+    }
+
+    /// Returns the hash of the public key or script the address pays to.
+    pub fn hash(&self) -> &[u8; 20] {
+        &self.hash
+    }
+
+    fn try_from_parts(version: u8, hash: [u8; 20]) -> Result<Self, ParseAddressError> {
+        if !BTC_VERSIONS.iter().any(|(v, _, _)| *v == version) {
+            return Err(ParseAddressErrorKind::InvalidVersion(version).into());
+        }
+        Ok(Self { version, hash })
+    }
+
+    fn info(&self) -> (u8, BtcNetwork, BtcAddressKind) {
+        // The version is validated on construction.
+        BTC_VERSIONS.iter().copied().find(|(v, _, _)| *v == self.version).unwrap()
+    }
+
+    fn payload(&self) -> [u8; 21] {
+        let mut payload = [0u8; 21];
+        payload[0] = self.version;
+        payload[1..].copy_from_slice(&self.hash);
+        payload
+    }
+}
+
+fn btc_checksum(payload: &[u8]) -> [u8; 4] {
+    let hash = env::sha256_array(&env::sha256_array(payload));
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+impl fmt::Display for BtcAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let payload = self.payload();
+        let bytes = [&payload[..], &btc_checksum(&payload)].concat();
+        f.write_str(&bs58::encode(bytes).into_string())
+    }
+}
+
+impl std::str::FromStr for BtcAddress {
+    type Err = ParseAddressError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(value).into_vec()?;
+        if bytes.len() != 25 {
+            return Err(ParseAddressErrorKind::InvalidLength(bytes.len()).into());
+        }
+        let (payload, checksum) = bytes.split_at(21);
+        if btc_checksum(payload) != checksum {
+            return Err(ParseAddressErrorKind::InvalidChecksum.into());
+        }
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&payload[1..]);
+        Self::try_from_parts(payload[0], hash)
+    }
+}
+
+impl TryFrom<&str> for BtcAddress {
+    type Error = ParseAddressError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl BorshDeserialize for BtcAddress {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let version = u8::deserialize(buf)?;
+        let hash = <[u8; 20]>::deserialize(buf)?;
+        Self::try_from_parts(version, hash)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
+impl ser::Serialize for BtcAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for BtcAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse::<Self>().map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    bytes
+        .iter()
+        .flat_map(|byte| {
+            let high = DIGITS[(byte >> 4) as usize] as char;
+            let low = DIGITS[(byte & 0x0f) as usize] as char;
+            std::iter::once(high).chain(std::iter::once(low))
+        })
+        .collect()
+}
+
+fn decode_hex_digit(digit: u8) -> Result<u8, ParseAddressError> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(ParseAddressErrorKind::InvalidHex.into()),
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseAddressError {
+    kind: ParseAddressErrorKind,
+}
+
+#[derive(Debug)]
+enum ParseAddressErrorKind {
+    MissingPrefix,
+    InvalidLength(usize),
+    InvalidHex,
+    InvalidChecksum,
+    InvalidVersion(u8),
+    Base58(B58Error),
+}
+
+impl fmt::Display for ParseAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseAddressErrorKind::MissingPrefix => write!(f, "the address must start with 0x"),
+            ParseAddressErrorKind::InvalidLength(l) => {
+                write!(f, "invalid length of the address: {}", l)
+            }
+            ParseAddressErrorKind::InvalidHex => write!(f, "invalid hex digit in the address"),
+            ParseAddressErrorKind::InvalidChecksum => write!(f, "invalid checksum of the address"),
+            ParseAddressErrorKind::InvalidVersion(v) => {
+                write!(f, "unsupported address version: {}", v)
+            }
+            ParseAddressErrorKind::Base58(e) => write!(f, "base58 decoding error: {}", e),
+        }
+    }
+}
+
+impl From<ParseAddressErrorKind> for ParseAddressError {
+    fn from(kind: ParseAddressErrorKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl From<B58Error> for ParseAddressError {
+    fn from(e: B58Error) -> Self {
+        Self { kind: ParseAddressErrorKind::Base58(e) }
+    }
+}
+
+impl std::error::Error for ParseAddressError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eth_address_checksum() {
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ]
+        .iter()
+        {
+            let parsed: EthAddress = address.parse().unwrap();
+            assert_eq!(parsed.to_string(), *address);
+            assert_eq!(address.to_lowercase().parse::<EthAddress>().unwrap(), parsed);
+            assert_eq!(
+                serde_json::to_string(&parsed).unwrap(),
+                serde_json::to_string(address).unwrap()
+            );
+        }
+        // A single wrong case is rejected.
+        assert_eq!(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"
+                .parse::<EthAddress>()
+                .unwrap_err()
+                .to_string(),
+            "invalid checksum of the address"
+        );
+        assert!("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse::<EthAddress>().is_err());
+        assert!("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea".parse::<EthAddress>().is_err());
+        assert!("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beagd".parse::<EthAddress>().is_err());
+    }
+
+    #[test]
+    fn btc_address_checksum() {
+        let address: BtcAddress = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2".parse().unwrap();
+        assert_eq!(address.network(), BtcNetwork::Mainnet);
+        assert_eq!(address.kind(), BtcAddressKind::P2pkh);
+        assert_eq!(address.hash()[..2], [0x77, 0xbf]);
+        assert_eq!(address.to_string(), "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2");
+
+        let address: BtcAddress = "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy".parse().unwrap();
+        assert_eq!(address.kind(), BtcAddressKind::P2sh);
+        let address: BtcAddress =
+            serde_json::from_str("\"mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn\"").unwrap();
+        assert_eq!(address.network(), BtcNetwork::Testnet);
+        assert_eq!(BtcAddress::try_from_slice(&address.try_to_vec().unwrap()).unwrap(), address);
+        assert_eq!(
+            BtcAddress::new(BtcNetwork::Testnet, BtcAddressKind::P2pkh, *address.hash()),
+            address
+        );
+
+        assert_eq!(
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3".parse::<BtcAddress>().unwrap_err().to_string(),
+            "invalid checksum of the address"
+        );
+        assert!(BtcAddress::try_from_slice(&[0x01; 21]).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use super::Base64VecU8;
+use crate::env;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+/// Opaque position in a paginated view, serialized as the base64 encoding of the Borsh
+/// serialization of the position, e.g. the key of the last returned element.
+///
+/// Views return the cursor of the next page in a [`Page`] and take it back as `from_cursor`, so
+/// clients don't depend on numeric indices, which shift when elements are removed from a
+/// collection with `swap_remove`. Clients must not build or decode cursors themselves.
+///
+/// # Example
+/// ```
+/// use near_sdk::json_types::Cursor;
+///
+/// let cursor = Cursor::new(&"token-42".to_string());
+/// assert_eq!(near_sdk::serde_json::to_string(&cursor).unwrap(), "\"CAAAAHRva2VuLTQy\"");
+/// assert_eq!(cursor.position::<String>(), "token-42");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+pub struct Cursor(Base64VecU8);
+
+impl Cursor {
+    pub fn new<P: BorshSerialize>(position: &P) -> Self {
+        Self(position.try_to_vec().unwrap_or_else(|_| env::abort()).into())
+    }
+
+    /// Decodes the position. Panics if the cursor wasn't created with a position of type `P`.
+    pub fn position<P: BorshDeserialize>(&self) -> P {
+        P::try_from_slice(&(self.0).0)
+            .unwrap_or_else(|_| env::panic_str("Invalid pagination cursor"))
+    }
+}
+
+/// Page of a paginated view, with the cursor to pass as `from_cursor` to get the next page, or
+/// `None` if this is the last page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Collects up to `limit` items, all of them without a limit, from an iterator of the items
+    /// with their positions. The position of the last collected item becomes the cursor of the
+    /// next page if more items follow. Panics if `limit` is zero.
+    pub fn collect<P, I>(iter: I, limit: Option<u64>) -> Self
+    where
+        P: BorshSerialize,
+        I: IntoIterator<Item = (P, T)>,
+    {
+        let limit = limit.unwrap_or(u64::MAX);
+        if limit == 0 {
+            env::panic_str("Cannot provide limit of 0.");
+        }
+        let mut items = Vec::new();
+        let mut last_position = None;
+        let mut iter = iter.into_iter();
+        for (position, item) in iter.by_ref() {
+            items.push(item);
+            last_position = Some(position);
+            if items.len() as u64 == limit {
+                break;
+            }
+        }
+        let next_cursor = match (last_position, iter.next()) {
+            (Some(position), Some(_)) => Some(Cursor::new(&position)),
+            _ => None,
+        };
+        Self { items, next_cursor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_pages() {
+        let items = || (1u32..=5).map(|i| (i, i * 10));
+        let page = Page::collect(items(), Some(2));
+        assert_eq!(page.items, vec![10, 20]);
+        let position: u32 = page.next_cursor.unwrap().position();
+        assert_eq!(position, 2);
+        let page = Page::collect(items().skip(position as usize), Some(3));
+        assert_eq!(page.items, vec![30, 40, 50]);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(Page::collect(items(), None).items.len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid pagination cursor")]
+    fn invalid_cursor() {
+        Cursor::new(&1u8).position::<u64>();
+    }
+}
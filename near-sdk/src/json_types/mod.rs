@@ -1,13 +1,19 @@
 //! Helper types for JSON serialization.
 
+mod address;
+mod cursor;
 mod hash;
 mod integers;
+mod seq;
 mod vector;
 
 use crate::types::{AccountId, PublicKey};
 
+pub use address::{BtcAddress, BtcAddressKind, BtcNetwork, EthAddress, ParseAddressError};
+pub use cursor::{Cursor, Page};
 pub use hash::Base58CryptoHash;
 pub use integers::{I128, I64, U128, U64};
+pub use seq::JsonSeq;
 pub use vector::Base64VecU8;
 
 #[deprecated(
@@ -0,0 +1,68 @@
+use serde::ser::Error;
+use serde::{Serialize, Serializer};
+use std::cell::RefCell;
+
+/// Iterator which is serialized as a JSON array, item by item, so that view methods can return
+/// the records of a collection without collecting them into a `Vec` first. Useful for methods
+/// returning thousands of records, which would otherwise be held in memory twice.
+///
+/// The iterator is consumed by serialization, so the value can only be serialized once.
+///
+/// # Example
+/// ```
+/// use near_sdk::collections::Vector;
+/// use near_sdk::json_types::JsonSeq;
+///
+/// let mut records: Vector<u64> = Vector::new(b"r");
+/// records.extend(vec![1, 2, 3]);
+///
+/// let seq = JsonSeq::new(records.iter().filter(|record| record % 2 == 1));
+/// assert_eq!(near_sdk::serde_json::to_string(&seq).unwrap(), "[1,3]");
+/// ```
+pub struct JsonSeq<I>(RefCell<Option<I>>);
+
+impl<I> JsonSeq<I> {
+    pub fn new(iter: I) -> Self {
+        Self(RefCell::new(Some(iter)))
+    }
+}
+
+impl<I> From<I> for JsonSeq<I>
+where
+    I: Iterator,
+{
+    fn from(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<I> Serialize for JsonSeq<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| S::Error::custom("Sequence already serialized"))?;
+        serializer.collect_seq(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_once() {
+        let seq = JsonSeq::new((0..3).map(|i| i.to_string()));
+        assert_eq!(serde_json::to_string(&seq).unwrap(), r#"["0","1","2"]"#);
+        assert!(serde_json::to_string(&seq).is_err());
+        assert_eq!(serde_json::to_string(&JsonSeq::new(std::iter::empty::<u8>())).unwrap(), "[]");
+    }
+}
@@ -0,0 +1,295 @@
+//! Declared storage layout of a contract: the prefixes under which its components store their
+//! entries and the types of those entries, so that components can't silently write over each
+//! other and tools can decode the raw state.
+//!
+//! Each component describes the storage it uses under a prefix by implementing
+//! [`StorageLayout`], and the contract declares the prefix of each of its fields with
+//! [`storage_layout!`](crate::storage_layout):
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::collections::{LazyOption, UnorderedMap};
+//! use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     balances: UnorderedMap<AccountId, u128>,
+//!     metadata: LazyOption<String>,
+//! }
+//!
+//! near_sdk::storage_layout!(Contract {
+//!     balances: UnorderedMap<AccountId, u128> = b"b",
+//!     metadata: LazyOption<String> = b"m",
+//! });
+//!
+//! impl Contract {
+//!     pub fn new() -> Self {
+//!         Self {
+//!             balances: UnorderedMap::new(Self::balances),
+//!             metadata: LazyOption::new(Self::metadata, None),
+//!         }
+//!     }
+//! }
+//!
+//! let manifest = Contract::storage_layout();
+//! assert_eq!(manifest.fields[0].regions.len(), 3);
+//! assert!(manifest.overlaps().is_empty());
+//! ```
+//!
+//! Declaring a prefix which is a prefix of another one, including the `STATE` key of the
+//! contract struct, fails to compile with the error `overlapping storage prefixes`. The prefixes
+//! must therefore be constant byte strings, and the check requires Rust 1.57.
+//!
+//! The macro also generates an associated constant with the prefix of each field, named like the
+//! field, which the constructors of the contract pass to the components so that the declared
+//! prefixes are the ones actually used. The manifest returned by the generated `storage_layout`
+//! function also checks the regions within the components with [`LayoutManifest::overlaps`] and
+//! serializes to JSON for tools and docs.
+
+use crate::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
+use crate::json_types::Base64VecU8;
+use serde::Serialize;
+use std::any::type_name;
+
+/// Key of the contract struct, which every contract stores.
+pub const STATE_KEY: &[u8] = b"STATE";
+
+/// Entries stored under a prefix, keyed by the Borsh serialization of `key` after the prefix.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StorageRegion {
+    pub prefix: Base64VecU8,
+    /// Type of the key after the prefix, empty for a single entry stored at the prefix.
+    pub key: String,
+    /// Type of the values.
+    pub value: String,
+}
+
+impl StorageRegion {
+    pub fn new(prefix: Vec<u8>, key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self { prefix: prefix.into(), key: key.into(), value: value.into() }
+    }
+
+    /// Region of a map from `K` to `V` under the prefix.
+    pub fn map<K: ?Sized, V: ?Sized>(prefix: Vec<u8>) -> Self {
+        Self::new(prefix, type_name::<K>(), type_name::<V>())
+    }
+}
+
+/// Storage used by a component created with a prefix, like a collection or a standard.
+pub trait StorageLayout {
+    /// Regions of storage used by the component created with `prefix`.
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion>;
+}
+
+/// Storage of a field of the contract.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FieldLayout {
+    pub field: String,
+    /// Type of the field.
+    pub component: String,
+    pub prefix: Base64VecU8,
+    pub regions: Vec<StorageRegion>,
+}
+
+impl FieldLayout {
+    pub fn new<T: StorageLayout + ?Sized>(field: &str, prefix: &[u8]) -> Self {
+        Self {
+            field: field.to_string(),
+            component: type_name::<T>().to_string(),
+            prefix: prefix.to_vec().into(),
+            regions: T::storage_layout(prefix),
+        }
+    }
+}
+
+/// Storage layout of a contract, as generated by [`storage_layout!`](crate::storage_layout).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LayoutManifest {
+    pub contract: String,
+    /// The contract struct, stored at [`STATE_KEY`].
+    pub state: StorageRegion,
+    pub fields: Vec<FieldLayout>,
+}
+
+impl LayoutManifest {
+    pub fn new(contract: &str, fields: Vec<FieldLayout>) -> Self {
+        Self {
+            contract: contract.to_string(),
+            state: StorageRegion::new(STATE_KEY.to_vec(), "", contract),
+            fields,
+        }
+    }
+
+    /// Returns the names of the pairs of fields with overlapping regions, `"STATE"` for the
+    /// contract struct.
+    pub fn overlaps(&self) -> Vec<(String, String)> {
+        let state = ("STATE", &self.state);
+        let regions: Vec<(&str, &StorageRegion)> = std::iter::once(state)
+            .chain(self.fields.iter().flat_map(|field| {
+                field.regions.iter().map(move |region| (field.field.as_str(), region))
+            }))
+            .collect();
+        let mut overlaps = vec![];
+        for (i, (field, region)) in regions.iter().enumerate() {
+            for (other_field, other) in &regions[i + 1..] {
+                if field != other_field
+                    && prefixes_overlap(&region.prefix.0, &other.prefix.0)
+                    && !overlaps.contains(&(field.to_string(), other_field.to_string()))
+                {
+                    overlaps.push((field.to_string(), other_field.to_string()));
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// Serializes the manifest to pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| crate::env::abort())
+    }
+}
+
+/// Returns `true` if one prefix is a prefix of the other, so that their keys can collide.
+pub const fn prefixes_overlap(a: &[u8], b: &[u8]) -> bool {
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Returns `true` if any two of the prefixes overlap. Evaluated at compile time by
+/// [`storage_layout!`](crate::storage_layout).
+pub const fn have_overlapping_prefixes(prefixes: &[&[u8]]) -> bool {
+    let mut i = 0;
+    while i < prefixes.len() {
+        let mut j = i + 1;
+        while j < prefixes.len() {
+            if prefixes_overlap(prefixes[i], prefixes[j]) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+fn append(prefix: &[u8], suffix: u8) -> Vec<u8> {
+    [prefix, &[suffix]].concat()
+}
+
+impl<K, V> StorageLayout for LookupMap<K, V> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![StorageRegion::map::<K, V>(prefix.to_vec())]
+    }
+}
+
+impl<T> StorageLayout for LookupSet<T> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![StorageRegion::map::<T, ()>(prefix.to_vec())]
+    }
+}
+
+impl<T> StorageLayout for Vector<T> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        // Indices are stored in little endian rather than Borsh, which is the same for `u64`.
+        vec![StorageRegion::map::<u64, T>(prefix.to_vec())]
+    }
+}
+
+impl<T> StorageLayout for LazyOption<T> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![StorageRegion::new(prefix.to_vec(), "", type_name::<T>())]
+    }
+}
+
+impl<K, V> StorageLayout for UnorderedMap<K, V> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![
+            StorageRegion::map::<K, u64>(append(prefix, b'i')),
+            StorageRegion::map::<u64, K>(append(prefix, b'k')),
+            StorageRegion::map::<u64, V>(append(prefix, b'v')),
+        ]
+    }
+}
+
+impl<T> StorageLayout for UnorderedSet<T> {
+    fn storage_layout(prefix: &[u8]) -> Vec<StorageRegion> {
+        vec![
+            StorageRegion::map::<T, u64>(append(prefix, b'i')),
+            StorageRegion::map::<u64, T>(append(prefix, b'e')),
+        ]
+    }
+}
+
+/// Declares the storage prefixes of the fields of a contract, checking at compile time that no
+/// two prefixes overlap with each other or with the `STATE` key, and generates the
+/// `storage_layout` function of the contract returning its [`LayoutManifest`].
+///
+/// Each field is declared with the type of its component, which implements [`StorageLayout`],
+/// and its prefix as a constant byte string, exposed as an associated constant named like the
+/// field for the constructors. See the [`layout`](crate::layout) module.
+#[macro_export]
+macro_rules! storage_layout {
+    ($contract: ident { $($field: ident: $component: ty = $prefix: expr),* $(,)? }) => {
+        const _: () = assert!(
+            !$crate::layout::have_overlapping_prefixes(&[
+                $crate::layout::STATE_KEY,
+                $($prefix as &[u8],)*
+            ]),
+            "overlapping storage prefixes"
+        );
+
+        impl $contract {
+            $(
+                /// Storage prefix of the field, declared with `near_sdk::storage_layout!`.
+                #[allow(non_upper_case_globals)]
+                pub const $field: &'static [u8] = $prefix;
+            )*
+
+            /// Storage layout of the contract, declared with `near_sdk::storage_layout!`.
+            pub fn storage_layout() -> $crate::layout::LayoutManifest {
+                $crate::layout::LayoutManifest::new(
+                    stringify!($contract),
+                    vec![$($crate::layout::FieldLayout::new::<$component>(
+                        stringify!($field),
+                        $prefix,
+                    ),)*],
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_prefixes() {
+        assert!(prefixes_overlap(b"a", b"ab"));
+        assert!(prefixes_overlap(b"ab", b"a"));
+        assert!(!prefixes_overlap(b"ab", b"ac"));
+        assert!(have_overlapping_prefixes(&[STATE_KEY, b"a", b"S"]));
+        assert!(!have_overlapping_prefixes(&[STATE_KEY, b"a", b"b"]));
+    }
+
+    #[test]
+    fn manifest_overlaps() {
+        let manifest = LayoutManifest::new(
+            "Contract",
+            vec![
+                FieldLayout::new::<UnorderedSet<u32>>("set", b"s"),
+                FieldLayout::new::<LookupMap<u32, u32>>("map", b"se"),
+                FieldLayout::new::<Vector<u8>>("vector", b"v"),
+            ],
+        );
+        assert_eq!(manifest.overlaps(), vec![("set".to_string(), "map".to_string())]);
+        assert_eq!(manifest.fields[0].regions[1], StorageRegion::new(b"se".to_vec(), "u64", "u32"));
+    }
+}
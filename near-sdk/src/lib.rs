@@ -6,8 +6,9 @@
 extern crate quickcheck;
 
 pub use near_sdk_macros::{
-    callback, callback_vec, ext_contract, init, metadata, near_bindgen, result_serializer,
-    serializer, BorshStorageKey, FunctionError, PanicOnDefault,
+    abi_event, callback, callback_vec, ext_contract, health_check, init, invariant, metadata,
+    near_bindgen, result_serializer, serializer, BorshStorageKey, FunctionError, Migrate,
+    NearSchema, PanicOnDefault,
 };
 
 #[cfg(feature = "unstable")]
@@ -21,13 +22,17 @@ pub use environment::env;
 pub use near_sys as sys;
 
 mod promise;
-pub use promise::{Promise, PromiseOrValue};
+pub use promise::{FunctionCallKey, Promise, PromiseOrValue};
 
 mod metadata;
 pub use metadata::{Metadata, MethodMetadata};
 
 pub mod json_types;
 
+pub mod schema;
+
+pub mod layout;
+
 mod types;
 pub use crate::types::*;
 
@@ -48,11 +53,21 @@ pub use crate::utils::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod test_utils;
 
+#[cfg(all(feature = "offchain", not(target_arch = "wasm32")))]
+pub mod offchain;
+
+#[cfg(any(target_arch = "wasm32", test))]
+pub mod allocator;
+
 // Set up global allocator by default if custom-allocator feature is not set in wasm32 architecture.
-#[cfg(all(feature = "wee_alloc", target_arch = "wasm32"))]
+#[cfg(all(feature = "wee_alloc", not(feature = "bump_alloc"), target_arch = "wasm32"))]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+#[cfg(all(feature = "bump_alloc", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOC: allocator::BumpAlloc = allocator::BumpAlloc::INIT;
+
 // Exporting common crates
 
 #[doc(hidden)]
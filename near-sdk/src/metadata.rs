@@ -1,6 +1,6 @@
 use borsh::{schema::BorshSchemaContainer, BorshDeserialize, BorshSchema, BorshSerialize};
 /// Version of the metadata format.
-const METADATA_SEMVER: [u32; 3] = [0, 1, 0];
+const METADATA_SEMVER: [u32; 3] = [0, 2, 0];
 
 /// Metadata of the contract.
 #[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
@@ -33,4 +33,9 @@ pub struct MethodMetadata {
     pub callbacks_vec: Option<BorshSchemaContainer>,
     /// Schema of the return type.
     pub result: Option<BorshSchemaContainer>,
+    /// Minimum deposit in yoctoNEAR of a payable method, from `#[payable(min_deposit = ..)]`.
+    pub min_deposit: Option<u128>,
+    /// Gas recommended to attach to calls of the method, from `#[gas_budget(tgas = ..)]`, so
+    /// that wallets don't have to attach the maximum.
+    pub recommended_gas: Option<u64>,
 }
@@ -0,0 +1,174 @@
+//! Execution of contract logic natively outside of wasm, against in-memory storage.
+//!
+//! [`OffchainRuntime`] lets simulation tools, bots and model checking harnesses run the exact
+//! methods of a contract struct to project the effects of calls, without a node. Each execution
+//! installs the storage of the runtime into the environment, so collections and `env::*`
+//! functions work as they would on chain, and takes the updated storage back afterwards.
+//!
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::offchain::OffchainRuntime;
+//! use near_sdk::AccountId;
+//!
+//! #[derive(BorshDeserialize, BorshSerialize)]
+//! struct Counter {
+//!     value: u64,
+//! }
+//!
+//! let mut runtime = OffchainRuntime::new("counter.near".parse().unwrap());
+//! runtime.init(|| Counter { value: 0 });
+//! runtime.call(|counter: &mut Counter| counter.value += 1);
+//! assert_eq!(runtime.view(|counter: &Counter| counter.value), 1);
+//! ```
+use std::collections::HashMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::test_utils::VMContextBuilder;
+use crate::{env, AccountId, MockedBlockchain, RuntimeFeesConfig, VMConfig};
+
+/// In-memory runtime for a single contract account. See the [module documentation](self).
+pub struct OffchainRuntime {
+    context: VMContextBuilder,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    logs: Vec<String>,
+}
+
+impl OffchainRuntime {
+    /// Creates a runtime with empty storage for the contract deployed to `contract_id`.
+    pub fn new(contract_id: AccountId) -> Self {
+        Self::with_storage(contract_id, HashMap::new())
+    }
+
+    /// Creates a runtime from existing storage, e.g. a state dump of the contract.
+    pub fn with_storage(contract_id: AccountId, storage: HashMap<Vec<u8>, Vec<u8>>) -> Self {
+        let mut context = VMContextBuilder::new();
+        context.current_account_id(contract_id.clone()).predecessor_account_id(contract_id);
+        Self { context, storage, logs: vec![] }
+    }
+
+    /// Context of the following executions, to set the predecessor, attached deposit, block
+    /// timestamp and so on.
+    pub fn context(&mut self) -> &mut VMContextBuilder {
+        &mut self.context
+    }
+
+    /// Runs `f` against the storage of the runtime. If `f` panics, the storage is left as it was
+    /// before the execution.
+    pub fn execute<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        env::set_blockchain_interface(MockedBlockchain::new(
+            self.context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            vec![],
+            self.storage.clone(),
+            Default::default(),
+            None,
+        ));
+        let result = f();
+        crate::mock::with_mocked_blockchain(|b| {
+            self.storage = b.take_storage();
+            self.logs = b.logs();
+        });
+        result
+    }
+
+    /// Runs the initializer of the contract and stores the returned state.
+    pub fn init<T: BorshSerialize>(&mut self, f: impl FnOnce() -> T) {
+        self.execute(|| env::state_write(&f()))
+    }
+
+    /// Runs a change method on the contract state and stores the updated state.
+    pub fn call<T, R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: BorshSerialize + BorshDeserialize,
+    {
+        self.execute(|| {
+            let mut state = read_state::<T>();
+            let result = f(&mut state);
+            env::state_write(&state);
+            result
+        })
+    }
+
+    /// Runs a view method on the contract state.
+    pub fn view<T, R>(&mut self, f: impl FnOnce(&T) -> R) -> R
+    where
+        T: BorshDeserialize,
+    {
+        self.execute(|| f(&read_state::<T>()))
+    }
+
+    /// Logs of the last execution.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    pub fn storage(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+        &self.storage
+    }
+
+    pub fn into_storage(self) -> HashMap<Vec<u8>, Vec<u8>> {
+        self.storage
+    }
+}
+
+fn read_state<T: BorshDeserialize>() -> T {
+    env::state_read().unwrap_or_else(|| env::panic_str("The contract is not initialized"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::LookupMap;
+    use crate::test_utils::accounts;
+
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct Ledger {
+        balances: LookupMap<AccountId, u64>,
+    }
+
+    impl Ledger {
+        fn deposit(&mut self, amount: u64) {
+            let account_id = env::predecessor_account_id();
+            let balance = self.balances.get(&account_id).unwrap_or(0);
+            self.balances.insert(&account_id, &(balance + amount));
+            crate::log!("{} deposited {}", account_id, amount);
+        }
+    }
+
+    #[test]
+    fn call_and_view() {
+        let mut runtime = OffchainRuntime::new(accounts(0));
+        runtime.init(|| Ledger { balances: LookupMap::new(b"b") });
+        runtime.context().predecessor_account_id(accounts(1));
+        runtime.call(|ledger: &mut Ledger| ledger.deposit(5));
+        runtime.call(|ledger: &mut Ledger| ledger.deposit(2));
+        assert_eq!(runtime.logs(), ["bob deposited 2"]);
+        assert_eq!(runtime.view(|ledger: &Ledger| ledger.balances.get(&accounts(1))), Some(7));
+
+        let storage = runtime.into_storage();
+        let mut runtime = OffchainRuntime::with_storage(accounts(0), storage);
+        assert_eq!(runtime.view(|ledger: &Ledger| ledger.balances.get(&accounts(2))), None);
+    }
+
+    #[test]
+    fn panic_keeps_storage() {
+        let mut runtime = OffchainRuntime::new(accounts(0));
+        runtime.init(|| Ledger { balances: LookupMap::new(b"b") });
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            runtime.call(|ledger: &mut Ledger| {
+                ledger.deposit(5);
+                panic!("failed");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(runtime.view(|ledger: &Ledger| ledger.balances.get(&accounts(0))), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract is not initialized")]
+    fn not_initialized() {
+        OffchainRuntime::new(accounts(0)).view(|ledger: &Ledger| ledger.balances.get(&accounts(0)));
+    }
+}
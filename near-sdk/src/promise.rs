@@ -143,6 +143,58 @@ impl PromiseJoint {
     }
 }
 
+/// Function call access key added with [`Promise::add_function_call_key`]. The key can only
+/// call the `method_names` of `receiver_id`, or any of its methods if there are none, and spend
+/// at most its allowance on gas fees, without limit if the allowance isn't set.
+///
+/// ```no_run
+/// # use near_sdk::{env, FunctionCallKey, Promise, PublicKey};
+/// let public_key: PublicKey =
+///     "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+/// Promise::new(env::current_account_id()).add_function_call_key(
+///     FunctionCallKey::new(public_key, env::current_account_id(), &["claim", "create_account"])
+///         .with_allowance(250_000_000_000_000_000_000_000),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionCallKey {
+    public_key: PublicKey,
+    receiver_id: AccountId,
+    method_names: Vec<String>,
+    allowance: Balance,
+    nonce: u64,
+}
+
+impl FunctionCallKey {
+    /// Panics if a method name is empty or contains a comma.
+    pub fn new(public_key: PublicKey, receiver_id: AccountId, method_names: &[&str]) -> Self {
+        for method_name in method_names {
+            crate::require!(
+                !method_name.is_empty() && !method_name.contains(','),
+                "Method names of access keys can't be empty nor contain commas"
+            );
+        }
+        Self {
+            public_key,
+            receiver_id,
+            method_names: method_names.iter().map(|name| name.to_string()).collect(),
+            allowance: 0,
+            nonce: 0,
+        }
+    }
+
+    /// Sets the amount of yoctoNEAR the key can spend on gas fees.
+    pub fn with_allowance(mut self, allowance: Balance) -> Self {
+        self.allowance = allowance;
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+}
+
 /// A structure representing a result of the scheduled execution on another contract.
 ///
 /// Smart contract developers will explicitly use `Promise` in two situations:
@@ -304,6 +356,17 @@ impl Promise {
         })
     }
 
+    /// Add a function call access key to the given account.
+    pub fn add_function_call_key(self, key: FunctionCallKey) -> Self {
+        self.add_access_key_with_nonce(
+            key.public_key,
+            key.allowance,
+            key.receiver_id,
+            key.method_names.join(","),
+            key.nonce,
+        )
+    }
+
     /// Delete access key from the given account.
     pub fn delete_key(self, public_key: PublicKey) -> Self {
         self.add_action(PromiseAction::DeleteKey { public_key })
@@ -468,3 +531,54 @@ impl<T: borsh::BorshSerialize> borsh::BorshSerialize for PromiseOrValue<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::VmAction;
+    use crate::test_utils::{accounts, get_created_receipts};
+
+    fn public_key() -> PublicKey {
+        PublicKey::ed25519([1; 32])
+    }
+
+    #[test]
+    fn add_function_call_key() {
+        Promise::new(accounts(1)).add_function_call_key(
+            FunctionCallKey::new(public_key(), accounts(2), &["claim", "create_account"])
+                .with_allowance(100),
+        );
+        Promise::new(accounts(1)).add_function_call_key(FunctionCallKey::new(
+            public_key(),
+            accounts(2),
+            &[],
+        ));
+        let actions: Vec<VmAction> =
+            get_created_receipts().into_iter().flat_map(|receipt| receipt.actions).collect();
+        assert_eq!(
+            actions,
+            vec![
+                VmAction::AddKeyWithFunctionCall {
+                    public_key: public_key(),
+                    nonce: 0,
+                    allowance: Some(100),
+                    receiver_id: accounts(2),
+                    function_names: vec!["claim".to_string(), "create_account".to_string()],
+                },
+                VmAction::AddKeyWithFunctionCall {
+                    public_key: public_key(),
+                    nonce: 0,
+                    allowance: None,
+                    receiver_id: accounts(2),
+                    function_names: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Method names of access keys can't be empty nor contain commas")]
+    fn method_name_with_comma() {
+        FunctionCallKey::new(public_key(), accounts(2), &["claim,withdraw"]);
+    }
+}
@@ -0,0 +1,159 @@
+use super::args_schema;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Version of the format of the [`Abi`], changed when fields are renamed or removed.
+pub const ABI_SCHEMA_VERSION: &str = "0.1.0";
+
+/// Machine-readable JSON description of the methods and events of a contract, for frontends and
+/// indexers, built from the [`NearSchema`](super::NearSchema)s of their types.
+///
+/// ```
+/// use near_sdk::schema::{Abi, AbiMethod, NearSchema};
+/// use near_sdk::AccountId;
+///
+/// let mut abi = Abi::new();
+/// abi.add_method(AbiMethod {
+///     name: "get_status".to_string(),
+///     is_view: true,
+///     is_init: false,
+///     is_payable: false,
+///     is_private: false,
+///     params: Some(AbiMethod::params_schema(vec![("account_id", AccountId::json_schema())])),
+///     result: Some(Option::<String>::json_schema()),
+/// });
+/// let json = abi.to_json();
+/// assert!(json.starts_with(r#"{"schema_version":"0.1.0","methods":[{"name":"get_status""#));
+/// ```
+///
+/// With the `abi` feature, `near_sdk::metadata!{}` describes all `#[near_bindgen]` methods and
+/// the event data types marked with `#[abi_event(standard = "..", version = "..", event = "..")]`,
+/// and exports the `__contract_abi` view method returning the [`to_json`](Abi::to_json) of the
+/// description. The argument and return types of JSON methods and the event data types must
+/// implement `NearSchema`. The description is built when the method is called, as the schemas
+/// aren't known when the contract is compiled, so it isn't embedded in a custom section of the
+/// wasm.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Abi {
+    pub schema_version: String,
+    pub methods: Vec<AbiMethod>,
+    pub events: Vec<AbiEvent>,
+}
+
+/// Method of an [`Abi`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AbiMethod {
+    pub name: String,
+    pub is_view: bool,
+    pub is_init: bool,
+    pub is_payable: bool,
+    /// Whether only the contract itself can call the method, e.g. a callback.
+    pub is_private: bool,
+    /// Schema of the JSON object of the arguments, `None` if the method takes no JSON arguments.
+    pub params: Option<Value>,
+    /// Schema of the JSON result, `None` if the method returns nothing or its result isn't JSON.
+    pub result: Option<Value>,
+}
+
+/// NEP-297 event of an [`Abi`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AbiEvent {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    /// Schema of the `data` of the event.
+    pub data: Value,
+}
+
+impl Default for Abi {
+    fn default() -> Self {
+        Self { schema_version: ABI_SCHEMA_VERSION.to_string(), methods: vec![], events: vec![] }
+    }
+}
+
+impl Abi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_method(&mut self, method: AbiMethod) {
+        self.methods.push(method);
+    }
+
+    pub fn add_event(&mut self, event: AbiEvent) {
+        self.events.push(event);
+    }
+
+    /// Serializes the description to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Failed to serialize the ABI")
+    }
+}
+
+impl AbiMethod {
+    /// Returns the schema of the JSON object of the arguments from the names and schemas of the
+    /// arguments. The arguments which can't be `null` are required.
+    pub fn params_schema(args: Vec<(&str, Value)>) -> Value {
+        args_schema(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_types::U128;
+    use crate::schema::NearSchema;
+    use serde_json::json;
+
+    #[test]
+    fn methods_and_events() {
+        let mut abi = Abi::new();
+        abi.add_method(AbiMethod {
+            name: "donate".to_string(),
+            is_view: false,
+            is_init: false,
+            is_payable: true,
+            is_private: false,
+            params: Some(AbiMethod::params_schema(vec![
+                ("amount", U128::json_schema()),
+                ("message", Option::<String>::json_schema()),
+            ])),
+            result: None,
+        });
+        abi.add_event(AbiEvent {
+            standard: "nep141".to_string(),
+            version: "1.0.0".to_string(),
+            event: "ft_mint".to_string(),
+            data: Vec::<String>::json_schema(),
+        });
+        let abi: Value = serde_json::from_str(&abi.to_json()).unwrap();
+        assert_eq!(
+            abi,
+            json!({
+                "schema_version": "0.1.0",
+                "methods": [{
+                    "name": "donate",
+                    "is_view": false,
+                    "is_init": false,
+                    "is_payable": true,
+                    "is_private": false,
+                    "params": {
+                        "type": "object",
+                        "properties": {
+                            "amount": { "type": "string", "pattern": "^[0-9]+$" },
+                            "message": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                        },
+                        "required": ["amount"],
+                    },
+                    "result": null,
+                }],
+                "events": [{
+                    "standard": "nep141",
+                    "version": "1.0.0",
+                    "event": "ft_mint",
+                    "data": { "type": "array", "items": { "type": "string" } },
+                }],
+            })
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! JSON Schemas of the JSON representation of argument and return types, so that wallets and
+//! other clients can render human-readable prompts for custom contract methods.
+//!
+//! The schemas can be derived with `#[derive(NearSchema)]` for types which also derive serde's
+//! `Serialize` or `Deserialize`:
+//!
+//! ```
+//! use near_sdk::json_types::U128;
+//! use near_sdk::schema::NearSchema as _;
+//! use near_sdk::serde::Deserialize;
+//! use near_sdk::serde_json::json;
+//! use near_sdk::{AccountId, NearSchema};
+//!
+//! /// Donation to a campaign.
+//! #[derive(Deserialize, NearSchema)]
+//! #[serde(crate = "near_sdk::serde")]
+//! pub struct DonateArgs {
+//!     campaign_id: AccountId,
+//!     /// Amount in yoctoNEAR.
+//!     amount: U128,
+//!     message: Option<String>,
+//! }
+//!
+//! let schema = DonateArgs::json_schema();
+//! assert_eq!(schema["title"], "DonateArgs");
+//! assert_eq!(schema["description"], "Donation to a campaign.");
+//! assert_eq!(schema["properties"]["amount"]["type"], "string");
+//! assert_eq!(schema["required"], json!(["campaign_id", "amount"]));
+//! ```
+//!
+//! The contract [`Metadata`](crate::Metadata) describes the Borsh schemas of methods, so these
+//! JSON schemas are independent of it and only generated for the types that derive them.
+//! [`TypeScript`] renders them as TypeScript declarations for frontends, and [`Abi`] as a JSON
+//! description of the contract for frontends and indexers.
+
+mod abi;
+pub use abi::{Abi, AbiEvent, AbiMethod, ABI_SCHEMA_VERSION};
+
+mod typescript;
+pub use typescript::{TypeScript, TYPESCRIPT_OUT_ENV};
+
+use crate::json_types::{Base58CryptoHash, Base64VecU8, I128, I64, U128, U64};
+use crate::{AccountId, Gas, Promise, PromiseOrValue, PublicKey};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Describes the JSON representation of a type as a JSON Schema (draft 7).
+pub trait NearSchema {
+    /// Returns the JSON Schema of the JSON serialization of the type.
+    fn json_schema() -> Value;
+}
+
+/// Sets the `title` of the schema if it is an object.
+#[doc(hidden)]
+pub fn with_title(mut schema: Value, title: &str) -> Value {
+    if let Some(schema) = schema.as_object_mut() {
+        schema.insert("title".to_string(), Value::String(title.to_string()));
+    }
+    schema
+}
+
+/// Sets the `description` of the schema if it is an object.
+#[doc(hidden)]
+pub fn describe(mut schema: Value, description: &str) -> Value {
+    if let Some(schema) = schema.as_object_mut() {
+        schema.insert("description".to_string(), Value::String(description.to_string()));
+    }
+    schema
+}
+
+/// Returns the schema of the JSON object of the arguments of a method, with the arguments which
+/// can't be `null` required.
+pub(crate) fn args_schema(args: Vec<(&str, Value)>) -> Value {
+    let required: Vec<&str> =
+        args.iter().filter(|(_, schema)| !is_nullable(schema)).map(|(name, _)| *name).collect();
+    let properties: serde_json::Map<String, Value> =
+        args.into_iter().map(|(name, schema)| (name.to_string(), schema)).collect();
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema
+        .get("anyOf")
+        .and_then(Value::as_array)
+        .map_or(false, |variants| variants.iter().any(|v| v.get("type") == Some(&json!("null"))))
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty),* => $schema:tt) => {
+        $(
+            impl NearSchema for $ty {
+                fn json_schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_schema!(bool => { "type": "boolean" });
+impl_schema!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize => { "type": "integer" });
+impl_schema!(f32, f64 => { "type": "number" });
+impl_schema!(String, str, AccountId, PublicKey => { "type": "string" });
+impl_schema!(char => { "type": "string", "minLength": 1, "maxLength": 1 });
+impl_schema!(() => { "type": "null" });
+impl_schema!(U64, U128 => { "type": "string", "pattern": "^[0-9]+$" });
+impl_schema!(I64, I128 => { "type": "string", "pattern": "^-?[0-9]+$" });
+impl_schema!(Gas => { "type": "string", "pattern": "^[0-9]+$" });
+impl_schema!(Base64VecU8 => { "type": "string", "contentEncoding": "base64" });
+impl_schema!(Base58CryptoHash => { "type": "string" });
+
+/// The result of a promise returned by a method can't be known from its type.
+impl NearSchema for Promise {
+    fn json_schema() -> Value {
+        json!({})
+    }
+}
+
+impl<T: NearSchema> NearSchema for PromiseOrValue<T> {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: NearSchema + ?Sized> NearSchema for &T {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: NearSchema + ?Sized> NearSchema for Box<T> {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: NearSchema> NearSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+}
+
+macro_rules! impl_schema_array {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: NearSchema> NearSchema for $ty<T> {
+                fn json_schema() -> Value {
+                    json!({ "type": "array", "items": T::json_schema() })
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_array!(Vec, HashSet, BTreeSet);
+
+impl<T: NearSchema> NearSchema for [T] {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<V: NearSchema, S> NearSchema for HashMap<String, V, S> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::json_schema() })
+    }
+}
+
+impl<V: NearSchema> NearSchema for BTreeMap<String, V> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::json_schema() })
+    }
+}
+
+macro_rules! impl_schema_tuple {
+    ($($len:literal => ($($name:ident),+)),*) => {
+        $(
+            impl<$($name: NearSchema),+> NearSchema for ($($name,)+) {
+                fn json_schema() -> Value {
+                    json!({
+                        "type": "array",
+                        "items": [$($name::json_schema()),+],
+                        "minItems": $len,
+                        "maxItems": $len,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_tuple!(
+    1 => (A),
+    2 => (A, B),
+    3 => (A, B, C),
+    4 => (A, B, C, D)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!(Option::<u64>::json_schema()["anyOf"][1], json!({ "type": "null" }));
+        assert_eq!(
+            Vec::<AccountId>::json_schema(),
+            json!({ "type": "array", "items": { "type": "string" } })
+        );
+        assert_eq!(<(u8, String)>::json_schema()["items"][1], json!({ "type": "string" }));
+        assert_eq!(<&str>::json_schema(), json!({ "type": "string" }));
+    }
+}
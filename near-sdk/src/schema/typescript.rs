@@ -0,0 +1,315 @@
+use super::{args_schema, NearSchema};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Environment variable overriding the path of the file written by
+/// [`write_output`](TypeScript::write_output).
+pub const TYPESCRIPT_OUT_ENV: &str = "NEAR_TYPESCRIPT_OUT";
+
+/// TypeScript declarations of the JSON interface of a contract, rendered from the
+/// [`NearSchema`]s of its argument and return types.
+///
+/// Types with a title, i.e. the ones deriving `NearSchema`, become named interfaces or type
+/// aliases, and every method gets a `<Method>Args` interface and a `<Method>Result` type:
+///
+/// ```
+/// use near_sdk::schema::{NearSchema, TypeScript};
+///
+/// let mut definitions = TypeScript::new();
+/// definitions.add_method(
+///     "set_status",
+///     false,
+///     Some(vec![("message", String::json_schema()), ("ttl", Option::<u64>::json_schema())]),
+///     None,
+/// );
+/// assert!(definitions.render().contains(
+///     "export interface SetStatusArgs {\n  message: string;\n  ttl?: number | null;\n}\n"
+/// ));
+/// ```
+///
+/// With the `typescript` feature, `near_sdk::metadata!{}` generates a test which writes the
+/// declarations of all public methods to `<crate name>.d.ts` next to the `Cargo.toml`, so
+/// running `cargo test --features near-sdk/typescript` keeps the frontend types in sync with
+/// the contract. The argument and return types of JSON methods must implement `NearSchema`.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TypeScript {
+    /// Declarations of the named types, by name.
+    types: BTreeMap<String, String>,
+    /// Declarations of the argument and return types of the methods, in the order they were
+    /// added.
+    methods: Vec<String>,
+    view_methods: Vec<String>,
+    change_methods: Vec<String>,
+}
+
+impl TypeScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the type if it's named and returns the TypeScript type of its JSON
+    /// representation, e.g. for types only used in events.
+    pub fn add_type<T: NearSchema + ?Sized>(&mut self) -> String {
+        self.type_of(&T::json_schema())
+    }
+
+    /// Declares the arguments and result of a method. `args` are the names and schemas of the
+    /// JSON arguments, `None` if the method takes no JSON arguments, and `result` is the schema
+    /// of the JSON result, `None` if it returns nothing or its result isn't JSON.
+    pub fn add_method(
+        &mut self,
+        name: &str,
+        is_view: bool,
+        args: Option<Vec<(&str, Value)>>,
+        result: Option<Value>,
+    ) {
+        let type_name = pascal_case(name);
+        if let Some(args) = args {
+            let schema = args_schema(args);
+            let members = self.members(&schema, "  ");
+            self.methods.push(format!("export interface {}Args {{\n{}}}\n", type_name, members));
+        }
+        if let Some(result) = result {
+            let ty = self.type_of(&result);
+            self.methods.push(format!("export type {}Result = {};\n", type_name, ty));
+        }
+        if is_view {
+            self.view_methods.push(name.to_string());
+        } else {
+            self.change_methods.push(name.to_string());
+        }
+    }
+
+    /// Renders the declarations as the contents of a `.d.ts` file.
+    pub fn render(&self) -> String {
+        let mut output =
+            String::from("// Generated by near-sdk from the contract types, do not edit.\n");
+        for declaration in self.types.values().chain(self.methods.iter()) {
+            output.push('\n');
+            output.push_str(declaration);
+        }
+        output.push('\n');
+        output.push_str(&format!("export type ViewMethod = {};\n", literals(&self.view_methods)));
+        output
+            .push_str(&format!("export type ChangeMethod = {};\n", literals(&self.change_methods)));
+        output
+    }
+
+    /// Writes the rendered declarations to the file at `path`.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.render())
+    }
+
+    /// Writes the rendered declarations to the file named by the `NEAR_TYPESCRIPT_OUT`
+    /// environment variable, or to `default_path` if it isn't set.
+    pub fn write_output(&self, default_path: &str) -> std::io::Result<()> {
+        match std::env::var(TYPESCRIPT_OUT_ENV) {
+            Ok(path) => self.write(path),
+            Err(_) => self.write(default_path),
+        }
+    }
+
+    /// Returns the TypeScript type of the schema, declaring it first if it's named.
+    fn type_of(&mut self, schema: &Value) -> String {
+        let title = match schema.get("title").and_then(Value::as_str) {
+            Some(title) => title.to_string(),
+            None => return self.inline_type(schema),
+        };
+        if !self.types.contains_key(&title) {
+            // Inserted before rendering the declaration so that recursive types terminate.
+            self.types.insert(title.clone(), String::new());
+            let doc = schema.get("description").and_then(Value::as_str).map(|d| doc_comment(d, ""));
+            let declaration = if schema.get("properties").map_or(false, Value::is_object) {
+                format!("export interface {} {{\n{}}}\n", title, self.members(schema, "  "))
+            } else {
+                format!("export type {} = {};\n", title, self.inline_type(schema))
+            };
+            self.types.insert(title.clone(), doc.unwrap_or_default() + &declaration);
+        }
+        title
+    }
+
+    fn inline_type(&mut self, schema: &Value) -> String {
+        if let Some(variants) =
+            schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array)
+        {
+            let variants: Vec<String> = variants.iter().map(|v| self.type_of(v)).collect();
+            return variants.join(" | ");
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            let values: Vec<String> = values.iter().map(Value::to_string).collect();
+            return values.join(" | ");
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("string") => "string".to_string(),
+            Some("integer") | Some("number") => "number".to_string(),
+            Some("boolean") => "boolean".to_string(),
+            Some("null") => "null".to_string(),
+            Some("array") => match schema.get("items") {
+                Some(Value::Array(items)) => {
+                    let items: Vec<String> = items.iter().map(|item| self.type_of(item)).collect();
+                    format!("[{}]", items.join(", "))
+                }
+                Some(item) => {
+                    let item = self.type_of(item);
+                    if item.contains(' ') {
+                        format!("({})[]", item)
+                    } else {
+                        format!("{}[]", item)
+                    }
+                }
+                None => "unknown[]".to_string(),
+            },
+            Some("object") if schema.get("properties").map_or(false, Value::is_object) => {
+                let members: Vec<String> =
+                    self.properties(schema).into_iter().map(|(_, member)| member).collect();
+                format!("{{ {} }}", members.join("; "))
+            }
+            Some("object") => match schema.get("additionalProperties") {
+                Some(value) if value.is_object() => {
+                    format!("Record<string, {}>", self.type_of(value))
+                }
+                _ => "Record<string, unknown>".to_string(),
+            },
+            _ => "unknown".to_string(),
+        }
+    }
+
+    /// Renders the properties of an object schema as interface members, one per line.
+    fn members(&mut self, schema: &Value, indent: &str) -> String {
+        let mut output = String::new();
+        for (description, member) in self.properties(schema) {
+            if let Some(description) = description {
+                output.push_str(&doc_comment(&description, indent));
+            }
+            output.push_str(&format!("{}{};\n", indent, member));
+        }
+        output
+    }
+
+    /// Returns the description and the `name: type` member of each property of an object schema.
+    fn properties(&mut self, schema: &Value) -> Vec<(Option<String>, String)> {
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let properties = match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => properties,
+            None => return vec![],
+        };
+        properties
+            .iter()
+            .map(|(name, property)| {
+                // Named types carry their description in their own declaration.
+                let description = match property.get("title") {
+                    Some(_) => None,
+                    None => property.get("description").and_then(Value::as_str).map(String::from),
+                };
+                let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+                let member =
+                    format!("{}{}: {}", property_name(name), optional, self.type_of(property));
+                (description, member)
+            })
+            .collect()
+    }
+}
+
+/// Converts a `snake_case` method name to `PascalCase`.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn property_name(name: &str) -> String {
+    let is_identifier = name.chars().next().map_or(false, |c| !c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+fn literals(names: &[String]) -> String {
+    if names.is_empty() {
+        return "never".to_string();
+    }
+    let names: Vec<String> =
+        names.iter().map(|name| Value::String(name.clone()).to_string()).collect();
+    names.join(" | ")
+}
+
+fn doc_comment(description: &str, indent: &str) -> String {
+    if description.contains('\n') {
+        let lines: Vec<String> = description
+            .lines()
+            .map(|line| format!("{} * {}", indent, line).trim_end().to_string())
+            .collect();
+        format!("{}/**\n{}\n{} */\n", indent, lines.join("\n"), indent)
+    } else {
+        format!("{}/** {} */\n", indent, description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn named_types_and_methods() {
+        let status = json!({
+            "title": "Status",
+            "description": "Status of an account.",
+            "type": "object",
+            "properties": {
+                "message": { "type": "string", "description": "Text of the status." },
+                "tags": { "type": "array", "items": { "anyOf": [{ "type": "string" }, { "type": "null" }] } },
+                "kind": { "title": "Kind", "type": "string", "enum": ["public", "private"] },
+            },
+            "required": ["message", "kind"],
+            "additionalProperties": false,
+        });
+        let mut definitions = TypeScript::new();
+        definitions.add_method(
+            "get_status",
+            true,
+            Some(vec![("account_id", json!({ "type": "string" }))]),
+            Some(json!({ "anyOf": [status, { "type": "null" }] })),
+        );
+        definitions.add_method("clear", false, None, None);
+        assert_eq!(
+            definitions.render(),
+            r#"// Generated by near-sdk from the contract types, do not edit.
+
+export type Kind = "public" | "private";
+
+/** Status of an account. */
+export interface Status {
+  kind: Kind;
+  /** Text of the status. */
+  message: string;
+  tags?: (string | null)[];
+}
+
+export interface GetStatusArgs {
+  account_id: string;
+}
+
+export type GetStatusResult = Status | null;
+
+export type ViewMethod = "get_status";
+export type ChangeMethod = "clear";
+"#
+        );
+    }
+}
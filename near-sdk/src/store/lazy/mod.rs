@@ -141,6 +141,14 @@ where
 
         expect_consistent_state(entry.value_mut().as_mut())
     }
+
+    /// Replaces the value, returning the previous one. The previous value is loaded from storage
+    /// if it isn't cached yet.
+    ///
+    /// This function will panic if the cache is not loaded and the value at the key does not exist.
+    pub fn replace(&mut self, value: T) -> T {
+        core::mem::replace(self.get_mut(), value)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -163,6 +171,9 @@ mod tests {
         assert_eq!(u32::try_from_slice(&env::storage_read(b"a").unwrap()).unwrap(), 8);
         assert_eq!(*a, 42);
 
+        assert_eq!(a.replace(35), 42);
+        assert_eq!(*a, 35);
+
         *a = 30;
         let serialized = a.try_to_vec().unwrap();
         drop(a);
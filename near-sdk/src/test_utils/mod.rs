@@ -6,6 +6,20 @@ use crate::mock::Receipt;
 #[allow(deprecated)]
 pub use context::{accounts, testing_env_with_promise_results, VMContextBuilder};
 
+mod receipts;
+pub use receipts::{expect, ExpectedFunctionCall, ExpectedReceipts};
+
+mod promise_fixture;
+pub use promise_fixture::{
+    FixtureStep, PromiseFixture, PromiseRecorder, PromiseReplayer, RecordedAction, RecordedArgs,
+    RecordedReceipt, RecordedResult,
+};
+
+mod storage_diff;
+pub use storage_diff::{
+    storage_diff, storage_snapshot, StorageChange, StorageDiff, StorageSnapshot,
+};
+
 /// Initializes a testing environment to mock interactions which would otherwise go through a
 /// validator node. This macro will initialize or overwrite the [`MockedBlockchain`]
 /// instance for interactions from a smart contract.
@@ -93,12 +107,71 @@ pub fn get_logs() -> Vec<String> {
     crate::mock::with_mocked_blockchain(|b| b.logs())
 }
 
+/// Moves the block timestamp of the current [`MockedBlockchain`] to `block_timestamp`
+/// nanoseconds, keeping its storage, logs and receipts. Only available in unit tests.
+///
+/// [`MockedBlockchain`]: crate::mock::MockedBlockchain
+pub fn set_block_timestamp(block_timestamp: crate::Timestamp) {
+    crate::mock::with_mocked_blockchain(|b| b.set_block_timestamp(block_timestamp))
+}
+
 /// Accessing receipts created by the contract. Only available in unit tests.
 #[allow(dead_code)]
 pub fn get_created_receipts() -> Vec<Receipt> {
     crate::mock::with_mocked_blockchain(|b| b.created_receipts().clone())
 }
 
+/// Logs, receipts and storage usage change of a call, returned by [`observe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallEffects<R> {
+    /// Value returned by the call.
+    pub result: R,
+    /// Logs emitted during the call, e.g. `EVENT_JSON:` events.
+    pub logs: Vec<String>,
+    /// Receipts created during the call.
+    pub receipts: Vec<Receipt>,
+    /// Change of the storage usage of the contract in bytes, negative if storage was released.
+    pub storage_usage_delta: i64,
+}
+
+/// Calls `call` and returns the logs and receipts it created and its change of the storage
+/// usage, leaving out the ones of previous calls in the same [`testing_env!`]. Only available in
+/// unit tests.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::collections::LookupMap;
+/// use near_sdk::test_utils::{accounts, observe, VMContextBuilder};
+/// use near_sdk::{env, testing_env, Promise};
+///
+/// testing_env!(VMContextBuilder::new().attached_deposit(10).build());
+/// let mut balances: LookupMap<String, u64> = LookupMap::new(b"b");
+/// env::log_str("setup");
+///
+/// let effects = observe(|| {
+///     balances.insert(&"alice".to_string(), &1);
+///     env::log_str("deposit");
+///     Promise::new(accounts(1)).transfer(env::attached_deposit());
+/// });
+/// assert_eq!(effects.logs, vec!["deposit"]);
+/// assert_eq!(effects.receipts.len(), 1);
+/// assert!(effects.storage_usage_delta > 0);
+/// ```
+pub fn observe<R>(call: impl FnOnce() -> R) -> CallEffects<R> {
+    let logs_before = get_logs().len();
+    let receipts_before = get_created_receipts().len();
+    let storage_usage_before = crate::env::storage_usage();
+    let result = call();
+    let storage_usage_delta = crate::env::storage_usage() as i64 - storage_usage_before as i64;
+    CallEffects {
+        result,
+        logs: get_logs().split_off(logs_before),
+        receipts: get_created_receipts().split_off(receipts_before),
+        storage_usage_delta,
+    }
+}
+
 /// Objects stored on the trie directly should have identifiers. If identifier is not provided
 /// explicitly than `Default` trait would use this index to generate an id.
 #[allow(dead_code)]
@@ -0,0 +1,317 @@
+use crate::json_types::{Base64VecU8, U128, U64};
+use crate::mock::{Receipt, VmAction};
+use crate::test_utils::get_created_receipts;
+use crate::{AccountId, PromiseResult};
+use serde::{Deserialize, Serialize};
+
+/// Arguments of a recorded function call, kept as JSON when they are valid JSON so that fixtures
+/// stay readable.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedArgs {
+    Json(serde_json::Value),
+    Base64(Base64VecU8),
+}
+
+impl RecordedArgs {
+    fn new(args: &[u8]) -> Self {
+        match serde_json::from_slice(args) {
+            Ok(json) => Self::Json(json),
+            Err(_) => Self::Base64(args.to_vec().into()),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Json(json) => serde_json::to_vec(json).unwrap(),
+            Self::Base64(bytes) => bytes.0.clone(),
+        }
+    }
+}
+
+/// Action of a recorded receipt. Actions other than function calls and transfers are recorded
+/// with their debug representation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedAction {
+    FunctionCall { method_name: String, args: RecordedArgs, gas: U64, deposit: U128 },
+    Transfer { deposit: U128 },
+    Other { action: String },
+}
+
+/// Receipt created by the contract, as recorded in a [`PromiseFixture`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedReceipt {
+    pub receiver_id: AccountId,
+    /// Indices of the receipts this receipt waits for, i.e. it's a callback of them.
+    pub depends_on: Vec<u64>,
+    pub actions: Vec<RecordedAction>,
+}
+
+impl From<&Receipt> for RecordedReceipt {
+    fn from(receipt: &Receipt) -> Self {
+        let actions = receipt
+            .actions
+            .iter()
+            .map(|action| match action {
+                VmAction::FunctionCall { function_name, args, gas, deposit } => {
+                    RecordedAction::FunctionCall {
+                        method_name: function_name.clone(),
+                        args: RecordedArgs::new(args),
+                        gas: gas.0.into(),
+                        deposit: (*deposit).into(),
+                    }
+                }
+                VmAction::Transfer { deposit } => {
+                    RecordedAction::Transfer { deposit: (*deposit).into() }
+                }
+                action => RecordedAction::Other { action: format!("{:?}", action) },
+            })
+            .collect();
+        Self {
+            receiver_id: receipt.receiver_id.clone(),
+            depends_on: receipt.receipt_indices.clone(),
+            actions,
+        }
+    }
+}
+
+/// Result of a promise fed to the callback of the next step, as recorded in a
+/// [`PromiseFixture`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedResult {
+    Successful(RecordedArgs),
+    Failed,
+}
+
+impl From<&PromiseResult> for RecordedResult {
+    fn from(result: &PromiseResult) -> Self {
+        match result {
+            PromiseResult::Successful(value) => Self::Successful(RecordedArgs::new(value)),
+            _ => Self::Failed,
+        }
+    }
+}
+
+impl From<&RecordedResult> for PromiseResult {
+    fn from(result: &RecordedResult) -> Self {
+        match result {
+            RecordedResult::Successful(value) => Self::Successful(value.to_bytes()),
+            RecordedResult::Failed => Self::Failed,
+        }
+    }
+}
+
+/// Step of a scenario: the receipts created by one contract call and the results of their
+/// promises, which are fed to the callback called in the next step.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FixtureStep {
+    pub name: String,
+    pub receipts: Vec<RecordedReceipt>,
+    pub results: Vec<RecordedResult>,
+}
+
+/// Cross-contract behavior of a scenario, e.g. an `ft_transfer_call` and its resolution,
+/// recorded with [`PromiseRecorder`] and stored as JSON, so that a regression test can replay
+/// it with [`PromiseReplayer`] and fail when the contract schedules different calls.
+///
+/// Each step is a call of the contract in a fresh [`testing_env!`](crate::testing_env), and
+/// records the receipts created by it.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::test_utils::{PromiseRecorder, PromiseReplayer, VMContextBuilder};
+/// use near_sdk::{testing_env, Gas, Promise, PromiseResult, RuntimeFeesConfig, VMConfig};
+///
+/// fn transfer_call(promise_results: Vec<PromiseResult>) {
+///     testing_env!(
+///         VMContextBuilder::new().build(),
+///         VMConfig::test(),
+///         RuntimeFeesConfig::test(),
+///         Default::default(),
+///         promise_results,
+///     );
+///     Promise::new("bob.near".parse().unwrap()).function_call(
+///         "ft_on_transfer".to_string(),
+///         br#"{"amount":"10"}"#.to_vec(),
+///         0,
+///         Gas(10_000_000_000_000),
+///     );
+/// }
+///
+/// // Record the scenario once, then store the fixture with the tests.
+/// let mut recorder = PromiseRecorder::default();
+/// transfer_call(vec![]);
+/// recorder.record("ft_transfer_call", vec![PromiseResult::Successful(b"\"0\"".to_vec())]);
+/// let fixture = recorder.into_fixture().to_json();
+///
+/// // Replay it in the regression test.
+/// let mut replayer = PromiseReplayer::from_json(&fixture);
+/// transfer_call(vec![]);
+/// let results = replayer.replay("ft_transfer_call");
+/// assert_eq!(results, vec![PromiseResult::Successful(b"\"0\"".to_vec())]);
+/// replayer.finish();
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PromiseFixture {
+    pub steps: Vec<FixtureStep>,
+}
+
+impl PromiseFixture {
+    /// Serializes the fixture as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_else(|err| panic!("Invalid promise fixture: {}", err))
+    }
+}
+
+/// Records the steps of a scenario into a [`PromiseFixture`]. Only available in unit tests.
+#[derive(Debug, Clone, Default)]
+pub struct PromiseRecorder {
+    fixture: PromiseFixture,
+}
+
+impl PromiseRecorder {
+    /// Records the receipts created in the current mocked blockchain as the step `name`, with
+    /// the results of their promises to feed to the callback of the next step.
+    pub fn record(&mut self, name: &str, results: Vec<PromiseResult>) {
+        self.fixture.steps.push(FixtureStep {
+            name: name.to_string(),
+            receipts: recorded_receipts(),
+            results: results.iter().map(RecordedResult::from).collect(),
+        });
+    }
+
+    pub fn into_fixture(self) -> PromiseFixture {
+        self.fixture
+    }
+}
+
+/// Replays the steps of a [`PromiseFixture`], checking that the contract creates the recorded
+/// receipts and returning the recorded results. Only available in unit tests.
+#[derive(Debug, Clone)]
+pub struct PromiseReplayer {
+    fixture: PromiseFixture,
+    next_step: usize,
+}
+
+impl PromiseReplayer {
+    pub fn new(fixture: PromiseFixture) -> Self {
+        Self { fixture, next_step: 0 }
+    }
+
+    pub fn from_json(json: &str) -> Self {
+        Self::new(PromiseFixture::from_json(json))
+    }
+
+    /// Asserts that `name` is the next recorded step and that the receipts created in the
+    /// current mocked blockchain are the recorded ones. Returns the recorded results of their
+    /// promises, to pass to the `testing_env!` of the callback of the next step.
+    pub fn replay(&mut self, name: &str) -> Vec<PromiseResult> {
+        let step = self.fixture.steps.get(self.next_step).unwrap_or_else(|| {
+            panic!("Step `{}` is not recorded, the fixture has {} steps", name, self.next_step)
+        });
+        assert_eq!(step.name, name, "Unexpected step #{}", self.next_step);
+        let receipts = recorded_receipts();
+        assert_eq!(
+            receipts, step.receipts,
+            "Receipts of step `{}` differ from the recorded ones",
+            name
+        );
+        self.next_step += 1;
+        step.results.iter().map(PromiseResult::from).collect()
+    }
+
+    /// Asserts that all recorded steps were replayed.
+    pub fn finish(&self) {
+        assert_eq!(
+            self.next_step,
+            self.fixture.steps.len(),
+            "Only {} of the {} recorded steps were replayed",
+            self.next_step,
+            self.fixture.steps.len()
+        );
+    }
+}
+
+fn recorded_receipts() -> Vec<RecordedReceipt> {
+    get_created_receipts().iter().map(RecordedReceipt::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::{testing_env, Gas, Promise, RuntimeFeesConfig, VMConfig};
+
+    fn step(promise_results: Vec<PromiseResult>, amount: &str) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            promise_results,
+        );
+        if crate::env::promise_results_count() == 0 {
+            Promise::new(accounts(1))
+                .function_call(
+                    "ft_on_transfer".to_string(),
+                    format!(r#"{{"amount":"{}"}}"#, amount).into_bytes(),
+                    0,
+                    Gas(30_000_000_000_000),
+                )
+                .then(Promise::new(accounts(0)).function_call(
+                    "ft_resolve_transfer".to_string(),
+                    vec![],
+                    0,
+                    Gas(5_000_000_000_000),
+                ));
+        } else {
+            Promise::new(accounts(2)).transfer(1);
+        }
+    }
+
+    fn record() -> PromiseFixture {
+        let mut recorder = PromiseRecorder::default();
+        step(vec![], "10");
+        recorder.record("ft_transfer_call", vec![PromiseResult::Successful(b"\"3\"".to_vec())]);
+        step(vec![PromiseResult::Successful(b"\"3\"".to_vec())], "10");
+        recorder.record("ft_resolve_transfer", vec![]);
+        recorder.into_fixture()
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let fixture = PromiseFixture::from_json(&record().to_json());
+        assert_eq!(
+            fixture.steps[0].receipts[0].actions,
+            vec![RecordedAction::FunctionCall {
+                method_name: "ft_on_transfer".to_string(),
+                args: RecordedArgs::Json(serde_json::json!({ "amount": "10" })),
+                gas: U64(30_000_000_000_000),
+                deposit: U128(0),
+            }]
+        );
+        assert_eq!(fixture.steps[0].receipts[1].depends_on, vec![0]);
+
+        let mut replayer = PromiseReplayer::new(fixture);
+        step(vec![], "10");
+        let results = replayer.replay("ft_transfer_call");
+        step(results, "10");
+        assert!(replayer.replay("ft_resolve_transfer").is_empty());
+        replayer.finish();
+    }
+
+    #[test]
+    #[should_panic(expected = "Receipts of step `ft_transfer_call` differ from the recorded ones")]
+    fn replay_changed_calls() {
+        let mut replayer = PromiseReplayer::new(record());
+        step(vec![], "11");
+        replayer.replay("ft_transfer_call");
+    }
+}
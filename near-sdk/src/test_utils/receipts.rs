@@ -0,0 +1,281 @@
+use crate::mock::{Receipt, VmAction};
+use crate::test_utils::get_created_receipts;
+use crate::{AccountId, Balance, Gas};
+
+/// Captures the receipts created so far in the mocked blockchain to make assertions on them.
+///
+/// This is a thin assertion layer over [`get_created_receipts`] to make tests which schedule
+/// cross-contract calls concise. Every assertion panics with a descriptive message on mismatch.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::test_utils::expect;
+/// use near_sdk::{Gas, Promise};
+///
+/// Promise::new("bob.near".parse().unwrap()).function_call(
+///     "ft_on_transfer".to_string(),
+///     b"{}".to_vec(),
+///     0,
+///     Gas(10_000_000_000_000),
+/// );
+///
+/// expect()
+///     .receipt_count(1)
+///     .function_call("ft_on_transfer")
+///     .receiver(&"bob.near".parse().unwrap())
+///     .deposit(0)
+///     .gas_at_least(Gas(5_000_000_000_000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExpectedReceipts {
+    receipts: Vec<Receipt>,
+}
+
+/// Returns an [`ExpectedReceipts`] assertion builder over the receipts created by the contract.
+pub fn expect() -> ExpectedReceipts {
+    ExpectedReceipts { receipts: get_created_receipts() }
+}
+
+impl ExpectedReceipts {
+    /// Receipts that assertions are run against.
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.receipts
+    }
+
+    /// Asserts the exact number of receipts created.
+    pub fn receipt_count(&self, count: usize) -> &Self {
+        assert_eq!(
+            self.receipts.len(),
+            count,
+            "Expected {} created receipts, found {}: {:?}",
+            count,
+            self.receipts.len(),
+            self.receipts
+        );
+        self
+    }
+
+    /// Asserts that no receipts were created.
+    pub fn none(&self) -> &Self {
+        self.receipt_count(0)
+    }
+
+    /// Asserts that a function call to `function_name` was scheduled and returns a builder to make
+    /// further assertions on it. If there are multiple calls with the same name, the first one
+    /// is selected. Use [`ExpectedReceipts::nth_function_call`] to select a specific one.
+    pub fn function_call(&self, function_name: &str) -> ExpectedFunctionCall<'_> {
+        self.nth_function_call(function_name, 0)
+    }
+
+    /// Asserts that at least `n + 1` function calls to `function_name` were scheduled and returns
+    /// a builder over the `n`-th one (zero-indexed).
+    pub fn nth_function_call(&self, function_name: &str, n: usize) -> ExpectedFunctionCall<'_> {
+        self.function_calls(function_name).nth(n).unwrap_or_else(|| {
+            panic!(
+                "Expected function call #{} to `{}` in created receipts, found: {:?}",
+                n, function_name, self.receipts
+            )
+        })
+    }
+
+    /// Asserts the number of function calls to `function_name` across all receipts.
+    pub fn function_call_count(&self, function_name: &str, count: usize) -> &Self {
+        let actual = self.function_calls(function_name).count();
+        assert_eq!(
+            actual, count,
+            "Expected {} function calls to `{}`, found {}",
+            count, function_name, actual
+        );
+        self
+    }
+
+    /// Asserts that a transfer of exactly `amount` to `receiver_id` was scheduled.
+    pub fn transfer(&self, receiver_id: &AccountId, amount: Balance) -> &Self {
+        let found = self.receipts.iter().any(|receipt| {
+            &receipt.receiver_id == receiver_id
+                && receipt.actions.iter().any(
+                    |action| matches!(action, VmAction::Transfer { deposit } if *deposit == amount),
+                )
+        });
+        assert!(
+            found,
+            "Expected transfer of {} to {} in created receipts, found: {:?}",
+            amount, receiver_id, self.receipts
+        );
+        self
+    }
+
+    fn function_calls(&self, name: &str) -> std::vec::IntoIter<ExpectedFunctionCall<'_>> {
+        let mut calls = Vec::new();
+        for receipt in self.receipts.iter() {
+            for action in receipt.actions.iter() {
+                if let VmAction::FunctionCall { function_name, args, gas, deposit } = action {
+                    if function_name == name {
+                        calls.push(ExpectedFunctionCall {
+                            parent: self,
+                            receipt,
+                            function_name,
+                            args,
+                            gas: *gas,
+                            deposit: *deposit,
+                        });
+                    }
+                }
+            }
+        }
+        calls.into_iter()
+    }
+}
+
+/// Assertion builder over a single function call action found by
+/// [`ExpectedReceipts::function_call`].
+#[derive(Debug, Clone)]
+pub struct ExpectedFunctionCall<'a> {
+    parent: &'a ExpectedReceipts,
+    receipt: &'a Receipt,
+    function_name: &'a str,
+    args: &'a [u8],
+    gas: Gas,
+    deposit: Balance,
+}
+
+impl<'a> ExpectedFunctionCall<'a> {
+    /// Asserts the account the function call is scheduled on.
+    pub fn receiver(self, receiver_id: &AccountId) -> Self {
+        assert_eq!(
+            &self.receipt.receiver_id, receiver_id,
+            "Function call `{}` has unexpected receiver",
+            self.function_name
+        );
+        self
+    }
+
+    /// Asserts the exact deposit attached to the function call.
+    pub fn deposit(self, amount: Balance) -> Self {
+        assert_eq!(
+            self.deposit, amount,
+            "Function call `{}` has unexpected attached deposit",
+            self.function_name
+        );
+        self
+    }
+
+    /// Asserts the exact gas attached to the function call.
+    pub fn gas(self, gas: Gas) -> Self {
+        assert_eq!(self.gas, gas, "Function call `{}` has unexpected gas", self.function_name);
+        self
+    }
+
+    /// Asserts that at least `gas` is attached to the function call.
+    pub fn gas_at_least(self, gas: Gas) -> Self {
+        assert!(
+            self.gas >= gas,
+            "Function call `{}` has {} gas attached, expected at least {}",
+            self.function_name,
+            self.gas.0,
+            gas.0
+        );
+        self
+    }
+
+    /// Asserts the raw arguments of the function call.
+    pub fn args(self, args: &[u8]) -> Self {
+        assert_eq!(self.args, args, "Function call `{}` has unexpected args", self.function_name);
+        self
+    }
+
+    /// Asserts that the arguments of the function call are JSON equal to `args`.
+    pub fn args_json(self, args: serde_json::Value) -> Self {
+        let actual: serde_json::Value = serde_json::from_slice(self.args).unwrap_or_else(|_| {
+            panic!("Function call `{}` args are not valid JSON", self.function_name)
+        });
+        assert_eq!(actual, args, "Function call `{}` has unexpected args", self.function_name);
+        self
+    }
+
+    /// Asserts that this function call depends on (is a callback of) some other receipt.
+    pub fn is_callback(self) -> Self {
+        assert!(
+            !self.receipt.receipt_indices.is_empty(),
+            "Function call `{}` is not a callback",
+            self.function_name
+        );
+        self
+    }
+
+    /// Returns the raw arguments for custom assertions.
+    pub fn raw_args(&self) -> &'a [u8] {
+        self.args
+    }
+
+    /// Returns to the parent builder to chain assertions on other receipts.
+    pub fn and(self) -> &'a ExpectedReceipts {
+        self.parent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::{testing_env, Promise};
+
+    fn schedule_transfer_call() {
+        Promise::new(accounts(1))
+            .function_call(
+                "ft_on_transfer".to_string(),
+                br#"{"amount":"10"}"#.to_vec(),
+                0,
+                Gas(30_000_000_000_000),
+            )
+            .then(Promise::new(accounts(0)).function_call(
+                "ft_resolve_transfer".to_string(),
+                vec![],
+                0,
+                Gas(5_000_000_000_000),
+            ));
+    }
+
+    #[test]
+    fn function_call_assertions() {
+        testing_env!(VMContextBuilder::new().build());
+        schedule_transfer_call();
+
+        expect()
+            .receipt_count(2)
+            .function_call("ft_on_transfer")
+            .receiver(&accounts(1))
+            .deposit(0)
+            .gas_at_least(Gas(10_000_000_000_000))
+            .args_json(serde_json::json!({ "amount": "10" }))
+            .and()
+            .function_call("ft_resolve_transfer")
+            .receiver(&accounts(0))
+            .gas(Gas(5_000_000_000_000))
+            .is_callback();
+    }
+
+    #[test]
+    fn transfer_assertion() {
+        testing_env!(VMContextBuilder::new().build());
+        Promise::new(accounts(2)).transfer(100);
+
+        expect().receipt_count(1).transfer(&accounts(2), 100).function_call_count("any", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected function call #0 to `ft_on_transfer`")]
+    fn missing_function_call() {
+        testing_env!(VMContextBuilder::new().build());
+        expect().none().function_call("ft_on_transfer");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected at least")]
+    fn not_enough_gas() {
+        testing_env!(VMContextBuilder::new().build());
+        schedule_transfer_call();
+        expect().function_call("ft_on_transfer").gas_at_least(Gas(100_000_000_000_000));
+    }
+}
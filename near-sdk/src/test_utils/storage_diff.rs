@@ -0,0 +1,187 @@
+use crate::IntoStorageKey;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Copy of the storage of the mocked blockchain, taken with [`storage_snapshot`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageSnapshot(pub BTreeMap<Vec<u8>, Vec<u8>>);
+
+/// Takes a copy of the storage of the mocked blockchain, to compare it with [`storage_diff`].
+/// Only available in unit tests.
+pub fn storage_snapshot() -> StorageSnapshot {
+    crate::mock::with_mocked_blockchain(|b| {
+        StorageSnapshot(b.storage().iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+    })
+}
+
+/// Change of a storage entry between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageChange {
+    Added { key: Vec<u8>, value: Vec<u8> },
+    Removed { key: Vec<u8>, value: Vec<u8> },
+    Modified { key: Vec<u8>, before: Vec<u8>, after: Vec<u8> },
+}
+
+impl StorageChange {
+    pub fn key(&self) -> &[u8] {
+        match self {
+            Self::Added { key, .. } | Self::Removed { key, .. } | Self::Modified { key, .. } => key,
+        }
+    }
+}
+
+/// Changes of the storage between two snapshots, in the order of their keys. Keys are printed
+/// with the names of the collection prefixes registered with [`prefix`](Self::prefix).
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::collections::LookupMap;
+/// use near_sdk::test_utils::{storage_diff, storage_snapshot};
+///
+/// let mut balances: LookupMap<String, u64> = LookupMap::new(b"b");
+/// balances.insert(&"alice".to_string(), &1);
+/// let before = storage_snapshot();
+/// balances.insert(&"alice".to_string(), &2);
+/// balances.insert(&"bob".to_string(), &3);
+///
+/// let diff = storage_diff(&before, &storage_snapshot()).prefix("balances", b"b");
+/// assert_eq!(diff.len(), 2);
+/// assert!(diff.touches_only(&[b"b"]));
+/// println!("{}", diff);
+/// // + balances[0x03000000626f62]: 0x0300000000000000
+/// // ~ balances[0x05000000616c696365]: 0x0100000000000000 -> 0x0200000000000000
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageDiff {
+    changes: Vec<StorageChange>,
+    prefixes: Vec<(String, Vec<u8>)>,
+}
+
+/// Returns the changes from the `before` to the `after` snapshot of the storage.
+pub fn storage_diff(before: &StorageSnapshot, after: &StorageSnapshot) -> StorageDiff {
+    let mut changes = vec![];
+    for (key, value) in &before.0 {
+        match after.0.get(key) {
+            None => changes.push(StorageChange::Removed { key: key.clone(), value: value.clone() }),
+            Some(after) if after != value => changes.push(StorageChange::Modified {
+                key: key.clone(),
+                before: value.clone(),
+                after: after.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, value) in &after.0 {
+        if !before.0.contains_key(key) {
+            changes.push(StorageChange::Added { key: key.clone(), value: value.clone() });
+        }
+    }
+    changes.sort_by(|a, b| a.key().cmp(b.key()));
+    StorageDiff { changes, prefixes: vec![] }
+}
+
+impl StorageDiff {
+    /// Registers the prefix of a collection, so that its keys are printed as `name[<rest>]`.
+    /// Keys matching several prefixes are printed with the longest one.
+    pub fn prefix<S>(mut self, name: &str, prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        self.prefixes.push((name.to_string(), prefix.into_storage_key()));
+        self
+    }
+
+    pub fn changes(&self) -> &[StorageChange] {
+        &self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns `true` if the storage didn't change.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns `true` if all changed keys start with one of the prefixes, e.g. to catch
+    /// accidental writes to other collections.
+    pub fn touches_only(&self, prefixes: &[&[u8]]) -> bool {
+        self.changes
+            .iter()
+            .all(|change| prefixes.iter().any(|prefix| change.key().starts_with(prefix)))
+    }
+
+    /// Formats the key with the name of its longest registered prefix.
+    pub fn format_key(&self, key: &[u8]) -> String {
+        let prefix = self
+            .prefixes
+            .iter()
+            .filter(|(_, prefix)| key.starts_with(prefix))
+            .max_by_key(|(_, prefix)| prefix.len());
+        match prefix {
+            Some((name, prefix)) => format!("{}[{}]", name, format_bytes(&key[prefix.len()..])),
+            None => format_bytes(key),
+        }
+    }
+}
+
+/// Formats printable ASCII bytes as a quoted string and other bytes as hex.
+fn format_bytes(bytes: &[u8]) -> String {
+    if !bytes.is_empty() && bytes.iter().all(|byte| byte.is_ascii_graphic() || *byte == b' ') {
+        format!("{:?}", String::from_utf8_lossy(bytes))
+    } else {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("0x{}", hex)
+    }
+}
+
+impl fmt::Display for StorageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let key = self.format_key(change.key());
+            match change {
+                StorageChange::Added { value, .. } => {
+                    writeln!(f, "+ {}: {}", key, format_bytes(value))?
+                }
+                StorageChange::Removed { value, .. } => {
+                    writeln!(f, "- {}: {}", key, format_bytes(value))?
+                }
+                StorageChange::Modified { before, after, .. } => {
+                    writeln!(f, "~ {}: {} -> {}", key, format_bytes(before), format_bytes(after))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env;
+
+    #[test]
+    fn diff_of_snapshots() {
+        env::storage_write(b"STATE", b"\x01");
+        env::storage_write(b"a\x01", b"\x00");
+        env::storage_write(b"ab", b"x");
+        let before = storage_snapshot();
+        env::storage_write(b"STATE", b"\x02");
+        env::storage_remove(b"a\x01");
+        env::storage_write(b"ac", b"\x03");
+        env::storage_write(b"ab", b"x");
+
+        let diff =
+            storage_diff(&before, &storage_snapshot()).prefix("a", b"a").prefix("state", b"S");
+        assert_eq!(diff.len(), 3);
+        assert!(diff.touches_only(&[b"a", b"S"]));
+        assert!(!diff.touches_only(&[b"a"]));
+        assert_eq!(
+            diff.to_string(),
+            "~ state[\"TATE\"]: 0x01 -> 0x02\n- a[0x01]: 0x00\n+ a[\"c\"]: 0x03\n"
+        );
+        assert!(storage_diff(&before, &before).is_empty());
+    }
+}
@@ -54,6 +54,54 @@ impl AccountId {
         debug_assert!(is_valid_account_id(id.as_bytes()));
         Self(id)
     }
+
+    /// Creates the direct sub-account `<name>.<parent>`, validating the result. Fails if `name`
+    /// contains a `.`, since that would not be a direct sub-account of `parent`.
+    ///
+    /// ```
+    /// use near_sdk::AccountId;
+    ///
+    /// let factory: AccountId = "factory.near".parse().unwrap();
+    /// let token = AccountId::sub_account_of("token", &factory).unwrap();
+    /// assert_eq!(token.as_str(), "token.factory.near");
+    /// assert!(AccountId::sub_account_of("a.token", &factory).is_err());
+    /// ```
+    pub fn sub_account_of(name: &str, parent: &AccountId) -> Result<Self, ParseAccountIdError> {
+        if name.contains('.') {
+            return Err(ParseAccountIdError {});
+        }
+        format!("{}.{}", name, parent).parse()
+    }
+
+    /// Returns `true` if this account is a direct sub-account of `parent`, e.g. `alice.near` is a
+    /// sub-account of `near`, but `app.alice.near` is not.
+    pub fn is_sub_account_of(&self, parent: &AccountId) -> bool {
+        match self.0.strip_suffix(parent.as_str()) {
+            Some(prefix) => !prefix.is_empty() && prefix.find('.') == Some(prefix.len() - 1),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if this is an implicit account, i.e. the 64 character lowercase hex
+    /// encoding of an ED25519 public key.
+    pub fn is_implicit(&self) -> bool {
+        self.0.len() == 64 && self.0.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    }
+
+    /// Returns `true` if the account has no parent account, e.g. `near` or an implicit account.
+    /// Top level accounts other than implicit ones can only be created by the registrar.
+    pub fn is_top_level(&self) -> bool {
+        !self.0.contains('.') && self.0 != "system"
+    }
+
+    /// Returns `true` if the account is under the top level account `tld` at any depth, e.g.
+    /// both `alice.near` and `app.alice.near` end with the `near` TLD, while `near` does not.
+    pub fn ends_with_tld(&self, tld: &str) -> bool {
+        match self.0.strip_suffix(tld) {
+            Some(prefix) => prefix.len() > 1 && prefix.ends_with('.'),
+            None => false,
+        }
+    }
 }
 
 impl fmt::Display for AccountId {
@@ -164,4 +212,35 @@ mod tests {
         // Test to make sure the account ID is serialized as a string through borsh
         assert_eq!(str::try_to_vec(id).unwrap(), account_id.try_to_vec().unwrap());
     }
+
+    #[test]
+    fn naming_constraints() {
+        let near: AccountId = "near".parse().unwrap();
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let app: AccountId = "app.alice.near".parse().unwrap();
+        let implicit = AccountId::new_unchecked("a".repeat(64));
+
+        assert_eq!(AccountId::sub_account_of("alice", &near), Ok(alice.clone()));
+        assert!(AccountId::sub_account_of("app.alice", &near).is_err());
+        assert!(AccountId::sub_account_of("Alice", &near).is_err());
+
+        assert!(alice.is_sub_account_of(&near));
+        assert!(app.is_sub_account_of(&alice));
+        assert!(!app.is_sub_account_of(&near));
+        assert!(!near.is_sub_account_of(&near));
+        assert!(!"xnear".parse::<AccountId>().unwrap().is_sub_account_of(&near));
+
+        assert!(implicit.is_implicit());
+        assert!(implicit.is_top_level());
+        assert!(!alice.is_implicit());
+        assert!(near.is_top_level());
+        assert!(!alice.is_top_level());
+        assert!(!"system".parse::<AccountId>().unwrap().is_top_level());
+
+        assert!(alice.ends_with_tld("near"));
+        assert!(app.ends_with_tld("near"));
+        assert!(!near.ends_with_tld("near"));
+        assert!(!alice.ends_with_tld("testnet"));
+        assert!(!"alice.xnear".parse::<AccountId>().unwrap().ends_with_tld("near"));
+    }
 }
@@ -0,0 +1,53 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Element of the base field of the alt_bn128 curve, as 32 little-endian bytes.
+pub type AltBn128Fq = [u8; 32];
+
+/// Scalar of the alt_bn128 curve, as 32 little-endian bytes.
+pub type AltBn128Fr = [u8; 32];
+
+/// Point of the G1 group of the alt_bn128 curve, as taken and returned by the
+/// `env::alt_bn128_*` functions. The point at infinity has both coordinates zero.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct G1Point {
+    pub x: AltBn128Fq,
+    pub y: AltBn128Fq,
+}
+
+/// Point of the G2 group of the alt_bn128 curve, whose coordinates are in the quadratic
+/// extension of the base field, each as its `[c0, c1]` coefficients.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct G2Point {
+    pub x: [AltBn128Fq; 2],
+    pub y: [AltBn128Fq; 2],
+}
+
+impl G1Point {
+    /// Reads the point from the 64 bytes of `x` followed by `y`.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+        let mut point = Self::default();
+        point.x.copy_from_slice(&bytes[..32]);
+        point.y.copy_from_slice(&bytes[32..]);
+        point
+    }
+
+    /// Returns the 64 bytes of `x` followed by `y`.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.x);
+        bytes[32..].copy_from_slice(&self.y);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn g1_point_bytes() {
+        let point = G1Point { x: [1; 32], y: [2; 32] };
+        assert_eq!(G1Point::from_bytes(&point.to_bytes()), point);
+        assert_eq!(point.try_to_vec().unwrap(), point.to_bytes().to_vec());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::ContractResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Call of a `#[batch_view]` method in the `batch_view` method generated by `#[near_bindgen]`,
+/// e.g. `{"method_name":"ft_balance_of","args":{"account_id":"alice.near"}}`. The `args` can be
+/// omitted for methods without arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ViewRequest {
+    pub method_name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+/// Result of a [`ViewRequest`], which is the JSON result of the method, or an
+/// [`UNKNOWN_METHOD`](crate::ContractError::UNKNOWN_METHOD) or
+/// [`INVALID_ARGUMENTS`](crate::ContractError::INVALID_ARGUMENTS) error when the request can't be
+/// dispatched. The `Err` of a method with `#[return_result]` is the error of its request, see
+/// [`IntoContractError`](crate::IntoContractError). A method which panics fails the whole batch.
+pub type ViewResponse = ContractResult<Value>;
+
+/// Implemented by `#[near_bindgen]` for a contract with `#[batch_view]` methods. The methods must
+/// be declared in a single impl section, which exports the `batch_view` method, so a second
+/// section with `#[batch_view]` methods fails to compile with conflicting implementations of
+/// this trait.
+pub trait BatchView {}
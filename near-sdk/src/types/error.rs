@@ -1,3 +1,6 @@
+use crate::{AccountId, Balance};
+use serde::{Deserialize, Serialize};
+
 /// Enables contract runtime to panic with the given type. Any error type used in conjunction
 /// with `#[return_result]` has to implement this trait.
 ///
@@ -32,3 +35,173 @@ where
         crate::env::panic_str(self.as_ref())
     }
 }
+
+/// Converts the `Err` of a `#[batch_view]` method with `#[return_result]` into the
+/// [`ContractError`] of its [`ViewResponse`](crate::ViewResponse), so that the error fails only
+/// its request instead of the whole batch. Errors which are strings get the
+/// [`METHOD_FAILED`](ContractError::METHOD_FAILED) code.
+pub trait IntoContractError {
+    fn into_contract_error(self) -> ContractError;
+}
+
+impl<T> IntoContractError for T
+where
+    T: AsRef<str>,
+{
+    fn into_contract_error(self) -> ContractError {
+        ContractError::new(ContractError::METHOD_FAILED, self.as_ref())
+    }
+}
+
+impl IntoContractError for ContractError {
+    fn into_contract_error(self) -> ContractError {
+        self
+    }
+}
+
+/// Error returned by contract methods and callbacks of multi-leg workflows as the `Err` of a
+/// [`ContractResult`], so that every leg reports failures in the same format.
+///
+/// Methods which panic with a `ContractError`, including methods with `#[return_result]` which
+/// return one, panic with the error serialized as JSON after the `ERROR_JSON:` prefix, e.g.
+/// `ERROR_JSON:{"code":"PAUSED","message":"The contract is paused"}`, so that clients can read
+/// the code from the failure of the transaction with
+/// [`from_panic_message`](Self::from_panic_message) instead of matching strings. Typed errors of
+/// contracts can panic in the same format by converting to a `ContractError`.
+///
+/// Callbacks which return a `ContractResult` instead of panicking keep the state changes they
+/// made, e.g. refunds scheduled by [`chain_or_rollback!`](crate::chain_or_rollback), and the
+/// result is serialized as `{"Ok":...}` or `{"Err":{"code":"...","message":"..."}}`. The next leg
+/// reads it with [`promise_result_as_contract_result`](crate::promise_result_as_contract_result).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContractError {
+    /// Machine readable kind of the error, e.g. `"INSUFFICIENT_BALANCE"`.
+    pub code: String,
+    pub message: String,
+}
+
+/// Result of contract methods and callbacks, see [`ContractError`].
+pub type ContractResult<T> = Result<T, ContractError>;
+
+impl ContractError {
+    /// Code of the error of a promise which failed, e.g. because it panicked.
+    pub const PROMISE_FAILED: &'static str = "PROMISE_FAILED";
+    /// Code of the error of a promise whose result can't be deserialized.
+    pub const INVALID_RESULT: &'static str = "INVALID_RESULT";
+    /// Code of the error of a method called with a deposit it doesn't accept.
+    pub const INVALID_DEPOSIT: &'static str = "INVALID_DEPOSIT";
+    /// Code of the error of a method called with a deposit lower than it requires.
+    pub const INSUFFICIENT_DEPOSIT: &'static str = "INSUFFICIENT_DEPOSIT";
+    /// Code of the error of a method called for an account which isn't registered.
+    pub const ACCOUNT_NOT_REGISTERED: &'static str = "ACCOUNT_NOT_REGISTERED";
+    /// Code of the error of a method called while the contract or the feature is paused.
+    pub const PAUSED: &'static str = "PAUSED";
+    /// Code of the error of a batched view request for a method which can't be batched.
+    pub const UNKNOWN_METHOD: &'static str = "UNKNOWN_METHOD";
+    /// Code of the error of a request whose arguments can't be deserialized.
+    pub const INVALID_ARGUMENTS: &'static str = "INVALID_ARGUMENTS";
+    /// Code of the error of a batched view request whose method returned a string error.
+    pub const METHOD_FAILED: &'static str = "METHOD_FAILED";
+    /// Code of the error of a transfer with a memo longer than the contract accepts.
+    pub const MEMO_TOO_LONG: &'static str = "MEMO_TOO_LONG";
+
+    /// Prefix of the panic messages of contract errors, followed by the error as JSON.
+    pub const PANIC_PREFIX: &'static str = "ERROR_JSON:";
+
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { code: code.into(), message: message.into() }
+    }
+
+    /// Returns the message the contract panics with for the error.
+    pub fn to_panic_message(&self) -> String {
+        format!(
+            "{}{}",
+            Self::PANIC_PREFIX,
+            serde_json::to_string(self).expect("Failed to serialize the error")
+        )
+    }
+
+    /// Parses the error from the message of a failed transaction, which may have text before
+    /// the `ERROR_JSON:` prefix, e.g. `Smart contract panicked: `. Returns `None` if the
+    /// message isn't a contract error.
+    pub fn from_panic_message(message: &str) -> Option<Self> {
+        let start = message.find(Self::PANIC_PREFIX)? + Self::PANIC_PREFIX.len();
+        serde_json::from_str(&message[start..]).ok()
+    }
+
+    /// Error of a method which requires a deposit of at least `amount` yoctoNEAR.
+    pub fn insufficient_deposit(amount: Balance) -> Self {
+        Self::new(
+            Self::INSUFFICIENT_DEPOSIT,
+            format!("Requires attached deposit of at least {} yoctoNEAR", amount),
+        )
+    }
+
+    /// Error of a method called for an account which isn't registered.
+    pub fn account_not_registered(account_id: &AccountId) -> Self {
+        Self::new(
+            Self::ACCOUNT_NOT_REGISTERED,
+            format!("The account {} is not registered", account_id),
+        )
+    }
+
+    /// Error of a transfer with a memo longer than `max_length` bytes.
+    pub fn memo_too_long(max_length: usize) -> Self {
+        Self::new(
+            Self::MEMO_TOO_LONG,
+            format!("The memo can't be longer than {} bytes", max_length),
+        )
+    }
+
+    /// Error of a method called while the whole contract, or the feature if it's given, is
+    /// paused.
+    pub fn paused(feature: Option<&str>) -> Self {
+        let message = match feature {
+            Some(feature) => format!("The feature {} is paused", feature),
+            None => "The contract is paused".to_string(),
+        };
+        Self::new(Self::PAUSED, message)
+    }
+}
+
+impl std::fmt::Display for ContractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+impl FunctionError for ContractError {
+    fn panic(&self) -> ! {
+        crate::env::panic_str(&self.to_panic_message())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_message() {
+        let error = ContractError::memo_too_long(4);
+        let message = error.to_panic_message();
+        assert_eq!(
+            message,
+            r#"ERROR_JSON:{"code":"MEMO_TOO_LONG","message":"The memo can't be longer than 4 bytes"}"#
+        );
+        let message = format!("Smart contract panicked: {}", message);
+        assert_eq!(ContractError::from_panic_message(&message), Some(error));
+        assert_eq!(ContractError::from_panic_message("MEMO_TOO_LONG: too long"), None);
+    }
+
+    #[test]
+    fn into_contract_error() {
+        let error = ContractError::memo_too_long(4);
+        assert_eq!(error.clone().into_contract_error(), error);
+        assert_eq!(
+            "not found".into_contract_error(),
+            ContractError::new(ContractError::METHOD_FAILED, "not found")
+        );
+    }
+}
@@ -23,8 +23,60 @@ pub struct Gas(pub u64);
 impl Gas {
     /// One Tera gas, which is 10^12 gas units.
     pub const ONE_TERA: Gas = Gas(1_000_000_000_000);
+
+    /// Gas the current call can still attach to promises after keeping `reserved` for itself and
+    /// its callbacks: the prepaid gas minus the gas used so far and `reserved`, or zero.
+    ///
+    /// Unlike `env::prepaid_gas() - reserved`, this accounts for the gas already burnt by the
+    /// call, so the result can be attached without exceeding the prepaid gas.
+    pub fn remaining_after(reserved: Gas) -> Gas {
+        Gas(crate::env::prepaid_gas()
+            .0
+            .saturating_sub(crate::env::used_gas().0)
+            .saturating_sub(reserved.0))
+    }
+
+    /// Splits the gas into one share per weight, proportional to the weights, e.g. to attach
+    /// [`remaining_after`](Self::remaining_after) to several promises. What's left by the rounding
+    /// of the shares goes to the first weight which isn't zero. Returns zero shares if all the
+    /// weights are zero.
+    pub fn split_by_weight(self, weights: &[GasWeight]) -> Vec<Gas> {
+        let total_weight: u128 = weights.iter().map(|weight| weight.0 as u128).sum();
+        if total_weight == 0 {
+            return vec![Gas(0); weights.len()];
+        }
+        let mut shares: Vec<Gas> = weights
+            .iter()
+            .map(|weight| Gas((self.0 as u128 * weight.0 as u128 / total_weight) as u64))
+            .collect();
+        let allocated: u64 = shares.iter().map(|share| share.0).sum();
+        if let Some(index) = weights.iter().position(|weight| weight.0 > 0) {
+            shares[index].0 += self.0 - allocated;
+        }
+        shares
+    }
+
+    /// Share of the gas for `weight` out of `total_weight`, rounded down, e.g. for a promise
+    /// created separately from the others it shares [`remaining_after`](Self::remaining_after)
+    /// with. Returns zero if `total_weight` is zero, and the whole gas if `weight` exceeds it.
+    pub fn weighted_share(self, weight: GasWeight, total_weight: GasWeight) -> Gas {
+        if total_weight.0 == 0 {
+            return Gas(0);
+        }
+        let weight = weight.0.min(total_weight.0);
+        Gas((self.0 as u128 * weight as u128 / total_weight.0 as u128) as u64)
+    }
 }
 
+/// Weight of a promise in the split of some gas with [`Gas::split_by_weight`] or
+/// [`Gas::weighted_share`].
+///
+/// The split happens when the promises are created, from the gas left at that point. The
+/// protocol-level distribution of the gas a call leaves unused between its promises, by weight,
+/// isn't available with the supported protocol version.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasWeight(pub u64);
+
 impl Serialize for Gas {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -137,4 +189,32 @@ mod tests {
         test_json_ser(8);
         test_json_ser(0);
     }
+
+    #[test]
+    fn split_by_weight() {
+        let weights = [GasWeight(1), GasWeight(0), GasWeight(2)];
+        assert_eq!(Gas(10).split_by_weight(&weights), vec![Gas(4), Gas(0), Gas(6)]);
+        assert_eq!(Gas(u64::MAX).split_by_weight(&[GasWeight(u64::MAX)]), vec![Gas(u64::MAX)]);
+        assert_eq!(Gas(10).split_by_weight(&[GasWeight(0)]), vec![Gas(0)]);
+        assert!(Gas(10).split_by_weight(&[]).is_empty());
+    }
+
+    #[test]
+    fn weighted_share() {
+        assert_eq!(Gas(10).weighted_share(GasWeight(1), GasWeight(3)), Gas(3));
+        assert_eq!(Gas(u64::MAX).weighted_share(GasWeight(1), GasWeight(1)), Gas(u64::MAX));
+        assert_eq!(Gas(10).weighted_share(GasWeight(5), GasWeight(2)), Gas(10));
+        assert_eq!(Gas(10).weighted_share(GasWeight(1), GasWeight(0)), Gas(0));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn remaining_after() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .prepaid_gas(Gas::ONE_TERA * 10)
+            .build());
+        let remaining = Gas::remaining_after(Gas::ONE_TERA * 4);
+        assert!(remaining <= Gas::ONE_TERA * 6 && remaining > Gas::ONE_TERA * 5);
+        assert_eq!(Gas::remaining_after(Gas::ONE_TERA * 11), Gas(0));
+    }
 }
@@ -0,0 +1,80 @@
+use crate::json_types::U64;
+use serde::{Deserialize, Serialize};
+
+/// Result of a named invariant check, as reported by the `health` view.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InvariantCheck {
+    pub name: String,
+    pub passed: bool,
+    /// Message describing the broken invariant, if the check failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl InvariantCheck {
+    /// Creates the result of a check returning a message describing the broken invariant, like
+    /// the `validate` of a configuration.
+    pub fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+        Self { name: name.into(), passed: result.is_ok(), message: result.err() }
+    }
+}
+
+/// Health of a contract as returned by the `health` view generated with
+/// `#[health_check(..)]`, so that monitoring systems can poll the same endpoint on every
+/// contract, e.g.:
+///
+/// ```text
+/// {"healthy":false,"state_version":"2","paused":false,"pending_upgrade":"1650000000000000000","last_config_change":null,"invariants":[{"name":"supply","passed":false,"message":"total supply doesn't match the balances"}]}
+/// ```
+///
+/// Timestamps are in nanoseconds. Values which the contract doesn't declare are `null`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    /// `true` if the contract isn't paused and all invariant checks passed.
+    pub healthy: bool,
+    pub state_version: Option<U64>,
+    /// Whether the whole contract is paused.
+    pub paused: bool,
+    /// Timestamp from which staged code can be deployed, if an upgrade is pending.
+    pub pending_upgrade: Option<U64>,
+    /// Timestamp of the last change of the configuration.
+    pub last_config_change: Option<U64>,
+    pub invariants: Vec<InvariantCheck>,
+}
+
+impl HealthReport {
+    pub fn new(
+        state_version: Option<U64>,
+        paused: bool,
+        pending_upgrade: Option<U64>,
+        last_config_change: Option<U64>,
+        invariants: Vec<InvariantCheck>,
+    ) -> Self {
+        let healthy = !paused && invariants.iter().all(|check| check.passed);
+        Self { healthy, state_version, paused, pending_upgrade, last_config_change, invariants }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_report() {
+        let report = HealthReport::new(
+            Some(U64(2)),
+            false,
+            None,
+            Some(U64(10)),
+            vec![
+                InvariantCheck::new("config", Ok(())),
+                InvariantCheck::new("supply", Err("total supply is off".to_string())),
+            ],
+        );
+        assert!(!report.healthy);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"healthy":false,"state_version":"2","paused":false,"pending_upgrade":null,"last_config_change":"10","invariants":[{"name":"config","passed":true},{"name":"supply","passed":false,"message":"total supply is off"}]}"#
+        );
+    }
+}
@@ -11,10 +11,21 @@ mod account_id;
 pub use self::account_id::{AccountId, ParseAccountIdError};
 
 mod gas;
-pub use self::gas::Gas;
+pub use self::gas::{Gas, GasWeight};
 
 mod error;
-pub use self::error::FunctionError;
+pub use self::error::{ContractError, ContractResult, FunctionError, IntoContractError};
+
+mod batch_view;
+pub use self::batch_view::{BatchView, ViewRequest, ViewResponse};
+
+mod health;
+pub use self::health::{HealthReport, InvariantCheck};
+
+#[cfg(feature = "protocol_feature_alt_bn128")]
+mod alt_bn128;
+#[cfg(feature = "protocol_feature_alt_bn128")]
+pub use self::alt_bn128::{AltBn128Fq, AltBn128Fr, G1Point, G2Point};
 
 /// Raw type for duration in nanoseconds
 pub type Duration = u64;
@@ -90,6 +90,24 @@ impl PublicKey {
         Ok(Self { data: bytes })
     }
 
+    /// Creates an ed25519 key from its 32 bytes, e.g. a key generated off-chain for a linkdrop.
+    pub fn ed25519(bytes: [u8; 32]) -> Self {
+        Self::from_fixed_parts(CurveType::ED25519, &bytes)
+    }
+
+    /// Creates a secp256k1 key from the 64 bytes of its uncompressed format, without the `0x04`
+    /// prefix.
+    pub fn secp256k1(bytes: [u8; 64]) -> Self {
+        Self::from_fixed_parts(CurveType::SECP256K1, &bytes)
+    }
+
+    fn from_fixed_parts(curve: CurveType, bytes: &[u8]) -> Self {
+        let mut data = Vec::with_capacity(1 + bytes.len());
+        data.push(curve as u8);
+        data.extend_from_slice(bytes);
+        Self { data }
+    }
+
     /// Returns a byte slice of this `PublicKey`'s contents.
     pub fn as_bytes(&self) -> &[u8] {
         &self.data
@@ -249,6 +267,17 @@ mod tests {
         assert_eq!(key, expected_key());
     }
 
+    #[test]
+    fn test_public_key_from_array() {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&expected_key().as_bytes()[1..]);
+        assert_eq!(PublicKey::ed25519(bytes), expected_key());
+
+        let key = PublicKey::secp256k1([7; 64]);
+        assert_eq!(key.curve_type(), CurveType::SECP256K1);
+        assert_eq!(String::from(&key).parse::<PublicKey>().unwrap(), key);
+    }
+
     #[test]
     fn test_public_key_to_string() {
         let key: PublicKey = expected_key();
@@ -35,4 +35,6 @@ pub enum PromiseError {
     Failed,
     /// Current version of the protocol never returns this variant.
     NotReady,
+    /// Promise result is larger than the accepted number of bytes, holds its length.
+    TooLarge(u64),
 }
@@ -0,0 +1,203 @@
+use crate::{Balance, Gas};
+
+/// Minimum price of gas on mainnet, in yoctoNEAR per unit of gas. The actual price can be higher
+/// when blocks are congested.
+pub const MIN_GAS_PRICE: Balance = 100_000_000;
+
+/// Fee of an action, in gas, split like the fees of the runtime config: `send` is burnt when the
+/// receipt is created and `execution` when it's executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionFee {
+    pub send: Gas,
+    pub execution: Gas,
+}
+
+impl ActionFee {
+    const fn new(send: u64, execution: u64) -> Self {
+        Self { send: Gas(send), execution: Gas(execution) }
+    }
+
+    fn total(&self) -> Gas {
+        self.send + self.execution
+    }
+
+    fn per_bytes(&self, bytes: u64) -> Gas {
+        Gas(self.total().0.saturating_mul(bytes))
+    }
+}
+
+/// Fees of the actions of a receipt. Contracts can't read the fee config of the runtime, so the
+/// [`Default`] fees are those of the current protocol. Off-chain, they can be taken from the
+/// `RuntimeFeesConfig` of the runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionFees {
+    pub action_receipt_creation: ActionFee,
+    pub create_account: ActionFee,
+    pub transfer: ActionFee,
+    pub function_call: ActionFee,
+    pub function_call_per_byte: ActionFee,
+    pub deploy_contract: ActionFee,
+    pub deploy_contract_per_byte: ActionFee,
+}
+
+impl Default for ActionFees {
+    fn default() -> Self {
+        Self {
+            action_receipt_creation: ActionFee::new(108_059_500_000, 108_059_500_000),
+            create_account: ActionFee::new(99_607_375_000, 99_607_375_000),
+            transfer: ActionFee::new(115_123_062_500, 115_123_062_500),
+            function_call: ActionFee::new(2_319_861_500_000, 2_319_861_500_000),
+            function_call_per_byte: ActionFee::new(2_235_934, 2_235_934),
+            deploy_contract: ActionFee::new(184_765_750_000, 184_765_750_000),
+            deploy_contract_per_byte: ActionFee::new(6_812_999, 64_572_944),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<&crate::RuntimeFeesConfig> for ActionFees {
+    fn from(config: &crate::RuntimeFeesConfig) -> Self {
+        let fee = |fee: &near_primitives_core::runtime::fees::Fee| {
+            ActionFee::new(fee.send_not_sir, fee.execution)
+        };
+        let actions = &config.action_creation_config;
+        Self {
+            action_receipt_creation: fee(&config.action_receipt_creation_config),
+            create_account: fee(&actions.create_account_cost),
+            transfer: fee(&actions.transfer_cost),
+            function_call: fee(&actions.function_call_cost),
+            function_call_per_byte: fee(&actions.function_call_cost_per_byte),
+            deploy_contract: fee(&actions.deploy_contract_cost),
+            deploy_contract_per_byte: fee(&actions.deploy_contract_cost_per_byte),
+        }
+    }
+}
+
+/// Action of a receipt created by the contract, whose cost is estimated with
+/// [`estimate_action_cost`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptAction<'a> {
+    CreateAccount,
+    Transfer { amount: Balance },
+    FunctionCall { method_name: &'a str, args_len: u64, amount: Balance, gas: Gas },
+    DeployContract { code_len: u64 },
+}
+
+/// Cost of actions: the gas burnt for them and attached to function calls, which is taken from
+/// the prepaid gas of the current call, and the NEAR attached to them, which is taken from the
+/// balance of the contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActionCost {
+    pub gas: Gas,
+    pub deposit: Balance,
+}
+
+impl ActionCost {
+    /// Cost in NEAR of the gas and the deposit at the given gas price, e.g. to charge the caller
+    /// of a factory for the receipts it creates on their behalf.
+    pub fn to_near(&self, gas_price: Balance) -> Balance {
+        (self.gas.0 as Balance).saturating_mul(gas_price).saturating_add(self.deposit)
+    }
+}
+
+impl core::ops::Add for ActionCost {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { gas: self.gas + other.gas, deposit: self.deposit.saturating_add(other.deposit) }
+    }
+}
+
+/// Estimates the cost of an action with the [`Default`] fees of [`ActionFees`]. The cost of
+/// creating the receipt holding the action is not included, see [`estimate_receipt_cost`].
+///
+/// # Example
+/// ```
+/// use near_sdk::utils::{estimate_action_cost, ReceiptAction, MIN_GAS_PRICE};
+///
+/// let cost = estimate_action_cost(&ReceiptAction::Transfer { amount: 10 });
+/// assert_eq!(cost.deposit, 10);
+/// assert_eq!(cost.to_near(MIN_GAS_PRICE), 230_246_125_000 * MIN_GAS_PRICE + 10);
+/// ```
+pub fn estimate_action_cost(action: &ReceiptAction) -> ActionCost {
+    ActionFees::default().action_cost(action)
+}
+
+/// Estimates the cost of a receipt with the actions with the [`Default`] fees of
+/// [`ActionFees`], including the creation of the receipt.
+pub fn estimate_receipt_cost(actions: &[ReceiptAction]) -> ActionCost {
+    ActionFees::default().receipt_cost(actions)
+}
+
+impl ActionFees {
+    /// Estimates the cost of an action, without the creation of its receipt.
+    pub fn action_cost(&self, action: &ReceiptAction) -> ActionCost {
+        match action {
+            ReceiptAction::CreateAccount => {
+                ActionCost { gas: self.create_account.total(), deposit: 0 }
+            }
+            ReceiptAction::Transfer { amount } => {
+                ActionCost { gas: self.transfer.total(), deposit: *amount }
+            }
+            ReceiptAction::FunctionCall { method_name, args_len, amount, gas } => {
+                let bytes = (method_name.len() as u64).saturating_add(*args_len);
+                ActionCost {
+                    gas: self.function_call.total()
+                        + self.function_call_per_byte.per_bytes(bytes)
+                        + *gas,
+                    deposit: *amount,
+                }
+            }
+            ReceiptAction::DeployContract { code_len } => ActionCost {
+                gas: self.deploy_contract.total()
+                    + self.deploy_contract_per_byte.per_bytes(*code_len),
+                deposit: 0,
+            },
+        }
+    }
+
+    /// Estimates the cost of a receipt with the actions, including its creation.
+    pub fn receipt_cost(&self, actions: &[ReceiptAction]) -> ActionCost {
+        actions.iter().fold(
+            ActionCost { gas: self.action_receipt_creation.total(), deposit: 0 },
+            |cost, action| cost + self.action_cost(action),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RuntimeFeesConfig;
+
+    #[test]
+    fn function_call_cost() {
+        let fees = ActionFees::default();
+        let call = ReceiptAction::FunctionCall {
+            method_name: "ft_transfer",
+            args_len: 89,
+            amount: 1,
+            gas: Gas(5_000_000_000_000),
+        };
+        let cost = fees.receipt_cost(&[call.clone()]);
+        assert_eq!(
+            cost.gas.0,
+            2 * (108_059_500_000 + 2_319_861_500_000 + 100 * 2_235_934) + 5_000_000_000_000
+        );
+        assert_eq!(cost.deposit, 1);
+        assert_eq!(
+            estimate_receipt_cost(&[ReceiptAction::CreateAccount, call]),
+            cost + estimate_action_cost(&ReceiptAction::CreateAccount)
+        );
+    }
+
+    #[test]
+    fn fees_from_runtime_config() {
+        let config = RuntimeFeesConfig::test();
+        let fees = ActionFees::from(&config);
+        assert_eq!(
+            fees.transfer.execution.0,
+            config.action_creation_config.transfer_cost.execution
+        );
+    }
+}
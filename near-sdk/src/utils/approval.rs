@@ -0,0 +1,274 @@
+use crate::{env, require, AccountId, CryptoHash, IntoStorageKey, PublicKey, Timestamp};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Prefix of the signed message, which separates approval digests from hashes of other data.
+pub const APPROVAL_DOMAIN: &[u8] = b"NEAR_APPROVAL_V1:";
+
+const DEFAULT_APPROVAL_NONCES_PREFIX: &[u8] = b"__approval_nonces";
+
+/// Context of a call a signer approves off-chain, e.g. for permits and meta-transactions which
+/// are relayed to the contract by another account.
+///
+/// The signer signs the [`digest`](Self::digest) of the context, which binds the approval to the
+/// contract, the method and its exact arguments, and makes it valid only once, for the signer's
+/// next nonce, and only until `expires_at`. Contracts check the context with
+/// [`ApprovalNonces::verify`] before the signature.
+///
+/// The digest is the SHA-256 hash of [`APPROVAL_DOMAIN`] followed by the Borsh serialization of
+/// the context, so it can be computed by clients in any language:
+/// `contract_id` and `method_name` as `u32` length prefixed strings, `args_hash` as 32 bytes and
+/// `nonce` and `expires_at` as little endian `u64`.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::ApprovalContext;
+///
+/// let context = ApprovalContext::new(
+///     "token.near".parse().unwrap(),
+///     "ft_transfer",
+///     br#"{"receiver_id":"bob.near","amount":"100"}"#,
+///     0,
+///     1_700_000_000_000_000_000,
+/// );
+/// assert_eq!(context.digest().len(), 32);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalContext {
+    /// Contract which executes the approved call.
+    pub contract_id: AccountId,
+    pub method_name: String,
+    /// SHA-256 hash of the serialized arguments of the call.
+    pub args_hash: CryptoHash,
+    /// Nonce of the signer, see [`ApprovalNonces::next_nonce`].
+    pub nonce: u64,
+    /// Block timestamp in nanoseconds after which the approval can't be used.
+    pub expires_at: Timestamp,
+}
+
+impl ApprovalContext {
+    /// Creates the context of a call of `method_name` with the serialized `args`.
+    pub fn new(
+        contract_id: AccountId,
+        method_name: &str,
+        args: &[u8],
+        nonce: u64,
+        expires_at: Timestamp,
+    ) -> Self {
+        Self {
+            contract_id,
+            method_name: method_name.to_string(),
+            args_hash: env::sha256_array(args),
+            nonce,
+            expires_at,
+        }
+    }
+
+    /// Creates the context of a call of `method_name` with the serialized `args` on the current
+    /// contract.
+    pub fn for_current_contract(
+        method_name: &str,
+        args: &[u8],
+        nonce: u64,
+        expires_at: Timestamp,
+    ) -> Self {
+        Self::new(env::current_account_id(), method_name, args, nonce, expires_at)
+    }
+
+    /// Message signed by the signer of the approval.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = APPROVAL_DOMAIN.to_vec();
+        self.serialize(&mut message).unwrap_or_else(|_| env::abort());
+        message
+    }
+
+    /// SHA-256 hash of the [`message`](Self::message).
+    pub fn digest(&self) -> CryptoHash {
+        env::sha256_array(&self.message())
+    }
+
+    /// Returns `true` if the `signature` of the digest with the recovery byte `v` was created by
+    /// the secp256k1 `public_key`. Returns `false` for keys of other curves.
+    #[cfg(feature = "unstable")]
+    pub fn is_signed_by_secp256k1(
+        &self,
+        public_key: &PublicKey,
+        signature: &[u8; 64],
+        v: u8,
+    ) -> bool {
+        public_key.curve_type() == crate::CurveType::SECP256K1
+            && env::ecrecover(&self.digest(), signature, v, true)
+                .map_or(false, |recovered| recovered[..] == public_key.as_bytes()[1..])
+    }
+}
+
+/// Nonces of the signers of [`ApprovalContext`]s, which make every approval usable only once.
+///
+/// Nonces are sequential per signer key starting from 0, so an approval is only valid for the
+/// [`next_nonce`](Self::next_nonce) of its signer and approvals are used in the order they were
+/// signed. Nonces are stored directly in contract storage under the prefix.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::{env, ApprovalContext, ApprovalNonces, PublicKey};
+///
+/// # near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new().build());
+/// let signer: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+/// let nonces = ApprovalNonces::default();
+/// let context = ApprovalContext::for_current_contract(
+///     "withdraw",
+///     b"{}",
+///     nonces.next_nonce(&signer),
+///     env::block_timestamp() + 1,
+/// );
+/// let digest = nonces.verify(&signer, &context);
+/// // Check the signature of `digest` by `signer` here.
+/// assert_eq!(nonces.next_nonce(&signer), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalNonces {
+    prefix: Vec<u8>,
+}
+
+impl Default for ApprovalNonces {
+    fn default() -> Self {
+        Self::new(DEFAULT_APPROVAL_NONCES_PREFIX)
+    }
+}
+
+impl ApprovalNonces {
+    /// Creates nonces stored under `prefix`.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key() }
+    }
+
+    /// Nonce the next approval of the signer has to use.
+    pub fn next_nonce(&self, signer: &PublicKey) -> u64 {
+        env::storage_read(&self.storage_key(signer)).map_or(0, |bytes| {
+            u64::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize approval nonce"))
+        })
+    }
+
+    /// Checks that the approval is for the current contract, has not expired and uses the next
+    /// nonce of the signer, then consumes the nonce and returns the digest whose signature by
+    /// `signer` has to be verified by the caller. Panics if a check fails.
+    pub fn verify(&self, signer: &PublicKey, context: &ApprovalContext) -> CryptoHash {
+        require!(
+            context.contract_id == env::current_account_id(),
+            "Approval is for another contract"
+        );
+        require!(env::block_timestamp() <= context.expires_at, "Approval expired");
+        let nonce = self.next_nonce(signer);
+        require!(context.nonce == nonce, "Invalid approval nonce");
+        let next_nonce =
+            nonce.checked_add(1).unwrap_or_else(|| env::panic_str("Approval nonce overflow"));
+        env::storage_write(
+            &self.storage_key(signer),
+            &next_nonce.try_to_vec().unwrap_or_else(|_| env::abort()),
+        );
+        context.digest()
+    }
+
+    /// Same as [`verify`](Self::verify), but also checks that the approval is for a call of
+    /// `method_name` with the serialized `args`.
+    pub fn verify_call(
+        &self,
+        signer: &PublicKey,
+        context: &ApprovalContext,
+        method_name: &str,
+        args: &[u8],
+    ) -> CryptoHash {
+        require!(context.method_name == method_name, "Approval is for another method");
+        require!(context.args_hash == env::sha256_array(args), "Approval is for other arguments");
+        self.verify(signer, context)
+    }
+
+    fn storage_key(&self, signer: &PublicKey) -> Vec<u8> {
+        [self.prefix.as_slice(), signer.as_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::testing_env;
+
+    fn signer() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap()
+    }
+
+    fn setup() -> ApprovalNonces {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .block_timestamp(100)
+            .build());
+        ApprovalNonces::new(b"n".to_vec())
+    }
+
+    #[test]
+    fn digest_is_canonical() {
+        let context = ApprovalContext::new(accounts(0), "withdraw", b"{}", 7, 100);
+        let mut message = APPROVAL_DOMAIN.to_vec();
+        message.extend_from_slice(&5u32.to_le_bytes());
+        message.extend_from_slice(b"alice");
+        message.extend_from_slice(&8u32.to_le_bytes());
+        message.extend_from_slice(b"withdraw");
+        message.extend_from_slice(&env::sha256_array(b"{}"));
+        message.extend_from_slice(&7u64.to_le_bytes());
+        message.extend_from_slice(&100u64.to_le_bytes());
+        assert_eq!(context.message(), message);
+        assert_eq!(context.digest(), env::sha256_array(&message));
+
+        let other_args = ApprovalContext::new(accounts(0), "withdraw", b"{ }", 7, 100);
+        assert_ne!(other_args.digest(), context.digest());
+    }
+
+    #[test]
+    fn nonces_are_sequential() {
+        let nonces = setup();
+        let context = ApprovalContext::for_current_contract("withdraw", b"{}", 0, 100);
+        assert_eq!(nonces.verify_call(&signer(), &context, "withdraw", b"{}"), context.digest());
+        assert_eq!(nonces.next_nonce(&signer()), 1);
+        let context = ApprovalContext { nonce: 1, ..context };
+        nonces.verify(&signer(), &context);
+        assert_eq!(nonces.next_nonce(&signer()), 2);
+        assert_eq!(ApprovalNonces::new(b"m".to_vec()).next_nonce(&signer()), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid approval nonce")]
+    fn replay() {
+        let nonces = setup();
+        let context = ApprovalContext::for_current_contract("withdraw", b"{}", 0, 100);
+        nonces.verify(&signer(), &context);
+        nonces.verify(&signer(), &context);
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval expired")]
+    fn expired() {
+        let nonces = setup();
+        nonces.verify(&signer(), &ApprovalContext::for_current_contract("withdraw", b"{}", 0, 99));
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval is for another contract")]
+    fn other_contract() {
+        let nonces = setup();
+        nonces.verify(&signer(), &ApprovalContext::new(accounts(1), "withdraw", b"{}", 0, 100));
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval is for other arguments")]
+    fn other_args() {
+        let nonces = setup();
+        let context = ApprovalContext::for_current_contract("withdraw", b"{}", 0, 100);
+        nonces.verify_call(&signer(), &context, "withdraw", b"{\"amount\":\"1\"}");
+    }
+}
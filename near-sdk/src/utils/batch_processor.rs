@@ -0,0 +1,153 @@
+use crate::collections::Vector;
+use crate::{env, Gas, IntoStorageKey};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Processes a large collection across multiple function calls, so that methods like
+/// "distribute rewards to all users" cannot run out of gas halfway through.
+///
+/// Items are processed by index until only the configured gas reserve remains, at which point
+/// the index of the next item is persisted as a cursor and processing stops. The method should
+/// then be called again until the processor reports that no work remains, after which the
+/// cursor is reset so that the next run starts from the first item again.
+///
+/// The cursor only tracks the index of the next item, so the collection should not be
+/// reordered or have items removed until a run is complete.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::collections::Vector;
+/// use near_sdk::{BatchProcessor, Gas};
+///
+/// let mut balances: Vector<u128> = Vector::new(b"b");
+/// balances.extend(vec![10, 20, 30]);
+///
+/// let batch = BatchProcessor::new(b"c".to_vec(), Gas(5 * Gas::ONE_TERA.0));
+/// let mut total = 0;
+/// let has_more = batch.process_vector(&balances, |balance| total += balance);
+/// assert!(!has_more);
+/// assert_eq!(total, 60);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchProcessor {
+    cursor_key: Vec<u8>,
+    gas_reserve: Gas,
+}
+
+impl BatchProcessor {
+    /// Creates a processor which persists its cursor under `cursor_key` and stops processing
+    /// when less than `gas_reserve` gas remains, leaving enough to finish the function call.
+    pub fn new<S>(cursor_key: S, gas_reserve: Gas) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { cursor_key: cursor_key.into_storage_key(), gas_reserve }
+    }
+
+    /// Gas which is kept available for the rest of the function call.
+    pub fn gas_reserve(&self) -> Gas {
+        self.gas_reserve
+    }
+
+    /// Index of the next item to process.
+    pub fn cursor(&self) -> u64 {
+        env::storage_read(&self.cursor_key)
+            .map(|bytes| {
+                u64::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str("Cannot deserialize batch cursor"))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Resets the cursor so that the next call starts from the first item.
+    pub fn reset(&self) {
+        env::storage_remove(&self.cursor_key);
+    }
+
+    /// Calls `f` with the indices of the items from the cursor up to `len`, until the gas
+    /// reserve is reached. Returns `true` if more items remain to be processed in a following
+    /// function call, and `false` once all items have been processed.
+    #[must_use = "the method should be called again if more work remains"]
+    pub fn process<F>(&self, len: u64, mut f: F) -> bool
+    where
+        F: FnMut(u64),
+    {
+        let mut index = self.cursor();
+        while index < len {
+            if !self.has_gas_remaining() {
+                let cursor = index.try_to_vec().unwrap_or_else(|_| env::abort());
+                env::storage_write(&self.cursor_key, &cursor);
+                return true;
+            }
+            f(index);
+            index += 1;
+        }
+        self.reset();
+        false
+    }
+
+    /// Calls `f` with the elements of `vector` from the cursor, like
+    /// [`process`](Self::process).
+    #[must_use = "the method should be called again if more work remains"]
+    pub fn process_vector<T, F>(&self, vector: &Vector<T>, mut f: F) -> bool
+    where
+        T: BorshSerialize + BorshDeserialize,
+        F: FnMut(T),
+    {
+        self.process(vector.len(), |index| {
+            f(vector.get(index).unwrap_or_else(|| env::panic_str("Batch index out of bounds")))
+        })
+    }
+
+    fn has_gas_remaining(&self) -> bool {
+        env::prepaid_gas().0.saturating_sub(env::used_gas().0) > self.gas_reserve.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[test]
+    fn out_of_gas() {
+        testing_env!(VMContextBuilder::new().build());
+        let batch = BatchProcessor::new(b"c".to_vec(), Gas(u64::MAX));
+        let mut processed = vec![];
+        assert!(batch.process(3, |index| processed.push(index)));
+        assert!(processed.is_empty());
+        assert_eq!(batch.cursor(), 0);
+    }
+
+    #[test]
+    fn resumes_from_cursor() {
+        let prepaid_gas = Gas(20 * Gas::ONE_TERA.0);
+        testing_env!(VMContextBuilder::new().prepaid_gas(prepaid_gas).build());
+        // Reserve all but a few host calls worth of gas to force stopping early.
+        let used_gas = env::used_gas();
+        env::storage_write(b"probe", &[0u8; 64]);
+        let write_gas = env::used_gas().0 - used_gas.0;
+        let batch = BatchProcessor::new(b"c".to_vec(), Gas(prepaid_gas.0 - 5 * write_gas));
+
+        let mut processed = vec![];
+        let mut calls = 0;
+        loop {
+            testing_env!(VMContextBuilder::new().prepaid_gas(prepaid_gas).build());
+            calls += 1;
+            assert!(calls < 100, "no progress made");
+            let has_more = batch.process(20, |index| {
+                env::storage_write(b"item", &[0u8; 64]);
+                processed.push(index);
+            });
+            if !has_more {
+                break;
+            }
+            assert_eq!(batch.cursor(), processed.len() as u64);
+        }
+
+        assert!(calls > 1);
+        assert_eq!(processed, (0..20).collect::<Vec<_>>());
+        assert_eq!(batch.cursor(), 0);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{env, Timestamp};
+
+/// Source of the current time for components with time based logic, such as the expiry of
+/// [`Idempotency`](crate::Idempotency) entries.
+///
+/// Contracts use [`BlockClock`]. Unit tests can inject any function returning a timestamp
+/// instead, or move the block time with
+/// [`test_utils::set_block_timestamp`](crate::test_utils::set_block_timestamp).
+pub trait Clock {
+    /// Current time in nanoseconds since the Unix epoch.
+    fn now(&self) -> Timestamp;
+}
+
+/// Clock returning the timestamp of the current block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockClock;
+
+impl Clock for BlockClock {
+    fn now(&self) -> Timestamp {
+        env::block_timestamp()
+    }
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> Timestamp,
+{
+    fn now(&self) -> Timestamp {
+        self()
+    }
+}
@@ -0,0 +1,144 @@
+use crate::env;
+use crate::environment::overlay;
+use crate::json_types::{U128, U64};
+use serde::{Deserialize, Serialize};
+
+/// Result of the `estimate_<method>` view generated for a method marked with `#[estimate]`, so
+/// that clients can quote the gas and storage deposit of a call before sending the transaction.
+///
+/// The view runs the method against a scratch storage overlay: its storage writes, including the
+/// write of the contract state, are kept in memory and discarded afterwards. The fees of the
+/// skipped storage host functions are added to the gas used, and the storage usage delta counts
+/// the bytes of the records which would be added or removed. `env::storage_usage()` includes
+/// the delta while the method runs, so storage deposits computed by the method are estimated.
+///
+/// Logs of the method, including its events, are dropped. Promises can't be created while the
+/// method runs, so methods which schedule them fail in their estimate, as do methods which read
+/// the caller, the attached deposit or the prepaid gas. The view rejects attached deposits and
+/// applies the `#[private]` check of the method, so the estimate of a private method can only be
+/// called by the contract itself. Requires the `estimate` feature.
+///
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// # use near_sdk::near_bindgen;
+/// # use std::collections::HashMap;
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {
+///     notes: HashMap<u64, String>,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     /// Also exported as the `estimate_add_note` view.
+///     #[estimate]
+///     pub fn add_note(&mut self, id: u64, text: String) {
+///         self.notes.insert(id, text);
+///     }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GasEstimate {
+    /// Gas used by the method, including the serialization of the contract state and the fees
+    /// of its storage writes.
+    pub gas_used: U64,
+    /// Change of the storage usage of the contract caused by the method, in bytes.
+    pub storage_usage_delta: i64,
+    /// Storage staking cost of the added bytes, zero if the method releases storage.
+    pub storage_cost: U128,
+}
+
+impl GasEstimate {
+    /// Runs `f` without committing its storage writes and measures its cost.
+    pub fn measure(f: impl FnOnce()) -> Self {
+        /// Discards the overlay even if `f` panics, e.g. in unit tests.
+        struct Guard;
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                overlay::end();
+            }
+        }
+
+        let initial_gas = env::used_gas();
+        overlay::begin();
+        let _guard = Guard;
+        f();
+        let gas_used = env::used_gas().0 - initial_gas.0;
+        let overlay = overlay::end().unwrap_or_else(|| env::abort());
+        let storage_usage_delta = overlay.storage_usage_delta;
+        Self {
+            gas_used: U64(gas_used.saturating_add(overlay.storage_gas)),
+            storage_usage_delta,
+            storage_cost: U128(storage_usage_delta.max(0) as u128 * env::storage_byte_cost()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_are_not_committed() {
+        env::storage_write(b"a", b"12345");
+        env::storage_write(b"b", b"1");
+        let initial_usage = env::storage_usage();
+        let estimate = GasEstimate::measure(|| {
+            assert!(env::storage_write(b"a", b"123"));
+            assert_eq!(env::storage_get_evicted(), Some(b"12345".to_vec()));
+            assert!(!env::storage_write(b"c", b"1234"));
+            assert!(env::storage_remove(b"b"));
+            assert!(!env::storage_has_key(b"b"));
+            assert_eq!(env::storage_read(b"a"), Some(b"123".to_vec()));
+            assert_eq!(env::storage_usage(), initial_usage + 1);
+        });
+        // -2 bytes of "a", +45 bytes of the record of "c", -42 bytes of the record of "b".
+        assert_eq!(estimate.storage_usage_delta, 1);
+        assert_eq!(estimate.storage_cost, U128(env::storage_byte_cost()));
+        assert!(estimate.gas_used.0 > 0);
+        assert_eq!(env::storage_read(b"a"), Some(b"12345".to_vec()));
+        assert!(env::storage_has_key(b"b"));
+        assert!(!env::storage_has_key(b"c"));
+        assert_eq!(env::storage_usage(), initial_usage);
+    }
+
+    #[test]
+    fn released_storage() {
+        env::storage_write(b"a", b"1");
+        let estimate = GasEstimate::measure(|| {
+            env::storage_remove(b"a");
+        });
+        assert_eq!(estimate.storage_usage_delta, -42);
+        assert_eq!(estimate.storage_cost, U128(0));
+    }
+
+    #[test]
+    fn logs_are_dropped() {
+        GasEstimate::measure(|| {
+            env::log_str("EVENT_JSON:{}");
+        });
+        env::log_str("committed");
+        assert_eq!(crate::test_utils::get_logs(), vec!["committed".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Promises can't be created while estimating a method")]
+    fn promises_are_denied() {
+        GasEstimate::measure(|| {
+            crate::Promise::new("alice.near".parse().unwrap()).transfer(1);
+        });
+    }
+
+    #[test]
+    fn panic_discards_overlay() {
+        let result = std::panic::catch_unwind(|| {
+            GasEstimate::measure(|| {
+                env::storage_write(b"a", b"1");
+                panic!("failed");
+            })
+        });
+        assert!(result.is_err());
+        env::storage_write(b"a", b"2");
+        assert_eq!(env::storage_read(b"a"), Some(b"2".to_vec()));
+    }
+}
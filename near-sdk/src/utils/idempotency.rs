@@ -0,0 +1,207 @@
+use crate::{env, BlockClock, Clock, Duration, IntoStorageKey, Timestamp};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Time after which a request id of [`Idempotency::default`] can be reused, 24 hours.
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = 24 * 60 * 60 * 1_000_000_000;
+
+const DEFAULT_IDEMPOTENCY_PREFIX: &[u8] = b"__idempotency";
+
+/// Guard which records the results of requests by their id, so that a request which is retried
+/// (for example by a payment processor or bridge relayer) is only executed once and the prior
+/// result is returned on replay.
+///
+/// Entries are stored directly in contract storage under the guard's prefix and remain valid
+/// for the configured time to live. Expired entries are not removed automatically, but are
+/// overwritten when the request id is reused or can be removed with [`Idempotency::forget`].
+///
+/// This is used by the `#[idempotent(key_arg = "request_id")]` attribute of methods within
+/// `#[near_bindgen]`, which uses the default guard with the request id scoped by
+/// [`Idempotency::caller_request_id`]. Use `#[idempotent(key_arg = "request_id", ttl = <nanoseconds>)]` to override the
+/// time to live.
+///
+/// Expiry is measured with the [`BlockClock`] unless another [`Clock`] is injected with
+/// [`Idempotency::with_clock`].
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::Idempotency;
+///
+/// let guard = Idempotency::default();
+/// assert_eq!(guard.cached_result("transfer:42"), None);
+/// guard.record("transfer:42", b"\"done\"");
+/// assert_eq!(guard.cached_result("transfer:42"), Some(b"\"done\"".to_vec()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Idempotency<C = BlockClock> {
+    prefix: Vec<u8>,
+    ttl: Duration,
+    clock: C,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct IdempotencyEntry {
+    expires_at: Timestamp,
+    result: Vec<u8>,
+}
+
+impl Default for Idempotency {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_PREFIX, DEFAULT_IDEMPOTENCY_TTL)
+    }
+}
+
+impl Idempotency {
+    /// Creates a guard storing request ids under `prefix` for `ttl` nanoseconds.
+    pub fn new<S>(prefix: S, ttl: Duration) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key(), ttl, clock: BlockClock }
+    }
+
+    /// Creates a guard with the default prefix and a custom time to live in nanoseconds.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_PREFIX, ttl)
+    }
+
+    /// Returns the request id of a call of `method_name`, scoped by the method and the
+    /// predecessor so that callers reusing each other's request ids don't get each other's
+    /// results.
+    pub fn caller_request_id(method_name: &str, request_id: &str) -> String {
+        format!("{}:{}:{}", method_name, env::predecessor_account_id(), request_id)
+    }
+}
+
+impl<C: Clock> Idempotency<C> {
+    /// Replaces the clock used to measure expiry, e.g. to control time in unit tests.
+    pub fn with_clock<T: Clock>(self, clock: T) -> Idempotency<T> {
+        Idempotency { prefix: self.prefix, ttl: self.ttl, clock }
+    }
+
+    /// Time in nanoseconds for which a recorded result is returned on replay.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the result recorded for the request id if it has not expired yet.
+    pub fn cached_result(&self, request_id: &str) -> Option<Vec<u8>> {
+        let entry = self.read_entry(request_id)?;
+        if self.clock.now() < entry.expires_at {
+            Some(entry.result)
+        } else {
+            None
+        }
+    }
+
+    /// Records the serialized result of the request, overwriting any previous, expired or not.
+    pub fn record(&self, request_id: &str, result: &[u8]) {
+        let entry = IdempotencyEntry {
+            expires_at: self.clock.now().saturating_add(self.ttl),
+            result: result.to_vec(),
+        };
+        env::storage_write(
+            &self.storage_key(request_id),
+            &entry.try_to_vec().unwrap_or_else(|_| env::abort()),
+        );
+    }
+
+    /// Removes the request id from storage, returning `true` if it was recorded.
+    pub fn forget(&self, request_id: &str) -> bool {
+        env::storage_remove(&self.storage_key(request_id))
+    }
+
+    fn read_entry(&self, request_id: &str) -> Option<IdempotencyEntry> {
+        env::storage_read(&self.storage_key(request_id)).map(|bytes| {
+            IdempotencyEntry::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize idempotency entry"))
+        })
+    }
+
+    fn storage_key(&self, request_id: &str) -> Vec<u8> {
+        [self.prefix.as_slice(), request_id.as_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::testing_env;
+
+    #[test]
+    fn replay_within_ttl() {
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        let guard = Idempotency::new(b"i".to_vec(), 50);
+        assert_eq!(guard.cached_result("a"), None);
+        guard.record("a", b"1");
+        assert_eq!(guard.cached_result("a"), Some(b"1".to_vec()));
+        assert_eq!(guard.cached_result("b"), None);
+
+        testing_env!(VMContextBuilder::new().block_timestamp(149).build());
+        assert_eq!(guard.cached_result("a"), Some(b"1".to_vec()));
+
+        testing_env!(VMContextBuilder::new().block_timestamp(150).build());
+        assert_eq!(guard.cached_result("a"), None);
+        guard.record("a", b"2");
+        assert_eq!(guard.cached_result("a"), Some(b"2".to_vec()));
+    }
+
+    #[test]
+    fn injected_clock() {
+        testing_env!(VMContextBuilder::new().build());
+        let now = std::cell::Cell::new(100);
+        let guard = Idempotency::new(b"i".to_vec(), 50).with_clock(|| now.get());
+        guard.record("a", b"1");
+        now.set(149);
+        assert_eq!(guard.cached_result("a"), Some(b"1".to_vec()));
+        now.set(150);
+        assert_eq!(guard.cached_result("a"), None);
+    }
+
+    #[test]
+    fn set_block_timestamp() {
+        testing_env!(VMContextBuilder::new().block_timestamp(100).build());
+        let guard = Idempotency::new(b"i".to_vec(), 50);
+        guard.record("a", b"1");
+        crate::test_utils::set_block_timestamp(150);
+        assert_eq!(guard.cached_result("a"), None);
+        assert_eq!(env::block_timestamp(), 150);
+    }
+
+    #[test]
+    fn forget() {
+        testing_env!(VMContextBuilder::new().build());
+        let guard = Idempotency::default();
+        assert!(!guard.forget("a"));
+        guard.record("a", &[]);
+        assert_eq!(guard.cached_result("a"), Some(vec![]));
+        assert!(guard.forget("a"));
+        assert_eq!(guard.cached_result("a"), None);
+    }
+
+    #[test]
+    fn callers_are_separate() {
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        let guard = Idempotency::default();
+        guard.record(&Idempotency::caller_request_id("pay", "1"), b"1");
+        assert_eq!(
+            guard.cached_result(&Idempotency::caller_request_id("pay", "1")),
+            Some(b"1".to_vec())
+        );
+
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(1)).build());
+        assert_eq!(guard.cached_result(&Idempotency::caller_request_id("pay", "1")), None);
+        assert_eq!(guard.cached_result(&Idempotency::caller_request_id("refund", "1")), None);
+    }
+
+    #[test]
+    fn prefixes_are_separate() {
+        testing_env!(VMContextBuilder::new().build());
+        Idempotency::new(b"x".to_vec(), DEFAULT_IDEMPOTENCY_TTL).record("a", b"1");
+        assert_eq!(
+            Idempotency::new(b"y".to_vec(), DEFAULT_IDEMPOTENCY_TTL).cached_result("a"),
+            None
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{env, BlockHeight, BlockHeightDelta, IntoStorageKey};
+use borsh::BorshDeserialize;
+
+/// Number of blocks after which a lock of [`IntentLock::default`] expires.
+pub const DEFAULT_INTENT_LOCK_TTL: BlockHeightDelta = 200;
+
+const DEFAULT_INTENT_LOCK_PREFIX: &[u8] = b"__intent_lock";
+
+/// Locks of named resources, e.g. `"withdraw:alice.near"`, held for the duration of a workflow
+/// spanning several receipts, so that interleaved workflows don't process the same user twice.
+///
+/// A lock is taken by the method starting the workflow and released by its final callback with
+/// [`IntentLock::unlock`]. If the callback never runs, e.g. because it ran out of gas, the lock
+/// expires after the configured number of blocks. Like [`Idempotency`](crate::Idempotency),
+/// locks are stored directly in contract storage under the prefix.
+///
+/// This is used by the `#[locks("withdraw:{account_id}")]` attribute of methods within
+/// `#[near_bindgen]`, which takes the default lock with the placeholders replaced by the method
+/// arguments before the method runs. Use `#[locks("withdraw:{account_id}", ttl_blocks = 50)]`
+/// to override the number of blocks after which the lock expires.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::IntentLock;
+///
+/// let locks = IntentLock::default();
+/// locks.lock("withdraw:alice.near");
+/// assert!(locks.is_locked("withdraw:alice.near"));
+/// assert!(!locks.try_lock("withdraw:alice.near"));
+/// assert!(locks.unlock("withdraw:alice.near"));
+/// assert!(locks.try_lock("withdraw:alice.near"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntentLock {
+    prefix: Vec<u8>,
+    ttl: BlockHeightDelta,
+}
+
+impl Default for IntentLock {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTENT_LOCK_PREFIX, DEFAULT_INTENT_LOCK_TTL)
+    }
+}
+
+impl IntentLock {
+    /// Creates locks stored under `prefix` which expire after `ttl` blocks.
+    pub fn new<S>(prefix: S, ttl: BlockHeightDelta) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { prefix: prefix.into_storage_key(), ttl }
+    }
+
+    /// Creates locks with the default prefix and a custom number of blocks after which they
+    /// expire.
+    pub fn with_ttl(ttl: BlockHeightDelta) -> Self {
+        Self::new(DEFAULT_INTENT_LOCK_PREFIX, ttl)
+    }
+
+    /// Number of blocks after which a lock expires.
+    pub fn ttl(&self) -> BlockHeightDelta {
+        self.ttl
+    }
+
+    /// Locks the resource, panicking if it is already locked.
+    pub fn lock(&self, resource: &str) {
+        if let Some(expires_at) = self.locked_until(resource) {
+            env::panic_str(&format!("Resource {} is locked until block {}", resource, expires_at))
+        }
+        self.write(resource);
+    }
+
+    /// Locks the resource if it isn't locked, returning `false` if it is.
+    pub fn try_lock(&self, resource: &str) -> bool {
+        if self.is_locked(resource) {
+            return false;
+        }
+        self.write(resource);
+        true
+    }
+
+    /// Releases the lock of the resource, returning `true` if it was stored, expired or not.
+    pub fn unlock(&self, resource: &str) -> bool {
+        env::storage_remove(&self.storage_key(resource))
+    }
+
+    /// Returns `true` if the resource is locked and the lock hasn't expired.
+    pub fn is_locked(&self, resource: &str) -> bool {
+        self.locked_until(resource).is_some()
+    }
+
+    /// Returns the block height at which the lock of the resource expires, or `None` if it
+    /// isn't locked.
+    pub fn locked_until(&self, resource: &str) -> Option<BlockHeight> {
+        let expires_at = env::storage_read(&self.storage_key(resource)).map(|bytes| {
+            BlockHeight::try_from_slice(&bytes)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize intent lock"))
+        })?;
+        if env::block_height() < expires_at {
+            Some(expires_at)
+        } else {
+            None
+        }
+    }
+
+    fn write(&self, resource: &str) {
+        let expires_at = env::block_height().saturating_add(self.ttl);
+        env::storage_write(&self.storage_key(resource), &expires_at.to_le_bytes());
+    }
+
+    fn storage_key(&self, resource: &str) -> Vec<u8> {
+        [self.prefix.as_slice(), resource.as_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[test]
+    fn lock_expires() {
+        testing_env!(VMContextBuilder::new().block_index(100).build());
+        let locks = IntentLock::new(b"l".to_vec(), 10);
+        assert!(!locks.is_locked("a"));
+        locks.lock("a");
+        assert_eq!(locks.locked_until("a"), Some(110));
+        assert!(!locks.is_locked("b"));
+
+        testing_env!(VMContextBuilder::new().block_index(109).build());
+        assert!(!locks.try_lock("a"));
+
+        testing_env!(VMContextBuilder::new().block_index(110).build());
+        assert!(!locks.is_locked("a"));
+        assert!(locks.try_lock("a"));
+        assert_eq!(locks.locked_until("a"), Some(120));
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource withdraw:bob is locked until block 200")]
+    fn lock_twice() {
+        testing_env!(VMContextBuilder::new().build());
+        let locks = IntentLock::default();
+        locks.lock("withdraw:bob");
+        locks.lock("withdraw:bob");
+    }
+
+    #[test]
+    fn unlock() {
+        testing_env!(VMContextBuilder::new().build());
+        let locks = IntentLock::default();
+        assert!(!locks.unlock("a"));
+        locks.lock("a");
+        assert!(locks.unlock("a"));
+        assert!(!locks.is_locked("a"));
+        locks.lock("a");
+    }
+}
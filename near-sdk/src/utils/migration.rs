@@ -0,0 +1,132 @@
+use crate::environment::overlay;
+use crate::json_types::U64;
+use crate::{env, StorageUsage};
+use borsh::BorshSerialize;
+use serde::{Deserialize, Serialize};
+
+const STATE_KEY: &[u8] = b"STATE";
+
+/// Result of the `<method>_dry_run` view generated for a `#[migrate]` method, describing the
+/// state the migration would write, so that operators can check a migration against the live
+/// state before deploying the new code.
+///
+/// A `#[migrate]` method is a private `#[init(ignore_state)]` method which reads the old state
+/// and returns the new one. Its dry run calls it against a scratch storage overlay, like the
+/// views of [`GasEstimate`](crate::GasEstimate), and measures the new state instead of writing
+/// it. The other entries written by the migration, e.g. elements moved between collections, are
+/// discarded as well but counted in the storage usage delta, logs are dropped and promises can't
+/// be created. The dry run rejects attached deposits and is private like the migration, so it can
+/// only be called by the contract itself. Requires the `estimate` feature.
+///
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// # use near_sdk::{env, near_bindgen, PanicOnDefault};
+/// #[derive(BorshDeserialize)]
+/// struct OldContract {
+///     owner: String,
+/// }
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+/// pub struct Contract {
+///     owner: String,
+///     fee_bps: u32,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     /// Also exported as the `migrate_dry_run` view.
+///     #[migrate]
+///     pub fn migrate(fee_bps: u32) -> Self {
+///         let old: OldContract = env::state_read().expect("Old state doesn't exist");
+///         Self { owner: old.owner, fee_bps }
+///     }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MigrationSummary {
+    /// Size of the serialized state before the migration, in bytes.
+    pub old_state_bytes: U64,
+    /// Size of the serialized state after the migration, in bytes.
+    pub new_state_bytes: U64,
+    /// Change of the storage usage of the contract caused by the new state and the other writes
+    /// of the migration, in bytes. Its storage staking cost is this times
+    /// `env::storage_byte_cost()`.
+    pub storage_usage_delta: i64,
+    /// Gas used by the migration, including the serialization of the new state and the fees of
+    /// its storage writes.
+    pub gas_used: U64,
+}
+
+impl MigrationSummary {
+    /// Runs the migration without committing its storage writes and measures the state it
+    /// returns instead of writing it.
+    pub fn measure<T: BorshSerialize>(migrate: impl FnOnce() -> T) -> Self {
+        /// Discards the overlay even if the migration panics, e.g. in unit tests.
+        struct Guard;
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                overlay::end();
+            }
+        }
+
+        let initial_gas = env::used_gas();
+        let old_state_bytes = env::storage_read(STATE_KEY).map_or(0, |state| state.len());
+        overlay::begin();
+        let _guard = Guard;
+        let new_state = migrate()
+            .try_to_vec()
+            .unwrap_or_else(|_| env::panic_str("Cannot serialize the migrated state"));
+        let gas_used = env::used_gas().0 - initial_gas.0;
+        let overlay = overlay::end().unwrap_or_else(|| env::abort());
+        let new_state_bytes = new_state.len();
+        let storage_usage_delta =
+            new_state_bytes as i64 - old_state_bytes as i64 + overlay.storage_usage_delta;
+        Self {
+            old_state_bytes: U64(old_state_bytes as StorageUsage),
+            new_state_bytes: U64(new_state_bytes as StorageUsage),
+            storage_usage_delta,
+            gas_used: U64(gas_used.saturating_add(overlay.storage_gas)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_migration() {
+        env::state_write(&(7u64, "owner".to_string()));
+        let summary = MigrationSummary::measure(|| {
+            let (value, owner): (u64, String) = env::state_read().unwrap();
+            (value, owner, 25u32)
+        });
+        assert_eq!(summary.old_state_bytes, U64(17));
+        assert_eq!(summary.new_state_bytes, U64(21));
+        assert_eq!(summary.storage_usage_delta, 4);
+        assert_eq!(env::state_read::<(u64, String)>(), Some((7, "owner".to_string())));
+    }
+
+    #[test]
+    fn other_writes_are_not_committed() {
+        env::state_write(&7u64);
+        let summary = MigrationSummary::measure(|| {
+            env::storage_write(b"moved", b"1");
+            env::state_read::<u64>().unwrap()
+        });
+        // +46 bytes of the record of "moved".
+        assert_eq!(summary.storage_usage_delta, 46);
+        // The base fee of the write kept in the overlay is charged.
+        assert!(summary.gas_used.0 >= 64_196_736_000);
+        assert!(!env::storage_has_key(b"moved"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Promises can't be created while estimating a method")]
+    fn promises_are_denied() {
+        MigrationSummary::measure(|| {
+            crate::Promise::new("alice.near".parse().unwrap()).transfer(1);
+        });
+    }
+}
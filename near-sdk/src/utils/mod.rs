@@ -9,7 +9,66 @@ mod cache_entry;
 #[cfg(feature = "unstable")]
 pub(crate) use cache_entry::{CacheEntry, EntryState};
 
-use crate::{env, AccountId, PromiseResult};
+mod clock;
+pub use self::clock::{BlockClock, Clock};
+
+mod batch_processor;
+pub use self::batch_processor::BatchProcessor;
+
+mod idempotency;
+pub use self::idempotency::{Idempotency, DEFAULT_IDEMPOTENCY_TTL};
+
+mod intent_lock;
+pub use self::intent_lock::{IntentLock, DEFAULT_INTENT_LOCK_TTL};
+
+mod approval;
+pub use self::approval::{ApprovalContext, ApprovalNonces, APPROVAL_DOMAIN};
+
+mod payout;
+pub use self::payout::{payout_many, PendingPayouts};
+
+#[cfg(feature = "estimate")]
+mod migration;
+#[cfg(feature = "estimate")]
+pub use self::migration::MigrationSummary;
+
+mod versioned;
+pub use self::versioned::{FromPreviousVersion, Migrate, Versioned};
+
+#[cfg(feature = "estimate")]
+mod gas_estimate;
+#[cfg(feature = "estimate")]
+pub use self::gas_estimate::GasEstimate;
+
+mod storage_tracker;
+pub use self::storage_tracker::StorageTracker;
+
+mod rng;
+pub use self::rng::Rng;
+
+mod trace;
+pub use self::trace::{TraceSpan, TRACE_LOG_PREFIX};
+
+mod token_gate;
+pub use self::token_gate::{check_token_gate, TokenGate, TokenRequirement};
+
+mod action_cost;
+pub use self::action_cost::{
+    estimate_action_cost, estimate_receipt_cost, ActionCost, ActionFee, ActionFees, ReceiptAction,
+    MIN_GAS_PRICE,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod view_call;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::view_call::{ViewCall, ViewCallError};
+
+use crate::collections::LookupMap;
+use crate::{
+    env, AccountId, Balance, ContractError, ContractResult, FunctionError, Promise, PromiseOrValue,
+    PromiseResult, StorageUsage, Timestamp,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
 
 /// Helper macro to log a message through [`env::log_str`].
 /// This macro can be used similar to the [`std::format`] macro in most cases.
@@ -87,7 +146,116 @@ pub fn assert_self() {
 
 /// Assert that 1 yoctoNEAR was attached.
 pub fn assert_one_yocto() {
-    require!(env::attached_deposit() == 1, "Requires attached deposit of exactly 1 yoctoNEAR")
+    if env::attached_deposit() != 1 {
+        ContractError::new(
+            ContractError::INVALID_DEPOSIT,
+            "Requires attached deposit of exactly 1 yoctoNEAR",
+        )
+        .panic()
+    }
+}
+
+/// Assert that at least 1 yoctoNEAR was attached.
+pub fn assert_at_least_one_yocto() {
+    assert_min_deposit(1)
+}
+
+/// Assert that at least `amount` yoctoNEAR was attached.
+pub fn assert_min_deposit(amount: Balance) {
+    if env::attached_deposit() < amount {
+        ContractError::insufficient_deposit(amount).panic()
+    }
+}
+
+/// Assert that the account is a key of the map of registered accounts.
+pub fn assert_account_registered<V>(accounts: &LookupMap<AccountId, V>, account_id: &AccountId)
+where
+    V: BorshSerialize + BorshDeserialize,
+{
+    if !accounts.contains_key(account_id) {
+        ContractError::account_not_registered(account_id).panic()
+    }
+}
+
+/// Assert that the memo of a transfer, if any, is at most `max_length` bytes long, so that
+/// indexers and payment processors reading memos from the events get bounded data.
+pub fn assert_memo_length(memo: Option<&str>, max_length: usize) {
+    if memo.map_or(false, |memo| memo.len() > max_length) {
+        ContractError::memo_too_long(max_length).panic()
+    }
+}
+
+/// Pause flags checked by [`assert_not_paused`], e.g. the `Pause` component of
+/// `near-contract-standards`.
+pub trait PauseFlags {
+    /// Returns `true` if the whole contract is paused.
+    fn is_contract_paused(&self) -> bool;
+
+    /// Returns `true` if the feature is paused individually.
+    fn is_feature_paused(&self, feature: &str) -> bool;
+}
+
+/// Assert that neither the whole contract nor the feature is paused.
+pub fn assert_not_paused<P>(pause: &P, feature: &str)
+where
+    P: PauseFlags + ?Sized,
+{
+    if pause.is_contract_paused() {
+        ContractError::paused(None).panic()
+    }
+    if pause.is_feature_paused(feature) {
+        ContractError::paused(Some(feature)).panic()
+    }
+}
+
+/// Refunds the storage staking cost of `freed_bytes` to `account_id`, for example bytes freed by
+/// the `remove_measured` methods of [`collections`](crate::collections) when an account deletes
+/// its data. Returns `None` if there is nothing to refund.
+pub fn refund_freed_storage(account_id: AccountId, freed_bytes: StorageUsage) -> Option<Promise> {
+    let refund = env::storage_byte_cost() * Balance::from(freed_bytes);
+    if refund == 0 {
+        None
+    } else {
+        Some(Promise::new(account_id).transfer(refund))
+    }
+}
+
+/// Probes whether `account_id` exists, e.g. before refunding tokens to an account which may have
+/// been deleted in the meantime.
+///
+/// Implicit accounts and the current account always exist for this purpose, so `true` is
+/// returned right away. Otherwise the returned promise transfers 0 yoctoNEAR to the account,
+/// which fails if the account doesn't exist. Chain a callback to it and read the answer there
+/// with [`is_promise_success`].
+pub fn account_exists(account_id: AccountId) -> PromiseOrValue<bool> {
+    if account_id.is_implicit() || account_id == env::current_account_id() {
+        PromiseOrValue::Value(true)
+    } else {
+        PromiseOrValue::Promise(Promise::new(account_id).transfer(0))
+    }
+}
+
+/// Check of the `#[sunset(after = "2023-01-01", message = "...")]` attribute of a method, which
+/// logs a deprecation notice while the block timestamp is before `after` (in nanoseconds), and
+/// panics with `message` once it's later.
+///
+/// The notice is logged as `DEPRECATED:{"method":"...","sunset":"...","message":"..."}` so that
+/// integrators can detect calls to deprecated methods from the receipt outcomes.
+pub fn check_sunset(method: &str, after: Timestamp, message: &str) {
+    if env::block_timestamp() > after {
+        let error = if message.is_empty() {
+            format!("Method {} was retired", method)
+        } else {
+            format!("Method {} was retired: {}", method, message)
+        };
+        env::panic_str(&error);
+    }
+    let notice = serde_json::json!({
+        "method": method,
+        "sunset": after.to_string(),
+        "message": message,
+    });
+    env::log_str(&format!("DEPRECATED:{}", notice));
 }
 
 /// Returns true if promise was successful.
@@ -106,6 +274,84 @@ pub fn promise_result_as_success() -> Option<Vec<u8>> {
     }
 }
 
+/// Returns the JSON result of the promise with index `result_index` as a [`ContractResult`],
+/// for callbacks of multi-leg workflows.
+///
+/// The result of a method returning a `ContractResult` is returned as is. Other results are
+/// returned as `Ok`, and promises which failed or whose result can't be deserialized as errors
+/// with the codes [`ContractError::PROMISE_FAILED`] and [`ContractError::INVALID_RESULT`].
+pub fn promise_result_as_contract_result<T>(result_index: u64) -> ContractResult<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let data = match env::promise_result(result_index) {
+        PromiseResult::Successful(data) if data.is_empty() => b"null".to_vec(),
+        PromiseResult::Successful(data) => data,
+        _ => {
+            return Err(ContractError::new(
+                ContractError::PROMISE_FAILED,
+                format!("Promise {} failed", result_index),
+            ))
+        }
+    };
+    serde_json::from_slice::<ContractResult<T>>(&data)
+        .or_else(|_| serde_json::from_slice::<T>(&data).map(Ok))
+        .unwrap_or_else(|_| {
+            Err(ContractError::new(
+                ContractError::INVALID_RESULT,
+                format!("Cannot deserialize the result of promise {}", result_index),
+            ))
+        })
+}
+
+/// Unwraps the `Ok` value of a result in a method returning a `Result`, or runs compensating
+/// actions and returns the error, converted with `From`, like the `?` operator. Used in the
+/// callbacks of multi-leg workflows, e.g. to refund the deposit of the caller when a leg failed.
+///
+/// The compensating actions are an expression, or a closure-like `|err| expression` which has
+/// access to the error.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::json_types::U128;
+/// use near_sdk::{
+///     chain_or_rollback, promise_result_as_contract_result, AccountId, ContractResult, Promise,
+/// };
+///
+/// fn on_swap(sender_id: AccountId, deposit: U128) -> ContractResult<U128> {
+///     let amount_out: U128 = chain_or_rollback!(promise_result_as_contract_result(0), {
+///         Promise::new(sender_id.clone()).transfer(deposit.0);
+///     });
+///     let amount_out: U128 = chain_or_rollback!(promise_result_as_contract_result(1), |err| {
+///         near_sdk::log!("Refunding {} after {}", sender_id, err);
+///         Promise::new(sender_id).transfer(deposit.0);
+///     });
+///     Ok(amount_out)
+/// }
+/// ```
+#[macro_export]
+macro_rules! chain_or_rollback {
+    ($result:expr, |$err:ident| $rollback:expr $(,)?) => {
+        match $result {
+            Ok(value) => value,
+            Err($err) => {
+                $rollback;
+                return Err(::core::convert::From::from($err));
+            }
+        }
+    };
+    ($result:expr, $rollback:expr $(,)?) => {
+        match $result {
+            Ok(value) => value,
+            Err(err) => {
+                $rollback;
+                return Err(::core::convert::From::from(err));
+            }
+        }
+    };
+}
+
 /// Used in the simulation code generator from near_sdk.
 #[derive(Debug)]
 pub struct PendingContractTx {
@@ -140,6 +386,39 @@ impl PendingContractTx {
     }
 }
 
+/// Arguments of a contract method. `#[near_bindgen]` implements it for the `<Method>Args` struct
+/// it generates for each exported method marked with `#[method_args]`, so that other contracts
+/// and tests can build the arguments of calls with the types of the contract instead of JSON
+/// literals. The arguments must be owned types, and the method can't be generic.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{near_bindgen, AccountId, MethodArgs};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Greeter {}
+///
+/// #[near_bindgen]
+/// impl Greeter {
+///     #[method_args]
+///     pub fn greet(&mut self, account_id: AccountId, times: u32) {}
+/// }
+///
+/// let args = GreetArgs { account_id: "alice.near".parse().unwrap(), times: 2 };
+/// assert_eq!(GreetArgs::METHOD_NAME, "greet");
+/// assert_eq!(args.to_args(), br#"{"account_id":"alice.near","times":2}"#.to_vec());
+/// ```
+pub trait MethodArgs {
+    /// Name of the method the arguments are for.
+    const METHOD_NAME: &'static str;
+
+    /// Serializes the arguments with the serializer of the method, as expected in `env::input()`.
+    fn to_args(&self) -> Vec<u8>;
+}
+
 /// Deprecated helper function which used to generate code to initialize the [`GlobalAllocator`].
 /// This is now initialized by default. Disable `wee_alloc` feature to configure manually.
 ///
@@ -172,4 +451,104 @@ mod tests {
 
         assert_eq!(get_logs(), vec!["hello user_name (25)".to_string()]);
     }
+
+    #[test]
+    #[should_panic(
+        expected = r#"ERROR_JSON:{"code":"MEMO_TOO_LONG","message":"The memo can't be longer than 4 bytes"}"#
+    )]
+    fn test_memo_length() {
+        super::assert_memo_length(None, 4);
+        super::assert_memo_length(Some("memo"), 4);
+        super::assert_memo_length(Some("memos"), 4);
+    }
+
+    #[test]
+    fn test_account_exists() {
+        use crate::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+        use crate::{testing_env, AccountId, PromiseOrValue};
+
+        testing_env!(VMContextBuilder::new().current_account_id(accounts(0)).build());
+
+        let implicit = AccountId::new_unchecked("a".repeat(64));
+        assert!(matches!(super::account_exists(implicit), PromiseOrValue::Value(true)));
+        assert!(matches!(super::account_exists(accounts(0)), PromiseOrValue::Value(true)));
+
+        assert!(matches!(super::account_exists(accounts(1)), PromiseOrValue::Promise(_)));
+        assert_eq!(get_created_receipts()[0].receiver_id, accounts(1));
+    }
+
+    #[test]
+    fn test_contract_results() {
+        use crate::test_utils::VMContextBuilder;
+        use crate::{testing_env, ContractError, ContractResult, PromiseResult};
+
+        testing_env!(
+            VMContextBuilder::new().build(),
+            crate::VMConfig::test(),
+            crate::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                PromiseResult::Successful(br#"{"Ok":"5"}"#.to_vec()),
+                PromiseResult::Successful(br#"{"Err":{"code":"EMPTY","message":"m"}}"#.to_vec()),
+                PromiseResult::Failed,
+                PromiseResult::Successful(br#""7""#.to_vec()),
+                PromiseResult::Successful(b"[]".to_vec()),
+            ],
+        );
+        let result = |index| super::promise_result_as_contract_result::<String>(index);
+        assert_eq!(result(0), Ok("5".to_string()));
+        assert_eq!(result(1), Err(ContractError::new("EMPTY", "m")));
+        assert_eq!(result(2).unwrap_err().code, ContractError::PROMISE_FAILED);
+        assert_eq!(result(3), Ok("7".to_string()));
+        assert_eq!(result(4).unwrap_err().code, ContractError::INVALID_RESULT);
+
+        fn workflow(index: u64, refunds: &mut Vec<String>) -> ContractResult<String> {
+            let value = chain_or_rollback!(
+                super::promise_result_as_contract_result::<String>(index),
+                |err| refunds.push(err.code.clone())
+            );
+            Ok(value)
+        }
+        let mut refunds = vec![];
+        assert_eq!(workflow(0, &mut refunds), Ok("5".to_string()));
+        assert!(workflow(2, &mut refunds).is_err());
+        assert_eq!(refunds, vec![ContractError::PROMISE_FAILED.to_string()]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = r#"ERROR_JSON:{"code":"ACCOUNT_NOT_REGISTERED","message":"The account bob is not registered"}"#
+    )]
+    fn test_assert_account_registered() {
+        use crate::collections::LookupMap;
+        use crate::test_utils::{accounts, VMContextBuilder};
+        use crate::testing_env;
+
+        testing_env!(VMContextBuilder::new().attached_deposit(10).build());
+        super::assert_at_least_one_yocto();
+        super::assert_min_deposit(10);
+        let mut accounts_map = LookupMap::new(b"a");
+        accounts_map.insert(&accounts(0), &0u128);
+        super::assert_account_registered(&accounts_map, &accounts(0));
+        super::assert_account_registered(&accounts_map, &accounts(1));
+    }
+
+    #[test]
+    fn test_check_sunset() {
+        use crate::test_utils::VMContextBuilder;
+        use crate::testing_env;
+
+        testing_env!(VMContextBuilder::new().block_timestamp(10).build());
+        super::check_sunset("ft_register", 10, "Use storage_deposit");
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"DEPRECATED:{"message":"Use storage_deposit","method":"ft_register","sunset":"10"}"#
+            ]
+        );
+
+        testing_env!(VMContextBuilder::new().block_timestamp(11).build());
+        let result = std::panic::catch_unwind(|| super::check_sunset("ft_register", 10, ""));
+        assert!(result.is_err());
+    }
 }
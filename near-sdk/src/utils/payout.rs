@@ -0,0 +1,162 @@
+use crate::collections::LookupMap;
+use crate::{env, require, AccountId, Balance, IntoStorageKey, Promise, PromiseResult};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Transfers NEAR to several receivers with independent transfer promises, e.g. for royalty and
+/// dividend distributions. Returns the joint promise of the transfers to chain a callback to, or
+/// `None` if there are no payouts.
+///
+/// A transfer which fails, for example because the receiver account was deleted, doesn't affect
+/// the other transfers, but its amount is refunded to the contract. The callback should pass the
+/// same payouts to [`PendingPayouts::resolve`], which records the failed ones so that the
+/// receivers can claim them later instead of the funds getting lost in the contract balance.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::json_types::U128;
+/// use near_sdk::{
+///     env, near_bindgen, payout_many, AccountId, Gas, PanicOnDefault, PendingPayouts, Promise,
+/// };
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+/// pub struct Dividends {
+///     pending_payouts: PendingPayouts,
+/// }
+///
+/// #[near_bindgen]
+/// impl Dividends {
+///     pub fn distribute(&mut self, payouts: Vec<(AccountId, U128)>) -> Option<Promise> {
+///         let amounts =
+///             payouts.iter().map(|(account_id, amount)| (account_id.clone(), amount.0)).collect();
+///         payout_many(amounts).map(|transfers| {
+///             transfers.then(Promise::new(env::current_account_id()).function_call(
+///                 "on_payouts".to_string(),
+///                 near_sdk::serde_json::json!({ "payouts": payouts }).to_string().into_bytes(),
+///                 0,
+///                 Gas(5_000_000_000_000),
+///             ))
+///         })
+///     }
+///
+///     #[private]
+///     pub fn on_payouts(&mut self, payouts: Vec<(AccountId, U128)>) -> U128 {
+///         let payouts: Vec<_> =
+///             payouts.into_iter().map(|(account_id, amount)| (account_id, amount.0)).collect();
+///         U128(self.pending_payouts.resolve(&payouts))
+///     }
+/// }
+/// ```
+pub fn payout_many(payouts: Vec<(AccountId, Balance)>) -> Option<Promise> {
+    payouts
+        .into_iter()
+        .map(|(account_id, amount)| Promise::new(account_id).transfer(amount))
+        .reduce(|payouts, payout| payouts.and(payout))
+}
+
+/// Payouts of [`payout_many`] which failed, by receiver, which the receivers can claim later.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PendingPayouts {
+    /// AccountID -> Amount which failed to be paid out.
+    pub amounts: LookupMap<AccountId, Balance>,
+}
+
+impl PendingPayouts {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { amounts: LookupMap::new(prefix) }
+    }
+
+    /// Amount the account can claim.
+    pub fn pending(&self, account_id: &AccountId) -> Balance {
+        self.amounts.get(account_id).unwrap_or(0)
+    }
+
+    /// Adds `amount` to the pending payout of the account.
+    pub fn record(&mut self, account_id: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        let pending = self
+            .pending(account_id)
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Pending payout overflow"));
+        self.amounts.insert(account_id, &pending);
+    }
+
+    /// Records the payouts whose transfers failed. Has to be called in the callback of the
+    /// promise returned by [`payout_many`] with the same payouts, in the same order. Returns the
+    /// total amount which was recorded.
+    pub fn resolve(&mut self, payouts: &[(AccountId, Balance)]) -> Balance {
+        require!(
+            env::promise_results_count() == payouts.len() as u64,
+            "Expected a promise result for each payout"
+        );
+        let mut failed_amount: Balance = 0;
+        for (index, (account_id, amount)) in payouts.iter().enumerate() {
+            if !matches!(env::promise_result(index as u64), PromiseResult::Successful(_)) {
+                self.record(account_id, *amount);
+                failed_amount = failed_amount.saturating_add(*amount);
+            }
+        }
+        failed_amount
+    }
+
+    /// Removes the pending payout of the account and transfers it. Panics if there is nothing to
+    /// claim.
+    pub fn claim(&mut self, account_id: AccountId) -> Promise {
+        let amount = self.amounts.remove(&account_id).unwrap_or(0);
+        require!(amount > 0, "No pending payout");
+        Promise::new(account_id).transfer(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use crate::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    #[test]
+    fn payouts_are_independent_transfers() {
+        testing_env!(VMContextBuilder::new().build());
+        assert!(payout_many(vec![]).is_none());
+        payout_many(vec![(accounts(1), 10), (accounts(2), 20)]).unwrap();
+        let receivers: Vec<_> =
+            get_created_receipts().into_iter().map(|receipt| receipt.receiver_id).collect();
+        assert_eq!(receivers, vec![accounts(1), accounts(2)]);
+    }
+
+    #[test]
+    fn failed_payouts_are_claimable() {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed, PromiseResult::Successful(vec![]), PromiseResult::Failed],
+        );
+        let mut pending_payouts = PendingPayouts::new(b"p".to_vec());
+        pending_payouts.record(&accounts(1), 5);
+        let payouts = vec![(accounts(1), 10), (accounts(2), 20), (accounts(3), 30)];
+        assert_eq!(pending_payouts.resolve(&payouts), 40);
+        assert_eq!(pending_payouts.pending(&accounts(1)), 15);
+        assert_eq!(pending_payouts.pending(&accounts(2)), 0);
+        assert_eq!(pending_payouts.pending(&accounts(3)), 30);
+
+        pending_payouts.claim(accounts(1));
+        assert_eq!(pending_payouts.pending(&accounts(1)), 0);
+        assert_eq!(get_created_receipts().last().unwrap().receiver_id, accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "No pending payout")]
+    fn claim_nothing() {
+        testing_env!(VMContextBuilder::new().build());
+        PendingPayouts::new(b"p".to_vec()).claim(accounts(1));
+    }
+}
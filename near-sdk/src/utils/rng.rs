@@ -0,0 +1,156 @@
+use crate::{env, require};
+use std::cell::Cell;
+
+thread_local! {
+    /// Number of generators created in the current call, so that each gets its own stream.
+    static GENERATORS: Cell<u64> = Cell::new(0);
+}
+
+/// Deterministic pseudo-random generator seeded from [`env::random_seed`], for lotteries, raffles
+/// and random traits of minted tokens.
+///
+/// `env::random_seed()` is the same for every call made in the same receipt, so contracts which
+/// draw from it directly get the same value twice. [`Rng::new`] mixes the seed with the
+/// predecessor, the number of generators created before it in the call and a `nonce` of the
+/// contract, e.g. a persistent counter or the id of the minted token, and hashes them with
+/// SHA-256. The values drawn from one generator are its hash chained with a counter.
+///
+/// [`gen_range`](Self::gen_range) rejects the values which would bias the modulo, and
+/// [`shuffle`](Self::shuffle) is a Fisher-Yates shuffle on top of it.
+///
+/// Security model: the seed is produced by the validators, so a block producer can predict it
+/// and, by skipping its block, choose between two seeds. Everyone can compute the values once the
+/// receipt runs, and a contract calling this one can read them in a callback and revert a losing
+/// draw. Don't use it where a validator or a contract caller gains more than the cost of such an
+/// attack, and prefer [commit-reveal](https://en.wikipedia.org/wiki/Commitment_scheme) schemes
+/// or delayed draws for high stakes.
+///
+/// ```
+/// use near_sdk::Rng;
+///
+/// let mut rng = Rng::new(b"raffle");
+/// let winner = rng.gen_range(10);
+/// assert!(winner < 10);
+///
+/// let mut cards: Vec<u8> = (0..52).collect();
+/// rng.shuffle(&mut cards);
+/// ```
+pub struct Rng {
+    seed: [u8; 32],
+    block: [u8; 32],
+    /// Number of blocks hashed so far.
+    counter: u64,
+    /// Offset of the next unread byte of `block`.
+    offset: usize,
+}
+
+impl Rng {
+    /// Creates a generator seeded from the random seed, the predecessor, the number of generators
+    /// created before in the call and `nonce`.
+    pub fn new(nonce: &[u8]) -> Self {
+        let index = GENERATORS.with(|generators| generators.replace(generators.get() + 1));
+        let predecessor_account_id = env::predecessor_account_id();
+        let mut input = env::random_seed();
+        input.extend_from_slice(&(predecessor_account_id.as_bytes().len() as u32).to_le_bytes());
+        input.extend_from_slice(predecessor_account_id.as_bytes());
+        input.extend_from_slice(&index.to_le_bytes());
+        input.extend_from_slice(nonce);
+        Self::from_seed(env::sha256_array(&input))
+    }
+
+    /// Creates a generator drawing from `seed` only, e.g. to replay draws in tests.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self { seed, block: [0; 32], counter: 0, offset: 32 }
+    }
+
+    /// Returns the next 8 bytes of the stream as a number.
+    pub fn next_u64(&mut self) -> u64 {
+        if self.offset == self.block.len() {
+            let mut input = self.seed.to_vec();
+            input.extend_from_slice(&self.counter.to_le_bytes());
+            self.block = env::sha256_array(&input);
+            self.counter += 1;
+            self.offset = 0;
+        }
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.block[self.offset..self.offset + 8]);
+        self.offset += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Returns a number in `0..upper`, each with the same probability. Panics if `upper` is 0.
+    pub fn gen_range(&mut self, upper: u64) -> u64 {
+        require!(upper > 0, "The upper bound must be positive");
+        // 2^64 mod upper: the lowest values are rejected, so that the rest are a multiple of
+        // `upper` and each remainder is reached as often.
+        let rejected = upper.wrapping_neg() % upper;
+        loop {
+            let value = self.next_u64();
+            if value >= rejected {
+                return value % upper;
+            }
+        }
+    }
+
+    /// Shuffles the items in place, each order with the same probability.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            let other = self.gen_range(index as u64 + 1) as usize;
+            items.swap(index, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::testing_env;
+
+    #[test]
+    fn same_seed_same_values() {
+        let mut first = Rng::from_seed([1; 32]);
+        let mut second = Rng::from_seed([1; 32]);
+        let values: Vec<u64> = (0..10).map(|_| first.next_u64()).collect();
+        assert_eq!(values, (0..10).map(|_| second.next_u64()).collect::<Vec<_>>());
+        assert_ne!(values[0], values[4]);
+    }
+
+    #[test]
+    fn generators_of_a_call_differ() {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .random_seed([7; 32])
+            .build());
+        let first = Rng::new(b"nonce").next_u64();
+        assert_ne!(Rng::new(b"nonce").next_u64(), first);
+    }
+
+    #[test]
+    fn gen_range_bounds() {
+        let mut rng = Rng::from_seed([2; 32]);
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            seen[rng.gen_range(6) as usize] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+        assert_eq!(rng.gen_range(1), 0);
+        assert!(rng.gen_range(u64::MAX) < u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "The upper bound must be positive")]
+    fn gen_range_zero() {
+        Rng::from_seed([3; 32]).gen_range(0);
+    }
+
+    #[test]
+    fn shuffle_is_permutation() {
+        let mut rng = Rng::from_seed([4; 32]);
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -22,7 +22,9 @@ impl<'a> IntoStorageKey for &'a [u8] {
     }
 }
 
-impl<'a> IntoStorageKey for &'a [u8; 1] {
+/// Byte string literals of any length, e.g. `b"ft"` for the prefix of a component, so that
+/// components embedded together can be given distinct multi-byte prefixes without `to_vec()`.
+impl<'a, const N: usize> IntoStorageKey for &'a [u8; N] {
     #[inline]
     fn into_storage_key(self) -> Vec<u8> {
         self.to_vec()
@@ -64,3 +66,15 @@ where
         self.try_to_vec().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_string_literals() {
+        assert_eq!(b"a".into_storage_key(), vec![b'a']);
+        assert_eq!(b"ft".into_storage_key(), vec![b'f', b't']);
+        assert_eq!(b"".into_storage_key(), Vec::<u8>::new());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{env, AccountId, Balance, ContractError, FunctionError, Promise, StorageUsage};
+
+/// Measures the storage used by a call, to charge its storage staking cost to the caller.
+///
+/// The tracker snapshots `env::storage_usage()` when it is started. [`finish`](Self::finish)
+/// computes the cost of the bytes added since then, panics with an `INSUFFICIENT_DEPOSIT` error
+/// if the attached deposit doesn't cover it, and refunds the excess. If the call freed storage,
+/// the staking cost of the freed bytes stays with the contract, since the caller isn't
+/// necessarily the account which paid for them. Calls which free storage paid by a known
+/// account, e.g. the owner of a removed entry, release it to that account with
+/// [`finish_with_release`](Self::finish_with_release).
+///
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// # use near_sdk::collections::LookupMap;
+/// # use near_sdk::{near_bindgen, AccountId, StorageTracker};
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {
+///     notes: LookupMap<AccountId, String>,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     #[payable]
+///     pub fn set_note(&mut self, text: String) {
+///         let tracker = StorageTracker::start();
+///         self.notes.insert(&near_sdk::env::predecessor_account_id(), &text);
+///         tracker.finish();
+///     }
+/// }
+/// ```
+#[must_use = "the storage cost is only charged when the tracker is finished"]
+pub struct StorageTracker {
+    initial_storage_usage: StorageUsage,
+}
+
+impl StorageTracker {
+    /// Starts measuring from the current storage usage.
+    pub fn start() -> Self {
+        Self { initial_storage_usage: env::storage_usage() }
+    }
+
+    /// Change of the storage usage since the tracker was started, in bytes.
+    pub fn bytes_delta(&self) -> i64 {
+        env::storage_usage() as i64 - self.initial_storage_usage as i64
+    }
+
+    /// Storage staking cost of the bytes added since the tracker was started, zero if storage
+    /// was freed.
+    pub fn storage_cost(&self) -> Balance {
+        env::storage_byte_cost() * Balance::from(self.bytes_delta().max(0) as u64)
+    }
+
+    /// Charges the storage cost to the predecessor, see
+    /// [`finish_for`](Self::finish_for).
+    pub fn finish(self) -> Option<Promise> {
+        self.finish_for(env::predecessor_account_id())
+    }
+
+    /// Requires the attached deposit to cover the storage cost and refunds the rest of the
+    /// deposit to `account_id`. The staking cost of freed bytes isn't refunded. Returns `None` if
+    /// there is nothing to refund.
+    pub fn finish_for(self, account_id: AccountId) -> Option<Promise> {
+        self.finish_with_refund(account_id, 0)
+    }
+
+    /// Same as [`finish_for`](Self::finish_for), also refunding the staking cost of the freed
+    /// bytes if storage was freed. `payer` must be the account which paid for the storage the
+    /// call freed, otherwise the contract pays out storage it was never paid for.
+    pub fn finish_with_release(self, payer: AccountId) -> Option<Promise> {
+        let freed_bytes = (-self.bytes_delta()).max(0) as u64;
+        let released = env::storage_byte_cost() * Balance::from(freed_bytes);
+        self.finish_with_refund(payer, released)
+    }
+
+    fn finish_with_refund(self, account_id: AccountId, released: Balance) -> Option<Promise> {
+        let attached_deposit = env::attached_deposit();
+        let required_cost = self.storage_cost();
+        if attached_deposit < required_cost {
+            ContractError::insufficient_deposit(required_cost).panic()
+        }
+        let refund = attached_deposit - required_cost + released;
+        if refund == 0 {
+            None
+        } else {
+            Some(Promise::new(account_id).transfer(refund))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::VmAction;
+    use crate::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use crate::testing_env;
+
+    fn set_deposit(attached_deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(attached_deposit)
+            .build());
+    }
+
+    fn refunds() -> Vec<(AccountId, Vec<VmAction>)> {
+        get_created_receipts()
+            .into_iter()
+            .map(|receipt| (receipt.receiver_id, receipt.actions))
+            .collect()
+    }
+
+    #[test]
+    fn refunds_excess_deposit() {
+        set_deposit(env::storage_byte_cost() * 100);
+        let tracker = StorageTracker::start();
+        env::storage_write(b"key", b"value");
+        let bytes = tracker.bytes_delta();
+        assert!(bytes > 0);
+        assert!(tracker.finish().is_some());
+        let refund = env::storage_byte_cost() * (100 - bytes as u128);
+        assert_eq!(refunds(), vec![(accounts(1), vec![VmAction::Transfer { deposit: refund }])]);
+    }
+
+    #[test]
+    fn nothing_to_refund() {
+        set_deposit(0);
+        let tracker = StorageTracker::start();
+        assert_eq!(tracker.bytes_delta(), 0);
+        assert!(tracker.finish().is_none());
+        assert!(refunds().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "INSUFFICIENT_DEPOSIT")]
+    fn insufficient_deposit() {
+        set_deposit(1);
+        let tracker = StorageTracker::start();
+        env::storage_write(b"key", b"value");
+        let _ = tracker.finish();
+    }
+
+    #[test]
+    fn keeps_freed_storage() {
+        set_deposit(1);
+        env::storage_write(b"key", b"value");
+        let tracker = StorageTracker::start();
+        env::storage_remove(b"key");
+        assert!(tracker.bytes_delta() < 0);
+        let _ = tracker.finish_for(accounts(2));
+        assert_eq!(refunds(), vec![(accounts(2), vec![VmAction::Transfer { deposit: 1 }])]);
+    }
+
+    #[test]
+    fn releases_freed_storage() {
+        set_deposit(1);
+        env::storage_write(b"key", b"value");
+        let tracker = StorageTracker::start();
+        env::storage_remove(b"key");
+        let freed_bytes = -tracker.bytes_delta();
+        assert_eq!(tracker.storage_cost(), 0);
+        let _ = tracker.finish_with_release(accounts(2));
+        let refund = 1 + env::storage_byte_cost() * freed_bytes as u128;
+        assert_eq!(refunds(), vec![(accounts(2), vec![VmAction::Transfer { deposit: refund }])]);
+    }
+}
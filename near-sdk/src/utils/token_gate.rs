@@ -0,0 +1,46 @@
+use crate::{env, AccountId, Balance};
+
+/// Tokens the caller of a method guarded with `#[requires_token]` must hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenRequirement {
+    /// A balance of at least this amount, or this number of tokens of an NFT contract.
+    MinBalance(Balance),
+    /// The token with this id.
+    Token(String),
+}
+
+/// Holdings of tokens checked by the `#[requires_token(contract = "field")]` attribute of
+/// methods within `#[near_bindgen]`, where `field` is a field of the contract implementing this
+/// trait.
+///
+/// The trait is implemented for the embedded fungible and non-fungible tokens of
+/// near-contract-standards, which check the state of the contract, and for its cached gate,
+/// which checks the holdings of a token of another contract, as last queried.
+///
+/// ```ignore
+/// #[requires_token(contract = "membership", min_balance = "100")]
+/// pub fn post(&mut self, text: String) { .. }
+///
+/// #[requires_token(contract = "passes", token_id = "pass_id")]
+/// pub fn enter(&mut self, pass_id: TokenId) { .. }
+/// ```
+pub trait TokenGate {
+    /// Returns `true` if the account holds the tokens. May panic if the holdings are unknown.
+    fn holds(&self, account_id: &AccountId, requirement: &TokenRequirement) -> bool;
+}
+
+/// Check of the `#[requires_token]` attribute of a method, which panics unless the predecessor
+/// holds the tokens required by the gate.
+pub fn check_token_gate<G: TokenGate + ?Sized>(gate: &G, requirement: TokenRequirement) {
+    if !gate.holds(&env::predecessor_account_id(), &requirement) {
+        let error = match requirement {
+            TokenRequirement::MinBalance(amount) => {
+                format!("The caller must hold at least {} tokens", amount)
+            }
+            TokenRequirement::Token(token_id) => {
+                format!("The caller must hold the token {}", token_id)
+            }
+        };
+        env::panic_str(&error);
+    }
+}
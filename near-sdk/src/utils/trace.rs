@@ -0,0 +1,125 @@
+//! Spans of [`trace_span!`](crate::trace_span), logged with the `trace` feature.
+#[cfg(feature = "trace")]
+use crate::env;
+#[cfg(feature = "trace")]
+use std::cell::Cell;
+
+/// Prefix of the logs of trace spans.
+pub const TRACE_LOG_PREFIX: &str = "TRACE:";
+
+#[cfg(feature = "trace")]
+thread_local! {
+    static DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// Guard of a span created with [`trace_span!`](crate::trace_span), which logs the exit of the
+/// span when dropped. Without the `trace` feature it does nothing and has no size.
+#[must_use = "the span is exited when the guard is dropped"]
+pub struct TraceSpan {
+    #[cfg(feature = "trace")]
+    name: &'static str,
+    #[cfg(feature = "trace")]
+    gas_at_enter: u64,
+}
+
+impl TraceSpan {
+    /// Enters the span, logging `TRACE: > name` indented by the depth of the span.
+    pub fn enter(name: &'static str) -> Self {
+        #[cfg(feature = "trace")]
+        {
+            let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+            env::log_str(&format!(
+                "{}{:indent$} > {}",
+                TRACE_LOG_PREFIX,
+                "",
+                name,
+                indent = depth * 2
+            ));
+            Self { name, gas_at_enter: env::used_gas().0 }
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            let _ = name;
+            Self {}
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for TraceSpan {
+    /// Exits the span, logging `TRACE: < name gas_used=...` with the gas used inside the span.
+    fn drop(&mut self) {
+        let gas_used = env::used_gas().0.saturating_sub(self.gas_at_enter);
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get().saturating_sub(1));
+            depth.get()
+        });
+        env::log_str(&format!(
+            "{}{:indent$} < {} gas_used={}",
+            TRACE_LOG_PREFIX,
+            "",
+            self.name,
+            gas_used,
+            indent = depth * 2
+        ));
+    }
+}
+
+/// Traces the rest of the enclosing block as a span named `$name`, to see where the gas of a
+/// method goes when it composes several components, e.g. a token transfer running hooks.
+///
+/// With the `trace` feature of `near-sdk`, entering the span logs `TRACE: > name` and leaving the
+/// block logs `TRACE: < name gas_used=...`, indented by the nesting of the spans, so that the
+/// logs of a call form a call tree. The gas used by a span includes the logs of its nested
+/// spans. Without the feature, spans compile to nothing, so they can stay in release builds.
+///
+/// ```
+/// use near_sdk::trace_span;
+///
+/// fn ft_transfer() {
+///     trace_span!("ft_transfer");
+///     {
+///         trace_span!("on_transfer_hooks");
+///         // ...
+///     }
+/// }
+/// # ft_transfer();
+/// ```
+///
+/// logs, with the feature:
+///
+/// ```text
+/// TRACE: > ft_transfer
+/// TRACE:   > on_transfer_hooks
+/// TRACE:   < on_transfer_hooks gas_used=2000000
+/// TRACE: < ft_transfer gas_used=5000000
+/// ```
+#[macro_export]
+macro_rules! trace_span {
+    ($name:expr) => {
+        let _near_trace_span = $crate::TraceSpan::enter($name);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::get_logs;
+
+    #[test]
+    fn nested_spans() {
+        {
+            trace_span!("outer");
+            trace_span!("inner");
+        }
+        let logs = get_logs();
+        if cfg!(feature = "trace") {
+            assert_eq!(logs.len(), 4);
+            assert_eq!(logs[0], "TRACE: > outer");
+            assert_eq!(logs[1], "TRACE:   > inner");
+            assert!(logs[2].starts_with("TRACE:   < inner gas_used="));
+            assert!(logs[3].starts_with("TRACE: < outer gas_used="));
+        } else {
+            assert!(logs.is_empty());
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::io;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// Conversion of the previous version of a type into this one, chained by
+/// [`#[derive(Migrate)]`](derive@crate::Migrate) to upgrade old versions.
+pub trait FromPreviousVersion<P> {
+    fn from_previous_version(previous: P) -> Self;
+}
+
+/// Enum with a variant per version of a type, usually implemented with
+/// [`#[derive(Migrate)]`](derive@crate::Migrate), which persists the type so that its layout
+/// can change without bricking the state written by older versions of the contract.
+///
+/// The last variant holds the [`Current`](Self::Current) version, and older versions are upgraded
+/// into it with `From`. The enum must be serialized with the default Borsh layout, in which the
+/// index of the variant precedes its value, and existing variants must never be reordered or
+/// removed.
+pub trait Migrate: Sized + From<Self::Current> {
+    /// Latest version of the type.
+    type Current: From<Self>;
+
+    /// Index of the variant of the current version.
+    const CURRENT_VERSION: u8;
+}
+
+/// Value stored as its [`Migrate`] enum `V` and used as the current version of the type, e.g. the
+/// `FungibleToken` of a contract or its whole state.
+///
+/// Older versions are upgraded when the value is read, and the value is always written back as
+/// the current version. `Versioned` dereferences to the current version, so it can replace a
+/// field of that type with no other change to the contract. Contracts which already stored the
+/// unversioned type must migrate their state once, since it lacks the version.
+///
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{FromPreviousVersion, Migrate, Versioned};
+///
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct ConfigV1 {
+///     owner: String,
+/// }
+///
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct Config {
+///     owner: String,
+///     fee_bps: u16,
+/// }
+///
+/// impl FromPreviousVersion<ConfigV1> for Config {
+///     fn from_previous_version(previous: ConfigV1) -> Self {
+///         Self { owner: previous.owner, fee_bps: 0 }
+///     }
+/// }
+///
+/// #[derive(BorshDeserialize, BorshSerialize, Migrate)]
+/// pub enum VersionedConfig {
+///     V1(ConfigV1),
+///     Current(Config),
+/// }
+///
+/// let old = VersionedConfig::V1(ConfigV1 { owner: "alice.near".to_string() });
+/// let mut config: Versioned<VersionedConfig> =
+///     BorshDeserialize::try_from_slice(&old.try_to_vec().unwrap()).unwrap();
+/// config.fee_bps = 10;
+/// assert_eq!(config.owner, "alice.near");
+/// ```
+pub struct Versioned<V: Migrate> {
+    current: V::Current,
+    version: PhantomData<V>,
+}
+
+impl<V: Migrate> Versioned<V> {
+    pub fn new(current: V::Current) -> Self {
+        Self { current, version: PhantomData }
+    }
+
+    pub fn into_inner(self) -> V::Current {
+        self.current
+    }
+}
+
+impl<V: Migrate> Deref for Versioned<V> {
+    type Target = V::Current;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+impl<V: Migrate> DerefMut for Versioned<V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.current
+    }
+}
+
+impl<V> BorshSerialize for Versioned<V>
+where
+    V: Migrate,
+    V::Current: BorshSerialize,
+{
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        // Same bytes as the current variant of `V`, without moving the value into it.
+        V::CURRENT_VERSION.serialize(writer)?;
+        self.current.serialize(writer)
+    }
+}
+
+impl<V> BorshDeserialize for Versioned<V>
+where
+    V: Migrate + BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        V::deserialize(buf).map(|versioned| Self::new(V::Current::from(versioned)))
+    }
+}
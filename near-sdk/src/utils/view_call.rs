@@ -0,0 +1,158 @@
+use crate::utils::PendingContractTx;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Typed view call to a contract which can be sent through the NEAR JSON RPC `query` endpoint.
+///
+/// These are generated by `#[near_bindgen]` for view methods on the `<Contract>Contract` proxy
+/// struct as `<method>_view`, so that off-chain clients (indexers, bots) can be built with the
+/// argument and return types of the contract crate instead of duplicating them.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::{PendingContractTx, ViewCall};
+///
+/// let call: ViewCall<u64> = ViewCall::new(PendingContractTx::new(
+///     "counter.near".parse().unwrap(),
+///     "get_num",
+///     near_sdk::serde_json::json!({}),
+///     true,
+/// ));
+/// let request = call.rpc_request("dontcare");
+/// assert_eq!(request["params"]["method_name"], "get_num");
+///
+/// let response = near_sdk::serde_json::json!({ "result": { "result": b"42", "logs": [] } });
+/// assert_eq!(call.parse_rpc_response(&response).unwrap(), 42);
+/// ```
+pub struct ViewCall<T> {
+    /// Account, method and serialized arguments of the view call.
+    pub tx: PendingContractTx,
+    result: PhantomData<fn() -> T>,
+}
+
+impl<T> ViewCall<T> {
+    pub fn new(tx: PendingContractTx) -> Self {
+        Self { tx, result: PhantomData }
+    }
+
+    /// Parameters of the `query` RPC method with `call_function` request type on final state.
+    pub fn rpc_params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "request_type": "call_function",
+            "finality": "final",
+            "account_id": self.tx.receiver_id,
+            "method_name": self.tx.method,
+            "args_base64": base64::encode(&self.tx.args),
+        })
+    }
+
+    /// Full JSON RPC request body for the view call with the given request `id`.
+    pub fn rpc_request(&self, id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "query",
+            "params": self.rpc_params(),
+        })
+    }
+}
+
+impl<T: DeserializeOwned> ViewCall<T> {
+    /// Deserializes the raw bytes returned by the view method.
+    pub fn parse_result(&self, result: &[u8]) -> Result<T, ViewCallError> {
+        serde_json::from_slice(result).map_err(ViewCallError::Deserialize)
+    }
+
+    /// Extracts and deserializes the result from a JSON RPC `query` response.
+    pub fn parse_rpc_response(&self, response: &serde_json::Value) -> Result<T, ViewCallError> {
+        if let Some(error) = response.get("error") {
+            return Err(ViewCallError::Rpc(error.clone()));
+        }
+        let bytes: Vec<u8> = response
+            .get("result")
+            .and_then(|result| result.get("result"))
+            .cloned()
+            .ok_or(ViewCallError::MissingResult)
+            .and_then(|bytes| serde_json::from_value(bytes).map_err(ViewCallError::Deserialize))?;
+        self.parse_result(&bytes)
+    }
+}
+
+impl<T> fmt::Debug for ViewCall<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewCall").field("tx", &self.tx).finish()
+    }
+}
+
+/// Errors which can occur while interpreting the response of a [`ViewCall`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ViewCallError {
+    /// The RPC node returned an error object.
+    Rpc(serde_json::Value),
+    /// The response did not contain the result bytes.
+    MissingResult,
+    /// The result could not be deserialized into the expected type.
+    Deserialize(serde_json::Error),
+}
+
+impl fmt::Display for ViewCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(error) => write!(f, "RPC error: {}", error),
+            Self::MissingResult => write!(f, "response does not contain a result"),
+            Self::Deserialize(error) => write!(f, "failed to deserialize result: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ViewCallError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call() -> ViewCall<Vec<String>> {
+        ViewCall::new(PendingContractTx::new(
+            "token.near".parse().unwrap(),
+            "ft_holders",
+            serde_json::json!({ "limit": 2 }),
+            true,
+        ))
+    }
+
+    #[test]
+    fn rpc_params() {
+        assert_eq!(
+            call().rpc_params(),
+            serde_json::json!({
+                "request_type": "call_function",
+                "finality": "final",
+                "account_id": "token.near",
+                "method_name": "ft_holders",
+                "args_base64": base64::encode(br#"{"limit":2}"#),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_response() {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "result": { "result": br#"["alice","bob"]"#.to_vec(), "logs": [] },
+            "id": "dontcare"
+        });
+        assert_eq!(call().parse_rpc_response(&response).unwrap(), vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let rpc_error = serde_json::json!({ "error": { "code": -32000 } });
+        assert!(matches!(call().parse_rpc_response(&rpc_error), Err(ViewCallError::Rpc(_))));
+        let empty = serde_json::json!({ "result": {} });
+        assert!(matches!(call().parse_rpc_response(&empty), Err(ViewCallError::MissingResult)));
+        assert!(matches!(call().parse_result(b"42"), Err(ViewCallError::Deserialize(_))));
+    }
+}
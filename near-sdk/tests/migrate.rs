@@ -0,0 +1,63 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{FromPreviousVersion, Migrate, Versioned};
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct ConfigV1 {
+    owner: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct ConfigV2 {
+    owner: String,
+    fee_bps: u16,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+struct Config {
+    owner: String,
+    fee_bps: u16,
+    paused: bool,
+}
+
+impl FromPreviousVersion<ConfigV1> for ConfigV2 {
+    fn from_previous_version(previous: ConfigV1) -> Self {
+        Self { owner: previous.owner, fee_bps: 100 }
+    }
+}
+
+impl FromPreviousVersion<ConfigV2> for Config {
+    fn from_previous_version(previous: ConfigV2) -> Self {
+        Self { owner: previous.owner, fee_bps: previous.fee_bps, paused: false }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Migrate)]
+enum VersionedConfig {
+    V1(ConfigV1),
+    V2(ConfigV2),
+    Current(Config),
+}
+
+#[test]
+fn upgrades_through_every_version() {
+    assert_eq!(VersionedConfig::CURRENT_VERSION, 2);
+    let old = VersionedConfig::V1(ConfigV1 { owner: "alice".to_string() });
+    assert_eq!(
+        Config::from(old),
+        Config { owner: "alice".to_string(), fee_bps: 100, paused: false }
+    );
+}
+
+#[test]
+fn versioned_is_written_as_current() {
+    let old = VersionedConfig::V2(ConfigV2 { owner: "alice".to_string(), fee_bps: 5 });
+    let mut config =
+        Versioned::<VersionedConfig>::try_from_slice(&old.try_to_vec().unwrap()).unwrap();
+    config.paused = true;
+
+    let current = Config { owner: "alice".to_string(), fee_bps: 5, paused: true };
+    let bytes = config.try_to_vec().unwrap();
+    assert_eq!(bytes, VersionedConfig::from(current).try_to_vec().unwrap());
+    let config = Versioned::<VersionedConfig>::try_from_slice(&bytes).unwrap();
+    assert!(config.into_inner().paused);
+}
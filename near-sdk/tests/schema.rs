@@ -0,0 +1,45 @@
+use near_sdk::schema::NearSchema as _;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{AccountId, NearSchema};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    /// Stops all transfers.
+    Pause,
+    Transfer {
+        receiver_id: AccountId,
+        #[serde(default)]
+        amount: u128,
+    },
+    SetOwner(AccountId),
+}
+
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+struct Proposal<T> {
+    #[serde(rename = "kind")]
+    action: T,
+    votes: HashMap<String, bool>,
+}
+
+#[test]
+fn derived_schema() {
+    let schema = Proposal::<Action>::json_schema();
+    assert_eq!(schema["title"], "Proposal");
+    assert_eq!(schema["required"], json!(["kind", "votes"]));
+    assert_eq!(schema["properties"]["votes"]["additionalProperties"], json!({ "type": "boolean" }));
+
+    let action = &schema["properties"]["kind"];
+    assert_eq!(action["title"], "Action");
+    assert_eq!(
+        action["oneOf"][0],
+        json!({ "enum": ["pause"], "description": "Stops all transfers." })
+    );
+    assert_eq!(action["oneOf"][1]["required"], json!(["transfer"]));
+    assert_eq!(action["oneOf"][1]["properties"]["transfer"]["required"], json!(["receiver_id"]));
+    assert_eq!(action["oneOf"][2]["properties"]["set_owner"], json!({ "type": "string" }));
+}
@@ -0,0 +1,194 @@
+/*!
+{{token_name}} ({{token_symbol}}) non-fungible token, generated from the near-sdk non-fungible
+token template.
+*/
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
+};
+use near_contract_standards::non_fungible_token::NonFungibleToken;
+use near_contract_standards::non_fungible_token::{Token, TokenId};
+{%- if pausable %}
+use near_contract_standards::pause::Pause;
+{%- endif %}
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+{%- if approval %}
+use near_sdk::Promise;
+{%- endif %}
+use near_sdk::{
+    env, near_bindgen, require, AccountId, BorshStorageKey, PanicOnDefault, PromiseOrValue,
+};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    tokens: NonFungibleToken,
+    metadata: LazyOption<NFTContractMetadata>,
+{%- if pausable %}
+    pause: Pause,
+{%- endif %}
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    NonFungibleToken,
+    Metadata,
+    TokenMetadata,
+{%- if enumeration %}
+    Enumeration,
+{%- endif %}
+{%- if approval %}
+    Approval,
+{%- endif %}
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        require!(!env::state_exists(), "Already initialized");
+        let metadata = NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "{{token_name}}".to_string(),
+            symbol: "{{token_symbol}}".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        };
+        metadata.assert_valid();
+        Self {
+            tokens: NonFungibleToken::new(
+                StorageKey::NonFungibleToken,
+                owner_id,
+                Some(StorageKey::TokenMetadata),
+{%- if enumeration %}
+                Some(StorageKey::Enumeration),
+{%- else %}
+                None::<StorageKey>,
+{%- endif %}
+{%- if approval %}
+                Some(StorageKey::Approval),
+{%- else %}
+                None::<StorageKey>,
+{%- endif %}
+            ),
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+{%- if pausable %}
+            pause: Pause::new(),
+{%- endif %}
+        }
+    }
+
+    /// Mint a new token with ID=`token_id` belonging to `token_owner_id`. Can only be called by
+    /// the owner.
+    #[payable]
+    pub fn nft_mint(
+        &mut self,
+        token_id: TokenId,
+        token_owner_id: AccountId,
+        token_metadata: TokenMetadata,
+    ) -> Token {
+        assert_eq!(env::predecessor_account_id(), self.tokens.owner_id, "Unauthorized");
+{%- if pausable %}
+        self.pause.assert_not_paused("nft_mint");
+{%- endif %}
+        self.tokens.internal_mint(token_id, token_owner_id, Some(token_metadata))
+    }
+{%- if pausable %}
+
+    /// Pauses minting. Can only be called by the owner.
+    pub fn pause(&mut self) {
+        require!(env::predecessor_account_id() == self.tokens.owner_id, "Unauthorized");
+        self.pause.pause();
+    }
+
+    /// Unpauses minting. Can only be called by the owner.
+    pub fn unpause(&mut self) {
+        require!(env::predecessor_account_id() == self.tokens.owner_id, "Unauthorized");
+        self.pause.unpause();
+    }
+{%- endif %}
+}
+
+near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
+{%- if approval %}
+near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
+{%- endif %}
+{%- if enumeration %}
+near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
+{%- endif %}
+{%- if pausable %}
+near_contract_standards::impl_pausable!(Contract, pause);
+{%- endif %}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        self.metadata.get().unwrap()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+
+    const MINT_STORAGE_COST: u128 = 5870000000000000000000;
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    fn sample_token_metadata() -> TokenMetadata {
+        TokenMetadata {
+            title: Some("Olympus Mons".into()),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: Some(1u64),
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_mint() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new(accounts(0));
+        assert_eq!(contract.nft_metadata().symbol, "{{token_symbol}}");
+
+        testing_env!(context.attached_deposit(MINT_STORAGE_COST).build());
+        let token_id = "0".to_string();
+        let token = contract.nft_mint(token_id.clone(), accounts(1), sample_token_metadata());
+        assert_eq!(token.token_id, token_id);
+        assert_eq!(token.owner_id, accounts(1));
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(1));
+    }
+{%- if pausable %}
+
+    #[test]
+    #[should_panic(expected = "The contract is paused")]
+    fn test_paused_mint() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new(accounts(0));
+        contract.pause();
+        testing_env!(context.attached_deposit(MINT_STORAGE_COST).build());
+        contract.nft_mint("0".to_string(), accounts(1), sample_token_metadata());
+    }
+{%- endif %}
+}